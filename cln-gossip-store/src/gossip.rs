@@ -0,0 +1,204 @@
+//! The network graph, as described by the gossip store.
+use crate::messages::{ChannelAnnouncement, ChannelUpdate, ChannelUpdateExt, NodeAnnouncement};
+use crate::{Entry, GossipStore, Record};
+use anyhow::Result;
+use cln_rpc::primitives::{Amount, Features, PublicKey, ShortChannelId};
+use cln_rpc::routing::{Edge, Graph};
+use log::debug;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{Read, Seek};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Channel {
+    pub announcement: ChannelAnnouncement,
+    pub private: bool,
+    /// The capacity, if the store told us.
+    pub amount: Option<Amount>,
+    /// The latest update for each direction.
+    pub updates: [Option<ChannelUpdate>; 2],
+    /// The block height at which `gossipd` will forget the channel,
+    /// as its funding output was spent.
+    pub dying: Option<u32>,
+}
+
+impl Channel {
+    pub fn short_channel_id(&self) -> ShortChannelId {
+        self.announcement.short_channel_id
+    }
+
+    /// The node forwarding in `direction`.
+    pub fn source(&self, direction: u32) -> PublicKey {
+        match direction {
+            0 => self.announcement.node_id_1,
+            _ => self.announcement.node_id_2,
+        }
+    }
+
+    fn update(&mut self, u: ChannelUpdate) {
+        let slot = &mut self.updates[u.direction() as usize];
+        if slot.as_ref().is_none_or(|old| old.timestamp <= u.timestamp) {
+            *slot = Some(u);
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    pub node_id: PublicKey,
+    pub announcement: Option<NodeAnnouncement>,
+    pub channels: BTreeSet<ShortChannelId>,
+}
+
+/// Channels and nodes, built by applying the store's entries in
+/// order.
+#[derive(Clone, Debug, Default)]
+pub struct Gossip {
+    pub channels: HashMap<ShortChannelId, Channel>,
+    pub nodes: HashMap<PublicKey, Node>,
+    /// `gossip_store_channel_amount` refers to the channel announced
+    /// just before it.
+    last_channel: Option<ShortChannelId>,
+}
+
+impl Gossip {
+    pub fn new() -> Gossip {
+        Gossip::default()
+    }
+
+    /// Read all entries currently in `store`.
+    pub fn read<R: Read + Seek>(store: &mut GossipStore<R>) -> Result<Gossip> {
+        let mut gossip = Gossip::new();
+        gossip.refresh(store)?;
+        Ok(gossip)
+    }
+
+    /// Apply the entries added to `store` since we last read it.
+    pub fn refresh<R: Read + Seek>(&mut self, store: &mut GossipStore<R>) -> Result<()> {
+        while let Some(entry) = store.next_entry()? {
+            self.apply(&entry);
+        }
+        Ok(())
+    }
+
+    pub fn apply(&mut self, entry: &Entry) {
+        if entry.deleted {
+            return;
+        }
+        match &entry.record {
+            Record::ChannelAnnouncement(a) => self.add_channel(a, None, false),
+            Record::PrivateChannel {
+                amount,
+                announcement,
+            } => self.add_channel(announcement, Some(*amount), true),
+            Record::ChannelAmount(amount) => {
+                if let Some(c) = self
+                    .last_channel
+                    .and_then(|scid| self.channels.get_mut(&scid))
+                {
+                    c.amount = Some(*amount);
+                }
+            }
+            Record::ChannelUpdate(u) | Record::PrivateUpdate(u) => {
+                match self.channels.get_mut(&u.short_channel_id) {
+                    Some(c) => c.update(u.clone()),
+                    None => debug!("Update for unknown channel {}", u.short_channel_id),
+                }
+            }
+            Record::NodeAnnouncement(n) => {
+                self.node(n.node_id).announcement = Some(n.clone());
+            }
+            Record::DeleteChannel(scid) => self.remove_channel(scid),
+            Record::ChannelDying {
+                short_channel_id,
+                blockheight,
+            } => {
+                if let Some(c) = self.channels.get_mut(short_channel_id) {
+                    c.dying = Some(*blockheight);
+                }
+            }
+            Record::Ended { .. } | Record::Unknown { .. } => {}
+        }
+    }
+
+    fn node(&mut self, node_id: PublicKey) -> &mut Node {
+        self.nodes.entry(node_id).or_insert_with(|| Node {
+            node_id,
+            announcement: None,
+            channels: BTreeSet::new(),
+        })
+    }
+
+    fn add_channel(&mut self, a: &ChannelAnnouncement, amount: Option<Amount>, private: bool) {
+        let scid = a.short_channel_id;
+        self.node(a.node_id_1).channels.insert(scid);
+        self.node(a.node_id_2).channels.insert(scid);
+        // A private channel being announced keeps its updates.
+        let updates = self
+            .channels
+            .remove(&scid)
+            .map(|c| c.updates)
+            .unwrap_or_default();
+        self.channels.insert(
+            scid,
+            Channel {
+                announcement: a.clone(),
+                private,
+                amount,
+                updates,
+                dying: None,
+            },
+        );
+        self.last_channel = Some(scid);
+    }
+
+    /// Forget a channel, and nodes left without channels.
+    fn remove_channel(&mut self, scid: &ShortChannelId) {
+        let c = match self.channels.remove(scid) {
+            Some(c) => c,
+            None => return,
+        };
+        for id in [c.announcement.node_id_1, c.announcement.node_id_2] {
+            if let Some(n) = self.nodes.get_mut(&id) {
+                n.channels.remove(scid);
+                if n.channels.is_empty() {
+                    self.nodes.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// A routing graph with an edge for each channel direction that
+    /// has an update. Channels without a known capacity are assumed
+    /// to be able to carry their `htlc_maximum_msat`.
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        for c in self.channels.values() {
+            for (dir, u) in c.updates.iter().enumerate() {
+                let u = match u {
+                    Some(u) => u,
+                    None => continue,
+                };
+                let dir = dir as u32;
+                graph.add_edge(Edge {
+                    source: c.source(dir),
+                    destination: c.source(dir ^ 1),
+                    short_channel_id: c.short_channel_id(),
+                    direction: dir,
+                    capacity: c.amount.unwrap_or(u.htlc_maximum_msat),
+                    base_fee_msat: u.fee_base_msat,
+                    fee_per_millionth: u.fee_proportional_millionths,
+                    cltv_delta: u.cltv_expiry_delta as u32,
+                    htlc_minimum_msat: u.htlc_minimum_msat,
+                    htlc_maximum_msat: Some(u.htlc_maximum_msat),
+                    active: !u.is_disabled(),
+                });
+            }
+        }
+        for n in self.nodes.values() {
+            if let Some(a) = &n.announcement {
+                graph.set_node_features(n.node_id, Features::from_bytes(&a.features));
+            }
+        }
+        graph
+    }
+}
@@ -0,0 +1,753 @@
+//! Read `lightningd`'s `gossip_store` file.
+//!
+//! The file is a version byte followed by records, each a 12 byte
+//! header (flags and length, CRC32C of timestamp and message,
+//! timestamp) and a message. Messages are either BOLT7 gossip, or one
+//! of the `gossip_store_*` messages `gossipd` defines in
+//! `gossipd/gossip_store_wire.csv`. See `doc/GOSSIP_STORE.md`.
+//!
+//! [`GossipStore`] iterates over the [`Entry`]s in a store, and can
+//! keep following the file while `gossipd` appends to it.
+//! [`gossip::Gossip`] applies them to build the network graph.
+use crate::messages::{
+    ChannelAnnouncement, ChannelUpdate, NodeAnnouncement, CHANNEL_ANNOUNCEMENT, CHANNEL_UPDATE,
+    NODE_ANNOUNCEMENT,
+};
+use anyhow::{anyhow, Context, Result};
+use cln_rpc::primitives::{Amount, ShortChannelId};
+use cln_rpc::wire::{len, write_sat, Reader, WireMessage, WireType};
+use log::debug;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub mod gossip;
+pub mod messages;
+
+/// The top 3 bits of the version byte are the major version, which
+/// changes on incompatible changes.
+pub const MAJOR_VERSION_MASK: u8 = 0xE0;
+/// The version `gossipd` currently writes.
+pub const VERSION: u8 = 11;
+
+/// Flags in the record header.
+pub const LEN_DELETED_BIT: u32 = 0x8000_0000;
+pub const LEN_PUSH_BIT: u32 = 0x4000_0000;
+pub const LEN_RATELIMIT_BIT: u32 = 0x2000_0000;
+pub const FLAGS_MASK: u32 = 0xFFFF_0000;
+pub const LEN_MASK: u32 = !FLAGS_MASK;
+
+/// `gossip_store_*` message types.
+pub const CHANNEL_AMOUNT: u16 = 4101;
+pub const PRIVATE_UPDATE: u16 = 4102;
+pub const DELETE_CHAN: u16 = 4103;
+pub const PRIVATE_CHANNEL: u16 = 4104;
+pub const ENDED: u16 = 4105;
+pub const CHAN_DYING: u16 = 4106;
+
+const HEADER_LEN: usize = 12;
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg());
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+/// A `u16` length followed by that many bytes, how the store wraps
+/// messages in its own.
+fn read_var<'a>(r: &mut Reader<'a>) -> Result<&'a [u8]> {
+    let len: u16 = r.read()?;
+    r.take(len as usize)
+}
+
+fn write_var(buf: &mut Vec<u8>, b: &[u8]) -> Result<()> {
+    len::<u16>(b.len())?.write(buf)?;
+    buf.extend_from_slice(b);
+    Ok(())
+}
+
+/// CRC32C as `ccan/crc32c` computes it, continuing from `crc`. The
+/// store seeds it with the record's timestamp.
+pub fn crc32c(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for b in data {
+        crc = CRC32C_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// A message in the store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Record {
+    ChannelAnnouncement(ChannelAnnouncement),
+    /// The capacity of the channel announced just before.
+    ChannelAmount(Amount),
+    /// A channel only we know about, the announcement has zero
+    /// signatures.
+    PrivateChannel {
+        amount: Amount,
+        announcement: ChannelAnnouncement,
+    },
+    ChannelUpdate(ChannelUpdate),
+    /// An update for a private channel.
+    PrivateUpdate(ChannelUpdate),
+    NodeAnnouncement(NodeAnnouncement),
+    DeleteChannel(ShortChannelId),
+    /// The funding output was spent, `gossipd` will delete the
+    /// channel at `blockheight`.
+    ChannelDying {
+        short_channel_id: ShortChannelId,
+        blockheight: u32,
+    },
+    /// The file was replaced: reopen it, and continue at
+    /// `equivalent_offset` if still following it.
+    Ended {
+        equivalent_offset: u64,
+    },
+    Unknown {
+        typ: u16,
+        msg: Vec<u8>,
+    },
+}
+
+impl Record {
+    pub fn from_wire(msg: &[u8]) -> Result<Record> {
+        let mut r = Reader::new(msg);
+        let typ: u16 = r.read()?;
+        Ok(match typ {
+            CHANNEL_ANNOUNCEMENT => Record::ChannelAnnouncement(r.read()?),
+            CHANNEL_UPDATE => Record::ChannelUpdate(r.read()?),
+            NODE_ANNOUNCEMENT => Record::NodeAnnouncement(r.read()?),
+            CHANNEL_AMOUNT => Record::ChannelAmount(r.sat()?),
+            PRIVATE_CHANNEL => Record::PrivateChannel {
+                amount: r.sat()?,
+                announcement: ChannelAnnouncement::from_wire(read_var(&mut r)?)?,
+            },
+            PRIVATE_UPDATE => Record::PrivateUpdate(ChannelUpdate::from_wire(read_var(&mut r)?)?),
+            DELETE_CHAN => Record::DeleteChannel(r.read()?),
+            ENDED => Record::Ended {
+                equivalent_offset: r.read()?,
+            },
+            CHAN_DYING => Record::ChannelDying {
+                short_channel_id: r.read()?,
+                blockheight: r.read()?,
+            },
+            typ => Record::Unknown {
+                typ,
+                msg: r.rest().to_vec(),
+            },
+        })
+    }
+
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        match self {
+            Record::ChannelAnnouncement(a) => return a.to_wire(),
+            Record::ChannelUpdate(u) => return u.to_wire(),
+            Record::NodeAnnouncement(n) => return n.to_wire(),
+            Record::ChannelAmount(a) => {
+                CHANNEL_AMOUNT.write(&mut buf)?;
+                write_sat(&mut buf, a)?;
+            }
+            Record::PrivateChannel {
+                amount,
+                announcement,
+            } => {
+                PRIVATE_CHANNEL.write(&mut buf)?;
+                write_sat(&mut buf, amount)?;
+                write_var(&mut buf, &announcement.to_wire()?)?;
+            }
+            Record::PrivateUpdate(u) => {
+                PRIVATE_UPDATE.write(&mut buf)?;
+                write_var(&mut buf, &u.to_wire()?)?;
+            }
+            Record::DeleteChannel(scid) => {
+                DELETE_CHAN.write(&mut buf)?;
+                scid.write(&mut buf)?;
+            }
+            Record::Ended { equivalent_offset } => {
+                ENDED.write(&mut buf)?;
+                equivalent_offset.write(&mut buf)?;
+            }
+            Record::ChannelDying {
+                short_channel_id,
+                blockheight,
+            } => {
+                CHAN_DYING.write(&mut buf)?;
+                short_channel_id.write(&mut buf)?;
+                blockheight.write(&mut buf)?;
+            }
+            Record::Unknown { typ, msg } => {
+                typ.write(&mut buf)?;
+                buf.extend_from_slice(msg);
+            }
+        }
+        Ok(buf)
+    }
+}
+
+/// A record and its header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// Offset of the record header in the file.
+    pub offset: u64,
+    pub timestamp: u32,
+    /// The record was superseded, and should be ignored.
+    pub deleted: bool,
+    /// Locally generated gossip.
+    pub push: bool,
+    /// Gossip that came too fast, which we don't relay.
+    pub ratelimited: bool,
+    pub record: Record,
+}
+
+/// Reads entries from a gossip store.
+pub struct GossipStore<R> {
+    reader: R,
+    version: u8,
+    /// Offset of the next record.
+    offset: u64,
+    /// Where to reopen the store, if it's a file.
+    path: Option<PathBuf>,
+}
+
+impl GossipStore<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("opening {}", path.as_ref().display()))?;
+        let mut store = GossipStore::new(BufReader::new(file))?;
+        store.path = Some(path.as_ref().to_path_buf());
+        Ok(store)
+    }
+
+    /// Reopen the file after it was replaced, continuing at `offset`.
+    pub fn reopen(&mut self, offset: u64) -> Result<()> {
+        let path = self.path.clone().unwrap();
+        debug!("Reopening {} at offset {}", path.display(), offset);
+        let mut store = GossipStore::open(&path)?;
+        store.seek(offset)?;
+        *self = store;
+        Ok(())
+    }
+
+    /// Wait for the next entry, checking for new records every
+    /// `interval`. Reopens the file when it was replaced, so this
+    /// never returns [`Record::Ended`].
+    pub fn wait_entry(&mut self, interval: Duration) -> Result<Entry> {
+        loop {
+            match self.next_entry()? {
+                Some(Entry {
+                    record: Record::Ended { equivalent_offset },
+                    deleted: false,
+                    ..
+                }) => self.reopen(equivalent_offset)?,
+                Some(e) => return Ok(e),
+                None => std::thread::sleep(interval),
+            }
+        }
+    }
+
+    /// Follow the file, see [`GossipStore::wait_entry`]. The iterator
+    /// only ends after an error.
+    pub fn tail(&mut self, interval: Duration) -> impl Iterator<Item = Result<Entry>> + '_ {
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            let res = self.wait_entry(interval);
+            failed = res.is_err();
+            Some(res)
+        })
+    }
+}
+
+impl<R: Read + Seek> GossipStore<R> {
+    /// Read the version from `reader` and check we understand it.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut version = [0u8; 1];
+        reader
+            .read_exact(&mut version)
+            .context("reading gossip_store version")?;
+        let version = version[0];
+        if version & MAJOR_VERSION_MASK != 0 {
+            return Err(anyhow!("Unsupported gossip_store version {}", version));
+        }
+        Ok(GossipStore {
+            reader,
+            version,
+            offset: 1,
+            path: None,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The offset of the next record we'll read.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Continue reading at `offset`, which must be the start of a
+    /// record.
+    pub fn seek(&mut self, offset: u64) -> Result<()> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.offset = offset;
+        Ok(())
+    }
+
+    /// Fill `buf`, returning `false` if we hit the end of the file.
+    fn read_full(&mut self, buf: &mut [u8]) -> Result<bool> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.reader.read(&mut buf[read..]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(true)
+    }
+
+    /// The next entry, or `None` at the end of the file. A partially
+    /// written record at the end is left alone, to be read once it is
+    /// complete.
+    pub fn next_entry(&mut self) -> Result<Option<Entry>> {
+        let offset = self.offset;
+        let mut hdr = [0u8; HEADER_LEN];
+        if !self.read_full(&mut hdr)? {
+            self.seek(offset)?;
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes(hdr[0..4].try_into().unwrap());
+        let crc = u32::from_be_bytes(hdr[4..8].try_into().unwrap());
+        let timestamp = u32::from_be_bytes(hdr[8..12].try_into().unwrap());
+        let mut msg = vec![0u8; (len & LEN_MASK) as usize];
+        if !self.read_full(&mut msg)? {
+            self.seek(offset)?;
+            return Ok(None);
+        }
+        self.offset += (HEADER_LEN + msg.len()) as u64;
+
+        let deleted = len & LEN_DELETED_BIT != 0;
+        // Like `gossipd`, don't check deleted records.
+        let record = if deleted {
+            Record::from_wire(&msg).unwrap_or_else(|_| Record::Unknown {
+                typ: msg.get(..2).map_or(0, |t| u16::from_be_bytes([t[0], t[1]])),
+                msg: msg.get(2..).unwrap_or_default().to_vec(),
+            })
+        } else {
+            if crc32c(timestamp, &msg) != crc {
+                return Err(anyhow!("Bad checksum for record at offset {}", offset));
+            }
+            Record::from_wire(&msg)
+                .with_context(|| format!("parsing record at offset {}", offset))?
+        };
+        Ok(Some(Entry {
+            offset,
+            timestamp,
+            deleted,
+            push: len & LEN_PUSH_BIT != 0,
+            ratelimited: len & LEN_RATELIMIT_BIT != 0,
+            record,
+        }))
+    }
+}
+
+impl<R: Read + Seek> Iterator for GossipStore<R> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+/// Writes a gossip store, e.g. to generate test data.
+pub struct GossipStoreWriter<W> {
+    writer: W,
+    offset: u64,
+}
+
+impl<W: Write> GossipStoreWriter<W> {
+    pub fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(&[VERSION])?;
+        Ok(GossipStoreWriter { writer, offset: 1 })
+    }
+
+    /// Append `record`, with `flags` being any of the `LEN_*_BIT`s.
+    /// Returns the offset of the record.
+    pub fn append(&mut self, record: &Record, timestamp: u32, flags: u32) -> Result<u64> {
+        let msg = record.to_wire()?;
+        let offset = self.offset;
+        let len = msg.len() as u32;
+        if len & FLAGS_MASK != 0 {
+            return Err(anyhow!("record too long: {} bytes", len));
+        }
+        self.writer
+            .write_all(&(len | (flags & FLAGS_MASK)).to_be_bytes())?;
+        self.writer
+            .write_all(&crc32c(timestamp, &msg).to_be_bytes())?;
+        self.writer.write_all(&timestamp.to_be_bytes())?;
+        self.writer.write_all(&msg)?;
+        self.offset += (HEADER_LEN + msg.len()) as u64;
+        Ok(offset)
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gossip::Gossip;
+    use crate::messages::{Address, NodeAnnouncementExt};
+    use bitcoin::hashes::Hash;
+    use bitcoin::BlockHash;
+    use cln_rpc::primitives::PublicKey;
+    use cln_rpc::routing::RouteParams;
+    use cln_rpc::wire::peer::NodeAnnTlvs;
+    use std::io::Cursor;
+    use std::str::FromStr;
+
+    /// G, 2G and 3G, in ascending order.
+    fn node(i: usize) -> PublicKey {
+        let keys = [
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        ];
+        PublicKey::from_str(keys[i]).unwrap()
+    }
+
+    fn scid(block: u64) -> ShortChannelId {
+        ShortChannelId::new(block, 1, 0).unwrap()
+    }
+
+    fn announcement(block: u64, a: usize, b: usize) -> ChannelAnnouncement {
+        ChannelAnnouncement {
+            node_signature_1: [1; 64],
+            node_signature_2: [2; 64],
+            bitcoin_signature_1: [3; 64],
+            bitcoin_signature_2: [4; 64],
+            features: vec![],
+            chain_hash: BlockHash::from_inner([6; 32]),
+            short_channel_id: scid(block),
+            node_id_1: node(a),
+            node_id_2: node(b),
+            bitcoin_key_1: node(a),
+            bitcoin_key_2: node(b),
+        }
+    }
+
+    fn update(block: u64, dir: u8, timestamp: u32, base: u32) -> ChannelUpdate {
+        ChannelUpdate {
+            signature: [7; 64],
+            chain_hash: BlockHash::from_inner([6; 32]),
+            short_channel_id: scid(block),
+            timestamp,
+            message_flags: 1,
+            channel_flags: dir,
+            cltv_expiry_delta: 6,
+            htlc_minimum_msat: Amount::from_msat(1),
+            fee_base_msat: base,
+            fee_proportional_millionths: 10,
+            htlc_maximum_msat: Amount::from_sat(990_000),
+        }
+    }
+
+    fn node_announcement(i: usize, timestamp: u32) -> NodeAnnouncement {
+        let mut alias = [0; 32];
+        alias[..5].copy_from_slice(b"node1");
+        NodeAnnouncement {
+            signature: [8; 64],
+            features: vec![0x02, 0x00],
+            timestamp,
+            node_id: node(i),
+            rgb_color: [1, 2, 3],
+            alias,
+            addresses: Address::write_all(&[
+                Address::Ipv4 {
+                    addr: "127.0.0.1".parse().unwrap(),
+                    port: 9735,
+                },
+                Address::Ipv6 {
+                    addr: "::1".parse().unwrap(),
+                    port: 9736,
+                },
+                Address::TorV3 {
+                    addr: [5; 35],
+                    port: 9737,
+                },
+                Address::Dns {
+                    hostname: "example.com".to_string(),
+                    port: 9738,
+                },
+            ])
+            .unwrap(),
+            tlvs: NodeAnnTlvs::default(),
+        }
+    }
+
+    /// A store with channel 1 - 2 (101) public, 2 - 3 (102) private.
+    fn records() -> Vec<(Record, u32, u32)> {
+        vec![
+            (Record::ChannelAnnouncement(announcement(101, 0, 1)), 0, 0),
+            (Record::ChannelAmount(Amount::from_sat(1_000_000)), 0, 0),
+            (Record::ChannelUpdate(update(101, 0, 100, 1)), 100, 0),
+            (
+                Record::ChannelUpdate(update(101, 0, 90, 5)),
+                90,
+                LEN_DELETED_BIT,
+            ),
+            (
+                Record::ChannelUpdate(update(101, 1, 100, 2)),
+                100,
+                LEN_RATELIMIT_BIT,
+            ),
+            (
+                Record::NodeAnnouncement(node_announcement(0, 100)),
+                100,
+                LEN_PUSH_BIT,
+            ),
+            (
+                Record::PrivateChannel {
+                    amount: Amount::from_sat(500_000),
+                    announcement: announcement(102, 1, 2),
+                },
+                0,
+                0,
+            ),
+            (Record::PrivateUpdate(update(102, 0, 100, 3)), 100, 0),
+            (Record::PrivateUpdate(update(102, 1, 100, 4)), 100, 0),
+            (
+                Record::Unknown {
+                    typ: 4200,
+                    msg: vec![1, 2, 3],
+                },
+                0,
+                0,
+            ),
+        ]
+    }
+
+    fn write(records: &[(Record, u32, u32)]) -> Vec<u8> {
+        let mut w = GossipStoreWriter::new(vec![]).unwrap();
+        for (r, timestamp, flags) in records {
+            w.append(r, *timestamp, *flags).unwrap();
+        }
+        w.into_inner()
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gossip_store-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(0, b"123456789"), 0xE3069283);
+        assert_eq!(crc32c(0, b""), 0);
+        // Continuing a CRC is the same as computing it at once.
+        assert_eq!(crc32c(crc32c(0, b"1234"), b"56789"), 0xE3069283);
+    }
+
+    #[test]
+    fn test_read() {
+        let records = records();
+        let buf = write(&records);
+        assert_eq!(buf[0], VERSION);
+
+        let store = GossipStore::new(Cursor::new(buf.clone())).unwrap();
+        let entries: Vec<Entry> = store.collect::<Result<_>>().unwrap();
+        assert_eq!(entries.len(), records.len());
+        assert_eq!(entries[0].offset, 1);
+        for (e, (r, timestamp, flags)) in entries.iter().zip(&records) {
+            assert_eq!(&e.record, r);
+            assert_eq!(e.timestamp, *timestamp);
+            assert_eq!(e.deleted, flags & LEN_DELETED_BIT != 0);
+            assert_eq!(e.push, flags & LEN_PUSH_BIT != 0);
+            assert_eq!(e.ratelimited, flags & LEN_RATELIMIT_BIT != 0);
+        }
+        match &entries[5].record {
+            Record::NodeAnnouncement(n) => {
+                assert_eq!(n.alias(), "node1");
+                assert_eq!(n.addresses().unwrap().len(), 4);
+            }
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // A partially written record is left for later.
+        let mut store = GossipStore::new(Cursor::new(buf[..buf.len() - 1].to_vec())).unwrap();
+        assert_eq!(store.by_ref().count(), records.len() - 1);
+        let end = store.offset();
+        assert_eq!(end, entries.last().unwrap().offset);
+        assert!(store.next_entry().unwrap().is_none());
+        assert_eq!(store.offset(), end);
+
+        // Unknown major version.
+        let mut bad = buf.clone();
+        bad[0] = 0x20 | VERSION;
+        assert!(GossipStore::new(Cursor::new(bad)).is_err());
+
+        // Corrupted message, but deleted ones aren't checked.
+        let mut bad = buf.clone();
+        let off = entries[3].offset as usize;
+        bad[off + HEADER_LEN + 10] ^= 1;
+        assert!(GossipStore::new(Cursor::new(bad.clone()))
+            .unwrap()
+            .all(|e| e.is_ok()));
+        let off = entries[2].offset as usize;
+        bad[off + HEADER_LEN + 10] ^= 1;
+        let err = GossipStore::new(Cursor::new(bad))
+            .unwrap()
+            .find_map(|e| e.err())
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!("Bad checksum for record at offset {}", off)
+        );
+    }
+
+    #[test]
+    fn test_gossip() {
+        let mut records = records();
+        let mut store = GossipStore::new(Cursor::new(write(&records))).unwrap();
+        let gossip = Gossip::read(&mut store).unwrap();
+        assert_eq!(gossip.channels.len(), 2);
+        assert_eq!(gossip.nodes.len(), 3);
+
+        let c = &gossip.channels[&scid(101)];
+        assert!(!c.private);
+        assert_eq!(c.amount, Some(Amount::from_sat(1_000_000)));
+        assert_eq!(c.updates[0].as_ref().unwrap().fee_base_msat, 1);
+        assert_eq!(c.updates[1].as_ref().unwrap().fee_base_msat, 2);
+        let c = &gossip.channels[&scid(102)];
+        assert!(c.private);
+        assert_eq!(c.amount, Some(Amount::from_sat(500_000)));
+        assert_eq!(c.source(1), node(2));
+
+        let n = &gossip.nodes[&node(1)];
+        assert!(n.announcement.is_none());
+        assert_eq!(
+            n.channels.iter().cloned().collect::<Vec<_>>(),
+            vec![scid(101), scid(102)]
+        );
+        assert!(gossip.nodes[&node(0)].announcement.is_some());
+
+        // Route over both channels, paying node 1's fee on 102.
+        let graph = gossip.to_graph();
+        let route = graph
+            .route(
+                &node(0),
+                &node(2),
+                Amount::from_sat(1000),
+                &RouteParams::default(),
+            )
+            .unwrap();
+        assert_eq!(route.len(), 2);
+        assert_eq!(route[0].channel, scid(101));
+        assert_eq!(route[0].amount_msat, Amount::from_msat(1_000_013));
+
+        // Newer updates win, channels go away, then nodes.
+        records.push((Record::ChannelUpdate(update(101, 0, 110, 11)), 110, 0));
+        records.push((
+            Record::ChannelDying {
+                short_channel_id: scid(101),
+                blockheight: 1000,
+            },
+            0,
+            0,
+        ));
+        let mut store = GossipStore::new(Cursor::new(write(&records))).unwrap();
+        let mut gossip = Gossip::read(&mut store).unwrap();
+        let c = &gossip.channels[&scid(101)];
+        assert_eq!(c.updates[0].as_ref().unwrap().fee_base_msat, 11);
+        assert_eq!(c.dying, Some(1000));
+
+        gossip.apply(&Entry {
+            offset: 0,
+            timestamp: 0,
+            deleted: false,
+            push: false,
+            ratelimited: false,
+            record: Record::DeleteChannel(scid(101)),
+        });
+        assert_eq!(gossip.channels.len(), 1);
+        assert!(!gossip.nodes.contains_key(&node(0)));
+        assert_eq!(gossip.nodes[&node(1)].channels.len(), 1);
+    }
+
+    #[test]
+    fn test_tail() {
+        let path = temp_path("tail");
+        let records = records();
+        std::fs::write(&path, write(&records[..2])).unwrap();
+        let mut store = GossipStore::open(&path).unwrap();
+        let mut gossip = Gossip::new();
+        gossip.refresh(&mut store).unwrap();
+        assert_eq!(gossip.channels.len(), 1);
+        assert!(gossip.channels[&scid(101)].updates[0].is_none());
+
+        // gossipd appends, one byte at a time.
+        let mut old = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        let mut w = GossipStoreWriter::new(vec![]).unwrap();
+        w.append(&records[2].0, 100, 0).unwrap();
+        let update = w.into_inner()[1..].to_vec();
+        for b in &update[..update.len() - 1] {
+            old.write_all(&[*b]).unwrap();
+            assert!(store.next_entry().unwrap().is_none());
+        }
+        old.write_all(&update[update.len() - 1..]).unwrap();
+        gossip.refresh(&mut store).unwrap();
+        assert!(gossip.channels[&scid(101)].updates[0].is_some());
+
+        // Then it replaces the file and marks the old one as ended,
+        // with the new one having an extra record.
+        let new = temp_path("tail.new");
+        let mut w = GossipStoreWriter::new(vec![]).unwrap();
+        for (r, timestamp, flags) in &records[..3] {
+            w.append(r, *timestamp, *flags).unwrap();
+        }
+        let equivalent_offset = w.offset();
+        w.append(&records[4].0, 100, 0).unwrap();
+        std::fs::write(&new, w.into_inner()).unwrap();
+        std::fs::rename(&new, &path).unwrap();
+        let mut w = GossipStoreWriter::new(vec![]).unwrap();
+        w.append(&Record::Ended { equivalent_offset }, 0, 0)
+            .unwrap();
+        old.write_all(&w.into_inner()[1..]).unwrap();
+
+        let entry = store
+            .tail(Duration::from_millis(1))
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.record, records[4].0);
+        assert_eq!(entry.offset, equivalent_offset);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
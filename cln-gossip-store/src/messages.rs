@@ -0,0 +1,159 @@
+//! The BOLT7 gossip messages found in the store.
+//!
+//! These are the [`cln_rpc::wire::peer`] types, with helpers for the
+//! fields the store and the graph need. The store only holds messages
+//! `gossipd` validated, so signatures are kept as they are and not
+//! checked.
+use anyhow::Result;
+use cln_rpc::wire::{len, Reader, WireMessage, WireType};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+pub use cln_rpc::wire::peer::{ChannelAnnouncement, ChannelUpdate, NodeAnnouncement};
+
+pub const CHANNEL_ANNOUNCEMENT: u16 = ChannelAnnouncement::TYPE;
+pub const NODE_ANNOUNCEMENT: u16 = NodeAnnouncement::TYPE;
+pub const CHANNEL_UPDATE: u16 = ChannelUpdate::TYPE;
+
+/// `channel_update` `channel_flags` bit for a disabled channel.
+const CHANNEL_FLAG_DISABLED: u8 = 2;
+
+/// What `channel_update`'s `channel_flags` say.
+pub trait ChannelUpdateExt {
+    /// 0 if this updates the direction from `node_id_1`, 1 otherwise.
+    fn direction(&self) -> u32;
+    fn is_disabled(&self) -> bool;
+}
+
+impl ChannelUpdateExt for ChannelUpdate {
+    fn direction(&self) -> u32 {
+        (self.channel_flags & 1) as u32
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.channel_flags & CHANNEL_FLAG_DISABLED != 0
+    }
+}
+
+/// The `node_announcement` fields that are left encoded.
+pub trait NodeAnnouncementExt {
+    /// The alias, without the trailing zero padding.
+    fn alias(&self) -> String;
+    fn addresses(&self) -> Result<Vec<Address>>;
+}
+
+impl NodeAnnouncementExt for NodeAnnouncement {
+    fn alias(&self) -> String {
+        let end = self.alias.iter().position(|b| *b == 0).unwrap_or(32);
+        String::from_utf8_lossy(&self.alias[..end]).into_owned()
+    }
+
+    fn addresses(&self) -> Result<Vec<Address>> {
+        Address::parse_all(&self.addresses)
+    }
+}
+
+/// An address from a `node_announcement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Address {
+    Ipv4 {
+        addr: Ipv4Addr,
+        port: u16,
+    },
+    Ipv6 {
+        addr: Ipv6Addr,
+        port: u16,
+    },
+    /// Deprecated, but still found in old announcements.
+    TorV2 {
+        addr: [u8; 10],
+        port: u16,
+    },
+    TorV3 {
+        addr: [u8; 35],
+        port: u16,
+    },
+    Dns {
+        hostname: String,
+        port: u16,
+    },
+}
+
+impl Address {
+    /// Parse addresses until the first one of an unknown type, which
+    /// BOLT7 says to ignore along with the rest.
+    pub fn parse_all(buf: &[u8]) -> Result<Vec<Address>> {
+        let mut r = Reader::new(buf);
+        let mut addrs = vec![];
+        while !r.is_empty() {
+            let addr = match r.read::<u8>()? {
+                1 => Address::Ipv4 {
+                    addr: Ipv4Addr::from(r.read::<[u8; 4]>()?),
+                    port: r.read()?,
+                },
+                2 => Address::Ipv6 {
+                    addr: Ipv6Addr::from(r.read::<[u8; 16]>()?),
+                    port: r.read()?,
+                },
+                3 => Address::TorV2 {
+                    addr: r.read()?,
+                    port: r.read()?,
+                },
+                4 => Address::TorV3 {
+                    addr: r.read()?,
+                    port: r.read()?,
+                },
+                5 => {
+                    let len: u8 = r.read()?;
+                    Address::Dns {
+                        hostname: String::from_utf8_lossy(r.take(len as usize)?).into_owned(),
+                        port: r.read()?,
+                    }
+                }
+                _ => break,
+            };
+            addrs.push(addr);
+        }
+        Ok(addrs)
+    }
+
+    /// Encode `addrs` for `node_announcement`'s `addresses`.
+    pub fn write_all(addrs: &[Address]) -> Result<Vec<u8>> {
+        let mut buf = vec![];
+        for a in addrs {
+            a.write(&mut buf)?;
+        }
+        Ok(buf)
+    }
+
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let port = match self {
+            Address::Ipv4 { addr, port } => {
+                1u8.write(buf)?;
+                addr.octets().write(buf)?;
+                port
+            }
+            Address::Ipv6 { addr, port } => {
+                2u8.write(buf)?;
+                addr.octets().write(buf)?;
+                port
+            }
+            Address::TorV2 { addr, port } => {
+                3u8.write(buf)?;
+                addr.write(buf)?;
+                port
+            }
+            Address::TorV3 { addr, port } => {
+                4u8.write(buf)?;
+                addr.write(buf)?;
+                port
+            }
+            Address::Dns { hostname, port } => {
+                5u8.write(buf)?;
+                len::<u8>(hostname.len())?.write(buf)?;
+                buf.extend_from_slice(hostname.as_bytes());
+                port
+            }
+        };
+        port.write(buf)
+    }
+}
@@ -0,0 +1,522 @@
+// This file was automatically derived from the JSON-RPC schemas in
+// `doc/schemas`. Do not edit this file manually as it would get
+// overwritten.
+
+use crate::pb;
+use crate::pb::node_client::NodeClient;
+use crate::status::status_error;
+use cln_rpc::rpc::{self, Value};
+use cln_rpc::{Request, Response, Rpc, RpcError};
+use tonic::transport::Channel;
+
+/// An [`Rpc`] forwarding the calls to a remote node's
+/// [`Node`](crate::pb::node_server::Node) service.
+#[derive(Clone, Debug)]
+pub struct NodeRpc {
+    client: NodeClient<Channel>,
+}
+
+impl NodeRpc {
+    pub fn new(client: NodeClient<Channel>) -> Self {
+        Self { client }
+    }
+
+    /// Connect to the `Node` service at `uri`. Use
+    /// [`NodeRpc::new`] to configure the channel, e.g., with TLS.
+    pub async fn connect(uri: String) -> anyhow::Result<Self> {
+        Ok(Self::new(NodeClient::connect(uri).await?))
+    }
+}
+
+#[tonic::async_trait]
+impl Rpc for NodeRpc {
+    async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let req = rpc::request(method, params)?;
+        rpc::result(self.call(req).await?)
+    }
+
+    async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        match req {
+            Request::Getinfo(req) => {
+                let res = self
+                    .client
+                    .getinfo(pb::GetinfoRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Getinfo", s))?;
+                Ok(Response::Getinfo(res.into_inner().try_into()?))
+            }
+            Request::ListPeers(req) => {
+                let res = self
+                    .client
+                    .list_peers(pb::ListpeersRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListPeers", s))?;
+                Ok(Response::ListPeers(res.into_inner().try_into()?))
+            }
+            Request::ListFunds(req) => {
+                let res = self
+                    .client
+                    .list_funds(pb::ListfundsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListFunds", s))?;
+                Ok(Response::ListFunds(res.into_inner().try_into()?))
+            }
+            Request::SendPay(req) => {
+                let res = self
+                    .client
+                    .send_pay(pb::SendpayRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SendPay", s))?;
+                Ok(Response::SendPay(res.into_inner().try_into()?))
+            }
+            Request::ListChannels(req) => {
+                let res = self
+                    .client
+                    .list_channels(pb::ListchannelsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListChannels", s))?;
+                Ok(Response::ListChannels(res.into_inner().try_into()?))
+            }
+            Request::ListConfigs(req) => {
+                let res = self
+                    .client
+                    .list_configs(pb::ListconfigsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListConfigs", s))?;
+                Ok(Response::ListConfigs(res.into_inner().try_into()?))
+            }
+            Request::AddGossip(req) => {
+                let res = self
+                    .client
+                    .add_gossip(pb::AddgossipRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("AddGossip", s))?;
+                Ok(Response::AddGossip(res.into_inner().try_into()?))
+            }
+            Request::AutoCleanInvoice(req) => {
+                let res = self
+                    .client
+                    .auto_clean_invoice(pb::AutocleaninvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("AutoCleanInvoice", s))?;
+                Ok(Response::AutoCleanInvoice(res.into_inner().try_into()?))
+            }
+            Request::CheckMessage(req) => {
+                let res = self
+                    .client
+                    .check_message(pb::CheckmessageRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("CheckMessage", s))?;
+                Ok(Response::CheckMessage(res.into_inner().try_into()?))
+            }
+            Request::Close(req) => {
+                let res = self
+                    .client
+                    .close(pb::CloseRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Close", s))?;
+                Ok(Response::Close(res.into_inner().try_into()?))
+            }
+            Request::Connect(req) => {
+                let res = self
+                    .client
+                    .connect_peer(pb::ConnectRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Connect", s))?;
+                Ok(Response::Connect(res.into_inner().try_into()?))
+            }
+            Request::CreateInvoice(req) => {
+                let res = self
+                    .client
+                    .create_invoice(pb::CreateinvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("CreateInvoice", s))?;
+                Ok(Response::CreateInvoice(res.into_inner().try_into()?))
+            }
+            Request::Datastore(req) => {
+                let res = self
+                    .client
+                    .datastore(pb::DatastoreRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Datastore", s))?;
+                Ok(Response::Datastore(res.into_inner().try_into()?))
+            }
+            Request::CreateOnion(req) => {
+                let res = self
+                    .client
+                    .create_onion(pb::CreateonionRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("CreateOnion", s))?;
+                Ok(Response::CreateOnion(res.into_inner().try_into()?))
+            }
+            Request::DelDatastore(req) => {
+                let res = self
+                    .client
+                    .del_datastore(pb::DeldatastoreRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("DelDatastore", s))?;
+                Ok(Response::DelDatastore(res.into_inner().try_into()?))
+            }
+            Request::DelExpiredInvoice(req) => {
+                let res = self
+                    .client
+                    .del_expired_invoice(pb::DelexpiredinvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("DelExpiredInvoice", s))?;
+                Ok(Response::DelExpiredInvoice(res.into_inner().try_into()?))
+            }
+            Request::DelInvoice(req) => {
+                let res = self
+                    .client
+                    .del_invoice(pb::DelinvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("DelInvoice", s))?;
+                Ok(Response::DelInvoice(res.into_inner().try_into()?))
+            }
+            Request::Invoice(req) => {
+                let res = self
+                    .client
+                    .invoice(pb::InvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Invoice", s))?;
+                Ok(Response::Invoice(res.into_inner().try_into()?))
+            }
+            Request::ListDatastore(req) => {
+                let res = self
+                    .client
+                    .list_datastore(pb::ListdatastoreRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListDatastore", s))?;
+                Ok(Response::ListDatastore(res.into_inner().try_into()?))
+            }
+            Request::ListInvoices(req) => {
+                let res = self
+                    .client
+                    .list_invoices(pb::ListinvoicesRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListInvoices", s))?;
+                Ok(Response::ListInvoices(res.into_inner().try_into()?))
+            }
+            Request::SendOnion(req) => {
+                let res = self
+                    .client
+                    .send_onion(pb::SendonionRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SendOnion", s))?;
+                Ok(Response::SendOnion(res.into_inner().try_into()?))
+            }
+            Request::ListSendPays(req) => {
+                let res = self
+                    .client
+                    .list_send_pays(pb::ListsendpaysRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListSendPays", s))?;
+                Ok(Response::ListSendPays(res.into_inner().try_into()?))
+            }
+            Request::ListTransactions(req) => {
+                let res = self
+                    .client
+                    .list_transactions(pb::ListtransactionsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListTransactions", s))?;
+                Ok(Response::ListTransactions(res.into_inner().try_into()?))
+            }
+            Request::Pay(req) => {
+                let res = self
+                    .client
+                    .pay(pb::PayRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Pay", s))?;
+                Ok(Response::Pay(res.into_inner().try_into()?))
+            }
+            Request::ListNodes(req) => {
+                let res = self
+                    .client
+                    .list_nodes(pb::ListnodesRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListNodes", s))?;
+                Ok(Response::ListNodes(res.into_inner().try_into()?))
+            }
+            Request::WaitAnyInvoice(req) => {
+                let res = self
+                    .client
+                    .wait_any_invoice(pb::WaitanyinvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("WaitAnyInvoice", s))?;
+                Ok(Response::WaitAnyInvoice(res.into_inner().try_into()?))
+            }
+            Request::WaitInvoice(req) => {
+                let res = self
+                    .client
+                    .wait_invoice(pb::WaitinvoiceRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("WaitInvoice", s))?;
+                Ok(Response::WaitInvoice(res.into_inner().try_into()?))
+            }
+            Request::WaitSendPay(req) => {
+                let res = self
+                    .client
+                    .wait_send_pay(pb::WaitsendpayRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("WaitSendPay", s))?;
+                Ok(Response::WaitSendPay(res.into_inner().try_into()?))
+            }
+            Request::NewAddr(req) => {
+                let res = self
+                    .client
+                    .new_addr(pb::NewaddrRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("NewAddr", s))?;
+                Ok(Response::NewAddr(res.into_inner().try_into()?))
+            }
+            Request::Withdraw(req) => {
+                let res = self
+                    .client
+                    .withdraw(pb::WithdrawRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Withdraw", s))?;
+                Ok(Response::Withdraw(res.into_inner().try_into()?))
+            }
+            Request::KeySend(req) => {
+                let res = self
+                    .client
+                    .key_send(pb::KeysendRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("KeySend", s))?;
+                Ok(Response::KeySend(res.into_inner().try_into()?))
+            }
+            Request::FundPsbt(req) => {
+                let res = self
+                    .client
+                    .fund_psbt(pb::FundpsbtRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("FundPsbt", s))?;
+                Ok(Response::FundPsbt(res.into_inner().try_into()?))
+            }
+            Request::SendPsbt(req) => {
+                let res = self
+                    .client
+                    .send_psbt(pb::SendpsbtRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SendPsbt", s))?;
+                Ok(Response::SendPsbt(res.into_inner().try_into()?))
+            }
+            Request::SignPsbt(req) => {
+                let res = self
+                    .client
+                    .sign_psbt(pb::SignpsbtRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SignPsbt", s))?;
+                Ok(Response::SignPsbt(res.into_inner().try_into()?))
+            }
+            Request::UtxoPsbt(req) => {
+                let res = self
+                    .client
+                    .utxo_psbt(pb::UtxopsbtRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("UtxoPsbt", s))?;
+                Ok(Response::UtxoPsbt(res.into_inner().try_into()?))
+            }
+            Request::TxDiscard(req) => {
+                let res = self
+                    .client
+                    .tx_discard(pb::TxdiscardRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("TxDiscard", s))?;
+                Ok(Response::TxDiscard(res.into_inner().try_into()?))
+            }
+            Request::TxPrepare(req) => {
+                let res = self
+                    .client
+                    .tx_prepare(pb::TxprepareRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("TxPrepare", s))?;
+                Ok(Response::TxPrepare(res.into_inner().try_into()?))
+            }
+            Request::TxSend(req) => {
+                let res = self
+                    .client
+                    .tx_send(pb::TxsendRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("TxSend", s))?;
+                Ok(Response::TxSend(res.into_inner().try_into()?))
+            }
+            Request::DecodePay(req) => {
+                let res = self
+                    .client
+                    .decode_pay(pb::DecodepayRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("DecodePay", s))?;
+                Ok(Response::DecodePay(res.into_inner().try_into()?))
+            }
+            Request::Decode(req) => {
+                let res = self
+                    .client
+                    .decode(pb::DecodeRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Decode", s))?;
+                Ok(Response::Decode(res.into_inner().try_into()?))
+            }
+            Request::Disconnect(req) => {
+                let res = self
+                    .client
+                    .disconnect(pb::DisconnectRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Disconnect", s))?;
+                Ok(Response::Disconnect(res.into_inner().try_into()?))
+            }
+            Request::Feerates(req) => {
+                let res = self
+                    .client
+                    .feerates(pb::FeeratesRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Feerates", s))?;
+                Ok(Response::Feerates(res.into_inner().try_into()?))
+            }
+            Request::FundChannel(req) => {
+                let res = self
+                    .client
+                    .fund_channel(pb::FundchannelRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("FundChannel", s))?;
+                Ok(Response::FundChannel(res.into_inner().try_into()?))
+            }
+            Request::GetRoute(req) => {
+                let res = self
+                    .client
+                    .get_route(pb::GetrouteRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("GetRoute", s))?;
+                Ok(Response::GetRoute(res.into_inner().try_into()?))
+            }
+            Request::ListForwards(req) => {
+                let res = self
+                    .client
+                    .list_forwards(pb::ListforwardsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListForwards", s))?;
+                Ok(Response::ListForwards(res.into_inner().try_into()?))
+            }
+            Request::ListPays(req) => {
+                let res = self
+                    .client
+                    .list_pays(pb::ListpaysRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ListPays", s))?;
+                Ok(Response::ListPays(res.into_inner().try_into()?))
+            }
+            Request::Ping(req) => {
+                let res = self
+                    .client
+                    .ping(pb::PingRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Ping", s))?;
+                Ok(Response::Ping(res.into_inner().try_into()?))
+            }
+            Request::ReserveInputs(req) => {
+                let res = self
+                    .client
+                    .reserve_inputs(pb::ReserveinputsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("ReserveInputs", s))?;
+                Ok(Response::ReserveInputs(res.into_inner().try_into()?))
+            }
+            Request::SendCustomMsg(req) => {
+                let res = self
+                    .client
+                    .send_custom_msg(pb::SendcustommsgRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SendCustomMsg", s))?;
+                Ok(Response::SendCustomMsg(res.into_inner().try_into()?))
+            }
+            Request::SendOnionMessage(req) => {
+                let res = self
+                    .client
+                    .send_onion_message(pb::SendonionmessageRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SendOnionMessage", s))?;
+                Ok(Response::SendOnionMessage(res.into_inner().try_into()?))
+            }
+            Request::BlindedPath(req) => {
+                let res = self
+                    .client
+                    .blinded_path(pb::BlindedpathRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("BlindedPath", s))?;
+                Ok(Response::BlindedPath(res.into_inner().try_into()?))
+            }
+            Request::SetChannel(req) => {
+                let res = self
+                    .client
+                    .set_channel(pb::SetchannelRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SetChannel", s))?;
+                Ok(Response::SetChannel(res.into_inner().try_into()?))
+            }
+            Request::SignMessage(req) => {
+                let res = self
+                    .client
+                    .sign_message(pb::SignmessageRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("SignMessage", s))?;
+                Ok(Response::SignMessage(res.into_inner().try_into()?))
+            }
+            Request::UnreserveInputs(req) => {
+                let res = self
+                    .client
+                    .unreserve_inputs(pb::UnreserveinputsRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("UnreserveInputs", s))?;
+                Ok(Response::UnreserveInputs(res.into_inner().try_into()?))
+            }
+            Request::WaitBlockHeight(req) => {
+                let res = self
+                    .client
+                    .wait_block_height(pb::WaitblockheightRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("WaitBlockHeight", s))?;
+                Ok(Response::WaitBlockHeight(res.into_inner().try_into()?))
+            }
+            Request::CommandoRune(req) => {
+                let res = self
+                    .client
+                    .commando_rune(pb::CommandoruneRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("CommandoRune", s))?;
+                Ok(Response::CommandoRune(res.into_inner().try_into()?))
+            }
+            Request::StaticBackup(req) => {
+                let res = self
+                    .client
+                    .static_backup(pb::StaticbackupRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("StaticBackup", s))?;
+                Ok(Response::StaticBackup(res.into_inner().try_into()?))
+            }
+            Request::EmergencyRecover(req) => {
+                let res = self
+                    .client
+                    .emergency_recover(pb::EmergencyrecoverRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("EmergencyRecover", s))?;
+                Ok(Response::EmergencyRecover(res.into_inner().try_into()?))
+            }
+            Request::RecoverChannel(req) => {
+                let res = self
+                    .client
+                    .recover_channel(pb::RecoverchannelRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("RecoverChannel", s))?;
+                Ok(Response::RecoverChannel(res.into_inner().try_into()?))
+            }
+            Request::Stop(req) => {
+                let res = self
+                    .client
+                    .stop(pb::StopRequest::try_from(req)?)
+                    .await
+                    .map_err(|s| status_error("Stop", s))?;
+                Ok(Response::Stop(res.into_inner().try_into()?))
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
 // Huge json!() macros require lots of recursion
 #![recursion_limit = "1024"]
 
+mod client;
 mod convert;
 pub mod pb;
 mod server;
+mod status;
 
+pub use crate::client::NodeRpc;
 pub use crate::server::Server;
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
 tonic::include_proto!("cln");
+use crate::status::{invalid, missing};
 use bitcoin::hashes::Hash;
+use cln_rpc::RpcError;
 use std::str::FromStr;
 
 use cln_rpc::primitives::{
@@ -28,33 +30,82 @@ impl From<JOutpoint> for Outpoint {
     }
 }
 
-impl From<Outpoint> for JOutpoint {
-    fn from(a: Outpoint) -> Self {
-        JOutpoint {
-            txid: bitcoin::hashes::sha256::Hash::from_slice(&a.txid).unwrap(),
+impl TryFrom<Outpoint> for JOutpoint {
+    type Error = RpcError;
+
+    fn try_from(a: Outpoint) -> Result<Self, RpcError> {
+        Ok(JOutpoint {
+            txid: bitcoin::hashes::sha256::Hash::from_slice(&a.txid)
+                .map_err(|e| invalid("Outpoint.txid", e))?,
             outnum: a.outnum,
-        }
+        })
     }
 }
 
-impl From<Feerate> for cln_rpc::primitives::Feerate {
-    fn from(f: Feerate) -> cln_rpc::primitives::Feerate {
+impl TryFrom<Feerate> for cln_rpc::primitives::Feerate {
+    type Error = RpcError;
+
+    fn try_from(f: Feerate) -> Result<cln_rpc::primitives::Feerate, RpcError> {
         use feerate::Style;
-        match f.style.unwrap() {
+        Ok(match f.style.ok_or_else(|| missing("Feerate.style"))? {
             Style::Slow(_) => JFeerate::Slow,
             Style::Normal(_) => JFeerate::Normal,
             Style::Urgent(_) => JFeerate::Urgent,
             Style::Perkw(i) => JFeerate::PerKw(i),
             Style::Perkb(i) => JFeerate::PerKb(i),
-        }
+            Style::Opening(_) => JFeerate::Opening,
+            Style::MutualClose(_) => JFeerate::MutualClose,
+            Style::UnilateralClose(_) => JFeerate::UnilateralClose,
+            Style::DelayedToUs(_) => JFeerate::DelayedToUs,
+            Style::HtlcResolution(_) => JFeerate::HtlcResolution,
+            Style::Penalty(_) => JFeerate::Penalty,
+            Style::MinAcceptable(_) => JFeerate::MinAcceptable,
+            Style::MaxAcceptable(_) => JFeerate::MaxAcceptable,
+        })
+    }
+}
+
+impl From<cln_rpc::primitives::Feerate> for Feerate {
+    fn from(f: cln_rpc::primitives::Feerate) -> Feerate {
+        use feerate::Style;
+        let style = match f {
+            JFeerate::Slow => Style::Slow(true),
+            JFeerate::Normal => Style::Normal(true),
+            JFeerate::Urgent => Style::Urgent(true),
+            JFeerate::PerKw(i) => Style::Perkw(i),
+            JFeerate::PerKb(i) => Style::Perkb(i),
+            JFeerate::Opening => Style::Opening(true),
+            JFeerate::MutualClose => Style::MutualClose(true),
+            JFeerate::UnilateralClose => Style::UnilateralClose(true),
+            JFeerate::DelayedToUs => Style::DelayedToUs(true),
+            JFeerate::HtlcResolution => Style::HtlcResolution(true),
+            JFeerate::Penalty => Style::Penalty(true),
+            JFeerate::MinAcceptable => Style::MinAcceptable(true),
+            JFeerate::MaxAcceptable => Style::MaxAcceptable(true),
+        };
+        Feerate { style: Some(style) }
+    }
+}
+
+impl TryFrom<OutputDesc> for JOutputDesc {
+    type Error = RpcError;
+
+    fn try_from(od: OutputDesc) -> Result<JOutputDesc, RpcError> {
+        Ok(JOutputDesc {
+            address: od.address,
+            amount: od
+                .amount
+                .ok_or_else(|| missing("OutputDesc.amount"))?
+                .into(),
+        })
     }
 }
 
-impl From<OutputDesc> for JOutputDesc {
-    fn from(od: OutputDesc) -> JOutputDesc {
-        JOutputDesc {
+impl From<JOutputDesc> for OutputDesc {
+    fn from(od: JOutputDesc) -> OutputDesc {
+        OutputDesc {
             address: od.address,
-            amount: od.amount.unwrap().into(),
+            amount: Some(od.amount.into()),
         }
     }
 }
@@ -72,12 +123,14 @@ impl From<JAmountOrAll> for AmountOrAll {
     }
 }
 
-impl From<AmountOrAll> for JAmountOrAll {
-    fn from(a: AmountOrAll) -> Self {
+impl TryFrom<AmountOrAll> for JAmountOrAll {
+    type Error = RpcError;
+
+    fn try_from(a: AmountOrAll) -> Result<Self, RpcError> {
         match a.value {
-            Some(amount_or_all::Value::Amount(a)) => JAmountOrAll::Amount(a.into()),
-            Some(amount_or_all::Value::All(_)) => JAmountOrAll::All,
-            None => panic!("AmountOrAll is neither amount nor all: {:?}", a),
+            Some(amount_or_all::Value::Amount(a)) => Ok(JAmountOrAll::Amount(a.into())),
+            Some(amount_or_all::Value::All(_)) => Ok(JAmountOrAll::All),
+            None => Err(missing("AmountOrAll.value")),
         }
     }
 }
@@ -94,43 +147,129 @@ impl From<JAmountOrAny> for AmountOrAny {
         }
     }
 }
-impl From<AmountOrAny> for JAmountOrAny {
-    fn from(a: AmountOrAny) -> Self {
+impl TryFrom<AmountOrAny> for JAmountOrAny {
+    type Error = RpcError;
+
+    fn try_from(a: AmountOrAny) -> Result<Self, RpcError> {
         match a.value {
-            Some(amount_or_any::Value::Amount(a)) => JAmountOrAny::Amount(a.into()),
-            Some(amount_or_any::Value::Any(_)) => JAmountOrAny::Any,
-            None => panic!("AmountOrAll is neither amount nor any: {:?}", a),
+            Some(amount_or_any::Value::Amount(a)) => Ok(JAmountOrAny::Amount(a.into())),
+            Some(amount_or_any::Value::Any(_)) => Ok(JAmountOrAny::Any),
+            None => Err(missing("AmountOrAny.value")),
         }
     }
 }
-impl From<RouteHop> for cln_rpc::primitives::Routehop {
-    fn from(c: RouteHop) -> Self {
-        Self {
-            id: cln_rpc::primitives::PublicKey::from_slice(&c.id).unwrap(),
-            scid: cln_rpc::primitives::ShortChannelId::from_str(&c.short_channel_id).unwrap(),
-            feebase: c.feebase.unwrap().into(),
+impl TryFrom<RouteHop> for cln_rpc::primitives::Routehop {
+    type Error = RpcError;
+
+    fn try_from(c: RouteHop) -> Result<Self, RpcError> {
+        Ok(Self {
+            id: cln_rpc::primitives::PublicKey::from_slice(&c.id)
+                .map_err(|e| invalid("RouteHop.id", e))?,
+            scid: cln_rpc::primitives::ShortChannelId::from_str(&c.short_channel_id)
+                .map_err(|e| invalid("RouteHop.short_channel_id", e))?,
+            feebase: c.feebase.ok_or_else(|| missing("RouteHop.feebase"))?.into(),
             feeprop: c.feeprop,
             expirydelta: c.expirydelta as u16,
+        })
+    }
+}
+
+impl TryFrom<Routehint> for cln_rpc::primitives::Routehint {
+    type Error = RpcError;
+
+    fn try_from(c: Routehint) -> Result<Self, RpcError> {
+        Ok(Self {
+            hops: c
+                .hops
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<RoutehintList> for cln_rpc::primitives::RoutehintList {
+    type Error = RpcError;
+
+    fn try_from(c: RoutehintList) -> Result<Self, RpcError> {
+        Ok(Self {
+            hints: c
+                .hints
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<cln_rpc::primitives::Routehop> for RouteHop {
+    fn from(c: cln_rpc::primitives::Routehop) -> Self {
+        Self {
+            id: c.id.serialize().to_vec(),
+            short_channel_id: c.scid.to_string(),
+            feebase: Some(c.feebase.into()),
+            feeprop: c.feeprop,
+            expirydelta: c.expirydelta as u32,
         }
     }
 }
 
-impl From<Routehint> for cln_rpc::primitives::Routehint {
-    fn from(c: Routehint) -> Self {
+impl From<cln_rpc::primitives::Routehint> for Routehint {
+    fn from(c: cln_rpc::primitives::Routehint) -> Self {
         Self {
             hops: c.hops.into_iter().map(|h| h.into()).collect(),
         }
     }
 }
 
-impl From<RoutehintList> for cln_rpc::primitives::RoutehintList {
-    fn from(c: RoutehintList) -> Self {
+impl From<cln_rpc::primitives::RoutehintList> for RoutehintList {
+    fn from(c: cln_rpc::primitives::RoutehintList) -> Self {
+        Self {
+            hints: c.hints.into_iter().map(|h| h.into()).collect(),
+        }
+    }
+}
+
+impl From<cln_rpc::primitives::DecodeRoutehintList> for DecodeRoutehintList {
+    fn from(c: cln_rpc::primitives::DecodeRoutehintList) -> Self {
         Self {
             hints: c.hints.into_iter().map(|h| h.into()).collect(),
         }
     }
 }
 
+impl TryFrom<DecodeRoutehintList> for cln_rpc::primitives::DecodeRoutehintList {
+    type Error = RpcError;
+
+    fn try_from(c: DecodeRoutehintList) -> Result<Self, RpcError> {
+        Ok(Self {
+            hints: c
+                .hints
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<cln_rpc::primitives::PluginOptions> for PluginOptions {
+    fn from(c: cln_rpc::primitives::PluginOptions) -> Self {
+        Self {
+            options: c.0.into_iter().map(|(k, v)| (k, v.to_string())).collect(),
+        }
+    }
+}
+
+impl From<PluginOptions> for cln_rpc::primitives::PluginOptions {
+    fn from(c: PluginOptions) -> Self {
+        let mut options = Self::default();
+        for (k, v) in c.options {
+            options.insert_json(k, v);
+        }
+        options
+    }
+}
+
 impl From<TlvStream> for cln_rpc::primitives::TlvStream {
     fn from(s: TlvStream) -> Self {
         Self {
@@ -148,6 +287,67 @@ impl From<TlvEntry> for cln_rpc::primitives::TlvEntry {
     }
 }
 
+impl From<cln_rpc::primitives::TlvStream> for TlvStream {
+    fn from(s: cln_rpc::primitives::TlvStream) -> Self {
+        Self {
+            entries: s.entries.into_iter().map(|e| e.into()).collect(),
+        }
+    }
+}
+
+impl From<cln_rpc::primitives::TlvEntry> for TlvEntry {
+    fn from(e: cln_rpc::primitives::TlvEntry) -> Self {
+        Self {
+            r#type: e.typ,
+            value: e.value,
+        }
+    }
+}
+
+impl From<cln_rpc::primitives::RuneRestrictions> for RuneRestrictions {
+    fn from(r: cln_rpc::primitives::RuneRestrictions) -> Self {
+        use cln_rpc::primitives::RuneRestrictions as JRuneRestrictions;
+        match r {
+            JRuneRestrictions::Readonly => RuneRestrictions {
+                readonly: true,
+                restrictions: vec![],
+            },
+            JRuneRestrictions::Restrictions(r) => RuneRestrictions {
+                readonly: false,
+                restrictions: r
+                    .into_iter()
+                    .map(|r| RuneRestriction {
+                        alternatives: r.alternatives.iter().map(|a| a.to_string()).collect(),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl TryFrom<RuneRestrictions> for cln_rpc::primitives::RuneRestrictions {
+    type Error = anyhow::Error;
+
+    fn try_from(r: RuneRestrictions) -> Result<Self, Self::Error> {
+        if r.readonly {
+            return Ok(Self::Readonly);
+        }
+        let restrictions = r
+            .restrictions
+            .into_iter()
+            .map(|r| {
+                let alternatives = r
+                    .alternatives
+                    .iter()
+                    .map(|a| a.parse())
+                    .collect::<Result<_, _>>()?;
+                cln_rpc::primitives::Restriction::new(alternatives)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::Restrictions(restrictions))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
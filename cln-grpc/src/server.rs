@@ -1,6 +1,7 @@
 use crate::pb::node_server::Node;
 use crate::pb;
-use cln_rpc::{Request, Response, ClnRpc};
+use crate::status::rpc_status;
+use cln_rpc::{Request, Response, ClnRpc, RpcError};
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 use cln_rpc::model::requests;
@@ -31,7 +32,9 @@ async fn getinfo(
     request: tonic::Request<pb::GetinfoRequest>,
 ) -> Result<tonic::Response<pb::GetinfoResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::GetinfoRequest = req.into();
+    let req: requests::GetinfoRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for getinfo");
     trace!("getinfo request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -39,9 +42,7 @@ async fn getinfo(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Getinfo(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Getinfo: {:?}", e)))?;
+        .map_err(|e| rpc_status("Getinfo", e))?;
     match result {
         Response::Getinfo(r) => {
            trace!("getinfo response: {:?}", r);
@@ -63,7 +64,9 @@ async fn list_peers(
     request: tonic::Request<pb::ListpeersRequest>,
 ) -> Result<tonic::Response<pb::ListpeersResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListpeersRequest = req.into();
+    let req: requests::ListpeersRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_peers");
     trace!("list_peers request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -71,9 +74,7 @@ async fn list_peers(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListPeers(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListPeers: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListPeers", e))?;
     match result {
         Response::ListPeers(r) => {
            trace!("list_peers response: {:?}", r);
@@ -95,7 +96,9 @@ async fn list_funds(
     request: tonic::Request<pb::ListfundsRequest>,
 ) -> Result<tonic::Response<pb::ListfundsResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListfundsRequest = req.into();
+    let req: requests::ListfundsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_funds");
     trace!("list_funds request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -103,9 +106,7 @@ async fn list_funds(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListFunds(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListFunds: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListFunds", e))?;
     match result {
         Response::ListFunds(r) => {
            trace!("list_funds response: {:?}", r);
@@ -127,7 +128,9 @@ async fn send_pay(
     request: tonic::Request<pb::SendpayRequest>,
 ) -> Result<tonic::Response<pb::SendpayResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SendpayRequest = req.into();
+    let req: requests::SendpayRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for send_pay");
     trace!("send_pay request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -135,9 +138,7 @@ async fn send_pay(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SendPay(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SendPay: {:?}", e)))?;
+        .map_err(|e| rpc_status("SendPay", e))?;
     match result {
         Response::SendPay(r) => {
            trace!("send_pay response: {:?}", r);
@@ -159,7 +160,9 @@ async fn list_channels(
     request: tonic::Request<pb::ListchannelsRequest>,
 ) -> Result<tonic::Response<pb::ListchannelsResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListchannelsRequest = req.into();
+    let req: requests::ListchannelsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_channels");
     trace!("list_channels request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -167,9 +170,7 @@ async fn list_channels(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListChannels(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListChannels: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListChannels", e))?;
     match result {
         Response::ListChannels(r) => {
            trace!("list_channels response: {:?}", r);
@@ -186,12 +187,46 @@ async fn list_channels(
 
 }
 
+async fn list_configs(
+    &self,
+    request: tonic::Request<pb::ListconfigsRequest>,
+) -> Result<tonic::Response<pb::ListconfigsResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::ListconfigsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for list_configs");
+    trace!("list_configs request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::ListConfigs(req))
+        .await
+        .map_err(|e| rpc_status("ListConfigs", e))?;
+    match result {
+        Response::ListConfigs(r) => {
+           trace!("list_configs response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call ListConfigs",
+                r
+            )
+        )),
+    }
+
+}
+
 async fn add_gossip(
     &self,
     request: tonic::Request<pb::AddgossipRequest>,
 ) -> Result<tonic::Response<pb::AddgossipResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::AddgossipRequest = req.into();
+    let req: requests::AddgossipRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for add_gossip");
     trace!("add_gossip request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -199,9 +234,7 @@ async fn add_gossip(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::AddGossip(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method AddGossip: {:?}", e)))?;
+        .map_err(|e| rpc_status("AddGossip", e))?;
     match result {
         Response::AddGossip(r) => {
            trace!("add_gossip response: {:?}", r);
@@ -223,7 +256,9 @@ async fn auto_clean_invoice(
     request: tonic::Request<pb::AutocleaninvoiceRequest>,
 ) -> Result<tonic::Response<pb::AutocleaninvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::AutocleaninvoiceRequest = req.into();
+    let req: requests::AutocleaninvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for auto_clean_invoice");
     trace!("auto_clean_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -231,9 +266,7 @@ async fn auto_clean_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::AutoCleanInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method AutoCleanInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("AutoCleanInvoice", e))?;
     match result {
         Response::AutoCleanInvoice(r) => {
            trace!("auto_clean_invoice response: {:?}", r);
@@ -255,7 +288,9 @@ async fn check_message(
     request: tonic::Request<pb::CheckmessageRequest>,
 ) -> Result<tonic::Response<pb::CheckmessageResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::CheckmessageRequest = req.into();
+    let req: requests::CheckmessageRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for check_message");
     trace!("check_message request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -263,9 +298,7 @@ async fn check_message(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::CheckMessage(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method CheckMessage: {:?}", e)))?;
+        .map_err(|e| rpc_status("CheckMessage", e))?;
     match result {
         Response::CheckMessage(r) => {
            trace!("check_message response: {:?}", r);
@@ -287,7 +320,9 @@ async fn close(
     request: tonic::Request<pb::CloseRequest>,
 ) -> Result<tonic::Response<pb::CloseResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::CloseRequest = req.into();
+    let req: requests::CloseRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for close");
     trace!("close request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -295,9 +330,7 @@ async fn close(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Close(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Close: {:?}", e)))?;
+        .map_err(|e| rpc_status("Close", e))?;
     match result {
         Response::Close(r) => {
            trace!("close response: {:?}", r);
@@ -319,7 +352,9 @@ async fn connect_peer(
     request: tonic::Request<pb::ConnectRequest>,
 ) -> Result<tonic::Response<pb::ConnectResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ConnectRequest = req.into();
+    let req: requests::ConnectRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for connect_peer");
     trace!("connect_peer request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -327,9 +362,7 @@ async fn connect_peer(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Connect(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Connect: {:?}", e)))?;
+        .map_err(|e| rpc_status("Connect", e))?;
     match result {
         Response::Connect(r) => {
            trace!("connect_peer response: {:?}", r);
@@ -351,7 +384,9 @@ async fn create_invoice(
     request: tonic::Request<pb::CreateinvoiceRequest>,
 ) -> Result<tonic::Response<pb::CreateinvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::CreateinvoiceRequest = req.into();
+    let req: requests::CreateinvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for create_invoice");
     trace!("create_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -359,9 +394,7 @@ async fn create_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::CreateInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method CreateInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("CreateInvoice", e))?;
     match result {
         Response::CreateInvoice(r) => {
            trace!("create_invoice response: {:?}", r);
@@ -383,7 +416,9 @@ async fn datastore(
     request: tonic::Request<pb::DatastoreRequest>,
 ) -> Result<tonic::Response<pb::DatastoreResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::DatastoreRequest = req.into();
+    let req: requests::DatastoreRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for datastore");
     trace!("datastore request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -391,9 +426,7 @@ async fn datastore(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Datastore(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Datastore: {:?}", e)))?;
+        .map_err(|e| rpc_status("Datastore", e))?;
     match result {
         Response::Datastore(r) => {
            trace!("datastore response: {:?}", r);
@@ -415,7 +448,9 @@ async fn create_onion(
     request: tonic::Request<pb::CreateonionRequest>,
 ) -> Result<tonic::Response<pb::CreateonionResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::CreateonionRequest = req.into();
+    let req: requests::CreateonionRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for create_onion");
     trace!("create_onion request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -423,9 +458,7 @@ async fn create_onion(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::CreateOnion(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method CreateOnion: {:?}", e)))?;
+        .map_err(|e| rpc_status("CreateOnion", e))?;
     match result {
         Response::CreateOnion(r) => {
            trace!("create_onion response: {:?}", r);
@@ -447,7 +480,9 @@ async fn del_datastore(
     request: tonic::Request<pb::DeldatastoreRequest>,
 ) -> Result<tonic::Response<pb::DeldatastoreResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::DeldatastoreRequest = req.into();
+    let req: requests::DeldatastoreRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for del_datastore");
     trace!("del_datastore request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -455,9 +490,7 @@ async fn del_datastore(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::DelDatastore(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method DelDatastore: {:?}", e)))?;
+        .map_err(|e| rpc_status("DelDatastore", e))?;
     match result {
         Response::DelDatastore(r) => {
            trace!("del_datastore response: {:?}", r);
@@ -479,7 +512,9 @@ async fn del_expired_invoice(
     request: tonic::Request<pb::DelexpiredinvoiceRequest>,
 ) -> Result<tonic::Response<pb::DelexpiredinvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::DelexpiredinvoiceRequest = req.into();
+    let req: requests::DelexpiredinvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for del_expired_invoice");
     trace!("del_expired_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -487,9 +522,7 @@ async fn del_expired_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::DelExpiredInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method DelExpiredInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("DelExpiredInvoice", e))?;
     match result {
         Response::DelExpiredInvoice(r) => {
            trace!("del_expired_invoice response: {:?}", r);
@@ -511,7 +544,9 @@ async fn del_invoice(
     request: tonic::Request<pb::DelinvoiceRequest>,
 ) -> Result<tonic::Response<pb::DelinvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::DelinvoiceRequest = req.into();
+    let req: requests::DelinvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for del_invoice");
     trace!("del_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -519,9 +554,7 @@ async fn del_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::DelInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method DelInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("DelInvoice", e))?;
     match result {
         Response::DelInvoice(r) => {
            trace!("del_invoice response: {:?}", r);
@@ -543,7 +576,9 @@ async fn invoice(
     request: tonic::Request<pb::InvoiceRequest>,
 ) -> Result<tonic::Response<pb::InvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::InvoiceRequest = req.into();
+    let req: requests::InvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for invoice");
     trace!("invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -551,9 +586,7 @@ async fn invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Invoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Invoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("Invoice", e))?;
     match result {
         Response::Invoice(r) => {
            trace!("invoice response: {:?}", r);
@@ -575,7 +608,9 @@ async fn list_datastore(
     request: tonic::Request<pb::ListdatastoreRequest>,
 ) -> Result<tonic::Response<pb::ListdatastoreResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListdatastoreRequest = req.into();
+    let req: requests::ListdatastoreRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_datastore");
     trace!("list_datastore request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -583,9 +618,7 @@ async fn list_datastore(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListDatastore(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListDatastore: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListDatastore", e))?;
     match result {
         Response::ListDatastore(r) => {
            trace!("list_datastore response: {:?}", r);
@@ -607,7 +640,9 @@ async fn list_invoices(
     request: tonic::Request<pb::ListinvoicesRequest>,
 ) -> Result<tonic::Response<pb::ListinvoicesResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListinvoicesRequest = req.into();
+    let req: requests::ListinvoicesRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_invoices");
     trace!("list_invoices request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -615,9 +650,7 @@ async fn list_invoices(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListInvoices(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListInvoices: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListInvoices", e))?;
     match result {
         Response::ListInvoices(r) => {
            trace!("list_invoices response: {:?}", r);
@@ -639,7 +672,9 @@ async fn send_onion(
     request: tonic::Request<pb::SendonionRequest>,
 ) -> Result<tonic::Response<pb::SendonionResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SendonionRequest = req.into();
+    let req: requests::SendonionRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for send_onion");
     trace!("send_onion request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -647,9 +682,7 @@ async fn send_onion(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SendOnion(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SendOnion: {:?}", e)))?;
+        .map_err(|e| rpc_status("SendOnion", e))?;
     match result {
         Response::SendOnion(r) => {
            trace!("send_onion response: {:?}", r);
@@ -671,7 +704,9 @@ async fn list_send_pays(
     request: tonic::Request<pb::ListsendpaysRequest>,
 ) -> Result<tonic::Response<pb::ListsendpaysResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListsendpaysRequest = req.into();
+    let req: requests::ListsendpaysRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_send_pays");
     trace!("list_send_pays request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -679,9 +714,7 @@ async fn list_send_pays(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListSendPays(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListSendPays: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListSendPays", e))?;
     match result {
         Response::ListSendPays(r) => {
            trace!("list_send_pays response: {:?}", r);
@@ -703,7 +736,9 @@ async fn list_transactions(
     request: tonic::Request<pb::ListtransactionsRequest>,
 ) -> Result<tonic::Response<pb::ListtransactionsResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListtransactionsRequest = req.into();
+    let req: requests::ListtransactionsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_transactions");
     trace!("list_transactions request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -711,9 +746,7 @@ async fn list_transactions(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListTransactions(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListTransactions: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListTransactions", e))?;
     match result {
         Response::ListTransactions(r) => {
            trace!("list_transactions response: {:?}", r);
@@ -735,7 +768,9 @@ async fn pay(
     request: tonic::Request<pb::PayRequest>,
 ) -> Result<tonic::Response<pb::PayResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::PayRequest = req.into();
+    let req: requests::PayRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for pay");
     trace!("pay request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -743,9 +778,7 @@ async fn pay(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Pay(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Pay: {:?}", e)))?;
+        .map_err(|e| rpc_status("Pay", e))?;
     match result {
         Response::Pay(r) => {
            trace!("pay response: {:?}", r);
@@ -767,7 +800,9 @@ async fn list_nodes(
     request: tonic::Request<pb::ListnodesRequest>,
 ) -> Result<tonic::Response<pb::ListnodesResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListnodesRequest = req.into();
+    let req: requests::ListnodesRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_nodes");
     trace!("list_nodes request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -775,9 +810,7 @@ async fn list_nodes(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListNodes(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListNodes: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListNodes", e))?;
     match result {
         Response::ListNodes(r) => {
            trace!("list_nodes response: {:?}", r);
@@ -799,7 +832,9 @@ async fn wait_any_invoice(
     request: tonic::Request<pb::WaitanyinvoiceRequest>,
 ) -> Result<tonic::Response<pb::WaitanyinvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::WaitanyinvoiceRequest = req.into();
+    let req: requests::WaitanyinvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for wait_any_invoice");
     trace!("wait_any_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -807,9 +842,7 @@ async fn wait_any_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::WaitAnyInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method WaitAnyInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("WaitAnyInvoice", e))?;
     match result {
         Response::WaitAnyInvoice(r) => {
            trace!("wait_any_invoice response: {:?}", r);
@@ -831,7 +864,9 @@ async fn wait_invoice(
     request: tonic::Request<pb::WaitinvoiceRequest>,
 ) -> Result<tonic::Response<pb::WaitinvoiceResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::WaitinvoiceRequest = req.into();
+    let req: requests::WaitinvoiceRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for wait_invoice");
     trace!("wait_invoice request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -839,9 +874,7 @@ async fn wait_invoice(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::WaitInvoice(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method WaitInvoice: {:?}", e)))?;
+        .map_err(|e| rpc_status("WaitInvoice", e))?;
     match result {
         Response::WaitInvoice(r) => {
            trace!("wait_invoice response: {:?}", r);
@@ -863,7 +896,9 @@ async fn wait_send_pay(
     request: tonic::Request<pb::WaitsendpayRequest>,
 ) -> Result<tonic::Response<pb::WaitsendpayResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::WaitsendpayRequest = req.into();
+    let req: requests::WaitsendpayRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for wait_send_pay");
     trace!("wait_send_pay request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -871,9 +906,7 @@ async fn wait_send_pay(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::WaitSendPay(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method WaitSendPay: {:?}", e)))?;
+        .map_err(|e| rpc_status("WaitSendPay", e))?;
     match result {
         Response::WaitSendPay(r) => {
            trace!("wait_send_pay response: {:?}", r);
@@ -895,7 +928,9 @@ async fn new_addr(
     request: tonic::Request<pb::NewaddrRequest>,
 ) -> Result<tonic::Response<pb::NewaddrResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::NewaddrRequest = req.into();
+    let req: requests::NewaddrRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for new_addr");
     trace!("new_addr request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -903,9 +938,7 @@ async fn new_addr(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::NewAddr(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method NewAddr: {:?}", e)))?;
+        .map_err(|e| rpc_status("NewAddr", e))?;
     match result {
         Response::NewAddr(r) => {
            trace!("new_addr response: {:?}", r);
@@ -927,7 +960,9 @@ async fn withdraw(
     request: tonic::Request<pb::WithdrawRequest>,
 ) -> Result<tonic::Response<pb::WithdrawResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::WithdrawRequest = req.into();
+    let req: requests::WithdrawRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for withdraw");
     trace!("withdraw request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -935,9 +970,7 @@ async fn withdraw(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Withdraw(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Withdraw: {:?}", e)))?;
+        .map_err(|e| rpc_status("Withdraw", e))?;
     match result {
         Response::Withdraw(r) => {
            trace!("withdraw response: {:?}", r);
@@ -959,7 +992,9 @@ async fn key_send(
     request: tonic::Request<pb::KeysendRequest>,
 ) -> Result<tonic::Response<pb::KeysendResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::KeysendRequest = req.into();
+    let req: requests::KeysendRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for key_send");
     trace!("key_send request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -967,9 +1002,7 @@ async fn key_send(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::KeySend(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method KeySend: {:?}", e)))?;
+        .map_err(|e| rpc_status("KeySend", e))?;
     match result {
         Response::KeySend(r) => {
            trace!("key_send response: {:?}", r);
@@ -991,7 +1024,9 @@ async fn fund_psbt(
     request: tonic::Request<pb::FundpsbtRequest>,
 ) -> Result<tonic::Response<pb::FundpsbtResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::FundpsbtRequest = req.into();
+    let req: requests::FundpsbtRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for fund_psbt");
     trace!("fund_psbt request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -999,9 +1034,7 @@ async fn fund_psbt(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::FundPsbt(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method FundPsbt: {:?}", e)))?;
+        .map_err(|e| rpc_status("FundPsbt", e))?;
     match result {
         Response::FundPsbt(r) => {
            trace!("fund_psbt response: {:?}", r);
@@ -1023,7 +1056,9 @@ async fn send_psbt(
     request: tonic::Request<pb::SendpsbtRequest>,
 ) -> Result<tonic::Response<pb::SendpsbtResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SendpsbtRequest = req.into();
+    let req: requests::SendpsbtRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for send_psbt");
     trace!("send_psbt request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1031,9 +1066,7 @@ async fn send_psbt(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SendPsbt(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SendPsbt: {:?}", e)))?;
+        .map_err(|e| rpc_status("SendPsbt", e))?;
     match result {
         Response::SendPsbt(r) => {
            trace!("send_psbt response: {:?}", r);
@@ -1055,7 +1088,9 @@ async fn sign_psbt(
     request: tonic::Request<pb::SignpsbtRequest>,
 ) -> Result<tonic::Response<pb::SignpsbtResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SignpsbtRequest = req.into();
+    let req: requests::SignpsbtRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for sign_psbt");
     trace!("sign_psbt request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1063,9 +1098,7 @@ async fn sign_psbt(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SignPsbt(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SignPsbt: {:?}", e)))?;
+        .map_err(|e| rpc_status("SignPsbt", e))?;
     match result {
         Response::SignPsbt(r) => {
            trace!("sign_psbt response: {:?}", r);
@@ -1087,7 +1120,9 @@ async fn utxo_psbt(
     request: tonic::Request<pb::UtxopsbtRequest>,
 ) -> Result<tonic::Response<pb::UtxopsbtResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::UtxopsbtRequest = req.into();
+    let req: requests::UtxopsbtRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for utxo_psbt");
     trace!("utxo_psbt request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1095,9 +1130,7 @@ async fn utxo_psbt(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::UtxoPsbt(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method UtxoPsbt: {:?}", e)))?;
+        .map_err(|e| rpc_status("UtxoPsbt", e))?;
     match result {
         Response::UtxoPsbt(r) => {
            trace!("utxo_psbt response: {:?}", r);
@@ -1119,7 +1152,9 @@ async fn tx_discard(
     request: tonic::Request<pb::TxdiscardRequest>,
 ) -> Result<tonic::Response<pb::TxdiscardResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::TxdiscardRequest = req.into();
+    let req: requests::TxdiscardRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for tx_discard");
     trace!("tx_discard request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1127,9 +1162,7 @@ async fn tx_discard(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::TxDiscard(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method TxDiscard: {:?}", e)))?;
+        .map_err(|e| rpc_status("TxDiscard", e))?;
     match result {
         Response::TxDiscard(r) => {
            trace!("tx_discard response: {:?}", r);
@@ -1151,7 +1184,9 @@ async fn tx_prepare(
     request: tonic::Request<pb::TxprepareRequest>,
 ) -> Result<tonic::Response<pb::TxprepareResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::TxprepareRequest = req.into();
+    let req: requests::TxprepareRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for tx_prepare");
     trace!("tx_prepare request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1159,9 +1194,7 @@ async fn tx_prepare(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::TxPrepare(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method TxPrepare: {:?}", e)))?;
+        .map_err(|e| rpc_status("TxPrepare", e))?;
     match result {
         Response::TxPrepare(r) => {
            trace!("tx_prepare response: {:?}", r);
@@ -1183,7 +1216,9 @@ async fn tx_send(
     request: tonic::Request<pb::TxsendRequest>,
 ) -> Result<tonic::Response<pb::TxsendResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::TxsendRequest = req.into();
+    let req: requests::TxsendRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for tx_send");
     trace!("tx_send request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1191,9 +1226,7 @@ async fn tx_send(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::TxSend(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method TxSend: {:?}", e)))?;
+        .map_err(|e| rpc_status("TxSend", e))?;
     match result {
         Response::TxSend(r) => {
            trace!("tx_send response: {:?}", r);
@@ -1210,12 +1243,78 @@ async fn tx_send(
 
 }
 
+async fn decode_pay(
+    &self,
+    request: tonic::Request<pb::DecodepayRequest>,
+) -> Result<tonic::Response<pb::DecodepayResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::DecodepayRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for decode_pay");
+    trace!("decode_pay request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::DecodePay(req))
+        .await
+        .map_err(|e| rpc_status("DecodePay", e))?;
+    match result {
+        Response::DecodePay(r) => {
+           trace!("decode_pay response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call DecodePay",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn decode(
+    &self,
+    request: tonic::Request<pb::DecodeRequest>,
+) -> Result<tonic::Response<pb::DecodeResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::DecodeRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for decode");
+    trace!("decode request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::Decode(req))
+        .await
+        .map_err(|e| rpc_status("Decode", e))?;
+    match result {
+        Response::Decode(r) => {
+           trace!("decode response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call Decode",
+                r
+            )
+        )),
+    }
+
+}
+
 async fn disconnect(
     &self,
     request: tonic::Request<pb::DisconnectRequest>,
 ) -> Result<tonic::Response<pb::DisconnectResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::DisconnectRequest = req.into();
+    let req: requests::DisconnectRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for disconnect");
     trace!("disconnect request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1223,9 +1322,7 @@ async fn disconnect(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Disconnect(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Disconnect: {:?}", e)))?;
+        .map_err(|e| rpc_status("Disconnect", e))?;
     match result {
         Response::Disconnect(r) => {
            trace!("disconnect response: {:?}", r);
@@ -1247,7 +1344,9 @@ async fn feerates(
     request: tonic::Request<pb::FeeratesRequest>,
 ) -> Result<tonic::Response<pb::FeeratesResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::FeeratesRequest = req.into();
+    let req: requests::FeeratesRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for feerates");
     trace!("feerates request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1255,9 +1354,7 @@ async fn feerates(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Feerates(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Feerates: {:?}", e)))?;
+        .map_err(|e| rpc_status("Feerates", e))?;
     match result {
         Response::Feerates(r) => {
            trace!("feerates response: {:?}", r);
@@ -1279,7 +1376,9 @@ async fn fund_channel(
     request: tonic::Request<pb::FundchannelRequest>,
 ) -> Result<tonic::Response<pb::FundchannelResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::FundchannelRequest = req.into();
+    let req: requests::FundchannelRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for fund_channel");
     trace!("fund_channel request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1287,9 +1386,7 @@ async fn fund_channel(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::FundChannel(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method FundChannel: {:?}", e)))?;
+        .map_err(|e| rpc_status("FundChannel", e))?;
     match result {
         Response::FundChannel(r) => {
            trace!("fund_channel response: {:?}", r);
@@ -1311,7 +1408,9 @@ async fn get_route(
     request: tonic::Request<pb::GetrouteRequest>,
 ) -> Result<tonic::Response<pb::GetrouteResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::GetrouteRequest = req.into();
+    let req: requests::GetrouteRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for get_route");
     trace!("get_route request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1319,9 +1418,7 @@ async fn get_route(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::GetRoute(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method GetRoute: {:?}", e)))?;
+        .map_err(|e| rpc_status("GetRoute", e))?;
     match result {
         Response::GetRoute(r) => {
            trace!("get_route response: {:?}", r);
@@ -1343,7 +1440,9 @@ async fn list_forwards(
     request: tonic::Request<pb::ListforwardsRequest>,
 ) -> Result<tonic::Response<pb::ListforwardsResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListforwardsRequest = req.into();
+    let req: requests::ListforwardsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_forwards");
     trace!("list_forwards request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1351,9 +1450,7 @@ async fn list_forwards(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListForwards(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListForwards: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListForwards", e))?;
     match result {
         Response::ListForwards(r) => {
            trace!("list_forwards response: {:?}", r);
@@ -1375,7 +1472,9 @@ async fn list_pays(
     request: tonic::Request<pb::ListpaysRequest>,
 ) -> Result<tonic::Response<pb::ListpaysResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::ListpaysRequest = req.into();
+    let req: requests::ListpaysRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for list_pays");
     trace!("list_pays request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1383,9 +1482,7 @@ async fn list_pays(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::ListPays(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method ListPays: {:?}", e)))?;
+        .map_err(|e| rpc_status("ListPays", e))?;
     match result {
         Response::ListPays(r) => {
            trace!("list_pays response: {:?}", r);
@@ -1407,7 +1504,9 @@ async fn ping(
     request: tonic::Request<pb::PingRequest>,
 ) -> Result<tonic::Response<pb::PingResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::PingRequest = req.into();
+    let req: requests::PingRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for ping");
     trace!("ping request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1415,9 +1514,7 @@ async fn ping(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Ping(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Ping: {:?}", e)))?;
+        .map_err(|e| rpc_status("Ping", e))?;
     match result {
         Response::Ping(r) => {
            trace!("ping response: {:?}", r);
@@ -1434,12 +1531,142 @@ async fn ping(
 
 }
 
+async fn reserve_inputs(
+    &self,
+    request: tonic::Request<pb::ReserveinputsRequest>,
+) -> Result<tonic::Response<pb::ReserveinputsResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::ReserveinputsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for reserve_inputs");
+    trace!("reserve_inputs request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::ReserveInputs(req))
+        .await
+        .map_err(|e| rpc_status("ReserveInputs", e))?;
+    match result {
+        Response::ReserveInputs(r) => {
+           trace!("reserve_inputs response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call ReserveInputs",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn send_custom_msg(
+    &self,
+    request: tonic::Request<pb::SendcustommsgRequest>,
+) -> Result<tonic::Response<pb::SendcustommsgResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::SendcustommsgRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for send_custom_msg");
+    trace!("send_custom_msg request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::SendCustomMsg(req))
+        .await
+        .map_err(|e| rpc_status("SendCustomMsg", e))?;
+    match result {
+        Response::SendCustomMsg(r) => {
+           trace!("send_custom_msg response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call SendCustomMsg",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn send_onion_message(
+    &self,
+    request: tonic::Request<pb::SendonionmessageRequest>,
+) -> Result<tonic::Response<pb::SendonionmessageResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::SendonionmessageRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for send_onion_message");
+    trace!("send_onion_message request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::SendOnionMessage(req))
+        .await
+        .map_err(|e| rpc_status("SendOnionMessage", e))?;
+    match result {
+        Response::SendOnionMessage(r) => {
+           trace!("send_onion_message response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call SendOnionMessage",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn blinded_path(
+    &self,
+    request: tonic::Request<pb::BlindedpathRequest>,
+) -> Result<tonic::Response<pb::BlindedpathResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::BlindedpathRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for blinded_path");
+    trace!("blinded_path request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::BlindedPath(req))
+        .await
+        .map_err(|e| rpc_status("BlindedPath", e))?;
+    match result {
+        Response::BlindedPath(r) => {
+           trace!("blinded_path response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call BlindedPath",
+                r
+            )
+        )),
+    }
+
+}
+
 async fn set_channel(
     &self,
     request: tonic::Request<pb::SetchannelRequest>,
 ) -> Result<tonic::Response<pb::SetchannelResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SetchannelRequest = req.into();
+    let req: requests::SetchannelRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for set_channel");
     trace!("set_channel request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1447,9 +1674,7 @@ async fn set_channel(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SetChannel(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SetChannel: {:?}", e)))?;
+        .map_err(|e| rpc_status("SetChannel", e))?;
     match result {
         Response::SetChannel(r) => {
            trace!("set_channel response: {:?}", r);
@@ -1471,7 +1696,9 @@ async fn sign_message(
     request: tonic::Request<pb::SignmessageRequest>,
 ) -> Result<tonic::Response<pb::SignmessageResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::SignmessageRequest = req.into();
+    let req: requests::SignmessageRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for sign_message");
     trace!("sign_message request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1479,9 +1706,7 @@ async fn sign_message(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::SignMessage(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method SignMessage: {:?}", e)))?;
+        .map_err(|e| rpc_status("SignMessage", e))?;
     match result {
         Response::SignMessage(r) => {
            trace!("sign_message response: {:?}", r);
@@ -1498,12 +1723,206 @@ async fn sign_message(
 
 }
 
+async fn unreserve_inputs(
+    &self,
+    request: tonic::Request<pb::UnreserveinputsRequest>,
+) -> Result<tonic::Response<pb::UnreserveinputsResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::UnreserveinputsRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for unreserve_inputs");
+    trace!("unreserve_inputs request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::UnreserveInputs(req))
+        .await
+        .map_err(|e| rpc_status("UnreserveInputs", e))?;
+    match result {
+        Response::UnreserveInputs(r) => {
+           trace!("unreserve_inputs response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call UnreserveInputs",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn wait_block_height(
+    &self,
+    request: tonic::Request<pb::WaitblockheightRequest>,
+) -> Result<tonic::Response<pb::WaitblockheightResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::WaitblockheightRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for wait_block_height");
+    trace!("wait_block_height request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::WaitBlockHeight(req))
+        .await
+        .map_err(|e| rpc_status("WaitBlockHeight", e))?;
+    match result {
+        Response::WaitBlockHeight(r) => {
+           trace!("wait_block_height response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call WaitBlockHeight",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn commando_rune(
+    &self,
+    request: tonic::Request<pb::CommandoruneRequest>,
+) -> Result<tonic::Response<pb::CommandoruneResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::CommandoruneRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for commando_rune");
+    trace!("commando_rune request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::CommandoRune(req))
+        .await
+        .map_err(|e| rpc_status("CommandoRune", e))?;
+    match result {
+        Response::CommandoRune(r) => {
+           trace!("commando_rune response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call CommandoRune",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn static_backup(
+    &self,
+    request: tonic::Request<pb::StaticbackupRequest>,
+) -> Result<tonic::Response<pb::StaticbackupResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::StaticbackupRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for static_backup");
+    trace!("static_backup request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::StaticBackup(req))
+        .await
+        .map_err(|e| rpc_status("StaticBackup", e))?;
+    match result {
+        Response::StaticBackup(r) => {
+           trace!("static_backup response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call StaticBackup",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn emergency_recover(
+    &self,
+    request: tonic::Request<pb::EmergencyrecoverRequest>,
+) -> Result<tonic::Response<pb::EmergencyrecoverResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::EmergencyrecoverRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for emergency_recover");
+    trace!("emergency_recover request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::EmergencyRecover(req))
+        .await
+        .map_err(|e| rpc_status("EmergencyRecover", e))?;
+    match result {
+        Response::EmergencyRecover(r) => {
+           trace!("emergency_recover response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call EmergencyRecover",
+                r
+            )
+        )),
+    }
+
+}
+
+async fn recover_channel(
+    &self,
+    request: tonic::Request<pb::RecoverchannelRequest>,
+) -> Result<tonic::Response<pb::RecoverchannelResponse>, tonic::Status> {
+    let req = request.into_inner();
+    let req: requests::RecoverchannelRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
+    debug!("Client asked for recover_channel");
+    trace!("recover_channel request: {:?}", req);
+    let mut rpc = ClnRpc::new(&self.rpc_path)
+        .await
+        .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
+    let result = rpc.call(Request::RecoverChannel(req))
+        .await
+        .map_err(|e| rpc_status("RecoverChannel", e))?;
+    match result {
+        Response::RecoverChannel(r) => {
+           trace!("recover_channel response: {:?}", r);
+           Ok(tonic::Response::new(r.into()))
+        },
+        r => Err(Status::new(
+            Code::Internal,
+            format!(
+                "Unexpected result {:?} to method call RecoverChannel",
+                r
+            )
+        )),
+    }
+
+}
+
 async fn stop(
     &self,
     request: tonic::Request<pb::StopRequest>,
 ) -> Result<tonic::Response<pb::StopResponse>, tonic::Status> {
     let req = request.into_inner();
-    let req: requests::StopRequest = req.into();
+    let req: requests::StopRequest = req
+        .try_into()
+        .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
     debug!("Client asked for stop");
     trace!("stop request: {:?}", req);
     let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -1511,9 +1930,7 @@ async fn stop(
         .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
     let result = rpc.call(Request::Stop(req))
         .await
-        .map_err(|e| Status::new(
-           Code::Unknown,
-           format!("Error calling method Stop: {:?}", e)))?;
+        .map_err(|e| rpc_status("Stop", e))?;
     match result {
         Response::Stop(r) => {
            trace!("stop response: {:?}", r);
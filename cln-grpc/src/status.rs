@@ -0,0 +1,65 @@
+//! Carry JSON-RPC errors across gRPC.
+//!
+//! The error's message is the status message, its code goes in the
+//! `cln-rpc-code` metadata and its data, as JSON, in the status
+//! details, so that a [`NodeRpc`](crate::NodeRpc) client gets back
+//! the error `lightningd` returned. Messages that don't convert to
+//! the JSON-RPC types are reported as errors without a code.
+use cln_rpc::rpc::Value;
+use cln_rpc::RpcError;
+use log::debug;
+use tonic::codegen::Bytes;
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Status};
+
+pub(crate) const CODE_KEY: &str = "cln-rpc-code";
+
+/// The status for an error `method` returned.
+pub(crate) fn rpc_status(method: &str, e: RpcError) -> Status {
+    let mut metadata = MetadataMap::new();
+    let code = match e.code {
+        Some(c) => {
+            metadata.insert(CODE_KEY, c.into());
+            Code::Unknown
+        }
+        // Only errors talking to `lightningd` have no code.
+        None => Code::Internal,
+    };
+    let details = match e.data {
+        Some(d) => Bytes::from(d.to_string()),
+        None => Bytes::new(),
+    };
+    debug!("Error calling method {}: {}", method, e.message);
+    Status::with_details_and_metadata(code, e.message, details, metadata)
+}
+
+/// The error carried by `s`, the status `method` failed with.
+pub(crate) fn status_error(method: &str, s: Status) -> RpcError {
+    let code = s
+        .metadata()
+        .get(CODE_KEY)
+        .and_then(|c| c.to_str().ok())
+        .and_then(|c| c.parse().ok());
+    let message = match code {
+        Some(_) => s.message().to_string(),
+        None => format!("Error calling method {}: {}", method, s.message()),
+    };
+    let err = RpcError::new(code, message);
+    match std::str::from_utf8(s.details())
+        .ok()
+        .and_then(|d| d.parse::<Value>().ok())
+    {
+        Some(data) => err.with_data(data),
+        None => err,
+    }
+}
+
+/// The error for a `field` of a grpc message that doesn't convert.
+pub(crate) fn invalid(field: &str, e: impl std::fmt::Display) -> RpcError {
+    RpcError::new(None, format!("Invalid {}: {}", field, e))
+}
+
+/// The error for a required `field` missing from a grpc message.
+pub(crate) fn missing(field: &str) -> RpcError {
+    RpcError::new(None, format!("Missing {}", field))
+}
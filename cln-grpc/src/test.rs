@@ -1,5 +1,8 @@
 use crate::pb::*;
+use crate::status;
+use cln_rpc::RpcError;
 use serde_json::json;
+use tonic::{Code, Status};
 
 #[test]
 fn test_listpeers() {
@@ -285,7 +288,7 @@ fn test_keysend() {
             extratlvs: None,
         };
 
-    let u: cln_rpc::model::KeysendRequest = g.into();
+    let u: cln_rpc::model::KeysendRequest = g.try_into().unwrap();
     let _ser = serde_json::to_string(&u);
 
     let j = r#"{
@@ -304,3 +307,235 @@ fn test_keysend() {
     let g: KeysendResponse = u.into();
     println!("{:?}", g);
 }
+
+#[test]
+fn test_listconfigs() {
+    let j = json!({
+        "# version": "v22.11.1",
+        "plugins": [
+            {
+                "path": "/usr/libexec/c-lightning/plugins/clnrest",
+                "name": "clnrest",
+                "options": {
+                    "rest-port": 3010,
+                    "rest-host": "127.0.0.1",
+                    "rest-cors": ["a.example.com", "b.example.com"],
+                    "rest-tls": true
+                }
+            }
+        ],
+        "important-plugins": [
+            {"path": "/usr/libexec/c-lightning/plugins/pay", "name": "pay"}
+        ],
+        "disable-plugin": [],
+        "lightning-dir": "/tmp/l1/regtest",
+        "network": "regtest",
+        "fee-base": 1,
+        "fee-per-satoshi": 10,
+        "htlc-minimum-msat": "0msat",
+        "max-dust-htlc-exposure-msat": "50000000msat",
+        "rescan": -100
+    });
+    let u: cln_rpc::model::ListconfigsResponse = serde_json::from_value(j).unwrap();
+    assert_eq!(u.version.as_deref(), Some("v22.11.1"));
+    assert_eq!(u.lightning_dir.as_deref(), Some("/tmp/l1/regtest"));
+    assert_eq!(u.fee_base, Some(1));
+    assert_eq!(u.rescan, Some(-100));
+    assert_eq!(u.important_plugins.as_ref().unwrap()[0].name, "pay");
+
+    let opts = u.plugins.as_ref().unwrap()[0].options.clone().unwrap();
+    assert_eq!(opts.get_i64("rest-port"), Some(3010));
+    assert_eq!(opts.get_str("rest-host"), Some("127.0.0.1"));
+    assert_eq!(opts.get_bool("rest-tls"), Some(true));
+
+    let g: ListconfigsResponse = u.into();
+    assert_eq!(g.fee_per_satoshi, Some(10));
+    let gopts = g.plugins[0].options.clone().unwrap();
+    assert_eq!(gopts.options["rest-host"], "\"127.0.0.1\"");
+
+    let back: cln_rpc::primitives::PluginOptions = gopts.into();
+    assert_eq!(back, opts);
+}
+
+#[test]
+fn test_commando_rune() {
+    let req = CommandoruneRequest {
+        rune: Some("tU-RLjMiDpY2U0o3W1oFowar36RFGpWloPbW9-RuZdo9MyZpZD0wMjRiOWExZmE4ZTAwNmYxZTM5MzdmNjVmNjZjNDA4ZTZkYThlMWNhNzI4ZWE0MzIyMmE3MzgxZGYxY2M0NDk2MDUmbWV0aG9kPWxpc3RwZWVycyZwbnVtPTEmcG5hbWVpZF4wMjRiOWExZmE4ZTAwNmYxZTM5M3xwYXJyMF4wMjRiOWExZmE4ZTAwNmYxZTM5MyZ0aW1lPDE2NTY5MjA1MzgmcmF0ZT0y".to_string()),
+        restrictions: Some(RuneRestrictions {
+            readonly: false,
+            restrictions: vec![RuneRestriction {
+                alternatives: vec!["method^list".to_string(), "method^get".to_string()],
+            }],
+        }),
+    };
+    let u: cln_rpc::model::CommandoruneRequest = req.try_into().unwrap();
+    assert_eq!(u.rune.as_ref().unwrap().unique_id(), Some("3"));
+    match u.restrictions.as_ref().unwrap() {
+        cln_rpc::primitives::RuneRestrictions::Restrictions(r) => {
+            assert_eq!(r[0].to_string(), "method^list|method^get")
+        }
+        r => panic!("unexpected restrictions {:?}", r),
+    }
+    let j = serde_json::to_value(&u).unwrap();
+    assert_eq!(j["restrictions"], json!([["method^list", "method^get"]]));
+
+    let readonly = CommandoruneRequest {
+        rune: None,
+        restrictions: Some(RuneRestrictions {
+            readonly: true,
+            restrictions: vec![],
+        }),
+    };
+    let u: cln_rpc::model::CommandoruneRequest = readonly.try_into().unwrap();
+    assert_eq!(serde_json::to_value(&u).unwrap(), json!({"restrictions": "readonly"}));
+}
+
+#[test]
+fn test_feerate() {
+    use cln_rpc::primitives::Feerate as JFeerate;
+    use feerate::Style;
+
+    for s in [
+        "slow",
+        "normal",
+        "urgent",
+        "opening",
+        "mutual_close",
+        "unilateral_close",
+        "delayed_to_us",
+        "htlc_resolution",
+        "penalty",
+        "min_acceptable",
+        "max_acceptable",
+        "1000perkb",
+        "253perkw",
+    ] {
+        let j: JFeerate = s.parse().unwrap();
+        let g: Feerate = j.into();
+        let back: JFeerate = g.try_into().unwrap();
+        assert_eq!(back, j);
+        assert_eq!(back.to_string(), s);
+    }
+
+    let g = Feerate {
+        style: Some(Style::Perkb(1013)),
+    };
+    let j: JFeerate = g.try_into().unwrap();
+    assert_eq!(serde_json::to_value(j).unwrap(), json!("1013perkb"));
+}
+
+#[test]
+fn test_response_roundtrip() {
+    let j = json!({
+        "id": "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518",
+        "alias": "JUNIORBEAM-2-509-ged26651-modded",
+        "color": "0266e4",
+        "num_peers": 1,
+        "num_pending_channels": 0,
+        "num_active_channels": 1,
+        "num_inactive_channels": 0,
+        "address": [],
+        "binding": [{"type": "ipv4", "address": "127.0.0.1", "port": 34143}],
+        "version": "v0.10.2-509-ged26651-modded",
+        "blockheight": 103,
+        "network": "regtest",
+        "fees_collected_msat": "0msat",
+        "lightning-dir": "/tmp/ltests-20irp76f/test_pay_variants_1/lightning-1/regtest",
+        "our_features": {"init": "8808226aa2", "node": "80008808226aa2", "channel": "", "invoice": "024200"}});
+    let u: cln_rpc::model::GetinfoResponse = serde_json::from_value(j).unwrap();
+    let g: GetinfoResponse = u.clone().into();
+    let back: cln_rpc::model::GetinfoResponse = g.try_into().unwrap();
+    assert_eq!(
+        serde_json::to_value(back).unwrap(),
+        serde_json::to_value(u).unwrap()
+    );
+}
+
+#[test]
+fn test_request_roundtrip() {
+    use cln_rpc::model::requests;
+    use cln_rpc::primitives::{Amount as JAmount, AmountOrAll as JAmountOrAll, Feerate as JFeerate};
+
+    let req = requests::WithdrawRequest::builder(
+        "bcrt1qhfmyce4ujce2pyugew2435tlwft6p6w4s3py6d".to_string(),
+    )
+    .satoshi(JAmountOrAll::Amount(JAmount::from_sat(10000)))
+    .feerate(JFeerate::PerKw(253))
+    .minconf(1)
+    .build();
+    let j = serde_json::to_value(&req).unwrap();
+    let g: WithdrawRequest = req.try_into().unwrap();
+    let back: requests::WithdrawRequest = g.try_into().unwrap();
+    assert_eq!(serde_json::to_value(back).unwrap(), j);
+
+    let req = requests::CommandoruneRequest::builder()
+        .restrictions(cln_rpc::primitives::RuneRestrictions::Readonly)
+        .build();
+    let g: CommandoruneRequest = req.try_into().unwrap();
+    assert!(g.restrictions.unwrap().readonly);
+}
+
+#[test]
+fn test_malformed_request() {
+    let req = PingRequest {
+        id: vec![0x02; 3],
+        len: None,
+        pongbytes: None,
+    };
+    let r: Result<cln_rpc::model::PingRequest, RpcError> = req.try_into();
+    assert!(r.unwrap_err().message.starts_with("Invalid Ping.id: "));
+
+    let req = FundpsbtRequest {
+        satoshi: Some(AmountOrAll { value: None }),
+        feerate: None,
+        startweight: 1000,
+        ..Default::default()
+    };
+    let r: Result<cln_rpc::model::FundpsbtRequest, RpcError> = req.try_into();
+    assert_eq!(r.unwrap_err().message, "Missing AmountOrAll.value");
+}
+
+#[tokio::test]
+async fn test_malformed_call() {
+    use cln_rpc::model::requests;
+    use cln_rpc::{Request, Rpc};
+
+    // The request fails to convert before anything is sent, so the
+    // channel never connects.
+    let channel = tonic::transport::Endpoint::from_static("http://[::1]:1").connect_lazy();
+    let mut rpc = crate::NodeRpc::new(node_client::NodeClient::new(channel));
+    let peer = cln_rpc::primitives::PublicKey::from_slice(&[2; 33]).unwrap();
+    let req = requests::SendcustommsgRequest::builder(peer, "8001zz".to_string()).build();
+    let e = rpc.call(Request::SendCustomMsg(req)).await.unwrap_err();
+    assert_eq!(e.code, None);
+    assert!(e.message.starts_with("Invalid SendCustomMsg.msg: "));
+}
+
+#[test]
+fn test_status_roundtrip() {
+    let e = RpcError::new(Some(203), "Destination permanently rejected")
+        .with_data(json!({"erring_index": 1}));
+    let s = status::rpc_status("WaitSendPay", e);
+    assert_eq!(s.code(), Code::Unknown);
+    assert_eq!(s.message(), "Destination permanently rejected");
+
+    let e = status::status_error("WaitSendPay", s);
+    assert_eq!(e.code, Some(203));
+    assert_eq!(e.message, "Destination permanently rejected");
+    assert_eq!(e.data, Some(json!({"erring_index": 1})));
+}
+
+#[test]
+fn test_status_transport_error() {
+    let e = status::status_error("Getinfo", Status::unavailable("connection refused"));
+    assert_eq!(e.code, None);
+    assert_eq!(
+        e.message,
+        "Error calling method Getinfo: connection refused"
+    );
+    assert_eq!(e.data, None);
+
+    let s = status::rpc_status("Getinfo", RpcError::new(None, "broken pipe"));
+    assert_eq!(s.code(), Code::Internal);
+    assert!(s.metadata().get(status::CODE_KEY).is_none());
+}
@@ -14,7 +14,7 @@ async fn main() -> Result<(), anyhow::Error> {
 
     let mut rpc = ClnRpc::new(p).await?;
     let response = rpc
-        .call(Request::Getinfo(GetinfoRequest {}))
+        .call(Request::Getinfo(GetinfoRequest::builder().build()))
         .await
         .map_err(|e| anyhow!("Error calling getinfo: {:?}", e))?;
     println!("{}", serde_json::to_string_pretty(&response)?);
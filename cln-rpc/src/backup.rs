@@ -0,0 +1,365 @@
+//! Static channel backups (SCB).
+//!
+//! `staticbackup` returns one TLV-encoded SCB per channel, which is
+//! enough for `recoverchannel` to ask our peers to force-close the
+//! channels and return our funds. The [`ScbExporter`] periodically
+//! fetches these and hands them to a [`ScbSink`] as an [`ScbBackup`],
+//! a small self-describing blob with a format version, a sequence
+//! number and a SHA256 checksum, so that a corrupted or truncated
+//! backup is detected before we attempt to restore from it.
+use crate::model::requests::{RecoverchannelRequest, StaticbackupRequest};
+use crate::wire::Reader;
+use crate::ClnRpc;
+use anyhow::{anyhow, Context, Result};
+use bitcoin::hashes::{sha256, Hash};
+use log::{debug, warn};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Magic bytes identifying a serialized [`ScbBackup`].
+const MAGIC: &[u8; 6] = b"CLNSCB";
+
+/// The current version of the [`ScbBackup`] serialization format.
+pub const SCB_FORMAT_VERSION: u8 = 1;
+
+/// A snapshot of the static channel backups of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScbBackup {
+    /// Incremented by the [`ScbExporter`] every time the set of
+    /// channels changes, so newer backups can be told apart from
+    /// older ones.
+    pub sequence: u64,
+    /// Seconds since the UNIX epoch when the snapshot was taken.
+    pub created_at: u64,
+    /// The raw SCB of each channel, as returned by `staticbackup`.
+    pub channels: Vec<Vec<u8>>,
+}
+
+impl ScbBackup {
+    /// Build a backup from a `staticbackup` response.
+    pub fn from_scbs(sequence: u64, created_at: u64, scb: &[String]) -> Result<ScbBackup> {
+        let channels = scb
+            .iter()
+            .map(|s| hex::decode(s).with_context(|| format!("invalid SCB hex {}", s)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ScbBackup {
+            sequence,
+            created_at,
+            channels,
+        })
+    }
+
+    /// Serialize the backup, appending a checksum over the contents.
+    ///
+    /// The format is the magic `CLNSCB`, the format version byte, the
+    /// big-endian `sequence`, `created_at` and channel count, each
+    /// channel prefixed by its big-endian `u32` length, and finally
+    /// the SHA256 of everything before it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.push(SCB_FORMAT_VERSION);
+        buf.extend_from_slice(&self.sequence.to_be_bytes());
+        buf.extend_from_slice(&self.created_at.to_be_bytes());
+        buf.extend_from_slice(&(self.channels.len() as u32).to_be_bytes());
+        for c in &self.channels {
+            buf.extend_from_slice(&(c.len() as u32).to_be_bytes());
+            buf.extend_from_slice(c);
+        }
+        let checksum = sha256::Hash::hash(&buf);
+        buf.extend_from_slice(&checksum[..]);
+        buf
+    }
+
+    /// Parse a serialized backup, verifying its version and checksum.
+    pub fn from_bytes(buf: &[u8]) -> Result<ScbBackup> {
+        if buf.len() < MAGIC.len() + 1 + 20 + 32 || &buf[..MAGIC.len()] != MAGIC {
+            return Err(anyhow!("not a static channel backup"));
+        }
+        let (body, checksum) = buf.split_at(buf.len() - 32);
+        if sha256::Hash::hash(body)[..] != *checksum {
+            return Err(anyhow!("static channel backup checksum mismatch"));
+        }
+
+        let version = body[MAGIC.len()];
+        if version != SCB_FORMAT_VERSION {
+            return Err(anyhow!(
+                "unsupported static channel backup version {}",
+                version
+            ));
+        }
+
+        let mut r = Reader::new(&body[MAGIC.len() + 1..]);
+        let truncated = "truncated static channel backup";
+        let sequence = r.read().context(truncated)?;
+        let created_at = r.read().context(truncated)?;
+        let count: u32 = r.read().context(truncated)?;
+        let mut channels = Vec::new();
+        for _ in 0..count {
+            let len: u32 = r.read().context(truncated)?;
+            channels.push(r.take(len as usize).context(truncated)?.to_vec());
+        }
+        if !r.is_empty() {
+            return Err(anyhow!("trailing data in static channel backup"));
+        }
+
+        Ok(ScbBackup {
+            sequence,
+            created_at,
+            channels,
+        })
+    }
+
+    /// The SCBs hex-encoded, as expected by `recoverchannel`.
+    pub fn to_scbs(&self) -> Vec<String> {
+        self.channels.iter().map(hex::encode).collect()
+    }
+}
+
+/// Where the [`ScbExporter`] stores backups.
+pub trait ScbSink: Send {
+    fn store(&mut self, backup: &ScbBackup) -> Result<()>;
+}
+
+impl<F> ScbSink for F
+where
+    F: FnMut(&ScbBackup) -> Result<()> + Send,
+{
+    fn store(&mut self, backup: &ScbBackup) -> Result<()> {
+        self(backup)
+    }
+}
+
+/// An [`ScbSink`] writing the serialized backup to a file. The file
+/// is replaced atomically, so a crash during the write never leaves a
+/// partial backup behind.
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new<P: AsRef<Path>>(path: P) -> FileSink {
+        FileSink {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ScbSink for FileSink {
+    fn store(&mut self, backup: &ScbBackup) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, backup.to_bytes())
+            .with_context(|| format!("writing {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("renaming {} to {}", tmp.display(), self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// Periodically exports the node's static channel backups.
+pub struct ScbExporter {
+    rpc_path: PathBuf,
+    interval: Duration,
+    sequence: u64,
+    last: Option<Vec<Vec<u8>>>,
+}
+
+impl ScbExporter {
+    pub fn new<P: AsRef<Path>>(rpc_path: P) -> ScbExporter {
+        ScbExporter {
+            rpc_path: rpc_path.as_ref().to_path_buf(),
+            interval: Duration::from_secs(60),
+            sequence: 0,
+            last: None,
+        }
+    }
+
+    /// How often [`ScbExporter::run`] polls for changes. Defaults to
+    /// one minute.
+    pub fn interval(mut self, interval: Duration) -> ScbExporter {
+        self.interval = interval;
+        self
+    }
+
+    /// Continue numbering backups after `sequence`, e.g., the
+    /// sequence of the last backup found in the sink.
+    pub fn start_sequence(mut self, sequence: u64) -> ScbExporter {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Fetch the current backups and store them in `sink` if the set
+    /// of channels changed since the last export. Returns the stored
+    /// backup, if any.
+    pub async fn export_once<K: ScbSink>(&mut self, sink: &mut K) -> Result<Option<ScbBackup>> {
+        let backup = self.fetch().await?;
+        self.store(sink, backup)
+    }
+
+    /// Export backups every `interval` until an error occurs. Failing
+    /// to reach `lightningd` is logged and retried, while errors from
+    /// the sink are returned since they mean backups are being lost.
+    pub async fn run<K: ScbSink>(mut self, mut sink: K) -> Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            match self.fetch().await {
+                Ok(backup) => {
+                    self.store(&mut sink, backup)?;
+                }
+                Err(e) => warn!("Failed to fetch static channel backup: {:#}", e),
+            }
+        }
+    }
+
+    async fn fetch(&self) -> Result<ScbBackup> {
+        let mut rpc = ClnRpc::new(&self.rpc_path).await?;
+        let res = rpc
+            .call_typed(StaticbackupRequest {})
+            .await
+            .context("calling staticbackup")?;
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        ScbBackup::from_scbs(self.sequence + 1, created_at, &res.scb)
+    }
+
+    fn store<K: ScbSink>(&mut self, sink: &mut K, backup: ScbBackup) -> Result<Option<ScbBackup>> {
+        if self.last.as_ref() == Some(&backup.channels) {
+            debug!("Static channel backup unchanged, skipping export");
+            return Ok(None);
+        }
+
+        sink.store(&backup)
+            .context("storing static channel backup")?;
+        debug!(
+            "Exported static channel backup #{} with {} channels",
+            backup.sequence,
+            backup.channels.len()
+        );
+        self.sequence = backup.sequence;
+        self.last = Some(backup.channels.clone());
+        Ok(Some(backup))
+    }
+}
+
+/// Verify a serialized backup and ask `lightningd` to recover the
+/// channels in it using `recoverchannel`. Returns the channel IDs of
+/// the stubs that were inserted.
+pub async fn restore<P: AsRef<Path>>(rpc_path: P, blob: &[u8]) -> Result<Vec<String>> {
+    let backup = ScbBackup::from_bytes(blob)?;
+    let mut rpc = ClnRpc::new(rpc_path).await?;
+    let res = rpc
+        .call_typed(RecoverchannelRequest {
+            scb: backup.to_scbs(),
+        })
+        .await
+        .context("calling recoverchannel")?;
+    Ok(res.stubs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::JsonCodec;
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+    use tokio_util::codec::Framed;
+
+    const SCB: &str = "0000000000000001c707da9b230e1655b0a6c082b8daf4fa44d9d1f68163ed4d531d45cf453dc651035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d00017f00000126070000000000000000000f4240";
+
+    #[test]
+    fn test_roundtrip() {
+        let backup = ScbBackup::from_scbs(7, 1675000000, &[SCB.to_string()]).unwrap();
+        let blob = backup.to_bytes();
+        assert_eq!(ScbBackup::from_bytes(&blob).unwrap(), backup);
+        assert_eq!(backup.to_scbs(), vec![SCB.to_string()]);
+
+        // Any corruption is caught by the checksum.
+        let mut corrupt = blob.clone();
+        corrupt[20] ^= 0x01;
+        assert!(ScbBackup::from_bytes(&corrupt).is_err());
+        assert!(ScbBackup::from_bytes(&blob[..blob.len() - 1]).is_err());
+
+        // Future versions are rejected even with a valid checksum.
+        let mut future = blob[..blob.len() - 32].to_vec();
+        future[MAGIC.len()] = SCB_FORMAT_VERSION + 1;
+        let checksum = sha256::Hash::hash(&future);
+        future.extend_from_slice(&checksum[..]);
+        assert!(ScbBackup::from_bytes(&future)
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported"));
+    }
+
+    /// A node whose `staticbackup` returns whatever we feed it, and
+    /// which reports `recoverchannel` requests back to the test.
+    async fn mock_node(
+        listener: UnixListener,
+        mut scbs: mpsc::UnboundedReceiver<Vec<String>>,
+        recovered: mpsc::UnboundedSender<Value>,
+    ) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut framed = Framed::new(stream, JsonCodec::default());
+            let req = framed.next().await.unwrap().unwrap();
+            let result = match req["method"].as_str().unwrap() {
+                "staticbackup" => json!({"scb": scbs.recv().await.unwrap()}),
+                "recoverchannel" => {
+                    recovered.send(req["params"].clone()).unwrap();
+                    json!({"stubs": ["c707da9b230e1655b0a6c082b8daf4fa44d9d1f68163ed4d531d45cf453dc651"]})
+                }
+                m => panic!("unexpected method {}", m),
+            };
+            framed
+                .send(json!({"jsonrpc": "2.0", "id": req["id"], "result": result}))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_restore() {
+        let path = std::env::temp_dir().join(format!("cln-rpc-backup-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let (scb_tx, scb_rx) = mpsc::unbounded_channel();
+        let (rec_tx, mut rec_rx) = mpsc::unbounded_channel();
+        tokio::spawn(mock_node(listener, scb_rx, rec_tx));
+
+        let mut stored: Vec<Vec<u8>> = vec![];
+        let mut sink = |b: &ScbBackup| -> Result<()> {
+            stored.push(b.to_bytes());
+            Ok(())
+        };
+        let mut exporter = ScbExporter::new(&path).start_sequence(41);
+
+        scb_tx.send(vec![SCB.to_string()]).unwrap();
+        let b = exporter.export_once(&mut sink).await.unwrap().unwrap();
+        assert_eq!(b.sequence, 42);
+
+        // Unchanged backups are not exported again.
+        scb_tx.send(vec![SCB.to_string()]).unwrap();
+        assert!(exporter.export_once(&mut sink).await.unwrap().is_none());
+
+        scb_tx.send(vec![]).unwrap();
+        let b = exporter.export_once(&mut sink).await.unwrap().unwrap();
+        assert_eq!(b.sequence, 43);
+        assert_eq!(stored.len(), 2);
+
+        let stubs = restore(&path, &stored[0]).await.unwrap();
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(rec_rx.recv().await.unwrap(), json!({"scb": [SCB]}));
+
+        let mut corrupt = stored[0].clone();
+        corrupt[30] ^= 0xff;
+        assert!(restore(&path, &corrupt).await.is_err());
+        assert!(rec_rx.try_recv().is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,739 @@
+//! Local BOLT11 invoice decoding and encoding.
+//!
+//! Parsing an invoice with [`str::parse`] checks the bech32 checksum,
+//! decodes the tagged fields and verifies the signature, recovering
+//! the payee if the invoice doesn't include it. The result is the
+//! same [`Bolt11Invoice`] that `decodepay` returns, so callers can
+//! use either interchangeably.
+//!
+//! [`Bolt11Builder`] creates signed invoices, which is mostly useful
+//! for tests.
+use crate::decode::Bolt11Invoice;
+use crate::model::responses::{DecodepayFallbacks, DecodepayFallbacksType};
+use crate::primitives::{Amount, Features, PublicKey, Routehint, Routehop, Secret, Sha256};
+use anyhow::{anyhow, Context, Error, Result};
+use bech32::{u5, FromBase32, ToBase32, Variant};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use bitcoin::util::address::{Address, WitnessVersion};
+use bitcoin::{Network, PubkeyHash, Script, ScriptHash};
+use std::str::FromStr;
+
+/// Expiry in seconds if the invoice has no `x` field.
+pub const DEFAULT_EXPIRY: u64 = 3600;
+/// `min_final_cltv_expiry` if the invoice has no `c` field.
+pub const DEFAULT_MIN_FINAL_CLTV_EXPIRY: u32 = 18;
+
+const TAG_PAYMENT_HASH: u8 = 1;
+const TAG_ROUTE: u8 = 3;
+const TAG_FEATURES: u8 = 5;
+const TAG_EXPIRY: u8 = 6;
+const TAG_FALLBACK: u8 = 9;
+const TAG_DESCRIPTION: u8 = 13;
+const TAG_PAYMENT_SECRET: u8 = 16;
+const TAG_PAYEE: u8 = 19;
+const TAG_DESCRIPTION_HASH: u8 = 23;
+const TAG_MIN_FINAL_CLTV_EXPIRY: u8 = 24;
+const TAG_METADATA: u8 = 27;
+
+/// Each route hint hop is a pubkey, scid, fee base, fee
+/// proportional and cltv delta.
+const ROUTEHOP_LEN: usize = 33 + 8 + 4 + 4 + 2;
+
+fn network(currency: &str) -> Option<Network> {
+    match currency {
+        "bc" => Some(Network::Bitcoin),
+        "tb" => Some(Network::Testnet),
+        "tbs" => Some(Network::Signet),
+        "bcrt" => Some(Network::Regtest),
+        _ => None,
+    }
+}
+
+/// Parse the human readable part after the `ln` prefix into the
+/// currency and optional amount.
+fn parse_hrp(hrp: &str) -> Result<(String, Option<Amount>)> {
+    let rest = hrp
+        .strip_prefix("ln")
+        .ok_or_else(|| anyhow!("Invoices must start with ln"))?;
+    let split = rest
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (currency, amount) = rest.split_at(split);
+    if amount.is_empty() {
+        return Ok((currency.to_string(), None));
+    }
+
+    let (digits, multiplier) = match amount.char_indices().last() {
+        Some((i, c)) if !c.is_ascii_digit() => (&amount[..i], Some(c)),
+        _ => (amount, None),
+    };
+    if !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Invalid amount '{}'", amount));
+    }
+    let n: u64 = digits
+        .parse()
+        .with_context(|| format!("Invalid amount '{}'", amount))?;
+    // Amounts are in BTC, with pico-BTC being a tenth of a msat.
+    let msat = match multiplier {
+        None => n.checked_mul(100_000_000_000),
+        Some('m') => n.checked_mul(100_000_000),
+        Some('u') => n.checked_mul(100_000),
+        Some('n') => n.checked_mul(100),
+        Some('p') => {
+            if n / 10 * 10 != n {
+                return Err(anyhow!("Invalid sub-millisatoshi amount '{}'", amount));
+            }
+            Some(n / 10)
+        }
+        Some(c) => return Err(anyhow!("Invalid amount postfix '{}'", c)),
+    }
+    .ok_or_else(|| anyhow!("Invalid amount '{}'", amount))?;
+    Ok((currency.to_string(), Some(Amount::from_msat(msat))))
+}
+
+/// Encode the amount using the largest multiplier that keeps it
+/// exact.
+fn encode_amount(amount: Amount) -> String {
+    let msat = amount.msat();
+    for (div, m) in [
+        (100_000_000_000, ""),
+        (100_000_000, "m"),
+        (100_000, "u"),
+        (100, "n"),
+    ] {
+        if msat / div * div == msat {
+            return format!("{}{}", msat / div, m);
+        }
+    }
+    format!("{}p", msat as u128 * 10)
+}
+
+fn be_int(data: &[u5]) -> Result<u64> {
+    if data.len() > 12 {
+        return Err(anyhow!("integer too large"));
+    }
+    Ok(data.iter().fold(0, |acc, d| (acc << 5) | d.to_u8() as u64))
+}
+
+fn int_to_u5(mut v: u64) -> Vec<u5> {
+    let mut out = vec![];
+    while v > 0 {
+        out.push(u5::try_from_u8((v & 31) as u8).unwrap());
+        v >>= 5;
+    }
+    out.reverse();
+    out
+}
+
+fn features_from_u5(data: &[u5]) -> Features {
+    let mut f = Features::default();
+    for (i, d) in data.iter().rev().enumerate() {
+        for b in 0..5 {
+            if d.to_u8() & (1 << b) != 0 {
+                f.set(i * 5 + b);
+            }
+        }
+    }
+    f
+}
+
+fn features_to_u5(f: &Features) -> Vec<u5> {
+    let bits = f.bits();
+    let len = bits.last().map(|b| b / 5 + 1).unwrap_or(0);
+    let mut out = vec![0u8; len];
+    for b in bits {
+        out[len - 1 - b / 5] |= 1 << (b % 5);
+    }
+    out.into_iter()
+        .map(|d| u5::try_from_u8(d).unwrap())
+        .collect()
+}
+
+fn parse_routehint(data: &[u8]) -> Result<Routehint> {
+    if data.is_empty() || data.len() / ROUTEHOP_LEN * ROUTEHOP_LEN != data.len() {
+        return Err(anyhow!("invalid length {}", data.len()));
+    }
+    let hops =
+        data.chunks(ROUTEHOP_LEN)
+            .map(|h| {
+                Ok(Routehop {
+                    id: PublicKey::from_slice(&h[0..33])?,
+                    scid: u64::from_be_bytes(h[33..41].try_into().unwrap()).into(),
+                    feebase: Amount::from_msat(
+                        u32::from_be_bytes(h[41..45].try_into().unwrap()) as u64
+                    ),
+                    feeprop: u32::from_be_bytes(h[45..49].try_into().unwrap()),
+                    expirydelta: u16::from_be_bytes(h[49..51].try_into().unwrap()),
+                })
+            })
+            .collect::<Result<_>>()?;
+    Ok(Routehint { hops })
+}
+
+fn encode_routehint(r: &Routehint) -> Result<Vec<u8>> {
+    let mut out = vec![];
+    for h in &r.hops {
+        let feebase: u32 = h
+            .feebase
+            .msat()
+            .try_into()
+            .context("route hint fee base too large")?;
+        out.extend_from_slice(&h.id.serialize());
+        out.extend_from_slice(&h.scid.to_u64().to_be_bytes());
+        out.extend_from_slice(&feebase.to_be_bytes());
+        out.extend_from_slice(&h.feeprop.to_be_bytes());
+        out.extend_from_slice(&h.expirydelta.to_be_bytes());
+    }
+    Ok(out)
+}
+
+/// Turn a fallback field into the script it pays to. Returns `None`
+/// for versions we don't know how to represent, which readers must
+/// skip.
+fn parse_fallback(data: &[u5], network: Option<Network>) -> Option<DecodepayFallbacks> {
+    let (version, program) = data.split_first()?;
+    let program = Vec::<u8>::from_base32(program).ok()?;
+    let (item_type, script) = match (version.to_u8(), program.len()) {
+        (17, 20) => (
+            DecodepayFallbacksType::P2PKH,
+            Script::new_p2pkh(&PubkeyHash::from_slice(&program).ok()?),
+        ),
+        (18, 20) => (
+            DecodepayFallbacksType::P2SH,
+            Script::new_p2sh(&ScriptHash::from_slice(&program).ok()?),
+        ),
+        (0, 20) => (
+            DecodepayFallbacksType::P2WPKH,
+            Script::new_witness_program(WitnessVersion::V0, &program),
+        ),
+        (0, 32) => (
+            DecodepayFallbacksType::P2WSH,
+            Script::new_witness_program(WitnessVersion::V0, &program),
+        ),
+        _ => return None,
+    };
+    Some(DecodepayFallbacks {
+        item_type,
+        addr: network
+            .and_then(|n| Address::from_script(&script, n).ok())
+            .map(|a| a.to_string()),
+        hex: hex::encode(script.as_bytes()),
+    })
+}
+
+fn encode_fallback(f: &DecodepayFallbacks) -> Result<Vec<u5>> {
+    let script = Script::from(hex::decode(&f.hex).context("fallback script is not hex")?);
+    let b = script.as_bytes();
+    let (version, program) = if script.is_p2pkh() {
+        (17, &b[3..23])
+    } else if script.is_p2sh() {
+        (18, &b[2..22])
+    } else if script.is_v0_p2wpkh() || script.is_v0_p2wsh() {
+        (0, &b[2..])
+    } else {
+        return Err(anyhow!("Unsupported fallback script {}", f.hex));
+    };
+    let mut out = vec![u5::try_from_u8(version).unwrap()];
+    out.extend(program.to_base32());
+    Ok(out)
+}
+
+/// The message the signature commits to: the human readable part
+/// followed by the data part, padded to a byte boundary.
+fn signing_message(hrp: &str, data: &[u5]) -> Message {
+    let mut preimage = hrp.as_bytes().to_vec();
+    preimage.extend(bech32::convert_bits(data, 5, 8, true).unwrap());
+    let hash = sha256::Hash::hash(&preimage);
+    Message::from_slice(&hash[..]).unwrap()
+}
+
+impl FromStr for Bolt11Invoice {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("lightning:").unwrap_or(s);
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| anyhow!("Bad bech32 string"))?;
+        if variant != Variant::Bech32 {
+            return Err(anyhow!("Bad bech32 string"));
+        }
+        let (currency, amount_msat) = parse_hrp(&hrp)?;
+
+        // 35 bits of timestamp and 520 bits of signature.
+        if data.len() < 7 + 104 {
+            return Err(anyhow!("Too short to contain signature"));
+        }
+        let (data, sigdata) = data.split_at(data.len() - 104);
+        let created_at = be_int(&data[..7])?;
+
+        let mut payment_hash = None;
+        let mut payee = None;
+        let mut description = None;
+        let mut description_hash = None;
+        let mut payment_secret = None;
+        let mut payment_metadata = None;
+        let mut expiry = None;
+        let mut min_final_cltv_expiry = None;
+        let mut features = Features::default();
+        let mut fallbacks = vec![];
+        let mut routes = vec![];
+
+        let mut fields = &data[7..];
+        while !fields.is_empty() {
+            if fields.len() < 3 {
+                return Err(anyhow!("Truncated tagged field"));
+            }
+            let tag = fields[0].to_u8();
+            let len = be_int(&fields[1..3])? as usize;
+            if fields.len() < 3 + len {
+                return Err(anyhow!("{}: truncated", tag));
+            }
+            let field = &fields[3..3 + len];
+            fields = &fields[3 + len..];
+
+            let bytes = || Vec::<u8>::from_base32(field);
+            // Fixed-length fields with the wrong length, and
+            // duplicates, must be skipped.
+            match tag {
+                TAG_PAYMENT_HASH if len == 52 && payment_hash.is_none() => {
+                    payment_hash = Some(Sha256::from_slice(&bytes()?)?);
+                }
+                TAG_DESCRIPTION_HASH if len == 52 && description_hash.is_none() => {
+                    description_hash = Some(Sha256::from_slice(&bytes()?)?);
+                }
+                TAG_PAYMENT_SECRET if len == 52 && payment_secret.is_none() => {
+                    payment_secret = Some(Secret::try_from(bytes()?)?);
+                }
+                TAG_PAYEE if len == 53 && payee.is_none() => {
+                    payee = Some(PublicKey::from_slice(&bytes()?).context("n: invalid pubkey")?);
+                }
+                TAG_DESCRIPTION if description.is_none() => {
+                    let d = bytes().ok().and_then(|b| String::from_utf8(b).ok());
+                    description = Some(d.ok_or_else(|| anyhow!("d: invalid utf8"))?);
+                }
+                TAG_METADATA if payment_metadata.is_none() => {
+                    payment_metadata = Some(bytes().context("m: invalid padding")?);
+                }
+                TAG_EXPIRY if expiry.is_none() => expiry = Some(be_int(field)?),
+                TAG_MIN_FINAL_CLTV_EXPIRY if min_final_cltv_expiry.is_none() => {
+                    min_final_cltv_expiry =
+                        Some(u32::try_from(be_int(field)?).context("c: too large")?);
+                }
+                TAG_FEATURES => features = features_from_u5(field),
+                TAG_FALLBACK => fallbacks.extend(parse_fallback(field, network(&currency))),
+                TAG_ROUTE => {
+                    routes.push(parse_routehint(&bytes()?).context("r: invalid route hint")?)
+                }
+                _ => {}
+            }
+        }
+        let payment_hash = payment_hash.ok_or_else(|| anyhow!("No valid 'p' field found"))?;
+
+        let sigbytes =
+            Vec::<u8>::from_base32(sigdata).map_err(|_| anyhow!("signature recovery failed"))?;
+        let recid = RecoveryId::from_i32(sigbytes[64] as i32)
+            .map_err(|_| anyhow!("signature recovery failed"))?;
+        let sig = RecoverableSignature::from_compact(&sigbytes[..64], recid)
+            .map_err(|_| anyhow!("signature recovery failed"))?;
+        let msg = signing_message(&hrp, data);
+        let secp = Secp256k1::verification_only();
+        let payee = match payee {
+            Some(p) => {
+                secp.verify_ecdsa(&msg, &sig.to_standard(), &p)
+                    .map_err(|_| anyhow!("invalid signature"))?;
+                p
+            }
+            None => secp
+                .recover_ecdsa(&msg, &sig)
+                .map_err(|_| anyhow!("signature recovery failed"))?,
+        };
+
+        Ok(Bolt11Invoice {
+            currency,
+            created_at,
+            expiry: expiry.unwrap_or(DEFAULT_EXPIRY),
+            payee,
+            amount_msat,
+            payment_hash,
+            signature: hex::encode(sig.to_standard().serialize_der()),
+            description,
+            description_hash,
+            min_final_cltv_expiry: min_final_cltv_expiry.unwrap_or(DEFAULT_MIN_FINAL_CLTV_EXPIRY),
+            payment_secret,
+            features,
+            payment_metadata,
+            fallbacks,
+            routes,
+        })
+    }
+}
+
+impl Bolt11Invoice {
+    /// When the invoice expires, in seconds since the UNIX epoch.
+    pub fn expires_at(&self) -> u64 {
+        self.created_at.saturating_add(self.expiry)
+    }
+}
+
+/// Creates and signs BOLT11 invoices.
+#[derive(Clone, Debug)]
+pub struct Bolt11Builder {
+    currency: String,
+    amount_msat: Option<Amount>,
+    created_at: u64,
+    payment_hash: Sha256,
+    payment_secret: Option<Secret>,
+    description: Option<String>,
+    description_hash: Option<Sha256>,
+    include_payee: bool,
+    expiry: Option<u64>,
+    min_final_cltv_expiry: Option<u32>,
+    fallbacks: Vec<DecodepayFallbacks>,
+    routes: Vec<Routehint>,
+    payment_metadata: Option<Vec<u8>>,
+    features: Features,
+}
+
+impl Bolt11Builder {
+    /// Start an invoice for `currency`, e.g., `bc` or `bcrt`.
+    pub fn new(currency: &str, payment_hash: Sha256, created_at: u64) -> Bolt11Builder {
+        Bolt11Builder {
+            currency: currency.to_string(),
+            amount_msat: None,
+            created_at,
+            payment_hash,
+            payment_secret: None,
+            description: None,
+            description_hash: None,
+            include_payee: false,
+            expiry: None,
+            min_final_cltv_expiry: None,
+            fallbacks: vec![],
+            routes: vec![],
+            payment_metadata: None,
+            features: Features::default(),
+        }
+    }
+
+    pub fn amount(mut self, amount: Amount) -> Self {
+        self.amount_msat = Some(amount);
+        self
+    }
+
+    pub fn payment_secret(mut self, secret: Secret) -> Self {
+        self.payment_secret = Some(secret);
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn description_hash(mut self, hash: Sha256) -> Self {
+        self.description_hash = Some(hash);
+        self
+    }
+
+    /// Include the payee explicitly, rather than relying on
+    /// signature recovery.
+    pub fn include_payee(mut self) -> Self {
+        self.include_payee = true;
+        self
+    }
+
+    pub fn expiry(mut self, expiry: u64) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+
+    pub fn min_final_cltv_expiry(mut self, delta: u32) -> Self {
+        self.min_final_cltv_expiry = Some(delta);
+        self
+    }
+
+    pub fn fallback(mut self, fallback: DecodepayFallbacks) -> Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
+    pub fn route(mut self, route: Routehint) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn payment_metadata(mut self, metadata: Vec<u8>) -> Self {
+        self.payment_metadata = Some(metadata);
+        self
+    }
+
+    pub fn features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Encode the invoice and sign it with `key`.
+    pub fn sign(self, key: &SecretKey) -> Result<String> {
+        let secp = Secp256k1::signing_only();
+        let mut hrp = format!("ln{}", self.currency);
+        if let Some(a) = self.amount_msat {
+            hrp.push_str(&encode_amount(a));
+        }
+
+        if self.created_at >= 1 << 35 {
+            return Err(anyhow!("Timestamp {} too large", self.created_at));
+        }
+        let mut data = int_to_u5(self.created_at);
+        while data.len() < 7 {
+            data.insert(0, u5::try_from_u8(0).unwrap());
+        }
+        let mut push = |tag: u8, field: Vec<u5>| -> Result<()> {
+            if field.len() >= 1024 {
+                return Err(anyhow!("Field {} too long", tag));
+            }
+            data.push(u5::try_from_u8(tag).unwrap());
+            data.push(u5::try_from_u8((field.len() >> 5) as u8).unwrap());
+            data.push(u5::try_from_u8((field.len() & 31) as u8).unwrap());
+            data.extend(field);
+            Ok(())
+        };
+
+        if let Some(s) = self.payment_secret {
+            push(TAG_PAYMENT_SECRET, s.to_vec().to_base32())?;
+        }
+        push(TAG_PAYMENT_HASH, self.payment_hash.to_base32())?;
+        if let Some(d) = &self.description {
+            push(TAG_DESCRIPTION, d.as_bytes().to_base32())?;
+        }
+        if let Some(h) = self.description_hash {
+            push(TAG_DESCRIPTION_HASH, h.to_base32())?;
+        }
+        if self.include_payee {
+            let payee = PublicKey::from_secret_key(&secp, key);
+            push(TAG_PAYEE, payee.serialize().to_base32())?;
+        }
+        if let Some(x) = self.expiry {
+            push(TAG_EXPIRY, int_to_u5(x))?;
+        }
+        if let Some(c) = self.min_final_cltv_expiry {
+            push(TAG_MIN_FINAL_CLTV_EXPIRY, int_to_u5(c as u64))?;
+        }
+        for f in &self.fallbacks {
+            push(TAG_FALLBACK, encode_fallback(f)?)?;
+        }
+        for r in &self.routes {
+            push(TAG_ROUTE, encode_routehint(r)?.to_base32())?;
+        }
+        if let Some(m) = &self.payment_metadata {
+            push(TAG_METADATA, m.to_base32())?;
+        }
+        if !self.features.bits().is_empty() {
+            push(TAG_FEATURES, features_to_u5(&self.features))?;
+        }
+
+        let sig = secp.sign_ecdsa_recoverable(&signing_message(&hrp, &data), key);
+        let (recid, compact) = sig.serialize_compact();
+        let mut sigbytes = compact.to_vec();
+        sigbytes.push(recid.to_i32() as u8);
+        data.extend(sigbytes.to_base32());
+
+        Ok(bech32::encode(&hrp, data, Variant::Bech32)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key() -> SecretKey {
+        SecretKey::from_slice(
+            &hex::decode("e126f68f7eafcc8b74f54d269fe206be715000f94dac067d1c04a8ca3b2db734")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn payment_hash() -> Sha256 {
+        Sha256::from_slice(
+            &hex::decode("0001020304050607080900010203040506070809000102030405060708090102")
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn features(bits: &[usize]) -> Features {
+        let mut f = Features::default();
+        bits.iter().for_each(|b| f.set(*b));
+        f
+    }
+
+    const PAYEE: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+
+    /// The examples from the BOLT11 spec, which our encoder must
+    /// reproduce exactly.
+    #[test]
+    fn test_spec_vectors() {
+        let b = || {
+            Bolt11Builder::new("bc", payment_hash(), 1496314658)
+                .payment_secret(Secret::try_from(vec![0x11; 32]).unwrap())
+                .features(features(&[8, 14]))
+        };
+
+        let s = "lnbc1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq9qrsgq357wnc5r2ueh7ck6q93dj32dlqnls087fxdwk8qakdyafkq3yap9us6v52vjjsrvywa6rt52cm9r9zqt8r2t7mlcwspyetp5h2tztugp9lfyql";
+        let built = b()
+            .description("Please consider supporting this project")
+            .sign(&key())
+            .unwrap();
+        assert_eq!(built, s);
+        let inv: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv.currency, "bc");
+        assert_eq!(inv.created_at, 1496314658);
+        assert_eq!(inv.amount_msat, None);
+        assert_eq!(inv.payee.to_string(), PAYEE);
+        assert_eq!(inv.payment_hash, payment_hash());
+        assert_eq!(
+            inv.description.as_deref(),
+            Some("Please consider supporting this project")
+        );
+        assert_eq!(inv.expiry, DEFAULT_EXPIRY);
+        assert_eq!(inv.min_final_cltv_expiry, DEFAULT_MIN_FINAL_CLTV_EXPIRY);
+        assert_eq!(inv.features.bits(), vec![8, 14]);
+        assert_eq!(inv.payment_secret.unwrap().to_vec(), vec![0x11; 32]);
+
+        let s = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+        let built = b()
+            .amount(Amount::from_msat(250_000_000))
+            .description("1 cup coffee")
+            .expiry(60)
+            .sign(&key())
+            .unwrap();
+        assert_eq!(built, s);
+        let inv: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv.amount_msat, Some(Amount::from_msat(250_000_000)));
+        assert_eq!(inv.expiry, 60);
+        assert_eq!(inv.expires_at(), 1496314718);
+
+        let s = "lnbc20m1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqhp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqs9qrsgq7ea976txfraylvgzuxs8kgcw23ezlrszfnh8r6qtfpr6cxga50aj6txm9rxrydzd06dfeawfk6swupvz4erwnyutnjq7x39ymw6j38gp7ynn44";
+        let hash = Sha256::hash(b"One piece of chocolate cake, one icecream cone, one pickle, one slice of swiss cheese, one slice of salami, one lollypop, one piece of cherry pie, one sausage, one cupcake, and one slice of watermelon");
+        let built = b()
+            .amount(Amount::from_msat(2_000_000_000))
+            .description_hash(hash)
+            .sign(&key())
+            .unwrap();
+        assert_eq!(built, s);
+        let inv: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv.description_hash, Some(hash));
+        assert_eq!(inv.description, None);
+
+        // Uppercase is fine too.
+        let inv: Bolt11Invoice = "LNBC2500U1PVJLUEZPP5QQQSYQCYQ5RQWZQFQQQSYQCYQ5RQWZQFQQQSYQCYQ5RQWZQFQYPQDQ5XYSXXATSYP3K7ENXV4JSXQZPUAZTRNWNGZN3KDZW5HYDLZF03QDGM2HDQ27CQV3AGM2AWHZ5SE903VRUATFHQ77W3LS4EVS3CH9ZW97J25EMUDUPQ63NYW24CG27H2RSPFJ9SRP".parse().unwrap();
+        assert_eq!(inv.payee.to_string(), PAYEE);
+        assert_eq!(inv.description.as_deref(), Some("1 cup coffee"));
+
+        // Metadata and a feature beyond the first byte.
+        let s = "lnbc10m1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdp9wpshjmt9de6zqmt9w3skgct5vysxjmnnd9jx2mq8q8a04uqsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9q2gqqqqqqsgq7hf8he7ecf7n4ffphs6awl9t6676rrclv9ckg3d3ncn7fct63p6s365duk5wrk202cfy3aj5xnnp5gs3vrdvruverwwq7yzhkf5a3xqpd05wjc";
+        let inv: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv.payment_metadata, Some(vec![0x01, 0xfa, 0xfa, 0xf0]));
+        assert_eq!(inv.features.bits(), vec![8, 14, 48]);
+        assert_eq!(inv.payee.to_string(), PAYEE);
+    }
+
+    #[test]
+    fn test_routes_and_fallbacks() {
+        let s = "lnbc9678785340p1pwmna7lpp5gc3xfm08u9qy06djf8dfflhugl6p7lgza6dsjxq454gxhj9t7a0sd8dgfkx7cmtwd68yetpd5s9xar0wfjn5gpc8qhrsdfq24f5ggrxdaezqsnvda3kkum5wfjkzmfqf3jkgem9wgsyuctwdus9xgrcyqcjcgpzgfskx6eqf9hzqnteypzxz7fzypfhg6trddjhygrcyqezcgpzfysywmm5ypxxjemgw3hxjmn8yptk7untd9hxwg3q2d6xjcmtv4ezq7pqxgsxzmnyyqcjqmt0wfjjq6t5v4khxsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygsxqyjw5qcqp2rzjq0gxwkzc8w6323m55m4jyxcjwmy7stt9hwkwe2qxmy8zpsgg7jcuwz87fcqqeuqqqyqqqqlgqqqqn3qq9q9qrsgqrvgkpnmps664wgkp43l22qsgdw4ve24aca4nymnxddlnp8vh9v2sdxlu5ywdxefsfvm0fq3sesf08uf6q9a2ke0hc9j6z6wlxg5z5kqpu2v9wz";
+        let inv: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv.amount_msat, Some(Amount::from_msat(967878534)));
+        assert_eq!(inv.expiry, 604800);
+        assert_eq!(inv.min_final_cltv_expiry, 10);
+        assert_eq!(inv.routes.len(), 1);
+        let hop = &inv.routes[0].hops[0];
+        assert_eq!(
+            hop.id.to_string(),
+            "03d06758583bb5154774a6eb221b1276c9e82d65bbaceca806d90e20c108f4b1c7"
+        );
+        assert_eq!(hop.scid.to_string(), "589390x3312x1");
+        assert_eq!(hop.feebase, Amount::from_msat(1000));
+        assert_eq!(hop.feeprop, 2500);
+        assert_eq!(hop.expirydelta, 40);
+
+        // Round trip everything through our own encoder.
+        let fallback = |hex: &str, t| DecodepayFallbacks {
+            item_type: t,
+            addr: None,
+            hex: hex.to_string(),
+        };
+        let s = Bolt11Builder::new("bcrt", payment_hash(), 1700000000)
+            .amount(Amount::from_msat(1))
+            .description("test")
+            .include_payee()
+            .min_final_cltv_expiry(144)
+            .fallback(fallback(
+                "76a9143172b5654f6683c8fb146959d347ce303cae4ca788ac",
+                DecodepayFallbacksType::P2PKH,
+            ))
+            .fallback(fallback(
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                DecodepayFallbacksType::P2WPKH,
+            ))
+            .route(inv.routes[0].clone())
+            .sign(&key())
+            .unwrap();
+        assert!(s.starts_with("lnbcrt10p1"));
+        let inv2: Bolt11Invoice = s.parse().unwrap();
+        assert_eq!(inv2.payee.to_string(), PAYEE);
+        assert_eq!(inv2.amount_msat, Some(Amount::from_msat(1)));
+        assert_eq!(inv2.min_final_cltv_expiry, 144);
+        assert_eq!(inv2.routes[0].hops[0].scid, hop.scid);
+        assert_eq!(inv2.fallbacks.len(), 2);
+        assert!(matches!(
+            inv2.fallbacks[0].item_type,
+            DecodepayFallbacksType::P2PKH
+        ));
+        assert_eq!(
+            inv2.fallbacks[1].addr.as_deref(),
+            Some("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080")
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = |s: &str| s.parse::<Bolt11Invoice>().unwrap_err().to_string();
+
+        // Bad checksum, missing prefix, and mixed case.
+        assert_eq!(err("lnbc2500u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpquwpc4curk03c9wlrswe78q4eyqc7d8d0xqzpuyk0sg5g70me25alkluzd2x62aysf2pyy8edtjeevuv4p2d5p76r4zkmneet7uvyakky2zr4cusd45tftc9c5fh0nnqpnl2jfll544esqchsrnt"), "Bad bech32 string");
+        assert_eq!(err("pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpquwpc4curk03c9wlrswe78q4eyqc7d8d0xqzpuyk0sg5g70me25alkluzd2x62aysf2pyy8edtjeevuv4p2d5p76r4zkmneet7uvyakky2zr4cusd45tftc9c5fh0nnqpnl2jfll544esqchsrny"), "Bad bech32 string");
+        assert_eq!(err("LNBC2500u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpquwpc4curk03c9wlrswe78q4eyqc7d8d0xqzpuyk0sg5g70me25alkluzd2x62aysf2pyy8edtjeevuv4p2d5p76r4zkmneet7uvyakky2zr4cusd45tftc9c5fh0nnqpnl2jfll544esqchsrny"), "Bad bech32 string");
+
+        // Invalid signature, too short, bad amounts.
+        assert!(err("lnbc2500u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpusp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9qrsgqwgt7mcn5yqw3yx0w94pswkpq6j9uh6xfqqqtsk4tnarugeektd4hg5975x9am52rz4qskukxdmjemg92vvqz8nvmsye63r5ykel43pgz7zq0g2").contains("signature"));
+        assert!(err("lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6na6hlh").contains("signature"));
+        assert_eq!(err("lnbc2500x1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpusp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9qrsgqrrzc4cvfue4zp3hggxp47ag7xnrlr8vgcmkjxk3j5jqethnumgkpqp23z9jclu3v0a7e0aruz366e9wqdykw6dxhdzcjjhldxq0w6wgqcnu43j"), "Invalid amount postfix 'x'");
+        assert_eq!(err("lnbc2500000001p1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpusp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs9qrsgq0lzc236j96a95uv0m3umg28gclm5lqxtqqwk32uuk4k6673k6n5kfvx3d2h8s295fad45fdhmusm8sjudfhlf6dcsxmfvkeywmjdkxcp99202x"), "Invalid sub-millisatoshi amount '2500000001p'");
+
+        // Descriptions that aren't valid UTF-8.
+        for s in [
+            "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5gfskggz423rz6wp6yrqqcqpjkkrsmq07c4ht7qgjdmf2a8savsafcy8lqn4av4gs80gz88ff2y780tdcve7sxp80kd4vk7hajt5mskcsegz2qfll4jywfwhap2q2n6cqyz5tv4",
+            "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq4gfskggz423rz6wp6yrqzqcqpj30cjfyveywx7wk4gl45ua4g3hcsd9hp0qqtudua0529gfd5cp7kytnttu6dw0yp24v9aefvxamsdvrks9rsqr53ukrexf0vqp8fffusql6q3x4",
+            "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5gfskggz423rz6wp6yrcqcqpjg8ca44f2u2vw4df6zvata2p23v9dyzfjyyremz2f9t0xuzrzznrqcqm4pkmh36vj96qg0v93y0jvrp0u2607lgmc6gdes5lvpr42x9qptekthk",
+        ] {
+            assert_eq!(err(s), "d: invalid utf8");
+        }
+    }
+
+    #[test]
+    fn test_amount_encoding() {
+        for (msat, s) in [
+            (100_000_000_000, "1"),
+            (250_000_000, "2500u"),
+            (2_000_000_000, "20m"),
+            (100, "1n"),
+            (1, "10p"),
+            (967878534, "9678785340p"),
+        ] {
+            let a = Amount::from_msat(msat);
+            assert_eq!(encode_amount(a), s);
+            assert_eq!(parse_hrp(&format!("lnbc{}", s)).unwrap().1, Some(a));
+        }
+    }
+}
@@ -0,0 +1,1167 @@
+//! Local BOLT12 offer, invoice_request and invoice codec.
+//!
+//! BOLT12 messages are TLV streams, exchanged as checksum-less bech32
+//! strings with the `lno`, `lnr` and `lni` prefixes. [`Offer`],
+//! [`InvoiceRequest`] and [`Invoice`] give typed access to the fields
+//! we know about and keep any unknown odd fields, so a parsed message
+//! encodes back to the exact same [`TlvStream`] and its signature
+//! still checks out.
+//!
+//! Parsing a string with [`str::parse`] validates the message and, for
+//! invoice requests and invoices, its signature. The [`Display`]
+//! implementations produce the string form. Unlike the views in
+//! [`crate::decode`], none of this needs `lightningd`.
+//!
+//! The experimental recurrence fields are not supported, so offers
+//! using them fail to parse.
+use crate::onionmessage::BlindedPath;
+use crate::primitives::{
+    write_bigsize, Amount, Features, PublicKey, Sha256, TlvEntry, TlvStream, TlvValue,
+};
+use crate::wire::{Reader, WireType};
+use anyhow::{anyhow, Context, Error, Result};
+use bech32::{FromBase32, ToBase32};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::secp256k1::schnorr::Signature;
+use bitcoin::secp256k1::{KeyPair, Message, Secp256k1, SecretKey};
+use bitcoin::BlockHash;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub const OFFER_CHAINS: u64 = 2;
+pub const OFFER_METADATA: u64 = 4;
+pub const OFFER_CURRENCY: u64 = 6;
+pub const OFFER_AMOUNT: u64 = 8;
+pub const OFFER_DESCRIPTION: u64 = 10;
+pub const OFFER_FEATURES: u64 = 12;
+pub const OFFER_ABSOLUTE_EXPIRY: u64 = 14;
+pub const OFFER_PATHS: u64 = 16;
+pub const OFFER_ISSUER: u64 = 18;
+pub const OFFER_QUANTITY_MAX: u64 = 20;
+pub const OFFER_NODE_ID: u64 = 22;
+
+pub const INVREQ_METADATA: u64 = 0;
+pub const INVREQ_CHAIN: u64 = 80;
+pub const INVREQ_AMOUNT: u64 = 82;
+pub const INVREQ_FEATURES: u64 = 84;
+pub const INVREQ_QUANTITY: u64 = 86;
+pub const INVREQ_PAYER_ID: u64 = 88;
+pub const INVREQ_PAYER_NOTE: u64 = 89;
+
+pub const INVOICE_PATHS: u64 = 160;
+pub const INVOICE_BLINDEDPAY: u64 = 162;
+pub const INVOICE_CREATED_AT: u64 = 164;
+pub const INVOICE_RELATIVE_EXPIRY: u64 = 166;
+pub const INVOICE_PAYMENT_HASH: u64 = 168;
+pub const INVOICE_AMOUNT: u64 = 170;
+pub const INVOICE_FALLBACKS: u64 = 172;
+pub const INVOICE_FEATURES: u64 = 174;
+pub const INVOICE_NODE_ID: u64 = 176;
+
+pub const SIGNATURE: u64 = 240;
+
+/// `invoice_relative_expiry` if the invoice doesn't set it.
+pub const DEFAULT_RELATIVE_EXPIRY: u32 = 7200;
+
+/// Which message a TLV type belongs to. Each message may contain the
+/// fields of the ones it is built on, plus signature fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Offer,
+    InvoiceRequest,
+    Invoice,
+    Signature,
+}
+
+fn part(typ: u64) -> Option<Part> {
+    match typ {
+        1..=79 | 1_000_000_000..=1_999_999_999 => Some(Part::Offer),
+        0 | 80..=159 | 2_000_000_000..=2_999_999_999 => Some(Part::InvoiceRequest),
+        160..=239 | 3_000_000_000..=3_999_999_999 => Some(Part::Invoice),
+        240..=1000 => Some(Part::Signature),
+        _ => None,
+    }
+}
+
+/// BIP340 tagged hash, `SHA256(SHA256(tag) || SHA256(tag) || msg)`.
+fn tagged_hash(tag: &[u8], msg: &[u8]) -> Sha256 {
+    let tag = sha256::Hash::hash(tag);
+    let mut engine = sha256::Hash::engine();
+    engine.input(&tag[..]);
+    engine.input(&tag[..]);
+    engine.input(msg);
+    Sha256::from_engine(engine)
+}
+
+fn branch(a: &Sha256, b: &Sha256) -> Sha256 {
+    let (a, b) = if a[..] <= b[..] { (a, b) } else { (b, a) };
+    let mut msg = a[..].to_vec();
+    msg.extend_from_slice(&b[..]);
+    tagged_hash(b"LnBranch", &msg)
+}
+
+/// Combine `leaves` into a tree `width` leaves wide, `width` being a
+/// power of two. Missing leaves on the right are skipped, so the
+/// lowest-order leaves end up deepest.
+fn merkle_recurse(leaves: &[Sha256], width: usize) -> Sha256 {
+    if leaves.len() == 1 {
+        return leaves[0];
+    }
+    let half = width / 2;
+    if leaves.len() <= half {
+        return merkle_recurse(leaves, half);
+    }
+    branch(
+        &merkle_recurse(&leaves[..half], half),
+        &merkle_recurse(&leaves[half..], half),
+    )
+}
+
+/// The BOLT12 merkle root of all non-signature fields in `stream`.
+/// For an offer this is its `offer_id`.
+pub fn merkle_root(stream: &TlvStream) -> Sha256 {
+    let first = match stream.entries.first() {
+        Some(e) => e,
+        None => return Sha256::all_zeros(),
+    };
+    let mut nonce_tag = b"LnNonce".to_vec();
+    nonce_tag.extend(first.to_wire());
+
+    let leaves: Vec<Sha256> = stream
+        .entries
+        .iter()
+        .filter(|e| part(e.typ) != Some(Part::Signature))
+        .map(|e| {
+            let mut typ = Vec::new();
+            write_bigsize(&mut typ, e.typ);
+            branch(
+                &tagged_hash(b"LnLeaf", &e.to_wire()),
+                &tagged_hash(&nonce_tag, &typ),
+            )
+        })
+        .collect();
+    if leaves.is_empty() {
+        return Sha256::all_zeros();
+    }
+    merkle_recurse(&leaves, leaves.len().next_power_of_two())
+}
+
+/// The message that `messagename`'s `signature` field signs.
+fn signature_message(messagename: &str, merkle: &Sha256) -> Message {
+    let tag = format!("lightning{}signature", messagename);
+    Message::from_slice(&tagged_hash(tag.as_bytes(), &merkle[..])[..]).unwrap()
+}
+
+fn sign(
+    messagename: &str,
+    stream: &TlvStream,
+    key: &SecretKey,
+    signer: &PublicKey,
+) -> Result<Signature> {
+    let secp = Secp256k1::new();
+    if PublicKey::from_secret_key(&secp, key) != *signer {
+        return Err(anyhow!("Key does not match the {} signer", messagename));
+    }
+    let msg = signature_message(messagename, &merkle_root(stream));
+    Ok(secp.sign_schnorr_no_aux_rand(&msg, &KeyPair::from_secret_key(&secp, key)))
+}
+
+fn verify(
+    messagename: &str,
+    stream: &TlvStream,
+    signature: Option<&Signature>,
+    signer: &PublicKey,
+) -> Result<()> {
+    let signature = signature.ok_or_else(|| anyhow!("Missing signature"))?;
+    let msg = signature_message(messagename, &merkle_root(stream));
+    Secp256k1::verification_only()
+        .verify_schnorr(signature, &msg, &signer.x_only_public_key().0)
+        .map_err(|_| anyhow!("Invalid {} signature", messagename))
+}
+
+/// Decode a BOLT12 string, which may be split into several parts
+/// joined by `+` and optional whitespace.
+fn decode_string(s: &str, hrp: &str) -> Result<TlvStream> {
+    let mut parts = s.split('+');
+    let mut joined = parts.next().unwrap_or_default().to_string();
+    for p in parts {
+        let p = p.trim_start();
+        if joined.is_empty() || p.is_empty() {
+            return Err(anyhow!("Misplaced + in bolt12 string"));
+        }
+        joined.push_str(p);
+    }
+
+    let (found, data) = bech32::decode_without_checksum(&joined).context("Bad bech32 string")?;
+    if found != hrp {
+        return Err(anyhow!("Unexpected prefix {}, expected {}", found, hrp));
+    }
+    let bytes = Vec::<u8>::from_base32(&data).context("Bad bech32 padding")?;
+    TlvStream::from_wire(&bytes)
+}
+
+fn encode_string(hrp: &str, stream: &TlvStream, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let data = stream.to_wire().map_err(|_| std::fmt::Error)?.to_base32();
+    let s = bech32::encode_without_checksum(hrp, data).map_err(|_| std::fmt::Error)?;
+    write!(f, "{}", s)
+}
+
+fn parse_chain(v: &[u8]) -> Result<BlockHash> {
+    Ok(BlockHash::from_slice(v)?)
+}
+
+fn parse_paths(v: &[u8]) -> Result<Vec<BlindedPath>> {
+    Reader::new(v).read_all()
+}
+
+fn paths_to_wire(paths: &[BlindedPath]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for p in paths {
+        p.write(&mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// Keep an unknown field, as long as it's odd.
+fn unknown(unknown: &mut Vec<TlvEntry>, e: &TlvEntry) -> Result<()> {
+    if e.typ & 1 == 0 {
+        return Err(anyhow!("Unknown even field {}", e.typ));
+    }
+    unknown.push(e.clone());
+    Ok(())
+}
+
+/// Check that every field of `stream` may appear in a message made of
+/// `parts`, and return those belonging to `wanted`.
+fn fields<'a>(stream: &'a TlvStream, parts: &[Part], wanted: Part) -> Result<Vec<&'a TlvEntry>> {
+    let mut res = Vec::new();
+    for e in &stream.entries {
+        match part(e.typ) {
+            Some(p) if parts.contains(&p) => {
+                if p == wanted {
+                    res.push(e)
+                }
+            }
+            _ => return Err(anyhow!("Unexpected field {}", e.typ)),
+        }
+    }
+    Ok(res)
+}
+
+/// Put `entries` in the order required on the wire.
+fn stream(entries: Vec<TlvEntry>) -> Result<TlvStream> {
+    let mut stream = TlvStream { entries };
+    stream.canonicalize()?;
+    Ok(stream)
+}
+
+/// A BOLT12 offer, the `lno` string a payee publishes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Offer {
+    /// The chains the offer is valid for. Empty means bitcoin only.
+    pub chains: Vec<BlockHash>,
+    pub metadata: Option<Vec<u8>>,
+    /// ISO 4217 code `amount` is denominated in, if not msat.
+    pub currency: Option<String>,
+    pub amount: Option<u64>,
+    pub description: String,
+    pub features: Features,
+    pub absolute_expiry: Option<u64>,
+    pub paths: Vec<BlindedPath>,
+    pub issuer: Option<String>,
+    pub quantity_max: Option<u64>,
+    pub node_id: PublicKey,
+    /// Unknown odd fields in the offer range.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl Offer {
+    fn from_fields(fields: Vec<&TlvEntry>) -> Result<Offer> {
+        let mut chains = vec![];
+        let mut metadata = None;
+        let mut currency = None;
+        let mut amount = None;
+        let mut description = None;
+        let mut features = Features::default();
+        let mut absolute_expiry = None;
+        let mut offer_paths = vec![];
+        let mut issuer = None;
+        let mut quantity_max = None;
+        let mut node_id = None;
+        let mut unknown_fields = vec![];
+
+        for e in fields {
+            let v = &e.value[..];
+            match e.typ {
+                OFFER_CHAINS => {
+                    chains = v.chunks(32).map(parse_chain).collect::<Result<_>>()?;
+                }
+                OFFER_METADATA => metadata = Some(v.to_vec()),
+                OFFER_CURRENCY => currency = Some(String::from_tlv_value(v)?),
+                OFFER_AMOUNT => amount = Some(u64::from_tlv_value(v)?),
+                OFFER_DESCRIPTION => description = Some(String::from_tlv_value(v)?),
+                OFFER_FEATURES => features = Features::from_bytes(v),
+                OFFER_ABSOLUTE_EXPIRY => absolute_expiry = Some(u64::from_tlv_value(v)?),
+                OFFER_PATHS => offer_paths = parse_paths(v)?,
+                OFFER_ISSUER => issuer = Some(String::from_tlv_value(v)?),
+                OFFER_QUANTITY_MAX => quantity_max = Some(u64::from_tlv_value(v)?),
+                OFFER_NODE_ID => node_id = Some(PublicKey::from_tlv_value(v)?),
+                _ => unknown(&mut unknown_fields, e)?,
+            }
+        }
+
+        if currency.is_some() && amount.is_none() {
+            return Err(anyhow!("offer_currency without offer_amount"));
+        }
+        Ok(Offer {
+            chains,
+            metadata,
+            currency,
+            amount,
+            description: description.ok_or_else(|| anyhow!("Missing offer_description"))?,
+            features,
+            absolute_expiry,
+            paths: offer_paths,
+            issuer,
+            quantity_max,
+            node_id: node_id.ok_or_else(|| anyhow!("Missing offer_node_id"))?,
+            unknown: unknown_fields,
+        })
+    }
+
+    fn to_fields(&self, entries: &mut Vec<TlvEntry>) -> Result<()> {
+        let mut add = |typ, value| entries.push(TlvEntry { typ, value });
+        if !self.chains.is_empty() {
+            add(
+                OFFER_CHAINS,
+                self.chains.iter().flat_map(|c| c.to_vec()).collect(),
+            );
+        }
+        if let Some(m) = &self.metadata {
+            add(OFFER_METADATA, m.clone());
+        }
+        if let Some(c) = &self.currency {
+            add(OFFER_CURRENCY, c.as_bytes().to_vec());
+        }
+        if let Some(a) = self.amount {
+            add(OFFER_AMOUNT, a.to_tlv_value());
+        }
+        add(OFFER_DESCRIPTION, self.description.as_bytes().to_vec());
+        if !self.features.as_bytes().is_empty() {
+            add(OFFER_FEATURES, self.features.as_bytes().to_vec());
+        }
+        if let Some(e) = self.absolute_expiry {
+            add(OFFER_ABSOLUTE_EXPIRY, e.to_tlv_value());
+        }
+        if !self.paths.is_empty() {
+            add(OFFER_PATHS, paths_to_wire(&self.paths)?);
+        }
+        if let Some(i) = &self.issuer {
+            add(OFFER_ISSUER, i.as_bytes().to_vec());
+        }
+        if let Some(q) = self.quantity_max {
+            add(OFFER_QUANTITY_MAX, q.to_tlv_value());
+        }
+        add(OFFER_NODE_ID, self.node_id.serialize().to_vec());
+        entries.extend(self.unknown.iter().cloned());
+        Ok(())
+    }
+
+    /// The offer as a TLV stream.
+    pub fn to_tlv_stream(&self) -> Result<TlvStream> {
+        let mut entries = vec![];
+        self.to_fields(&mut entries)?;
+        stream(entries)
+    }
+
+    /// The `offer_id`, i.e., the merkle root of the offer fields.
+    pub fn id(&self) -> Result<Sha256> {
+        Ok(merkle_root(&self.to_tlv_stream()?))
+    }
+
+    /// The amount, if the offer has one and it is in msat.
+    pub fn amount_msat(&self) -> Option<Amount> {
+        match self.currency {
+            None => self.amount.map(Amount::from_msat),
+            Some(_) => None,
+        }
+    }
+}
+
+impl TryFrom<&TlvStream> for Offer {
+    type Error = Error;
+
+    fn try_from(s: &TlvStream) -> Result<Self> {
+        Offer::from_fields(fields(s, &[Part::Offer], Part::Offer)?)
+    }
+}
+
+impl FromStr for Offer {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Offer::try_from(&decode_string(s, "lno")?)
+    }
+}
+
+impl Display for Offer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stream = self.to_tlv_stream().map_err(|_| std::fmt::Error)?;
+        encode_string("lno", &stream, f)
+    }
+}
+
+/// Builds unsigned offers, which are complete as they are: offers
+/// don't carry a signature.
+pub struct OfferBuilder {
+    offer: Offer,
+}
+
+impl OfferBuilder {
+    pub fn new(description: &str, node_id: PublicKey) -> OfferBuilder {
+        OfferBuilder {
+            offer: Offer {
+                chains: vec![],
+                metadata: None,
+                currency: None,
+                amount: None,
+                description: description.to_string(),
+                features: Features::default(),
+                absolute_expiry: None,
+                paths: vec![],
+                issuer: None,
+                quantity_max: None,
+                node_id,
+                unknown: vec![],
+            },
+        }
+    }
+
+    /// Add a chain the offer is valid for. If none is added the offer
+    /// is for bitcoin only.
+    pub fn chain(mut self, chain: BlockHash) -> Self {
+        self.offer.chains.push(chain);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: Vec<u8>) -> Self {
+        self.offer.metadata = Some(metadata);
+        self
+    }
+
+    pub fn amount(mut self, amount: Amount) -> Self {
+        self.offer.currency = None;
+        self.offer.amount = Some(amount.msat());
+        self
+    }
+
+    /// Set an amount in the smallest unit of an ISO 4217 `currency`.
+    pub fn amount_in_currency(mut self, currency: &str, amount: u64) -> Self {
+        self.offer.currency = Some(currency.to_string());
+        self.offer.amount = Some(amount);
+        self
+    }
+
+    pub fn features(mut self, features: Features) -> Self {
+        self.offer.features = features;
+        self
+    }
+
+    pub fn absolute_expiry(mut self, expiry: u64) -> Self {
+        self.offer.absolute_expiry = Some(expiry);
+        self
+    }
+
+    pub fn path(mut self, path: BlindedPath) -> Self {
+        self.offer.paths.push(path);
+        self
+    }
+
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.offer.issuer = Some(issuer.to_string());
+        self
+    }
+
+    /// Allow buying up to `max` items at once, 0 meaning no limit.
+    pub fn quantity_max(mut self, max: u64) -> Self {
+        self.offer.quantity_max = Some(max);
+        self
+    }
+
+    pub fn build(self) -> Offer {
+        self.offer
+    }
+}
+
+/// A BOLT12 invoice_request, the `lnr` string a payer sends to ask for
+/// an invoice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvoiceRequest {
+    pub offer: Offer,
+    pub metadata: Vec<u8>,
+    pub chain: Option<BlockHash>,
+    pub amount: Option<Amount>,
+    pub features: Features,
+    pub quantity: Option<u64>,
+    pub payer_id: PublicKey,
+    pub payer_note: Option<String>,
+    /// Unknown odd fields in the invoice_request and signature ranges.
+    pub unknown: Vec<TlvEntry>,
+    pub signature: Option<Signature>,
+}
+
+impl InvoiceRequest {
+    /// Start an unsigned request for `offer`.
+    pub fn new(offer: Offer, metadata: Vec<u8>, payer_id: PublicKey) -> InvoiceRequest {
+        InvoiceRequest {
+            offer,
+            metadata,
+            chain: None,
+            amount: None,
+            features: Features::default(),
+            quantity: None,
+            payer_id,
+            payer_note: None,
+            unknown: vec![],
+            signature: None,
+        }
+    }
+
+    fn from_fields(fields: Vec<&TlvEntry>, offer: Offer) -> Result<InvoiceRequest> {
+        let mut metadata = None;
+        let mut chain = None;
+        let mut amount = None;
+        let mut features = Features::default();
+        let mut quantity = None;
+        let mut payer_id = None;
+        let mut payer_note = None;
+        let mut unknown_fields = vec![];
+        let mut signature = None;
+
+        for e in fields {
+            let v = &e.value[..];
+            match e.typ {
+                INVREQ_METADATA => metadata = Some(v.to_vec()),
+                INVREQ_CHAIN => chain = Some(parse_chain(v)?),
+                INVREQ_AMOUNT => amount = Some(Amount::from_tlv_value(v)?),
+                INVREQ_FEATURES => features = Features::from_bytes(v),
+                INVREQ_QUANTITY => quantity = Some(u64::from_tlv_value(v)?),
+                INVREQ_PAYER_ID => payer_id = Some(PublicKey::from_tlv_value(v)?),
+                INVREQ_PAYER_NOTE => payer_note = Some(String::from_tlv_value(v)?),
+                SIGNATURE => signature = Some(Signature::from_slice(v)?),
+                _ => unknown(&mut unknown_fields, e)?,
+            }
+        }
+
+        Ok(InvoiceRequest {
+            offer,
+            metadata: metadata.ok_or_else(|| anyhow!("Missing invreq_metadata"))?,
+            chain,
+            amount,
+            features,
+            quantity,
+            payer_id: payer_id.ok_or_else(|| anyhow!("Missing invreq_payer_id"))?,
+            payer_note,
+            unknown: unknown_fields,
+            signature,
+        })
+    }
+
+    fn to_fields(&self, entries: &mut Vec<TlvEntry>) -> Result<()> {
+        self.offer.to_fields(entries)?;
+        let mut add = |typ, value| entries.push(TlvEntry { typ, value });
+        add(INVREQ_METADATA, self.metadata.clone());
+        if let Some(c) = &self.chain {
+            add(INVREQ_CHAIN, c.to_vec());
+        }
+        if let Some(a) = self.amount {
+            add(INVREQ_AMOUNT, a.to_tlv_value());
+        }
+        if !self.features.as_bytes().is_empty() {
+            add(INVREQ_FEATURES, self.features.as_bytes().to_vec());
+        }
+        if let Some(q) = self.quantity {
+            add(INVREQ_QUANTITY, q.to_tlv_value());
+        }
+        add(INVREQ_PAYER_ID, self.payer_id.serialize().to_vec());
+        if let Some(n) = &self.payer_note {
+            add(INVREQ_PAYER_NOTE, n.as_bytes().to_vec());
+        }
+        entries.extend(self.unknown.iter().cloned());
+        Ok(())
+    }
+
+    /// The request as a TLV stream, including the signature if set.
+    pub fn to_tlv_stream(&self) -> Result<TlvStream> {
+        let mut entries = vec![];
+        self.to_fields(&mut entries)?;
+        if let Some(s) = &self.signature {
+            entries.push(TlvEntry {
+                typ: SIGNATURE,
+                value: s.as_ref().to_vec(),
+            });
+        }
+        stream(entries)
+    }
+
+    /// Sign the request with the secret key for `payer_id`.
+    pub fn sign(&mut self, payer_key: &SecretKey) -> Result<()> {
+        self.signature = None;
+        let stream = self.to_tlv_stream()?;
+        self.signature = Some(sign("invoice_request", &stream, payer_key, &self.payer_id)?);
+        Ok(())
+    }
+
+    /// Check that the request is signed by `payer_id`.
+    pub fn check_signature(&self) -> Result<()> {
+        verify(
+            "invoice_request",
+            &self.to_tlv_stream()?,
+            self.signature.as_ref(),
+            &self.payer_id,
+        )
+    }
+}
+
+impl TryFrom<&TlvStream> for InvoiceRequest {
+    type Error = Error;
+
+    fn try_from(s: &TlvStream) -> Result<Self> {
+        let parts = [Part::Offer, Part::InvoiceRequest, Part::Signature];
+        let offer = Offer::from_fields(fields(s, &parts, Part::Offer)?)?;
+        let mut invreq_fields = fields(s, &parts, Part::InvoiceRequest)?;
+        invreq_fields.extend(fields(s, &parts, Part::Signature)?);
+        InvoiceRequest::from_fields(invreq_fields, offer)
+    }
+}
+
+impl FromStr for InvoiceRequest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invreq = InvoiceRequest::try_from(&decode_string(s, "lnr")?)?;
+        invreq.check_signature()?;
+        Ok(invreq)
+    }
+}
+
+impl Display for InvoiceRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stream = self.to_tlv_stream().map_err(|_| std::fmt::Error)?;
+        encode_string("lnr", &stream, f)
+    }
+}
+
+/// How to pay through one of the `paths` of an [`Invoice`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlindedPayInfo {
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+    pub htlc_minimum_msat: Amount,
+    pub htlc_maximum_msat: Amount,
+    pub features: Features,
+}
+
+/// An on-chain address the invoice may also be paid to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FallbackAddress {
+    pub version: u8,
+    pub address: Vec<u8>,
+}
+
+/// A BOLT12 invoice, the `lni` string a payee sends in response to an
+/// invoice_request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    /// The request this invoice is for. Its `signature` is always
+    /// `None`, since invoices don't include it.
+    pub invoice_request: InvoiceRequest,
+    pub paths: Vec<BlindedPath>,
+    /// One entry for each of `paths`.
+    pub blindedpay: Vec<BlindedPayInfo>,
+    pub created_at: u64,
+    pub relative_expiry: Option<u32>,
+    pub payment_hash: Sha256,
+    pub amount: Amount,
+    pub fallbacks: Vec<FallbackAddress>,
+    pub features: Features,
+    pub node_id: PublicKey,
+    /// Unknown odd fields in the invoice and signature ranges.
+    pub unknown: Vec<TlvEntry>,
+    pub signature: Option<Signature>,
+}
+
+impl Invoice {
+    fn from_fields(fields: Vec<&TlvEntry>, invoice_request: InvoiceRequest) -> Result<Invoice> {
+        let mut invoice_paths = None;
+        let mut blindedpay = None;
+        let mut created_at = None;
+        let mut relative_expiry = None;
+        let mut payment_hash = None;
+        let mut amount = None;
+        let mut fallbacks = vec![];
+        let mut features = Features::default();
+        let mut node_id = None;
+        let mut unknown_fields = vec![];
+        let mut signature = None;
+
+        for e in fields {
+            let v = &e.value[..];
+            match e.typ {
+                INVOICE_PATHS => invoice_paths = Some(parse_paths(v)?),
+                INVOICE_BLINDEDPAY => {
+                    let mut r = Reader::new(v);
+                    let mut infos = vec![];
+                    while !r.is_empty() {
+                        let fee_base_msat = r.read()?;
+                        let fee_proportional_millionths = r.read()?;
+                        let cltv_expiry_delta = r.read()?;
+                        let htlc_minimum_msat = r.read()?;
+                        let htlc_maximum_msat = r.read()?;
+                        let flen: u16 = r.read()?;
+                        infos.push(BlindedPayInfo {
+                            fee_base_msat,
+                            fee_proportional_millionths,
+                            cltv_expiry_delta,
+                            htlc_minimum_msat,
+                            htlc_maximum_msat,
+                            features: Features::from_bytes(r.take(flen as usize)?),
+                        });
+                    }
+                    blindedpay = Some(infos);
+                }
+                INVOICE_CREATED_AT => created_at = Some(u64::from_tlv_value(v)?),
+                INVOICE_RELATIVE_EXPIRY => relative_expiry = Some(u32::from_tlv_value(v)?),
+                INVOICE_PAYMENT_HASH => payment_hash = Some(Sha256::from_slice(v)?),
+                INVOICE_AMOUNT => amount = Some(Amount::from_tlv_value(v)?),
+                INVOICE_FALLBACKS => {
+                    let mut r = Reader::new(v);
+                    while !r.is_empty() {
+                        let version = r.read()?;
+                        let len: u16 = r.read()?;
+                        fallbacks.push(FallbackAddress {
+                            version,
+                            address: r.take(len as usize)?.to_vec(),
+                        });
+                    }
+                }
+                INVOICE_FEATURES => features = Features::from_bytes(v),
+                INVOICE_NODE_ID => node_id = Some(PublicKey::from_tlv_value(v)?),
+                SIGNATURE => signature = Some(Signature::from_slice(v)?),
+                _ => unknown(&mut unknown_fields, e)?,
+            }
+        }
+
+        let paths = invoice_paths.ok_or_else(|| anyhow!("Missing invoice_paths"))?;
+        let blindedpay = blindedpay.ok_or_else(|| anyhow!("Missing invoice_blindedpay"))?;
+        if paths.len() != blindedpay.len() {
+            return Err(anyhow!(
+                "invoice_blindedpay has {} entries for {} invoice_paths",
+                blindedpay.len(),
+                paths.len()
+            ));
+        }
+        Ok(Invoice {
+            invoice_request,
+            paths,
+            blindedpay,
+            created_at: created_at.ok_or_else(|| anyhow!("Missing invoice_created_at"))?,
+            relative_expiry,
+            payment_hash: payment_hash.ok_or_else(|| anyhow!("Missing invoice_payment_hash"))?,
+            amount: amount.ok_or_else(|| anyhow!("Missing invoice_amount"))?,
+            fallbacks,
+            features,
+            node_id: node_id.ok_or_else(|| anyhow!("Missing invoice_node_id"))?,
+            unknown: unknown_fields,
+            signature,
+        })
+    }
+
+    fn to_fields(&self, entries: &mut Vec<TlvEntry>) -> Result<()> {
+        self.invoice_request.to_fields(entries)?;
+        let mut add = |typ, value| entries.push(TlvEntry { typ, value });
+        add(INVOICE_PATHS, paths_to_wire(&self.paths)?);
+        let mut pay = vec![];
+        for p in &self.blindedpay {
+            pay.extend_from_slice(&p.fee_base_msat.to_be_bytes());
+            pay.extend_from_slice(&p.fee_proportional_millionths.to_be_bytes());
+            pay.extend_from_slice(&p.cltv_expiry_delta.to_be_bytes());
+            pay.extend_from_slice(&p.htlc_minimum_msat.msat().to_be_bytes());
+            pay.extend_from_slice(&p.htlc_maximum_msat.msat().to_be_bytes());
+            let features = p.features.as_bytes();
+            let flen =
+                u16::try_from(features.len()).context("blinded payinfo features too long")?;
+            pay.extend_from_slice(&flen.to_be_bytes());
+            pay.extend_from_slice(features);
+        }
+        add(INVOICE_BLINDEDPAY, pay);
+        add(INVOICE_CREATED_AT, self.created_at.to_tlv_value());
+        if let Some(e) = self.relative_expiry {
+            add(INVOICE_RELATIVE_EXPIRY, e.to_tlv_value());
+        }
+        add(INVOICE_PAYMENT_HASH, self.payment_hash.to_vec());
+        add(INVOICE_AMOUNT, self.amount.to_tlv_value());
+        if !self.fallbacks.is_empty() {
+            let mut buf = vec![];
+            for f in &self.fallbacks {
+                let len = u16::try_from(f.address.len()).context("fallback address too long")?;
+                buf.push(f.version);
+                buf.extend_from_slice(&len.to_be_bytes());
+                buf.extend_from_slice(&f.address);
+            }
+            add(INVOICE_FALLBACKS, buf);
+        }
+        if !self.features.as_bytes().is_empty() {
+            add(INVOICE_FEATURES, self.features.as_bytes().to_vec());
+        }
+        add(INVOICE_NODE_ID, self.node_id.serialize().to_vec());
+        entries.extend(self.unknown.iter().cloned());
+        Ok(())
+    }
+
+    /// The invoice as a TLV stream, including the signature if set.
+    pub fn to_tlv_stream(&self) -> Result<TlvStream> {
+        let mut entries = vec![];
+        self.to_fields(&mut entries)?;
+        if let Some(s) = &self.signature {
+            entries.push(TlvEntry {
+                typ: SIGNATURE,
+                value: s.as_ref().to_vec(),
+            });
+        }
+        stream(entries)
+    }
+
+    /// Sign the invoice with the secret key for `node_id`.
+    pub fn sign(&mut self, node_key: &SecretKey) -> Result<()> {
+        self.signature = None;
+        let stream = self.to_tlv_stream()?;
+        self.signature = Some(sign("invoice", &stream, node_key, &self.node_id)?);
+        Ok(())
+    }
+
+    /// Check that the invoice is signed by `node_id`.
+    pub fn check_signature(&self) -> Result<()> {
+        verify(
+            "invoice",
+            &self.to_tlv_stream()?,
+            self.signature.as_ref(),
+            &self.node_id,
+        )
+    }
+
+    /// When the invoice expires, in seconds since the epoch.
+    pub fn expires_at(&self) -> u64 {
+        let expiry = self.relative_expiry.unwrap_or(DEFAULT_RELATIVE_EXPIRY);
+        self.created_at.saturating_add(expiry as u64)
+    }
+}
+
+impl TryFrom<&TlvStream> for Invoice {
+    type Error = Error;
+
+    fn try_from(s: &TlvStream) -> Result<Self> {
+        let parts = [
+            Part::Offer,
+            Part::InvoiceRequest,
+            Part::Invoice,
+            Part::Signature,
+        ];
+        let offer = Offer::from_fields(fields(s, &parts, Part::Offer)?)?;
+        let invoice_request =
+            InvoiceRequest::from_fields(fields(s, &parts, Part::InvoiceRequest)?, offer)?;
+        let mut invoice_fields = fields(s, &parts, Part::Invoice)?;
+        invoice_fields.extend(fields(s, &parts, Part::Signature)?);
+        Invoice::from_fields(invoice_fields, invoice_request)
+    }
+}
+
+impl FromStr for Invoice {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invoice = Invoice::try_from(&decode_string(s, "lni")?)?;
+        invoice.check_signature()?;
+        Ok(invoice)
+    }
+}
+
+impl Display for Invoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stream = self.to_tlv_stream().map_err(|_| std::fmt::Error)?;
+        encode_string("lni", &stream, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::onionmessage::BlindedHop;
+
+    fn key(b: u8) -> SecretKey {
+        SecretKey::from_slice(&[b; 32]).unwrap()
+    }
+
+    fn node_id(b: u8) -> PublicKey {
+        PublicKey::from_secret_key(&Secp256k1::new(), &key(b))
+    }
+
+    fn tlv(typ: u64, value: &[u8]) -> Vec<u8> {
+        let mut buf = vec![];
+        write_bigsize(&mut buf, typ);
+        write_bigsize(&mut buf, value.len() as u64);
+        buf.extend_from_slice(value);
+        buf
+    }
+
+    fn h(tag: &[u8], msg: &[u8]) -> Sha256 {
+        let t = sha256::Hash::hash(tag);
+        let mut full = t.to_vec();
+        full.extend_from_slice(&t[..]);
+        full.extend_from_slice(msg);
+        sha256::Hash::hash(&full)
+    }
+
+    fn ordered(a: Sha256, b: Sha256) -> Vec<u8> {
+        let (a, b) = if a[..] < b[..] { (a, b) } else { (b, a) };
+        [a.to_vec(), b.to_vec()].concat()
+    }
+
+    #[test]
+    fn test_offer() {
+        // From test_offer_needs_option in tests/test_pay.py
+        let s = "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrcgqyqs5pr5v4ehg93pqfnwgkvdr57yzh6h92zg3qctvrm7w38djg67kzcm4yeg8vc4cq63s";
+        let offer: Offer = s.parse().unwrap();
+        assert_eq!(offer.description, "test");
+        assert_eq!(offer.amount_msat(), Some(Amount::from_msat(1)));
+        assert_eq!(
+            offer.chains[0].to_string(),
+            "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"
+        );
+        assert_eq!(
+            offer.node_id.to_string(),
+            "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518"
+        );
+        assert_eq!(offer.to_string(), s);
+
+        // Split strings are joined back together.
+        let split = format!("{}+\n  {}", &s[..20], &s[20..]);
+        assert_eq!(split.parse::<Offer>().unwrap(), offer);
+        assert!(format!("{}+", s).parse::<Offer>().is_err());
+        assert!(s.to_uppercase().parse::<Offer>().is_ok());
+        assert!(s.replacen("lno", "lnr", 1).parse::<Offer>().is_err());
+
+        let path = BlindedPath {
+            first_node_id: node_id(1),
+            blinding: node_id(2),
+            hops: vec![BlindedHop {
+                blinded_node_id: node_id(3),
+                encrypted_recipient_data: vec![1, 2, 3],
+            }],
+        };
+        let offer = OfferBuilder::new("A Mathematical Treatise", node_id(0x41))
+            .amount_in_currency("USD", 100)
+            .issuer("Alice")
+            .quantity_max(0)
+            .path(path)
+            .build();
+        let s = offer.to_string();
+        assert!(s.starts_with("lno1"));
+        let parsed: Offer = s.parse().unwrap();
+        assert_eq!(parsed, offer);
+        assert_eq!(parsed.amount_msat(), None);
+        assert_eq!(
+            parsed.id().unwrap(),
+            merkle_root(&decode_string(&s, "lno").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invoice_request_signature() {
+        // The invoice_request from common/test/run-bolt12_merkle.c
+        let offer = OfferBuilder::new("A Mathematical Treatise", node_id(0x41))
+            .amount_in_currency("USD", 100)
+            .build();
+        let mut invreq = InvoiceRequest::new(offer, vec![0; 8], node_id(0x42));
+        assert!(invreq.sign(&key(0x41)).is_err());
+        invreq.sign(&key(0x42)).unwrap();
+
+        let fieldwires = [
+            tlv(0, &[0; 8]),
+            tlv(6, b"USD"),
+            tlv(8, &[100]),
+            tlv(10, b"A Mathematical Treatise"),
+            tlv(22, &node_id(0x41).serialize()),
+            tlv(88, &node_id(0x42).serialize()),
+        ];
+        let nonce_tag = [b"LnNonce".to_vec(), fieldwires[0].clone()].concat();
+        let leaf: Vec<Sha256> = fieldwires
+            .iter()
+            .map(|f| {
+                h(
+                    b"LnBranch",
+                    &ordered(h(b"LnLeaf", f), h(&nonce_tag, &f[..1])),
+                )
+            })
+            .collect();
+        let b01 = h(b"LnBranch", &ordered(leaf[0], leaf[1]));
+        let b23 = h(b"LnBranch", &ordered(leaf[2], leaf[3]));
+        let b45 = h(b"LnBranch", &ordered(leaf[4], leaf[5]));
+        let m = h(
+            b"LnBranch",
+            &ordered(h(b"LnBranch", &ordered(b01, b23)), b45),
+        );
+
+        let stream = invreq.to_tlv_stream().unwrap();
+        assert_eq!(stream.entries.len(), 7);
+        assert_eq!(
+            stream.to_wire().unwrap()[..],
+            [
+                fieldwires.concat(),
+                tlv(240, invreq.signature.unwrap().as_ref())
+            ]
+            .concat()[..]
+        );
+        assert_eq!(merkle_root(&stream), m);
+
+        let sighash = h(b"lightninginvoice_requestsignature", &m[..]);
+        let msg = Message::from_slice(&sighash[..]).unwrap();
+        Secp256k1::new()
+            .verify_schnorr(
+                &invreq.signature.unwrap(),
+                &msg,
+                &node_id(0x42).x_only_public_key().0,
+            )
+            .unwrap();
+
+        let s = invreq.to_string();
+        assert!(s.starts_with("lnr1"));
+        assert_eq!(s.parse::<InvoiceRequest>().unwrap(), invreq);
+
+        // Changing any field invalidates the signature.
+        let mut tampered = invreq.clone();
+        tampered.payer_note = Some("free please".to_string());
+        let err = tampered.to_string().parse::<InvoiceRequest>().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid invoice_request signature");
+
+        let mut unsigned = invreq;
+        unsigned.signature = None;
+        assert!(unsigned.to_string().parse::<InvoiceRequest>().is_err());
+    }
+
+    #[test]
+    fn test_invoice() {
+        let offer = OfferBuilder::new("coffee", node_id(0x41))
+            .amount(Amount::from_msat(10000))
+            .build();
+        let mut invoice_request = InvoiceRequest::new(offer, vec![1; 16], node_id(0x42));
+        invoice_request.quantity = Some(2);
+        invoice_request.amount = Some(Amount::from_msat(20000));
+        invoice_request.unknown.push(TlvEntry {
+            typ: 2_000_000_001,
+            value: vec![7],
+        });
+
+        let mut invoice = Invoice {
+            invoice_request,
+            paths: vec![BlindedPath {
+                first_node_id: node_id(1),
+                blinding: node_id(2),
+                hops: vec![BlindedHop {
+                    blinded_node_id: node_id(3),
+                    encrypted_recipient_data: vec![4; 20],
+                }],
+            }],
+            blindedpay: vec![BlindedPayInfo {
+                fee_base_msat: 1,
+                fee_proportional_millionths: 10,
+                cltv_expiry_delta: 18,
+                htlc_minimum_msat: Amount::from_msat(1),
+                htlc_maximum_msat: Amount::from_msat(100_000_000),
+                features: Features::default(),
+            }],
+            created_at: 1667237562,
+            relative_expiry: None,
+            payment_hash: sha256::Hash::hash(b"preimage"),
+            amount: Amount::from_msat(20000),
+            fallbacks: vec![FallbackAddress {
+                version: 0,
+                address: vec![5; 20],
+            }],
+            features: Features::default(),
+            node_id: node_id(0x41),
+            unknown: vec![],
+            signature: None,
+        };
+        invoice.sign(&key(0x41)).unwrap();
+        assert_eq!(invoice.expires_at(), 1667237562 + 7200);
+
+        let s = invoice.to_string();
+        assert!(s.starts_with("lni1"));
+        let parsed: Invoice = s.parse().unwrap();
+        assert_eq!(parsed, invoice);
+        assert_eq!(parsed.invoice_request.unknown[0].typ, 2_000_000_001);
+
+        // An invoice is not an invoice_request.
+        let stream = invoice.to_tlv_stream().unwrap();
+        let err = InvoiceRequest::try_from(&stream).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected field 160");
+
+        // Unknown even fields are rejected, odd ones are fine.
+        let mut bad = invoice.clone();
+        bad.unknown.push(TlvEntry {
+            typ: 200,
+            value: vec![],
+        });
+        let err = Invoice::try_from(&bad.to_tlv_stream().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown even field 200");
+        bad.unknown[0].typ = 201;
+        assert!(Invoice::try_from(&bad.to_tlv_stream().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid() {
+        // Out of order fields
+        let buf = [tlv(10, b"x"), tlv(8, &[1])].concat();
+        assert!(TlvStream::from_wire(&buf).is_err());
+        // Duplicate fields
+        let buf = [tlv(8, &[1]), tlv(8, &[1])].concat();
+        assert!(TlvStream::from_wire(&buf).is_err());
+        // Truncated value
+        assert!(TlvStream::from_wire(&[8, 2, 1]).is_err());
+        // Non-canonical bigsize
+        assert!(TlvStream::from_wire(&[0xfd, 0, 8, 1, 1]).is_err());
+
+        let stream = |entries: Vec<(u64, Vec<u8>)>| TlvStream {
+            entries: entries
+                .into_iter()
+                .map(|(typ, value)| TlvEntry { typ, value })
+                .collect(),
+        };
+        let id = node_id(1).serialize().to_vec();
+        assert!(Offer::try_from(&stream(vec![(10, b"x".to_vec()), (22, id.clone())])).is_ok());
+        // Missing node_id and description
+        assert!(Offer::try_from(&stream(vec![(10, b"x".to_vec())])).is_err());
+        assert!(Offer::try_from(&stream(vec![(22, id.clone())])).is_err());
+        // Non-minimal amount
+        let err = Offer::try_from(&stream(vec![
+            (8, vec![0, 1]),
+            (10, b"x".to_vec()),
+            (22, id.clone()),
+        ]))
+        .unwrap_err();
+        assert_eq!(err.to_string(), "non-minimal truncated integer");
+        // Currency without amount
+        assert!(Offer::try_from(&stream(vec![
+            (6, b"USD".to_vec()),
+            (10, b"x".to_vec()),
+            (22, id.clone()),
+        ]))
+        .is_err());
+        // invoice_request fields in an offer
+        let err =
+            Offer::try_from(&stream(vec![(0, vec![]), (10, b"x".to_vec()), (22, id)])).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected field 0");
+    }
+}
@@ -70,6 +70,17 @@ pub struct JsonCodec {
     /// Sub-codec used to split the input into chunks that can then be
     /// parsed by the JSON parser.
     inner: MultiLineCodec,
+
+    /// Size in bytes of the last message decoded.
+    last_len: Option<usize>,
+}
+
+impl JsonCodec {
+    /// The size in bytes of the message decoded since the last call,
+    /// before parsing, if any.
+    pub(crate) fn take_last_len(&mut self) -> Option<usize> {
+        self.last_len.take()
+    }
 }
 
 impl<T> Encoder<T> for JsonCodec
@@ -92,6 +103,7 @@ impl Decoder for JsonCodec {
             Ok(None) => Ok(None),
             Err(e) => Err(e),
             Ok(Some(s)) => {
+                self.last_len = Some(s.len());
                 if let Ok(v) = Value::from_str(&s) {
                     Ok(Some(v))
                 } else {
@@ -0,0 +1,590 @@
+//! Coin control on top of the node's on-chain wallet.
+//!
+//! The [`CoinControl`] helper picks UTXOs from `listfunds` according
+//! to a [`UtxoFilter`], and then asks `lightningd` to reserve them and
+//! build a PSBT spending them through `utxopsbt`. The reservation is
+//! tracked by a [`PsbtSession`] which releases the inputs again via
+//! `unreserveinputs` unless the PSBT is sent or explicitly consumed.
+use crate::model::requests::{
+    FeeratesRequest, FeeratesStyle, GetinfoRequest, ListfundsRequest, UtxopsbtRequest,
+};
+use crate::model::responses::{FeeratesPerkw, ListfundsOutputs, ListfundsOutputsStatus};
+use crate::primitives::{Amount, Feerate, Outpoint, Sha256};
+use crate::psbt::PsbtSession;
+use crate::ClnRpc;
+use anyhow::{anyhow, Context, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::Script;
+use log::debug;
+use std::path::{Path, PathBuf};
+
+/// Number of blocks `lightningd` reserves inputs for by default.
+pub const DEFAULT_RESERVE_BLOCKS: u32 = 72;
+
+/// The kind of script a UTXO is locked to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AddressType {
+    P2pkh,
+    P2shP2wpkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    Other,
+}
+
+impl AddressType {
+    /// Classify an output based on its `scriptpubkey` and, for
+    /// p2sh-wrapped outputs, the `redeemscript`.
+    pub fn from_output(o: &ListfundsOutputs) -> AddressType {
+        let script = match hex::decode(&o.scriptpubkey) {
+            Ok(s) => Script::from(s),
+            Err(_) => return AddressType::Other,
+        };
+
+        if script.is_v0_p2wpkh() {
+            AddressType::P2wpkh
+        } else if script.is_v0_p2wsh() {
+            AddressType::P2wsh
+        } else if script.is_v1_p2tr() {
+            AddressType::P2tr
+        } else if script.is_p2pkh() {
+            AddressType::P2pkh
+        } else if script.is_p2sh() {
+            let wrapped = o
+                .redeemscript
+                .as_ref()
+                .and_then(|r| hex::decode(r).ok())
+                .map(|r| Script::from(r).is_v0_p2wpkh())
+                .unwrap_or(false);
+            if wrapped {
+                AddressType::P2shP2wpkh
+            } else {
+                AddressType::P2sh
+            }
+        } else {
+            AddressType::Other
+        }
+    }
+
+    /// The weight an input of this type adds to a transaction once
+    /// signed. We can't know the witness of script outputs, so those
+    /// are assumed to be as large as a legacy input.
+    pub fn input_weight(&self) -> u64 {
+        // txid, vout, sequence and the scriptSig length, all non-witness.
+        let base = (32 + 4 + 4 + 1) * 4;
+        match self {
+            // Witness item count, signature and pubkey.
+            AddressType::P2wpkh => base + 1 + 1 + 72 + 1 + 33,
+            // As above, plus the wrapped witness program in the scriptSig.
+            AddressType::P2shP2wpkh => base + 23 * 4 + 1 + 1 + 72 + 1 + 33,
+            // Witness item count and a schnorr signature.
+            AddressType::P2tr => base + 1 + 1 + 64,
+            // A scriptSig with signature and pubkey.
+            AddressType::P2pkh | AddressType::P2sh | AddressType::P2wsh | AddressType::Other => {
+                base + 107 * 4
+            }
+        }
+    }
+}
+
+/// Criteria an output from `listfunds` has to satisfy in order to be
+/// considered for selection. Spent, immature and already reserved
+/// outputs are never selected.
+#[derive(Clone, Debug, Default)]
+pub struct UtxoFilter {
+    /// Skip outputs smaller than this.
+    pub min_amount: Option<Amount>,
+    /// Skip outputs larger than this.
+    pub max_amount: Option<Amount>,
+    /// Minimum number of confirmations, `0` allows unconfirmed outputs.
+    pub min_confirmations: u32,
+    /// Only select outputs of these types. Empty means any type.
+    pub address_types: Vec<AddressType>,
+}
+
+impl UtxoFilter {
+    /// Number of confirmations of `o` given the current `blockheight`.
+    pub fn confirmations(o: &ListfundsOutputs, blockheight: u32) -> u32 {
+        match (o.status, o.blockheight) {
+            (ListfundsOutputsStatus::CONFIRMED, Some(h)) if h <= blockheight => blockheight - h + 1,
+            _ => 0,
+        }
+    }
+
+    pub fn matches(&self, o: &ListfundsOutputs, blockheight: u32) -> bool {
+        let spendable = matches!(
+            o.status,
+            ListfundsOutputsStatus::CONFIRMED | ListfundsOutputsStatus::UNCONFIRMED
+        );
+        if !spendable || o.reserved {
+            return false;
+        }
+
+        let amount = o.amount_msat.msat();
+        if self.min_amount.map(|a| amount < a.msat()).unwrap_or(false)
+            || self.max_amount.map(|a| amount > a.msat()).unwrap_or(false)
+        {
+            return false;
+        }
+
+        if UtxoFilter::confirmations(o, blockheight) < self.min_confirmations {
+            return false;
+        }
+
+        self.address_types.is_empty() || self.address_types.contains(&AddressType::from_output(o))
+    }
+}
+
+/// The value `o` contributes once the fee for spending it at
+/// `feerate_per_kw` is paid, in millisatoshi. Negative if spending it
+/// costs more than it is worth.
+pub fn effective_value(o: &ListfundsOutputs, feerate_per_kw: u32) -> i128 {
+    // Satoshi per kiloweight is the same as millisatoshi per weight.
+    let fee = AddressType::from_output(o).input_weight() as i128 * feerate_per_kw as i128;
+    o.amount_msat.msat() as i128 - fee
+}
+
+/// Select outputs matching `filter` until their sum covers `target`
+/// plus the fee at `feerate_per_kw` for the inputs themselves and for
+/// the `startweight` of the rest of the transaction, like `utxopsbt`
+/// charges it.
+///
+/// Outputs are picked by largest [`effective_value`] first, which
+/// minimizes the number of inputs and thus the fee, and outputs that
+/// would cost more to spend than they are worth are skipped. Fails if
+/// the matching outputs do not add up to `target`.
+pub fn select<'a>(
+    outputs: &'a [ListfundsOutputs],
+    blockheight: u32,
+    filter: &UtxoFilter,
+    target: Amount,
+    startweight: u32,
+    feerate_per_kw: u32,
+) -> Result<Vec<&'a ListfundsOutputs>> {
+    let mut candidates: Vec<(i128, &ListfundsOutputs)> = outputs
+        .iter()
+        .filter(|o| filter.matches(o, blockheight))
+        .map(|o| (effective_value(o, feerate_per_kw), o))
+        .filter(|(v, _)| *v > 0)
+        .collect();
+    candidates.sort_by_key(|(v, _)| std::cmp::Reverse(*v));
+
+    let target = target.msat() as i128 + startweight as i128 * feerate_per_kw as i128;
+    let mut selected = vec![];
+    let mut sum = 0i128;
+    for (v, o) in candidates {
+        if sum >= target {
+            break;
+        }
+        sum += v;
+        selected.push(o);
+    }
+
+    if sum < target {
+        return Err(anyhow!(
+            "Insufficient funds: matching outputs total {}msat after fees, need {}msat",
+            sum,
+            target
+        ));
+    }
+    Ok(selected)
+}
+
+/// Resolve `feerate` to satoshi per 1000 weight units, asking the node
+/// for its estimates if it isn't an explicit rate.
+async fn feerate_per_kw(rpc: &mut ClnRpc, feerate: Feerate) -> Result<u32> {
+    if let Some(v) = feerate.per_kw() {
+        return Ok(v);
+    }
+
+    let estimates: FeeratesPerkw = rpc
+        .call_typed(FeeratesRequest {
+            style: FeeratesStyle::PERKW,
+        })
+        .await?
+        .perkw
+        .context("feerates did not return perkw estimates")?;
+    let estimate = match feerate {
+        Feerate::Slow | Feerate::MinAcceptable => Some(estimates.min_acceptable),
+        Feerate::MaxAcceptable => Some(estimates.max_acceptable),
+        Feerate::Normal | Feerate::Opening => estimates.opening,
+        Feerate::Urgent | Feerate::UnilateralClose => estimates.unilateral_close,
+        Feerate::MutualClose => estimates.mutual_close,
+        Feerate::DelayedToUs => estimates.delayed_to_us,
+        Feerate::HtlcResolution => estimates.htlc_resolution,
+        Feerate::Penalty => estimates.penalty,
+        _ => None,
+    };
+    estimate.with_context(|| format!("No estimate for feerate {}", feerate))
+}
+
+fn outpoint(o: &ListfundsOutputs) -> Result<Outpoint> {
+    let txid = hex::decode(&o.txid).with_context(|| format!("Malformed txid {}", o.txid))?;
+    Ok(Outpoint {
+        txid: Sha256::from_slice(&txid)?,
+        outnum: o.output,
+    })
+}
+
+/// Selects and reserves UTXOs from the node's wallet. Each operation
+/// uses a fresh connection to the RPC socket.
+pub struct CoinControl {
+    rpc_path: PathBuf,
+    reserve: u32,
+}
+
+impl CoinControl {
+    pub fn new<P>(rpc_path: P) -> CoinControl
+    where
+        P: AsRef<Path>,
+    {
+        CoinControl {
+            rpc_path: rpc_path.as_ref().to_path_buf(),
+            reserve: DEFAULT_RESERVE_BLOCKS,
+        }
+    }
+
+    /// Number of blocks to reserve the selected inputs for.
+    pub fn reserve_blocks(mut self, blocks: u32) -> CoinControl {
+        self.reserve = blocks;
+        self
+    }
+
+    /// Return the outputs currently matching `filter`.
+    pub async fn list(&self, filter: &UtxoFilter) -> Result<Vec<ListfundsOutputs>> {
+        let mut rpc = ClnRpc::new(&self.rpc_path).await?;
+        let blockheight = rpc.call_typed(GetinfoRequest {}).await?.blockheight;
+        let funds = rpc
+            .call_typed(ListfundsRequest { spent: Some(false) })
+            .await?;
+        Ok(funds
+            .outputs
+            .into_iter()
+            .filter(|o| filter.matches(o, blockheight))
+            .collect())
+    }
+
+    /// Select outputs covering `amount` and have `lightningd` reserve
+    /// them and build a PSBT spending them at `feerate`, see
+    /// [`PsbtSession::utxo`]. `startweight` is the weight of the
+    /// transaction without any inputs, as expected by `utxopsbt`.
+    pub async fn reserve(
+        &self,
+        filter: &UtxoFilter,
+        amount: Amount,
+        feerate: Feerate,
+        startweight: u32,
+    ) -> Result<PsbtSession> {
+        let mut rpc = ClnRpc::new(&self.rpc_path).await?;
+        let blockheight = rpc.call_typed(GetinfoRequest {}).await?.blockheight;
+        let funds = rpc
+            .call_typed(ListfundsRequest { spent: Some(false) })
+            .await?;
+
+        let perkw = feerate_per_kw(&mut rpc, feerate).await?;
+
+        let selected = select(
+            &funds.outputs,
+            blockheight,
+            filter,
+            amount,
+            startweight,
+            perkw,
+        )?;
+        let utxos = selected
+            .into_iter()
+            .map(outpoint)
+            .collect::<Result<Vec<_>>>()?;
+        debug!(
+            "Selected {} inputs to cover {}msat",
+            utxos.len(),
+            amount.msat()
+        );
+
+        PsbtSession::utxo(
+            &self.rpc_path,
+            UtxopsbtRequest {
+                satoshi: amount,
+                feerate,
+                startweight,
+                utxos,
+                reserve: Some(self.reserve),
+                reservedok: Some(false),
+                locktime: None,
+                min_witness_weight: None,
+                excess_as_change: None,
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::JsonCodec;
+    use crate::psbt::encode_psbt;
+    use bitcoin::psbt::Psbt;
+    use bitcoin::{OutPoint, PackedLockTime, Transaction, TxIn, Txid};
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+    use tokio_util::codec::Framed;
+
+    fn output(txid: &str, amount: u64, status: &str, blockheight: Option<u32>) -> ListfundsOutputs {
+        serde_json::from_value(json!({
+            "txid": txid,
+            "output": 0,
+            "amount_msat": amount,
+            "scriptpubkey": "0014cf7e4c9fa2e0fa64d8a0cbf3e6c2b4e7d21d5a7b",
+            "status": status,
+            "reserved": false,
+            "blockheight": blockheight,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_address_type() {
+        let mut o = output(&"00".repeat(32), 1000, "confirmed", Some(100));
+        assert_eq!(AddressType::from_output(&o), AddressType::P2wpkh);
+        o.scriptpubkey = format!("5120{}", "11".repeat(32));
+        assert_eq!(AddressType::from_output(&o), AddressType::P2tr);
+        o.scriptpubkey = format!("a914{}87", "22".repeat(20));
+        assert_eq!(AddressType::from_output(&o), AddressType::P2sh);
+        o.redeemscript = Some(format!("0014{}", "33".repeat(20)));
+        assert_eq!(AddressType::from_output(&o), AddressType::P2shP2wpkh);
+    }
+
+    #[test]
+    fn test_reserved_output() {
+        let o: ListfundsOutputs = serde_json::from_value(json!({
+            "txid": "01".repeat(32),
+            "output": 1,
+            "amount_msat": 1_000_000,
+            "scriptpubkey": "0014cf7e4c9fa2e0fa64d8a0cbf3e6c2b4e7d21d5a7b",
+            "status": "confirmed",
+            "blockheight": 100,
+            "reserved": true,
+            "reserved_to_block": 172,
+        }))
+        .unwrap();
+        assert!(o.reserved);
+        assert_eq!(o.reserved_to_block, Some(172));
+        assert!(!UtxoFilter::default().matches(&o, 110));
+
+        let o = output(&"01".repeat(32), 1_000_000, "confirmed", Some(100));
+        assert_eq!(o.reserved_to_block, None);
+    }
+
+    #[test]
+    fn test_select() {
+        let outputs = vec![
+            output(&"01".repeat(32), 1_000_000, "confirmed", Some(100)),
+            output(&"02".repeat(32), 5_000_000, "confirmed", Some(109)),
+            output(&"03".repeat(32), 3_000_000, "confirmed", Some(90)),
+            output(&"04".repeat(32), 9_000_000, "unconfirmed", None),
+            output(&"05".repeat(32), 9_000_000, "spent", Some(90)),
+        ];
+        let filter = UtxoFilter {
+            min_confirmations: 3,
+            ..Default::default()
+        };
+
+        // Output 02 only has 2 confirmations, the unconfirmed and
+        // spent ones are skipped too.
+        let selected = select(&outputs, 110, &filter, Amount::from_msat(3_500_000), 0, 0).unwrap();
+        let txids: Vec<&str> = selected.iter().map(|o| &o.txid[..2]).collect();
+        assert_eq!(txids, vec!["03", "01"]);
+
+        assert!(select(&outputs, 110, &filter, Amount::from_msat(4_000_001), 0, 0).is_err());
+
+        let filter = UtxoFilter {
+            max_amount: Some(Amount::from_msat(2_000_000)),
+            address_types: vec![AddressType::P2wpkh],
+            ..Default::default()
+        };
+        let selected = select(&outputs, 110, &filter, Amount::from_msat(1), 0, 0).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(&selected[0].txid[..2], "01");
+
+        let filter = UtxoFilter {
+            address_types: vec![AddressType::P2tr],
+            ..Default::default()
+        };
+        assert!(select(&outputs, 110, &filter, Amount::from_msat(1), 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_select_fees() {
+        let outputs = vec![
+            output(&"01".repeat(32), 1_000_000, "confirmed", Some(100)),
+            output(&"02".repeat(32), 3_000_000, "confirmed", Some(100)),
+            output(&"03".repeat(32), 200_000, "confirmed", Some(100)),
+        ];
+        let filter = UtxoFilter::default();
+
+        // Each p2wpkh input weighs 272, i.e. costs 272_000msat at
+        // 1000perkw.
+        assert_eq!(AddressType::P2wpkh.input_weight(), 272);
+        assert_eq!(effective_value(&outputs[0], 1000), 728_000);
+        assert_eq!(effective_value(&outputs[2], 1000), -72_000);
+
+        // Without fees the two large outputs exactly cover the target,
+        // with fees they no longer do.
+        let target = Amount::from_msat(4_000_000);
+        assert_eq!(
+            select(&outputs, 110, &filter, target, 0, 0).unwrap().len(),
+            2
+        );
+        assert!(select(&outputs, 110, &filter, target, 0, 1000).is_err());
+
+        // The dust output is never worth adding.
+        let selected = select(
+            &outputs,
+            110,
+            &filter,
+            Amount::from_msat(3_456_000),
+            0,
+            1000,
+        )
+        .unwrap();
+        let txids: Vec<&str> = selected.iter().map(|o| &o.txid[..2]).collect();
+        assert_eq!(txids, vec!["02", "01"]);
+        assert!(select(
+            &outputs,
+            110,
+            &filter,
+            Amount::from_msat(3_456_001),
+            0,
+            1000
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_select_startweight() {
+        let outputs = vec![
+            output(&"01".repeat(32), 1_000_000, "confirmed", Some(100)),
+            output(&"02".repeat(32), 3_000_000, "confirmed", Some(100)),
+        ];
+        let filter = UtxoFilter::default();
+
+        // The larger output is worth 2_728_000msat after its input
+        // fee, which covers the target but not an additional 200
+        // weight for the rest of the transaction, so both are needed.
+        let target = Amount::from_msat(2_600_000);
+        let selected = select(&outputs, 110, &filter, target, 0, 1000).unwrap();
+        assert_eq!(selected.len(), 1);
+        let selected = select(&outputs, 110, &filter, target, 200, 1000).unwrap();
+        assert_eq!(selected.len(), 2);
+
+        // Both together are worth 3_456_000msat, which the target
+        // plus 856 weight exactly uses up.
+        assert!(select(&outputs, 110, &filter, target, 856, 1000).is_ok());
+        assert!(select(&outputs, 110, &filter, target, 857, 1000).is_err());
+    }
+
+    /// The PSBT `utxopsbt` returns, spending a single input.
+    fn psbt() -> String {
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_inner([1; 32]), 0),
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+        encode_psbt(&Psbt::from_unsigned_tx(tx).unwrap())
+    }
+
+    /// A fake `lightningd` answering the calls made by `CoinControl`
+    /// and reporting the `unreserveinputs` calls it sees.
+    async fn mock_node(listener: UnixListener, unreserved: mpsc::UnboundedSender<Value>) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let unreserved = unreserved.clone();
+            tokio::spawn(async move {
+                let mut framed = Framed::new(stream, JsonCodec::default());
+                while let Some(Ok(req)) = framed.next().await {
+                    let result = match req["method"].as_str().unwrap() {
+                        "getinfo" => json!({
+                            "id": "02".repeat(33), "alias": "mock", "num_peers": 0, "num_pending_channels": 0,
+                            "num_active_channels": 0, "num_inactive_channels": 0,
+                            "version": "v22.11", "lightning-dir": "/tmp", "blockheight": 110,
+                            "network": "regtest", "fees_collected_msat": 0, "address": [],
+                            "our_features": {"init": "", "node": "", "channel": "", "invoice": ""},
+                            "color": "000000"
+                        }),
+                        "listfunds" => json!({
+                            "outputs": [
+                                serde_json::to_value(output(&"01".repeat(32), 1_000_000, "confirmed", Some(100))).unwrap(),
+                            ],
+                            "channels": [],
+                        }),
+                        "feerates" => json!({
+                            "perkw": {"opening": 1000, "min_acceptable": 253, "max_acceptable": 100000},
+                        }),
+                        "utxopsbt" => json!({
+                            "psbt": psbt(),
+                            "feerate_per_kw": 253,
+                            "estimated_final_weight": 500,
+                            "excess_msat": 0,
+                        }),
+                        "unreserveinputs" => {
+                            unreserved.send(req["params"].clone()).unwrap();
+                            json!({"reservations": []})
+                        }
+                        m => panic!("unexpected method {}", m),
+                    };
+                    framed
+                        .send(json!({"jsonrpc": "2.0", "id": req["id"], "result": result}))
+                        .await
+                        .unwrap();
+                }
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_release_on_drop() {
+        let path = std::env::temp_dir().join(format!("cln-rpc-coincontrol-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(mock_node(listener, tx));
+
+        let cc = CoinControl::new(&path).reserve_blocks(6);
+        let filter = UtxoFilter::default();
+
+        let reserved = cc
+            .reserve(&filter, Amount::from_sat(500), Feerate::Normal, 0)
+            .await
+            .unwrap();
+        assert_eq!(encode_psbt(reserved.psbt()), psbt());
+        drop(reserved);
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            json!({"psbt": psbt(), "reserve": 6})
+        );
+
+        // Consumed reservations are left alone.
+        let reserved = cc
+            .reserve(&filter, Amount::from_sat(500), Feerate::Normal, 0)
+            .await
+            .unwrap();
+        let _ = reserved.into_psbt();
+        let reserved = cc
+            .reserve(&filter, Amount::from_sat(500), Feerate::Normal, 0)
+            .await
+            .unwrap();
+        reserved.release().await.unwrap();
+        assert_eq!(rx.recv().await.unwrap()["reserve"], json!(6));
+        assert!(rx.try_recv().is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,411 @@
+//! Remote RPC over a lightning peer connection.
+//!
+//! The `commando` plugin lets peers run RPC commands on a node,
+//! authorized by a rune. [`CommandoClient`] connects to the node like
+//! any other peer, and then sends requests and receives replies as
+//! custom messages. This is useful for managing a node from a device
+//! that has no access to its RPC socket, e.g., a mobile wallet.
+//!
+//! ```no_run
+//! use cln_rpc::commando::CommandoClient;
+//! use cln_rpc::model::requests::GetinfoRequest;
+//! use cln_rpc::primitives::PublicKey;
+//! use std::str::FromStr;
+//!
+//! # async fn run() -> Result<(), anyhow::Error> {
+//! let node_id = PublicKey::from_str(
+//!     "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+//! )?;
+//! let mut client = CommandoClient::connect("127.0.0.1:9735", node_id, "<rune>").await?;
+//! let info = client.call_typed(GetinfoRequest::builder().build()).await?;
+//! println!("{} is at height {}", info.id, info.blockheight);
+//! # Ok(())
+//! # }
+//! ```
+use crate::model::IntoRequest;
+use crate::noise::{random_key, NoiseStream};
+use crate::primitives::PublicKey;
+use crate::{parse_response, take_result, Request, Response, Rpc, RpcError};
+use anyhow::{anyhow, Context, Result};
+use bitcoin::secp256k1::SecretKey;
+use log::{debug, trace};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, ToSocketAddrs};
+
+const MSG_INIT: u16 = 16;
+const MSG_ERROR: u16 = 17;
+const MSG_PING: u16 = 18;
+const MSG_PONG: u16 = 19;
+
+/// Request chunks, the last one terminates the request.
+const COMMANDO_CMD_CONTINUES: u16 = 0x4c4d;
+const COMMANDO_CMD_TERM: u16 = 0x4c4f;
+/// Reply chunks, the last one terminates the reply.
+const COMMANDO_REPLY_CONTINUES: u16 = 0x594b;
+const COMMANDO_REPLY_TERM: u16 = 0x594d;
+
+/// The error code `commando` uses for a missing or rejected rune.
+pub const COMMANDO_ERROR_REMOTE_AUTH: i32 = 0x4c51;
+
+/// Requests and replies are split into chunks of at most this many
+/// bytes, leaving room for the message header.
+const CHUNK_SIZE: usize = 65000;
+
+/// An RPC client talking to a node's `commando` plugin over a peer
+/// connection.
+pub struct CommandoClient<S = TcpStream> {
+    conn: NoiseStream<S>,
+    rune: String,
+    next_id: u64,
+}
+
+impl CommandoClient<TcpStream> {
+    /// Connect to the node `node_id` listening at `addr`, using a
+    /// fresh throwaway key as our node id.
+    pub async fn connect<A: ToSocketAddrs>(
+        addr: A,
+        node_id: PublicKey,
+        rune: &str,
+    ) -> Result<CommandoClient<TcpStream>> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .context("connecting to node")?;
+        CommandoClient::from_stream(stream, &random_key(), &node_id, rune).await
+    }
+}
+
+impl<S> CommandoClient<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Perform the handshake with `node_id` over `stream`, using
+    /// `local` as our node key, and exchange `init` messages.
+    pub async fn from_stream(
+        stream: S,
+        local: &SecretKey,
+        node_id: &PublicKey,
+        rune: &str,
+    ) -> Result<CommandoClient<S>> {
+        let mut conn = NoiseStream::initiate(stream, local, node_id)
+            .await
+            .context("handshake with node")?;
+        debug!("Connected to {}", node_id);
+
+        // We don't need any features, so we can keep our `init`
+        // empty: no global features, no features, no TLVs.
+        let mut init = MSG_INIT.to_be_bytes().to_vec();
+        init.extend_from_slice(&[0, 0, 0, 0]);
+        conn.send(&init).await?;
+
+        let msg = conn.recv().await?;
+        match msg_type(&msg)? {
+            MSG_INIT => {}
+            MSG_ERROR => return Err(anyhow!("node sent error: {}", error_text(&msg))),
+            t => return Err(anyhow!("expected init, got message of type {}", t)),
+        }
+
+        Ok(CommandoClient {
+            conn,
+            rune: rune.to_string(),
+            next_id: 1,
+        })
+    }
+
+    pub async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        let mut req = serde_json::to_value(req).map_err(|e| RpcError {
+            code: None,
+            message: format!("Error parsing request: {}", e),
+            data: None,
+        })?;
+        let params = req["params"].take();
+        let method = req["method"].as_str().unwrap_or_default();
+        let result = self.call_raw(method, params).await?;
+        parse_response(&req["method"], json!({ "result": result }))
+    }
+
+    pub async fn call_typed<R: IntoRequest>(
+        &mut self,
+        request: R,
+    ) -> Result<R::Response, RpcError> {
+        Ok(self
+            .call(request.into())
+            .await?
+            .try_into()
+            .expect("CLN will reply correctly"))
+    }
+
+    /// Run `method` with `params` on the node, returning the JSON
+    /// `result`.
+    pub async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let req = json!({
+            "method": method,
+            "id": id,
+            "params": params,
+            "rune": self.rune,
+        });
+        trace!("Sending request {}", crate::redact::redact(&req));
+        let reply = self
+            .exchange(req.to_string().as_bytes())
+            .await
+            .map_err(|e| RpcError {
+                code: None,
+                message: format!("commando request failed: {}", e),
+                data: None,
+            })?;
+        let response: Value = serde_json::from_slice(&reply).map_err(|e| RpcError {
+            code: None,
+            message: format!("Malformed response from commando: {}", e),
+            data: None,
+        })?;
+        trace!("Read response {}", crate::redact::redact(&response));
+        match response.get("id") {
+            // `commando` errors, e.g., for a bad rune, don't carry an id.
+            None => {}
+            Some(rid) if rid == &json!(id) => {}
+            Some(rid) => debug!("Reply with wrong id: sent {}, got {}", id, rid),
+        }
+        take_result(response)
+    }
+
+    /// Send a request and collect the chunks of its reply.
+    async fn exchange(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+        let cmd_id: u64 = rand::random();
+        let chunks: Vec<&[u8]> = request.chunks(CHUNK_SIZE).collect();
+        let last = chunks.len() - 1;
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let t = if i == last {
+                COMMANDO_CMD_TERM
+            } else {
+                COMMANDO_CMD_CONTINUES
+            };
+            let mut msg = t.to_be_bytes().to_vec();
+            msg.extend_from_slice(&cmd_id.to_be_bytes());
+            msg.extend_from_slice(chunk);
+            self.conn.send(&msg).await?;
+        }
+
+        let mut reply = vec![];
+        loop {
+            let msg = self.conn.recv().await?;
+            let t = msg_type(&msg)?;
+            match t {
+                MSG_PING => self.pong(&msg).await?,
+                MSG_ERROR => return Err(anyhow!("node sent error: {}", error_text(&msg))),
+                COMMANDO_REPLY_CONTINUES | COMMANDO_REPLY_TERM => {
+                    if msg.len() < 10 {
+                        return Err(anyhow!("truncated commando reply"));
+                    }
+                    let id = u64::from_be_bytes(msg[2..10].try_into().unwrap());
+                    if id != cmd_id {
+                        debug!("Ignoring reply to unknown command {}", id);
+                        continue;
+                    }
+                    reply.extend_from_slice(&msg[10..]);
+                    if t == COMMANDO_REPLY_TERM {
+                        return Ok(reply);
+                    }
+                }
+                // Gossip and the like, which we don't care about.
+                t => trace!("Ignoring message of type {}", t),
+            }
+        }
+    }
+
+    /// Answer a `ping`, so the node doesn't hang up on us.
+    async fn pong(&mut self, ping: &[u8]) -> Result<()> {
+        if ping.len() < 4 {
+            return Err(anyhow!("truncated ping"));
+        }
+        let num_pong_bytes = u16::from_be_bytes([ping[2], ping[3]]);
+        // Pings asking for more than this don't expect a reply.
+        if num_pong_bytes >= 65532 {
+            return Ok(());
+        }
+        let mut pong = MSG_PONG.to_be_bytes().to_vec();
+        pong.extend_from_slice(&num_pong_bytes.to_be_bytes());
+        pong.resize(pong.len() + num_pong_bytes as usize, 0);
+        self.conn.send(&pong).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> Rpc for CommandoClient<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        CommandoClient::call_raw(self, method, params).await
+    }
+
+    async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        CommandoClient::call(self, req).await
+    }
+}
+
+fn msg_type(msg: &[u8]) -> Result<u16> {
+    if msg.len() < 2 {
+        return Err(anyhow!("message is too short to contain a type"));
+    }
+    Ok(u16::from_be_bytes([msg[0], msg[1]]))
+}
+
+/// The text of an `error` message: the type, `channel_id`, then a
+/// `u16` length-prefixed string.
+fn error_text(msg: &[u8]) -> String {
+    let data = msg.get(34..36).and_then(|len| {
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        msg.get(36..36 + len)
+    });
+    match data {
+        Some(data) => String::from_utf8_lossy(data).to_string(),
+        None => "malformed error".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::requests::{GetinfoRequest, InvoiceRequest};
+    use crate::primitives::{Amount, AmountOrAny};
+    use bitcoin::secp256k1::Secp256k1;
+
+    const RUNE: &str = "OSqc7ixY6F-gjcigBfxtzKUI54uzgFSA6YfBQoWGDV89MA==";
+
+    fn key(b: u8) -> SecretKey {
+        SecretKey::from_slice(&[b; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_error_text() {
+        let mut msg = MSG_ERROR.to_be_bytes().to_vec();
+        msg.extend_from_slice(&[0; 32]);
+        msg.extend_from_slice(&5u16.to_be_bytes());
+        msg.extend_from_slice(b"oops!");
+        assert_eq!(error_text(&msg), "oops!");
+        // Anything after the data is ignored.
+        msg.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(error_text(&msg), "oops!");
+        assert_eq!(error_text(&msg[..38]), "malformed error");
+        assert_eq!(error_text(&msg[..35]), "malformed error");
+    }
+
+    fn header(t: u16, id: u64) -> Vec<u8> {
+        let mut msg = t.to_be_bytes().to_vec();
+        msg.extend_from_slice(&id.to_be_bytes());
+        msg
+    }
+
+    /// The node side of commando: reassembles requests, checks the
+    /// rune and replies in two chunks.
+    async fn responder<S: AsyncRead + AsyncWrite + Unpin>(stream: S, node: SecretKey) {
+        let mut conn = NoiseStream::respond(stream, &node).await.unwrap();
+        assert_eq!(msg_type(&conn.recv().await.unwrap()).unwrap(), MSG_INIT);
+        conn.send(&[0, 16, 0, 0, 0, 2, 0x02, 0x00]).await.unwrap();
+
+        loop {
+            let mut request = vec![];
+            let cmd_id = loop {
+                let msg = match conn.recv().await {
+                    Ok(msg) => msg,
+                    Err(_) => return,
+                };
+                request.extend_from_slice(&msg[10..]);
+                match msg_type(&msg).unwrap() {
+                    COMMANDO_CMD_CONTINUES => continue,
+                    COMMANDO_CMD_TERM => break u64::from_be_bytes(msg[2..10].try_into().unwrap()),
+                    t => panic!("unexpected message type {}", t),
+                }
+            };
+            let request: Value = serde_json::from_slice(&request).unwrap();
+
+            // Make sure the client keeps the connection alive.
+            conn.send(&[0, 18, 0, 4, 0, 0]).await.unwrap();
+            assert_eq!(conn.recv().await.unwrap(), vec![0, 19, 0, 4, 0, 0, 0, 0]);
+
+            let reply = if request["rune"] != RUNE {
+                json!({"error": {"code": COMMANDO_ERROR_REMOTE_AUTH, "message": "Not authorized: Invalid rune"}})
+            } else if request["method"] == "getinfo" {
+                json!({"jsonrpc": "2.0", "id": request["id"], "result": {
+                    "id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+                    "alias": "LOUDTRAIN",
+                    "color": "02eec7",
+                    "num_peers": 0,
+                    "num_pending_channels": 0,
+                    "num_active_channels": 0,
+                    "num_inactive_channels": 0,
+                    "version": "v23.02",
+                    "lightning-dir": "/tmp/l1/regtest",
+                    "our_features": {"init": "", "node": "", "channel": "", "invoice": ""},
+                    "blockheight": 103,
+                    "network": "regtest",
+                    "fees_collected_msat": 0,
+                    "address": [],
+                    "binding": []
+                }})
+            } else {
+                let len = request["params"]["description"].as_str().unwrap().len();
+                json!({"jsonrpc": "2.0", "id": request["id"], "error": {
+                    "code": -32602, "message": format!("description of {} bytes", len)
+                }})
+            };
+
+            // A reply to some other command, which must be ignored.
+            let mut stray = header(COMMANDO_REPLY_TERM, cmd_id ^ 1);
+            stray.extend_from_slice(b"{}");
+            conn.send(&stray).await.unwrap();
+
+            let reply = reply.to_string().into_bytes();
+            let (a, b) = reply.split_at(reply.len() / 2);
+            let mut msg = header(COMMANDO_REPLY_CONTINUES, cmd_id);
+            msg.extend_from_slice(a);
+            conn.send(&msg).await.unwrap();
+            let mut msg = header(COMMANDO_REPLY_TERM, cmd_id);
+            msg.extend_from_slice(b);
+            conn.send(&msg).await.unwrap();
+        }
+    }
+
+    async fn client(rune: &str) -> CommandoClient<tokio::io::DuplexStream> {
+        let (a, b) = tokio::io::duplex(1 << 16);
+        let node = key(0x21);
+        let node_id = PublicKey::from_secret_key(&Secp256k1::signing_only(), &node);
+        tokio::spawn(responder(b, node));
+        CommandoClient::from_stream(a, &key(0x11), &node_id, rune)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let mut client = client(RUNE).await;
+        let info = client.call_typed(GetinfoRequest {}).await.unwrap();
+        assert_eq!(info.alias, "LOUDTRAIN");
+        assert_eq!(info.blockheight, 103);
+
+        // Large requests are split across several messages.
+        let req = InvoiceRequest {
+            amount_msat: AmountOrAny::Amount(Amount::from_msat(1000)),
+            description: "x".repeat(150_000),
+            label: "big".to_string(),
+            expiry: None,
+            fallbacks: None,
+            preimage: None,
+            exposeprivatechannels: None,
+            cltv: None,
+            deschashonly: None,
+        };
+        let err = client.call_typed(req).await.unwrap_err();
+        assert_eq!(err.code, Some(-32602));
+        assert_eq!(err.message, "description of 150000 bytes");
+    }
+
+    #[tokio::test]
+    async fn test_bad_rune() {
+        let mut client = client("bad").await;
+        let err = client.call_typed(GetinfoRequest {}).await.unwrap_err();
+        assert_eq!(err.code, Some(COMMANDO_ERROR_REMOTE_AUTH));
+    }
+}
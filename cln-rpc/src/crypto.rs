@@ -0,0 +1,235 @@
+//! Offline `signmessage` and `checkmessage`.
+//!
+//! `lightningd` signs `SHA256(SHA256("Lightning Signed Message:" ||
+//! message))` with the node key, and encodes the recoverable signature
+//! as zbase32, prefixed by a byte holding `31 + recovery id`. This is
+//! the same format `lnd` uses. The functions here produce exactly the
+//! [`SignmessageResponse`] `lightningd` would, and verify signatures
+//! without talking to a node.
+use crate::model::requests::CheckmessageRequest;
+use crate::model::responses::{CheckmessageResponse, SignmessageResponse};
+use crate::primitives::PublicKey;
+use anyhow::{anyhow, Result};
+use bitcoin::hashes::{sha256d, Hash, HashEngine};
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+
+/// Prepended to every message before hashing, so a message signature
+/// can never be mistaken for a signature on a protocol message.
+pub const MESSAGE_PREFIX: &str = "Lightning Signed Message:";
+
+/// `lightningd` refuses to sign longer messages.
+pub const MAX_MESSAGE_LEN: usize = 65535;
+
+const ZBASE32_CHARS: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// Encode `data` as zbase32, padding the last character with zero
+/// bits.
+pub fn zbase32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for b in data {
+        acc = (acc << 8 | *b as u32) & 0xffff;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ZBASE32_CHARS[(acc >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ZBASE32_CHARS[(acc << (5 - bits)) as usize & 31] as char);
+    }
+    out
+}
+
+/// Decode a zbase32 string. Leftover bits at the end must be zero
+/// and make up less than a byte.
+pub fn zbase32_decode(s: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for c in s.bytes() {
+        let v = ZBASE32_CHARS
+            .iter()
+            .position(|z| *z == c)
+            .ok_or_else(|| anyhow!("zbase is not valid zbase32"))?;
+        acc = (acc << 5 | v as u32) & 0xffff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        return Err(anyhow!("zbase is not valid zbase32"));
+    }
+    Ok(out)
+}
+
+/// The hash that gets signed for `message`.
+pub fn message_hash(message: &str) -> Message {
+    let mut engine = sha256d::Hash::engine();
+    engine.input(MESSAGE_PREFIX.as_bytes());
+    engine.input(message.as_bytes());
+    Message::from_slice(&sha256d::Hash::from_engine(engine)[..]).unwrap()
+}
+
+/// Sign `message` with the node `key`, like `signmessage` does.
+pub fn sign_message(key: &SecretKey, message: &str) -> Result<SignmessageResponse> {
+    if message.len() > MAX_MESSAGE_LEN {
+        return Err(anyhow!("Message must be < 64k"));
+    }
+    let sig = Secp256k1::signing_only().sign_ecdsa_recoverable(&message_hash(message), key);
+    let (recid, compact) = sig.serialize_compact();
+    let recid = recid.to_i32() as u8;
+
+    let mut zbase = vec![recid + 31];
+    zbase.extend_from_slice(&compact);
+    Ok(SignmessageResponse {
+        signature: hex::encode(compact),
+        recid: hex::encode([recid]),
+        zbase: zbase32_encode(&zbase),
+    })
+}
+
+/// Recover the key that signed `message`, giving the `zbase` output
+/// of `signmessage`. This only says who signed it: compare the result
+/// with the key you expect.
+pub fn recover_message_signer(message: &str, zbase: &str) -> Result<PublicKey> {
+    let sig = zbase32_decode(zbase)?;
+    if sig.len() != 65 {
+        return Err(anyhow!(
+            "zbase is too {}",
+            if sig.len() < 65 { "short" } else { "long" }
+        ));
+    }
+    let sig = sig[0]
+        .checked_sub(31)
+        .and_then(|r| RecoveryId::from_i32(r as i32).ok())
+        .and_then(|r| RecoverableSignature::from_compact(&sig[1..], r).ok())
+        .ok_or_else(|| anyhow!("cannot parse zbase signature"))?;
+    Secp256k1::verification_only()
+        .recover_ecdsa(&message_hash(message), &sig)
+        .map_err(|_| anyhow!("cannot recover key from zbase signature"))
+}
+
+/// Check a signature locally, like `checkmessage` does. Without a
+/// `pubkey` in the request `lightningd` would look the signer up in
+/// the network graph, which we can't do, so the result is never
+/// `verified` in that case.
+pub fn check_message(req: &CheckmessageRequest) -> Result<CheckmessageResponse> {
+    let pubkey = recover_message_signer(&req.message, &req.zbase)?;
+    Ok(CheckmessageResponse {
+        verified: req.pubkey == Some(pubkey),
+        pubkey,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_zbase32() {
+        // From contrib/pyln-proto/tests/test_zbase32.py
+        let vectors = [
+            ("this", "qtwg1ha"),
+            ("is", "pf3o"),
+            ("a", "cr"),
+            ("split", "qpaga4mw"),
+            ("message:", "pi1zgh5bc71uw"),
+            ("lightning", "ptwsq4dwp3wsh3a"),
+            ("rocks", "qjzsg45u"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(zbase32_encode(plain.as_bytes()), encoded);
+            assert_eq!(zbase32_decode(encoded).unwrap(), plain.as_bytes());
+        }
+
+        let data = hex::decode("1f76e8acd54afbf23610b7166ba689afcc9e8ec3c44e442e765012dfc1d299958827d0205f7e4e1a12620e7fc8ce1c7d3651acefde899c33f12b6958d3304106a0").unwrap();
+        let encoded = "d75qtmgijm79rpooshmgzjwji9gj7dsdat8remuskyjp9oq1ugkaoj6orbxzhuo4njtyh96e3aq84p1tiuz77nchgxa1s4ka4carnbiy";
+        assert_eq!(zbase32_encode(&data), encoded);
+        assert_eq!(zbase32_decode(encoded).unwrap(), data);
+
+        for bad in ["00", "[]", "vv", "1234", "y"] {
+            assert!(zbase32_decode(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_recover() {
+        // From test_signmessage and test_checkmessage_pubkey_not_found
+        // in tests/test_misc.py
+        let corpus = [
+            (
+                "is this compatible?",
+                "rbgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+                "02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5",
+            ),
+            (
+                "hi",
+                "rnrphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+                "02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b",
+            ),
+            (
+                "hi",
+                "ry8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
+                "022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931",
+            ),
+            (
+                "testcase to check new rpc error",
+                "d66bqz3qsku5fxtqsi37j11pci47ydxa95iusphutggz9ezaxt56neh77kxe5hyr41kwgkncgiu94p9ecxiexgpgsz8daoq4tw8kj8yx",
+                "03be3b0e9992153b1d5a6e1623670b6c3663f72ce6cf2e0dd39c0a373a7de5a3b7",
+            ),
+        ];
+        for (message, zbase, pubkey) in corpus {
+            let pubkey = PublicKey::from_str(pubkey).unwrap();
+            assert_eq!(recover_message_signer(message, zbase).unwrap(), pubkey);
+
+            let mut req = CheckmessageRequest {
+                message: message.to_string(),
+                zbase: zbase.to_string(),
+                pubkey: Some(pubkey),
+            };
+            let res = check_message(&req).unwrap();
+            assert!(res.verified);
+            assert_eq!(res.pubkey, pubkey);
+
+            req.message.push_str("modified");
+            assert!(!check_message(&req).unwrap().verified);
+            req.pubkey = None;
+            assert!(!check_message(&req).unwrap().verified);
+        }
+
+        let err = recover_message_signer("hi", "wrong zbase format").unwrap_err();
+        assert_eq!(err.to_string(), "zbase is not valid zbase32");
+        let err = recover_message_signer("hi", "rnrphcjs").unwrap_err();
+        assert_eq!(err.to_string(), "zbase is too short");
+    }
+
+    #[test]
+    fn test_sign() {
+        let key = SecretKey::from_slice(&[0x41; 32]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), &key);
+        let res = sign_message(&key, "this is a test!").unwrap();
+        assert_eq!(res.signature.len(), 128);
+        assert_eq!(res.recid.len(), 2);
+
+        let zbase = zbase32_decode(&res.zbase).unwrap();
+        assert_eq!(hex::encode([zbase[0] - 31]), res.recid);
+        assert_eq!(hex::encode(&zbase[1..]), res.signature);
+        assert_eq!(
+            recover_message_signer("this is a test!", &res.zbase).unwrap(),
+            pubkey
+        );
+
+        // Signing is deterministic.
+        assert_eq!(
+            sign_message(&key, "this is a test!").unwrap().zbase,
+            res.zbase
+        );
+        assert!(sign_message(&key, &"x".repeat(MAX_MESSAGE_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! Typed views on the results of `decodepay` and `decode`.
+//!
+//! The generated [`DecodeResponse`] has to flatten all the things
+//! `decode` may return into a single struct of optional fields. The
+//! [`Decoded`] union instead tells them apart by their `type`, and
+//! checks that the fields required for each kind are present.
+use crate::model::responses::{
+    DecodeFallbacks, DecodeFallbacksType, DecodeInvoice_fallbacks, DecodeInvoice_paths,
+    DecodeOffer_paths, DecodeOffer_recurrence, DecodeResponse, DecodeRestrictions, DecodeType,
+    DecodepayFallbacks, DecodepayFallbacksType, DecodepayResponse,
+};
+use crate::primitives::{Amount, Features, PublicKey, Routehint, Secret, Sha256};
+use anyhow::{anyhow, Context, Error, Result};
+use bitcoin::hashes::Hash;
+use std::str::FromStr;
+
+/// Something `lightningd` was able to decode.
+#[derive(Clone, Debug)]
+pub enum Decoded {
+    Bolt11Invoice(Bolt11Invoice),
+    Bolt12Offer(Bolt12Offer),
+    Bolt12InvoiceRequest(Box<Bolt12InvoiceRequest>),
+    Bolt12Invoice(Box<Bolt12Invoice>),
+    Rune(DecodedRune),
+}
+
+#[derive(Clone, Debug)]
+pub struct Bolt11Invoice {
+    pub currency: String,
+    pub created_at: u64,
+    pub expiry: u64,
+    pub payee: PublicKey,
+    pub amount_msat: Option<Amount>,
+    pub payment_hash: Sha256,
+    pub signature: String,
+    pub description: Option<String>,
+    pub description_hash: Option<Sha256>,
+    pub min_final_cltv_expiry: u32,
+    pub payment_secret: Option<Secret>,
+    pub features: Features,
+    pub payment_metadata: Option<Vec<u8>>,
+    pub fallbacks: Vec<DecodepayFallbacks>,
+    pub routes: Vec<Routehint>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bolt12Offer {
+    /// Only present when decoding an offer itself.
+    pub offer_id: Option<Sha256>,
+    pub chains: Vec<String>,
+    pub metadata: Option<Vec<u8>>,
+    pub currency: Option<String>,
+    pub amount: Option<u64>,
+    pub amount_msat: Option<Amount>,
+    pub description: String,
+    pub issuer: Option<String>,
+    pub features: Features,
+    pub absolute_expiry: Option<u64>,
+    pub quantity_max: Option<u64>,
+    pub paths: Vec<DecodeOffer_paths>,
+    pub node_id: PublicKey,
+    pub recurrence: Option<DecodeOffer_recurrence>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bolt12InvoiceRequest {
+    pub offer: Bolt12Offer,
+    pub metadata: Vec<u8>,
+    pub payer_id: PublicKey,
+    pub chain: Option<String>,
+    pub amount_msat: Option<Amount>,
+    pub features: Features,
+    pub quantity: Option<u64>,
+    pub payer_note: Option<String>,
+    pub recurrence_counter: Option<u32>,
+    pub recurrence_start: Option<u32>,
+    /// Only present when decoding an invoice_request itself.
+    pub signature: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bolt12Invoice {
+    pub invoice_request: Bolt12InvoiceRequest,
+    pub paths: Vec<DecodeInvoice_paths>,
+    pub created_at: u64,
+    pub relative_expiry: Option<u32>,
+    pub payment_hash: Sha256,
+    pub amount_msat: Amount,
+    pub fallbacks: Vec<DecodeInvoice_fallbacks>,
+    pub features: Features,
+    pub node_id: Option<PublicKey>,
+    pub recurrence_basetime: Option<u64>,
+    pub signature: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct DecodedRune {
+    pub string: String,
+    pub unique_id: Option<String>,
+    pub version: Option<String>,
+    pub restrictions: Vec<DecodeRestrictions>,
+}
+
+fn required<T>(v: Option<T>, name: &str) -> Result<T> {
+    v.ok_or_else(|| anyhow!("Missing field {} in decoded response", name))
+}
+
+fn sha256(s: &str) -> Result<Sha256> {
+    let b = hex::decode(s).with_context(|| format!("Malformed hash {}", s))?;
+    Ok(Sha256::from_slice(&b)?)
+}
+
+fn pubkey(s: &str) -> Result<PublicKey> {
+    PublicKey::from_str(s).with_context(|| format!("Malformed public key {}", s))
+}
+
+fn features(s: Option<String>) -> Result<Features> {
+    s.map(|s| Features::from_str(&s))
+        .transpose()
+        .map(|f| f.unwrap_or_default())
+}
+
+fn bytes(s: Option<String>) -> Result<Option<Vec<u8>>> {
+    s.map(hex::decode).transpose().map_err(Error::from)
+}
+
+fn secret(s: Option<String>) -> Result<Option<Secret>> {
+    bytes(s)?.map(Secret::try_from).transpose()
+}
+
+/// `decode` reports the same fallbacks as `decodepay`, but its schema
+/// has to allow for the bolt12 ones too.
+fn fallback(f: DecodeFallbacks) -> Result<DecodepayFallbacks> {
+    let item_type = match required(f.item_type, "fallbacks[].type")? {
+        DecodeFallbacksType::P2PKH => DecodepayFallbacksType::P2PKH,
+        DecodeFallbacksType::P2SH => DecodepayFallbacksType::P2SH,
+        DecodeFallbacksType::P2WPKH => DecodepayFallbacksType::P2WPKH,
+        DecodeFallbacksType::P2WSH => DecodepayFallbacksType::P2WSH,
+    };
+    Ok(DecodepayFallbacks {
+        item_type,
+        addr: f.addr,
+        hex: f.hex,
+    })
+}
+
+impl TryFrom<DecodepayResponse> for Bolt11Invoice {
+    type Error = Error;
+
+    fn try_from(r: DecodepayResponse) -> Result<Self> {
+        Ok(Bolt11Invoice {
+            currency: r.currency,
+            created_at: r.created_at,
+            expiry: r.expiry,
+            payee: r.payee,
+            amount_msat: r.amount_msat,
+            payment_hash: sha256(&r.payment_hash)?,
+            signature: r.signature,
+            description: r.description,
+            description_hash: r.description_hash.as_deref().map(sha256).transpose()?,
+            min_final_cltv_expiry: r.min_final_cltv_expiry,
+            payment_secret: secret(r.payment_secret)?,
+            features: features(r.features)?,
+            payment_metadata: bytes(r.payment_metadata)?,
+            fallbacks: r.fallbacks.unwrap_or_default(),
+            routes: r.routes.map(|r| r.hints).unwrap_or_default(),
+        })
+    }
+}
+
+impl Bolt12Offer {
+    fn from_decode(r: &mut DecodeResponse) -> Result<Self> {
+        Ok(Bolt12Offer {
+            offer_id: r.offer_id.as_deref().map(sha256).transpose()?,
+            chains: r.offer_chains.take().unwrap_or_default(),
+            metadata: bytes(r.offer_metadata.take())?,
+            currency: r.offer_currency.take(),
+            amount: r.offer_amount,
+            amount_msat: r.offer_amount_msat,
+            description: required(r.offer_description.take(), "offer_description")?,
+            issuer: r.offer_issuer.take(),
+            features: features(r.offer_features.take())?,
+            absolute_expiry: r.offer_absolute_expiry,
+            quantity_max: r.offer_quantity_max,
+            paths: r.offer_paths.take().unwrap_or_default(),
+            node_id: required(r.offer_node_id, "offer_node_id")?,
+            recurrence: r.offer_recurrence.take(),
+        })
+    }
+}
+
+impl Bolt12InvoiceRequest {
+    fn from_decode(r: &mut DecodeResponse) -> Result<Self> {
+        Ok(Bolt12InvoiceRequest {
+            offer: Bolt12Offer::from_decode(r)?,
+            metadata: required(bytes(r.invreq_metadata.take())?, "invreq_metadata")?,
+            payer_id: pubkey(&required(r.invreq_payer_id.take(), "invreq_payer_id")?)?,
+            chain: r.invreq_chain.take(),
+            amount_msat: r.invreq_amount_msat,
+            features: features(r.invreq_features.take())?,
+            quantity: r.invreq_quantity,
+            payer_note: r.invreq_payer_note.take(),
+            recurrence_counter: r.invreq_recurrence_counter,
+            recurrence_start: r.invreq_recurrence_start,
+            signature: r.signature.clone(),
+        })
+    }
+}
+
+impl DecodeResponse {
+    /// Collect all `warning_*` fields that are set.
+    pub fn warnings(&self) -> Vec<String> {
+        let v = serde_json::to_value(self).unwrap_or_default();
+        v.as_object()
+            .map(|o| {
+                o.iter()
+                    .filter(|(k, _)| k.starts_with("warning_"))
+                    .map(|(k, v)| format!("{}: {}", k, v.as_str().unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TryFrom<DecodeResponse> for Decoded {
+    type Error = Error;
+
+    fn try_from(mut r: DecodeResponse) -> Result<Self> {
+        if !r.valid {
+            return Err(anyhow!(
+                "Invalid {:?}: {}",
+                r.item_type,
+                r.warnings().join(", ")
+            ));
+        }
+
+        Ok(match r.item_type {
+            DecodeType::BOLT11_INVOICE => Decoded::Bolt11Invoice(Bolt11Invoice {
+                currency: required(r.currency, "currency")?,
+                created_at: required(r.created_at, "created_at")?,
+                expiry: required(r.expiry, "expiry")?,
+                payee: required(r.payee, "payee")?,
+                amount_msat: r.amount_msat,
+                payment_hash: sha256(&required(r.payment_hash, "payment_hash")?)?,
+                signature: required(r.signature, "signature")?,
+                description: r.description,
+                description_hash: r.description_hash.as_deref().map(sha256).transpose()?,
+                min_final_cltv_expiry: required(r.min_final_cltv_expiry, "min_final_cltv_expiry")?,
+                payment_secret: secret(r.payment_secret)?,
+                features: features(r.features)?,
+                payment_metadata: bytes(r.payment_metadata)?,
+                fallbacks: r
+                    .fallbacks
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(fallback)
+                    .collect::<Result<_>>()?,
+                routes: r.routes.map(|r| r.hints).unwrap_or_default(),
+            }),
+            DecodeType::BOLT12_OFFER => Decoded::Bolt12Offer(Bolt12Offer::from_decode(&mut r)?),
+            DecodeType::BOLT12_INVOICE_REQUEST => {
+                let invreq = Bolt12InvoiceRequest::from_decode(&mut r)?;
+                required(invreq.signature.as_ref(), "signature")?;
+                Decoded::Bolt12InvoiceRequest(Box::new(invreq))
+            }
+            DecodeType::BOLT12_INVOICE => {
+                let mut invoice_request = Bolt12InvoiceRequest::from_decode(&mut r)?;
+                // The signature belongs to the invoice, not the request.
+                invoice_request.signature = None;
+                Decoded::Bolt12Invoice(Box::new(Bolt12Invoice {
+                    invoice_request,
+                    paths: required(r.invoice_paths, "invoice_paths")?,
+                    created_at: required(r.invoice_created_at, "invoice_created_at")?,
+                    relative_expiry: r.invoice_relative_expiry,
+                    payment_hash: sha256(&required(
+                        r.invoice_payment_hash,
+                        "invoice_payment_hash",
+                    )?)?,
+                    amount_msat: required(r.invoice_amount_msat, "invoice_amount_msat")?,
+                    fallbacks: r.invoice_fallbacks.unwrap_or_default(),
+                    features: features(r.invoice_features)?,
+                    node_id: r.invoice_node_id,
+                    recurrence_basetime: r.invoice_recurrence_basetime,
+                    signature: required(r.signature, "signature")?,
+                }))
+            }
+            DecodeType::RUNE => Decoded::Rune(DecodedRune {
+                string: required(r.string, "string")?,
+                unique_id: r.unique_id,
+                version: r.version,
+                restrictions: required(r.restrictions, "restrictions")?,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::Response;
+    use serde_json::json;
+
+    #[test]
+    fn test_decodepay() {
+        let r: DecodepayResponse = serde_json::from_value(json!({
+            "currency": "bcrt",
+            "created_at": 1667237562,
+            "expiry": 604800,
+            "payee": "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518",
+            "amount_msat": 1000,
+            "description": "desc",
+            "min_final_cltv_expiry": 5,
+            "payment_secret": "5bc4ab1f5b2e05f9fbd5b1a3a6c33d6f9bbf85b8a1e1a7b6e1ad04d7a24be2b2",
+            "features": "02024100",
+            "routes": [[{
+                "pubkey": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                "short_channel_id": "103x1x0",
+                "fee_base_msat": 1,
+                "fee_proportional_millionths": 10,
+                "cltv_expiry_delta": 6
+            }]],
+            "payment_hash": "d17a42c4f7f49648064a0ce7ce848bd92c4c50f24d35fe5c3d1f3a7a9bf474b2",
+            "signature": "3045022100"
+        }))
+        .unwrap();
+
+        let inv: Bolt11Invoice = r.clone().try_into().unwrap();
+        assert_eq!(inv.routes.len(), 1);
+        assert_eq!(inv.routes[0].hops[0].scid.to_string(), "103x1x0");
+        assert_eq!(inv.routes[0].hops[0].feebase, Amount::from_msat(1));
+        assert_eq!(inv.routes[0].hops[0].expirydelta, 6);
+        // var_onion_optin (8), payment_secret (14) and basic_mpp (17)
+        assert!(inv.features.has(8));
+        assert!(inv.features.has(14));
+        assert!(inv.features.supports(16));
+        assert!(!inv.features.has(16));
+        assert_eq!(inv.features.bits(), vec![8, 14, 17, 25]);
+
+        // The route hints survive a round trip through JSON
+        let v = serde_json::to_value(&r).unwrap();
+        assert_eq!(v["routes"][0][0]["short_channel_id"], "103x1x0");
+        assert_eq!(v["routes"][0][0]["cltv_expiry_delta"], 6);
+    }
+
+    #[test]
+    fn test_decode_union() {
+        let offer: Response = serde_json::from_value(json!({
+            "method": "decode",
+            "result": {
+                "type": "bolt12 offer",
+                "valid": true,
+                "offer_id": "9c5ee7b5a1b5fc3ef4ec23d9e5e45ba4b5bb1e2b5c5cc6b94fdc47e0a1b4d7d4",
+                "offer_description": "coffee",
+                "offer_amount_msat": 10000,
+                "offer_node_id": "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518",
+                "offer_features": "0100"
+            }
+        }))
+        .unwrap();
+        let offer: DecodeResponse = offer.try_into().unwrap();
+        match Decoded::try_from(offer).unwrap() {
+            Decoded::Bolt12Offer(o) => {
+                assert_eq!(o.description, "coffee");
+                assert_eq!(o.amount_msat, Some(Amount::from_msat(10000)));
+                assert!(o.features.has(8));
+            }
+            d => panic!("Unexpected decode result {:?}", d),
+        }
+
+        let invoice: DecodeResponse = serde_json::from_value(json!({
+            "type": "bolt11 invoice",
+            "valid": true,
+            "currency": "bcrt",
+            "created_at": 1667237562,
+            "expiry": 604800,
+            "payee": "0266e4598d1d3c415f572a8488830b60f7e744ed9235eb0b1ba93283b315c03518",
+            "min_final_cltv_expiry": 5,
+            "payment_hash": "d17a42c4f7f49648064a0ce7ce848bd92c4c50f24d35fe5c3d1f3a7a9bf474b2",
+            "signature": "3045022100",
+            "fallbacks": [{
+                "type": "P2WPKH",
+                "addr": "bcrt1qkyrst0gpwqcljfdrq6rshkd9hhgzcf2jjdgvw2",
+                "hex": "0014b10705bd01703f925a3068705ee6a5bdd02c2552"
+            }]
+        }))
+        .unwrap();
+        match Decoded::try_from(invoice).unwrap() {
+            Decoded::Bolt11Invoice(i) => {
+                assert_eq!(i.fallbacks.len(), 1);
+                assert!(matches!(
+                    i.fallbacks[0].item_type,
+                    DecodepayFallbacksType::P2WPKH
+                ));
+                assert_eq!(
+                    i.fallbacks[0].hex,
+                    "0014b10705bd01703f925a3068705ee6a5bdd02c2552"
+                );
+            }
+            d => panic!("Unexpected decode result {:?}", d),
+        }
+
+        let rune: DecodeResponse = serde_json::from_value(json!({
+            "type": "rune",
+            "valid": true,
+            "string": "4f1f1d0cc7=0&method=getinfo",
+            "unique_id": "0",
+            "restrictions": [{"alternatives": ["method=getinfo"], "summary": "method (of command) equal to 'getinfo'"}]
+        }))
+        .unwrap();
+        match Decoded::try_from(rune).unwrap() {
+            Decoded::Rune(r) => assert_eq!(r.restrictions[0].alternatives, vec!["method=getinfo"]),
+            d => panic!("Unexpected decode result {:?}", d),
+        }
+
+        let invalid: DecodeResponse = serde_json::from_value(json!({
+            "type": "bolt12 offer",
+            "valid": false,
+            "warning_missing_offer_node_id": "offer_node_id is missing"
+        }))
+        .unwrap();
+        let err = Decoded::try_from(invalid).unwrap_err().to_string();
+        assert!(err.contains("offer_node_id is missing"), "{}", err);
+    }
+}
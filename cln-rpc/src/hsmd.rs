@@ -0,0 +1,672 @@
+//! The `hsmd` wire protocol.
+//!
+//! `lightningd` and the subdaemons never touch the node's keys, they
+//! ask `hsmd` to sign or derive whatever they need instead. Each
+//! request is a message from `hsmd/hsmd_wire.csv`, framed by
+//! [`HsmdCodec`], and gets exactly one reply. The messages are
+//! generated into [`crate::wire::hsmd`] and re-exported here, along
+//! with the C types they use.
+//!
+//! [`HsmdClient`] sends requests and waits for their reply, while
+//! [`serve`] answers requests with a [`Handler`], which lets a Rust
+//! process stand in for `hsmd`, e.g., as a signer in tests. A
+//! `Handler` only needs to implement the requests it's expected to
+//! get, the others fail.
+//!
+//! ```no_run
+//! use cln_rpc::hsmd::{serve, Handler, HsmdEcdhReq, HsmdEcdhResp};
+//! use bitcoin::secp256k1::{ecdh::SharedSecret, SecretKey};
+//! use tokio::net::UnixStream;
+//!
+//! struct Signer(SecretKey);
+//!
+//! impl Handler for Signer {
+//!     fn ecdh(&mut self, req: HsmdEcdhReq) -> anyhow::Result<HsmdEcdhResp> {
+//!         let ss = SharedSecret::new(&req.point, &self.0);
+//!         Ok(HsmdEcdhResp { ss: ss.secret_bytes().into() })
+//!     }
+//! }
+//!
+//! # async fn run(stream: UnixStream, key: SecretKey) -> anyhow::Result<()> {
+//! serve(&mut Signer(key), stream).await
+//! # }
+//! ```
+//!
+//! Requests that pass a file descriptor along with the reply, such as
+//! `hsmd_client_hsmfd`, aren't handled here.
+use crate::primitives::{Amount, PublicKey, Secret, Sha256};
+use crate::wire::{len, write_sat, Reader, WireMessage, WireType};
+use anyhow::{anyhow, Context, Result};
+use bitcoin::consensus::encode;
+use bitcoin::psbt::Psbt;
+use bitcoin::{OutPoint, Transaction};
+use bytes::{Buf, BufMut, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use log::trace;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+pub use crate::wire::channel_type::ChannelType;
+pub use crate::wire::hsmd::*;
+
+/// Messages must be shorter than this, like `WIRE_LEN_LIMIT`.
+pub const MAX_MESSAGE_LEN: usize = 1 << 27;
+
+/// The BIP32 version bytes of the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bip32KeyVersion {
+    pub pubkey_version: u32,
+    pub privkey_version: u32,
+}
+
+impl WireType for Bip32KeyVersion {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.pubkey_version.write(buf)?;
+        self.privkey_version.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Bip32KeyVersion {
+            pubkey_version: r.read()?,
+            privkey_version: r.read()?,
+        })
+    }
+}
+
+/// The secrets of a channel, only ever sent to force them in tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secrets {
+    pub funding_privkey: Secret,
+    pub revocation_basepoint_secret: Secret,
+    pub payment_basepoint_secret: Secret,
+    pub delayed_payment_basepoint_secret: Secret,
+    pub htlc_basepoint_secret: Secret,
+}
+
+impl WireType for Secrets {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.funding_privkey.write(buf)?;
+        self.revocation_basepoint_secret.write(buf)?;
+        self.payment_basepoint_secret.write(buf)?;
+        self.delayed_payment_basepoint_secret.write(buf)?;
+        self.htlc_basepoint_secret.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Secrets {
+            funding_privkey: r.read()?,
+            revocation_basepoint_secret: r.read()?,
+            payment_basepoint_secret: r.read()?,
+            delayed_payment_basepoint_secret: r.read()?,
+            htlc_basepoint_secret: r.read()?,
+        })
+    }
+}
+
+/// The basepoints of one side of a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Basepoints {
+    pub revocation: PublicKey,
+    pub payment: PublicKey,
+    pub htlc: PublicKey,
+    pub delayed_payment: PublicKey,
+}
+
+impl WireType for Basepoints {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.revocation.write(buf)?;
+        self.payment.write(buf)?;
+        self.htlc.write(buf)?;
+        self.delayed_payment.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Basepoints {
+            revocation: r.read()?,
+            payment: r.read()?,
+            htlc: r.read()?,
+            delayed_payment: r.read()?,
+        })
+    }
+}
+
+/// Which side of a channel offered an HTLC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+impl WireType for Side {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        match self {
+            Side::Local => 0u8,
+            Side::Remote => 1u8,
+        }
+        .write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        match r.read::<u8>()? {
+            0 => Ok(Side::Local),
+            1 => Ok(Side::Remote),
+            v => Err(anyhow!("invalid side {}", v)),
+        }
+    }
+}
+
+/// An HTLC in a commitment transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimpleHtlc {
+    pub side: Side,
+    pub amount: Amount,
+    pub payment_hash: Sha256,
+    pub cltv_expiry: u32,
+}
+
+impl WireType for SimpleHtlc {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.side.write(buf)?;
+        self.amount.write(buf)?;
+        self.payment_hash.write(buf)?;
+        self.cltv_expiry.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(SimpleHtlc {
+            side: r.read()?,
+            amount: r.read()?,
+            payment_hash: r.read()?,
+            cltv_expiry: r.read()?,
+        })
+    }
+}
+
+/// How to spend an output of a unilateral close.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CloseInfo {
+    pub channel_id: u64,
+    pub peer_id: PublicKey,
+    pub commitment_point: Option<PublicKey>,
+    pub option_anchor_outputs: bool,
+    pub csv: u32,
+}
+
+impl WireType for CloseInfo {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.peer_id.write(buf)?;
+        self.commitment_point.write(buf)?;
+        self.option_anchor_outputs.write(buf)?;
+        self.csv.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(CloseInfo {
+            channel_id: r.read()?,
+            peer_id: r.read()?,
+            commitment_point: r.read()?,
+            option_anchor_outputs: r.read()?,
+            csv: r.read()?,
+        })
+    }
+}
+
+/// A wallet output being spent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Utxo {
+    pub outpoint: OutPoint,
+    pub amount: Amount,
+    pub keyindex: u32,
+    pub is_p2sh: bool,
+    pub script_pubkey: Vec<u8>,
+    pub close_info: Option<CloseInfo>,
+    pub is_in_coinbase: bool,
+}
+
+impl WireType for Utxo {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.outpoint.txid.write(buf)?;
+        self.outpoint.vout.write(buf)?;
+        write_sat(buf, &self.amount)?;
+        self.keyindex.write(buf)?;
+        self.is_p2sh.write(buf)?;
+        len::<u16>(self.script_pubkey.len())?.write(buf)?;
+        buf.extend_from_slice(&self.script_pubkey);
+        self.close_info.write(buf)?;
+        self.is_in_coinbase.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let outpoint = OutPoint {
+            txid: r.read()?,
+            vout: r.read()?,
+        };
+        let amount = r.sat()?;
+        let keyindex = r.read()?;
+        let is_p2sh = r.read()?;
+        let len: u16 = r.read()?;
+        Ok(Utxo {
+            outpoint,
+            amount,
+            keyindex,
+            is_p2sh,
+            script_pubkey: r.take(len as usize)?.to_vec(),
+            close_info: r.read()?,
+            is_in_coinbase: r.read()?,
+        })
+    }
+}
+
+/// A transaction to sign, along with the PSBT holding what's needed
+/// to sign it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitcoinTx {
+    pub tx: Transaction,
+    pub psbt: Psbt,
+}
+
+impl BitcoinTx {
+    pub fn new(psbt: Psbt) -> BitcoinTx {
+        BitcoinTx {
+            tx: psbt.unsigned_tx.clone(),
+            psbt,
+        }
+    }
+}
+
+impl WireType for BitcoinTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let tx = encode::serialize(&self.tx);
+        len::<u32>(tx.len())?.write(buf)?;
+        buf.extend(tx);
+        self.psbt.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u32 = r.read()?;
+        Ok(BitcoinTx {
+            tx: encode::deserialize(r.take(len as usize)?)?,
+            psbt: r.read()?,
+        })
+    }
+}
+
+/// Frames messages with their 4 byte big endian length, like
+/// `wire_sync_read` and `wire_sync_write`.
+#[derive(Default)]
+pub struct HsmdCodec {}
+
+impl Decoder for HsmdCodec {
+    type Item = HsmdMessage;
+    type Error = crate::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<HsmdMessage>> {
+        if buf.len() < 4 {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes(buf[..4].try_into()?) as usize;
+        if len >= MAX_MESSAGE_LEN {
+            return Err(anyhow!("message of {} bytes is too long", len));
+        }
+        if buf.len() < 4 + len {
+            buf.reserve(4 + len - buf.len());
+            return Ok(None);
+        }
+        buf.advance(4);
+        let msg = buf.split_to(len);
+        Ok(Some(HsmdMessage::from_wire(&msg)?))
+    }
+}
+
+impl Encoder<HsmdMessage> for HsmdCodec {
+    type Error = crate::Error;
+
+    fn encode(&mut self, msg: HsmdMessage, buf: &mut BytesMut) -> Result<()> {
+        let msg = msg.to_wire()?;
+        if msg.len() >= MAX_MESSAGE_LEN {
+            return Err(anyhow!("message of {} bytes is too long", msg.len()));
+        }
+        buf.reserve(4 + msg.len());
+        buf.put_u32(msg.len() as u32);
+        buf.put(&msg[..]);
+        Ok(())
+    }
+}
+
+/// A request, and the message `hsmd` replies with.
+pub trait HsmdRequest: WireMessage + Into<HsmdMessage> {
+    type Reply: WireMessage + TryFrom<HsmdMessage, Error = anyhow::Error>;
+}
+
+/// Sends requests to `hsmd`, or anything that [`serve`]s them.
+pub struct HsmdClient<S> {
+    framed: Framed<S, HsmdCodec>,
+}
+
+impl<S> HsmdClient<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(stream: S) -> HsmdClient<S> {
+        HsmdClient {
+            framed: Framed::new(stream, HsmdCodec::default()),
+        }
+    }
+
+    pub async fn call<R: HsmdRequest>(&mut self, req: R) -> Result<R::Reply> {
+        trace!("Sending hsmd request of type {}", R::TYPE);
+        self.framed.send(req.into()).await?;
+        let reply = self
+            .framed
+            .next()
+            .await
+            .ok_or_else(|| anyhow!("hsmd closed the connection"))??;
+        R::Reply::try_from(reply).with_context(|| format!("reply to request {}", R::TYPE))
+    }
+}
+
+macro_rules! requests {
+    ($($req:ident => $reply:ident, $method:ident;)*) => {
+        $(
+            impl HsmdRequest for $req {
+                type Reply = $reply;
+            }
+        )*
+
+        /// Answers `hsmd` requests, one method per request. The default
+        /// implementations fail, so a `Handler` only implements the
+        /// requests it supports.
+        pub trait Handler {
+            $(
+                fn $method(&mut self, _req: $req) -> Result<$reply> {
+                    Err(anyhow!("Unsupported hsmd request {}", <$req>::TYPE))
+                }
+            )*
+        }
+
+        /// Answer `msg` with the method of `handler` for it.
+        pub fn handle<H: Handler + ?Sized>(handler: &mut H, msg: HsmdMessage) -> Result<HsmdMessage> {
+            match msg {
+                $(HsmdMessage::$req(req) => Ok(handler.$method(req)?.into()),)*
+                msg => Err(anyhow!("Unexpected hsmd message type {}", msg.typ())),
+            }
+        }
+    };
+}
+
+requests! {
+    HsmdInit => HsmdInitReplyV2, init;
+    HsmdNewChannel => HsmdNewChannelReply, new_channel;
+    HsmdGetChannelBasepoints => HsmdGetChannelBasepointsReply, get_channel_basepoints;
+    HsmdReadyChannel => HsmdReadyChannelReply, ready_channel;
+    HsmdNodeAnnouncementSigReq => HsmdNodeAnnouncementSigReply, sign_node_announcement;
+    HsmdSignWithdrawal => HsmdSignWithdrawalReply, sign_withdrawal;
+    HsmdSignInvoice => HsmdSignInvoiceReply, sign_invoice;
+    HsmdEcdhReq => HsmdEcdhResp, ecdh;
+    HsmdCannouncementSigReq => HsmdCannouncementSigReply, sign_channel_announcement;
+    HsmdCupdateSigReq => HsmdCupdateSigReply, sign_channel_update;
+    HsmdSignCommitmentTx => HsmdSignCommitmentTxReply, sign_commitment_tx;
+    HsmdValidateCommitmentTx => HsmdValidateCommitmentTxReply, validate_commitment_tx;
+    HsmdValidateRevocation => HsmdValidateRevocationReply, validate_revocation;
+    HsmdSignDelayedPaymentToUs => HsmdSignTxReply, sign_delayed_payment_to_us;
+    HsmdSignRemoteHtlcToUs => HsmdSignTxReply, sign_remote_htlc_to_us;
+    HsmdSignPenaltyToUs => HsmdSignTxReply, sign_penalty_to_us;
+    HsmdSignLocalHtlcTx => HsmdSignTxReply, sign_local_htlc_tx;
+    HsmdSignRemoteCommitmentTx => HsmdSignTxReply, sign_remote_commitment_tx;
+    HsmdSignRemoteHtlcTx => HsmdSignTxReply, sign_remote_htlc_tx;
+    HsmdSignMutualCloseTx => HsmdSignTxReply, sign_mutual_close_tx;
+    HsmdGetPerCommitmentPoint => HsmdGetPerCommitmentPointReply, get_per_commitment_point;
+    HsmdDevMemleak => HsmdDevMemleakReply, dev_memleak;
+    HsmdCheckFutureSecret => HsmdCheckFutureSecretReply, check_future_secret;
+    HsmdSignMessage => HsmdSignMessageReply, sign_message;
+    HsmdGetOutputScriptpubkey => HsmdGetOutputScriptpubkeyReply, get_output_scriptpubkey;
+    HsmdSignBolt12 => HsmdSignBolt12Reply, sign_bolt12;
+    HsmdSignOptionWillFundOffer => HsmdSignOptionWillFundOfferReply, sign_option_will_fund_offer;
+    HsmdDeriveSecret => HsmdDeriveSecretReply, derive_secret;
+}
+
+/// Answer the requests coming in on `stream` with `handler`, until
+/// it's closed. Fails on the first request `handler` fails, like
+/// `hsmd` dies on a bad request.
+pub async fn serve<H, S>(handler: &mut H, stream: S) -> Result<()>
+where
+    H: Handler + ?Sized,
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut framed = Framed::new(stream, HsmdCodec::default());
+    while let Some(msg) = framed.next().await {
+        let msg = msg?;
+        trace!("Handling hsmd request of type {}", msg.typ());
+        let reply = handle(handler, msg)?;
+        framed.send(reply).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crypto::message_hash;
+    use bitcoin::blockdata::constants::genesis_block;
+    use bitcoin::hashes::hex::FromHex;
+    use bitcoin::secp256k1::ecdh::SharedSecret;
+    use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+    use bitcoin::{EcdsaSig, EcdsaSighashType, Network, PackedLockTime, Script, TxIn, TxOut};
+
+    fn key(b: u8) -> SecretKey {
+        SecretKey::from_slice(&[b; 32]).unwrap()
+    }
+
+    fn pubkey(b: u8) -> PublicKey {
+        PublicKey::from_secret_key(&Secp256k1::new(), &key(b))
+    }
+
+    #[test]
+    fn test_init() {
+        let genesis = genesis_block(Network::Regtest).block_hash();
+        let init = HsmdInit {
+            bip32_key_version: Bip32KeyVersion {
+                pubkey_version: 0x043587cf,
+                privkey_version: 0x04358394,
+            },
+            chainparams: genesis,
+            hsm_encryption_key: None,
+            dev_force_privkey: Some(Secret::from([1; 32])),
+            dev_force_bip32_seed: None,
+            dev_force_channel_secrets: None,
+            dev_force_channel_secrets_shaseed: None,
+            hsm_wire_min_version: 1,
+            hsm_wire_max_version: 2,
+        };
+
+        let mut expected = hex::decode("000b043587cf04358394").unwrap();
+        expected.extend_from_slice(&genesis[..]);
+        expected.push(0);
+        expected.push(1);
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[0, 0, 0]);
+        expected.extend_from_slice(&hex::decode("0000000100000002").unwrap());
+
+        assert_eq!(init.to_wire().unwrap(), expected);
+        assert_eq!(HsmdInit::from_wire(&expected).unwrap(), init);
+        assert_eq!(
+            HsmdMessage::from_wire(&expected).unwrap(),
+            HsmdMessage::HsmdInit(init)
+        );
+
+        // A bool is either 0 or 1.
+        expected[10 + 32] = 2;
+        assert!(HsmdInit::from_wire(&expected).is_err());
+    }
+
+    #[test]
+    fn test_types() {
+        let msg = HsmstatusClientBadRequest {
+            id: pubkey(1),
+            description: "bad request".to_string(),
+            msg: vec![0, 1],
+        };
+        let wire = msg.to_wire().unwrap();
+        assert_eq!(&wire[35..47], b"bad request\0");
+        assert_eq!(HsmstatusClientBadRequest::from_wire(&wire).unwrap(), msg);
+
+        let bad = HsmstatusClientBadRequest {
+            description: "bad\nrequest".to_string(),
+            ..msg
+        };
+        assert!(bad.to_wire().is_err());
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: 1000,
+                script_pubkey: Script::new(),
+            }],
+        };
+        let tx = BitcoinTx::new(Psbt::from_unsigned_tx(tx).unwrap());
+        let utxo = Utxo {
+            outpoint: OutPoint::default(),
+            amount: Amount::from_sat(1000),
+            keyindex: 3,
+            is_p2sh: false,
+            script_pubkey: vec![0x00, 0x14],
+            close_info: Some(CloseInfo {
+                channel_id: 1,
+                peer_id: pubkey(2),
+                commitment_point: None,
+                option_anchor_outputs: true,
+                csv: 144,
+            }),
+            is_in_coinbase: false,
+        };
+        let withdrawal = HsmdSignWithdrawal {
+            inputs: vec![utxo.clone()],
+            psbt: tx.psbt.clone(),
+        };
+        let wire = withdrawal.to_wire().unwrap();
+        assert_eq!(HsmdSignWithdrawal::from_wire(&wire).unwrap(), withdrawal);
+
+        // amount_sat can't hold millisatoshis.
+        let withdrawal = HsmdSignWithdrawal {
+            inputs: vec![Utxo {
+                amount: Amount::from_msat(1),
+                ..utxo
+            }],
+            psbt: tx.psbt.clone(),
+        };
+        assert!(withdrawal.to_wire().is_err());
+
+        let sign = HsmdValidateCommitmentTx {
+            tx,
+            htlcs: vec![SimpleHtlc {
+                side: Side::Remote,
+                amount: Amount::from_msat(5000),
+                payment_hash: Sha256::from_hex(&"42".repeat(32)).unwrap(),
+                cltv_expiry: 500,
+            }],
+            commit_num: 7,
+            feerate: 253,
+            sig: EcdsaSig::sighash_all(
+                Secp256k1::new().sign_ecdsa(&Message::from_slice(&[3; 32]).unwrap(), &key(1)),
+            ),
+            htlc_sigs: vec![],
+        };
+        let wire = sign.to_wire().unwrap();
+        assert_eq!(HsmdValidateCommitmentTx::from_wire(&wire).unwrap(), sign);
+        assert_eq!(wire[wire.len() - 3], EcdsaSighashType::All as u8);
+
+        // Truncated messages fail.
+        for n in 2..wire.len() {
+            assert!(HsmdValidateCommitmentTx::from_wire(&wire[..n]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_codec() {
+        let mut codec = HsmdCodec::default();
+        let mut buf = BytesMut::new();
+        let ecdh = HsmdEcdhReq { point: pubkey(1) };
+        codec.encode(ecdh.clone().into(), &mut buf).unwrap();
+        codec.encode(HsmdDevMemleak {}.into(), &mut buf).unwrap();
+        assert_eq!(&buf[..6], &[0, 0, 0, 35, 0, 1]);
+
+        // Messages are only decoded once complete.
+        let mut input = BytesMut::new();
+        let mut decoded = vec![];
+        for b in buf.iter() {
+            input.put_u8(*b);
+            if let Some(msg) = codec.decode(&mut input).unwrap() {
+                decoded.push(msg);
+            }
+        }
+        assert_eq!(
+            decoded,
+            vec![
+                HsmdMessage::HsmdEcdhReq(ecdh),
+                HsmdMessage::HsmdDevMemleak(HsmdDevMemleak {})
+            ]
+        );
+        assert!(input.is_empty());
+
+        let mut input = BytesMut::from(&[0x08, 0, 0, 0, 0][..]);
+        assert!(codec.decode(&mut input).is_err());
+    }
+
+    struct TestSigner {
+        key: SecretKey,
+    }
+
+    impl Handler for TestSigner {
+        fn ecdh(&mut self, req: HsmdEcdhReq) -> Result<HsmdEcdhResp> {
+            let ss = SharedSecret::new(&req.point, &self.key);
+            Ok(HsmdEcdhResp {
+                ss: ss.secret_bytes().into(),
+            })
+        }
+
+        fn sign_message(&mut self, req: HsmdSignMessage) -> Result<HsmdSignMessageReply> {
+            let msg = String::from_utf8(req.msg)?;
+            Ok(HsmdSignMessageReply {
+                sig: Secp256k1::signing_only()
+                    .sign_ecdsa_recoverable(&message_hash(&msg), &self.key),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let (a, b) = tokio::io::duplex(1024);
+        let server = tokio::spawn(async move {
+            let mut signer = TestSigner { key: key(1) };
+            serve(&mut signer, b).await
+        });
+        let mut client = HsmdClient::new(a);
+
+        let reply = client.call(HsmdEcdhReq { point: pubkey(2) }).await.unwrap();
+        let ss = SharedSecret::new(&pubkey(1), &key(2));
+        assert_eq!(<[u8; 32]>::from(reply.ss), ss.secret_bytes());
+
+        let reply = client
+            .call(HsmdSignMessage {
+                msg: b"hello".to_vec(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            Secp256k1::new()
+                .recover_ecdsa(&message_hash("hello"), &reply.sig)
+                .unwrap(),
+            pubkey(1)
+        );
+
+        // The signer doesn't know how to do this, which ends it.
+        assert!(client
+            .call(HsmdGetPerCommitmentPoint { n: 0 })
+            .await
+            .is_err());
+        assert!(server.await.unwrap().is_err());
+    }
+}
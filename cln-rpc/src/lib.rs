@@ -1,5 +1,4 @@
 use crate::codec::JsonCodec;
-use crate::codec::JsonRpc;
 pub use anyhow::Error;
 use anyhow::Result;
 use futures_util::sink::SinkExt;
@@ -9,30 +8,61 @@ use serde_json::json;
 use std::path::Path;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::UnixStream;
 use tokio_util::codec::{FramedRead, FramedWrite};
 
+pub mod backup;
+pub mod bolt11;
+pub mod bolt12;
 pub mod codec;
+pub mod coincontrol;
+pub mod commando;
+pub mod crypto;
+pub mod decode;
+pub mod hsmd;
 pub mod jsonrpc;
+pub mod metrics;
 pub mod model;
+pub mod noise;
 pub mod notifications;
+pub mod onionmessage;
+pub mod pay;
 pub mod primitives;
+pub mod psbt;
+pub mod redact;
+pub mod routing;
+pub mod rpc;
+pub mod rune;
+pub mod wire;
 
 pub use crate::{
     model::{Request, Response},
     notifications::Notification,
     primitives::RpcError,
+    rpc::Rpc,
 };
-use crate::model::IntoRequest;
+use crate::metrics::Metrics;
+use crate::model::{requests::GetinfoRequest, responses::GetinfoResponse, IntoRequest};
 
+/// How long [`ClnRpc::wait_synced`] sleeps between `getinfo` polls.
+pub const SYNC_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A client for the `lightningd` JSON-RPC unix socket.
 ///
+/// Requests and responses are logged at `trace` level with the
+/// [`redact::SENSITIVE_FIELDS`] redacted. With the `tracing` feature
+/// each call also gets a `cln_rpc` span with the `method`, `id`,
+/// `latency_ms`, `response_size` and `error_code` as fields.
 pub struct ClnRpc {
     next_id: AtomicUsize,
 
     #[allow(dead_code)]
     read: FramedRead<OwnedReadHalf, JsonCodec>,
     write: FramedWrite<OwnedWriteHalf, JsonCodec>,
+    metrics: Option<Arc<dyn Metrics>>,
 }
 
 impl ClnRpc {
@@ -54,57 +84,105 @@ impl ClnRpc {
             next_id: AtomicUsize::new(1),
             read: FramedRead::new(read, JsonCodec::default()),
             write: FramedWrite::new(write, JsonCodec::default()),
+            metrics: None,
         })
     }
 
-    pub async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
-        trace!("Sending request {:?}", req);
+    /// Report the calls made to `metrics`, see [`metrics`].
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
 
-        // Wrap the raw request in a well-formed JSON-RPC outer dict.
-        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-        let req: JsonRpc<Notification, Request> = JsonRpc::Request(json!(id), req);
-        let req = serde_json::to_value(req).map_err(|e| RpcError {
+    pub async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        let mut req = serde_json::to_value(req).map_err(|e| RpcError {
             code: None,
             message: format!("Error parsing request: {}", e),
+            data: None,
         })?;
-        let req2 = req.clone();
+        let params = req["params"].take();
+        let method = req["method"].as_str().unwrap_or_default();
+        let result = self.call_raw(method, params).await?;
+        parse_response(&req["method"], json!({ "result": result }))
+    }
+
+    /// Call `method` with the JSON `params`, returning the JSON
+    /// `result`.
+    pub async fn call_raw(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, RpcError> {
+        // Wrap the raw request in a well-formed JSON-RPC outer dict.
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let req = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        trace!("Sending request {}", redact::redact(&req));
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "cln_rpc",
+            method = %method,
+            id,
+            latency_ms = tracing::field::Empty,
+            response_size = tracing::field::Empty,
+            error_code = tracing::field::Empty,
+        );
+
+        let start = Instant::now();
+        let res = self.roundtrip(req);
+        #[cfg(feature = "tracing")]
+        let res = tracing::Instrument::instrument(res, span.clone());
+        let res = res.await;
+        let latency = start.elapsed();
+        // Only set if we got as far as reading a response.
+        let size = self.read.decoder_mut().take_last_len();
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("latency_ms", latency.as_millis() as u64);
+            if let Some(size) = size {
+                span.record("response_size", size);
+            }
+            if let Err(RpcError { code: Some(c), .. }) = &res {
+                span.record("error_code", c);
+            }
+        }
+        if let Some(m) = &self.metrics {
+            metrics::record(m.as_ref(), method, latency, size, &res);
+        }
+        res
+    }
+
+    /// Send the JSON-RPC request `req` and return the `result` of the
+    /// response.
+    async fn roundtrip(&mut self, req: serde_json::Value) -> Result<serde_json::Value, RpcError> {
         self.write.send(req).await.map_err(|e| RpcError {
             code: None,
             message: format!("Error passing request to lightningd: {}", e),
+            data: None,
         })?;
 
-        let mut response = self
+        let response = self
             .read
             .next()
             .await
             .ok_or_else(|| RpcError {
                 code: None,
                 message: "no response from lightningd".to_string(),
+                data: None,
             })?
             .map_err(|_| RpcError {
                 code: None,
                 message: "reading response from socket".to_string(),
+                data: None,
             })?;
-        trace!("Read response {:?}", response);
-
-        // Annotate the response with the method from the request, so
-        // serde_json knows which variant of [`Request`] should be
-        // used.
-        response["method"] = req2["method"].clone();
-        if let Some(_) = response.get("result") {
-            serde_json::from_value(response).map_err(|e| RpcError {
-                code: None,
-                message: format!("Malformed response from lightningd: {}", e),
-            })
-        } else if let Some(e) = response.get("error") {
-            let e: RpcError = serde_json::from_value(e.clone()).unwrap();
-            Err(e)
-        } else {
-            Err(RpcError {
-                code: None,
-                message: format!("Malformed response from lightningd: {}", response),
-            })
-        }
+        trace!("Read response {}", redact::redact(&response));
+        take_result(response)
     }
 
     pub async fn call_typed<R: IntoRequest>(&mut self, request: R) -> Result<R::Response, RpcError> {
@@ -113,6 +191,124 @@ impl ClnRpc {
             .try_into()
             .expect("CLN will reply correctly"))
     }
+
+    /// Wait until `lightningd` has caught up with the blockchain,
+    /// i.e., `getinfo` no longer reports any [`SyncWarning`]. Returns
+    /// the first `getinfo` response without sync warnings.
+    pub async fn wait_synced(&mut self) -> Result<GetinfoResponse, RpcError> {
+        loop {
+            let info = self.call_typed(GetinfoRequest {}).await?;
+            let warnings = info.sync_warnings();
+            if warnings.is_empty() {
+                return Ok(info);
+            }
+            for w in warnings {
+                debug!("Waiting for node to sync at height {}: {}", info.blockheight, w);
+            }
+            tokio::time::sleep(SYNC_POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Turn a JSON-RPC response to a request for `method` into a
+/// [`Response`], or the [`RpcError`] it carries.
+pub(crate) fn parse_response(
+    method: &serde_json::Value,
+    mut response: serde_json::Value,
+) -> Result<Response, RpcError> {
+    // Annotate the response with the method from the request, so
+    // serde_json knows which variant of [`Request`] should be
+    // used.
+    response["method"] = method.clone();
+    if let Some(_) = response.get("result") {
+        serde_json::from_value(response).map_err(|e| RpcError {
+            code: None,
+            message: format!("Malformed response from lightningd: {}", e),
+            data: None,
+        })
+    } else if let Some(e) = response.get("error") {
+        let e: RpcError = serde_json::from_value(e.clone()).unwrap();
+        Err(e)
+    } else {
+        Err(RpcError {
+            code: None,
+            message: format!("Malformed response from lightningd: {}", response),
+            data: None,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Rpc for ClnRpc {
+    async fn call_raw(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, RpcError> {
+        ClnRpc::call_raw(self, method, params).await
+    }
+
+    async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        ClnRpc::call(self, req).await
+    }
+}
+
+/// Take the `result` out of a JSON-RPC response, or the
+/// [`RpcError`] it carries instead.
+pub(crate) fn take_result(mut response: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    if let Some(r) = response.get_mut("result") {
+        Ok(r.take())
+    } else if let Some(e) = response.get("error") {
+        Err(serde_json::from_value(e.clone()).map_err(|e| RpcError {
+            code: None,
+            message: format!("Malformed error from lightningd: {}", e),
+            data: None,
+        })?)
+    } else {
+        Err(RpcError {
+            code: None,
+            message: format!("Malformed response from lightningd: {}", response),
+            data: None,
+        })
+    }
+}
+
+/// A warning reported by `getinfo` while the node is still catching
+/// up with the blockchain.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncWarning {
+    /// `bitcoind` itself is still syncing.
+    Bitcoind(String),
+    /// `lightningd` is still processing blocks `bitcoind` already has.
+    Lightningd(String),
+}
+
+impl std::fmt::Display for SyncWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncWarning::Bitcoind(m) => write!(f, "bitcoind: {}", m),
+            SyncWarning::Lightningd(m) => write!(f, "lightningd: {}", m),
+        }
+    }
+}
+
+impl GetinfoResponse {
+    /// The sync warnings currently reported, if any.
+    pub fn sync_warnings(&self) -> Vec<SyncWarning> {
+        let mut warnings = vec![];
+        if let Some(w) = &self.warning_bitcoind_sync {
+            warnings.push(SyncWarning::Bitcoind(w.clone()));
+        }
+        if let Some(w) = &self.warning_lightningd_sync {
+            warnings.push(SyncWarning::Lightningd(w.clone()));
+        }
+        warnings
+    }
+
+    /// Whether the node has caught up with the blockchain.
+    pub fn is_synced(&self) -> bool {
+        self.warning_bitcoind_sync.is_none() && self.warning_lightningd_sync.is_none()
+    }
 }
 
 /// Used to skip optional arrays when serializing requests.
@@ -169,4 +365,172 @@ mod test {
             read_req
         );
     }
+
+    #[derive(Default)]
+    struct TestMetrics {
+        recorded: std::sync::Mutex<Vec<(&'static str, String, f64)>>,
+    }
+
+    impl Metrics for TestMetrics {
+        fn increment(&self, name: &'static str, method: &str) {
+            self.observe(name, method, 1.0);
+        }
+
+        fn observe(&self, name: &'static str, method: &str, value: f64) {
+            self.recorded
+                .lock()
+                .unwrap()
+                .push((name, method.to_string(), value));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let (uds1, uds2) = UnixStream::pair().unwrap();
+        let metrics = Arc::new(TestMetrics::default());
+        let mut cln = ClnRpc::from_stream(uds1)
+            .unwrap()
+            .with_metrics(metrics.clone());
+
+        let reply = json!({"jsonrpc": "2.0", "id": 1, "result": {"peers": []}});
+        let size = reply.to_string().len() as f64;
+        tokio::task::spawn(async move {
+            let mut node = tokio_util::codec::Framed::new(uds2, JsonCodec::default());
+            node.next().await.unwrap().unwrap();
+            node.send(reply).await.unwrap();
+            node.next().await.unwrap().unwrap();
+            let error = json!({"code": 210, "message": "failed"});
+            node.send(json!({"jsonrpc": "2.0", "id": 2, "error": error}))
+                .await
+                .unwrap();
+            // Hang up on the third call.
+            node.next().await.unwrap().unwrap();
+        });
+
+        let req = requests::ListpeersRequest::builder().build();
+        cln.call_typed(req.clone()).await.unwrap();
+        let err = cln.call_typed(req.clone()).await.unwrap_err();
+        assert_eq!(err.code, Some(210));
+        let err = cln.call_typed(req).await.unwrap_err();
+        assert_eq!(err.code, None);
+
+        let recorded = metrics.recorded.lock().unwrap();
+        let values = |name| -> Vec<f64> {
+            recorded
+                .iter()
+                .filter(|r| r.0 == name && r.1 == "listpeers")
+                .map(|r| r.2)
+                .collect()
+        };
+        assert_eq!(values(metrics::CALLS), vec![1.0, 1.0, 1.0]);
+        assert_eq!(values(metrics::ERRORS), vec![1.0, 1.0]);
+        // No size for the call that got no response.
+        assert_eq!(values(metrics::RESPONSE_SIZE).len(), 2);
+        assert_eq!(values(metrics::RESPONSE_SIZE)[0], size);
+        assert_eq!(values(metrics::LATENCY).len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_synced() {
+        let (uds1, uds2) = UnixStream::pair().unwrap();
+        let mut cln = ClnRpc::from_stream(uds1).unwrap();
+
+        tokio::task::spawn(async move {
+            let mut node = tokio_util::codec::Framed::new(uds2, JsonCodec::default());
+            let warnings = [
+                json!({"warning_bitcoind_sync": "Bitcoind is not up-to-date with network."}),
+                json!({"warning_lightningd_sync": "Still loading latest blocks from bitcoind."}),
+                json!({}),
+            ];
+            for (i, w) in warnings.iter().enumerate() {
+                let req = node.next().await.unwrap().unwrap();
+                assert_eq!(req["method"], "getinfo");
+                let mut result = json!({
+                    "id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+                    "alias": "node",
+                    "color": "02eec7",
+                    "num_peers": 0,
+                    "num_pending_channels": 0,
+                    "num_active_channels": 0,
+                    "num_inactive_channels": 0,
+                    "version": "v22.11.1",
+                    "blockheight": 100 + i,
+                    "network": "regtest",
+                    "fees_collected_msat": "0msat",
+                    "lightning-dir": "/tmp/l1/regtest",
+                    "our_features": {"init": "", "node": "", "channel": "", "invoice": ""}
+                });
+                result.as_object_mut().unwrap().extend(w.as_object().unwrap().clone());
+                node.send(json!({"jsonrpc": "2.0", "id": req["id"], "result": result}))
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let info = cln.wait_synced().await.unwrap();
+        assert!(info.is_synced());
+        assert_eq!(info.blockheight, 102);
+    }
+
+    #[test]
+    fn test_sync_warnings() {
+        let info: GetinfoResponse = serde_json::from_value(json!({
+            "id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+            "alias": "node",
+            "color": "02eec7",
+            "num_peers": 0,
+            "num_pending_channels": 0,
+            "num_active_channels": 0,
+            "num_inactive_channels": 0,
+            "version": "v22.11.1",
+            "blockheight": 100,
+            "network": "regtest",
+            "fees_collected_msat": "0msat",
+            "lightning-dir": "/tmp/l1/regtest",
+            "warning_lightningd_sync": "Still loading latest blocks from bitcoind."
+        }))
+        .unwrap();
+        assert!(!info.is_synced());
+        assert_eq!(
+            info.sync_warnings(),
+            vec![SyncWarning::Lightningd(
+                "Still loading latest blocks from bitcoind.".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        use crate::primitives::{Amount, PublicKey, Sha256, ShortChannelId};
+        use bitcoin::hashes::Hash;
+        use std::str::FromStr;
+
+        let req = requests::PayRequest::builder("lnbcrt1".to_string())
+            .maxfeepercent(0.5)
+            .retry_for(60)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            json!({"bolt11": "lnbcrt1", "maxfeepercent": 0.5, "retry_for": 60})
+        );
+
+        let route = vec![requests::SendpayRoute::builder(
+            Amount::from_msat(1000),
+            PublicKey::from_str(
+                "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+            )
+            .unwrap(),
+            6,
+            ShortChannelId::from_str("103x1x0").unwrap(),
+        )
+        .build()];
+        let hash = Sha256::from_slice(&[0u8; 32]).unwrap();
+        let req = requests::SendpayRequest::builder(route, hash)
+            .partid(1)
+            .groupid(2)
+            .build();
+        assert_eq!(req.partid, Some(1));
+        assert_eq!(req.groupid, Some(2));
+        assert_eq!(req.bolt11, None);
+    }
 }
@@ -0,0 +1,46 @@
+//! Metrics hooks for [`ClnRpc`](crate::ClnRpc) calls.
+//!
+//! Implement [`Metrics`] on top of the metrics library of your choice
+//! and pass it to [`ClnRpc::with_metrics`](crate::ClnRpc::with_metrics).
+//! Every call then increments [`CALLS`], and [`ERRORS`] if it
+//! failed, and records its [`LATENCY`] and [`RESPONSE_SIZE`], all
+//! labelled with the method name.
+use crate::RpcError;
+use std::time::Duration;
+
+/// Counter of calls made.
+pub const CALLS: &str = "cln_rpc_calls_total";
+/// Counter of calls that returned an error.
+pub const ERRORS: &str = "cln_rpc_errors_total";
+/// Histogram of the call latency, in seconds.
+pub const LATENCY: &str = "cln_rpc_call_duration_seconds";
+/// Histogram of the response size, in bytes.
+pub const RESPONSE_SIZE: &str = "cln_rpc_response_size_bytes";
+
+/// A sink for per-method counters and histograms.
+pub trait Metrics: Send + Sync {
+    /// Increment the counter `name` for `method` by one.
+    fn increment(&self, name: &'static str, method: &str);
+
+    /// Record `value` in the histogram `name` for `method`.
+    fn observe(&self, name: &'static str, method: &str, value: f64);
+}
+
+/// Record a call to `method` that completed after `latency` with a
+/// response of `size` bytes, if we got one.
+pub(crate) fn record<T>(
+    metrics: &dyn Metrics,
+    method: &str,
+    latency: Duration,
+    size: Option<usize>,
+    res: &Result<T, RpcError>,
+) {
+    metrics.increment(CALLS, method);
+    if res.is_err() {
+        metrics.increment(ERRORS, method);
+    }
+    metrics.observe(LATENCY, method, latency.as_secs_f64());
+    if let Some(size) = size {
+        metrics.observe(RESPONSE_SIZE, method, size as f64);
+    }
+}
@@ -0,0 +1,483 @@
+//! The BOLT8 transport: a `Noise_XK` handshake followed by
+//! length-prefixed, ChaCha20-Poly1305 encrypted messages.
+//!
+//! This is what lightning nodes use to talk to each other, and what
+//! [`crate::commando::CommandoClient`] uses to talk to a node without
+//! access to its RPC socket.
+use anyhow::{anyhow, Context, Result};
+use bitcoin::hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
+use bitcoin::secp256k1::ecdh::SharedSecret;
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const PROTOCOL_NAME: &[u8] = b"Noise_XK_secp256k1_ChaChaPoly_SHA256";
+const PROLOGUE: &[u8] = b"lightning";
+
+const ACT_ONE_SIZE: usize = 50;
+const ACT_TWO_SIZE: usize = 50;
+const ACT_THREE_SIZE: usize = 66;
+const MAC_SIZE: usize = 16;
+
+/// Keys are rotated after this many encryptions or decryptions.
+const KEY_ROTATION_INTERVAL: u64 = 1000;
+
+/// The largest message the transport can carry.
+pub const MAX_MESSAGE_SIZE: usize = 65535;
+
+fn hkdf(salt: &[u8; 32], ikm: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut engine = HmacEngine::<sha256::Hash>::new(salt);
+    engine.input(ikm);
+    let prk = Hmac::from_engine(engine).into_inner();
+
+    let mut engine = HmacEngine::<sha256::Hash>::new(&prk);
+    engine.input(&[1]);
+    let t1 = Hmac::from_engine(engine).into_inner();
+
+    let mut engine = HmacEngine::<sha256::Hash>::new(&prk);
+    engine.input(&t1);
+    engine.input(&[2]);
+    let t2 = Hmac::from_engine(engine).into_inner();
+    (t1, t2)
+}
+
+fn nonce(n: u64) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&n.to_le_bytes());
+    Nonce::clone_from_slice(&nonce)
+}
+
+fn encrypt_with_ad(key: &[u8; 32], n: u64, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    ChaCha20Poly1305::new(key.into())
+        .encrypt(
+            &nonce(n),
+            Payload {
+                msg: plaintext,
+                aad: ad,
+            },
+        )
+        .expect("encryption of in-memory buffers cannot fail")
+}
+
+fn decrypt_with_ad(key: &[u8; 32], n: u64, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(
+            &nonce(n),
+            Payload {
+                msg: ciphertext,
+                aad: ad,
+            },
+        )
+        .map_err(|_| anyhow!("message authentication failed"))
+}
+
+fn ecdh(pk: &PublicKey, sk: &SecretKey) -> [u8; 32] {
+    SharedSecret::new(pk, sk).secret_bytes()
+}
+
+/// Generate a fresh random key.
+pub(crate) fn random_key() -> SecretKey {
+    loop {
+        if let Ok(k) = SecretKey::from_slice(&rand::random::<[u8; 32]>()) {
+            return k;
+        }
+    }
+}
+
+/// The handshake state shared by both sides.
+struct SymmetricState {
+    ck: [u8; 32],
+    h: [u8; 32],
+    temp_k: [u8; 32],
+}
+
+impl SymmetricState {
+    /// Initialize the state, mixing in the responder's static key.
+    fn new(responder: &PublicKey) -> SymmetricState {
+        let h = sha256::Hash::hash(PROTOCOL_NAME).into_inner();
+        let mut s = SymmetricState {
+            ck: h,
+            h,
+            temp_k: [0; 32],
+        };
+        s.mix_hash(PROLOGUE);
+        s.mix_hash(&responder.serialize());
+        s
+    }
+
+    fn mix_hash(&mut self, data: &[u8]) {
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.h);
+        engine.input(data);
+        self.h = sha256::Hash::from_engine(engine).into_inner();
+    }
+
+    fn mix_key(&mut self, ss: &[u8; 32]) {
+        let (ck, temp_k) = hkdf(&self.ck, ss);
+        self.ck = ck;
+        self.temp_k = temp_k;
+    }
+
+    fn encrypt_and_hash(&mut self, n: u64, plaintext: &[u8]) -> Vec<u8> {
+        let c = encrypt_with_ad(&self.temp_k, n, &self.h, plaintext);
+        self.mix_hash(&c);
+        c
+    }
+
+    fn decrypt_and_hash(&mut self, n: u64, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let p = decrypt_with_ad(&self.temp_k, n, &self.h, ciphertext)?;
+        self.mix_hash(ciphertext);
+        Ok(p)
+    }
+
+    /// Derive the transport ciphers, with the initiator's sending key
+    /// first.
+    fn split(&self) -> (CipherState, CipherState) {
+        let (k1, k2) = hkdf(&self.ck, &[]);
+        (CipherState::new(self.ck, k1), CipherState::new(self.ck, k2))
+    }
+}
+
+/// Parse an act's `version || pubkey || rest`.
+fn parse_act<'a>(act: &'a [u8], name: &str) -> Result<(PublicKey, &'a [u8])> {
+    if act[0] != 0 {
+        return Err(anyhow!(
+            "{}: unsupported handshake version {}",
+            name,
+            act[0]
+        ));
+    }
+    let pk = PublicKey::from_slice(&act[1..34]).with_context(|| format!("{}: bad key", name))?;
+    Ok((pk, &act[34..]))
+}
+
+/// The initiator's side of the handshake.
+struct Initiator {
+    state: SymmetricState,
+    ls: SecretKey,
+    e: SecretKey,
+}
+
+impl Initiator {
+    fn new(ls: SecretKey, rs: &PublicKey, e: SecretKey) -> (Initiator, Vec<u8>) {
+        let secp = Secp256k1::signing_only();
+        let mut state = SymmetricState::new(rs);
+        let epub = PublicKey::from_secret_key(&secp, &e).serialize();
+        state.mix_hash(&epub);
+        state.mix_key(&ecdh(rs, &e));
+        let c = state.encrypt_and_hash(0, &[]);
+
+        let mut act = vec![0];
+        act.extend_from_slice(&epub);
+        act.extend_from_slice(&c);
+        (Initiator { state, ls, e }, act)
+    }
+
+    /// Process act two, returning act three and the transport ciphers.
+    fn act_two(mut self, act: &[u8]) -> Result<(Vec<u8>, CipherState, CipherState)> {
+        let (re, c) = parse_act(act, "act two")?;
+        self.state.mix_hash(&re.serialize());
+        self.state.mix_key(&ecdh(&re, &self.e));
+        self.state.decrypt_and_hash(0, c)?;
+
+        let secp = Secp256k1::signing_only();
+        let spub = PublicKey::from_secret_key(&secp, &self.ls).serialize();
+        let c = self.state.encrypt_and_hash(1, &spub);
+        self.state.mix_key(&ecdh(&re, &self.ls));
+        let t = encrypt_with_ad(&self.state.temp_k, 0, &self.state.h, &[]);
+
+        let mut act = vec![0];
+        act.extend_from_slice(&c);
+        act.extend_from_slice(&t);
+        let (send, recv) = self.state.split();
+        Ok((act, send, recv))
+    }
+}
+
+/// The responder's side of the handshake.
+struct Responder {
+    state: SymmetricState,
+    e: SecretKey,
+}
+
+impl Responder {
+    /// Process act one, returning act two.
+    fn act_one(ls: &SecretKey, e: SecretKey, act: &[u8]) -> Result<(Responder, Vec<u8>)> {
+        let secp = Secp256k1::signing_only();
+        let mut state = SymmetricState::new(&PublicKey::from_secret_key(&secp, ls));
+        let (re, c) = parse_act(act, "act one")?;
+        state.mix_hash(&re.serialize());
+        state.mix_key(&ecdh(&re, ls));
+        state.decrypt_and_hash(0, c)?;
+
+        let epub = PublicKey::from_secret_key(&secp, &e).serialize();
+        state.mix_hash(&epub);
+        state.mix_key(&ecdh(&re, &e));
+        let c = state.encrypt_and_hash(0, &[]);
+
+        let mut act = vec![0];
+        act.extend_from_slice(&epub);
+        act.extend_from_slice(&c);
+        Ok((Responder { state, e }, act))
+    }
+
+    /// Process act three, returning the initiator's static key and
+    /// the transport ciphers.
+    fn act_three(mut self, act: &[u8]) -> Result<(PublicKey, CipherState, CipherState)> {
+        if act[0] != 0 {
+            return Err(anyhow!(
+                "act three: unsupported handshake version {}",
+                act[0]
+            ));
+        }
+        let rs = self.state.decrypt_and_hash(1, &act[1..50])?;
+        let rs = PublicKey::from_slice(&rs).context("act three: bad key")?;
+        self.state.mix_key(&ecdh(&rs, &self.e));
+        decrypt_with_ad(&self.state.temp_k, 0, &self.state.h, &act[50..])?;
+
+        let (recv, send) = self.state.split();
+        Ok((rs, send, recv))
+    }
+}
+
+/// One direction of an established transport.
+struct CipherState {
+    ck: [u8; 32],
+    k: [u8; 32],
+    n: u64,
+}
+
+impl CipherState {
+    fn new(ck: [u8; 32], k: [u8; 32]) -> CipherState {
+        CipherState { ck, k, n: 0 }
+    }
+
+    fn advance(&mut self) {
+        self.n += 1;
+        if self.n == KEY_ROTATION_INTERVAL {
+            let (ck, k) = hkdf(&self.ck, &self.k);
+            self.ck = ck;
+            self.k = k;
+            self.n = 0;
+        }
+    }
+
+    fn encrypt(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let c = encrypt_with_ad(&self.k, self.n, &[], plaintext);
+        self.advance();
+        c
+    }
+
+    fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let p = decrypt_with_ad(&self.k, self.n, &[], ciphertext)?;
+        self.advance();
+        Ok(p)
+    }
+
+    /// Encrypt a message, including its length prefix.
+    fn encrypt_message(&mut self, msg: &[u8]) -> Result<Vec<u8>> {
+        if msg.len() > MAX_MESSAGE_SIZE {
+            return Err(anyhow!(
+                "message of {} bytes exceeds the maximum of {}",
+                msg.len(),
+                MAX_MESSAGE_SIZE
+            ));
+        }
+        let mut buf = self.encrypt(&(msg.len() as u16).to_be_bytes());
+        buf.extend(self.encrypt(msg));
+        Ok(buf)
+    }
+}
+
+/// An established, encrypted connection to a peer.
+pub struct NoiseStream<S> {
+    stream: S,
+    remote: PublicKey,
+    send: CipherState,
+    recv: CipherState,
+}
+
+impl<S> NoiseStream<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Perform the handshake as initiator, authenticating as
+    /// `local` to the node with the static key `remote`.
+    pub async fn initiate(mut stream: S, local: &SecretKey, remote: &PublicKey) -> Result<Self> {
+        let (initiator, act_one) = Initiator::new(*local, remote, random_key());
+        stream.write_all(&act_one).await?;
+
+        let mut act_two = [0u8; ACT_TWO_SIZE];
+        stream
+            .read_exact(&mut act_two)
+            .await
+            .context("reading act two")?;
+        let (act_three, send, recv) = initiator.act_two(&act_two)?;
+        stream.write_all(&act_three).await?;
+        stream.flush().await?;
+
+        Ok(NoiseStream {
+            stream,
+            remote: *remote,
+            send,
+            recv,
+        })
+    }
+
+    /// Perform the handshake as responder, using the static key
+    /// `local`. The initiator's static key is available from
+    /// [`NoiseStream::remote`] once this returns.
+    pub async fn respond(mut stream: S, local: &SecretKey) -> Result<Self> {
+        let mut act_one = [0u8; ACT_ONE_SIZE];
+        stream
+            .read_exact(&mut act_one)
+            .await
+            .context("reading act one")?;
+        let (responder, act_two) = Responder::act_one(local, random_key(), &act_one)?;
+        stream.write_all(&act_two).await?;
+        stream.flush().await?;
+
+        let mut act_three = [0u8; ACT_THREE_SIZE];
+        stream
+            .read_exact(&mut act_three)
+            .await
+            .context("reading act three")?;
+        let (remote, send, recv) = responder.act_three(&act_three)?;
+
+        Ok(NoiseStream {
+            stream,
+            remote,
+            send,
+            recv,
+        })
+    }
+
+    /// The static key of the other side.
+    pub fn remote(&self) -> &PublicKey {
+        &self.remote
+    }
+
+    /// Send a single message.
+    pub async fn send(&mut self, msg: &[u8]) -> Result<()> {
+        let buf = self.send.encrypt_message(msg)?;
+        self.stream.write_all(&buf).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Receive a single message.
+    pub async fn recv(&mut self) -> Result<Vec<u8>> {
+        let mut lc = [0u8; 2 + MAC_SIZE];
+        self.stream.read_exact(&mut lc).await?;
+        let len = self.recv.decrypt(&lc)?;
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+
+        let mut c = vec![0u8; len + MAC_SIZE];
+        self.stream.read_exact(&mut c).await?;
+        self.recv.decrypt(&c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(b: u8) -> SecretKey {
+        SecretKey::from_slice(&[b; 32]).unwrap()
+    }
+
+    fn unhex(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    /// The BOLT8 test vectors.
+    #[test]
+    fn test_handshake_vectors() {
+        let secp = Secp256k1::signing_only();
+        let rs = PublicKey::from_secret_key(&secp, &key(0x21));
+        assert_eq!(
+            rs.to_string(),
+            "028d7500dd4c12685d1f568b4c2b5048e8534b873319f3a8daa612b469132ec7f7"
+        );
+
+        let (initiator, act_one) = Initiator::new(key(0x11), &rs, key(0x12));
+        assert_eq!(act_one, unhex("00036360e856310ce5d294e8be33fc807077dc56ac80d95d9cd4ddbd21325eff73f70df6086551151f58b8afe6c195782c6a"));
+
+        let (responder, act_two) = Responder::act_one(&key(0x21), key(0x22), &act_one).unwrap();
+        assert_eq!(act_two, unhex("0002466d7fcae563e5cb09a0d1870bb580344804617879a14949cf22285f1bae3f276e2470b93aac583c9ef6eafca3f730ae"));
+
+        let (act_three, mut isend, _) = initiator.act_two(&act_two).unwrap();
+        assert_eq!(act_three, unhex("00b9e3a702e93e3a9948c2ed6e5fd7590a6e1c3a0344cfc9d5b57357049aa22355361aa02e55a8fc28fef5bd6d71ad0c38228dc68b1c466263b47fdf31e560e139ba"));
+        assert_eq!(
+            isend.k.to_vec(),
+            unhex("969ab31b4d288cedf6218839b27a3e2140827047f2c0f01bf5c04435d43511a9")
+        );
+
+        let (ls, _, mut rrecv) = responder.act_three(&act_three).unwrap();
+        assert_eq!(ls, PublicKey::from_secret_key(&secp, &key(0x11)));
+
+        let expected = [
+            (
+                0,
+                "cf2b30ddf0cf3f80e7c35a6e6730b59fe802473180f396d88a8fb0db8cbcf25d2f214cf9ea1d95",
+            ),
+            (
+                1,
+                "72887022101f0b6753e0c7de21657d35a4cb2a1f5cde2650528bbc8f837d0f0d7ad833b1a256a1",
+            ),
+            (
+                500,
+                "178cb9d7387190fa34db9c2d50027d21793c9bc2d40b1e14dcf30ebeeeb220f48364f7a4c68bf8",
+            ),
+            (
+                501,
+                "1b186c57d44eb6de4c057c49940d79bb838a145cb528d6e8fd26dbe50a60ca2c104b56b60e45bd",
+            ),
+            (
+                1000,
+                "4a2f3cc3b5e78ddb83dcb426d9863d9d9a723b0337c89dd0b005d89f8d3c05c52b76b29b740f09",
+            ),
+            (
+                1001,
+                "2ecd8c8a5629d0d02ab457a0fdd0f7b90a192cd46be5ecb6ca570bfc5e268338b1a16cf4ef2d36",
+            ),
+        ];
+        let mut expected = expected.iter().peekable();
+        for i in 0..1002 {
+            let c = isend.encrypt_message(b"hello").unwrap();
+            if let Some((_, out)) = expected.next_if(|(n, _)| *n == i) {
+                assert_eq!(hex::encode(&c), *out, "message {}", i);
+            }
+            assert_eq!(rrecv.decrypt(&c[..18]).unwrap(), vec![0, 5]);
+            assert_eq!(rrecv.decrypt(&c[18..]).unwrap(), b"hello");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let (a, b) = tokio::io::duplex(1024);
+        let secp = Secp256k1::signing_only();
+        let node = key(0x21);
+        let node_id = PublicKey::from_secret_key(&secp, &node);
+
+        let responder = tokio::spawn(async move {
+            let mut s = NoiseStream::respond(b, &node).await.unwrap();
+            let msg = s.recv().await.unwrap();
+            s.send(&msg).await.unwrap();
+            *s.remote()
+        });
+
+        let mut s = NoiseStream::initiate(a, &key(0x11), &node_id)
+            .await
+            .unwrap();
+        let big = vec![0x42; MAX_MESSAGE_SIZE];
+        s.send(&big).await.unwrap();
+        assert_eq!(s.recv().await.unwrap(), big);
+        assert!(s.send(&[0; MAX_MESSAGE_SIZE + 1]).await.is_err());
+        assert_eq!(
+            responder.await.unwrap(),
+            PublicKey::from_secret_key(&secp, &key(0x11))
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! Onion messages and blinded paths.
+//!
+//! `sendonionmessage` is a low-level primitive: the caller provides
+//! the (blinded) ids of each hop and the `onionmsg_tlv` payload each
+//! of them should receive. This module provides the types needed to
+//! fill it in, i.e., [`BlindedPath`] as returned by `blindedpath` and
+//! used for reply paths, and [`OnionMessagePayload`] for the
+//! per-hop TLV, plus [`send_to_path`] to address a message along a
+//! blinded path.
+//!
+//! Payments to a blinded path go through `createonion` instead, with
+//! [`blinded_payment_hops`] providing the hops inside the path.
+use crate::model::requests::{CreateonionHops, SendonionmessageHops, SendonionmessageRequest};
+use crate::model::responses::BlindedpathResponse;
+use crate::primitives::{write_bigsize, Amount, PublicKey, TlvEntry, TlvRecord, TlvStream};
+use crate::wire::onion::{self, TlvPayload};
+use crate::wire::{Reader, WireType};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// One hop in a [`BlindedPath`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindedHop {
+    pub blinded_node_id: PublicKey,
+    #[serde(serialize_with = "to_hex", deserialize_with = "from_hex")]
+    pub encrypted_recipient_data: Vec<u8>,
+}
+
+/// A blinded path as used for onion message reply paths and in
+/// BOLT12 offers and invoices. The JSON representation matches the
+/// `blindedpath` RPC result and the `reply_blindedpath` field of the
+/// onion message hooks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlindedPath {
+    pub first_node_id: PublicKey,
+    pub blinding: PublicKey,
+    pub hops: Vec<BlindedHop>,
+}
+
+impl BlindedPath {
+    /// Serialize as the BOLT4 `blinded_path` subtype.
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.write(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Parse the BOLT4 `blinded_path` subtype, returning the path and
+    /// the number of bytes consumed.
+    pub fn from_wire(buf: &[u8]) -> Result<(BlindedPath, usize)> {
+        let mut r = Reader::new(buf);
+        let path = r.read()?;
+        Ok((path, buf.len() - r.len()))
+    }
+}
+
+/// The wire encoding is the one of [`onion::BlindedPath`].
+impl WireType for BlindedPath {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        onion::BlindedPath::from(self).write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(r.read::<onion::BlindedPath>()?.into())
+    }
+}
+
+impl From<onion::BlindedPath> for BlindedPath {
+    fn from(p: onion::BlindedPath) -> Self {
+        BlindedPath {
+            first_node_id: p.first_node_id,
+            blinding: p.blinding,
+            hops: p
+                .path
+                .into_iter()
+                .map(|h| BlindedHop {
+                    blinded_node_id: h.blinded_node_id,
+                    encrypted_recipient_data: h.encrypted_recipient_data,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&BlindedPath> for onion::BlindedPath {
+    fn from(p: &BlindedPath) -> Self {
+        onion::BlindedPath {
+            first_node_id: p.first_node_id,
+            blinding: p.blinding,
+            path: p
+                .hops
+                .iter()
+                .map(|h| onion::OnionmsgHop {
+                    blinded_node_id: h.blinded_node_id,
+                    encrypted_recipient_data: h.encrypted_recipient_data.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<BlindedpathResponse> for BlindedPath {
+    type Error = anyhow::Error;
+
+    fn try_from(r: BlindedpathResponse) -> Result<Self, Self::Error> {
+        let p = r.blindedpath;
+        Ok(BlindedPath {
+            first_node_id: p.first_node_id,
+            blinding: p.blinding,
+            hops: p
+                .hops
+                .into_iter()
+                .map(|h| {
+                    Ok(BlindedHop {
+                        blinded_node_id: h.blinded_node_id,
+                        encrypted_recipient_data: hex::decode(&h.encrypted_recipient_data)
+                            .context("decoding encrypted_recipient_data")?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// The `onionmsg_tlv` payload delivered to a single hop of an onion
+/// message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OnionMessagePayload {
+    /// A path the recipient can use to reply to us.
+    pub reply_path: Option<BlindedPath>,
+    pub encrypted_recipient_data: Option<Vec<u8>>,
+    pub invoice_request: Option<Vec<u8>>,
+    pub invoice: Option<Vec<u8>>,
+    pub invoice_error: Option<Vec<u8>>,
+    /// Any other fields, as `(type, value)` pairs. Custom protocols
+    /// should use odd types above 64.
+    pub extra: Vec<(u64, Vec<u8>)>,
+}
+
+const REPLY_PATH: u64 = 2;
+const ENCRYPTED_RECIPIENT_DATA: u64 = 4;
+const INVOICE_REQUEST: u64 = 64;
+const INVOICE: u64 = 66;
+const INVOICE_ERROR: u64 = 68;
+
+impl OnionMessagePayload {
+    /// Serialize the payload as a TLV stream, ordered by type.
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        self.to_tlv_stream()?.to_wire()
+    }
+
+    /// Parse a payload from its TLV stream encoding.
+    pub fn from_wire(buf: &[u8]) -> Result<OnionMessagePayload> {
+        OnionMessagePayload::from_tlv_stream(&TlvStream::from_wire(buf)?)
+    }
+}
+
+impl TlvRecord for OnionMessagePayload {
+    fn to_tlv_stream(&self) -> Result<TlvStream> {
+        let mut stream = TlvStream::new();
+        if let Some(p) = &self.reply_path {
+            stream.insert(REPLY_PATH, p.to_wire()?);
+        }
+        let known = [
+            (ENCRYPTED_RECIPIENT_DATA, &self.encrypted_recipient_data),
+            (INVOICE_REQUEST, &self.invoice_request),
+            (INVOICE, &self.invoice),
+            (INVOICE_ERROR, &self.invoice_error),
+        ];
+        for (typ, v) in known {
+            if let Some(v) = v {
+                stream.insert(typ, v.clone());
+            }
+        }
+        for (typ, v) in &self.extra {
+            stream.entries.push(TlvEntry {
+                typ: *typ,
+                value: v.clone(),
+            });
+        }
+        stream
+            .canonicalize()
+            .context("invalid onion message payload")?;
+        Ok(stream)
+    }
+
+    fn from_tlv_stream(stream: &TlvStream) -> Result<Self> {
+        let known = [
+            REPLY_PATH,
+            ENCRYPTED_RECIPIENT_DATA,
+            INVOICE_REQUEST,
+            INVOICE,
+            INVOICE_ERROR,
+        ];
+        if let Some(t) = stream.unknown_required(&known).first() {
+            return Err(anyhow!("unknown even onion message field {}", t));
+        }
+        let reply_path = match stream.get(REPLY_PATH) {
+            Some(v) => {
+                let mut r = Reader::new(v);
+                let path = r.read()?;
+                if !r.is_empty() {
+                    return Err(anyhow!("trailing bytes after reply_path"));
+                }
+                Some(path)
+            }
+            None => None,
+        };
+        Ok(OnionMessagePayload {
+            reply_path,
+            encrypted_recipient_data: stream.get_as(ENCRYPTED_RECIPIENT_DATA)?,
+            invoice_request: stream.get_as(INVOICE_REQUEST)?,
+            invoice: stream.get_as(INVOICE)?,
+            invoice_error: stream.get_as(INVOICE_ERROR)?,
+            extra: stream
+                .entries
+                .iter()
+                .filter(|e| !known.contains(&e.typ))
+                .map(|e| (e.typ, e.value.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Build a `sendonionmessage` request delivering `payload` to the
+/// final node of a blinded `path`, e.g., the `reply_blindedpath` of
+/// an incoming message. Each intermediate hop receives its
+/// `encrypted_recipient_data` so it can forward the message.
+pub fn send_to_path(
+    path: &BlindedPath,
+    payload: OnionMessagePayload,
+) -> Result<SendonionmessageRequest> {
+    let (last, intermediate) = path
+        .hops
+        .split_last()
+        .ok_or_else(|| anyhow!("blinded path has no hops"))?;
+
+    let mut hops = intermediate
+        .iter()
+        .map(|h| {
+            let p = OnionMessagePayload {
+                encrypted_recipient_data: Some(h.encrypted_recipient_data.clone()),
+                ..Default::default()
+            };
+            Ok(SendonionmessageHops {
+                id: h.blinded_node_id,
+                tlv: hex::encode(p.to_wire()?),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let payload = OnionMessagePayload {
+        encrypted_recipient_data: Some(last.encrypted_recipient_data.clone()),
+        ..payload
+    };
+    hops.push(SendonionmessageHops {
+        id: last.blinded_node_id,
+        tlv: hex::encode(payload.to_wire()?),
+    });
+
+    Ok(SendonionmessageRequest {
+        first_id: path.first_node_id,
+        blinding: path.blinding,
+        hops,
+    })
+}
+
+/// Build the `createonion` hops paying `amount` to the recipient at
+/// the end of a blinded `path`, expiring at `cltv`. The introduction
+/// node is addressed by its real id and learns the blinding point from
+/// its payload, the hops after it by their blinded ids. Hops leading
+/// to the introduction node have to be prepended by the caller.
+pub fn blinded_payment_hops(
+    path: &BlindedPath,
+    amount: Amount,
+    cltv: u32,
+) -> Result<Vec<CreateonionHops>> {
+    if path.hops.is_empty() {
+        return Err(anyhow!("blinded path has no hops"));
+    }
+
+    let last = path.hops.len() - 1;
+    path.hops
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let (pubkey, blinding_point) = match i {
+                0 => (path.first_node_id, Some(path.blinding)),
+                _ => (h.blinded_node_id, None),
+            };
+            let payload = TlvPayload {
+                amt_to_forward: (i == last).then_some(amount),
+                outgoing_cltv_value: (i == last).then_some(cltv),
+                encrypted_recipient_data: Some(h.encrypted_recipient_data.clone()),
+                blinding_point,
+                ..Default::default()
+            };
+
+            let mut tlv = vec![];
+            payload.write(&mut tlv)?;
+            let mut buf = vec![];
+            write_bigsize(&mut buf, tlv.len() as u64);
+            buf.extend(tlv);
+            Ok(CreateonionHops::builder(pubkey, hex::encode(buf)).build())
+        })
+        .collect()
+}
+
+fn to_hex<S: Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&hex::encode(v))
+}
+
+fn from_hex<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+    use serde::de::Error;
+    let s: String = Deserialize::deserialize(d)?;
+    hex::decode(s).map_err(|e| Error::custom(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn pk(b: u8) -> PublicKey {
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let sk = bitcoin::secp256k1::SecretKey::from_slice(&[b; 32]).unwrap();
+        PublicKey::from_secret_key(&secp, &sk)
+    }
+
+    fn path() -> BlindedPath {
+        BlindedPath {
+            first_node_id: pk(1),
+            blinding: pk(2),
+            hops: vec![
+                BlindedHop {
+                    blinded_node_id: pk(3),
+                    encrypted_recipient_data: vec![0xaa; 51],
+                },
+                BlindedHop {
+                    blinded_node_id: pk(4),
+                    encrypted_recipient_data: vec![0xbb; 300],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_blinded_path_wire() {
+        let p = path();
+        let wire = p.to_wire().unwrap();
+        assert_eq!(wire.len(), 33 + 33 + 1 + 2 * (33 + 2) + 51 + 300);
+        assert_eq!(BlindedPath::from_wire(&wire).unwrap(), (p, wire.len()));
+        assert!(BlindedPath::from_wire(&wire[..wire.len() - 1]).is_err());
+
+        let r: BlindedpathResponse = serde_json::from_value(json!({
+            "blindedpath": serde_json::to_value(path()).unwrap()
+        }))
+        .unwrap();
+        assert_eq!(BlindedPath::try_from(r).unwrap(), path());
+    }
+
+    #[test]
+    fn test_send_to_path() {
+        let reply = path();
+        let payload = OnionMessagePayload {
+            reply_path: Some(reply.clone()),
+            invoice_request: Some(vec![1, 2, 3]),
+            extra: vec![(0xfffd, vec![0x42])],
+            ..Default::default()
+        };
+        let req = send_to_path(&path(), payload).unwrap();
+        assert_eq!(req.first_id, pk(1));
+        assert_eq!(req.blinding, pk(2));
+        assert_eq!(req.hops.len(), 2);
+
+        // The forwarding hop only gets its encrypted data.
+        assert_eq!(req.hops[0].id, pk(3));
+        assert_eq!(req.hops[0].tlv, format!("0433{}", "aa".repeat(51)));
+
+        // The final hop gets its encrypted data and the payload, in
+        // TLV type order.
+        let rp = reply.to_wire().unwrap();
+        let mut expected = vec![0x02, 0xfd];
+        expected.extend((rp.len() as u16).to_be_bytes());
+        expected.extend(rp);
+        expected.extend([0x04, 0xfd, 0x01, 0x2c]);
+        expected.extend([0xbb; 300]);
+        expected.extend([0x40, 0x03, 1, 2, 3]);
+        expected.extend([0xfd, 0xff, 0xfd, 0x01, 0x42]);
+        assert_eq!(req.hops[1].id, pk(4));
+        assert_eq!(req.hops[1].tlv, hex::encode(expected));
+
+        let dup = OnionMessagePayload {
+            invoice: Some(vec![]),
+            extra: vec![(66, vec![])],
+            ..Default::default()
+        };
+        assert!(dup.to_wire().is_err());
+
+        // What we send is what the recipient parses.
+        let wire = hex::decode(&req.hops[1].tlv).unwrap();
+        let parsed = OnionMessagePayload::from_wire(&wire).unwrap();
+        assert_eq!(parsed.reply_path, Some(reply));
+        assert_eq!(parsed.encrypted_recipient_data, Some(vec![0xbb; 300]));
+        assert_eq!(parsed.invoice_request, Some(vec![1, 2, 3]));
+        assert_eq!(parsed.extra, vec![(0xfffd, vec![0x42])]);
+        assert!(OnionMessagePayload::from_wire(&[0x06, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_blinded_payment_hops() {
+        let hops = blinded_payment_hops(&path(), Amount::from_msat(1000), 700).unwrap();
+        assert_eq!(hops.len(), 2);
+
+        let parse = |payload: &str| {
+            let wire = hex::decode(payload).unwrap();
+            let (len, n) = crate::primitives::read_bigsize(&wire).unwrap();
+            assert_eq!(len as usize, wire.len() - n);
+            TlvPayload::read(&mut crate::wire::Reader::new(&wire[n..])).unwrap()
+        };
+
+        // The introduction node gets the blinding point and is
+        // addressed by its real id.
+        assert_eq!(hops[0].pubkey, pk(1));
+        let p = parse(&hops[0].payload);
+        assert_eq!(p.blinding_point, Some(pk(2)));
+        assert_eq!(p.encrypted_recipient_data, Some(vec![0xaa; 51]));
+        assert_eq!((p.amt_to_forward, p.outgoing_cltv_value), (None, None));
+
+        // The recipient gets the amount and expiry.
+        assert_eq!(hops[1].pubkey, pk(4));
+        let p = parse(&hops[1].payload);
+        assert_eq!(p.blinding_point, None);
+        assert_eq!(p.encrypted_recipient_data, Some(vec![0xbb; 300]));
+        assert_eq!(p.amt_to_forward, Some(Amount::from_msat(1000)));
+        assert_eq!(p.outgoing_cltv_value, Some(700));
+
+        let empty = BlindedPath {
+            hops: vec![],
+            ..path()
+        };
+        assert!(blinded_payment_hops(&empty, Amount::from_msat(1000), 700).is_err());
+    }
+}
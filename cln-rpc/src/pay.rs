@@ -0,0 +1,920 @@
+//! A multi-part payer on top of `sendpay` and `waitsendpay`.
+//!
+//! [`Payer`] finds routes locally over a [`Graph`], splitting the
+//! amount in halves whenever no single route can carry it, and sends
+//! each part with its own `partid` in a common `groupid`. Every failed
+//! part teaches it something: failing nodes and channels are excluded,
+//! and a channel that lacked the liquidity gets its
+//! `htlc_maximum_msat` lowered below the amount it failed to carry.
+//! The payer retries within a fee budget and deadline, and returns a
+//! [`PayReport`] with every attempt it made.
+use crate::decode::Bolt11Invoice;
+use crate::model::requests::{SendpayRequest, SendpayRoute, WaitsendpayRequest};
+use crate::model::responses::WaitsendpayResponse;
+use crate::primitives::{Amount, PublicKey, Secret, Sha256, ShortChannelId, ShortChannelIdDir};
+use crate::routing::{Graph, RouteParams};
+use crate::{ClnRpc, RpcError};
+use anyhow::{anyhow, Result};
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// `sendpay` and `waitsendpay` error codes.
+pub const PAY_UNPARSEABLE_ONION: i32 = 202;
+pub const PAY_DESTINATION_PERM_FAIL: i32 = 203;
+pub const PAY_TRY_OTHER_ROUTE: i32 = 204;
+
+/// BOLT4 failure code flags.
+const NODE: u32 = 0x2000;
+const UPDATE: u32 = 0x1000;
+const TEMPORARY_CHANNEL_FAILURE: u32 = UPDATE | 7;
+
+/// What to pay.
+#[derive(Clone, Debug)]
+pub struct PayTarget {
+    pub destination: PublicKey,
+    /// The total amount the destination should receive.
+    pub amount: Amount,
+    pub payment_hash: Sha256,
+    pub payment_secret: Option<Secret>,
+    pub final_cltv: u32,
+    pub bolt11: Option<String>,
+    pub label: Option<String>,
+}
+
+impl PayTarget {
+    /// Pay a BOLT11 invoice. `amount` must be given iff the invoice
+    /// doesn't specify one.
+    pub fn from_bolt11(bolt11: &str, amount: Option<Amount>) -> Result<PayTarget> {
+        let invoice = Bolt11Invoice::from_str(bolt11)?;
+        let amount = match (invoice.amount_msat, amount) {
+            (Some(a), None) | (None, Some(a)) => a,
+            (Some(_), Some(_)) => return Err(anyhow!("amount_msat parameter unnecessary")),
+            (None, None) => return Err(anyhow!("amount_msat parameter required")),
+        };
+        Ok(PayTarget {
+            destination: invoice.payee,
+            amount,
+            payment_hash: invoice.payment_hash,
+            payment_secret: invoice.payment_secret,
+            final_cltv: invoice.min_final_cltv_expiry,
+            bolt11: Some(bolt11.to_string()),
+            label: None,
+        })
+    }
+}
+
+/// Limits for a payment.
+#[derive(Clone, Debug)]
+pub struct PayParams {
+    /// Total fee we are willing to pay, over all parts.
+    pub max_fee: Amount,
+    /// Stop retrying after this long.
+    pub retry_for: Duration,
+    /// Maximum number of parts in flight at the same time.
+    pub max_parts: usize,
+    /// Don't split into parts smaller than this.
+    pub min_part: Amount,
+    pub groupid: u64,
+    /// Weights and exclusions for the route search. `final_cltv` and
+    /// `max_fee` are set for each part.
+    pub route: RouteParams,
+}
+
+impl PayParams {
+    /// The same limits as `pay`: 0.5% of `amount` in fees, but at
+    /// least 5000msat, retrying for 60 seconds.
+    pub fn for_amount(amount: Amount) -> PayParams {
+        PayParams {
+            max_fee: Amount::from_msat(5000).max(amount / 200),
+            retry_for: Duration::from_secs(60),
+            max_parts: 16,
+            min_part: Amount::from_sat(100),
+            groupid: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            route: RouteParams::default(),
+        }
+    }
+}
+
+/// The details `sendpay` and `waitsendpay` give about a failed part.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct PaymentFailure {
+    /// Index of the failing node in the route, 0 being us.
+    pub erring_index: Option<usize>,
+    pub failcode: Option<u32>,
+    pub failcodename: Option<String>,
+    pub erring_node: Option<PublicKey>,
+    pub erring_channel: Option<ShortChannelId>,
+    pub erring_direction: Option<u32>,
+}
+
+impl PaymentFailure {
+    pub fn from_error(e: &RpcError) -> Option<PaymentFailure> {
+        serde_json::from_value(e.data.clone()?).ok()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttemptStatus {
+    Pending,
+    Complete,
+    Failed,
+}
+
+/// One part we sent.
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    pub partid: u16,
+    /// The amount this part delivers to the destination.
+    pub amount: Amount,
+    pub fee: Amount,
+    pub route: Vec<SendpayRoute>,
+    pub status: AttemptStatus,
+    pub error: Option<RpcError>,
+    pub failure: Option<PaymentFailure>,
+}
+
+/// The outcome of [`Payer::pay`].
+#[derive(Clone, Debug)]
+pub struct PayReport {
+    pub payment_hash: Sha256,
+    pub preimage: Option<Secret>,
+    pub amount: Amount,
+    /// The amount sent by completed parts, including fees.
+    pub amount_sent: Amount,
+    pub attempts: Vec<Attempt>,
+    /// Why we gave up, if we did.
+    pub error: Option<String>,
+}
+
+impl PayReport {
+    pub fn is_complete(&self) -> bool {
+        self.preimage.is_some()
+    }
+
+    pub fn fee(&self) -> Amount {
+        self.amount_sent.saturating_sub(self.amount)
+    }
+
+    fn committed_fee(&self) -> Amount {
+        self.attempts
+            .iter()
+            .filter(|a| a.status != AttemptStatus::Failed)
+            .map(|a| a.fee)
+            .sum()
+    }
+}
+
+/// Pays from `source` over a local copy of the channel graph.
+pub struct Payer {
+    graph: Graph,
+    source: PublicKey,
+    params: PayParams,
+}
+
+/// The channel direction `hop` was sent over.
+fn hop_scid(graph: &Graph, hop: &SendpayRoute) -> Option<ShortChannelIdDir> {
+    (0..2)
+        .map(|direction| ShortChannelIdDir {
+            short_channel_id: hop.channel,
+            direction,
+        })
+        .find(|scid| graph.edge(scid).is_some_and(|e| e.destination == hop.id))
+}
+
+/// Wait for attempt `idx` on a connection of its own, so we can wait
+/// for all parts at once.
+async fn wait_part(
+    rpc_path: PathBuf,
+    idx: usize,
+    req: WaitsendpayRequest,
+) -> Result<(usize, Result<WaitsendpayResponse, RpcError>)> {
+    let mut rpc = ClnRpc::new(&rpc_path).await?;
+    Ok((idx, rpc.call_typed(req).await))
+}
+
+impl Payer {
+    pub fn new(graph: Graph, source: PublicKey, params: PayParams) -> Payer {
+        Payer {
+            graph,
+            source,
+            params,
+        }
+    }
+
+    /// The graph, with what we learned about channel liquidity.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Pay `target`. Failing to pay is not an error, see
+    /// [`PayReport::error`]: errors are only returned if talking to
+    /// `lightningd` fails.
+    ///
+    /// Parts are sent over a connection to `rpc_path`, and each is
+    /// waited for on a connection of its own: the destination holds
+    /// them until it has the whole amount, so they resolve together
+    /// and in any order. Parts still in flight when we give up are
+    /// waited for, so the report is final.
+    pub async fn pay<P: AsRef<Path>>(
+        &mut self,
+        rpc_path: P,
+        target: &PayTarget,
+    ) -> Result<PayReport> {
+        let rpc_path = rpc_path.as_ref();
+        let mut rpc = ClnRpc::new(rpc_path).await?;
+        let deadline = Instant::now() + self.params.retry_for;
+        let mut route_params = self.params.route.clone();
+        route_params.final_cltv = target.final_cltv;
+        let mut report = PayReport {
+            payment_hash: target.payment_hash,
+            preimage: None,
+            amount: target.amount,
+            amount_sent: Amount::ZERO,
+            attempts: vec![],
+            error: None,
+        };
+        let mut pending = FuturesUnordered::new();
+        let mut remaining = target.amount;
+
+        loop {
+            while remaining > Amount::ZERO && report.error.is_none() && report.preimage.is_none() {
+                if Instant::now() >= deadline {
+                    report.error = Some(format!(
+                        "Gave up after {}s",
+                        self.params.retry_for.as_secs()
+                    ));
+                    break;
+                }
+                let idx = match self.next_part(
+                    &mut route_params,
+                    target,
+                    &report,
+                    pending.len(),
+                    remaining,
+                ) {
+                    Ok(a) => {
+                        report.attempts.push(a);
+                        report.attempts.len() - 1
+                    }
+                    Err(e) => {
+                        report.error = Some(e.to_string());
+                        break;
+                    }
+                };
+                let attempt = &report.attempts[idx];
+                remaining = remaining
+                    .checked_sub(attempt.amount)
+                    .expect("parts are split off the remaining amount");
+                self.reserve(&attempt.route, true);
+
+                let req = SendpayRequest {
+                    route: attempt.route.clone(),
+                    payment_hash: target.payment_hash,
+                    label: target.label.clone(),
+                    amount_msat: Some(target.amount),
+                    bolt11: target.bolt11.clone(),
+                    payment_secret: target.payment_secret,
+                    partid: Some(attempt.partid),
+                    localinvreqid: None,
+                    groupid: Some(self.params.groupid),
+                };
+                debug!(
+                    "Sending part {} of {} over {} hops",
+                    attempt.partid,
+                    attempt.amount,
+                    attempt.route.len()
+                );
+                match rpc.call_typed(req).await {
+                    Ok(_) => pending.push(wait_part(
+                        rpc_path.to_path_buf(),
+                        idx,
+                        WaitsendpayRequest {
+                            payment_hash: target.payment_hash,
+                            timeout: None,
+                            partid: Some(attempt.partid.into()),
+                            groupid: Some(self.params.groupid),
+                        },
+                    )),
+                    Err(e) if e.code.is_none() => return Err(e.into()),
+                    Err(e) => {
+                        remaining += report.attempts[idx].amount;
+                        self.fail(&mut route_params, &mut report, idx, target, e);
+                    }
+                }
+            }
+
+            let (idx, res) = match pending.next().await {
+                Some(r) => r?,
+                None => break,
+            };
+            match res {
+                Ok(res) => {
+                    let attempt = &mut report.attempts[idx];
+                    attempt.status = AttemptStatus::Complete;
+                    report.amount_sent += res.amount_sent_msat;
+                    report.preimage = report.preimage.or(res.payment_preimage);
+                }
+                Err(e) if e.code.is_none() => return Err(e.into()),
+                Err(e) => {
+                    remaining += report.attempts[idx].amount;
+                    self.fail(&mut route_params, &mut report, idx, target, e);
+                }
+            }
+        }
+
+        if report.preimage.is_some() {
+            report.error = None;
+        }
+        Ok(report)
+    }
+
+    /// Find a route for `remaining`, or as large a part of it as we
+    /// can.
+    fn next_part(
+        &self,
+        params: &mut RouteParams,
+        target: &PayTarget,
+        report: &PayReport,
+        in_flight: usize,
+        remaining: Amount,
+    ) -> Result<Attempt> {
+        if in_flight >= self.params.max_parts {
+            return Err(anyhow!(
+                "Cannot send more than {} parts",
+                self.params.max_parts
+            ));
+        }
+        params.max_fee = Some(self.params.max_fee.saturating_sub(report.committed_fee()));
+        let mut part = remaining;
+        loop {
+            match self
+                .graph
+                .route(&self.source, &target.destination, part, params)
+            {
+                Ok(route) => {
+                    let partid = u16::try_from(report.attempts.len() + 1)
+                        .map_err(|_| anyhow!("Ran out of partids"))?;
+                    let fee = route[0]
+                        .amount_msat
+                        .checked_sub(part)
+                        .ok_or_else(|| anyhow!("Route delivers less than {}", part))?;
+                    return Ok(Attempt {
+                        partid,
+                        amount: part,
+                        fee,
+                        route,
+                        status: AttemptStatus::Pending,
+                        error: None,
+                        failure: None,
+                    });
+                }
+                Err(_)
+                    if part / 2 >= self.params.min_part
+                        && in_flight + 1 < self.params.max_parts =>
+                {
+                    part = Amount::from_msat(part.msat().div_ceil(2));
+                }
+                Err(_) => return Err(anyhow!("Could not find a route for {}", remaining)),
+            }
+        }
+    }
+
+    /// Account for `route` using (or no longer using) the channels'
+    /// capacity.
+    fn reserve(&mut self, route: &[SendpayRoute], add: bool) {
+        for hop in route {
+            let edge = hop_scid(&self.graph, hop).and_then(|s| self.graph.edge_mut(&s));
+            if let Some(e) = edge {
+                *e.capacity = match add {
+                    true => e.capacity.saturating_sub(hop.amount_msat),
+                    false => e.capacity.saturating_add(hop.amount_msat),
+                };
+            }
+        }
+    }
+
+    /// Record the failure of attempt `idx` and learn from it, or give
+    /// up on the payment.
+    fn fail(
+        &mut self,
+        params: &mut RouteParams,
+        report: &mut PayReport,
+        idx: usize,
+        target: &PayTarget,
+        e: RpcError,
+    ) {
+        let attempt = &mut report.attempts[idx];
+        let failure = PaymentFailure::from_error(&e);
+        debug!("Part {} failed: {}", attempt.partid, e);
+        attempt.status = AttemptStatus::Failed;
+        attempt.failure = failure.clone();
+        attempt.error = Some(e.clone());
+        let route = attempt.route.clone();
+        self.reserve(&route, false);
+
+        let retry = matches!(e.code, Some(PAY_TRY_OTHER_ROUTE | PAY_UNPARSEABLE_ONION));
+        let res = match failure {
+            Some(f) if retry => self.learn(params, &route, target, &f),
+            _ => Err(anyhow!(e.message)),
+        };
+        // Keep the reason we first gave up for.
+        if let (Err(err), None) = (res, &report.error) {
+            report.error = Some(err.to_string());
+        }
+    }
+
+    fn learn(
+        &mut self,
+        params: &mut RouteParams,
+        route: &[SendpayRoute],
+        target: &PayTarget,
+        f: &PaymentFailure,
+    ) -> Result<()> {
+        // E.g. `mpp_timeout`, there is nothing to learn but we can
+        // retry.
+        if f.erring_node == Some(target.destination) {
+            return Ok(());
+        }
+        let failcode = f.failcode.unwrap_or(0);
+        if failcode & NODE != 0 {
+            match f.erring_node {
+                Some(n) if n != self.source => {
+                    params.exclude_nodes.insert(n);
+                    return Ok(());
+                }
+                _ => return Err(anyhow!("Our own node failed the payment")),
+            }
+        }
+        let scid = match (f.erring_channel, f.erring_direction) {
+            (Some(c), Some(d)) => ShortChannelIdDir {
+                short_channel_id: c,
+                direction: d,
+            },
+            _ => return Err(anyhow!("Payment failed without a failing channel")),
+        };
+        let amount = f
+            .erring_index
+            .and_then(|i| route.get(i))
+            .filter(|hop| hop.channel == scid.short_channel_id)
+            .map(|hop| hop.amount_msat);
+        match (self.graph.edge_mut(&scid), amount) {
+            (Some(e), Some(a)) if failcode == TEMPORARY_CHANNEL_FAILURE => {
+                let max = a.saturating_sub(Amount::from_msat(1));
+                *e.htlc_maximum_msat = Some(e.htlc_maximum_msat.map_or(max, |m| m.min(max)));
+            }
+            _ => {
+                params.exclude_channels.insert(scid);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::JsonCodec;
+    use crate::routing::Edge;
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use futures_util::SinkExt;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tokio::net::UnixListener;
+    use tokio::sync::watch;
+    use tokio_util::codec::Framed;
+
+    fn node(i: u8) -> PublicKey {
+        let sk = SecretKey::from_slice(&[i; 32]).unwrap();
+        PublicKey::from_secret_key(&Secp256k1::new(), &sk)
+    }
+
+    /// 1 - 2 - 4 is cheaper than 1 - 3 - 4.
+    fn graph(capacity: Amount) -> Graph {
+        let mut g = Graph::new();
+        for (a, b, block, base) in [
+            (1, 2, 101, 1),
+            (2, 4, 102, 1),
+            (1, 3, 103, 10),
+            (3, 4, 104, 10),
+        ] {
+            for (src, dst) in [(node(a), node(b)), (node(b), node(a))] {
+                g.add_edge(Edge {
+                    source: src,
+                    destination: dst,
+                    short_channel_id: ShortChannelId::new(block, 1, 0).unwrap(),
+                    direction: (src > dst) as u32,
+                    capacity,
+                    base_fee_msat: base,
+                    fee_per_millionth: 100,
+                    cltv_delta: 6,
+                    htlc_minimum_msat: Amount::from_msat(1),
+                    htlc_maximum_msat: None,
+                    active: true,
+                });
+            }
+        }
+        g
+    }
+
+    fn target(amount: Amount) -> PayTarget {
+        PayTarget {
+            destination: node(4),
+            amount,
+            payment_hash: Sha256::hash(&[7; 32]),
+            payment_secret: Some(Secret::try_from(vec![9; 32]).unwrap()),
+            final_cltv: 18,
+            bolt11: None,
+            label: Some("test".to_string()),
+        }
+    }
+
+    fn params() -> PayParams {
+        PayParams {
+            groupid: 42,
+            ..PayParams::for_amount(Amount::from_sat(100_000))
+        }
+    }
+
+    /// How the mock node fails parts.
+    #[derive(Default)]
+    struct Mock {
+        /// Liquidity of channels, by block height.
+        liquidity: HashMap<u64, u64>,
+        /// Fail everything at the destination with this code.
+        destination_failure: Option<u32>,
+    }
+
+    impl Mock {
+        /// Why `route` fails, as `waitsendpay` error data.
+        fn failure(&self, graph: &Graph, route: &[SendpayRoute]) -> Option<(i32, Value)> {
+            if let Some(code) = self.destination_failure {
+                return Some((
+                    PAY_DESTINATION_PERM_FAIL,
+                    json!({
+                        "erring_index": route.len(),
+                        "failcode": code,
+                        "erring_node": node(4).to_string(),
+                    }),
+                ));
+            }
+            for (i, hop) in route.iter().enumerate() {
+                let block = hop.channel.block() as u64;
+                if self
+                    .liquidity
+                    .get(&block)
+                    .is_some_and(|l| hop.amount_msat.msat() > *l)
+                {
+                    let prev = if i == 0 { node(1) } else { route[i - 1].id };
+                    let scid = hop_scid(graph, hop).unwrap();
+                    return Some((
+                        PAY_TRY_OTHER_ROUTE,
+                        json!({
+                            "erring_index": i,
+                            "failcode": TEMPORARY_CHANNEL_FAILURE,
+                            "failcodename": "WIRE_TEMPORARY_CHANNEL_FAILURE",
+                            "erring_node": prev.to_string(),
+                            "erring_channel": hop.channel.to_string(),
+                            "erring_direction": scid.direction,
+                        }),
+                    ));
+                }
+            }
+            None
+        }
+    }
+
+    /// A part the mock node received.
+    struct Part {
+        amount: Amount,
+        sent: Amount,
+        failure: Option<(i32, Value)>,
+        /// Whether `waitsendpay` was called for it.
+        waiting: bool,
+    }
+
+    /// The state of the mock node, shared by its connections.
+    #[derive(Default)]
+    struct State {
+        sent: Vec<Value>,
+        parts: HashMap<u64, Part>,
+    }
+
+    /// Answer `sendpay` and `waitsendpay` like `lightningd` would. As
+    /// the destination does, parts are held until parts adding up to
+    /// the whole amount are waited for, and fail if all parts are
+    /// waited for without adding up to it.
+    fn mock_node(listener: UnixListener, graph: Graph, mock: Mock) -> Arc<Mutex<State>> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let changed = Arc::new(watch::channel(()).0);
+        let (graph, mock) = (Arc::new(graph), Arc::new(mock));
+        let shared = state.clone();
+        tokio::task::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (state, graph, mock) = (shared.clone(), graph.clone(), mock.clone());
+                let (changed, mut updates) = (changed.clone(), changed.subscribe());
+                tokio::task::spawn(async move {
+                    let mut conn = Framed::new(stream, JsonCodec::default());
+                    while let Some(Ok(req)) = conn.next().await {
+                        let result = match req["method"].as_str().unwrap() {
+                            "sendpay" => sendpay(&state, &graph, &mock, &req["params"]),
+                            "waitsendpay" => {
+                                let mut first = true;
+                                loop {
+                                    updates.borrow_and_update();
+                                    if let Some(r) = waitsendpay(&state, &req["params"]) {
+                                        break r;
+                                    }
+                                    // Wake the others, this part is now waiting.
+                                    if first {
+                                        changed.send_replace(());
+                                        first = false;
+                                    }
+                                    updates.changed().await.unwrap();
+                                }
+                            }
+                            m => panic!("unexpected method {}", m),
+                        };
+                        let reply = match result {
+                            Ok(r) => json!({"jsonrpc": "2.0", "id": req["id"], "result": r}),
+                            Err((code, data)) => json!({
+                                "jsonrpc": "2.0",
+                                "id": req["id"],
+                                "error": {"code": code, "message": "failed", "data": data},
+                            }),
+                        };
+                        conn.send(reply).await.unwrap();
+                        changed.send_replace(());
+                    }
+                });
+            }
+        });
+        state
+    }
+
+    fn sendpay(
+        state: &Mutex<State>,
+        graph: &Graph,
+        mock: &Mock,
+        params: &Value,
+    ) -> Result<Value, (i32, Value)> {
+        let mut state = state.lock().unwrap();
+        state.sent.push(params.clone());
+        let route: Vec<SendpayRoute> = serde_json::from_value(params["route"].clone()).unwrap();
+        let part = Part {
+            amount: route.last().unwrap().amount_msat,
+            sent: route[0].amount_msat,
+            failure: mock.failure(graph, &route),
+            waiting: false,
+        };
+        state.parts.insert(params["partid"].as_u64().unwrap(), part);
+        Ok(json!({
+            "id": params["partid"],
+            "payment_hash": params["payment_hash"],
+            "status": "pending",
+            "created_at": 0,
+            "amount_sent_msat": route[0].amount_msat,
+        }))
+    }
+
+    /// The result of `waitsendpay`, `None` while the part is held.
+    fn waitsendpay(state: &Mutex<State>, params: &Value) -> Option<Result<Value, (i32, Value)>> {
+        let mut state = state.lock().unwrap();
+        let part = state.parts.get_mut(&params["partid"].as_u64().unwrap())?;
+        if let Some((code, data)) = &part.failure {
+            return Some(Err((*code, data.clone())));
+        }
+        part.waiting = true;
+        let sent = part.sent;
+        let live = || state.parts.values().filter(|p| p.failure.is_none());
+        let total: u64 = live().filter(|p| p.waiting).map(|p| p.amount.msat()).sum();
+        if total >= 100_000_000 {
+            Some(Ok(json!({
+                "id": params["partid"],
+                "payment_hash": params["payment_hash"],
+                "status": "complete",
+                "created_at": 0,
+                "amount_sent_msat": sent,
+                "payment_preimage": hex::encode([7; 32]),
+            })))
+        } else if live().all(|p| p.waiting) {
+            Some(Err((
+                PAY_TRY_OTHER_ROUTE,
+                json!({"failcode": 23, "erring_node": node(4).to_string()}),
+            )))
+        } else {
+            None
+        }
+    }
+
+    /// Pay `amount` through a mock node, returning the `sendpay`
+    /// requests it got.
+    async fn pay(
+        graph: Graph,
+        mock: Mock,
+        params: PayParams,
+        amount: Amount,
+    ) -> (PayReport, Vec<Value>) {
+        static SOCKETS: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "cln-rpc-pay-{}-{}",
+            std::process::id(),
+            SOCKETS.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_file(&path);
+        let state = mock_node(UnixListener::bind(&path).unwrap(), graph.clone(), mock);
+        let mut payer = Payer::new(graph, node(1), params);
+        // Waiting for one part at a time would hold the first forever.
+        let report =
+            tokio::time::timeout(Duration::from_secs(10), payer.pay(&path, &target(amount)))
+                .await
+                .expect("parts are waited for together")
+                .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let sent = std::mem::take(&mut state.lock().unwrap().sent);
+        (report, sent)
+    }
+
+    fn scids(route: &[SendpayRoute]) -> Vec<u32> {
+        route.iter().map(|h| h.channel.block()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pay() {
+        let amount = Amount::from_sat(100_000);
+        let (report, sent) = pay(
+            graph(Amount::from_sat(1_000_000)),
+            Mock::default(),
+            params(),
+            amount,
+        )
+        .await;
+        assert!(report.is_complete(), "{:?}", report.error);
+        assert_eq!(report.preimage.unwrap().to_vec(), vec![7; 32]);
+        assert_eq!(report.attempts.len(), 1);
+        assert_eq!(scids(&report.attempts[0].route), vec![101, 102]);
+        assert_eq!(report.attempts[0].fee, Amount::from_msat(10001));
+        assert_eq!(report.fee(), Amount::from_msat(10001));
+
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0]["partid"], 1);
+        assert_eq!(sent[0]["groupid"], 42);
+        assert_eq!(sent[0]["amount_msat"], "100000000msat");
+        assert_eq!(sent[0]["label"], "test");
+        assert_eq!(sent[0]["payment_secret"], hex::encode([9; 32]));
+    }
+
+    #[tokio::test]
+    async fn test_pay_retry() {
+        let mock = Mock {
+            liquidity: HashMap::from([(102, 1000)]),
+            ..Default::default()
+        };
+        let amount = Amount::from_sat(100_000);
+        let (report, sent) = pay(graph(Amount::from_sat(1_000_000)), mock, params(), amount).await;
+        assert!(report.is_complete(), "{:?}", report.error);
+        assert_eq!(sent.len(), 2);
+        assert_eq!(report.attempts.len(), 2);
+
+        let failed = &report.attempts[0];
+        assert_eq!(failed.status, AttemptStatus::Failed);
+        assert_eq!(scids(&failed.route), vec![101, 102]);
+        let failure = failed.failure.as_ref().unwrap();
+        assert_eq!(failure.erring_node, Some(node(2)));
+        assert_eq!(failure.erring_channel.unwrap().block(), 102);
+        assert_eq!(
+            failed.error.as_ref().unwrap().code,
+            Some(PAY_TRY_OTHER_ROUTE)
+        );
+
+        let ok = &report.attempts[1];
+        assert_eq!(ok.status, AttemptStatus::Complete);
+        assert_eq!(ok.partid, 2);
+        assert_eq!(scids(&ok.route), vec![103, 104]);
+        assert_eq!(report.fee(), ok.fee);
+    }
+
+    #[tokio::test]
+    async fn test_pay_split() {
+        // Neither path can carry the whole amount.
+        let amount = Amount::from_sat(100_000);
+        let (report, sent) = pay(
+            graph(Amount::from_sat(60_000)),
+            Mock::default(),
+            params(),
+            amount,
+        )
+        .await;
+        assert!(report.is_complete(), "{:?}", report.error);
+        assert_eq!(report.attempts.len(), 2);
+        assert!(report
+            .attempts
+            .iter()
+            .all(|a| a.status == AttemptStatus::Complete));
+        assert_eq!(scids(&report.attempts[0].route), vec![101, 102]);
+        assert_eq!(scids(&report.attempts[1].route), vec![103, 104]);
+        assert_eq!(
+            report.attempts.iter().map(|a| a.amount).sum::<Amount>(),
+            amount
+        );
+        let fees: Amount = report.attempts.iter().map(|a| a.fee).sum();
+        assert_eq!(report.amount_sent, amount + fees);
+        assert_eq!(sent[0]["partid"], 1);
+        assert_eq!(sent[1]["partid"], 2);
+        assert!(sent
+            .iter()
+            .all(|s| s["amount_msat"] == "100000000msat" && s["groupid"] == 42));
+
+        // Learning the liquidity of a channel also makes us split.
+        let mock = Mock {
+            liquidity: HashMap::from([(102, 70_000_000), (104, 70_000_000)]),
+            ..Default::default()
+        };
+        let (report, _) = pay(graph(Amount::from_sat(1_000_000)), mock, params(), amount).await;
+        assert!(report.is_complete(), "{:?}", report.error);
+        let done: Vec<_> = report
+            .attempts
+            .iter()
+            .filter(|a| a.status == AttemptStatus::Complete)
+            .collect();
+        assert_eq!(done.len(), 2);
+        assert_eq!(done.iter().map(|a| a.amount).sum::<Amount>(), amount);
+    }
+
+    #[tokio::test]
+    async fn test_pay_fail() {
+        let amount = Amount::from_sat(100_000);
+        let mock = Mock {
+            destination_failure: Some(0x400f),
+            ..Default::default()
+        };
+        let (report, sent) = pay(graph(Amount::from_sat(1_000_000)), mock, params(), amount).await;
+        assert!(!report.is_complete());
+        assert_eq!(report.error.as_deref(), Some("failed"));
+        assert_eq!(sent.len(), 1);
+        assert_eq!(report.attempts[0].status, AttemptStatus::Failed);
+        assert_eq!(
+            report.attempts[0].failure.as_ref().unwrap().failcode,
+            Some(0x400f)
+        );
+
+        // Over budget.
+        let budget = PayParams {
+            max_fee: Amount::from_msat(5),
+            ..params()
+        };
+        let (report, sent) = pay(
+            graph(Amount::from_sat(1_000_000)),
+            Mock::default(),
+            budget,
+            amount,
+        )
+        .await;
+        assert!(sent.is_empty());
+        assert!(report.attempts.is_empty());
+        assert_eq!(
+            report.error.as_deref(),
+            Some("Could not find a route for 100000000msat")
+        );
+
+        // No liquidity anywhere.
+        let mock = Mock {
+            liquidity: HashMap::from([(102, 0), (104, 0)]),
+            ..Default::default()
+        };
+        let (report, _) = pay(graph(Amount::from_sat(1_000_000)), mock, params(), amount).await;
+        assert!(!report.is_complete());
+        assert!(report
+            .attempts
+            .iter()
+            .all(|a| a.status == AttemptStatus::Failed));
+        assert!(report.error.unwrap().starts_with("Could not find a route"));
+    }
+
+    #[test]
+    fn test_from_bolt11() {
+        let key = SecretKey::from_slice(&[4; 32]).unwrap();
+        let hash = Sha256::hash(&[7; 32]);
+        let bolt11 = crate::bolt11::Bolt11Builder::new("bcrt", hash, 1_700_000_000)
+            .description("test")
+            .payment_secret(Secret::try_from(vec![9; 32]).unwrap())
+            .sign(&key)
+            .unwrap();
+        let t = PayTarget::from_bolt11(&bolt11, Some(Amount::from_sat(1))).unwrap();
+        assert_eq!(t.destination, node(4));
+        assert_eq!(t.payment_hash, hash);
+        assert_eq!(t.final_cltv, 18);
+        assert_eq!(t.amount, Amount::from_sat(1));
+        assert!(PayTarget::from_bolt11(&bolt11, None).is_err());
+    }
+}
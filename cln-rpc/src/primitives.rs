@@ -7,8 +7,11 @@ use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use std::string::ToString;
 
+pub use crate::bolt11::Bolt11Builder;
+pub use crate::decode::Bolt11Invoice;
 pub use bitcoin::hashes::sha256::Hash as Sha256;
 pub use bitcoin::secp256k1::PublicKey;
+pub use crate::rune::{Alternative, Condition, Restriction, Rune, RuneRestrictions};
 
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 #[allow(non_camel_case_types)]
@@ -26,6 +29,27 @@ pub enum ChannelState {
     DUALOPEND_AWAITING_LOCKIN,
 }
 
+impl TryFrom<i32> for ChannelState {
+    type Error = anyhow::Error;
+
+    fn try_from(c: i32) -> Result<ChannelState, anyhow::Error> {
+        match c {
+            0 => Ok(ChannelState::OPENINGD),
+            1 => Ok(ChannelState::CHANNELD_AWAITING_LOCKIN),
+            2 => Ok(ChannelState::CHANNELD_NORMAL),
+            3 => Ok(ChannelState::CHANNELD_SHUTTING_DOWN),
+            4 => Ok(ChannelState::CLOSINGD_SIGEXCHANGE),
+            5 => Ok(ChannelState::CLOSINGD_COMPLETE),
+            6 => Ok(ChannelState::AWAITING_UNILATERAL),
+            7 => Ok(ChannelState::FUNDING_SPEND_SEEN),
+            8 => Ok(ChannelState::ONCHAIN),
+            9 => Ok(ChannelState::DUALOPEND_OPEN_INIT),
+            10 => Ok(ChannelState::DUALOPEND_AWAITING_LOCKIN),
+            o => Err(anyhow!("Unknown variant {} for enum ChannelState", o)),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize, Debug)]
 #[allow(non_camel_case_types)]
 #[serde(rename_all = "lowercase")]
@@ -55,52 +79,289 @@ pub enum AmountOrAll {
     All,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Amount {
     msat: u64,
 }
 
+/// The units an [`Amount`] can be parsed from and displayed in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AmountUnit {
+    Msat,
+    Sat,
+    Btc,
+}
+
+impl AmountUnit {
+    /// The number of decimal places between this unit and `msat`.
+    fn decimals(&self) -> u32 {
+        match self {
+            AmountUnit::Msat => 0,
+            AmountUnit::Sat => 3,
+            AmountUnit::Btc => 11,
+        }
+    }
+
+    fn suffix(&self) -> &'static str {
+        match self {
+            AmountUnit::Msat => "msat",
+            AmountUnit::Sat => "sat",
+            AmountUnit::Btc => "btc",
+        }
+    }
+}
+
 impl Amount {
+    pub const ZERO: Amount = Amount { msat: 0 };
+    pub const MAX: Amount = Amount { msat: u64::MAX };
+
     pub fn from_msat(msat: u64) -> Amount {
         Amount { msat }
     }
 
+    /// Saturates at [`Amount::MAX`], use [`Amount::checked_from_sat`]
+    /// to detect overflows.
     pub fn from_sat(sat: u64) -> Amount {
-        Amount { msat: 1_000 * sat }
+        Amount {
+            msat: sat.saturating_mul(1_000),
+        }
     }
 
+    /// Saturates at [`Amount::MAX`], use [`Amount::checked_from_btc`]
+    /// to detect overflows.
     pub fn from_btc(btc: u64) -> Amount {
         Amount {
-            msat: 100_000_000_000 * btc,
+            msat: btc.saturating_mul(100_000_000_000),
         }
     }
 
+    pub fn checked_from_sat(sat: u64) -> Option<Amount> {
+        sat.checked_mul(1_000).map(Amount::from_msat)
+    }
+
+    pub fn checked_from_btc(btc: u64) -> Option<Amount> {
+        btc.checked_mul(100_000_000_000).map(Amount::from_msat)
+    }
+
     pub fn msat(&self) -> u64 {
         self.msat
     }
+
+    /// The amount in whole satoshis, rounding down.
+    pub fn sat_floor(&self) -> u64 {
+        self.msat / 1_000
+    }
+
+    /// The amount in whole satoshis, rounding up.
+    pub fn sat_ceil(&self) -> u64 {
+        self.msat.div_ceil(1_000)
+    }
+
+    pub fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        self.msat.checked_add(rhs.msat).map(Amount::from_msat)
+    }
+
+    pub fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        self.msat.checked_sub(rhs.msat).map(Amount::from_msat)
+    }
+
+    pub fn checked_mul(self, rhs: u64) -> Option<Amount> {
+        self.msat.checked_mul(rhs).map(Amount::from_msat)
+    }
+
+    pub fn checked_div(self, rhs: u64) -> Option<Amount> {
+        self.msat.checked_div(rhs).map(Amount::from_msat)
+    }
+
+    pub fn saturating_add(self, rhs: Amount) -> Amount {
+        Amount::from_msat(self.msat.saturating_add(rhs.msat))
+    }
+
+    pub fn saturating_sub(self, rhs: Amount) -> Amount {
+        Amount::from_msat(self.msat.saturating_sub(rhs.msat))
+    }
+
+    pub fn saturating_mul(self, rhs: u64) -> Amount {
+        Amount::from_msat(self.msat.saturating_mul(rhs))
+    }
+
+    /// Display the amount in `unit`, using as many decimal places
+    /// as needed to be exact, e.g., `1.5sat` for 1500 msat.
+    pub fn display_in(self, unit: AmountUnit) -> AmountDisplay {
+        AmountDisplay { amount: self, unit }
+    }
+
+    /// Parse an amount with a `msat`, `sat` or `btc` suffix. Decimal
+    /// places are allowed as long as the result is a whole number of
+    /// millisatoshis, e.g., `0.001btc` or `1.5sat`.
+    fn parse(s: &str) -> Result<Amount> {
+        let lower = s.to_lowercase();
+        let (number, unit) = [AmountUnit::Msat, AmountUnit::Sat, AmountUnit::Btc]
+            .into_iter()
+            .find_map(|u| lower.strip_suffix(u.suffix()).map(|n| (n, u)))
+            .ok_or_else(|| anyhow!("Unable to parse amount from string: {}", s))?;
+
+        let (whole, frac) = match number.split_once('.') {
+            Some((w, f)) if !f.is_empty() => (w, f),
+            Some(_) => return Err(anyhow!("Missing decimal places in amount: {}", s)),
+            None => (number, ""),
+        };
+        let digits = |d: &str| !d.is_empty() && d.bytes().all(|c| c.is_ascii_digit());
+        if !digits(whole) || !(frac.is_empty() || digits(frac)) {
+            return Err(anyhow!("Unable to parse amount from string: {}", s));
+        }
+
+        // Anything beyond msat precision has to be zero to be exact.
+        let decimals = unit.decimals() as usize;
+        let (frac, rest) = frac.split_at(frac.len().min(decimals));
+        if rest.bytes().any(|c| c != b'0') {
+            return Err(anyhow!("Amount {} is not a whole number of msat", s));
+        }
+
+        let overflow = || anyhow!("Amount {} is too large", s);
+        let whole: u64 = whole.parse().map_err(|_| overflow())?;
+        let frac: u64 = format!("{:0<width$}", frac, width = decimals)
+            .parse()
+            .unwrap_or(0);
+        whole
+            .checked_mul(10u64.pow(unit.decimals()))
+            .and_then(|m| m.checked_add(frac))
+            .map(Amount::from_msat)
+            .ok_or_else(overflow)
+    }
+}
+
+/// Displays an [`Amount`] in a given unit, see
+/// [`Amount::display_in`].
+#[derive(Copy, Clone, Debug)]
+pub struct AmountDisplay {
+    amount: Amount,
+    unit: AmountUnit,
+}
+
+impl Display for AmountDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let scale = 10u64.pow(self.unit.decimals());
+        let (whole, frac) = (self.amount.msat / scale, self.amount.msat % scale);
+        if frac == 0 {
+            write!(f, "{}{}", whole, self.unit.suffix())
+        } else {
+            let frac = format!("{:0width$}", frac, width = self.unit.decimals() as usize);
+            write!(
+                f,
+                "{}.{}{}",
+                whole,
+                frac.trim_end_matches('0'),
+                self.unit.suffix()
+            )
+        }
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}msat", self.msat)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Amount::parse(s)
+    }
 }
 
 impl std::ops::Add for Amount {
     type Output = Amount;
 
+    /// Saturates at [`Amount::MAX`], use [`Amount::checked_add`] to
+    /// detect overflows.
     fn add(self, rhs: Self) -> Self::Output {
-        Amount {
-            msat: self.msat + rhs.msat,
-        }
+        self.saturating_add(rhs)
     }
 }
 
 impl std::ops::Sub for Amount {
     type Output = Amount;
 
+    /// Panics on underflow, use [`Amount::checked_sub`] or
+    /// [`Amount::saturating_sub`] if that's a possibility.
     fn sub(self, rhs: Self) -> Self::Output {
-        Amount {
-            msat: self.msat - rhs.msat,
+        self.checked_sub(rhs).expect("amount underflow")
+    }
+}
+
+impl std::ops::AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Mul<u64> for Amount {
+    type Output = Amount;
+
+    /// Saturates at [`Amount::MAX`], use [`Amount::checked_mul`] to
+    /// detect overflows.
+    fn mul(self, rhs: u64) -> Self::Output {
+        self.saturating_mul(rhs)
+    }
+}
+
+impl std::ops::Div<u64> for Amount {
+    type Output = Amount;
+
+    /// Rounds down, and panics if `rhs` is zero.
+    fn div(self, rhs: u64) -> Self::Output {
+        Amount::from_msat(self.msat / rhs)
+    }
+}
+
+impl std::iter::Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, |a, b| a + b)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Amount {
+        iter.copied().sum()
+    }
+}
+
+impl TryFrom<bitcoin::Amount> for Amount {
+    type Error = Error;
+
+    fn try_from(a: bitcoin::Amount) -> Result<Amount> {
+        a.to_sat()
+            .checked_mul(1_000)
+            .map(Amount::from_msat)
+            .ok_or_else(|| anyhow!("Amount {} is too large", a))
+    }
+}
+
+/// Fails unless the amount is a whole number of satoshis.
+impl TryFrom<Amount> for bitcoin::Amount {
+    type Error = Error;
+
+    fn try_from(a: Amount) -> Result<bitcoin::Amount> {
+        if a.sat_floor() != a.sat_ceil() {
+            return Err(anyhow!("Amount {} is not a whole number of sat", a));
         }
+        Ok(bitcoin::Amount::from_sat(a.sat_floor()))
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// A short channel id, i.e., the block height, transaction index and
+/// output number of the funding output. Internally this uses the
+/// BOLT7 `u64` encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ShortChannelId(u64);
 
 impl Serialize for ShortChannelId {
@@ -126,25 +387,72 @@ impl<'de> Deserialize<'de> for ShortChannelId {
 impl FromStr for ShortChannelId {
     type Err = crate::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Result<Vec<u64>, _> = s.split('x').map(|p| p.parse()).collect();
-        let parts = parts.with_context(|| format!("Malformed short_channel_id: {}", s))?;
+        let parts: Vec<&str> = s.split('x').collect();
         if parts.len() != 3 {
             return Err(anyhow!(
-                "Malformed short_channel_id: element count mismatch"
+                "Malformed short_channel_id {:?}: expected BLOCKxTXINDEXxOUTNUM",
+                s
             ));
         }
-
-        Ok(ShortChannelId(
-            (parts[0] << 40) | (parts[1] << 16) | (parts[2] << 0),
-        ))
+        let parse = |name: &str, p: &str| -> Result<u64> {
+            if p.is_empty() || !p.bytes().all(|c| c.is_ascii_digit()) {
+                return Err(anyhow!(
+                    "Malformed short_channel_id {:?}: {} is not a number",
+                    s,
+                    name
+                ));
+            }
+            p.parse().with_context(|| {
+                format!("Malformed short_channel_id {:?}: {} out of range", s, name)
+            })
+        };
+        let block = parse("block", parts[0])?;
+        let txindex = parse("txindex", parts[1])?;
+        let outnum = parse("outnum", parts[2])?;
+        if outnum > u16::MAX as u64 {
+            return Err(anyhow!(
+                "Malformed short_channel_id {:?}: outnum out of range",
+                s
+            ));
+        }
+        ShortChannelId::new(block, txindex, outnum as u16)
+            .with_context(|| format!("Malformed short_channel_id {:?}", s))
     }
 }
-impl ToString for ShortChannelId {
-    fn to_string(&self) -> String {
-        format!("{}x{}x{}", self.block(), self.txindex(), self.outnum())
+
+impl Display for ShortChannelId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}x{}", self.block(), self.txindex(), self.outnum())
     }
 }
+
 impl ShortChannelId {
+    /// The largest block height and transaction index that fit into
+    /// a short channel id.
+    pub const MAX_BLOCK: u64 = 0xFFFFFF;
+    pub const MAX_TXINDEX: u64 = 0xFFFFFF;
+
+    pub fn new(block: u64, txindex: u64, outnum: u16) -> Result<ShortChannelId> {
+        if block > Self::MAX_BLOCK {
+            return Err(anyhow!("block {} out of range", block));
+        }
+        if txindex > Self::MAX_TXINDEX {
+            return Err(anyhow!("txindex {} out of range", txindex));
+        }
+        Ok(ShortChannelId(
+            (block << 40) | (txindex << 16) | outnum as u64,
+        ))
+    }
+
+    /// Any `u64` is a valid BOLT7 encoding.
+    pub fn from_u64(scid: u64) -> ShortChannelId {
+        ShortChannelId(scid)
+    }
+
+    pub fn to_u64(&self) -> u64 {
+        self.0
+    }
+
     pub fn block(&self) -> u32 {
         (self.0 >> 40) as u32 & 0xFFFFFF
     }
@@ -156,9 +464,177 @@ impl ShortChannelId {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+impl From<u64> for ShortChannelId {
+    fn from(scid: u64) -> Self {
+        ShortChannelId::from_u64(scid)
+    }
+}
+
+impl From<ShortChannelId> for u64 {
+    fn from(scid: ShortChannelId) -> Self {
+        scid.to_u64()
+    }
+}
+
+/// A short channel id and a direction, formatted as `123x4x5/1`.
+/// Direction 0 is from the node with the lesser node id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShortChannelIdDir {
+    pub short_channel_id: ShortChannelId,
+    pub direction: u32,
+}
+
+impl ShortChannelIdDir {
+    pub fn new(short_channel_id: ShortChannelId, direction: u32) -> Result<ShortChannelIdDir> {
+        if direction > 1 {
+            return Err(anyhow!("direction must be 0 or 1, not {}", direction));
+        }
+        Ok(ShortChannelIdDir {
+            short_channel_id,
+            direction,
+        })
+    }
+
+    /// The same channel in the other direction.
+    pub fn reverse(&self) -> ShortChannelIdDir {
+        ShortChannelIdDir {
+            short_channel_id: self.short_channel_id,
+            direction: self.direction ^ 1,
+        }
+    }
+}
+
+impl FromStr for ShortChannelIdDir {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scid, dir) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Malformed short_channel_id_dir {:?}: missing direction", s))?;
+        let direction = match dir {
+            "0" => 0,
+            "1" => 1,
+            _ => {
+                return Err(anyhow!(
+                    "Malformed short_channel_id_dir {:?}: direction must be 0 or 1",
+                    s
+                ))
+            }
+        };
+        ShortChannelIdDir::new(scid.parse()?, direction)
+    }
+}
+
+impl Display for ShortChannelIdDir {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.short_channel_id, self.direction)
+    }
+}
+
+impl Serialize for ShortChannelIdDir {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortChannelIdDir {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| Error::custom(e.to_string()))
+    }
+}
+
+/// A short channel id that knows whether it refers to the funding
+/// output or is an alias. Aliases look just like real short channel
+/// ids on the wire, so this has to be tracked by whoever hands them
+/// out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScidOrAlias {
+    /// The short channel id of the funding output.
+    Real(ShortChannelId),
+    /// An alias we assigned to the channel.
+    LocalAlias(ShortChannelId),
+    /// An alias the peer assigned to the channel, to be used in
+    /// route hints.
+    RemoteAlias(ShortChannelId),
+}
+
+impl ScidOrAlias {
+    pub fn scid(&self) -> ShortChannelId {
+        match self {
+            ScidOrAlias::Real(s) | ScidOrAlias::LocalAlias(s) | ScidOrAlias::RemoteAlias(s) => *s,
+        }
+    }
+
+    pub fn is_alias(&self) -> bool {
+        !matches!(self, ScidOrAlias::Real(_))
+    }
+}
+
+impl From<ScidOrAlias> for ShortChannelId {
+    fn from(s: ScidOrAlias) -> Self {
+        s.scid()
+    }
+}
+
+/// Serializes as the bare short channel id, since that's all the
+/// JSON-RPC knows about.
+impl Serialize for ScidOrAlias {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.scid().serialize(serializer)
+    }
+}
+
+impl Display for ScidOrAlias {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.scid().fmt(f)
+    }
+}
+
+impl crate::model::ListpeersPeersChannels {
+    /// All short channel ids this channel is known by.
+    pub fn scids(&self) -> Vec<ScidOrAlias> {
+        let alias = self.alias.as_ref();
+        self.short_channel_id
+            .map(ScidOrAlias::Real)
+            .into_iter()
+            .chain(alias.and_then(|a| a.local).map(ScidOrAlias::LocalAlias))
+            .chain(alias.and_then(|a| a.remote).map(ScidOrAlias::RemoteAlias))
+            .collect()
+    }
+
+    /// The short channel id to use in a route hint towards us: the
+    /// real one for public channels, otherwise the peer's alias so
+    /// the funding output isn't revealed, falling back to the real
+    /// one if the peer didn't give us an alias.
+    pub fn routehint_scid(&self) -> Option<ScidOrAlias> {
+        let remote = self.alias.as_ref().and_then(|a| a.remote);
+        let hide = self.private == Some(true) || self.short_channel_id.is_none();
+        match remote {
+            Some(alias) if hide => Some(ScidOrAlias::RemoteAlias(alias)),
+            _ => self.short_channel_id.map(ScidOrAlias::Real),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Secret([u8; 32]);
 
+impl From<[u8; 32]> for Secret {
+    fn from(s: [u8; 32]) -> Secret {
+        Secret(s)
+    }
+}
+
 impl TryFrom<Vec<u8>> for Secret {
     type Error = crate::Error;
     fn try_from(v: Vec<u8>) -> Result<Self, crate::Error> {
@@ -254,6 +730,18 @@ pub enum ChannelSide {
     REMOTE,
 }
 
+impl TryFrom<i32> for ChannelSide {
+    type Error = anyhow::Error;
+
+    fn try_from(c: i32) -> Result<ChannelSide, anyhow::Error> {
+        match c {
+            0 => Ok(ChannelSide::LOCAL),
+            1 => Ok(ChannelSide::REMOTE),
+            o => Err(anyhow!("Unknown variant {} for enum ChannelSide", o)),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Amount {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -293,7 +781,7 @@ impl Serialize for Amount {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&format!("{}msat", self.msat))
+        serializer.serialize_str(&self.to_string())
     }
 }
 
@@ -303,7 +791,7 @@ impl Serialize for AmountOrAll {
         S: Serializer,
     {
         match self {
-            AmountOrAll::Amount(a) => serializer.serialize_str(&format!("{}msat", a.msat)),
+            AmountOrAll::Amount(a) => serializer.serialize_str(&a.to_string()),
             AmountOrAll::All => serializer.serialize_str("all"),
         }
     }
@@ -315,7 +803,7 @@ impl Serialize for AmountOrAny {
         S: Serializer,
     {
         match self {
-            AmountOrAny::Amount(a) => serializer.serialize_str(&format!("{}msat", a.msat)),
+            AmountOrAny::Amount(a) => serializer.serialize_str(&a.to_string()),
             AmountOrAny::Any => serializer.serialize_str("any"),
         }
     }
@@ -356,75 +844,152 @@ impl<'de> Deserialize<'de> for AmountOrAll {
 impl TryFrom<&str> for Amount {
     type Error = Error;
     fn try_from(s: &str) -> Result<Amount> {
-        let number: u64 = s
-            .chars()
-            .map(|c| c.to_digit(10))
-            .take_while(|opt| opt.is_some())
-            .fold(0, |acc, digit| acc * 10 + (digit.unwrap() as u64));
-
-        let s = s.to_lowercase();
-        if s.ends_with("msat") {
-            Ok(Amount::from_msat(number))
-        } else if s.ends_with("sat") {
-            Ok(Amount::from_sat(number))
-        } else if s.ends_with("btc") {
-            Ok(Amount::from_btc(number))
-        } else {
-            Err(anyhow!("Unable to parse amount from string: {}", s))
-        }
+        Amount::parse(s)
     }
 }
 
 impl From<Amount> for String {
     fn from(a: Amount) -> String {
-        format!("{}msat", a.msat)
+        a.to_string()
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// A feerate as accepted by `lightningd`: either one of its named
+/// estimates or an explicit rate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Feerate {
+    /// Alias for `min_acceptable`.
     Slow,
+    /// Alias for `opening`.
     Normal,
+    /// Alias for `unilateral_close`.
     Urgent,
+    Opening,
+    MutualClose,
+    UnilateralClose,
+    DelayedToUs,
+    HtlcResolution,
+    Penalty,
+    MinAcceptable,
+    MaxAcceptable,
+    /// Satoshi per 1000 virtual bytes.
     PerKb(u32),
+    /// Satoshi per 1000 weight units.
     PerKw(u32),
 }
 
+const FEERATE_NAMES: &[(&str, Feerate)] = &[
+    ("slow", Feerate::Slow),
+    ("normal", Feerate::Normal),
+    ("urgent", Feerate::Urgent),
+    ("opening", Feerate::Opening),
+    ("mutual_close", Feerate::MutualClose),
+    ("unilateral_close", Feerate::UnilateralClose),
+    ("delayed_to_us", Feerate::DelayedToUs),
+    ("htlc_resolution", Feerate::HtlcResolution),
+    ("penalty", Feerate::Penalty),
+    ("min_acceptable", Feerate::MinAcceptable),
+    ("max_acceptable", Feerate::MaxAcceptable),
+];
+
+impl Feerate {
+    /// The lowest feerate `lightningd` will use, in perkw.
+    pub const FLOOR_PERKW: u32 = 253;
+
+    /// An explicit rate in satoshi per virtual byte.
+    pub fn from_sat_per_vbyte(sat: u32) -> Result<Feerate> {
+        sat.checked_mul(1000)
+            .map(Feerate::PerKb)
+            .ok_or_else(|| anyhow!("Feerate {}sat/vB is too large", sat))
+    }
+
+    /// Is this an explicit rate, rather than an estimate `lightningd`
+    /// resolves?
+    pub fn is_explicit(&self) -> bool {
+        matches!(self, Feerate::PerKb(_) | Feerate::PerKw(_))
+    }
+
+    /// The explicit rate in satoshi per 1000 weight units. A perkb rate
+    /// is rounded up, as `lightningd` does. Note that `lightningd` also
+    /// raises rates below [`Feerate::FLOOR_PERKW`] to the floor, which
+    /// is not applied here.
+    pub fn per_kw(&self) -> Option<u32> {
+        match self {
+            Feerate::PerKw(v) => Some(*v),
+            Feerate::PerKb(v) => Some(v.div_ceil(4)),
+            _ => None,
+        }
+    }
+
+    /// The explicit rate in satoshi per 1000 virtual bytes. This is
+    /// always exact, which is why it doesn't fit in a `u32`.
+    pub fn per_kb(&self) -> Option<u64> {
+        match self {
+            Feerate::PerKw(v) => Some(*v as u64 * 4),
+            Feerate::PerKb(v) => Some(*v as u64),
+            _ => None,
+        }
+    }
+
+    /// The explicit rate in satoshi per virtual byte.
+    pub fn sat_per_vbyte(&self) -> Option<f64> {
+        self.per_kb().map(|v| v as f64 / 1000.0)
+    }
+}
+
+/// Parse a plain decimal `u32`, rejecting signs, whitespace and
+/// overflow.
+fn parse_feerate_number(digits: &str, s: &str) -> Result<u32> {
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("Unable to parse feerate from string: {}", s));
+    }
+    digits
+        .parse()
+        .with_context(|| format!("Feerate out of range: {}", s))
+}
+
 impl TryFrom<&str> for Feerate {
     type Error = Error;
     fn try_from(s: &str) -> Result<Feerate> {
-        let number: u32 = s
-            .chars()
-            .map(|c| c.to_digit(10))
-            .take_while(|opt| opt.is_some())
-            .fold(0, |acc, digit| acc * 10 + (digit.unwrap() as u32));
-
-        let s = s.to_lowercase();
-        if s.ends_with("perkw") {
-            Ok(Feerate::PerKw(number))
-        } else if s.ends_with("perkb") {
-            Ok(Feerate::PerKb(number))
-        } else if s == "slow" {
-            Ok(Feerate::Slow)
-        } else if s == "normal" {
-            Ok(Feerate::Normal)
-        } else if s == "urgent" {
-            Ok(Feerate::Urgent)
+        let lower = s.to_lowercase();
+        if let Some((_, f)) = FEERATE_NAMES.iter().find(|(n, _)| *n == lower) {
+            return Ok(*f);
+        }
+
+        if let Some(n) = lower.strip_suffix("perkw") {
+            Ok(Feerate::PerKw(parse_feerate_number(n, s)?))
+        } else if let Some(n) = lower.strip_suffix("perkb") {
+            Ok(Feerate::PerKb(parse_feerate_number(n, s)?))
         } else {
-            Err(anyhow!("Unable to parse feerate from string: {}", s))
+            // A bare number is perkb.
+            Ok(Feerate::PerKb(parse_feerate_number(&lower, s)?))
+        }
+    }
+}
+
+impl FromStr for Feerate {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Feerate> {
+        Feerate::try_from(s)
+    }
+}
+
+impl Display for Feerate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feerate::PerKb(v) => write!(f, "{}perkb", v),
+            Feerate::PerKw(v) => write!(f, "{}perkw", v),
+            named => {
+                let (name, _) = FEERATE_NAMES.iter().find(|(_, n)| n == named).unwrap();
+                write!(f, "{}", name)
+            }
         }
     }
 }
 
 impl From<&Feerate> for String {
     fn from(f: &Feerate) -> String {
-        match f {
-            Feerate::Slow => "slow".to_string(),
-            Feerate::Normal => "normal".to_string(),
-            Feerate::Urgent => "urgent".to_string(),
-            Feerate::PerKb(v) => format!("{}perkb", v),
-            Feerate::PerKw(v) => format!("{}perkw", v),
-        }
+        f.to_string()
     }
 }
 
@@ -490,21 +1055,244 @@ mod test {
     }
 
     #[test]
-    fn test_amount_all_any() {
-        let t = r#"{"any": "any", "all": "all", "not_any": "42msat", "not_all": "31337msat"}"#;
-
-        #[derive(Serialize, Deserialize, Debug, PartialEq)]
-        struct T {
-            all: AmountOrAll,
-            not_all: AmountOrAll,
-            any: AmountOrAny,
-            not_any: AmountOrAny,
+    fn test_amount_parse() {
+        let tests = vec![
+            ("0msat", 0),
+            ("10msat", 10),
+            ("1.000msat", 1),
+            ("42sat", 42_000),
+            ("1.5sat", 1_500),
+            ("0.001sat", 1),
+            ("1BTC", 100_000_000_000),
+            ("0.001btc", 100_000_000),
+            ("0.00000000001btc", 1),
+            ("0.000000000010btc", 1),
+            ("18446744073709551615msat", u64::MAX),
+        ];
+        for (s, msat) in tests {
+            let a: Amount = s.parse().unwrap();
+            assert_eq!(a.msat(), msat, "{}", s);
         }
 
-        let parsed: T = serde_json::from_str(t).unwrap();
+        for s in [
+            "",
+            "msat",
+            "10",
+            "1.5msat",
+            "0.0001sat",
+            "1.sat",
+            ".5sat",
+            "-1sat",
+            "1,5sat",
+            "0x10msat",
+            "18446744073709551616msat",
+            "184467440737095517sat",
+            "1 sat",
+        ] {
+            assert!(s.parse::<Amount>().is_err(), "{}", s);
+        }
+    }
 
-        let expected = T {
-            all: AmountOrAll::All,
+    #[test]
+    fn test_amount_display() {
+        let a = Amount::from_msat(123_456_789_001);
+        assert_eq!(a.to_string(), "123456789001msat");
+        assert_eq!(
+            a.display_in(AmountUnit::Sat).to_string(),
+            "123456789.001sat"
+        );
+        assert_eq!(
+            a.display_in(AmountUnit::Btc).to_string(),
+            "1.23456789001btc"
+        );
+        let a = Amount::from_sat(1_000);
+        assert_eq!(a.display_in(AmountUnit::Sat).to_string(), "1000sat");
+        assert_eq!(a.display_in(AmountUnit::Btc).to_string(), "0.00001btc");
+    }
+
+    #[test]
+    fn test_amount_ops() {
+        let a = Amount::from_msat(1_500);
+        assert_eq!(a + a, Amount::from_sat(3));
+        assert_eq!(a * 3, Amount::from_msat(4_500));
+        assert_eq!(a / 4, Amount::from_msat(375));
+        assert_eq!(Amount::MAX.checked_add(a), None);
+        assert_eq!(Amount::MAX.saturating_add(a), Amount::MAX);
+        assert_eq!(Amount::ZERO.checked_sub(a), None);
+        assert_eq!(Amount::ZERO.saturating_sub(a), Amount::ZERO);
+        assert_eq!(a.checked_div(0), None);
+        assert_eq!(Amount::MAX.checked_mul(2), None);
+        assert_eq!((a.sat_floor(), a.sat_ceil()), (1, 2));
+        assert_eq!([a, a, a].iter().sum::<Amount>(), Amount::from_msat(4_500));
+        assert_eq!(
+            Vec::<Amount>::new().into_iter().sum::<Amount>(),
+            Amount::ZERO
+        );
+
+        assert_eq!(
+            Amount::try_from(bitcoin::Amount::from_sat(42)).unwrap(),
+            Amount::from_sat(42)
+        );
+        assert!(Amount::try_from(bitcoin::Amount::from_sat(u64::MAX)).is_err());
+        assert!(bitcoin::Amount::try_from(a).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "amount underflow")]
+    fn test_amount_sub_underflow() {
+        let _ = Amount::ZERO - Amount::from_msat(1);
+    }
+
+    #[test]
+    fn test_amount_saturating() {
+        assert_eq!(
+            Amount::ZERO.saturating_sub(Amount::from_msat(1)),
+            Amount::ZERO
+        );
+        assert_eq!(Amount::MAX + Amount::from_msat(1), Amount::MAX);
+        assert_eq!(Amount::MAX * 2, Amount::MAX);
+        let max = [Amount::MAX, Amount::MAX];
+        assert_eq!(max.iter().sum::<Amount>(), Amount::MAX);
+        assert_eq!(Amount::from_sat(u64::MAX), Amount::MAX);
+        assert_eq!(Amount::from_btc(u64::MAX), Amount::MAX);
+        assert_eq!(Amount::checked_from_sat(u64::MAX), None);
+        assert_eq!(Amount::checked_from_btc(184_467_441), None);
+        assert_eq!(
+            Amount::checked_from_btc(184_467_440),
+            Some(Amount::from_msat(18_446_744_000_000_000_000))
+        );
+        assert_eq!(Amount::checked_from_sat(2), Some(Amount::from_msat(2_000)));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_amount_roundtrip(msat: u64) {
+            let a = Amount::from_msat(msat);
+            for unit in [AmountUnit::Msat, AmountUnit::Sat, AmountUnit::Btc] {
+                let s = a.display_in(unit).to_string();
+                proptest::prop_assert_eq!(s.parse::<Amount>().unwrap(), a);
+            }
+            let j = serde_json::to_string(&a).unwrap();
+            proptest::prop_assert_eq!(serde_json::from_str::<Amount>(&j).unwrap(), a);
+            proptest::prop_assert_eq!(serde_json::from_value::<Amount>(msat.into()).unwrap(), a);
+        }
+
+        #[test]
+        fn prop_amount_bitcoin(sat in 0..u64::MAX / 1_000) {
+            let a = Amount::from_sat(sat);
+            let b = bitcoin::Amount::try_from(a).unwrap();
+            proptest::prop_assert_eq!(b.to_sat(), sat);
+            proptest::prop_assert_eq!(Amount::try_from(b).unwrap(), a);
+        }
+
+        #[test]
+        fn prop_amount_ops(a: u64, b: u64) {
+            let (x, y) = (Amount::from_msat(a), Amount::from_msat(b));
+            proptest::prop_assert_eq!(x.checked_add(y).map(|s| s.msat()), a.checked_add(b));
+            proptest::prop_assert_eq!(x.checked_sub(y).map(|s| s.msat()), a.checked_sub(b));
+            proptest::prop_assert_eq!(x.saturating_add(y).msat(), a.saturating_add(b));
+            proptest::prop_assert_eq!(x.saturating_sub(y).msat(), a.saturating_sub(b));
+            if let Some(s) = x.checked_add(y) {
+                proptest::prop_assert_eq!(s - y, x);
+                proptest::prop_assert_eq!([x, y].into_iter().sum::<Amount>(), s);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scid() {
+        let scid: ShortChannelId = "103x2x1".parse().unwrap();
+        assert_eq!((scid.block(), scid.txindex(), scid.outnum()), (103, 2, 1));
+        assert_eq!(scid.to_u64(), (103 << 40) | (2 << 16) | 1);
+        assert_eq!(ShortChannelId::from_u64(scid.to_u64()), scid);
+        assert_eq!(scid.to_string(), "103x2x1");
+        assert_eq!(
+            "16777215x16777215x65535"
+                .parse::<ShortChannelId>()
+                .unwrap()
+                .to_u64(),
+            u64::MAX
+        );
+
+        for s in [
+            "",
+            "1x2",
+            "1x2x3x4",
+            "16777216x0x0",
+            "0x16777216x0",
+            "0x0x65536",
+            "1x-2x3",
+            "1x+2x3",
+            "ax1x1",
+            "1xx1",
+        ] {
+            assert!(s.parse::<ShortChannelId>().is_err(), "{}", s);
+        }
+        let e = "16777216x0x0".parse::<ShortChannelId>().unwrap_err();
+        assert_eq!(
+            format!("{:#}", e),
+            "Malformed short_channel_id \"16777216x0x0\": block 16777216 out of range"
+        );
+
+        let d: ShortChannelIdDir = serde_json::from_str("\"103x2x1/1\"").unwrap();
+        assert_eq!(d.short_channel_id, scid);
+        assert_eq!(d.direction, 1);
+        assert_eq!(d.reverse().to_string(), "103x2x1/0");
+        assert_eq!(serde_json::to_string(&d).unwrap(), "\"103x2x1/1\"");
+        for s in ["103x2x1", "103x2x1/2", "103x2x1/", "103x2x1/01"] {
+            assert!(s.parse::<ShortChannelIdDir>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_scid_alias() {
+        let c: crate::model::ListpeersPeersChannels = serde_json::from_value(serde_json::json!({
+            "state": "CHANNELD_NORMAL",
+            "short_channel_id": "103x2x1",
+            "private": true,
+            "alias": {"local": "15000000x1x0", "remote": "15000001x2x0"},
+            "opener": "local",
+            "features": [],
+            "htlcs": [],
+        }))
+        .unwrap();
+        let scids = c.scids();
+        assert_eq!(scids.len(), 3);
+        assert!(!scids[0].is_alias());
+        assert_eq!(
+            scids[1],
+            ScidOrAlias::LocalAlias("15000000x1x0".parse().unwrap())
+        );
+        assert_eq!(
+            c.routehint_scid(),
+            Some(ScidOrAlias::RemoteAlias("15000001x2x0".parse().unwrap()))
+        );
+        assert_eq!(serde_json::to_value(scids[2]).unwrap(), "15000001x2x0");
+
+        let mut public = c.clone();
+        public.private = Some(false);
+        assert_eq!(
+            public.routehint_scid(),
+            Some(ScidOrAlias::Real("103x2x1".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_amount_all_any() {
+        let t = r#"{"any": "any", "all": "all", "not_any": "42msat", "not_all": "31337msat"}"#;
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct T {
+            all: AmountOrAll,
+            not_all: AmountOrAll,
+            any: AmountOrAny,
+            not_any: AmountOrAny,
+        }
+
+        let parsed: T = serde_json::from_str(t).unwrap();
+
+        let expected = T {
+            all: AmountOrAll::All,
             any: AmountOrAny::Any,
             not_all: AmountOrAll::Amount(Amount { msat: 31337 }),
             not_any: AmountOrAny::Amount(Amount { msat: 42 }),
@@ -526,6 +1314,14 @@ mod test {
             ("urgent", Feerate::Urgent),
             ("12345perkb", Feerate::PerKb(12345)),
             ("54321perkw", Feerate::PerKw(54321)),
+            ("opening", Feerate::Opening),
+            ("mutual_close", Feerate::MutualClose),
+            ("unilateral_close", Feerate::UnilateralClose),
+            ("delayed_to_us", Feerate::DelayedToUs),
+            ("htlc_resolution", Feerate::HtlcResolution),
+            ("penalty", Feerate::Penalty),
+            ("min_acceptable", Feerate::MinAcceptable),
+            ("max_acceptable", Feerate::MaxAcceptable),
         ];
 
         for (input, output) in tests.into_iter() {
@@ -534,6 +1330,46 @@ mod test {
             let serialized: String = (&parsed).into();
             assert_eq!(serialized, input);
         }
+
+        assert_eq!(Feerate::from_str("1000").unwrap(), Feerate::PerKb(1000));
+        assert_eq!(Feerate::from_str("Urgent").unwrap(), Feerate::Urgent);
+        assert_eq!(
+            Feerate::from_str("4294967295perkw").unwrap(),
+            Feerate::PerKw(u32::MAX)
+        );
+        for bad in [
+            "",
+            "perkw",
+            "12x4perkw",
+            "-1perkb",
+            " 1perkb",
+            "4294967296perkw",
+            "99999999999999999999perkb",
+            "6blocks",
+            "minimum",
+            "fast",
+        ] {
+            assert!(Feerate::from_str(bad).is_err(), "{}", bad);
+        }
+        assert!(serde_json::from_str::<Feerate>(r#""1x""#).is_err());
+    }
+
+    #[test]
+    fn test_feerate_conversions() {
+        assert_eq!(Feerate::PerKw(253).per_kb(), Some(1012));
+        assert_eq!(Feerate::PerKw(u32::MAX).per_kb(), Some(u32::MAX as u64 * 4));
+        assert_eq!(Feerate::PerKb(1012).per_kw(), Some(253));
+        assert_eq!(Feerate::PerKb(1013).per_kw(), Some(254));
+        assert_eq!(Feerate::PerKb(u32::MAX).per_kw(), Some(1 << 30));
+        assert_eq!(Feerate::PerKw(2500).sat_per_vbyte(), Some(10.0));
+        assert_eq!(
+            Feerate::from_sat_per_vbyte(10).unwrap(),
+            Feerate::PerKb(10000)
+        );
+        assert!(Feerate::from_sat_per_vbyte(u32::MAX).is_err());
+        assert_eq!(Feerate::Normal.per_kw(), None);
+        assert!(!Feerate::Opening.is_explicit());
+        assert!(Feerate::PerKb(1).is_explicit());
     }
 
     #[test]
@@ -570,6 +1406,164 @@ mod test {
         let res = serde_json::to_string(&stream).unwrap();
         assert_eq!(res, "{\"31337\":\"0102030405\",\"42\":\"\"}");
     }
+
+    #[test]
+    fn test_bigsize() {
+        // From BOLT #1, Appendix A
+        let vectors: &[(u64, &str)] = &[
+            (0, "00"),
+            (252, "fc"),
+            (253, "fd00fd"),
+            (65535, "fdffff"),
+            (65536, "fe00010000"),
+            (4294967295, "feffffffff"),
+            (4294967296, "ff0000000100000000"),
+            (18446744073709551615, "ffffffffffffffffff"),
+        ];
+        for (v, enc) in vectors {
+            let mut buf = vec![];
+            write_bigsize(&mut buf, *v);
+            assert_eq!(hex::encode(&buf), *enc);
+            assert_eq!(read_bigsize(&buf).unwrap(), (*v, buf.len()));
+        }
+
+        let invalid: &[(&str, &str)] = &[
+            ("fd00fc", "non-canonical bigsize"),
+            ("fe0000ffff", "non-canonical bigsize"),
+            ("ff00000000ffffffff", "non-canonical bigsize"),
+            ("fd00", "truncated bigsize"),
+            ("feffff", "truncated bigsize"),
+            ("ffffffffff", "truncated bigsize"),
+            ("", "truncated bigsize"),
+        ];
+        for (enc, err) in invalid {
+            let e = read_bigsize(&hex::decode(enc).unwrap()).unwrap_err();
+            assert_eq!(e.to_string(), *err, "{}", enc);
+        }
+    }
+
+    #[test]
+    fn test_tlvstream_wire() {
+        let mut stream = TlvStream::new();
+        assert_eq!(stream.insert(253, vec![1]), None);
+        assert_eq!(stream.insert(1, vec![]), None);
+        assert_eq!(stream.insert(1, vec![2]), Some(vec![]));
+        let wire = stream.to_wire().unwrap();
+        assert_eq!(hex::encode(&wire), "010102fd00fd0101");
+        assert_eq!(TlvStream::from_wire(&wire).unwrap(), stream);
+        assert_eq!(stream.get(253), Some(&[1u8][..]));
+        assert_eq!(stream.remove(253), Some(vec![1]));
+        assert_eq!(stream.get(253), None);
+
+        // Entries pushed out of order are sorted, duplicates rejected.
+        stream.entries.push(TlvEntry {
+            typ: 0,
+            value: vec![],
+        });
+        assert_eq!(hex::encode(stream.to_wire().unwrap()), "0000010102");
+        stream.entries.push(TlvEntry {
+            typ: 1,
+            value: vec![],
+        });
+        let err = stream.to_wire().unwrap_err();
+        assert_eq!(err.to_string(), "duplicate TLV type 1");
+
+        let err = TlvStream::from_wire(&hex::decode("0100010100").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "duplicate TLV type 1");
+        let err = TlvStream::from_wire(&hex::decode("02000100").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "TLV type 1 out of order");
+        let err = TlvStream::from_wire(&hex::decode("0102ff").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "truncated value for TLV type 1");
+        assert!(TlvStream::from_wire(&hex::decode("fd000100").unwrap()).is_err());
+
+        let stream = TlvStream::from_wire(&hex::decode("0100020003000400").unwrap()).unwrap();
+        assert_eq!(stream.unknown_required(&[1, 2]), vec![4]);
+        assert!(stream.unknown_required(&[4]).contains(&2));
+    }
+
+    #[test]
+    fn test_tlvstream_serde() {
+        let stream: TlvStream = serde_json::from_str(r#"{"42": "", "31337": "0102"}"#).unwrap();
+        assert_eq!(stream.entries[0].typ, 42);
+        assert_eq!(stream.entries[1].typ, 31337);
+        assert_eq!(stream.entries[1].value, vec![1, 2]);
+
+        let err = serde_json::from_str::<TlvStream>(r#"{"42": "zz"}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid hex for TLV type 42"));
+    }
+
+    #[test]
+    fn test_tlv_values() {
+        assert_eq!(0u64.to_tlv_value(), Vec::<u8>::new());
+        assert_eq!(0x0102u32.to_tlv_value(), vec![1, 2]);
+        assert_eq!(u64::from_tlv_value(&[]).unwrap(), 0);
+        assert_eq!(u16::from_tlv_value(&[1, 2]).unwrap(), 0x0102);
+        assert!(u16::from_tlv_value(&[0, 1]).is_err());
+        assert!(u16::from_tlv_value(&[1, 2, 3]).is_err());
+        assert!(String::from_tlv_value(&[0xff]).is_err());
+
+        let scid = ShortChannelId::from_str("103x1x0").unwrap();
+        assert_eq!(
+            ShortChannelId::from_tlv_value(&scid.to_tlv_value()).unwrap(),
+            scid
+        );
+        assert!(ShortChannelId::from_tlv_value(&[1]).is_err());
+    }
+
+    #[test]
+    fn test_tlv_record() {
+        /// Extra TLVs a keysend might carry.
+        #[derive(Debug, PartialEq)]
+        struct Boost {
+            sender: String,
+            amount: Amount,
+            message: Option<String>,
+        }
+
+        impl TlvRecord for Boost {
+            fn to_tlv_stream(&self) -> Result<TlvStream> {
+                let mut s = TlvStream::new();
+                s.insert_as(65537, &self.sender);
+                s.insert_as(65539, &self.amount);
+                if let Some(m) = &self.message {
+                    s.insert_as(65541, m);
+                }
+                Ok(s)
+            }
+
+            fn from_tlv_stream(s: &TlvStream) -> Result<Self> {
+                if let Some(t) = s.unknown_required(&[]).first() {
+                    return Err(anyhow!("unknown even type {}", t));
+                }
+                Ok(Boost {
+                    sender: s.get_as(65537)?.ok_or_else(|| anyhow!("missing sender"))?,
+                    amount: s.get_as(65539)?.ok_or_else(|| anyhow!("missing amount"))?,
+                    message: s.get_as(65541)?,
+                })
+            }
+        }
+
+        let boost = Boost {
+            sender: "alice".to_string(),
+            amount: Amount::from_sat(21),
+            message: None,
+        };
+        let stream = boost.to_tlv_stream().unwrap();
+        assert_eq!(
+            serde_json::to_value(&stream).unwrap(),
+            serde_json::json!({"65537": "616c696365", "65539": "5208"})
+        );
+        let wire = stream.to_wire().unwrap();
+        let parsed = Boost::from_tlv_stream(&TlvStream::from_wire(&wire).unwrap()).unwrap();
+        assert_eq!(parsed, boost);
+
+        let mut stream = stream;
+        stream.insert(65538, vec![]);
+        assert!(Boost::from_tlv_stream(&stream).is_err());
+        stream.remove(65538);
+        stream.insert(65543, vec![]);
+        assert_eq!(Boost::from_tlv_stream(&stream).unwrap(), boost);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -627,12 +1621,238 @@ pub struct RoutehintList {
     pub hints: Vec<Routehint>,
 }
 
+/// The route hints as returned by `decodepay` and `decode`. On the
+/// wire this is an array of routes, each being an array of hops with
+/// the long-form field names.
+#[derive(Clone, Debug)]
+pub struct DecodeRoutehintList {
+    pub hints: Vec<Routehint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DecodeRoutehop {
+    pubkey: PublicKey,
+    short_channel_id: ShortChannelId,
+    fee_base_msat: Amount,
+    fee_proportional_millionths: u32,
+    cltv_expiry_delta: u32,
+}
+
+impl<'de> Deserialize<'de> for DecodeRoutehintList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let routes: Vec<Vec<DecodeRoutehop>> = Deserialize::deserialize(deserializer)?;
+
+        let mut hints = vec![];
+        for route in routes {
+            let mut hops = vec![];
+            for h in route {
+                hops.push(Routehop {
+                    id: h.pubkey,
+                    scid: h.short_channel_id,
+                    feebase: h.fee_base_msat,
+                    feeprop: h.fee_proportional_millionths,
+                    expirydelta: h
+                        .cltv_expiry_delta
+                        .try_into()
+                        .map_err(|_| Error::custom("cltv_expiry_delta out of range"))?,
+                });
+            }
+            hints.push(Routehint { hops });
+        }
+        Ok(DecodeRoutehintList { hints })
+    }
+}
+
+impl Serialize for DecodeRoutehintList {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let routes: Vec<Vec<DecodeRoutehop>> = self
+            .hints
+            .iter()
+            .map(|r| {
+                r.hops
+                    .iter()
+                    .map(|h| DecodeRoutehop {
+                        pubkey: h.id,
+                        short_channel_id: h.scid,
+                        fee_base_msat: h.feebase,
+                        fee_proportional_millionths: h.feeprop,
+                        cltv_expiry_delta: h.expirydelta as u32,
+                    })
+                    .collect()
+            })
+            .collect();
+        routes.serialize(serializer)
+    }
+}
+
+/// A set of feature bits, as used in `init` messages, node
+/// announcements, invoices and offers. The bits are stored in wire
+/// order, i.e., big-endian with bit 0 in the least significant bit of
+/// the last byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Features(Vec<u8>);
+
+impl Features {
+    pub fn from_bytes(b: &[u8]) -> Features {
+        Features(b.to_vec())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Is this exact `bit` set?
+    pub fn has(&self, bit: usize) -> bool {
+        let len = self.0.len();
+        if bit / 8 >= len {
+            return false;
+        }
+        self.0[len - 1 - bit / 8] & (1 << (bit % 8)) != 0
+    }
+
+    /// Is the feature `bit` belongs to set, either as required (even)
+    /// or optional (odd)?
+    pub fn supports(&self, bit: usize) -> bool {
+        self.has(bit & !1) || self.has(bit | 1)
+    }
+
+    /// Does this set require a feature we don't know about? `known`
+    /// lists the features we understand, in either parity.
+    pub fn unknown_required(&self, known: &[usize]) -> Vec<usize> {
+        self.bits()
+            .into_iter()
+            .filter(|b| b % 2 == 0 && !known.iter().any(|k| k & !1 == *b))
+            .collect()
+    }
+
+    pub fn set(&mut self, bit: usize) {
+        let needed = bit / 8 + 1;
+        if self.0.len() < needed {
+            let mut v = vec![0u8; needed - self.0.len()];
+            v.extend_from_slice(&self.0);
+            self.0 = v;
+        }
+        let len = self.0.len();
+        self.0[len - 1 - bit / 8] |= 1 << (bit % 8);
+    }
+
+    /// All set bits in ascending order.
+    pub fn bits(&self) -> Vec<usize> {
+        (0..self.0.len() * 8).filter(|b| self.has(*b)).collect()
+    }
+}
+
+impl FromStr for Features {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Features(
+            hex::decode(s).with_context(|| format!("Malformed feature bits: {}", s))?,
+        ))
+    }
+}
+
+impl Display for Features {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(&self.0))
+    }
+}
+
+impl Serialize for Features {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Features {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let s: String = Deserialize::deserialize(deserializer)?;
+        Features::from_str(&s).map_err(|e| Error::custom(e.to_string()))
+    }
+}
+
+/// Options a plugin registered with `lightningd`, as reported by
+/// `listconfigs`. Since plugins can register arbitrary options, this
+/// is a map from option name to its JSON value: a string, integer or
+/// boolean for simple options, an array for multi-options, or `null`
+/// if the option is unset.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PluginOptions(pub std::collections::BTreeMap<String, serde_json::Value>);
+
+impl PluginOptions {
+    /// Get the raw value for the option `name`, if the plugin
+    /// registered it.
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        self.0.get(name)
+    }
+
+    /// Get the value of a string option.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|v| v.as_str())
+    }
+
+    /// Get the value of an integer option.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(|v| v.as_i64())
+    }
+
+    /// Get the value of a boolean or flag option.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get(name).and_then(|v| v.as_bool())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &serde_json::Value)> {
+        self.0.iter()
+    }
+
+    /// Set the option `name` from its JSON encoding, keeping `value`
+    /// as a plain string if it isn't valid JSON.
+    pub fn insert_json(&mut self, name: String, value: String) {
+        let value = serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value));
+        self.0.insert(name, value);
+    }
+}
+
 /// An error returned by the lightningd RPC consisting of a code and a
 /// message
 #[derive(Clone, Serialize, Deserialize, Debug)]
+#[non_exhaustive]
 pub struct RpcError {
     pub code: Option<i32>,
     pub message: String,
+    /// Details some commands add, e.g. the failing hop for
+    /// `waitsendpay`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl RpcError {
+    pub fn new(code: Option<i32>, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> RpcError {
+        self.data = Some(data);
+        self
+    }
 }
 
 impl Display for RpcError {
@@ -647,31 +1867,303 @@ impl Display for RpcError {
 
 impl std::error::Error for RpcError {}
 
-#[derive(Clone, Debug)]
+/// Append `v` to `buf` as a BigSize, the variable length integer used
+/// for TLV types and lengths.
+pub fn write_bigsize(buf: &mut Vec<u8>, v: u64) {
+    match v {
+        0..=0xfc => buf.push(v as u8),
+        0xfd..=0xffff => {
+            buf.push(0xfd);
+            buf.extend_from_slice(&(v as u16).to_be_bytes());
+        }
+        0x10000..=0xffffffff => {
+            buf.push(0xfe);
+            buf.extend_from_slice(&(v as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xff);
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+}
+
+/// Read a BigSize from the start of `buf`, returning the value and
+/// the number of bytes used. Non-minimal encodings are rejected.
+pub fn read_bigsize(buf: &[u8]) -> Result<(u64, usize)> {
+    let (len, min) = match buf.first() {
+        None => return Err(anyhow!("truncated bigsize")),
+        Some(&b) if b < 0xfd => return Ok((b as u64, 1)),
+        Some(0xfd) => (2, 0xfd),
+        Some(0xfe) => (4, 0x10000),
+        Some(_) => (8, 0x100000000),
+    };
+    let bytes = buf
+        .get(1..1 + len)
+        .ok_or_else(|| anyhow!("truncated bigsize"))?;
+    let v = bytes.iter().fold(0u64, |acc, b| acc << 8 | *b as u64);
+    if v < min {
+        return Err(anyhow!("non-canonical bigsize"));
+    }
+    Ok((v, 1 + len))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TlvEntry {
     pub typ: u64,
     pub value: Vec<u8>,
 }
 
-#[derive(Clone, Debug)]
+impl TlvEntry {
+    /// Encode the type, length and value.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.value.len() + 2);
+        write_bigsize(&mut buf, self.typ);
+        write_bigsize(&mut buf, self.value.len() as u64);
+        buf.extend_from_slice(&self.value);
+        buf
+    }
+}
+
+/// A TLV stream. The wire encoding requires entries to be in strictly
+/// ascending type order. [`TlvStream::from_wire`], deserialization and
+/// the accessors keep it that way, while entries pushed directly can be
+/// put in order with [`TlvStream::canonicalize`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct TlvStream {
     pub entries: Vec<TlvEntry>,
 }
 
+impl TlvStream {
+    pub fn new() -> TlvStream {
+        TlvStream::default()
+    }
+
+    /// Parse a stream from its wire encoding, failing if types are
+    /// not strictly ascending, a bigsize isn't minimal or a value is
+    /// truncated.
+    pub fn from_wire(mut buf: &[u8]) -> Result<TlvStream> {
+        let mut entries: Vec<TlvEntry> = Vec::new();
+        while !buf.is_empty() {
+            let (typ, n) = read_bigsize(buf)?;
+            buf = &buf[n..];
+            let (len, n) = read_bigsize(buf)?;
+            buf = &buf[n..];
+            if (buf.len() as u64) < len {
+                return Err(anyhow!("truncated value for TLV type {}", typ));
+            }
+            if let Some(prev) = entries.last() {
+                if prev.typ == typ {
+                    return Err(anyhow!("duplicate TLV type {}", typ));
+                } else if prev.typ > typ {
+                    return Err(anyhow!("TLV type {} out of order", typ));
+                }
+            }
+            let (value, rest) = buf.split_at(len as usize);
+            entries.push(TlvEntry {
+                typ,
+                value: value.to_vec(),
+            });
+            buf = rest;
+        }
+        Ok(TlvStream { entries })
+    }
+
+    /// Encode the stream for the wire, in ascending type order.
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        let mut stream = self.clone();
+        stream.canonicalize()?;
+        Ok(stream.entries.iter().flat_map(|e| e.to_wire()).collect())
+    }
+
+    /// Sort the entries by type, failing if a type appears twice.
+    pub fn canonicalize(&mut self) -> Result<()> {
+        self.entries.sort_by_key(|e| e.typ);
+        match self.entries.windows(2).find(|w| w[0].typ == w[1].typ) {
+            Some(w) => Err(anyhow!("duplicate TLV type {}", w[0].typ)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get(&self, typ: u64) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|e| e.typ == typ)
+            .map(|e| &e.value[..])
+    }
+
+    /// Set the value of `typ`, returning the previous one if any.
+    pub fn insert(&mut self, typ: u64, value: Vec<u8>) -> Option<Vec<u8>> {
+        match self.entries.binary_search_by_key(&typ, |e| e.typ) {
+            Ok(i) => Some(std::mem::replace(&mut self.entries[i].value, value)),
+            Err(i) => {
+                self.entries.insert(i, TlvEntry { typ, value });
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, typ: u64) -> Option<Vec<u8>> {
+        let i = self.entries.iter().position(|e| e.typ == typ)?;
+        Some(self.entries.remove(i).value)
+    }
+
+    /// Get the value of `typ`, decoded as a `T`.
+    pub fn get_as<T: TlvValue>(&self, typ: u64) -> Result<Option<T>> {
+        self.get(typ)
+            .map(|v| T::from_tlv_value(v).with_context(|| format!("decoding TLV type {}", typ)))
+            .transpose()
+    }
+
+    /// Set the value of `typ` to the encoding of `value`.
+    pub fn insert_as<T: TlvValue>(&mut self, typ: u64, value: &T) {
+        self.insert(typ, value.to_tlv_value());
+    }
+
+    /// Even types other than the `known` ones. Unknown odd types can
+    /// be ignored ("it's OK to be odd"), but a stream with unknown even
+    /// types must be rejected.
+    pub fn unknown_required(&self, known: &[u64]) -> Vec<u64> {
+        self.entries
+            .iter()
+            .map(|e| e.typ)
+            .filter(|t| t & 1 == 0 && !known.contains(t))
+            .collect()
+    }
+}
+
+/// A value that can be stored in a TLV record. Integers use the
+/// truncated encodings (`tu16`, `tu32` and `tu64`), without leading
+/// zero bytes.
+pub trait TlvValue: Sized {
+    fn to_tlv_value(&self) -> Vec<u8>;
+    fn from_tlv_value(v: &[u8]) -> Result<Self>;
+}
+
+/// A typed TLV record, such as the `extratlvs` of a keysend or the
+/// extension fields of a custom message.
+///
+/// Implementations should use [`TlvStream::unknown_required`] to
+/// reject streams with even types they don't understand.
+pub trait TlvRecord: Sized {
+    fn to_tlv_stream(&self) -> Result<TlvStream>;
+    fn from_tlv_stream(stream: &TlvStream) -> Result<Self>;
+}
+
+macro_rules! tlv_truncated_int {
+    ($t:ty) => {
+        impl TlvValue for $t {
+            fn to_tlv_value(&self) -> Vec<u8> {
+                let b = self.to_be_bytes();
+                let skip = b.iter().take_while(|b| **b == 0).count();
+                b[skip..].to_vec()
+            }
+
+            fn from_tlv_value(v: &[u8]) -> Result<Self> {
+                if v.len() > std::mem::size_of::<$t>() {
+                    return Err(anyhow!("truncated integer too long"));
+                }
+                if v.first() == Some(&0) {
+                    return Err(anyhow!("non-minimal truncated integer"));
+                }
+                Ok(v.iter().fold(0, |acc, b| acc << 8 | *b as $t))
+            }
+        }
+    };
+}
+
+tlv_truncated_int!(u16);
+tlv_truncated_int!(u32);
+tlv_truncated_int!(u64);
+
+impl TlvValue for Vec<u8> {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        Ok(v.to_vec())
+    }
+}
+
+impl TlvValue for String {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        String::from_utf8(v.to_vec()).context("invalid utf8")
+    }
+}
+
+impl TlvValue for Amount {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.msat.to_tlv_value()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        Ok(Amount::from_msat(u64::from_tlv_value(v)?))
+    }
+}
+
+impl TlvValue for PublicKey {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.serialize().to_vec()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        Ok(PublicKey::from_slice(v)?)
+    }
+}
+
+impl TlvValue for Sha256 {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        Ok(Sha256::from_slice(v)?)
+    }
+}
+
+impl TlvValue for Secret {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        Secret::try_from(v.to_vec())
+    }
+}
+
+impl TlvValue for ShortChannelId {
+    fn to_tlv_value(&self) -> Vec<u8> {
+        self.to_u64().to_be_bytes().to_vec()
+    }
+
+    fn from_tlv_value(v: &[u8]) -> Result<Self> {
+        let b: [u8; 8] = v
+            .try_into()
+            .map_err(|_| anyhow!("short_channel_id must be 8 bytes"))?;
+        Ok(ShortChannelId::from_u64(u64::from_be_bytes(b)))
+    }
+}
+
 impl<'de> Deserialize<'de> for TlvStream {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let map: std::collections::HashMap<u64, String> = Deserialize::deserialize(deserializer)?;
+        use serde::de::Error;
+        let map: std::collections::BTreeMap<u64, String> = Deserialize::deserialize(deserializer)?;
 
         let entries = map
-            .iter()
-            .map(|(k, v)| TlvEntry {
-                typ: *k,
-                value: hex::decode(v).unwrap(),
+            .into_iter()
+            .map(|(typ, v)| {
+                let value = hex::decode(&v)
+                    .map_err(|_| Error::custom(format!("invalid hex for TLV type {}", typ)))?;
+                Ok(TlvEntry { typ, value })
             })
-            .collect();
+            .collect::<Result<_, D::Error>>()?;
 
         Ok(TlvStream { entries })
     }
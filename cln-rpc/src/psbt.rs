@@ -0,0 +1,613 @@
+//! Building, signing and sending transactions through PSBTs.
+//!
+//! `fundpsbt`, `utxopsbt` and `txprepare` return a base64 PSBT whose
+//! inputs are reserved in the node's wallet, which then has to be
+//! passed on to `signpsbt` and `sendpsbt`. A [`PsbtSession`] keeps the
+//! parsed [`Psbt`] in between, so outputs and external inputs can be
+//! added to it, and tracks the reservation: it's released through
+//! `unreserveinputs`, or `txdiscard` for `txprepare`, if sending fails
+//! or the session is dropped without being sent.
+//!
+//! Each operation uses a fresh connection to the RPC socket, so the
+//! reservation can be released from `Drop` as well.
+//! [`crate::coincontrol::CoinControl::reserve`] returns a session for
+//! the inputs it selects.
+use crate::model::requests::{
+    FundpsbtRequest, SendpsbtRequest, SignpsbtRequest, TxdiscardRequest, TxprepareRequest,
+    UnreserveinputsRequest, UtxopsbtRequest,
+};
+use crate::model::responses::SendpsbtResponse;
+use crate::primitives::Amount;
+use crate::ClnRpc;
+use anyhow::{anyhow, Context, Result};
+use bitcoin::consensus::encode;
+use bitcoin::psbt::{Input, Output, Psbt};
+use bitcoin::{OutPoint, Sequence, TxIn, TxOut, VarInt};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Weight of the witness of a wallet input, a signature and a key,
+/// like `bitcoin_tx_simple_input_witness_weight`.
+pub const SIMPLE_WITNESS_WEIGHT: u64 = 1 + (1 + 71) + (1 + 33);
+
+/// Weight of the `scriptSig` of a P2SH-wrapped wallet input.
+pub const P2SH_SCRIPTSIG_WEIGHT: u64 = 23 * 4;
+
+/// Parse the base64 PSBT returned by `lightningd`.
+pub fn decode_psbt(psbt: &str) -> Result<Psbt> {
+    let bytes = base64::decode(psbt).context("PSBT is not valid base64")?;
+    encode::deserialize(&bytes).context("Malformed PSBT")
+}
+
+/// Encode a PSBT to base64, as expected by `lightningd`.
+pub fn encode_psbt(psbt: &Psbt) -> String {
+    base64::encode(encode::serialize(psbt))
+}
+
+/// How to give reserved inputs back to the wallet.
+#[derive(Clone, Debug)]
+enum Release {
+    /// Inputs reserved by `fundpsbt` or `utxopsbt`.
+    Unreserve { psbt: String, reserve: Option<u32> },
+    /// A transaction from `txprepare`.
+    Discard { txid: String },
+}
+
+impl Release {
+    async fn run(self, rpc_path: &Path) -> Result<()> {
+        let mut rpc = ClnRpc::new(rpc_path).await?;
+        match self {
+            Release::Unreserve { psbt, reserve } => {
+                rpc.call_typed(UnreserveinputsRequest { psbt, reserve })
+                    .await?;
+            }
+            Release::Discard { txid } => {
+                rpc.call_typed(TxdiscardRequest { txid }).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A PSBT spending reserved wallet inputs, on its way to being sent.
+///
+/// Unless it's sent with [`PsbtSession::send`], consumed with
+/// [`PsbtSession::into_psbt`], or explicitly released with
+/// [`PsbtSession::release`], dropping it spawns a task releasing the
+/// inputs. That requires a running tokio runtime, otherwise the
+/// reservation simply expires.
+#[derive(Debug)]
+pub struct PsbtSession {
+    rpc_path: PathBuf,
+    psbt: Psbt,
+    release: Option<Release>,
+    /// The satisfaction weight of the inputs added by the caller.
+    external: HashMap<OutPoint, u64>,
+    change_outnum: Option<u32>,
+    signed: bool,
+}
+
+impl PsbtSession {
+    async fn new(rpc_path: &Path, psbt: &str, release: Release) -> Result<PsbtSession> {
+        let psbt = match decode_psbt(psbt) {
+            Ok(psbt) => psbt,
+            Err(e) => {
+                if let Err(r) = release.run(rpc_path).await {
+                    warn!("Failed to release reserved inputs: {}", r);
+                }
+                return Err(e);
+            }
+        };
+        Ok(PsbtSession {
+            rpc_path: rpc_path.to_path_buf(),
+            psbt,
+            release: Some(release),
+            external: HashMap::new(),
+            change_outnum: None,
+            signed: false,
+        })
+    }
+
+    /// Have `fundpsbt` select and reserve inputs.
+    pub async fn fund<P: AsRef<Path>>(rpc_path: P, req: FundpsbtRequest) -> Result<PsbtSession> {
+        let rpc_path = rpc_path.as_ref();
+        let reserve = req.reserve;
+        let res = ClnRpc::new(rpc_path).await?.call_typed(req).await?;
+        let release = Release::Unreserve {
+            psbt: res.psbt.clone(),
+            reserve,
+        };
+        let mut session = PsbtSession::new(rpc_path, &res.psbt, release).await?;
+        session.change_outnum = res.change_outnum;
+        Ok(session)
+    }
+
+    /// Have `utxopsbt` reserve the given inputs.
+    pub async fn utxo<P: AsRef<Path>>(rpc_path: P, req: UtxopsbtRequest) -> Result<PsbtSession> {
+        let rpc_path = rpc_path.as_ref();
+        let reserve = req.reserve;
+        let res = ClnRpc::new(rpc_path).await?.call_typed(req).await?;
+        let release = Release::Unreserve {
+            psbt: res.psbt.clone(),
+            reserve,
+        };
+        let mut session = PsbtSession::new(rpc_path, &res.psbt, release).await?;
+        session.change_outnum = res.change_outnum;
+        Ok(session)
+    }
+
+    /// Have `txprepare` build a transaction paying `req.outputs`.
+    pub async fn prepare<P: AsRef<Path>>(
+        rpc_path: P,
+        req: TxprepareRequest,
+    ) -> Result<PsbtSession> {
+        let rpc_path = rpc_path.as_ref();
+        let res = ClnRpc::new(rpc_path).await?.call_typed(req).await?;
+        let release = Release::Discard { txid: res.txid };
+        PsbtSession::new(rpc_path, &res.psbt, release).await
+    }
+
+    pub fn psbt(&self) -> &Psbt {
+        &self.psbt
+    }
+
+    /// The PSBT, e.g., to sign external inputs.
+    pub fn psbt_mut(&mut self) -> &mut Psbt {
+        &mut self.psbt
+    }
+
+    /// The index of the change output, if `lightningd` added one.
+    pub fn change_outnum(&self) -> Option<u32> {
+        self.change_outnum
+    }
+
+    /// Append `output`, returning its index.
+    pub fn add_output(&mut self, output: TxOut) -> u32 {
+        self.psbt.unsigned_tx.output.push(output);
+        self.psbt.outputs.push(Output::default());
+        self.psbt.outputs.len() as u32 - 1
+    }
+
+    /// Append an input spending `utxo` at `outpoint`, which isn't in
+    /// the node's wallet. It's up to the caller to sign it, and
+    /// `satisfaction_weight` is the weight its `scriptSig` and witness
+    /// will add once it is. Returns the index of the input.
+    pub fn add_input(&mut self, outpoint: OutPoint, utxo: TxOut, satisfaction_weight: u64) -> u32 {
+        self.psbt.unsigned_tx.input.push(TxIn {
+            previous_output: outpoint,
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            ..Default::default()
+        });
+        self.psbt.inputs.push(Input {
+            witness_utxo: Some(utxo),
+            ..Default::default()
+        });
+        self.external.insert(outpoint, satisfaction_weight);
+        self.psbt.inputs.len() as u32 - 1
+    }
+
+    /// Total amount spent by the inputs.
+    pub fn input_amount(&self) -> Result<Amount> {
+        let mut sat = 0;
+        for (i, (txin, input)) in self
+            .psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .zip(&self.psbt.inputs)
+            .enumerate()
+        {
+            let utxo = match (&input.witness_utxo, &input.non_witness_utxo) {
+                (Some(utxo), _) => utxo,
+                (None, Some(tx)) => tx
+                    .output
+                    .get(txin.previous_output.vout as usize)
+                    .ok_or_else(|| anyhow!("Input {} spends a missing output", i))?,
+                (None, None) => return Err(anyhow!("Input {} has no UTXO information", i)),
+            };
+            sat += utxo.value;
+        }
+        Ok(Amount::from_sat(sat))
+    }
+
+    /// Total amount of the outputs.
+    pub fn output_amount(&self) -> Amount {
+        Amount::from_sat(self.psbt.unsigned_tx.output.iter().map(|o| o.value).sum())
+    }
+
+    /// The fee paid by the transaction, fails if the outputs exceed
+    /// the inputs.
+    pub fn fee(&self) -> Result<Amount> {
+        let (input, output) = (self.input_amount()?, self.output_amount());
+        input.checked_sub(output).ok_or_else(|| {
+            anyhow!(
+                "Outputs ({}msat) exceed inputs ({}msat)",
+                output.msat(),
+                input.msat()
+            )
+        })
+    }
+
+    /// The weight of the transaction once signed. Inputs that are
+    /// already signed count with their actual `scriptSig` and witness,
+    /// wallet inputs like `lightningd` estimates them, and external
+    /// inputs with their `satisfaction_weight`.
+    pub fn weight(&self) -> Result<u64> {
+        let tx = &self.psbt.unsigned_tx;
+        // The segwit marker and flag.
+        let mut weight = tx.weight() as u64 + 2;
+        for (i, (txin, input)) in tx.input.iter().zip(&self.psbt.inputs).enumerate() {
+            weight += if input.final_script_sig.is_some() || input.final_script_witness.is_some() {
+                let script_sig = input
+                    .final_script_sig
+                    .as_ref()
+                    .map(|s| s.len())
+                    .unwrap_or(0);
+                let witness = input
+                    .final_script_witness
+                    .as_ref()
+                    .map(|w| w.serialized_len())
+                    .unwrap_or(1);
+                // The empty `scriptSig` is already part of the weight.
+                (VarInt(script_sig as u64).len() - 1 + script_sig) as u64 * 4 + witness as u64
+            } else if let Some(w) = self.external.get(&txin.previous_output) {
+                *w
+            } else {
+                let script = input
+                    .witness_utxo
+                    .as_ref()
+                    .map(|u| &u.script_pubkey)
+                    .ok_or_else(|| anyhow!("Can't estimate the weight of input {}", i))?;
+                if script.is_v0_p2wpkh() {
+                    SIMPLE_WITNESS_WEIGHT
+                } else if script.is_p2sh() {
+                    SIMPLE_WITNESS_WEIGHT + P2SH_SCRIPTSIG_WEIGHT
+                } else {
+                    return Err(anyhow!("Can't estimate the weight of input {}", i));
+                }
+            };
+        }
+        Ok(weight)
+    }
+
+    /// The feerate in satoshis per 1000 weight units, rounded down.
+    pub fn feerate_per_kw(&self) -> Result<u64> {
+        Ok(self.fee()?.sat_floor() * 1000 / self.weight()?)
+    }
+
+    /// Have `lightningd` sign the wallet inputs.
+    pub async fn sign(&mut self) -> Result<()> {
+        let mut rpc = ClnRpc::new(&self.rpc_path).await?;
+        let res = rpc
+            .call_typed(SignpsbtRequest {
+                psbt: encode_psbt(&self.psbt),
+                signonly: None,
+            })
+            .await?;
+        self.psbt = decode_psbt(&res.signed_psbt)?;
+        self.signed = true;
+        Ok(())
+    }
+
+    /// Sign the wallet inputs, unless [`PsbtSession::sign`] already
+    /// did, and broadcast the transaction with `sendpsbt`. The inputs
+    /// are released should either fail.
+    pub async fn send(mut self) -> Result<SendpsbtResponse> {
+        match self.try_send().await {
+            Ok(res) => {
+                self.release = None;
+                debug!("Sent transaction {}", res.txid);
+                Ok(res)
+            }
+            Err(e) => {
+                if let Err(r) = self.release_inner().await {
+                    warn!("Failed to release reserved inputs: {}", r);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn try_send(&mut self) -> Result<SendpsbtResponse> {
+        if !self.signed {
+            self.sign().await?;
+        }
+        let mut rpc = ClnRpc::new(&self.rpc_path).await?;
+        Ok(rpc
+            .call_typed(SendpsbtRequest {
+                psbt: encode_psbt(&self.psbt),
+                reserve: None,
+            })
+            .await?)
+    }
+
+    /// Return the PSBT, leaving its inputs reserved.
+    pub fn into_psbt(mut self) -> Psbt {
+        self.release = None;
+        self.psbt.clone()
+    }
+
+    /// Release the reserved inputs and wait for `lightningd` to
+    /// confirm.
+    pub async fn release(mut self) -> Result<()> {
+        self.release_inner().await
+    }
+
+    async fn release_inner(&mut self) -> Result<()> {
+        match self.release.take() {
+            Some(r) => r.run(&self.rpc_path).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PsbtSession {
+    fn drop(&mut self) {
+        let release = match self.release.take() {
+            Some(r) => r,
+            None => return,
+        };
+
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(h) => h,
+            Err(_) => {
+                warn!("No runtime to release reserved inputs, they will expire instead");
+                return;
+            }
+        };
+
+        let rpc_path = self.rpc_path.clone();
+        handle.spawn(async move {
+            if let Err(e) = release.run(&rpc_path).await {
+                warn!("Failed to release reserved inputs: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::codec::JsonCodec;
+    use crate::primitives::{AmountOrAll, Feerate};
+    use bitcoin::hashes::Hash;
+    use bitcoin::{PackedLockTime, Script, Transaction, Txid, Witness};
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::{json, Value};
+    use tokio::net::UnixListener;
+    use tokio::sync::mpsc;
+    use tokio_util::codec::Framed;
+
+    fn p2wpkh(b: u8) -> Script {
+        Script::from(hex::decode(format!("0014{}", hex::encode([b; 20]))).unwrap())
+    }
+
+    /// A PSBT spending a 100000sat wallet output, with 60000sat of
+    /// change.
+    fn funded() -> Psbt {
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime(0),
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::from_inner([1; 32]), 0),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: 60_000,
+                script_pubkey: p2wpkh(2),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: 100_000,
+            script_pubkey: p2wpkh(1),
+        });
+        psbt
+    }
+
+    /// A fake `lightningd` signing the wallet input, and reporting
+    /// the calls it sees. `sendpsbt` fails if the PSBT has more than
+    /// two outputs.
+    async fn mock_node(listener: UnixListener, calls: mpsc::UnboundedSender<(String, Value)>) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let calls = calls.clone();
+            tokio::spawn(async move {
+                let mut framed = Framed::new(stream, JsonCodec::default());
+                while let Some(Ok(req)) = framed.next().await {
+                    let method = req["method"].as_str().unwrap().to_string();
+                    let params = req["params"].clone();
+                    calls.send((method.clone(), params.clone())).unwrap();
+                    let result = match method.as_str() {
+                        "fundpsbt" => json!({
+                            "psbt": encode_psbt(&funded()),
+                            "feerate_per_kw": 253,
+                            "estimated_final_weight": 561,
+                            "excess_msat": 0,
+                            "change_outnum": 0,
+                        }),
+                        "txprepare" => json!({
+                            "psbt": encode_psbt(&funded()),
+                            "unsigned_tx": "",
+                            "txid": "11".repeat(32),
+                        }),
+                        "signpsbt" => {
+                            let mut psbt = decode_psbt(params["psbt"].as_str().unwrap()).unwrap();
+                            psbt.inputs[0].final_script_witness =
+                                Some(Witness::from_vec(vec![vec![0; 72], vec![2; 33]]));
+                            json!({"signed_psbt": encode_psbt(&psbt)})
+                        }
+                        "sendpsbt" => {
+                            let psbt = decode_psbt(params["psbt"].as_str().unwrap()).unwrap();
+                            if psbt.outputs.len() > 2 {
+                                json!(null)
+                            } else {
+                                let tx = psbt.extract_tx();
+                                json!({
+                                    "tx": hex::encode(encode::serialize(&tx)),
+                                    "txid": tx.txid().to_string(),
+                                })
+                            }
+                        }
+                        "unreserveinputs" => json!({"reservations": []}),
+                        "txdiscard" => json!({"unsigned_tx": "", "txid": "11".repeat(32)}),
+                        m => panic!("unexpected method {}", m),
+                    };
+                    let response = if result.is_null() {
+                        json!({"jsonrpc": "2.0", "id": req["id"], "error": {"code": -1, "message": "rejected"}})
+                    } else {
+                        json!({"jsonrpc": "2.0", "id": req["id"], "result": result})
+                    };
+                    framed.send(response).await.unwrap();
+                }
+            });
+        }
+    }
+
+    fn fund_request() -> FundpsbtRequest {
+        FundpsbtRequest {
+            satoshi: AmountOrAll::Amount(Amount::from_sat(30_000)),
+            feerate: Feerate::PerKw(253),
+            startweight: 0,
+            minconf: None,
+            reserve: Some(6),
+            locktime: None,
+            min_witness_weight: None,
+            excess_as_change: Some(true),
+        }
+    }
+
+    #[test]
+    fn test_accounting() {
+        let mut session = PsbtSession {
+            rpc_path: PathBuf::new(),
+            psbt: funded(),
+            release: None,
+            external: HashMap::new(),
+            change_outnum: None,
+            signed: false,
+        };
+
+        // 10 bytes of header, 41 per input and 31 per output, plus
+        // the marker, flag and witness.
+        assert_eq!(session.weight().unwrap(), (10 + 41 + 31) * 4 + 2 + 107);
+        assert_eq!(session.fee().unwrap(), Amount::from_sat(40_000));
+
+        session.add_output(TxOut {
+            value: 30_000,
+            script_pubkey: p2wpkh(3),
+        });
+        session.add_input(
+            OutPoint::new(Txid::from_inner([4; 32]), 1),
+            TxOut {
+                value: 5_000,
+                script_pubkey: p2wpkh(4),
+            },
+            200,
+        );
+        assert_eq!(
+            session.weight().unwrap(),
+            (10 + 41 * 2 + 31 * 2) * 4 + 2 + 107 + 200
+        );
+        assert_eq!(session.fee().unwrap(), Amount::from_sat(15_000));
+        assert_eq!(session.feerate_per_kw().unwrap(), 15_000_000 / 925);
+
+        session.psbt_mut().inputs[0].final_script_witness =
+            Some(Witness::from_vec(vec![vec![0; 72], vec![2; 33]]));
+        assert_eq!(
+            session.weight().unwrap(),
+            (10 + 41 * 2 + 31 * 2) * 4 + 2 + 108 + 200
+        );
+
+        session.add_output(TxOut {
+            value: 20_000,
+            script_pubkey: p2wpkh(5),
+        });
+        assert!(session.fee().is_err());
+        session.psbt_mut().inputs[1].witness_utxo = None;
+        assert!(session.input_amount().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session() {
+        let path = std::env::temp_dir().join(format!("cln-rpc-psbt-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        tokio::spawn(mock_node(listener, tx));
+
+        let mut session = PsbtSession::fund(&path, fund_request()).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().0, "fundpsbt");
+        assert_eq!(session.change_outnum(), Some(0));
+        session.add_output(TxOut {
+            value: 30_000,
+            script_pubkey: p2wpkh(3),
+        });
+        let res = session.send().await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().0, "signpsbt");
+        let (method, params) = rx.recv().await.unwrap();
+        assert_eq!(method, "sendpsbt");
+        let sent = decode_psbt(params["psbt"].as_str().unwrap()).unwrap();
+        assert_eq!(sent.unsigned_tx.output.len(), 2);
+        assert_eq!(res.txid, sent.unsigned_tx.txid().to_string());
+
+        // A failed send releases the reservation, with the original
+        // PSBT.
+        let mut session = PsbtSession::fund(&path, fund_request()).await.unwrap();
+        rx.recv().await.unwrap();
+        for v in [1, 2] {
+            session.add_output(TxOut {
+                value: v,
+                script_pubkey: p2wpkh(3),
+            });
+        }
+        assert!(session.send().await.is_err());
+        assert_eq!(rx.recv().await.unwrap().0, "signpsbt");
+        assert_eq!(rx.recv().await.unwrap().0, "sendpsbt");
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            (
+                "unreserveinputs".to_string(),
+                json!({"psbt": encode_psbt(&funded()), "reserve": 6})
+            )
+        );
+
+        // So does dropping it, `txprepare` ones are discarded.
+        let session = PsbtSession::fund(&path, fund_request()).await.unwrap();
+        rx.recv().await.unwrap();
+        drop(session);
+        assert_eq!(rx.recv().await.unwrap().0, "unreserveinputs");
+
+        let session = PsbtSession::prepare(
+            &path,
+            TxprepareRequest {
+                outputs: vec![],
+                feerate: None,
+                minconf: None,
+                utxos: None,
+            },
+        )
+        .await
+        .unwrap();
+        rx.recv().await.unwrap();
+        drop(session);
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            ("txdiscard".to_string(), json!({"txid": "11".repeat(32)}))
+        );
+
+        // Unless it's been taken.
+        let session = PsbtSession::fund(&path, fund_request()).await.unwrap();
+        rx.recv().await.unwrap();
+        assert_eq!(session.into_psbt(), funded());
+        let session = PsbtSession::fund(&path, fund_request()).await.unwrap();
+        rx.recv().await.unwrap();
+        session.release().await.unwrap();
+        assert_eq!(rx.recv().await.unwrap().0, "unreserveinputs");
+        assert!(rx.try_recv().is_err());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
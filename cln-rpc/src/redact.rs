@@ -0,0 +1,100 @@
+//! Redaction of sensitive fields in JSON-RPC messages, so requests and
+//! responses can be logged without leaking invoices, PSBTs or rune
+//! material.
+//!
+//! ```
+//! use serde_json::json;
+//!
+//! let req = json!({"method": "pay", "params": {"bolt11": "lnbc1...", "retry_for": 60}});
+//! assert_eq!(
+//!     cln_rpc::redact::redact(&req),
+//!     json!({"method": "pay", "params": {"bolt11": "[redacted]", "retry_for": 60}})
+//! );
+//! ```
+use serde_json::Value;
+
+/// What sensitive values are replaced with.
+pub const REDACTED: &str = "[redacted]";
+
+/// The fields whose values are redacted, wherever they appear in a
+/// message.
+pub const SENSITIVE_FIELDS: &[&str] = &[
+    "bolt11",
+    "bolt12",
+    "invoice",
+    "invstring",
+    "offer",
+    "label",
+    "description",
+    "psbt",
+    "initialpsbt",
+    "signed_psbt",
+    "rune",
+    "restrictions",
+    "payment_secret",
+    "payment_preimage",
+    "preimage",
+    "secret",
+];
+
+/// Return a copy of `value` with the values of all
+/// [`SENSITIVE_FIELDS`] replaced by [`REDACTED`].
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(o) => Value::Object(
+            o.iter()
+                .map(|(k, v)| {
+                    let v = if SENSITIVE_FIELDS.contains(&k.as_str()) && !v.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact(v)
+                    };
+                    (k.clone(), v)
+                })
+                .collect(),
+        ),
+        Value::Array(a) => Value::Array(a.iter().map(redact).collect()),
+        v => v.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact() {
+        let res = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"invoices": [
+                {"label": "coffee", "bolt11": "lnbcrt1", "status": "paid", "amount_msat": 1000},
+                {"label": "tea", "bolt11": null, "status": "unpaid"},
+            ]}
+        });
+        assert_eq!(
+            redact(&res),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {"invoices": [
+                    {"label": REDACTED, "bolt11": REDACTED, "status": "paid", "amount_msat": 1000},
+                    {"label": REDACTED, "bolt11": null, "status": "unpaid"},
+                ]}
+            })
+        );
+
+        let req = json!({"method": "commando-rune", "params": {"rune": "tU-RL", "restrictions": [["method^list"]]}});
+        assert_eq!(
+            redact(&req),
+            json!({"method": "commando-rune", "params": {"rune": REDACTED, "restrictions": REDACTED}})
+        );
+
+        let res = json!({"result": {"invoice": "lni1", "changes": {}}});
+        assert_eq!(
+            redact(&res),
+            json!({"result": {"invoice": REDACTED, "changes": {}}})
+        );
+    }
+}
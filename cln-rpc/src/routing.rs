@@ -0,0 +1,817 @@
+//! Local pathfinding over the channel graph.
+//!
+//! [`Graph`] is built from `listchannels` (and optionally `listnodes`)
+//! and searched from the destination back to the source, the same way
+//! `getroute` does, so each hop's fees can be computed from the amount
+//! it has to forward. Results are routes ready to be passed to
+//! `sendpay`.
+//!
+//! The cost of a hop combines its fee, the cost of locking up the
+//! amount for its CLTV delta (like `getroute`'s `riskfactor`) and an
+//! optional penalty for the probability that the channel lacks the
+//! liquidity, see [`RouteParams`].
+use crate::bolt11::DEFAULT_MIN_FINAL_CLTV_EXPIRY;
+use crate::model::requests::SendpayRoute;
+use crate::model::responses::{ListchannelsResponse, ListnodesResponse};
+use crate::primitives::{Amount, Features, PublicKey, ShortChannelId, ShortChannelIdDir};
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// `var_onion_optin`, which every hop of a route has to support.
+const VAR_ONION_OPTIN: usize = 8;
+
+/// Blocks per year, to turn `riskfactor` into a cost per block.
+const BLOCKS_PER_YEAR: f64 = 52596.0;
+
+/// One direction of a channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub source: PublicKey,
+    pub destination: PublicKey,
+    pub short_channel_id: ShortChannelId,
+    pub direction: u32,
+    pub capacity: Amount,
+    pub base_fee_msat: u32,
+    pub fee_per_millionth: u32,
+    pub cltv_delta: u32,
+    pub htlc_minimum_msat: Amount,
+    pub htlc_maximum_msat: Option<Amount>,
+    pub active: bool,
+}
+
+impl Edge {
+    /// The fee `source` charges to forward `amount` over this edge.
+    pub fn fee(&self, amount: Amount) -> Amount {
+        let prop = amount.msat() as u128 * self.fee_per_millionth as u128 / 1_000_000;
+        Amount::from_msat((self.base_fee_msat as u128 + prop).min(u64::MAX as u128) as u64)
+    }
+
+    /// Can `amount` be sent over this edge?
+    pub fn can_carry(&self, amount: Amount) -> bool {
+        self.active
+            && amount >= self.htlc_minimum_msat
+            && amount <= self.capacity
+            && self.htlc_maximum_msat.is_none_or(|max| amount <= max)
+    }
+
+    pub fn scid_dir(&self) -> ShortChannelIdDir {
+        ShortChannelIdDir {
+            short_channel_id: self.short_channel_id,
+            direction: self.direction,
+        }
+    }
+}
+
+/// Constraints and weights for a route search.
+#[derive(Clone, Debug)]
+pub struct RouteParams {
+    /// The CLTV delta the destination requires.
+    pub final_cltv: u32,
+    pub max_hops: usize,
+    pub max_fee: Option<Amount>,
+    /// Maximum total CLTV delay, including `final_cltv`.
+    pub max_delay: u32,
+    /// Multiplier for fees in the cost of a route.
+    pub fee_weight: f64,
+    /// Annual interest rate, in percent, the locked up amount costs
+    /// per block of delay, as in `getroute`.
+    pub riskfactor: f64,
+    /// Penalty in msat per unit of `-ln(p)`, where `p` is the
+    /// probability the channel can carry the amount assuming its
+    /// liquidity is uniformly distributed. 0 ignores capacity beyond
+    /// the hard limit.
+    pub probability_weight: f64,
+    pub exclude_channels: HashSet<ShortChannelIdDir>,
+    pub exclude_nodes: HashSet<PublicKey>,
+}
+
+impl Default for RouteParams {
+    fn default() -> Self {
+        RouteParams {
+            final_cltv: DEFAULT_MIN_FINAL_CLTV_EXPIRY,
+            max_hops: 20,
+            max_fee: None,
+            max_delay: 2016,
+            fee_weight: 1.0,
+            riskfactor: 10.0,
+            probability_weight: 0.0,
+            exclude_channels: HashSet::new(),
+            exclude_nodes: HashSet::new(),
+        }
+    }
+}
+
+impl RouteParams {
+    /// The cost of sending `amount` over `edge` for `delay` blocks,
+    /// `fee` being what the sender of the edge charges for it.
+    fn cost(&self, edge: &Edge, amount: Amount, fee: Amount, delay: u32) -> f64 {
+        let msat = amount.msat() as f64;
+        let mut cost = fee.msat() as f64 * self.fee_weight;
+        cost += msat * delay as f64 * self.riskfactor / BLOCKS_PER_YEAR / 100.0;
+        if self.probability_weight > 0.0 {
+            let cap = edge.capacity.msat() as f64 + 1.0;
+            cost -= self.probability_weight * ((cap - msat) / cap).ln();
+        }
+        cost
+    }
+}
+
+/// A route with its total cost, as edges from source to destination.
+#[derive(Clone, Debug)]
+struct Path {
+    edges: Vec<usize>,
+    cost: f64,
+    hops: Vec<SendpayRoute>,
+}
+
+/// Search state for a node: the amount it must receive and the delay
+/// it is offered, to get `amount` to the destination from there.
+#[derive(Clone, Copy, Debug)]
+struct Label {
+    cost: f64,
+    amount: Amount,
+    delay: u32,
+    hops: usize,
+    /// The edge this node uses to forward, `None` at the destination.
+    edge: Option<usize>,
+}
+
+struct HeapEntry {
+    cost: f64,
+    node: PublicKey,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    // Reversed, to make `BinaryHeap` a min-heap.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// The fields of an [`Edge`] that can be updated in place. The
+/// endpoints and channel id are left out since the graph indexes
+/// edges by them; use [`Graph::add_edge`] to replace an edge.
+#[derive(Debug)]
+pub struct EdgePolicyMut<'a> {
+    pub capacity: &'a mut Amount,
+    pub base_fee_msat: &'a mut u32,
+    pub fee_per_millionth: &'a mut u32,
+    pub cltv_delta: &'a mut u32,
+    pub htlc_minimum_msat: &'a mut Amount,
+    pub htlc_maximum_msat: &'a mut Option<Amount>,
+    pub active: &'a mut bool,
+}
+
+/// The channel graph.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    edges: Vec<Edge>,
+    /// Indices into `edges`, by destination.
+    incoming: HashMap<PublicKey, Vec<usize>>,
+    /// Indices into `edges`, by channel and direction.
+    by_scid: HashMap<ShortChannelIdDir, usize>,
+    /// Node features, for nodes we have an announcement for.
+    features: HashMap<PublicKey, Features>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph::default()
+    }
+
+    /// Add a channel direction, replacing any edge we already have
+    /// for it.
+    pub fn add_edge(&mut self, edge: Edge) {
+        let idx = match self.by_scid.get(&edge.scid_dir()) {
+            Some(&idx) => {
+                let old = &self.edges[idx];
+                if let Some(v) = self.incoming.get_mut(&old.destination) {
+                    v.retain(|i| *i != idx);
+                }
+                self.edges[idx] = edge;
+                idx
+            }
+            None => {
+                self.by_scid.insert(edge.scid_dir(), self.edges.len());
+                self.edges.push(edge);
+                self.edges.len() - 1
+            }
+        };
+        self.incoming
+            .entry(self.edges[idx].destination)
+            .or_default()
+            .push(idx);
+    }
+
+    /// Build a graph from the result of `listchannels`.
+    pub fn from_listchannels(res: &ListchannelsResponse) -> Graph {
+        let mut graph = Graph::new();
+        for c in &res.channels {
+            graph.add_edge(Edge {
+                source: c.source,
+                destination: c.destination,
+                short_channel_id: c.short_channel_id,
+                direction: (c.channel_flags & 1) as u32,
+                capacity: c.amount_msat,
+                base_fee_msat: c.base_fee_millisatoshi,
+                fee_per_millionth: c.fee_per_millionth,
+                cltv_delta: c.delay,
+                htlc_minimum_msat: c.htlc_minimum_msat,
+                htlc_maximum_msat: c.htlc_maximum_msat,
+                active: c.active,
+            });
+        }
+        graph
+    }
+
+    /// Record node announcements from `listnodes`. Intermediate nodes
+    /// that announce features without `var_onion_optin` are avoided.
+    pub fn add_nodes(&mut self, res: &ListnodesResponse) -> Result<()> {
+        for n in &res.nodes {
+            if let Some(f) = &n.features {
+                self.set_node_features(n.nodeid, Features::from_str(f)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the features `node` announced.
+    pub fn set_node_features(&mut self, node: PublicKey, features: Features) {
+        self.features.insert(node, features);
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn edge(&self, scid: &ShortChannelIdDir) -> Option<&Edge> {
+        self.by_scid.get(scid).map(|i| &self.edges[*i])
+    }
+
+    /// Update a channel, e.g. to lower its `htlc_maximum_msat` after
+    /// it failed to carry an amount.
+    pub fn edge_mut(&mut self, scid: &ShortChannelIdDir) -> Option<EdgePolicyMut<'_>> {
+        let e = &mut self.edges[*self.by_scid.get(scid)?];
+        Some(EdgePolicyMut {
+            capacity: &mut e.capacity,
+            base_fee_msat: &mut e.base_fee_msat,
+            fee_per_millionth: &mut e.fee_per_millionth,
+            cltv_delta: &mut e.cltv_delta,
+            htlc_minimum_msat: &mut e.htlc_minimum_msat,
+            htlc_maximum_msat: &mut e.htlc_maximum_msat,
+            active: &mut e.active,
+        })
+    }
+
+    fn usable_hop(&self, node: &PublicKey) -> bool {
+        self.features
+            .get(node)
+            .is_none_or(|f| f.supports(VAR_ONION_OPTIN))
+    }
+
+    /// Find the cheapest route delivering `amount` from `source` to
+    /// `destination`.
+    pub fn route(
+        &self,
+        source: &PublicKey,
+        destination: &PublicKey,
+        amount: Amount,
+        params: &RouteParams,
+    ) -> Result<Vec<SendpayRoute>> {
+        self.search(source, destination, amount, params, &HashSet::new())
+            .map(|p| p.hops)
+            .ok_or_else(|| anyhow!("Could not find a route"))
+    }
+
+    /// Find up to `k` loopless routes from `source` to `destination`,
+    /// cheapest first, using Yen's algorithm.
+    pub fn k_shortest_routes(
+        &self,
+        source: &PublicKey,
+        destination: &PublicKey,
+        amount: Amount,
+        k: usize,
+        params: &RouteParams,
+    ) -> Result<Vec<Vec<SendpayRoute>>> {
+        let first = self
+            .search(source, destination, amount, params, &HashSet::new())
+            .ok_or_else(|| anyhow!("Could not find a route"))?;
+        let mut found: Vec<Path> = vec![first];
+        let mut candidates: Vec<Path> = vec![];
+
+        while found.len() < k {
+            let last = found.last().unwrap().edges.clone();
+            for i in 0..last.len() {
+                let root = &last[..i];
+                let spur = match i {
+                    0 => *source,
+                    _ => self.edges[last[i - 1]].destination,
+                };
+
+                // Don't reuse the next edge of any route sharing this
+                // root, nor go back through the root's nodes.
+                let mut banned_edges = HashSet::new();
+                for p in found.iter().chain(candidates.iter()) {
+                    if p.edges.len() > i && p.edges[..i] == *root {
+                        banned_edges.insert(p.edges[i]);
+                    }
+                }
+                let mut spur_params = params.clone();
+                spur_params.max_hops = params.max_hops.saturating_sub(i);
+                spur_params.exclude_nodes.insert(*source);
+                for e in root {
+                    spur_params.exclude_nodes.insert(self.edges[*e].destination);
+                }
+                spur_params.exclude_nodes.remove(&spur);
+
+                let tail =
+                    match self.search(&spur, destination, amount, &spur_params, &banned_edges) {
+                        Some(t) => t,
+                        None => continue,
+                    };
+                let edges: Vec<usize> = root.iter().chain(tail.edges.iter()).cloned().collect();
+                if found
+                    .iter()
+                    .chain(candidates.iter())
+                    .any(|p| p.edges == edges)
+                {
+                    continue;
+                }
+                // The spur search didn't charge fees at the spur node,
+                // so price the whole route again.
+                if let Some(p) = self.price(&edges, amount, params) {
+                    candidates.push(p);
+                }
+            }
+
+            let best = candidates
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.cost.total_cmp(&b.cost))
+                .map(|(i, _)| i);
+            match best {
+                Some(i) => found.push(candidates.remove(i)),
+                None => break,
+            }
+        }
+        Ok(found.into_iter().map(|p| p.hops).collect())
+    }
+
+    fn excluded(&self, idx: usize, params: &RouteParams, banned: &HashSet<usize>) -> bool {
+        let e = &self.edges[idx];
+        banned.contains(&idx)
+            || params.exclude_nodes.contains(&e.source)
+            || params.exclude_channels.contains(&e.scid_dir())
+    }
+
+    /// Dijkstra from `destination` back to `source`.
+    fn search(
+        &self,
+        source: &PublicKey,
+        destination: &PublicKey,
+        amount: Amount,
+        params: &RouteParams,
+        banned: &HashSet<usize>,
+    ) -> Option<Path> {
+        if source == destination || params.exclude_nodes.contains(destination) {
+            return None;
+        }
+        let mut labels: HashMap<PublicKey, Label> = HashMap::new();
+        let mut done: HashSet<PublicKey> = HashSet::new();
+        let mut heap = BinaryHeap::new();
+        labels.insert(
+            *destination,
+            Label {
+                cost: 0.0,
+                amount,
+                delay: params.final_cltv,
+                hops: 0,
+                edge: None,
+            },
+        );
+        heap.push(HeapEntry {
+            cost: 0.0,
+            node: *destination,
+        });
+
+        while let Some(HeapEntry { node, .. }) = heap.pop() {
+            if !done.insert(node) {
+                continue;
+            }
+            if node == *source {
+                break;
+            }
+            let label = labels[&node];
+            if label.hops >= params.max_hops {
+                continue;
+            }
+            for idx in self.incoming.get(&node).into_iter().flatten() {
+                let e = &self.edges[*idx];
+                if done.contains(&e.source)
+                    || self.excluded(*idx, params, banned)
+                    || !e.can_carry(label.amount)
+                {
+                    continue;
+                }
+                // We don't pay ourselves a fee.
+                let (fee, delay) = if e.source == *source {
+                    (Amount::ZERO, label.delay)
+                } else {
+                    if !self.usable_hop(&e.source) {
+                        continue;
+                    }
+                    (
+                        e.fee(label.amount),
+                        label.delay.saturating_add(e.cltv_delta),
+                    )
+                };
+                let needed = match label.amount.checked_add(fee) {
+                    Some(a) => a,
+                    None => continue,
+                };
+                if delay > params.max_delay
+                    || params
+                        .max_fee
+                        .is_some_and(|max| needed.checked_sub(amount).is_none_or(|f| f > max))
+                {
+                    continue;
+                }
+                let cost = label.cost + params.cost(e, label.amount, fee, label.delay);
+                if labels.get(&e.source).is_none_or(|l| cost < l.cost) {
+                    labels.insert(
+                        e.source,
+                        Label {
+                            cost,
+                            amount: needed,
+                            delay,
+                            hops: label.hops + 1,
+                            edge: Some(*idx),
+                        },
+                    );
+                    heap.push(HeapEntry {
+                        cost,
+                        node: e.source,
+                    });
+                }
+            }
+        }
+
+        if !done.contains(source) {
+            return None;
+        }
+        let mut edges = vec![];
+        let mut node = *source;
+        while let Some(idx) = labels[&node].edge {
+            edges.push(idx);
+            node = self.edges[idx].destination;
+        }
+        self.price(&edges, amount, params)
+    }
+
+    /// Compute the hops and cost of sending `amount` along `edges`,
+    /// checking the route's constraints.
+    fn price(&self, edges: &[usize], amount: Amount, params: &RouteParams) -> Option<Path> {
+        if edges.is_empty() || edges.len() > params.max_hops {
+            return None;
+        }
+        let mut hops = Vec::with_capacity(edges.len());
+        let mut cost = 0.0;
+        let mut amt = amount;
+        let mut delay = params.final_cltv;
+        for (i, idx) in edges.iter().enumerate().rev() {
+            let e = &self.edges[*idx];
+            if !e.can_carry(amt) || delay > params.max_delay {
+                return None;
+            }
+            hops.push(SendpayRoute {
+                amount_msat: amt,
+                id: e.destination,
+                delay: u16::try_from(delay).ok()?,
+                channel: e.short_channel_id,
+            });
+            let fee = if i == 0 { Amount::ZERO } else { e.fee(amt) };
+            cost += params.cost(e, amt, fee, delay);
+            amt = amt.checked_add(fee)?;
+            if i > 0 {
+                delay = delay.checked_add(e.cltv_delta)?;
+            }
+        }
+        if let Some(max) = params.max_fee {
+            if amt.checked_sub(amount)? > max {
+                return None;
+            }
+        }
+        hops.reverse();
+        Some(Path {
+            edges: edges.to_vec(),
+            cost,
+            hops,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use serde_json::json;
+
+    fn node(i: u8) -> PublicKey {
+        let sk = SecretKey::from_slice(&[i; 32]).unwrap();
+        PublicKey::from_secret_key(&Secp256k1::new(), &sk)
+    }
+
+    /// Add a channel in both directions, with the same policy.
+    fn channel(g: &mut Graph, a: u8, b: u8, block: u64, base: u32, ppm: u32, cltv: u32) {
+        for (src, dst) in [(a, b), (b, a)] {
+            let (src, dst) = (node(src), node(dst));
+            g.add_edge(Edge {
+                source: src,
+                destination: dst,
+                short_channel_id: ShortChannelId::new(block, 1, 0).unwrap(),
+                direction: (src > dst) as u32,
+                capacity: Amount::from_sat(1_000_000),
+                base_fee_msat: base,
+                fee_per_millionth: ppm,
+                cltv_delta: cltv,
+                htlc_minimum_msat: Amount::from_msat(1),
+                htlc_maximum_msat: None,
+                active: true,
+            });
+        }
+    }
+
+    /// 1 - 2 - 3 - 5 is cheap, 1 - 4 - 5 is short but expensive.
+    fn graph() -> Graph {
+        let mut g = Graph::new();
+        channel(&mut g, 1, 2, 101, 1, 10, 6);
+        channel(&mut g, 2, 3, 102, 1, 10, 6);
+        channel(&mut g, 3, 5, 103, 1, 10, 6);
+        channel(&mut g, 1, 4, 104, 1000, 1000, 40);
+        channel(&mut g, 4, 5, 105, 1000, 1000, 40);
+        g
+    }
+
+    fn scids(route: &[SendpayRoute]) -> Vec<u64> {
+        route.iter().map(|h| h.channel.block() as u64).collect()
+    }
+
+    #[test]
+    fn test_route() {
+        let g = graph();
+        let amount = Amount::from_sat(100_000);
+        let route = g
+            .route(&node(1), &node(5), amount, &RouteParams::default())
+            .unwrap();
+        assert_eq!(scids(&route), vec![101, 102, 103]);
+
+        // The last hop gets the amount and the final CLTV, each hop
+        // before it also pays the next node's fee and delay.
+        assert_eq!(route[2].id, node(5));
+        assert_eq!(route[2].amount_msat, amount);
+        assert_eq!(route[2].delay, 18);
+        assert_eq!(route[1].id, node(3));
+        assert_eq!(route[1].amount_msat, Amount::from_msat(100_001_001));
+        assert_eq!(route[1].delay, 24);
+        assert_eq!(route[0].id, node(2));
+        assert_eq!(route[0].amount_msat, Amount::from_msat(100_002_002));
+        assert_eq!(route[0].delay, 30);
+
+        // Limits force the other path.
+        let params = RouteParams {
+            max_hops: 2,
+            ..Default::default()
+        };
+        let route = g.route(&node(1), &node(5), amount, &params).unwrap();
+        assert_eq!(scids(&route), vec![104, 105]);
+        assert_eq!(route[0].amount_msat, Amount::from_msat(100_101_000));
+        assert_eq!(route[0].delay, 58);
+
+        let mut params = RouteParams::default();
+        params.exclude_nodes.insert(node(3));
+        assert_eq!(
+            scids(&g.route(&node(1), &node(5), amount, &params).unwrap()),
+            vec![104, 105]
+        );
+        let mut params = RouteParams::default();
+        params.exclude_channels.insert(g.edges()[2].scid_dir());
+        assert_eq!(
+            scids(&g.route(&node(1), &node(5), amount, &params).unwrap()),
+            vec![104, 105]
+        );
+
+        // Nothing fits within these.
+        let params = RouteParams {
+            max_hops: 2,
+            max_fee: Some(Amount::from_sat(10)),
+            ..Default::default()
+        };
+        assert!(g.route(&node(1), &node(5), amount, &params).is_err());
+        let params = RouteParams {
+            max_hops: 2,
+            max_delay: 50,
+            ..Default::default()
+        };
+        assert!(g.route(&node(1), &node(5), amount, &params).is_err());
+        assert!(g
+            .route(
+                &node(1),
+                &node(5),
+                Amount::from_sat(2_000_000),
+                &RouteParams::default()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_edge() {
+        let mut g = graph();
+        let amount = Amount::from_sat(100_000);
+        let scid = g.edges()[4].scid_dir();
+
+        // Replacing an edge drops the old one from the search.
+        let mut e = g.edge(&scid).unwrap().clone();
+        assert_eq!(e.destination, node(5));
+        e.active = false;
+        g.add_edge(e);
+        assert_eq!(g.edges().len(), 10);
+        assert!(!g.edge(&scid).unwrap().active);
+        assert_eq!(
+            scids(
+                &g.route(&node(1), &node(5), amount, &RouteParams::default())
+                    .unwrap()
+            ),
+            vec![104, 105]
+        );
+
+        let e = g.edge_mut(&scid).unwrap();
+        *e.active = true;
+        *e.htlc_maximum_msat = Some(Amount::from_sat(50_000));
+        assert_eq!(
+            scids(
+                &g.route(&node(1), &node(5), amount, &RouteParams::default())
+                    .unwrap()
+            ),
+            vec![104, 105]
+        );
+        let route = g
+            .route(
+                &node(1),
+                &node(5),
+                Amount::from_sat(50_000),
+                &RouteParams::default(),
+            )
+            .unwrap();
+        assert_eq!(scids(&route), vec![101, 102, 103]);
+    }
+
+    #[test]
+    fn test_weights() {
+        let mut g = graph();
+        // A cheap but tiny shortcut.
+        let mut small = Graph::new();
+        channel(&mut small, 1, 5, 106, 0, 0, 6);
+        for mut e in small.edges().to_vec() {
+            e.capacity = Amount::from_sat(100_001);
+            g.add_edge(e);
+        }
+        let amount = Amount::from_sat(100_000);
+        let route = g
+            .route(&node(1), &node(5), amount, &RouteParams::default())
+            .unwrap();
+        assert_eq!(scids(&route), vec![106]);
+
+        let params = RouteParams {
+            probability_weight: 10_000.0,
+            ..Default::default()
+        };
+        let route = g.route(&node(1), &node(5), amount, &params).unwrap();
+        assert_eq!(scids(&route), vec![101, 102, 103]);
+
+        // Without fees, only the delay counts.
+        let params = RouteParams {
+            fee_weight: 0.0,
+            ..Default::default()
+        };
+        let mut g = graph();
+        g.edges.iter_mut().for_each(|e| e.cltv_delta = 144);
+        let route = g.route(&node(1), &node(5), amount, &params).unwrap();
+        assert_eq!(scids(&route), vec![104, 105]);
+    }
+
+    #[test]
+    fn test_k_shortest() {
+        let mut g = graph();
+        channel(&mut g, 2, 5, 107, 100, 100, 6);
+        channel(&mut g, 2, 4, 108, 1, 1, 6);
+        let amount = Amount::from_sat(100_000);
+        let routes = g
+            .k_shortest_routes(&node(1), &node(5), amount, 10, &RouteParams::default())
+            .unwrap();
+        let paths: Vec<Vec<u64>> = routes.iter().map(|r| scids(r)).collect();
+        assert_eq!(paths[0], vec![101, 102, 103]);
+        assert_eq!(paths[1], vec![101, 107]);
+        assert!(paths.contains(&vec![104, 105]));
+        assert!(paths.contains(&vec![101, 108, 105]));
+        assert!(paths.contains(&vec![104, 108, 107]));
+        assert!(paths.contains(&vec![104, 108, 102, 103]));
+
+        // Routes are distinct, loopless and ordered by cost.
+        let unique: HashSet<_> = paths.iter().collect();
+        assert_eq!(unique.len(), paths.len());
+        for r in &routes {
+            let nodes: HashSet<_> = r.iter().map(|h| h.id).collect();
+            assert_eq!(nodes.len(), r.len());
+            assert!(!nodes.contains(&node(1)));
+        }
+        let fees: Vec<u64> = routes.iter().map(|r| r[0].amount_msat.msat()).collect();
+        assert_eq!(fees[0], 100_002_002);
+
+        let two = g
+            .k_shortest_routes(&node(1), &node(5), amount, 2, &RouteParams::default())
+            .unwrap();
+        assert_eq!(two.len(), 2);
+    }
+
+    #[test]
+    fn test_from_listchannels() {
+        let c = |src: u8, dst: u8, flags: u8, active: bool| {
+            json!({
+                "source": node(src).to_string(),
+                "destination": node(dst).to_string(),
+                "short_channel_id": "103x1x0",
+                "public": true,
+                "amount_msat": 1_000_000_000,
+                "message_flags": 1,
+                "channel_flags": flags,
+                "active": active,
+                "last_update": 1667237562,
+                "base_fee_millisatoshi": 1,
+                "fee_per_millionth": 10,
+                "delay": 6,
+                "htlc_minimum_msat": 0,
+                "htlc_maximum_msat": 990_000_000,
+                "features": ""
+            })
+        };
+        let res: ListchannelsResponse = serde_json::from_value(json!({
+            "channels": [c(1, 2, 0, true), c(2, 1, 1, false)]
+        }))
+        .unwrap();
+        let mut g = Graph::from_listchannels(&res);
+        assert_eq!(g.edges()[1].direction, 1);
+
+        let amount = Amount::from_sat(1000);
+        let route = g
+            .route(&node(1), &node(2), amount, &RouteParams::default())
+            .unwrap();
+        assert_eq!(route.len(), 1);
+        assert_eq!(route[0].channel.to_string(), "103x1x0");
+        // Disabled direction.
+        assert!(g
+            .route(&node(2), &node(1), amount, &RouteParams::default())
+            .is_err());
+        // Over htlc_maximum_msat.
+        assert!(g
+            .route(
+                &node(1),
+                &node(2),
+                Amount::from_sat(995_000),
+                &RouteParams::default()
+            )
+            .is_err());
+
+        // Intermediate nodes need var_onion_optin.
+        channel(&mut g, 2, 3, 104, 1, 10, 6);
+        assert!(g
+            .route(&node(1), &node(3), amount, &RouteParams::default())
+            .is_ok());
+        let nodes: ListnodesResponse = serde_json::from_value(json!({
+            "nodes": [{"nodeid": node(2).to_string(), "features": "0800"}]
+        }))
+        .unwrap();
+        g.add_nodes(&nodes).unwrap();
+        assert!(g
+            .route(&node(1), &node(3), amount, &RouteParams::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! The [`Rpc`] trait abstracts over the ways of talking to a node, so
+//! code can be written once and then run against the unix socket
+//! ([`ClnRpc`](crate::ClnRpc)), a
+//! [`CommandoClient`](crate::commando::CommandoClient), a remote
+//! node's grpc interface (`cln_grpc::NodeRpc`), or a [`MockRpc`] in
+//! tests.
+//!
+//! ```
+//! use cln_rpc::model::requests::GetinfoRequest;
+//! use cln_rpc::rpc::MockRpc;
+//! use cln_rpc::{Rpc, RpcError};
+//! use serde_json::json;
+//!
+//! async fn alias<R: Rpc>(rpc: &mut R) -> Result<String, RpcError> {
+//!     let info = rpc.call_typed(GetinfoRequest::builder().build()).await?;
+//!     Ok(info.alias)
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut rpc = MockRpc::new();
+//! rpc.expect("getinfo", json!({
+//!     "id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+//!     "alias": "LOUDTRAIN",
+//!     "color": "02eec7",
+//!     "num_peers": 0,
+//!     "num_pending_channels": 0,
+//!     "num_active_channels": 0,
+//!     "num_inactive_channels": 0,
+//!     "version": "v22.11.1",
+//!     "blockheight": 100,
+//!     "network": "regtest",
+//!     "fees_collected_msat": "0msat",
+//!     "lightning-dir": "/tmp/l1/regtest",
+//! }));
+//! assert_eq!(alias(&mut rpc).await.unwrap(), "LOUDTRAIN");
+//! # }
+//! ```
+use crate::model::IntoRequest;
+use crate::{parse_response, Request, Response, RpcError};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+
+/// Re-exported since it is part of the [`Rpc`] interface.
+pub use serde_json::Value;
+
+/// A connection to a node that can run JSON-RPC methods.
+#[async_trait]
+pub trait Rpc: Send {
+    /// Call `method` with the JSON `params`, returning the JSON
+    /// `result`.
+    async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError>;
+
+    async fn call(&mut self, req: Request) -> Result<Response, RpcError> {
+        let mut req = serde_json::to_value(req).map_err(|e| RpcError {
+            code: None,
+            message: format!("Error parsing request: {}", e),
+            data: None,
+        })?;
+        let params = req["params"].take();
+        let method = req["method"].as_str().unwrap_or_default();
+        let result = self.call_raw(method, params).await?;
+        parse_response(&req["method"], json!({ "result": result }))
+    }
+
+    async fn call_typed<R>(&mut self, request: R) -> Result<R::Response, RpcError>
+    where
+        Self: Sized,
+        R: IntoRequest + Send,
+        R::Response: Send,
+    {
+        Ok(self
+            .call(request.into())
+            .await?
+            .try_into()
+            .expect("CLN will reply correctly"))
+    }
+}
+
+/// Parse a raw `method` and `params` into a typed [`Request`], for
+/// implementations of [`Rpc::call_raw`] that only handle typed
+/// requests.
+pub fn request(method: &str, params: Value) -> Result<Request, RpcError> {
+    serde_json::from_value(json!({"method": method, "params": params})).map_err(|e| RpcError {
+        code: None,
+        message: format!("Unsupported request to {}: {}", method, e),
+        data: None,
+    })
+}
+
+/// The raw `result` of a typed [`Response`], the counterpart to
+/// [`request`].
+pub fn result(res: Response) -> Result<Value, RpcError> {
+    let mut res = serde_json::to_value(res).map_err(|e| RpcError {
+        code: None,
+        message: format!("Error serializing response: {}", e),
+        data: None,
+    })?;
+    Ok(res["result"].take())
+}
+
+/// An [`Rpc`] returning canned results, for testing code written
+/// against [`Rpc`] without a node.
+#[derive(Debug, Default)]
+pub struct MockRpc {
+    results: HashMap<String, VecDeque<Result<Value, RpcError>>>,
+    calls: Vec<(String, Value)>,
+}
+
+impl MockRpc {
+    pub fn new() -> MockRpc {
+        MockRpc::default()
+    }
+
+    /// Return `result` from the next call to `method`. Calls to a
+    /// method with no results left fail.
+    pub fn expect(&mut self, method: &str, result: Value) -> &mut Self {
+        self.push(method, Ok(result))
+    }
+
+    /// Fail the next call to `method` with `err`.
+    pub fn expect_err(&mut self, method: &str, err: RpcError) -> &mut Self {
+        self.push(method, Err(err))
+    }
+
+    fn push(&mut self, method: &str, res: Result<Value, RpcError>) -> &mut Self {
+        self.results
+            .entry(method.to_string())
+            .or_default()
+            .push_back(res);
+        self
+    }
+
+    /// The method and params of the calls made so far.
+    pub fn calls(&self) -> &[(String, Value)] {
+        &self.calls
+    }
+}
+
+#[async_trait]
+impl Rpc for MockRpc {
+    async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        self.calls.push((method.to_string(), params));
+        self.results
+            .get_mut(method)
+            .and_then(|r| r.pop_front())
+            .unwrap_or_else(|| {
+                Err(RpcError {
+                    code: None,
+                    message: format!("Unexpected call to {}", method),
+                    data: None,
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::requests;
+
+    #[tokio::test]
+    async fn test_mock() {
+        let mut rpc = MockRpc::new();
+        rpc.expect("listpeers", json!({"peers": []})).expect_err(
+            "listpeers",
+            RpcError {
+                code: Some(-32602),
+                message: "Invalid id".to_string(),
+                data: None,
+            },
+        );
+
+        let req = requests::ListpeersRequest::builder()
+            .level("io".to_string())
+            .build();
+        let res = rpc.call_typed(req.clone()).await.unwrap();
+        assert!(res.peers.is_empty());
+        let err = rpc.call_typed(req).await.unwrap_err();
+        assert_eq!(err.code, Some(-32602));
+        let err = rpc.call_raw("getinfo", json!({})).await.unwrap_err();
+        assert_eq!(err.message, "Unexpected call to getinfo");
+
+        assert_eq!(
+            rpc.calls(),
+            &[
+                ("listpeers".to_string(), json!({"level": "io"})),
+                ("listpeers".to_string(), json!({"level": "io"})),
+                ("getinfo".to_string(), json!({})),
+            ]
+        );
+    }
+}
@@ -0,0 +1,856 @@
+//! Runes: bearer tokens with attenuable restrictions, as used by
+//! `commando`.
+//!
+//! A rune is a SHA256 authcode followed by a list of restrictions.
+//! Each restriction is a list of alternatives, at least one of which
+//! must be satisfied, e.g., `method^list|method^get` allows any
+//! method starting with `list` or `get`. Anyone holding a rune can
+//! add restrictions, but only the holder of the master secret can
+//! remove them.
+//!
+//! This module mirrors `ccan/rune` and the checks `commando` performs,
+//! so requests can be pre-checked locally before they are forwarded
+//! to a node. The node remains the authority on whether a rune is
+//! valid, since only it knows the master secret.
+use crate::model::Request;
+use crate::primitives::PublicKey;
+use anyhow::{anyhow, Context, Error, Result};
+use bitcoin::hashes::{sha256, HashEngine};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The condition an alternative applies to its field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// `!`: the field is not present.
+    Missing,
+    /// `=`: the field equals the value.
+    Equal,
+    /// `/`: the field is present and not equal to the value.
+    NotEqual,
+    /// `^`: the field starts with the value.
+    BeginsWith,
+    /// `$`: the field ends with the value.
+    EndsWith,
+    /// `~`: the field contains the value.
+    Contains,
+    /// `<`: the field is an integer less than the value.
+    IntLess,
+    /// `>`: the field is an integer greater than the value.
+    IntGreater,
+    /// `{`: the field sorts before the value.
+    LexoBefore,
+    /// `}`: the field sorts after the value.
+    LexoAfter,
+    /// `#`: a comment, always passes.
+    Comment,
+}
+
+impl Condition {
+    pub fn as_char(&self) -> char {
+        match self {
+            Condition::Missing => '!',
+            Condition::Equal => '=',
+            Condition::NotEqual => '/',
+            Condition::BeginsWith => '^',
+            Condition::EndsWith => '$',
+            Condition::Contains => '~',
+            Condition::IntLess => '<',
+            Condition::IntGreater => '>',
+            Condition::LexoBefore => '{',
+            Condition::LexoAfter => '}',
+            Condition::Comment => '#',
+        }
+    }
+}
+
+impl TryFrom<char> for Condition {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
+            '!' => Condition::Missing,
+            '=' => Condition::Equal,
+            '/' => Condition::NotEqual,
+            '^' => Condition::BeginsWith,
+            '$' => Condition::EndsWith,
+            '~' => Condition::Contains,
+            '<' => Condition::IntLess,
+            '>' => Condition::IntGreater,
+            '{' => Condition::LexoBefore,
+            '}' => Condition::LexoAfter,
+            '#' => Condition::Comment,
+            c => return Err(anyhow!("invalid rune condition {:?}", c)),
+        })
+    }
+}
+
+/// The value of a field an [`Alternative`] is tested against.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldValue<'a> {
+    Str(&'a str),
+    Int(i64),
+}
+
+/// A single `field`, `condition`, `value` test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alternative {
+    pub field: String,
+    pub condition: Condition,
+    pub value: String,
+}
+
+/// Field names end at the first ASCII punctuation character, which
+/// is where the condition starts.
+fn fieldname_len(s: &str) -> usize {
+    s.find(|c: char| c.is_ascii_punctuation())
+        .unwrap_or(s.len())
+}
+
+impl Alternative {
+    pub fn new(field: &str, condition: Condition, value: &str) -> Result<Alternative> {
+        if fieldname_len(field) != field.len() {
+            return Err(anyhow!("field name {:?} contains punctuation", field));
+        }
+        Ok(Alternative {
+            field: field.to_string(),
+            condition,
+            value: value.to_string(),
+        })
+    }
+
+    /// Test `value`, or a missing field if `None`. Returns a
+    /// description of the failure if the test fails.
+    pub fn test(&self, value: Option<FieldValue>) -> Result<(), String> {
+        let int;
+        let s = match value {
+            Some(FieldValue::Str(s)) => Some(s),
+            Some(FieldValue::Int(i)) => {
+                int = i.to_string();
+                Some(int.as_str())
+            }
+            None => None,
+        };
+        let cond_test = |complaint: &str, ok: bool| {
+            if ok {
+                Ok(())
+            } else {
+                Err(format!("{} {} {}", self.field, complaint, self.value))
+            }
+        };
+        let int_test = |complaint: &str, f: fn(i64, i64) -> bool| match value {
+            Some(FieldValue::Int(v)) => match self.value.trim_start().parse::<i64>() {
+                Ok(r) => cond_test(complaint, f(v, r)),
+                Err(_) => Err(format!("{} is not a valid integer", self.value)),
+            },
+            _ => Err(format!("{} is not an integer field", self.field)),
+        };
+
+        if self.condition == Condition::Comment {
+            return Ok(());
+        }
+        if self.condition == Condition::Missing {
+            return match s {
+                None => Ok(()),
+                Some(_) => Err(format!("{} is present", self.field)),
+            };
+        }
+        match self.condition {
+            Condition::IntLess => return int_test("is greater or equal to", |a, b| a < b),
+            Condition::IntGreater => return int_test("is less or equal to", |a, b| a > b),
+            _ => {}
+        }
+
+        let s = s.ok_or_else(|| format!("{} not present", self.field))?;
+        let v = self.value.as_str();
+        match self.condition {
+            Condition::Equal => cond_test("is not equal to", s == v),
+            Condition::NotEqual => cond_test("is equal to", s != v),
+            Condition::BeginsWith => cond_test("does not start with", s.starts_with(v)),
+            Condition::EndsWith => cond_test("does not end with", s.ends_with(v)),
+            Condition::Contains => cond_test("does not contain", s.contains(v)),
+            Condition::LexoBefore => {
+                cond_test("is equal to or ordered after", s.as_bytes() < v.as_bytes())
+            }
+            Condition::LexoAfter => {
+                cond_test("is equal to or ordered before", s.as_bytes() > v.as_bytes())
+            }
+            Condition::Missing
+            | Condition::Comment
+            | Condition::IntLess
+            | Condition::IntGreater => {
+                unreachable!()
+            }
+        }
+    }
+
+    /// Append the rune encoding, escaping the separators in the
+    /// value.
+    fn encode(&self, out: &mut String) {
+        out.push_str(&self.field);
+        out.push(self.condition.as_char());
+        for c in self.value.chars() {
+            if matches!(c, '\\' | '|' | '&') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+
+    fn is_unique_id(&self) -> bool {
+        self.field.is_empty()
+    }
+}
+
+/// Parses the unescaped form used in JSON, e.g., `method=getinfo`.
+impl FromStr for Alternative {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let len = fieldname_len(s);
+        let mut rest = s[len..].chars();
+        let cond = rest
+            .next()
+            .ok_or_else(|| anyhow!("alternative {:?} has no condition", s))?;
+        Ok(Alternative {
+            field: s[..len].to_string(),
+            condition: Condition::try_from(cond)?,
+            value: rest.as_str().to_string(),
+        })
+    }
+}
+
+impl Display for Alternative {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.field,
+            self.condition.as_char(),
+            self.value
+        )
+    }
+}
+
+impl Serialize for Alternative {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Alternative {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|e| Error::custom(format!("{}", e)))
+    }
+}
+
+/// A set of alternatives, at least one of which must pass.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Restriction {
+    pub alternatives: Vec<Alternative>,
+}
+
+impl Restriction {
+    pub fn new(alternatives: Vec<Alternative>) -> Result<Restriction> {
+        if alternatives.is_empty() {
+            return Err(anyhow!("restriction needs at least one alternative"));
+        }
+        Ok(Restriction { alternatives })
+    }
+
+    /// Run `check` on each alternative until one passes. If none
+    /// do, returns all their failures.
+    pub fn test<F>(&self, mut check: F) -> Result<(), String>
+    where
+        F: FnMut(&Alternative) -> Result<(), String>,
+    {
+        let mut errs = vec![];
+        for alt in &self.alternatives {
+            match check(alt) {
+                Ok(()) => return Ok(()),
+                Err(e) => errs.push(e),
+            }
+        }
+        Err(errs.join(" AND "))
+    }
+
+    fn encode(&self, out: &mut String) {
+        for (i, alt) in self.alternatives.iter().enumerate() {
+            if i != 0 {
+                out.push('|');
+            }
+            alt.encode(out);
+        }
+    }
+
+    /// Decode one restriction from the front of `s`, consuming the
+    /// trailing `&` if present.
+    fn decode(s: &mut std::str::Chars) -> Result<Restriction> {
+        let mut alternatives = vec![];
+        loop {
+            let rest = s.as_str();
+            let len = fieldname_len(rest);
+            let field = rest[..len].to_string();
+            *s = rest[len..].chars();
+            let cond = s
+                .next()
+                .ok_or_else(|| anyhow!("alternative for {:?} has no condition", field))?;
+            let condition = Condition::try_from(cond)?;
+
+            let mut value = String::new();
+            let mut more = false;
+            while let Some(c) = s.next() {
+                match c {
+                    '|' => {
+                        more = true;
+                        break;
+                    }
+                    '&' => break,
+                    '\\' => value.push(s.next().ok_or_else(|| anyhow!("trailing escape"))?),
+                    c => value.push(c),
+                }
+            }
+            alternatives.push(Alternative {
+                field,
+                condition,
+                value,
+            });
+            if !more {
+                return Ok(Restriction { alternatives });
+            }
+        }
+    }
+}
+
+/// Parses the rune encoding, e.g., `method^list|method^get`.
+impl FromStr for Restriction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let r = Restriction::decode(&mut chars)?;
+        if !chars.as_str().is_empty() {
+            return Err(anyhow!("trailing characters after restriction"));
+        }
+        Ok(r)
+    }
+}
+
+impl Display for Restriction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut s = String::new();
+        self.encode(&mut s);
+        f.write_str(&s)
+    }
+}
+
+/// Pad the SHA256 stream to the next block, the same way SHA256
+/// finalizes, so restrictions start on a block boundary.
+fn add_endmarker(engine: &mut sha256::HashEngine, hashed: usize) {
+    let padlen = 1 + ((128 - 8 - (hashed % 64) - 1) % 64);
+    let mut pad = vec![0u8; padlen];
+    pad[0] = 0x80;
+    engine.input(&pad);
+    engine.input(&((hashed as u64) << 3).to_be_bytes());
+}
+
+/// A rune, i.e., an authcode and its restrictions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rune {
+    authcode: [u8; 32],
+    /// How many bytes have been hashed into `authcode`, including
+    /// padding.
+    hashed: usize,
+    restrictions: Vec<Restriction>,
+    unique_id: Option<String>,
+    version: Option<String>,
+}
+
+impl Rune {
+    /// Create a master rune from `secret`. Only the node creating
+    /// runes needs this.
+    pub fn new(secret: &[u8]) -> Result<Rune> {
+        if secret.len() + 1 + 8 > 64 {
+            return Err(anyhow!("rune secret must be at most 55 bytes"));
+        }
+        let mut engine = sha256::HashEngine::default();
+        engine.input(secret);
+        add_endmarker(&mut engine, secret.len());
+        Ok(Rune {
+            authcode: engine.midstate().into_inner(),
+            hashed: 64,
+            restrictions: vec![],
+            unique_id: None,
+            version: None,
+        })
+    }
+
+    pub fn authcode(&self) -> &[u8; 32] {
+        &self.authcode
+    }
+
+    pub fn restrictions(&self) -> &[Restriction] {
+        &self.restrictions
+    }
+
+    /// The unique id assigned when the rune was created, if any.
+    pub fn unique_id(&self) -> Option<&str> {
+        self.unique_id.as_deref()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Add `restriction`, updating the authcode.
+    pub fn add_restriction(&mut self, restriction: Restriction) -> Result<()> {
+        // An empty field name is the unique id, which is special.
+        if restriction.alternatives.iter().any(|a| a.is_unique_id()) {
+            if restriction.alternatives.len() != 1 {
+                return Err(anyhow!("unique id must be the only alternative"));
+            }
+            if !self.restrictions.is_empty() {
+                return Err(anyhow!("unique id must be the first restriction"));
+            }
+            let alt = &restriction.alternatives[0];
+            if alt.condition != Condition::Equal {
+                return Err(anyhow!("unique id must use ="));
+            }
+            let (id, version) = match alt.value.split_once('-') {
+                Some((id, version)) => (id, Some(version.to_string())),
+                None => (alt.value.as_str(), None),
+            };
+            self.unique_id = Some(id.to_string());
+            self.version = version;
+        }
+
+        let mut encoded = String::new();
+        restriction.encode(&mut encoded);
+        let mut engine = sha256::HashEngine::from_midstate(
+            sha256::Midstate::from_inner(self.authcode),
+            self.hashed,
+        );
+        engine.input(encoded.as_bytes());
+        add_endmarker(&mut engine, self.hashed + encoded.len());
+        self.hashed = engine.n_bytes_hashed();
+        self.authcode = engine.midstate().into_inner();
+        self.restrictions.push(restriction);
+        Ok(())
+    }
+
+    /// Check that this rune was derived from `master` by adding
+    /// restrictions, i.e., that it is authentic if `master` is the
+    /// node's master rune.
+    pub fn is_derived_from(&self, master: &Rune) -> Result<()> {
+        if self.version != master.version {
+            return Err(anyhow!("Version mismatch"));
+        }
+        self.is_derived_anyversion(master)
+    }
+
+    fn is_derived_anyversion(&self, master: &Rune) -> Result<()> {
+        if self.restrictions.len() < master.restrictions.len() {
+            return Err(anyhow!("Fewer restrictions than master"));
+        }
+        let mut derived = master.clone();
+        for (i, r) in self.restrictions.iter().enumerate() {
+            match master.restrictions.get(i) {
+                Some(m) if m != r => return Err(anyhow!("Does not match master restrictions")),
+                Some(_) => {}
+                None => derived.add_restriction(r.clone())?,
+            }
+        }
+        if derived.authcode != self.authcode {
+            return Err(anyhow!("Not derived from master"));
+        }
+        Ok(())
+    }
+
+    /// Run `check` on the restrictions, failing on the first one
+    /// that doesn't pass.
+    pub fn check_with<F>(&self, mut check: F) -> Result<(), String>
+    where
+        F: FnMut(&Alternative) -> Result<(), String>,
+    {
+        for (i, r) in self.restrictions.iter().enumerate() {
+            // The unique id isn't something to check.
+            if i == 0 && r.alternatives[0].is_unique_id() {
+                continue;
+            }
+            r.test(&mut check)?;
+        }
+        Ok(())
+    }
+
+    /// Check whether `commando` would let this rune run `request`.
+    pub fn check(&self, request: &RuneRequest) -> Result<()> {
+        self.check_with(|alt| request.test(alt))
+            .map_err(|e| anyhow!("Not authorized: {}", e))
+    }
+}
+
+impl FromStr for Rune {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = base64::decode_config(s, base64::URL_SAFE).context("decoding rune")?;
+        if data.len() < 32 {
+            return Err(anyhow!("rune is too short"));
+        }
+        let restrictions = std::str::from_utf8(&data[32..]).context("rune is not a string")?;
+        if restrictions.contains('\0') {
+            return Err(anyhow!("rune contains a NUL character"));
+        }
+
+        let mut rune = Rune {
+            authcode: [0; 32],
+            hashed: 64,
+            restrictions: vec![],
+            unique_id: None,
+            version: None,
+        };
+        let mut chars = restrictions.chars();
+        while !chars.as_str().is_empty() {
+            rune.add_restriction(Restriction::decode(&mut chars)?)?;
+        }
+        rune.authcode.copy_from_slice(&data[..32]);
+        Ok(rune)
+    }
+}
+
+impl Display for Rune {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut restrictions = String::new();
+        for (i, r) in self.restrictions.iter().enumerate() {
+            if i != 0 {
+                restrictions.push('&');
+            }
+            r.encode(&mut restrictions);
+        }
+        let mut data = self.authcode.to_vec();
+        data.extend_from_slice(restrictions.as_bytes());
+        f.write_str(&base64::encode_config(data, base64::URL_SAFE))
+    }
+}
+
+impl Serialize for Rune {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rune {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|e| Error::custom(format!("{}", e)))
+    }
+}
+
+/// The `restrictions` to add with `commando-rune`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuneRestrictions {
+    /// The standard read-only restrictions: `list*`, `get*` and
+    /// `summary`, but not `listdatastore`.
+    Readonly,
+    Restrictions(Vec<Restriction>),
+}
+
+impl Serialize for RuneRestrictions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            RuneRestrictions::Readonly => serializer.serialize_str("readonly"),
+            RuneRestrictions::Restrictions(r) => r.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RuneRestrictions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        match Value::deserialize(deserializer)? {
+            Value::String(s) if s == "readonly" => Ok(RuneRestrictions::Readonly),
+            v => serde_json::from_value(v)
+                .map(RuneRestrictions::Restrictions)
+                .map_err(|e| Error::custom(format!("{}", e))),
+        }
+    }
+}
+
+/// A request as `commando` sees it when checking a rune.
+#[derive(Clone, Debug)]
+pub struct RuneRequest {
+    pub method: String,
+    pub params: Value,
+    /// The peer sending the request. Restrictions on `id` fail if
+    /// this is not set.
+    pub peer_id: Option<PublicKey>,
+    /// Seconds since the UNIX epoch.
+    pub time: i64,
+}
+
+impl RuneRequest {
+    /// A request for `method` with `params`, made now.
+    pub fn new(method: &str, params: Value) -> RuneRequest {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        RuneRequest {
+            method: method.to_string(),
+            params,
+            peer_id: None,
+            time,
+        }
+    }
+
+    pub fn from_request(request: &Request) -> Result<RuneRequest> {
+        let v = serde_json::to_value(request)?;
+        let method = v["method"]
+            .as_str()
+            .ok_or_else(|| anyhow!("request has no method"))?;
+        Ok(RuneRequest::new(method, v["params"].clone()))
+    }
+
+    pub fn peer_id(mut self, peer_id: PublicKey) -> RuneRequest {
+        self.peer_id = Some(peer_id);
+        self
+    }
+
+    pub fn time(mut self, time: i64) -> RuneRequest {
+        self.time = time;
+        self
+    }
+
+    /// Look up a parameter: `pnameX` for the named parameter `X`
+    /// with punctuation removed, `parrN` for positional parameters.
+    fn param(&self, field: &str) -> Option<&Value> {
+        match &self.params {
+            Value::Object(o) => {
+                let name = field.strip_prefix("pname")?;
+                o.iter()
+                    .find(|(k, _)| {
+                        k.chars()
+                            .filter(|c| !c.is_ascii_punctuation())
+                            .eq(name.chars())
+                    })
+                    .map(|(_, v)| v)
+            }
+            Value::Array(a) => a.get(field.strip_prefix("parr")?.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    fn test(&self, alt: &Alternative) -> Result<(), String> {
+        match alt.field.as_str() {
+            "time" => alt.test(Some(FieldValue::Int(self.time))),
+            "id" => match &self.peer_id {
+                Some(id) => alt.test(Some(FieldValue::Str(&id.to_string()))),
+                None => alt.test(None),
+            },
+            "method" => alt.test(Some(FieldValue::Str(&self.method))),
+            "pnum" => {
+                let n = match &self.params {
+                    Value::Object(o) => o.len(),
+                    Value::Array(a) => a.len(),
+                    _ => 0,
+                };
+                alt.test(Some(FieldValue::Int(n as i64)))
+            }
+            // Rate limits are tracked by the node, all we can check
+            // is that the restriction is well-formed.
+            "rate" => {
+                if alt.condition != Condition::Equal {
+                    return Err("rate operator must be =".to_string());
+                }
+                match alt.value.parse::<u32>() {
+                    Ok(r) if r > 0 && r < u32::MAX => Ok(()),
+                    _ => Err("malformed rate".to_string()),
+                }
+            }
+            field => match self.param(field) {
+                Some(Value::String(s)) => alt.test(Some(FieldValue::Str(s))),
+                Some(v) => alt.test(Some(FieldValue::Str(&v.to_string()))),
+                None => alt.test(None),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const VECTORS: &str = include_str!("../../ccan/ccan/rune/test/test_vectors.csv");
+
+    /// Parse the `hex(authcode):restrictions` form used by the test
+    /// vectors.
+    fn from_hexstring(s: &str) -> Result<Rune> {
+        let (hash, restrictions) = s.split_once(':').unwrap();
+        let mut data = hex::decode(hash).unwrap();
+        data.extend_from_slice(restrictions.as_bytes());
+        base64::encode_config(data, base64::URL_SAFE).parse()
+    }
+
+    /// The check used by the ccan test vectors: fields are given as
+    /// `name=value`, and integers are treated as such.
+    fn vector_check(fields: &[&str], alt: &Alternative) -> Result<(), String> {
+        let val = fields
+            .iter()
+            .filter_map(|f| f.strip_prefix(alt.field.as_str())?.strip_prefix('='))
+            .next_back();
+        match val {
+            Some(v) => match v.parse::<i64>() {
+                Ok(i) => alt.test(Some(FieldValue::Int(i))),
+                Err(_) => alt.test(Some(FieldValue::Str(v))),
+            },
+            None => alt.test(None),
+        }
+    }
+
+    #[test]
+    fn test_vectors() {
+        let master = Rune::new(&[0; 16]).unwrap();
+        let mut rune = master.clone();
+        for line in VECTORS.lines().filter(|l| !l.is_empty()) {
+            let parts: Vec<&str> = line.split(',').collect();
+            match parts[0] {
+                "VALID" => {
+                    let r1 = from_hexstring(parts[2]).unwrap();
+                    let r2: Rune = parts[3].parse().unwrap();
+                    assert_eq!(r1, r2, "{}", line);
+                    assert_eq!(r1.to_string(), parts[3], "{}", line);
+                    assert_eq!(r1.unique_id(), parts.get(4).copied(), "{}", line);
+                    assert_eq!(r1.version(), parts.get(5).copied(), "{}", line);
+                    r1.is_derived_anyversion(&master).unwrap();
+                    rune = r1;
+                }
+                "DERIVE" => {
+                    let mut r1: Rune = parts[2].parse().unwrap();
+                    let r2: Rune = parts[3].parse().unwrap();
+                    r2.is_derived_anyversion(&r1).unwrap();
+                    let alts = parts[4..]
+                        .chunks(3)
+                        .map(|c| {
+                            let cond = Condition::try_from(c[1].chars().next().unwrap()).unwrap();
+                            Alternative {
+                                field: c[0].to_string(),
+                                condition: cond,
+                                value: c[2].to_string(),
+                            }
+                        })
+                        .collect();
+                    r1.add_restriction(Restriction::new(alts).unwrap()).unwrap();
+                    assert_eq!(r1, r2, "{}", line);
+                    rune = r1;
+                }
+                "MALFORMED" => {
+                    assert!(from_hexstring(parts[2]).is_err(), "{}", line);
+                    assert!(parts[3].parse::<Rune>().is_err(), "{}", line);
+                }
+                "BAD DERIVATION" => {
+                    let r1 = from_hexstring(parts[2]).unwrap();
+                    assert_eq!(r1, parts[3].parse::<Rune>().unwrap());
+                    assert!(r1.is_derived_from(&master).is_err(), "{}", line);
+                }
+                "PASS" => {
+                    let res = rune.check_with(|alt| vector_check(&parts[1..], alt));
+                    assert_eq!(res, Ok(()), "{}", line);
+                }
+                "FAIL" => {
+                    let res = rune.check_with(|alt| vector_check(&parts[1..], alt));
+                    assert!(res.is_err(), "{}", line);
+                }
+                t => panic!("unknown test vector type {}", t),
+            }
+        }
+    }
+
+    #[test]
+    fn test_request() {
+        let mut rune = Rune::new(&[1; 32]).unwrap();
+        for r in [
+            "=0",
+            "method^list|method^get|method=summary",
+            "method/listdatastore",
+            "pnum<2",
+            "pnamechannelid!|pnamechannelid/bad",
+            "parr0!|parr0/bad",
+        ] {
+            rune.add_restriction(r.parse().unwrap()).unwrap();
+        }
+        assert_eq!(rune.unique_id(), Some("0"));
+
+        let req = |m: &str, p: Value| RuneRequest::new(m, p);
+        assert!(rune.check(&req("listpeers", json!({}))).is_ok());
+        assert!(rune.check(&req("getinfo", json!([]))).is_ok());
+        assert!(rune
+            .check(&req("listchannels", json!({"short_channel_id": "1x2x3"})))
+            .is_ok());
+        assert_eq!(
+            rune.check(&req("pay", json!({}))).unwrap_err().to_string(),
+            "Not authorized: method does not start with list AND method does not start with get AND method is not equal to summary"
+        );
+        assert!(rune.check(&req("listdatastore", json!({}))).is_err());
+        assert!(rune.check(&req("listfunds", json!([1, 2]))).is_err());
+        assert!(rune
+            .check(&req("listpeers", json!({"channel_id": "bad"})))
+            .is_err());
+        assert!(rune.check(&req("listpeers", json!(["bad"]))).is_err());
+
+        let request = Request::Getinfo(crate::model::requests::GetinfoRequest {});
+        let r = RuneRequest::from_request(&request).unwrap();
+        assert_eq!(r.method, "getinfo");
+        assert!(rune.check(&r).is_ok());
+
+        // Restrictions on the time and the peer.
+        let peer: PublicKey = "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619"
+            .parse()
+            .unwrap();
+        let mut timed = rune.clone();
+        timed.add_restriction("time<1000".parse().unwrap()).unwrap();
+        timed
+            .add_restriction(format!("id={}", peer).parse().unwrap())
+            .unwrap();
+        assert!(timed
+            .check(&req("getinfo", json!({})).time(999).peer_id(peer))
+            .is_ok());
+        assert!(timed
+            .check(&req("getinfo", json!({})).time(1000).peer_id(peer))
+            .is_err());
+        assert!(timed.check(&req("getinfo", json!({})).time(999)).is_err());
+
+        // Restricting a rune keeps it derived from the master.
+        let master = Rune::new(&[1; 32]).unwrap();
+        timed.is_derived_from(&master).unwrap();
+        timed.restrictions.pop();
+        assert!(timed.is_derived_from(&master).is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        let r: RuneRestrictions = serde_json::from_value(json!("readonly")).unwrap();
+        assert_eq!(r, RuneRestrictions::Readonly);
+        let v = json!([["method^list", "method^get"], ["pnamelabel~a|b&c\\d"]]);
+        let r: RuneRestrictions = serde_json::from_value(v.clone()).unwrap();
+        match &r {
+            RuneRestrictions::Restrictions(r) => {
+                assert_eq!(r[0].to_string(), "method^list|method^get");
+                assert_eq!(r[1].alternatives[0].value, "a|b&c\\d");
+                assert_eq!(r[1].to_string(), "pnamelabel~a\\|b\\&c\\\\d");
+                assert_eq!(r[1].to_string().parse::<Restriction>().unwrap(), r[1]);
+            }
+            _ => panic!(),
+        }
+        assert_eq!(serde_json::to_value(&r).unwrap(), v);
+        assert!(serde_json::from_value::<RuneRestrictions>(json!([["method"]])).is_err());
+        assert!(Alternative::new("pname_label", Condition::Equal, "x").is_err());
+    }
+}
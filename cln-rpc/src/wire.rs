@@ -0,0 +1,816 @@
+//! BOLT wire message codec.
+//!
+//! The types in [`peer`], [`onion`] and [`bolt12`] are generated from
+//! `wire/peer_wire.csv`, `wire/onion_wire.csv` and
+//! `wire/bolt12_wire.csv` by `msggen`, the same spec extracts the C
+//! code is generated from. [`hsmd`] and [`channel_type`] are generated
+//! from `hsmd/hsmd_wire.csv` and `wire/channel_type_wire.csv` the same
+//! way, see [`crate::hsmd`] for the types `hsmd` shares with the C
+//! code. This module provides the core they share: a [`Reader`] over a
+//! message, the [`WireType`] encoding of each field type, and the TLV
+//! stream handling built on [`TlvStream`].
+//!
+//! Length fields are implicit: they are computed from the field they
+//! describe when encoding, and don't appear in the generated structs.
+//! TLV streams keep unknown odd records in their `unknown` field, so
+//! they encode back to what was decoded, while unknown even records
+//! fail decoding.
+use crate::primitives::{
+    Amount, PublicKey, Secret, Sha256, ShortChannelId, TlvEntry, TlvStream, TlvValue,
+};
+use anyhow::{anyhow, Context, Result};
+use bitcoin::consensus::encode;
+use bitcoin::hashes::Hash;
+use bitcoin::psbt::Psbt;
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId, Signature};
+use bitcoin::util::bip32::ExtendedPubKey;
+use bitcoin::{BlockHash, EcdsaSig, EcdsaSighashType, Txid};
+
+pub mod bolt12;
+pub mod channel_type;
+pub mod hsmd;
+pub mod onion;
+pub mod peer;
+
+pub use crate::primitives::{read_bigsize, write_bigsize};
+
+/// Onion failure code flags, see BOLT #4.
+pub const BADONION: u16 = 0x8000;
+pub const PERM: u16 = 0x4000;
+pub const NODE: u16 = 0x2000;
+pub const UPDATE: u16 = 0x1000;
+
+/// A cursor over an encoded message.
+pub struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Reader<'a> {
+        Reader(buf)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(anyhow!("truncated field"));
+        }
+        let (v, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(v)
+    }
+
+    /// Consume everything that's left.
+    pub fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.0)
+    }
+
+    pub fn read<T: WireType>(&mut self) -> Result<T> {
+        T::read(self)
+    }
+
+    pub fn read_n<T: WireType>(&mut self, n: usize) -> Result<Vec<T>> {
+        (0..n).map(|_| T::read(self)).collect()
+    }
+
+    /// Read `T`s until the end.
+    pub fn read_all<T: WireType>(&mut self) -> Result<Vec<T>> {
+        let mut res = vec![];
+        while !self.is_empty() {
+            res.push(T::read(self)?);
+        }
+        Ok(res)
+    }
+
+    /// A truncated integer (`tu16`, `tu32` or `tu64`), which is
+    /// always the last field.
+    pub fn truncated<T: TlvValue>(&mut self) -> Result<T> {
+        T::from_tlv_value(self.rest())
+    }
+
+    pub fn bigsize(&mut self) -> Result<u64> {
+        let (v, n) = read_bigsize(self.0)?;
+        self.0 = &self.0[n..];
+        Ok(v)
+    }
+
+    pub fn utf8(&mut self, len: usize) -> Result<String> {
+        String::from_utf8(self.take(len)?.to_vec()).context("invalid utf8")
+    }
+
+    /// A NUL terminated string of printable characters, the C
+    /// `wirestring`.
+    pub fn wirestring(&mut self) -> Result<String> {
+        let len = self
+            .0
+            .iter()
+            .position(|c| *c < b' ')
+            .filter(|i| self.0[*i] == 0)
+            .ok_or_else(|| anyhow!("unterminated wirestring"))?;
+        let s = self.utf8(len)?;
+        self.take(1)?;
+        Ok(s)
+    }
+
+    /// An amount in satoshis, the C `amount_sat`.
+    pub fn sat(&mut self) -> Result<Amount> {
+        Ok(Amount::from_sat(self.read()?))
+    }
+}
+
+/// A type with a wire encoding.
+pub trait WireType: Sized {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()>;
+    fn read(r: &mut Reader<'_>) -> Result<Self>;
+}
+
+/// A message starting with its 2 byte type.
+pub trait WireMessage: WireType {
+    const TYPE: u16;
+
+    fn to_wire(&self) -> Result<Vec<u8>> {
+        let mut buf = Self::TYPE.to_be_bytes().to_vec();
+        self.write(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Decode a message, ignoring any data following the fields we
+    /// know about.
+    fn from_wire(buf: &[u8]) -> Result<Self> {
+        let mut r = Reader::new(buf);
+        let typ: u16 = r.read()?;
+        if typ != Self::TYPE {
+            return Err(anyhow!("Expected message type {}, got {}", Self::TYPE, typ));
+        }
+        Self::read(&mut r)
+    }
+}
+
+macro_rules! wire_int {
+    ($t:ty) => {
+        impl WireType for $t {
+            fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+                buf.extend_from_slice(&self.to_be_bytes());
+                Ok(())
+            }
+
+            fn read(r: &mut Reader<'_>) -> Result<Self> {
+                Ok(<$t>::from_be_bytes(r.read()?))
+            }
+        }
+    };
+}
+
+wire_int!(u16);
+wire_int!(u32);
+wire_int!(u64);
+
+impl WireType for u8 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.push(*self);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(r.take(1)?[0])
+    }
+}
+
+impl WireType for bool {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        (*self as u8).write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        match r.read::<u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(anyhow!("invalid bool {}", v)),
+        }
+    }
+}
+
+/// Optional fields (`?type` in the CSV) are preceded by a bool telling
+/// whether they're present.
+impl<T: WireType> WireType for Option<T> {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.is_some().write(buf)?;
+        match self {
+            Some(v) => v.write(buf),
+            None => Ok(()),
+        }
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(if r.read()? { Some(r.read()?) } else { None })
+    }
+}
+
+impl<const N: usize> WireType for [u8; N] {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.extend_from_slice(self);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(r.take(N)?.try_into()?)
+    }
+}
+
+impl WireType for Amount {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.msat().write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Amount::from_msat(r.read()?))
+    }
+}
+
+impl WireType for PublicKey {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.serialize().write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(PublicKey::from_slice(r.take(33)?)?)
+    }
+}
+
+impl WireType for ShortChannelId {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.to_u64().write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(ShortChannelId::from_u64(r.read()?))
+    }
+}
+
+impl WireType for Sha256 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.extend_from_slice(&self[..]);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Sha256::from_slice(r.take(32)?)?)
+    }
+}
+
+impl WireType for BlockHash {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.extend_from_slice(&self[..]);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(BlockHash::from_slice(r.take(32)?)?)
+    }
+}
+
+impl WireType for Txid {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.extend_from_slice(&self[..]);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Txid::from_slice(r.take(32)?)?)
+    }
+}
+
+impl WireType for Secret {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        <[u8; 32]>::from(*self).write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Secret::from(r.read::<[u8; 32]>()?))
+    }
+}
+
+impl WireType for Signature {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.serialize_compact().write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(Signature::from_compact(r.take(64)?)?)
+    }
+}
+
+/// The compact signature followed by the recovery id.
+impl WireType for RecoverableSignature {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let (recid, sig) = self.serialize_compact();
+        sig.write(buf)?;
+        (recid.to_i32() as u8).write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig = r.take(64)?;
+        let recid = RecoveryId::from_i32(r.read::<u8>()? as i32)?;
+        Ok(RecoverableSignature::from_compact(sig, recid)?)
+    }
+}
+
+/// The compact signature followed by the sighash type.
+impl WireType for EcdsaSig {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        let hash_ty = u8::try_from(self.hash_ty.to_u32())?;
+        hash_ty.write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig = r.read()?;
+        let hash_ty = EcdsaSighashType::from_standard(r.read::<u8>()? as u32)?;
+        Ok(EcdsaSig { sig, hash_ty })
+    }
+}
+
+/// The 78 byte BIP32 serialization of the public key.
+impl WireType for ExtendedPubKey {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.encode().write(buf)
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(ExtendedPubKey::decode(r.take(78)?)?)
+    }
+}
+
+/// The serialized PSBT, preceded by its u32 length.
+impl WireType for Psbt {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let psbt = encode::serialize(self);
+        len::<u32>(psbt.len())?.write(buf)?;
+        buf.extend(psbt);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u32 = r.read()?;
+        Ok(encode::deserialize(r.take(len as usize)?)?)
+    }
+}
+
+/// Write a C `wirestring`, see [`Reader::wirestring`].
+pub fn write_wirestring(buf: &mut Vec<u8>, s: &str) -> Result<()> {
+    if s.bytes().any(|c| c < b' ') {
+        return Err(anyhow!("{:?} contains control characters", s));
+    }
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(0);
+    Ok(())
+}
+
+/// Write a C `amount_sat`, which can't hold millisatoshis.
+pub fn write_sat(buf: &mut Vec<u8>, amount: &Amount) -> Result<()> {
+    if Amount::from_sat(amount.sat_floor()) != *amount {
+        return Err(anyhow!(
+            "{} is not a whole number of satoshis",
+            amount.msat()
+        ));
+    }
+    amount.sat_floor().write(buf)
+}
+
+/// The value of a length field for `len` elements.
+pub fn len<T: TryFrom<usize>>(len: usize) -> Result<T> {
+    T::try_from(len).map_err(|_| anyhow!("{} elements don't fit the length field", len))
+}
+
+/// Decode the value of a TLV record, which `f` must consume entirely.
+pub fn read_tlv<'a, T>(e: &'a TlvEntry, f: impl FnOnce(&mut Reader<'a>) -> Result<T>) -> Result<T> {
+    let mut r = Reader::new(&e.value);
+    let v = f(&mut r).with_context(|| format!("decoding TLV type {}", e.typ))?;
+    if !r.is_empty() {
+        return Err(anyhow!("{} trailing bytes in TLV type {}", r.len(), e.typ));
+    }
+    Ok(v)
+}
+
+/// Add a record whose value `f` writes to `stream`.
+pub fn write_tlv(
+    stream: &mut TlvStream,
+    typ: u64,
+    f: impl FnOnce(&mut Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    let mut value = vec![];
+    f(&mut value)?;
+    stream.insert(typ, value);
+    Ok(())
+}
+
+/// Keep a record we don't know about, as long as it's odd.
+pub fn unknown_tlv(unknown: &mut Vec<TlvEntry>, e: TlvEntry) -> Result<()> {
+    if e.typ & 1 == 0 {
+        return Err(anyhow!("Unknown even TLV type {}", e.typ));
+    }
+    unknown.push(e);
+    Ok(())
+}
+
+/// The stream holding `unknown`, to which known records get added.
+pub fn unknown_stream(unknown: &[TlvEntry]) -> Result<TlvStream> {
+    let mut stream = TlvStream {
+        entries: unknown.to_vec(),
+    };
+    stream.canonicalize()?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod test {
+    use super::onion::{OnionFailure, TemporaryChannelFailure};
+    use super::peer::*;
+    use super::*;
+    use std::str::FromStr;
+
+    fn hex(s: &str) -> Vec<u8> {
+        hex::decode(s.replace(' ', "")).unwrap()
+    }
+
+    fn node_id() -> PublicKey {
+        PublicKey::from_str("023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb")
+            .unwrap()
+    }
+
+    fn decode<T: WireType>(buf: &[u8]) -> Result<T> {
+        T::read(&mut Reader::new(buf))
+    }
+
+    fn encode<T: WireType>(v: &T) -> Vec<u8> {
+        let mut buf = vec![];
+        v.write(&mut buf).unwrap();
+        buf
+    }
+
+    // The BOLT #1 test vectors, as in wire/test/run-tlvstream.c
+    const INVALID_EITHER: &[&str] = &[
+        "fd",
+        "fd01",
+        "fd0001 00",
+        "fd0101",
+        "0f fd",
+        "0f fd26",
+        "0f fd2602",
+        "0f fd0001 00",
+        "0f fd0201 0000000000",
+        "12 00",
+        "fd0102 00",
+        "fe01000002 00",
+        "ff0100000000000002 00",
+    ];
+
+    const INVALID_N1: &[&str] = &[
+        "01 09 ffffffffffffffffff",
+        "01 01 00",
+        "01 02 0001",
+        "01 03 000100",
+        "01 04 00010000",
+        "01 05 0001000000",
+        "01 06 000100000000",
+        "01 07 00010000000000",
+        "01 08 0001000000000000",
+        "02 07 01010101010101",
+        "02 09 010101010101010101",
+        "03 21 023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb",
+        "03 29 023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb0000000000000001",
+        "03 30 023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb000000000000000100000000000001",
+        "03 31 043da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb00000000000000010000000000000002",
+        "03 32 023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb0000000000000001000000000000000001",
+        "fd00fe 00",
+        "fd00fe 01 01",
+        "fd00fe 03 010101",
+        "00 00",
+        "02 08 0000000000000226 01 01 2a",
+        "02 08 0000000000000231 02 08 0000000000000451",
+        "1f 00 0f 01 2a",
+        "1f 00 1f 01 2a",
+    ];
+
+    const VALID_EITHER: &[&str] = &[
+        "",
+        "21 00",
+        "fd0201 00",
+        "fd00fd 00",
+        "fd00ff 00",
+        "fe02000001 00",
+        "ff0200000000000001 00",
+    ];
+
+    fn valid_n1() -> Vec<(&'static str, N1)> {
+        let tlv1 = |msat| N1 {
+            tlv1: Some(Amount::from_msat(msat)),
+            ..Default::default()
+        };
+        vec![
+            ("01 00", tlv1(0)),
+            ("01 01 01", tlv1(1)),
+            ("01 02 0100", tlv1(256)),
+            ("01 03 010000", tlv1(65536)),
+            ("01 04 01000000", tlv1(16777216)),
+            ("01 05 0100000000", tlv1(4294967296)),
+            ("01 06 010000000000", tlv1(1099511627776)),
+            ("01 07 01000000000000", tlv1(281474976710656)),
+            ("01 08 0100000000000000", tlv1(72057594037927936)),
+            (
+                "02 08 0000000000000226",
+                N1 {
+                    tlv2: Some(ShortChannelId::from_str("0x0x550").unwrap()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "03 31 023da092f6980e58d2c037173180e9a465476026ee50f96695963e8efe436f54eb00000000000000010000000000000002",
+                N1 {
+                    tlv3: Some(N1Tlv3 {
+                        node_id: node_id(),
+                        amount_msat_1: Amount::from_msat(1),
+                        amount_msat_2: Amount::from_msat(2),
+                    }),
+                    ..Default::default()
+                },
+            ),
+            (
+                "fd00fe 02 0226",
+                N1 {
+                    tlv4: Some(550),
+                    ..Default::default()
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_tlv_vectors() {
+        for s in INVALID_EITHER {
+            assert!(decode::<N1>(&hex(s)).is_err(), "{}", s);
+            assert!(decode::<N2>(&hex(s)).is_err(), "{}", s);
+        }
+        for s in INVALID_N1 {
+            assert!(decode::<N1>(&hex(s)).is_err(), "{}", s);
+        }
+        assert!(decode::<N2>(&hex("ffffffffffffffffff 00 00 00")).is_err());
+
+        for s in VALID_EITHER {
+            let n1: N1 = decode(&hex(s)).unwrap();
+            assert_eq!(n1.tlv1, None);
+            assert_eq!(encode(&n1), hex(s));
+            let n2: N2 = decode(&hex(s)).unwrap();
+            assert_eq!(n2.tlv1, None);
+            assert_eq!(encode(&n2), hex(s));
+        }
+        let valid = valid_n1();
+        for (s, expected) in &valid {
+            assert_eq!(&decode::<N1>(&hex(s)).unwrap(), expected, "{}", s);
+            assert_eq!(encode(expected), hex(s));
+        }
+
+        // Appending an invalid stream to a valid one makes it invalid,
+        // appending a higher numbered one keeps it valid.
+        let first_type = |s: &str| read_bigsize(&hex(s)).unwrap().0;
+        for (a, _) in &valid {
+            for b in INVALID_EITHER.iter().chain(INVALID_N1) {
+                let s = format!("{}{}", a, b);
+                assert!(decode::<N1>(&hex(&s)).is_err(), "{}", s);
+            }
+            for (b, _) in &valid {
+                let s = format!("{}{}", a, b);
+                let res = decode::<N1>(&hex(&s));
+                assert_eq!(res.is_ok(), first_type(a) < first_type(b), "{}", s);
+            }
+        }
+    }
+
+    fn messages() -> Vec<PeerMessage> {
+        let channel_id = [3; 32];
+        vec![
+            Init {
+                globalfeatures: vec![],
+                features: vec![0x02, 0xa2],
+                tlvs: InitTlvs {
+                    networks: Some(vec![BlockHash::from_slice(&[6; 32]).unwrap()]),
+                    remote_addr: None,
+                    unknown: vec![],
+                },
+            }
+            .into(),
+            Ping {
+                num_pong_bytes: 10,
+                ignored: vec![0; 3],
+            }
+            .into(),
+            OpenChannel2 {
+                chain_hash: BlockHash::from_slice(&[6; 32]).unwrap(),
+                channel_id,
+                funding_feerate_perkw: 253,
+                commitment_feerate_perkw: 253,
+                funding_satoshis: 100_000,
+                dust_limit_satoshis: 546,
+                max_htlc_value_in_flight_msat: Amount::from_msat(u64::MAX),
+                htlc_minimum_msat: Amount::from_msat(0),
+                to_self_delay: 6,
+                max_accepted_htlcs: 483,
+                locktime: 100,
+                funding_pubkey: node_id(),
+                revocation_basepoint: node_id(),
+                payment_basepoint: node_id(),
+                delayed_payment_basepoint: node_id(),
+                htlc_basepoint: node_id(),
+                first_per_commitment_point: node_id(),
+                channel_flags: 1,
+                tlvs: OpeningTlvs {
+                    option_upfront_shutdown_script: Some(vec![0, 20, 1, 2]),
+                    request_funds: Some(OpeningTlvsRequestFunds {
+                        requested_sats: 50_000,
+                        blockheight: 800_000,
+                    }),
+                    unknown: vec![TlvEntry {
+                        typ: 7,
+                        value: vec![1],
+                    }],
+                },
+            }
+            .into(),
+            TxSignatures {
+                channel_id,
+                txid: Sha256::from_slice(&[4; 32]).unwrap(),
+                witness_stack: vec![WitnessStack {
+                    witness_element: vec![
+                        WitnessElement { witness: vec![] },
+                        WitnessElement {
+                            witness: vec![1, 2, 3],
+                        },
+                    ],
+                }],
+            }
+            .into(),
+            UpdateAddHtlc {
+                channel_id,
+                id: 1,
+                amount_msat: Amount::from_msat(1000),
+                payment_hash: Sha256::from_slice(&[5; 32]).unwrap(),
+                cltv_expiry: 500_000,
+                onion_routing_packet: [7; 1366],
+                tlvs: UpdateAddTlvs {
+                    blinding: Some(node_id()),
+                    unknown: vec![],
+                },
+            }
+            .into(),
+            CommitmentSigned {
+                channel_id,
+                signature: [1; 64],
+                htlc_signature: vec![[2; 64], [3; 64]],
+            }
+            .into(),
+            QueryChannelRange {
+                chain_hash: BlockHash::from_slice(&[6; 32]).unwrap(),
+                first_blocknum: 1,
+                number_of_blocks: 0xffffffff,
+                tlvs: QueryChannelRangeTlvs {
+                    query_option: Some(0x10000),
+                    unknown: vec![],
+                },
+            }
+            .into(),
+            PeerMessage::Unknown {
+                typ: 32769,
+                payload: vec![1, 2, 3],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_peer_messages() {
+        for m in messages() {
+            let wire = m.to_wire().unwrap();
+            assert_eq!(u16::from_be_bytes([wire[0], wire[1]]), m.typ());
+            assert_eq!(PeerMessage::from_wire(&wire).unwrap(), m);
+            if let PeerMessage::Unknown { .. } = m {
+                continue;
+            }
+
+            // Truncated messages fail, unless they end with a complete
+            // TLV record, or right before the TLV stream.
+            let mut fixed = m.clone();
+            let has_tlvs = match &mut fixed {
+                PeerMessage::Init(m) => {
+                    m.tlvs = Default::default();
+                    true
+                }
+                PeerMessage::OpenChannel2(m) => {
+                    m.tlvs = Default::default();
+                    true
+                }
+                PeerMessage::UpdateAddHtlc(m) => {
+                    m.tlvs = Default::default();
+                    true
+                }
+                PeerMessage::QueryChannelRange(m) => {
+                    m.tlvs = Default::default();
+                    true
+                }
+                _ => false,
+            };
+            let fixed = fixed.to_wire().unwrap().len();
+            for n in 0..wire.len() {
+                let res = PeerMessage::from_wire(&wire[..n]);
+                let complete = n >= fixed && TlvStream::from_wire(&wire[fixed..n]).is_ok();
+                assert_eq!(res.is_ok(), has_tlvs && complete, "{:?} at {}", m, n);
+            }
+        }
+
+        // Trailing data is ignored by messages without a TLV stream.
+        let mut wire = messages()[1].to_wire().unwrap();
+        wire.push(0);
+        assert_eq!(PeerMessage::from_wire(&wire).unwrap(), messages()[1]);
+
+        let init = Init::from_wire(&messages()[0].to_wire().unwrap()).unwrap();
+        assert_eq!(init.features, vec![0x02, 0xa2]);
+        assert!(Ping::from_wire(&messages()[0].to_wire().unwrap()).is_err());
+
+        // Lengths must fit their field.
+        let ping = Ping {
+            num_pong_bytes: 0,
+            ignored: vec![0; 0x10000],
+        };
+        assert!(ping.to_wire().is_err());
+    }
+
+    #[test]
+    fn test_onion_failures() {
+        let update = ChannelUpdate {
+            signature: [1; 64],
+            chain_hash: BlockHash::from_slice(&[6; 32]).unwrap(),
+            short_channel_id: ShortChannelId::from_str("103x1x0").unwrap(),
+            timestamp: 1,
+            message_flags: 1,
+            channel_flags: 0,
+            cltv_expiry_delta: 6,
+            htlc_minimum_msat: Amount::from_msat(1),
+            fee_base_msat: 1,
+            fee_proportional_millionths: 10,
+            htlc_maximum_msat: Amount::from_sat(1),
+        };
+        let failure = TemporaryChannelFailure {
+            channel_update: update.to_wire().unwrap(),
+        };
+        let wire = failure.to_wire().unwrap();
+        assert_eq!(wire[..2], [0x10, 0x07]);
+        let OnionFailure::TemporaryChannelFailure(f) = OnionFailure::from_wire(&wire).unwrap()
+        else {
+            panic!("Not a temporary_channel_failure");
+        };
+        assert_eq!(
+            PeerMessage::from_wire(&f.channel_update).unwrap(),
+            PeerMessage::ChannelUpdate(update)
+        );
+
+        let f = OnionFailure::from_wire(&hex("4016 fd0102 0020")).unwrap();
+        assert_eq!(f.typ(), PERM | 22);
+        assert_eq!(
+            f,
+            OnionFailure::InvalidOnionPayload(super::onion::InvalidOnionPayload {
+                typ: 0x102,
+                offset: 32
+            })
+        );
+        assert_eq!(
+            OnionFailure::from_wire(&hex("2002")).unwrap(),
+            OnionFailure::TemporaryNodeFailure(super::onion::TemporaryNodeFailure {})
+        );
+    }
+
+    #[test]
+    fn test_bolt12() {
+        // The same offer as in the `bolt12` tests.
+        let s = "lno1qgsqvgnwgcg35z6ee2h3yczraddm72xrfua9uve2rlrm9deu7xyfzrcgqyqs5pr5v4ehg93pqfnwgkvdr57yzh6h92zg3qctvrm7w38djg67kzcm4yeg8vc4cq63s";
+        let offer: crate::bolt12::Offer = s.parse().unwrap();
+        let wire = offer.to_tlv_stream().unwrap().to_wire().unwrap();
+        let decoded: super::bolt12::Offer = decode(&wire).unwrap();
+        assert_eq!(decoded.offer_description.as_deref(), Some("test"));
+        assert_eq!(decoded.offer_amount, Some(1));
+        assert_eq!(decoded.offer_chains, Some(offer.chains.clone()));
+        assert_eq!(decoded.offer_node_id, Some(offer.node_id));
+        assert_eq!(encode(&decoded), wire);
+
+        // Unknown odd fields are kept, even ones are rejected.
+        let mut stream = TlvStream::from_wire(&wire).unwrap();
+        stream.insert(1001, vec![1]);
+        let decoded: super::bolt12::Offer = decode(&stream.to_wire().unwrap()).unwrap();
+        assert_eq!(decoded.unknown.len(), 1);
+        assert_eq!(encode(&decoded), stream.to_wire().unwrap());
+        stream.insert(1000, vec![1]);
+        assert!(decode::<super::bolt12::Offer>(&stream.to_wire().unwrap()).is_err());
+    }
+}
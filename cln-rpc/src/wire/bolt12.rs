@@ -0,0 +1,896 @@
+//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! contrib/msggen/msggen/__main__.py
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `wire/bolt12_wire.csv`
+//! which this file was generated from
+
+use anyhow::Result;
+use bitcoin::BlockHash;
+use crate::primitives::Amount;
+use crate::primitives::PublicKey;
+use crate::primitives::Sha256;
+use crate::primitives::TlvEntry;
+use crate::primitives::TlvStream;
+use super::onion::BlindedPath;
+use crate::primitives::TlvValue;
+use crate::wire::{Reader, WireType, len, read_tlv, unknown_stream, unknown_tlv, write_tlv};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recurrence {
+    pub time_unit: u8,
+    pub period: u32,
+}
+
+impl WireType for Recurrence {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.time_unit.write(buf)?;
+        buf.extend(self.period.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let time_unit: u8 = r.read()?;
+        let period: u32 = r.truncated()?;
+        Ok(Recurrence {
+            time_unit,
+            period,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrencePaywindow {
+    pub seconds_before: u32,
+    pub proportional_amount: u8,
+    pub seconds_after: u32,
+}
+
+impl WireType for RecurrencePaywindow {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.seconds_before.write(buf)?;
+        self.proportional_amount.write(buf)?;
+        buf.extend(self.seconds_after.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let seconds_before: u32 = r.read()?;
+        let proportional_amount: u8 = r.read()?;
+        let seconds_after: u32 = r.truncated()?;
+        Ok(RecurrencePaywindow {
+            seconds_before,
+            proportional_amount,
+            seconds_after,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurrenceBase {
+    pub start_any_period: u8,
+    pub basetime: u64,
+}
+
+impl WireType for RecurrenceBase {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.start_any_period.write(buf)?;
+        buf.extend(self.basetime.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let start_any_period: u8 = r.read()?;
+        let basetime: u64 = r.truncated()?;
+        Ok(RecurrenceBase {
+            start_any_period,
+            basetime,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlindedPayinfo {
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+    pub htlc_minimum_msat: Amount,
+    pub htlc_maximum_msat: Amount,
+    pub features: Vec<u8>,
+}
+
+impl WireType for BlindedPayinfo {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.fee_base_msat.write(buf)?;
+        self.fee_proportional_millionths.write(buf)?;
+        self.cltv_expiry_delta.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.htlc_maximum_msat.write(buf)?;
+        len::<u16>(self.features.len())?.write(buf)?;
+        buf.extend_from_slice(&self.features);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let fee_base_msat: u32 = r.read()?;
+        let fee_proportional_millionths: u32 = r.read()?;
+        let cltv_expiry_delta: u16 = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let htlc_maximum_msat: Amount = r.read()?;
+        let flen: u16 = r.read()?;
+        let features: Vec<u8> = r.take(flen as usize)?.to_vec();
+        Ok(BlindedPayinfo {
+            fee_base_msat,
+            fee_proportional_millionths,
+            cltv_expiry_delta,
+            htlc_minimum_msat,
+            htlc_maximum_msat,
+            features,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FallbackAddress {
+    pub version: u8,
+    pub address: Vec<u8>,
+}
+
+impl WireType for FallbackAddress {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.version.write(buf)?;
+        len::<u16>(self.address.len())?.write(buf)?;
+        buf.extend_from_slice(&self.address);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let version: u8 = r.read()?;
+        let len: u16 = r.read()?;
+        let address: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(FallbackAddress {
+            version,
+            address,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Offer {
+    pub offer_chains: Option<Vec<BlockHash>>,
+    pub offer_metadata: Option<Vec<u8>>,
+    pub offer_currency: Option<String>,
+    pub offer_amount: Option<u64>,
+    pub offer_description: Option<String>,
+    pub offer_features: Option<Vec<u8>>,
+    pub offer_absolute_expiry: Option<u64>,
+    pub offer_paths: Option<Vec<BlindedPath>>,
+    pub offer_issuer: Option<String>,
+    pub offer_quantity_max: Option<u64>,
+    pub offer_node_id: Option<PublicKey>,
+    pub offer_recurrence: Option<Recurrence>,
+    pub offer_recurrence_paywindow: Option<RecurrencePaywindow>,
+    pub offer_recurrence_limit: Option<u32>,
+    pub offer_recurrence_base: Option<RecurrenceBase>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for Offer {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.offer_chains {
+            write_tlv(&mut stream, 2, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_metadata {
+            write_tlv(&mut stream, 4, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_currency {
+            write_tlv(&mut stream, 6, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_amount {
+            write_tlv(&mut stream, 8, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_description {
+            write_tlv(&mut stream, 10, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_features {
+            write_tlv(&mut stream, 12, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_absolute_expiry {
+            write_tlv(&mut stream, 14, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_paths {
+            write_tlv(&mut stream, 16, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_issuer {
+            write_tlv(&mut stream, 18, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_quantity_max {
+            write_tlv(&mut stream, 20, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_node_id {
+            write_tlv(&mut stream, 22, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence {
+            write_tlv(&mut stream, 26, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_paywindow {
+            write_tlv(&mut stream, 28, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_limit {
+            write_tlv(&mut stream, 30, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_base {
+            write_tlv(&mut stream, 32, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = Offer::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                2 => res.offer_chains = Some(read_tlv(&e, |r| r.read_all())?),
+                4 => res.offer_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                6 => res.offer_currency = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                8 => res.offer_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                10 => res.offer_description = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                12 => res.offer_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                14 => res.offer_absolute_expiry = Some(read_tlv(&e, |r| r.truncated())?),
+                16 => res.offer_paths = Some(read_tlv(&e, |r| r.read_all())?),
+                18 => res.offer_issuer = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                20 => res.offer_quantity_max = Some(read_tlv(&e, |r| r.truncated())?),
+                22 => res.offer_node_id = Some(read_tlv(&e, |r| r.read())?),
+                26 => res.offer_recurrence = Some(read_tlv(&e, |r| r.read())?),
+                28 => res.offer_recurrence_paywindow = Some(read_tlv(&e, |r| r.read())?),
+                30 => res.offer_recurrence_limit = Some(read_tlv(&e, |r| r.truncated())?),
+                32 => res.offer_recurrence_base = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InvoiceRequest {
+    pub invreq_metadata: Option<Vec<u8>>,
+    pub offer_chains: Option<Vec<BlockHash>>,
+    pub offer_metadata: Option<Vec<u8>>,
+    pub offer_currency: Option<String>,
+    pub offer_amount: Option<u64>,
+    pub offer_description: Option<String>,
+    pub offer_features: Option<Vec<u8>>,
+    pub offer_absolute_expiry: Option<u64>,
+    pub offer_paths: Option<Vec<BlindedPath>>,
+    pub offer_issuer: Option<String>,
+    pub offer_quantity_max: Option<u64>,
+    pub offer_node_id: Option<PublicKey>,
+    pub offer_recurrence: Option<Recurrence>,
+    pub offer_recurrence_paywindow: Option<RecurrencePaywindow>,
+    pub offer_recurrence_limit: Option<u32>,
+    pub offer_recurrence_base: Option<RecurrenceBase>,
+    pub invreq_chain: Option<BlockHash>,
+    pub invreq_amount: Option<Amount>,
+    pub invreq_features: Option<Vec<u8>>,
+    pub invreq_quantity: Option<u64>,
+    pub invreq_payer_id: Option<PublicKey>,
+    pub invreq_payer_note: Option<String>,
+    pub invreq_recurrence_counter: Option<u32>,
+    pub invreq_recurrence_start: Option<u32>,
+    pub signature: Option<[u8; 64]>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for InvoiceRequest {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.invreq_metadata {
+            write_tlv(&mut stream, 0, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_chains {
+            write_tlv(&mut stream, 2, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_metadata {
+            write_tlv(&mut stream, 4, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_currency {
+            write_tlv(&mut stream, 6, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_amount {
+            write_tlv(&mut stream, 8, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_description {
+            write_tlv(&mut stream, 10, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_features {
+            write_tlv(&mut stream, 12, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_absolute_expiry {
+            write_tlv(&mut stream, 14, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_paths {
+            write_tlv(&mut stream, 16, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_issuer {
+            write_tlv(&mut stream, 18, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_quantity_max {
+            write_tlv(&mut stream, 20, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_node_id {
+            write_tlv(&mut stream, 22, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence {
+            write_tlv(&mut stream, 26, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_paywindow {
+            write_tlv(&mut stream, 28, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_limit {
+            write_tlv(&mut stream, 30, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_base {
+            write_tlv(&mut stream, 32, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_chain {
+            write_tlv(&mut stream, 80, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_amount {
+            write_tlv(&mut stream, 82, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_features {
+            write_tlv(&mut stream, 84, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_quantity {
+            write_tlv(&mut stream, 86, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_payer_id {
+            write_tlv(&mut stream, 88, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_payer_note {
+            write_tlv(&mut stream, 89, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_recurrence_counter {
+            write_tlv(&mut stream, 90, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_recurrence_start {
+            write_tlv(&mut stream, 92, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.signature {
+            write_tlv(&mut stream, 240, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = InvoiceRequest::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                0 => res.invreq_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                2 => res.offer_chains = Some(read_tlv(&e, |r| r.read_all())?),
+                4 => res.offer_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                6 => res.offer_currency = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                8 => res.offer_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                10 => res.offer_description = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                12 => res.offer_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                14 => res.offer_absolute_expiry = Some(read_tlv(&e, |r| r.truncated())?),
+                16 => res.offer_paths = Some(read_tlv(&e, |r| r.read_all())?),
+                18 => res.offer_issuer = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                20 => res.offer_quantity_max = Some(read_tlv(&e, |r| r.truncated())?),
+                22 => res.offer_node_id = Some(read_tlv(&e, |r| r.read())?),
+                26 => res.offer_recurrence = Some(read_tlv(&e, |r| r.read())?),
+                28 => res.offer_recurrence_paywindow = Some(read_tlv(&e, |r| r.read())?),
+                30 => res.offer_recurrence_limit = Some(read_tlv(&e, |r| r.truncated())?),
+                32 => res.offer_recurrence_base = Some(read_tlv(&e, |r| r.read())?),
+                80 => res.invreq_chain = Some(read_tlv(&e, |r| r.read())?),
+                82 => res.invreq_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                84 => res.invreq_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                86 => res.invreq_quantity = Some(read_tlv(&e, |r| r.truncated())?),
+                88 => res.invreq_payer_id = Some(read_tlv(&e, |r| r.read())?),
+                89 => res.invreq_payer_note = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                90 => res.invreq_recurrence_counter = Some(read_tlv(&e, |r| r.truncated())?),
+                92 => res.invreq_recurrence_start = Some(read_tlv(&e, |r| r.truncated())?),
+                240 => res.signature = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Invoice {
+    pub invreq_metadata: Option<Vec<u8>>,
+    pub offer_chains: Option<Vec<BlockHash>>,
+    pub offer_metadata: Option<Vec<u8>>,
+    pub offer_currency: Option<String>,
+    pub offer_amount: Option<u64>,
+    pub offer_description: Option<String>,
+    pub offer_features: Option<Vec<u8>>,
+    pub offer_absolute_expiry: Option<u64>,
+    pub offer_paths: Option<Vec<BlindedPath>>,
+    pub offer_issuer: Option<String>,
+    pub offer_quantity_max: Option<u64>,
+    pub offer_node_id: Option<PublicKey>,
+    pub offer_recurrence: Option<Recurrence>,
+    pub offer_recurrence_paywindow: Option<RecurrencePaywindow>,
+    pub offer_recurrence_limit: Option<u32>,
+    pub offer_recurrence_base: Option<RecurrenceBase>,
+    pub invreq_chain: Option<BlockHash>,
+    pub invreq_amount: Option<Amount>,
+    pub invreq_features: Option<Vec<u8>>,
+    pub invreq_quantity: Option<u64>,
+    pub invreq_payer_id: Option<PublicKey>,
+    pub invreq_payer_note: Option<String>,
+    pub invreq_recurrence_counter: Option<u32>,
+    pub invreq_recurrence_start: Option<u32>,
+    pub invoice_paths: Option<Vec<BlindedPath>>,
+    pub invoice_blindedpay: Option<Vec<BlindedPayinfo>>,
+    pub invoice_created_at: Option<u64>,
+    pub invoice_relative_expiry: Option<u32>,
+    pub invoice_payment_hash: Option<Sha256>,
+    pub invoice_amount: Option<Amount>,
+    pub invoice_fallbacks: Option<Vec<FallbackAddress>>,
+    pub invoice_features: Option<Vec<u8>>,
+    pub invoice_node_id: Option<PublicKey>,
+    pub invoice_recurrence_basetime: Option<u64>,
+    pub signature: Option<[u8; 64]>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for Invoice {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.invreq_metadata {
+            write_tlv(&mut stream, 0, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_chains {
+            write_tlv(&mut stream, 2, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_metadata {
+            write_tlv(&mut stream, 4, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_currency {
+            write_tlv(&mut stream, 6, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_amount {
+            write_tlv(&mut stream, 8, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_description {
+            write_tlv(&mut stream, 10, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_features {
+            write_tlv(&mut stream, 12, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_absolute_expiry {
+            write_tlv(&mut stream, 14, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_paths {
+            write_tlv(&mut stream, 16, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_issuer {
+            write_tlv(&mut stream, 18, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_quantity_max {
+            write_tlv(&mut stream, 20, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_node_id {
+            write_tlv(&mut stream, 22, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence {
+            write_tlv(&mut stream, 26, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_paywindow {
+            write_tlv(&mut stream, 28, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_limit {
+            write_tlv(&mut stream, 30, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.offer_recurrence_base {
+            write_tlv(&mut stream, 32, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_chain {
+            write_tlv(&mut stream, 80, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_amount {
+            write_tlv(&mut stream, 82, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_features {
+            write_tlv(&mut stream, 84, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_quantity {
+            write_tlv(&mut stream, 86, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_payer_id {
+            write_tlv(&mut stream, 88, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_payer_note {
+            write_tlv(&mut stream, 89, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_recurrence_counter {
+            write_tlv(&mut stream, 90, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invreq_recurrence_start {
+            write_tlv(&mut stream, 92, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_paths {
+            write_tlv(&mut stream, 160, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_blindedpay {
+            write_tlv(&mut stream, 162, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_created_at {
+            write_tlv(&mut stream, 164, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_relative_expiry {
+            write_tlv(&mut stream, 166, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_payment_hash {
+            write_tlv(&mut stream, 168, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_amount {
+            write_tlv(&mut stream, 170, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_fallbacks {
+            write_tlv(&mut stream, 172, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_features {
+            write_tlv(&mut stream, 174, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_node_id {
+            write_tlv(&mut stream, 176, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_recurrence_basetime {
+            write_tlv(&mut stream, 178, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.signature {
+            write_tlv(&mut stream, 240, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = Invoice::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                0 => res.invreq_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                2 => res.offer_chains = Some(read_tlv(&e, |r| r.read_all())?),
+                4 => res.offer_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                6 => res.offer_currency = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                8 => res.offer_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                10 => res.offer_description = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                12 => res.offer_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                14 => res.offer_absolute_expiry = Some(read_tlv(&e, |r| r.truncated())?),
+                16 => res.offer_paths = Some(read_tlv(&e, |r| r.read_all())?),
+                18 => res.offer_issuer = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                20 => res.offer_quantity_max = Some(read_tlv(&e, |r| r.truncated())?),
+                22 => res.offer_node_id = Some(read_tlv(&e, |r| r.read())?),
+                26 => res.offer_recurrence = Some(read_tlv(&e, |r| r.read())?),
+                28 => res.offer_recurrence_paywindow = Some(read_tlv(&e, |r| r.read())?),
+                30 => res.offer_recurrence_limit = Some(read_tlv(&e, |r| r.truncated())?),
+                32 => res.offer_recurrence_base = Some(read_tlv(&e, |r| r.read())?),
+                80 => res.invreq_chain = Some(read_tlv(&e, |r| r.read())?),
+                82 => res.invreq_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                84 => res.invreq_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                86 => res.invreq_quantity = Some(read_tlv(&e, |r| r.truncated())?),
+                88 => res.invreq_payer_id = Some(read_tlv(&e, |r| r.read())?),
+                89 => res.invreq_payer_note = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                90 => res.invreq_recurrence_counter = Some(read_tlv(&e, |r| r.truncated())?),
+                92 => res.invreq_recurrence_start = Some(read_tlv(&e, |r| r.truncated())?),
+                160 => res.invoice_paths = Some(read_tlv(&e, |r| r.read_all())?),
+                162 => res.invoice_blindedpay = Some(read_tlv(&e, |r| r.read_all())?),
+                164 => res.invoice_created_at = Some(read_tlv(&e, |r| r.truncated())?),
+                166 => res.invoice_relative_expiry = Some(read_tlv(&e, |r| r.truncated())?),
+                168 => res.invoice_payment_hash = Some(read_tlv(&e, |r| r.read())?),
+                170 => res.invoice_amount = Some(read_tlv(&e, |r| r.truncated())?),
+                172 => res.invoice_fallbacks = Some(read_tlv(&e, |r| r.read_all())?),
+                174 => res.invoice_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                176 => res.invoice_node_id = Some(read_tlv(&e, |r| r.read())?),
+                178 => res.invoice_recurrence_basetime = Some(read_tlv(&e, |r| r.truncated())?),
+                240 => res.signature = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InvoiceError {
+    pub erroneous_field: Option<u64>,
+    pub suggested_value: Option<Vec<u8>>,
+    pub error: Option<String>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for InvoiceError {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.erroneous_field {
+            write_tlv(&mut stream, 1, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.suggested_value {
+            write_tlv(&mut stream, 3, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.error {
+            write_tlv(&mut stream, 5, |buf| {
+                buf.extend_from_slice(v.as_bytes());
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = InvoiceError::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.erroneous_field = Some(read_tlv(&e, |r| r.truncated())?),
+                3 => res.suggested_value = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                5 => res.error = Some(read_tlv(&e, |r| r.utf8(r.len()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
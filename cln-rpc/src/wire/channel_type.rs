@@ -0,0 +1,32 @@
+//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! contrib/msggen/msggen/__main__.py
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `wire/channel_type_wire.csv`
+//! which this file was generated from
+
+use anyhow::Result;
+use crate::wire::{Reader, WireType, len};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelType {
+    pub features: Vec<u8>,
+}
+
+impl WireType for ChannelType {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.features.len())?.write(buf)?;
+        buf.extend_from_slice(&self.features);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let features: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(ChannelType {
+            features,
+        })
+    }
+}
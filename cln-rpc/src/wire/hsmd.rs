@@ -0,0 +1,2775 @@
+//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! contrib/msggen/msggen/__main__.py
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `hsmd/hsmd_wire.csv`
+//! which this file was generated from
+
+use anyhow::{anyhow, Result};
+use bitcoin::BlockHash;
+use bitcoin::EcdsaSig;
+use bitcoin::Txid;
+use bitcoin::psbt::Psbt;
+use bitcoin::secp256k1::ecdsa::RecoverableSignature;
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::util::bip32::ExtendedPubKey;
+use crate::hsmd::Basepoints;
+use crate::hsmd::Bip32KeyVersion;
+use crate::hsmd::BitcoinTx;
+use crate::hsmd::Secrets;
+use crate::hsmd::SimpleHtlc;
+use crate::hsmd::Utxo;
+use crate::primitives::Amount;
+use crate::primitives::PublicKey;
+use crate::primitives::Secret;
+use crate::primitives::Sha256;
+use super::channel_type::ChannelType;
+use crate::wire::{Reader, WireMessage, WireType, len, write_sat, write_wirestring};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmstatusClientBadRequest {
+    pub id: PublicKey,
+    pub description: String,
+    pub msg: Vec<u8>,
+}
+
+impl WireType for HsmstatusClientBadRequest {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.id.write(buf)?;
+        write_wirestring(buf, &self.description)?;
+        len::<u16>(self.msg.len())?.write(buf)?;
+        buf.extend_from_slice(&self.msg);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let id: PublicKey = r.read()?;
+        let description: String = r.wirestring()?;
+        let len: u16 = r.read()?;
+        let msg: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(HsmstatusClientBadRequest {
+            id,
+            description,
+            msg,
+        })
+    }
+}
+
+impl WireMessage for HsmstatusClientBadRequest {
+    const TYPE: u16 = 1000;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdInit {
+    pub bip32_key_version: Bip32KeyVersion,
+    pub chainparams: BlockHash,
+    pub hsm_encryption_key: Option<Secret>,
+    pub dev_force_privkey: Option<Secret>,
+    pub dev_force_bip32_seed: Option<Secret>,
+    pub dev_force_channel_secrets: Option<Secrets>,
+    pub dev_force_channel_secrets_shaseed: Option<Sha256>,
+    pub hsm_wire_min_version: u32,
+    pub hsm_wire_max_version: u32,
+}
+
+impl WireType for HsmdInit {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.bip32_key_version.write(buf)?;
+        self.chainparams.write(buf)?;
+        self.hsm_encryption_key.write(buf)?;
+        self.dev_force_privkey.write(buf)?;
+        self.dev_force_bip32_seed.write(buf)?;
+        self.dev_force_channel_secrets.write(buf)?;
+        self.dev_force_channel_secrets_shaseed.write(buf)?;
+        self.hsm_wire_min_version.write(buf)?;
+        self.hsm_wire_max_version.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let bip32_key_version: Bip32KeyVersion = r.read()?;
+        let chainparams: BlockHash = r.read()?;
+        let hsm_encryption_key: Option<Secret> = r.read()?;
+        let dev_force_privkey: Option<Secret> = r.read()?;
+        let dev_force_bip32_seed: Option<Secret> = r.read()?;
+        let dev_force_channel_secrets: Option<Secrets> = r.read()?;
+        let dev_force_channel_secrets_shaseed: Option<Sha256> = r.read()?;
+        let hsm_wire_min_version: u32 = r.read()?;
+        let hsm_wire_max_version: u32 = r.read()?;
+        Ok(HsmdInit {
+            bip32_key_version,
+            chainparams,
+            hsm_encryption_key,
+            dev_force_privkey,
+            dev_force_bip32_seed,
+            dev_force_channel_secrets,
+            dev_force_channel_secrets_shaseed,
+            hsm_wire_min_version,
+            hsm_wire_max_version,
+        })
+    }
+}
+
+impl WireMessage for HsmdInit {
+    const TYPE: u16 = 11;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdInitReplyV1 {
+    pub node_id: PublicKey,
+    pub bip32: ExtendedPubKey,
+    pub bolt12: [u8; 32],
+    pub onion_reply_secret: Secret,
+}
+
+impl WireType for HsmdInitReplyV1 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.node_id.write(buf)?;
+        self.bip32.write(buf)?;
+        self.bolt12.write(buf)?;
+        self.onion_reply_secret.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let node_id: PublicKey = r.read()?;
+        let bip32: ExtendedPubKey = r.read()?;
+        let bolt12: [u8; 32] = r.read()?;
+        let onion_reply_secret: Secret = r.read()?;
+        Ok(HsmdInitReplyV1 {
+            node_id,
+            bip32,
+            bolt12,
+            onion_reply_secret,
+        })
+    }
+}
+
+impl WireMessage for HsmdInitReplyV1 {
+    const TYPE: u16 = 111;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdInitReplyV2 {
+    pub node_id: PublicKey,
+    pub bip32: ExtendedPubKey,
+    pub bolt12: PublicKey,
+}
+
+impl WireType for HsmdInitReplyV2 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.node_id.write(buf)?;
+        self.bip32.write(buf)?;
+        self.bolt12.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let node_id: PublicKey = r.read()?;
+        let bip32: ExtendedPubKey = r.read()?;
+        let bolt12: PublicKey = r.read()?;
+        Ok(HsmdInitReplyV2 {
+            node_id,
+            bip32,
+            bolt12,
+        })
+    }
+}
+
+impl WireMessage for HsmdInitReplyV2 {
+    const TYPE: u16 = 113;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdNewChannel {
+    pub id: PublicKey,
+    pub dbid: u64,
+}
+
+impl WireType for HsmdNewChannel {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.id.write(buf)?;
+        self.dbid.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let id: PublicKey = r.read()?;
+        let dbid: u64 = r.read()?;
+        Ok(HsmdNewChannel {
+            id,
+            dbid,
+        })
+    }
+}
+
+impl WireMessage for HsmdNewChannel {
+    const TYPE: u16 = 30;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdNewChannelReply {
+}
+
+impl WireType for HsmdNewChannelReply {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(HsmdNewChannelReply {})
+    }
+}
+
+impl WireMessage for HsmdNewChannelReply {
+    const TYPE: u16 = 130;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdClientHsmfd {
+    pub id: PublicKey,
+    pub dbid: u64,
+    pub capabilities: u64,
+}
+
+impl WireType for HsmdClientHsmfd {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.id.write(buf)?;
+        self.dbid.write(buf)?;
+        self.capabilities.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let id: PublicKey = r.read()?;
+        let dbid: u64 = r.read()?;
+        let capabilities: u64 = r.read()?;
+        Ok(HsmdClientHsmfd {
+            id,
+            dbid,
+            capabilities,
+        })
+    }
+}
+
+impl WireMessage for HsmdClientHsmfd {
+    const TYPE: u16 = 9;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdClientHsmfdReply {
+}
+
+impl WireType for HsmdClientHsmfdReply {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(HsmdClientHsmfdReply {})
+    }
+}
+
+impl WireMessage for HsmdClientHsmfdReply {
+    const TYPE: u16 = 109;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetChannelBasepoints {
+    pub peerid: PublicKey,
+    pub dbid: u64,
+}
+
+impl WireType for HsmdGetChannelBasepoints {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.peerid.write(buf)?;
+        self.dbid.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let peerid: PublicKey = r.read()?;
+        let dbid: u64 = r.read()?;
+        Ok(HsmdGetChannelBasepoints {
+            peerid,
+            dbid,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetChannelBasepoints {
+    const TYPE: u16 = 10;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetChannelBasepointsReply {
+    pub basepoints: Basepoints,
+    pub funding_pubkey: PublicKey,
+}
+
+impl WireType for HsmdGetChannelBasepointsReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.basepoints.write(buf)?;
+        self.funding_pubkey.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let basepoints: Basepoints = r.read()?;
+        let funding_pubkey: PublicKey = r.read()?;
+        Ok(HsmdGetChannelBasepointsReply {
+            basepoints,
+            funding_pubkey,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetChannelBasepointsReply {
+    const TYPE: u16 = 110;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdReadyChannel {
+    pub is_outbound: bool,
+    pub channel_value: Amount,
+    pub push_value: Amount,
+    pub funding_txid: Txid,
+    pub funding_txout: u16,
+    pub local_to_self_delay: u16,
+    pub local_shutdown_script: Vec<u8>,
+    pub local_shutdown_wallet_index: Option<u32>,
+    pub remote_basepoints: Basepoints,
+    pub remote_funding_pubkey: PublicKey,
+    pub remote_to_self_delay: u16,
+    pub remote_shutdown_script: Vec<u8>,
+    pub channel_type: ChannelType,
+}
+
+impl WireType for HsmdReadyChannel {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.is_outbound.write(buf)?;
+        write_sat(buf, &self.channel_value)?;
+        self.push_value.write(buf)?;
+        self.funding_txid.write(buf)?;
+        self.funding_txout.write(buf)?;
+        self.local_to_self_delay.write(buf)?;
+        len::<u16>(self.local_shutdown_script.len())?.write(buf)?;
+        buf.extend_from_slice(&self.local_shutdown_script);
+        self.local_shutdown_wallet_index.write(buf)?;
+        self.remote_basepoints.write(buf)?;
+        self.remote_funding_pubkey.write(buf)?;
+        self.remote_to_self_delay.write(buf)?;
+        len::<u16>(self.remote_shutdown_script.len())?.write(buf)?;
+        buf.extend_from_slice(&self.remote_shutdown_script);
+        self.channel_type.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let is_outbound: bool = r.read()?;
+        let channel_value: Amount = r.sat()?;
+        let push_value: Amount = r.read()?;
+        let funding_txid: Txid = r.read()?;
+        let funding_txout: u16 = r.read()?;
+        let local_to_self_delay: u16 = r.read()?;
+        let local_shutdown_script_len: u16 = r.read()?;
+        let local_shutdown_script: Vec<u8> = r.take(local_shutdown_script_len as usize)?.to_vec();
+        let local_shutdown_wallet_index: Option<u32> = r.read()?;
+        let remote_basepoints: Basepoints = r.read()?;
+        let remote_funding_pubkey: PublicKey = r.read()?;
+        let remote_to_self_delay: u16 = r.read()?;
+        let remote_shutdown_script_len: u16 = r.read()?;
+        let remote_shutdown_script: Vec<u8> = r.take(remote_shutdown_script_len as usize)?.to_vec();
+        let channel_type: ChannelType = r.read()?;
+        Ok(HsmdReadyChannel {
+            is_outbound,
+            channel_value,
+            push_value,
+            funding_txid,
+            funding_txout,
+            local_to_self_delay,
+            local_shutdown_script,
+            local_shutdown_wallet_index,
+            remote_basepoints,
+            remote_funding_pubkey,
+            remote_to_self_delay,
+            remote_shutdown_script,
+            channel_type,
+        })
+    }
+}
+
+impl WireMessage for HsmdReadyChannel {
+    const TYPE: u16 = 31;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdReadyChannelReply {
+}
+
+impl WireType for HsmdReadyChannelReply {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(HsmdReadyChannelReply {})
+    }
+}
+
+impl WireMessage for HsmdReadyChannelReply {
+    const TYPE: u16 = 131;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdNodeAnnouncementSigReq {
+    pub announcement: Vec<u8>,
+}
+
+impl WireType for HsmdNodeAnnouncementSigReq {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.announcement.len())?.write(buf)?;
+        buf.extend_from_slice(&self.announcement);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let annlen: u16 = r.read()?;
+        let announcement: Vec<u8> = r.take(annlen as usize)?.to_vec();
+        Ok(HsmdNodeAnnouncementSigReq {
+            announcement,
+        })
+    }
+}
+
+impl WireMessage for HsmdNodeAnnouncementSigReq {
+    const TYPE: u16 = 6;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdNodeAnnouncementSigReply {
+    pub signature: Signature,
+}
+
+impl WireType for HsmdNodeAnnouncementSigReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.signature.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let signature: Signature = r.read()?;
+        Ok(HsmdNodeAnnouncementSigReply {
+            signature,
+        })
+    }
+}
+
+impl WireMessage for HsmdNodeAnnouncementSigReply {
+    const TYPE: u16 = 106;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignWithdrawal {
+    pub inputs: Vec<Utxo>,
+    pub psbt: Psbt,
+}
+
+impl WireType for HsmdSignWithdrawal {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.inputs.len())?.write(buf)?;
+        for x in &self.inputs {
+            x.write(buf)?;
+        }
+        self.psbt.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let num_inputs: u16 = r.read()?;
+        let inputs: Vec<Utxo> = r.read_n(num_inputs as usize)?;
+        let psbt: Psbt = r.read()?;
+        Ok(HsmdSignWithdrawal {
+            inputs,
+            psbt,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignWithdrawal {
+    const TYPE: u16 = 7;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignWithdrawalReply {
+    pub psbt: Psbt,
+}
+
+impl WireType for HsmdSignWithdrawalReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.psbt.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let psbt: Psbt = r.read()?;
+        Ok(HsmdSignWithdrawalReply {
+            psbt,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignWithdrawalReply {
+    const TYPE: u16 = 107;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignInvoice {
+    pub u5bytes: Vec<u8>,
+    pub hrp: Vec<u8>,
+}
+
+impl WireType for HsmdSignInvoice {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.u5bytes.len())?.write(buf)?;
+        buf.extend_from_slice(&self.u5bytes);
+        len::<u16>(self.hrp.len())?.write(buf)?;
+        buf.extend_from_slice(&self.hrp);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let u5bytes: Vec<u8> = r.take(len as usize)?.to_vec();
+        let hrplen: u16 = r.read()?;
+        let hrp: Vec<u8> = r.take(hrplen as usize)?.to_vec();
+        Ok(HsmdSignInvoice {
+            u5bytes,
+            hrp,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignInvoice {
+    const TYPE: u16 = 8;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignInvoiceReply {
+    pub sig: RecoverableSignature,
+}
+
+impl WireType for HsmdSignInvoiceReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig: RecoverableSignature = r.read()?;
+        Ok(HsmdSignInvoiceReply {
+            sig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignInvoiceReply {
+    const TYPE: u16 = 108;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdEcdhReq {
+    pub point: PublicKey,
+}
+
+impl WireType for HsmdEcdhReq {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.point.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let point: PublicKey = r.read()?;
+        Ok(HsmdEcdhReq {
+            point,
+        })
+    }
+}
+
+impl WireMessage for HsmdEcdhReq {
+    const TYPE: u16 = 1;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdEcdhResp {
+    pub ss: Secret,
+}
+
+impl WireType for HsmdEcdhResp {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.ss.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let ss: Secret = r.read()?;
+        Ok(HsmdEcdhResp {
+            ss,
+        })
+    }
+}
+
+impl WireMessage for HsmdEcdhResp {
+    const TYPE: u16 = 100;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCannouncementSigReq {
+    pub ca: Vec<u8>,
+}
+
+impl WireType for HsmdCannouncementSigReq {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.ca.len())?.write(buf)?;
+        buf.extend_from_slice(&self.ca);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let calen: u16 = r.read()?;
+        let ca: Vec<u8> = r.take(calen as usize)?.to_vec();
+        Ok(HsmdCannouncementSigReq {
+            ca,
+        })
+    }
+}
+
+impl WireMessage for HsmdCannouncementSigReq {
+    const TYPE: u16 = 2;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCannouncementSigReply {
+    pub node_signature: Signature,
+    pub bitcoin_signature: Signature,
+}
+
+impl WireType for HsmdCannouncementSigReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.node_signature.write(buf)?;
+        self.bitcoin_signature.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let node_signature: Signature = r.read()?;
+        let bitcoin_signature: Signature = r.read()?;
+        Ok(HsmdCannouncementSigReply {
+            node_signature,
+            bitcoin_signature,
+        })
+    }
+}
+
+impl WireMessage for HsmdCannouncementSigReply {
+    const TYPE: u16 = 102;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCupdateSigReq {
+    pub cu: Vec<u8>,
+}
+
+impl WireType for HsmdCupdateSigReq {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.cu.len())?.write(buf)?;
+        buf.extend_from_slice(&self.cu);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let culen: u16 = r.read()?;
+        let cu: Vec<u8> = r.take(culen as usize)?.to_vec();
+        Ok(HsmdCupdateSigReq {
+            cu,
+        })
+    }
+}
+
+impl WireMessage for HsmdCupdateSigReq {
+    const TYPE: u16 = 3;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCupdateSigReply {
+    pub cu: Vec<u8>,
+}
+
+impl WireType for HsmdCupdateSigReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.cu.len())?.write(buf)?;
+        buf.extend_from_slice(&self.cu);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let culen: u16 = r.read()?;
+        let cu: Vec<u8> = r.take(culen as usize)?.to_vec();
+        Ok(HsmdCupdateSigReply {
+            cu,
+        })
+    }
+}
+
+impl WireMessage for HsmdCupdateSigReply {
+    const TYPE: u16 = 103;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignCommitmentTx {
+    pub peer_id: PublicKey,
+    pub channel_dbid: u64,
+    pub tx: BitcoinTx,
+    pub remote_funding_key: PublicKey,
+    pub commit_num: u64,
+}
+
+impl WireType for HsmdSignCommitmentTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.peer_id.write(buf)?;
+        self.channel_dbid.write(buf)?;
+        self.tx.write(buf)?;
+        self.remote_funding_key.write(buf)?;
+        self.commit_num.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let peer_id: PublicKey = r.read()?;
+        let channel_dbid: u64 = r.read()?;
+        let tx: BitcoinTx = r.read()?;
+        let remote_funding_key: PublicKey = r.read()?;
+        let commit_num: u64 = r.read()?;
+        Ok(HsmdSignCommitmentTx {
+            peer_id,
+            channel_dbid,
+            tx,
+            remote_funding_key,
+            commit_num,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignCommitmentTx {
+    const TYPE: u16 = 5;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignCommitmentTxReply {
+    pub sig: EcdsaSig,
+}
+
+impl WireType for HsmdSignCommitmentTxReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig: EcdsaSig = r.read()?;
+        Ok(HsmdSignCommitmentTxReply {
+            sig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignCommitmentTxReply {
+    const TYPE: u16 = 105;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdValidateCommitmentTx {
+    pub tx: BitcoinTx,
+    pub htlcs: Vec<SimpleHtlc>,
+    pub commit_num: u64,
+    pub feerate: u32,
+    pub sig: EcdsaSig,
+    pub htlc_sigs: Vec<EcdsaSig>,
+}
+
+impl WireType for HsmdValidateCommitmentTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.tx.write(buf)?;
+        len::<u16>(self.htlcs.len())?.write(buf)?;
+        for x in &self.htlcs {
+            x.write(buf)?;
+        }
+        self.commit_num.write(buf)?;
+        self.feerate.write(buf)?;
+        self.sig.write(buf)?;
+        len::<u16>(self.htlc_sigs.len())?.write(buf)?;
+        for x in &self.htlc_sigs {
+            x.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let tx: BitcoinTx = r.read()?;
+        let num_htlcs: u16 = r.read()?;
+        let htlcs: Vec<SimpleHtlc> = r.read_n(num_htlcs as usize)?;
+        let commit_num: u64 = r.read()?;
+        let feerate: u32 = r.read()?;
+        let sig: EcdsaSig = r.read()?;
+        let num_htlc_sigs: u16 = r.read()?;
+        let htlc_sigs: Vec<EcdsaSig> = r.read_n(num_htlc_sigs as usize)?;
+        Ok(HsmdValidateCommitmentTx {
+            tx,
+            htlcs,
+            commit_num,
+            feerate,
+            sig,
+            htlc_sigs,
+        })
+    }
+}
+
+impl WireMessage for HsmdValidateCommitmentTx {
+    const TYPE: u16 = 35;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdValidateCommitmentTxReply {
+    pub old_commitment_secret: Option<Secret>,
+    pub next_per_commitment_point: PublicKey,
+}
+
+impl WireType for HsmdValidateCommitmentTxReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.old_commitment_secret.write(buf)?;
+        self.next_per_commitment_point.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let old_commitment_secret: Option<Secret> = r.read()?;
+        let next_per_commitment_point: PublicKey = r.read()?;
+        Ok(HsmdValidateCommitmentTxReply {
+            old_commitment_secret,
+            next_per_commitment_point,
+        })
+    }
+}
+
+impl WireMessage for HsmdValidateCommitmentTxReply {
+    const TYPE: u16 = 135;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdValidateRevocation {
+    pub revoke_num: u64,
+    pub per_commitment_secret: Secret,
+}
+
+impl WireType for HsmdValidateRevocation {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.revoke_num.write(buf)?;
+        self.per_commitment_secret.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let revoke_num: u64 = r.read()?;
+        let per_commitment_secret: Secret = r.read()?;
+        Ok(HsmdValidateRevocation {
+            revoke_num,
+            per_commitment_secret,
+        })
+    }
+}
+
+impl WireMessage for HsmdValidateRevocation {
+    const TYPE: u16 = 36;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdValidateRevocationReply {
+}
+
+impl WireType for HsmdValidateRevocationReply {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(HsmdValidateRevocationReply {})
+    }
+}
+
+impl WireMessage for HsmdValidateRevocationReply {
+    const TYPE: u16 = 136;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignDelayedPaymentToUs {
+    pub commit_num: u64,
+    pub tx: BitcoinTx,
+    pub wscript: Vec<u8>,
+}
+
+impl WireType for HsmdSignDelayedPaymentToUs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.commit_num.write(buf)?;
+        self.tx.write(buf)?;
+        len::<u16>(self.wscript.len())?.write(buf)?;
+        buf.extend_from_slice(&self.wscript);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let commit_num: u64 = r.read()?;
+        let tx: BitcoinTx = r.read()?;
+        let wscript_len: u16 = r.read()?;
+        let wscript: Vec<u8> = r.take(wscript_len as usize)?.to_vec();
+        Ok(HsmdSignDelayedPaymentToUs {
+            commit_num,
+            tx,
+            wscript,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignDelayedPaymentToUs {
+    const TYPE: u16 = 12;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignRemoteHtlcToUs {
+    pub remote_per_commitment_point: PublicKey,
+    pub tx: BitcoinTx,
+    pub wscript: Vec<u8>,
+    pub option_anchor_outputs: bool,
+}
+
+impl WireType for HsmdSignRemoteHtlcToUs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.remote_per_commitment_point.write(buf)?;
+        self.tx.write(buf)?;
+        len::<u16>(self.wscript.len())?.write(buf)?;
+        buf.extend_from_slice(&self.wscript);
+        self.option_anchor_outputs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let remote_per_commitment_point: PublicKey = r.read()?;
+        let tx: BitcoinTx = r.read()?;
+        let wscript_len: u16 = r.read()?;
+        let wscript: Vec<u8> = r.take(wscript_len as usize)?.to_vec();
+        let option_anchor_outputs: bool = r.read()?;
+        Ok(HsmdSignRemoteHtlcToUs {
+            remote_per_commitment_point,
+            tx,
+            wscript,
+            option_anchor_outputs,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignRemoteHtlcToUs {
+    const TYPE: u16 = 13;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignPenaltyToUs {
+    pub revocation_secret: Secret,
+    pub tx: BitcoinTx,
+    pub wscript: Vec<u8>,
+}
+
+impl WireType for HsmdSignPenaltyToUs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.revocation_secret.write(buf)?;
+        self.tx.write(buf)?;
+        len::<u16>(self.wscript.len())?.write(buf)?;
+        buf.extend_from_slice(&self.wscript);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let revocation_secret: Secret = r.read()?;
+        let tx: BitcoinTx = r.read()?;
+        let wscript_len: u16 = r.read()?;
+        let wscript: Vec<u8> = r.take(wscript_len as usize)?.to_vec();
+        Ok(HsmdSignPenaltyToUs {
+            revocation_secret,
+            tx,
+            wscript,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignPenaltyToUs {
+    const TYPE: u16 = 14;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignLocalHtlcTx {
+    pub commit_num: u64,
+    pub tx: BitcoinTx,
+    pub wscript: Vec<u8>,
+    pub option_anchor_outputs: bool,
+}
+
+impl WireType for HsmdSignLocalHtlcTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.commit_num.write(buf)?;
+        self.tx.write(buf)?;
+        len::<u16>(self.wscript.len())?.write(buf)?;
+        buf.extend_from_slice(&self.wscript);
+        self.option_anchor_outputs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let commit_num: u64 = r.read()?;
+        let tx: BitcoinTx = r.read()?;
+        let wscript_len: u16 = r.read()?;
+        let wscript: Vec<u8> = r.take(wscript_len as usize)?.to_vec();
+        let option_anchor_outputs: bool = r.read()?;
+        Ok(HsmdSignLocalHtlcTx {
+            commit_num,
+            tx,
+            wscript,
+            option_anchor_outputs,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignLocalHtlcTx {
+    const TYPE: u16 = 16;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignRemoteCommitmentTx {
+    pub tx: BitcoinTx,
+    pub remote_funding_key: PublicKey,
+    pub remote_per_commit: PublicKey,
+    pub option_static_remotekey: bool,
+    pub commit_num: u64,
+    pub htlcs: Vec<SimpleHtlc>,
+    pub feerate: u32,
+}
+
+impl WireType for HsmdSignRemoteCommitmentTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.tx.write(buf)?;
+        self.remote_funding_key.write(buf)?;
+        self.remote_per_commit.write(buf)?;
+        self.option_static_remotekey.write(buf)?;
+        self.commit_num.write(buf)?;
+        len::<u16>(self.htlcs.len())?.write(buf)?;
+        for x in &self.htlcs {
+            x.write(buf)?;
+        }
+        self.feerate.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let tx: BitcoinTx = r.read()?;
+        let remote_funding_key: PublicKey = r.read()?;
+        let remote_per_commit: PublicKey = r.read()?;
+        let option_static_remotekey: bool = r.read()?;
+        let commit_num: u64 = r.read()?;
+        let num_htlcs: u16 = r.read()?;
+        let htlcs: Vec<SimpleHtlc> = r.read_n(num_htlcs as usize)?;
+        let feerate: u32 = r.read()?;
+        Ok(HsmdSignRemoteCommitmentTx {
+            tx,
+            remote_funding_key,
+            remote_per_commit,
+            option_static_remotekey,
+            commit_num,
+            htlcs,
+            feerate,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignRemoteCommitmentTx {
+    const TYPE: u16 = 19;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignRemoteHtlcTx {
+    pub tx: BitcoinTx,
+    pub wscript: Vec<u8>,
+    pub remote_per_commit_point: PublicKey,
+    pub option_anchor_outputs: bool,
+}
+
+impl WireType for HsmdSignRemoteHtlcTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.tx.write(buf)?;
+        len::<u16>(self.wscript.len())?.write(buf)?;
+        buf.extend_from_slice(&self.wscript);
+        self.remote_per_commit_point.write(buf)?;
+        self.option_anchor_outputs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let tx: BitcoinTx = r.read()?;
+        let len: u16 = r.read()?;
+        let wscript: Vec<u8> = r.take(len as usize)?.to_vec();
+        let remote_per_commit_point: PublicKey = r.read()?;
+        let option_anchor_outputs: bool = r.read()?;
+        Ok(HsmdSignRemoteHtlcTx {
+            tx,
+            wscript,
+            remote_per_commit_point,
+            option_anchor_outputs,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignRemoteHtlcTx {
+    const TYPE: u16 = 20;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignMutualCloseTx {
+    pub tx: BitcoinTx,
+    pub remote_funding_key: PublicKey,
+}
+
+impl WireType for HsmdSignMutualCloseTx {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.tx.write(buf)?;
+        self.remote_funding_key.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let tx: BitcoinTx = r.read()?;
+        let remote_funding_key: PublicKey = r.read()?;
+        Ok(HsmdSignMutualCloseTx {
+            tx,
+            remote_funding_key,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignMutualCloseTx {
+    const TYPE: u16 = 21;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignTxReply {
+    pub sig: EcdsaSig,
+}
+
+impl WireType for HsmdSignTxReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig: EcdsaSig = r.read()?;
+        Ok(HsmdSignTxReply {
+            sig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignTxReply {
+    const TYPE: u16 = 112;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetPerCommitmentPoint {
+    pub n: u64,
+}
+
+impl WireType for HsmdGetPerCommitmentPoint {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.n.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let n: u64 = r.read()?;
+        Ok(HsmdGetPerCommitmentPoint {
+            n,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetPerCommitmentPoint {
+    const TYPE: u16 = 18;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetPerCommitmentPointReply {
+    pub per_commitment_point: PublicKey,
+    pub old_commitment_secret: Option<Secret>,
+}
+
+impl WireType for HsmdGetPerCommitmentPointReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.per_commitment_point.write(buf)?;
+        self.old_commitment_secret.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let per_commitment_point: PublicKey = r.read()?;
+        let old_commitment_secret: Option<Secret> = r.read()?;
+        Ok(HsmdGetPerCommitmentPointReply {
+            per_commitment_point,
+            old_commitment_secret,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetPerCommitmentPointReply {
+    const TYPE: u16 = 118;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdDevMemleak {
+}
+
+impl WireType for HsmdDevMemleak {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(HsmdDevMemleak {})
+    }
+}
+
+impl WireMessage for HsmdDevMemleak {
+    const TYPE: u16 = 33;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdDevMemleakReply {
+    pub leak: bool,
+}
+
+impl WireType for HsmdDevMemleakReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.leak.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let leak: bool = r.read()?;
+        Ok(HsmdDevMemleakReply {
+            leak,
+        })
+    }
+}
+
+impl WireMessage for HsmdDevMemleakReply {
+    const TYPE: u16 = 133;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCheckFutureSecret {
+    pub n: u64,
+    pub commitment_secret: Secret,
+}
+
+impl WireType for HsmdCheckFutureSecret {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.n.write(buf)?;
+        self.commitment_secret.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let n: u64 = r.read()?;
+        let commitment_secret: Secret = r.read()?;
+        Ok(HsmdCheckFutureSecret {
+            n,
+            commitment_secret,
+        })
+    }
+}
+
+impl WireMessage for HsmdCheckFutureSecret {
+    const TYPE: u16 = 22;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdCheckFutureSecretReply {
+    pub correct: bool,
+}
+
+impl WireType for HsmdCheckFutureSecretReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.correct.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let correct: bool = r.read()?;
+        Ok(HsmdCheckFutureSecretReply {
+            correct,
+        })
+    }
+}
+
+impl WireMessage for HsmdCheckFutureSecretReply {
+    const TYPE: u16 = 122;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignMessage {
+    pub msg: Vec<u8>,
+}
+
+impl WireType for HsmdSignMessage {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.msg.len())?.write(buf)?;
+        buf.extend_from_slice(&self.msg);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let msg: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(HsmdSignMessage {
+            msg,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignMessage {
+    const TYPE: u16 = 23;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignMessageReply {
+    pub sig: RecoverableSignature,
+}
+
+impl WireType for HsmdSignMessageReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig: RecoverableSignature = r.read()?;
+        Ok(HsmdSignMessageReply {
+            sig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignMessageReply {
+    const TYPE: u16 = 123;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetOutputScriptpubkey {
+    pub channel_id: u64,
+    pub peer_id: PublicKey,
+    pub commitment_point: Option<PublicKey>,
+}
+
+impl WireType for HsmdGetOutputScriptpubkey {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.peer_id.write(buf)?;
+        self.commitment_point.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: u64 = r.read()?;
+        let peer_id: PublicKey = r.read()?;
+        let commitment_point: Option<PublicKey> = r.read()?;
+        Ok(HsmdGetOutputScriptpubkey {
+            channel_id,
+            peer_id,
+            commitment_point,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetOutputScriptpubkey {
+    const TYPE: u16 = 24;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdGetOutputScriptpubkeyReply {
+    pub script: Vec<u8>,
+}
+
+impl WireType for HsmdGetOutputScriptpubkeyReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.script.len())?.write(buf)?;
+        buf.extend_from_slice(&self.script);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let script_len: u16 = r.read()?;
+        let script: Vec<u8> = r.take(script_len as usize)?.to_vec();
+        Ok(HsmdGetOutputScriptpubkeyReply {
+            script,
+        })
+    }
+}
+
+impl WireMessage for HsmdGetOutputScriptpubkeyReply {
+    const TYPE: u16 = 124;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignBolt12 {
+    pub messagename: String,
+    pub fieldname: String,
+    pub merkleroot: Sha256,
+    pub publictweak: Vec<u8>,
+}
+
+impl WireType for HsmdSignBolt12 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        write_wirestring(buf, &self.messagename)?;
+        write_wirestring(buf, &self.fieldname)?;
+        self.merkleroot.write(buf)?;
+        len::<u16>(self.publictweak.len())?.write(buf)?;
+        buf.extend_from_slice(&self.publictweak);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let messagename: String = r.wirestring()?;
+        let fieldname: String = r.wirestring()?;
+        let merkleroot: Sha256 = r.read()?;
+        let publictweaklen: u16 = r.read()?;
+        let publictweak: Vec<u8> = r.take(publictweaklen as usize)?.to_vec();
+        Ok(HsmdSignBolt12 {
+            messagename,
+            fieldname,
+            merkleroot,
+            publictweak,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignBolt12 {
+    const TYPE: u16 = 25;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignBolt12Reply {
+    pub sig: [u8; 64],
+}
+
+impl WireType for HsmdSignBolt12Reply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sig: [u8; 64] = r.read()?;
+        Ok(HsmdSignBolt12Reply {
+            sig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignBolt12Reply {
+    const TYPE: u16 = 125;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignOptionWillFundOffer {
+    pub funding_pubkey: PublicKey,
+    pub blockheight: u32,
+    pub channel_fee_base_max_msat: u32,
+    pub channel_fee_proportional_basis_max: u16,
+}
+
+impl WireType for HsmdSignOptionWillFundOffer {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.funding_pubkey.write(buf)?;
+        self.blockheight.write(buf)?;
+        self.channel_fee_base_max_msat.write(buf)?;
+        self.channel_fee_proportional_basis_max.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let funding_pubkey: PublicKey = r.read()?;
+        let blockheight: u32 = r.read()?;
+        let channel_fee_base_max_msat: u32 = r.read()?;
+        let channel_fee_proportional_basis_max: u16 = r.read()?;
+        Ok(HsmdSignOptionWillFundOffer {
+            funding_pubkey,
+            blockheight,
+            channel_fee_base_max_msat,
+            channel_fee_proportional_basis_max,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignOptionWillFundOffer {
+    const TYPE: u16 = 26;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdSignOptionWillFundOfferReply {
+    pub rsig: Signature,
+}
+
+impl WireType for HsmdSignOptionWillFundOfferReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.rsig.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let rsig: Signature = r.read()?;
+        Ok(HsmdSignOptionWillFundOfferReply {
+            rsig,
+        })
+    }
+}
+
+impl WireMessage for HsmdSignOptionWillFundOfferReply {
+    const TYPE: u16 = 126;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdDeriveSecret {
+    pub info: Vec<u8>,
+}
+
+impl WireType for HsmdDeriveSecret {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.info.len())?.write(buf)?;
+        buf.extend_from_slice(&self.info);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let info: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(HsmdDeriveSecret {
+            info,
+        })
+    }
+}
+
+impl WireMessage for HsmdDeriveSecret {
+    const TYPE: u16 = 27;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HsmdDeriveSecretReply {
+    pub secret: Secret,
+}
+
+impl WireType for HsmdDeriveSecretReply {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.secret.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let secret: Secret = r.read()?;
+        Ok(HsmdDeriveSecretReply {
+            secret,
+        })
+    }
+}
+
+impl WireMessage for HsmdDeriveSecretReply {
+    const TYPE: u16 = 127;
+}
+
+/// Any of the messages above, or one we don't know about.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HsmdMessage {
+    HsmstatusClientBadRequest(HsmstatusClientBadRequest),
+    HsmdInit(HsmdInit),
+    HsmdInitReplyV1(HsmdInitReplyV1),
+    HsmdInitReplyV2(HsmdInitReplyV2),
+    HsmdNewChannel(HsmdNewChannel),
+    HsmdNewChannelReply(HsmdNewChannelReply),
+    HsmdClientHsmfd(HsmdClientHsmfd),
+    HsmdClientHsmfdReply(HsmdClientHsmfdReply),
+    HsmdGetChannelBasepoints(HsmdGetChannelBasepoints),
+    HsmdGetChannelBasepointsReply(HsmdGetChannelBasepointsReply),
+    HsmdReadyChannel(HsmdReadyChannel),
+    HsmdReadyChannelReply(HsmdReadyChannelReply),
+    HsmdNodeAnnouncementSigReq(HsmdNodeAnnouncementSigReq),
+    HsmdNodeAnnouncementSigReply(HsmdNodeAnnouncementSigReply),
+    HsmdSignWithdrawal(HsmdSignWithdrawal),
+    HsmdSignWithdrawalReply(HsmdSignWithdrawalReply),
+    HsmdSignInvoice(HsmdSignInvoice),
+    HsmdSignInvoiceReply(HsmdSignInvoiceReply),
+    HsmdEcdhReq(HsmdEcdhReq),
+    HsmdEcdhResp(HsmdEcdhResp),
+    HsmdCannouncementSigReq(HsmdCannouncementSigReq),
+    HsmdCannouncementSigReply(HsmdCannouncementSigReply),
+    HsmdCupdateSigReq(HsmdCupdateSigReq),
+    HsmdCupdateSigReply(HsmdCupdateSigReply),
+    HsmdSignCommitmentTx(HsmdSignCommitmentTx),
+    HsmdSignCommitmentTxReply(HsmdSignCommitmentTxReply),
+    HsmdValidateCommitmentTx(HsmdValidateCommitmentTx),
+    HsmdValidateCommitmentTxReply(HsmdValidateCommitmentTxReply),
+    HsmdValidateRevocation(HsmdValidateRevocation),
+    HsmdValidateRevocationReply(HsmdValidateRevocationReply),
+    HsmdSignDelayedPaymentToUs(HsmdSignDelayedPaymentToUs),
+    HsmdSignRemoteHtlcToUs(HsmdSignRemoteHtlcToUs),
+    HsmdSignPenaltyToUs(HsmdSignPenaltyToUs),
+    HsmdSignLocalHtlcTx(HsmdSignLocalHtlcTx),
+    HsmdSignRemoteCommitmentTx(HsmdSignRemoteCommitmentTx),
+    HsmdSignRemoteHtlcTx(HsmdSignRemoteHtlcTx),
+    HsmdSignMutualCloseTx(HsmdSignMutualCloseTx),
+    HsmdSignTxReply(HsmdSignTxReply),
+    HsmdGetPerCommitmentPoint(HsmdGetPerCommitmentPoint),
+    HsmdGetPerCommitmentPointReply(HsmdGetPerCommitmentPointReply),
+    HsmdDevMemleak(HsmdDevMemleak),
+    HsmdDevMemleakReply(HsmdDevMemleakReply),
+    HsmdCheckFutureSecret(HsmdCheckFutureSecret),
+    HsmdCheckFutureSecretReply(HsmdCheckFutureSecretReply),
+    HsmdSignMessage(HsmdSignMessage),
+    HsmdSignMessageReply(HsmdSignMessageReply),
+    HsmdGetOutputScriptpubkey(HsmdGetOutputScriptpubkey),
+    HsmdGetOutputScriptpubkeyReply(HsmdGetOutputScriptpubkeyReply),
+    HsmdSignBolt12(HsmdSignBolt12),
+    HsmdSignBolt12Reply(HsmdSignBolt12Reply),
+    HsmdSignOptionWillFundOffer(HsmdSignOptionWillFundOffer),
+    HsmdSignOptionWillFundOfferReply(HsmdSignOptionWillFundOfferReply),
+    HsmdDeriveSecret(HsmdDeriveSecret),
+    HsmdDeriveSecretReply(HsmdDeriveSecretReply),
+    Unknown { typ: u16, payload: Vec<u8> },
+}
+
+impl HsmdMessage {
+    pub fn typ(&self) -> u16 {
+        match self {
+            HsmdMessage::HsmstatusClientBadRequest(_) => HsmstatusClientBadRequest::TYPE,
+            HsmdMessage::HsmdInit(_) => HsmdInit::TYPE,
+            HsmdMessage::HsmdInitReplyV1(_) => HsmdInitReplyV1::TYPE,
+            HsmdMessage::HsmdInitReplyV2(_) => HsmdInitReplyV2::TYPE,
+            HsmdMessage::HsmdNewChannel(_) => HsmdNewChannel::TYPE,
+            HsmdMessage::HsmdNewChannelReply(_) => HsmdNewChannelReply::TYPE,
+            HsmdMessage::HsmdClientHsmfd(_) => HsmdClientHsmfd::TYPE,
+            HsmdMessage::HsmdClientHsmfdReply(_) => HsmdClientHsmfdReply::TYPE,
+            HsmdMessage::HsmdGetChannelBasepoints(_) => HsmdGetChannelBasepoints::TYPE,
+            HsmdMessage::HsmdGetChannelBasepointsReply(_) => HsmdGetChannelBasepointsReply::TYPE,
+            HsmdMessage::HsmdReadyChannel(_) => HsmdReadyChannel::TYPE,
+            HsmdMessage::HsmdReadyChannelReply(_) => HsmdReadyChannelReply::TYPE,
+            HsmdMessage::HsmdNodeAnnouncementSigReq(_) => HsmdNodeAnnouncementSigReq::TYPE,
+            HsmdMessage::HsmdNodeAnnouncementSigReply(_) => HsmdNodeAnnouncementSigReply::TYPE,
+            HsmdMessage::HsmdSignWithdrawal(_) => HsmdSignWithdrawal::TYPE,
+            HsmdMessage::HsmdSignWithdrawalReply(_) => HsmdSignWithdrawalReply::TYPE,
+            HsmdMessage::HsmdSignInvoice(_) => HsmdSignInvoice::TYPE,
+            HsmdMessage::HsmdSignInvoiceReply(_) => HsmdSignInvoiceReply::TYPE,
+            HsmdMessage::HsmdEcdhReq(_) => HsmdEcdhReq::TYPE,
+            HsmdMessage::HsmdEcdhResp(_) => HsmdEcdhResp::TYPE,
+            HsmdMessage::HsmdCannouncementSigReq(_) => HsmdCannouncementSigReq::TYPE,
+            HsmdMessage::HsmdCannouncementSigReply(_) => HsmdCannouncementSigReply::TYPE,
+            HsmdMessage::HsmdCupdateSigReq(_) => HsmdCupdateSigReq::TYPE,
+            HsmdMessage::HsmdCupdateSigReply(_) => HsmdCupdateSigReply::TYPE,
+            HsmdMessage::HsmdSignCommitmentTx(_) => HsmdSignCommitmentTx::TYPE,
+            HsmdMessage::HsmdSignCommitmentTxReply(_) => HsmdSignCommitmentTxReply::TYPE,
+            HsmdMessage::HsmdValidateCommitmentTx(_) => HsmdValidateCommitmentTx::TYPE,
+            HsmdMessage::HsmdValidateCommitmentTxReply(_) => HsmdValidateCommitmentTxReply::TYPE,
+            HsmdMessage::HsmdValidateRevocation(_) => HsmdValidateRevocation::TYPE,
+            HsmdMessage::HsmdValidateRevocationReply(_) => HsmdValidateRevocationReply::TYPE,
+            HsmdMessage::HsmdSignDelayedPaymentToUs(_) => HsmdSignDelayedPaymentToUs::TYPE,
+            HsmdMessage::HsmdSignRemoteHtlcToUs(_) => HsmdSignRemoteHtlcToUs::TYPE,
+            HsmdMessage::HsmdSignPenaltyToUs(_) => HsmdSignPenaltyToUs::TYPE,
+            HsmdMessage::HsmdSignLocalHtlcTx(_) => HsmdSignLocalHtlcTx::TYPE,
+            HsmdMessage::HsmdSignRemoteCommitmentTx(_) => HsmdSignRemoteCommitmentTx::TYPE,
+            HsmdMessage::HsmdSignRemoteHtlcTx(_) => HsmdSignRemoteHtlcTx::TYPE,
+            HsmdMessage::HsmdSignMutualCloseTx(_) => HsmdSignMutualCloseTx::TYPE,
+            HsmdMessage::HsmdSignTxReply(_) => HsmdSignTxReply::TYPE,
+            HsmdMessage::HsmdGetPerCommitmentPoint(_) => HsmdGetPerCommitmentPoint::TYPE,
+            HsmdMessage::HsmdGetPerCommitmentPointReply(_) => HsmdGetPerCommitmentPointReply::TYPE,
+            HsmdMessage::HsmdDevMemleak(_) => HsmdDevMemleak::TYPE,
+            HsmdMessage::HsmdDevMemleakReply(_) => HsmdDevMemleakReply::TYPE,
+            HsmdMessage::HsmdCheckFutureSecret(_) => HsmdCheckFutureSecret::TYPE,
+            HsmdMessage::HsmdCheckFutureSecretReply(_) => HsmdCheckFutureSecretReply::TYPE,
+            HsmdMessage::HsmdSignMessage(_) => HsmdSignMessage::TYPE,
+            HsmdMessage::HsmdSignMessageReply(_) => HsmdSignMessageReply::TYPE,
+            HsmdMessage::HsmdGetOutputScriptpubkey(_) => HsmdGetOutputScriptpubkey::TYPE,
+            HsmdMessage::HsmdGetOutputScriptpubkeyReply(_) => HsmdGetOutputScriptpubkeyReply::TYPE,
+            HsmdMessage::HsmdSignBolt12(_) => HsmdSignBolt12::TYPE,
+            HsmdMessage::HsmdSignBolt12Reply(_) => HsmdSignBolt12Reply::TYPE,
+            HsmdMessage::HsmdSignOptionWillFundOffer(_) => HsmdSignOptionWillFundOffer::TYPE,
+            HsmdMessage::HsmdSignOptionWillFundOfferReply(_) => HsmdSignOptionWillFundOfferReply::TYPE,
+            HsmdMessage::HsmdDeriveSecret(_) => HsmdDeriveSecret::TYPE,
+            HsmdMessage::HsmdDeriveSecretReply(_) => HsmdDeriveSecretReply::TYPE,
+            HsmdMessage::Unknown { typ, .. } => *typ,
+        }
+    }
+
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        match self {
+            HsmdMessage::HsmstatusClientBadRequest(m) => m.to_wire(),
+            HsmdMessage::HsmdInit(m) => m.to_wire(),
+            HsmdMessage::HsmdInitReplyV1(m) => m.to_wire(),
+            HsmdMessage::HsmdInitReplyV2(m) => m.to_wire(),
+            HsmdMessage::HsmdNewChannel(m) => m.to_wire(),
+            HsmdMessage::HsmdNewChannelReply(m) => m.to_wire(),
+            HsmdMessage::HsmdClientHsmfd(m) => m.to_wire(),
+            HsmdMessage::HsmdClientHsmfdReply(m) => m.to_wire(),
+            HsmdMessage::HsmdGetChannelBasepoints(m) => m.to_wire(),
+            HsmdMessage::HsmdGetChannelBasepointsReply(m) => m.to_wire(),
+            HsmdMessage::HsmdReadyChannel(m) => m.to_wire(),
+            HsmdMessage::HsmdReadyChannelReply(m) => m.to_wire(),
+            HsmdMessage::HsmdNodeAnnouncementSigReq(m) => m.to_wire(),
+            HsmdMessage::HsmdNodeAnnouncementSigReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignWithdrawal(m) => m.to_wire(),
+            HsmdMessage::HsmdSignWithdrawalReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignInvoice(m) => m.to_wire(),
+            HsmdMessage::HsmdSignInvoiceReply(m) => m.to_wire(),
+            HsmdMessage::HsmdEcdhReq(m) => m.to_wire(),
+            HsmdMessage::HsmdEcdhResp(m) => m.to_wire(),
+            HsmdMessage::HsmdCannouncementSigReq(m) => m.to_wire(),
+            HsmdMessage::HsmdCannouncementSigReply(m) => m.to_wire(),
+            HsmdMessage::HsmdCupdateSigReq(m) => m.to_wire(),
+            HsmdMessage::HsmdCupdateSigReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignCommitmentTx(m) => m.to_wire(),
+            HsmdMessage::HsmdSignCommitmentTxReply(m) => m.to_wire(),
+            HsmdMessage::HsmdValidateCommitmentTx(m) => m.to_wire(),
+            HsmdMessage::HsmdValidateCommitmentTxReply(m) => m.to_wire(),
+            HsmdMessage::HsmdValidateRevocation(m) => m.to_wire(),
+            HsmdMessage::HsmdValidateRevocationReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignDelayedPaymentToUs(m) => m.to_wire(),
+            HsmdMessage::HsmdSignRemoteHtlcToUs(m) => m.to_wire(),
+            HsmdMessage::HsmdSignPenaltyToUs(m) => m.to_wire(),
+            HsmdMessage::HsmdSignLocalHtlcTx(m) => m.to_wire(),
+            HsmdMessage::HsmdSignRemoteCommitmentTx(m) => m.to_wire(),
+            HsmdMessage::HsmdSignRemoteHtlcTx(m) => m.to_wire(),
+            HsmdMessage::HsmdSignMutualCloseTx(m) => m.to_wire(),
+            HsmdMessage::HsmdSignTxReply(m) => m.to_wire(),
+            HsmdMessage::HsmdGetPerCommitmentPoint(m) => m.to_wire(),
+            HsmdMessage::HsmdGetPerCommitmentPointReply(m) => m.to_wire(),
+            HsmdMessage::HsmdDevMemleak(m) => m.to_wire(),
+            HsmdMessage::HsmdDevMemleakReply(m) => m.to_wire(),
+            HsmdMessage::HsmdCheckFutureSecret(m) => m.to_wire(),
+            HsmdMessage::HsmdCheckFutureSecretReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignMessage(m) => m.to_wire(),
+            HsmdMessage::HsmdSignMessageReply(m) => m.to_wire(),
+            HsmdMessage::HsmdGetOutputScriptpubkey(m) => m.to_wire(),
+            HsmdMessage::HsmdGetOutputScriptpubkeyReply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignBolt12(m) => m.to_wire(),
+            HsmdMessage::HsmdSignBolt12Reply(m) => m.to_wire(),
+            HsmdMessage::HsmdSignOptionWillFundOffer(m) => m.to_wire(),
+            HsmdMessage::HsmdSignOptionWillFundOfferReply(m) => m.to_wire(),
+            HsmdMessage::HsmdDeriveSecret(m) => m.to_wire(),
+            HsmdMessage::HsmdDeriveSecretReply(m) => m.to_wire(),
+            HsmdMessage::Unknown { typ, payload } => {
+                let mut buf = typ.to_be_bytes().to_vec();
+                buf.extend_from_slice(payload);
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Decode a message. Messages of an unknown type are returned
+    /// as [`Unknown`](Self::Unknown), it's up to the caller to
+    /// reject those with an even type.
+    pub fn from_wire(buf: &[u8]) -> Result<HsmdMessage> {
+        let mut r = Reader::new(buf);
+        let typ: u16 = r.read()?;
+        Ok(match typ {
+            HsmstatusClientBadRequest::TYPE => HsmdMessage::HsmstatusClientBadRequest(r.read()?),
+            HsmdInit::TYPE => HsmdMessage::HsmdInit(r.read()?),
+            HsmdInitReplyV1::TYPE => HsmdMessage::HsmdInitReplyV1(r.read()?),
+            HsmdInitReplyV2::TYPE => HsmdMessage::HsmdInitReplyV2(r.read()?),
+            HsmdNewChannel::TYPE => HsmdMessage::HsmdNewChannel(r.read()?),
+            HsmdNewChannelReply::TYPE => HsmdMessage::HsmdNewChannelReply(r.read()?),
+            HsmdClientHsmfd::TYPE => HsmdMessage::HsmdClientHsmfd(r.read()?),
+            HsmdClientHsmfdReply::TYPE => HsmdMessage::HsmdClientHsmfdReply(r.read()?),
+            HsmdGetChannelBasepoints::TYPE => HsmdMessage::HsmdGetChannelBasepoints(r.read()?),
+            HsmdGetChannelBasepointsReply::TYPE => HsmdMessage::HsmdGetChannelBasepointsReply(r.read()?),
+            HsmdReadyChannel::TYPE => HsmdMessage::HsmdReadyChannel(r.read()?),
+            HsmdReadyChannelReply::TYPE => HsmdMessage::HsmdReadyChannelReply(r.read()?),
+            HsmdNodeAnnouncementSigReq::TYPE => HsmdMessage::HsmdNodeAnnouncementSigReq(r.read()?),
+            HsmdNodeAnnouncementSigReply::TYPE => HsmdMessage::HsmdNodeAnnouncementSigReply(r.read()?),
+            HsmdSignWithdrawal::TYPE => HsmdMessage::HsmdSignWithdrawal(r.read()?),
+            HsmdSignWithdrawalReply::TYPE => HsmdMessage::HsmdSignWithdrawalReply(r.read()?),
+            HsmdSignInvoice::TYPE => HsmdMessage::HsmdSignInvoice(r.read()?),
+            HsmdSignInvoiceReply::TYPE => HsmdMessage::HsmdSignInvoiceReply(r.read()?),
+            HsmdEcdhReq::TYPE => HsmdMessage::HsmdEcdhReq(r.read()?),
+            HsmdEcdhResp::TYPE => HsmdMessage::HsmdEcdhResp(r.read()?),
+            HsmdCannouncementSigReq::TYPE => HsmdMessage::HsmdCannouncementSigReq(r.read()?),
+            HsmdCannouncementSigReply::TYPE => HsmdMessage::HsmdCannouncementSigReply(r.read()?),
+            HsmdCupdateSigReq::TYPE => HsmdMessage::HsmdCupdateSigReq(r.read()?),
+            HsmdCupdateSigReply::TYPE => HsmdMessage::HsmdCupdateSigReply(r.read()?),
+            HsmdSignCommitmentTx::TYPE => HsmdMessage::HsmdSignCommitmentTx(r.read()?),
+            HsmdSignCommitmentTxReply::TYPE => HsmdMessage::HsmdSignCommitmentTxReply(r.read()?),
+            HsmdValidateCommitmentTx::TYPE => HsmdMessage::HsmdValidateCommitmentTx(r.read()?),
+            HsmdValidateCommitmentTxReply::TYPE => HsmdMessage::HsmdValidateCommitmentTxReply(r.read()?),
+            HsmdValidateRevocation::TYPE => HsmdMessage::HsmdValidateRevocation(r.read()?),
+            HsmdValidateRevocationReply::TYPE => HsmdMessage::HsmdValidateRevocationReply(r.read()?),
+            HsmdSignDelayedPaymentToUs::TYPE => HsmdMessage::HsmdSignDelayedPaymentToUs(r.read()?),
+            HsmdSignRemoteHtlcToUs::TYPE => HsmdMessage::HsmdSignRemoteHtlcToUs(r.read()?),
+            HsmdSignPenaltyToUs::TYPE => HsmdMessage::HsmdSignPenaltyToUs(r.read()?),
+            HsmdSignLocalHtlcTx::TYPE => HsmdMessage::HsmdSignLocalHtlcTx(r.read()?),
+            HsmdSignRemoteCommitmentTx::TYPE => HsmdMessage::HsmdSignRemoteCommitmentTx(r.read()?),
+            HsmdSignRemoteHtlcTx::TYPE => HsmdMessage::HsmdSignRemoteHtlcTx(r.read()?),
+            HsmdSignMutualCloseTx::TYPE => HsmdMessage::HsmdSignMutualCloseTx(r.read()?),
+            HsmdSignTxReply::TYPE => HsmdMessage::HsmdSignTxReply(r.read()?),
+            HsmdGetPerCommitmentPoint::TYPE => HsmdMessage::HsmdGetPerCommitmentPoint(r.read()?),
+            HsmdGetPerCommitmentPointReply::TYPE => HsmdMessage::HsmdGetPerCommitmentPointReply(r.read()?),
+            HsmdDevMemleak::TYPE => HsmdMessage::HsmdDevMemleak(r.read()?),
+            HsmdDevMemleakReply::TYPE => HsmdMessage::HsmdDevMemleakReply(r.read()?),
+            HsmdCheckFutureSecret::TYPE => HsmdMessage::HsmdCheckFutureSecret(r.read()?),
+            HsmdCheckFutureSecretReply::TYPE => HsmdMessage::HsmdCheckFutureSecretReply(r.read()?),
+            HsmdSignMessage::TYPE => HsmdMessage::HsmdSignMessage(r.read()?),
+            HsmdSignMessageReply::TYPE => HsmdMessage::HsmdSignMessageReply(r.read()?),
+            HsmdGetOutputScriptpubkey::TYPE => HsmdMessage::HsmdGetOutputScriptpubkey(r.read()?),
+            HsmdGetOutputScriptpubkeyReply::TYPE => HsmdMessage::HsmdGetOutputScriptpubkeyReply(r.read()?),
+            HsmdSignBolt12::TYPE => HsmdMessage::HsmdSignBolt12(r.read()?),
+            HsmdSignBolt12Reply::TYPE => HsmdMessage::HsmdSignBolt12Reply(r.read()?),
+            HsmdSignOptionWillFundOffer::TYPE => HsmdMessage::HsmdSignOptionWillFundOffer(r.read()?),
+            HsmdSignOptionWillFundOfferReply::TYPE => HsmdMessage::HsmdSignOptionWillFundOfferReply(r.read()?),
+            HsmdDeriveSecret::TYPE => HsmdMessage::HsmdDeriveSecret(r.read()?),
+            HsmdDeriveSecretReply::TYPE => HsmdMessage::HsmdDeriveSecretReply(r.read()?),
+            _ => HsmdMessage::Unknown {
+                typ,
+                payload: r.rest().to_vec(),
+            },
+        })
+    }
+}
+
+impl From<HsmstatusClientBadRequest> for HsmdMessage {
+    fn from(m: HsmstatusClientBadRequest) -> Self {
+        HsmdMessage::HsmstatusClientBadRequest(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmstatusClientBadRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmstatusClientBadRequest(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdInit> for HsmdMessage {
+    fn from(m: HsmdInit) -> Self {
+        HsmdMessage::HsmdInit(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdInit {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdInit(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdInitReplyV1> for HsmdMessage {
+    fn from(m: HsmdInitReplyV1) -> Self {
+        HsmdMessage::HsmdInitReplyV1(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdInitReplyV1 {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdInitReplyV1(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdInitReplyV2> for HsmdMessage {
+    fn from(m: HsmdInitReplyV2) -> Self {
+        HsmdMessage::HsmdInitReplyV2(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdInitReplyV2 {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdInitReplyV2(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdNewChannel> for HsmdMessage {
+    fn from(m: HsmdNewChannel) -> Self {
+        HsmdMessage::HsmdNewChannel(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdNewChannel {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdNewChannel(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdNewChannelReply> for HsmdMessage {
+    fn from(m: HsmdNewChannelReply) -> Self {
+        HsmdMessage::HsmdNewChannelReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdNewChannelReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdNewChannelReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdClientHsmfd> for HsmdMessage {
+    fn from(m: HsmdClientHsmfd) -> Self {
+        HsmdMessage::HsmdClientHsmfd(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdClientHsmfd {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdClientHsmfd(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdClientHsmfdReply> for HsmdMessage {
+    fn from(m: HsmdClientHsmfdReply) -> Self {
+        HsmdMessage::HsmdClientHsmfdReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdClientHsmfdReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdClientHsmfdReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetChannelBasepoints> for HsmdMessage {
+    fn from(m: HsmdGetChannelBasepoints) -> Self {
+        HsmdMessage::HsmdGetChannelBasepoints(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetChannelBasepoints {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetChannelBasepoints(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetChannelBasepointsReply> for HsmdMessage {
+    fn from(m: HsmdGetChannelBasepointsReply) -> Self {
+        HsmdMessage::HsmdGetChannelBasepointsReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetChannelBasepointsReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetChannelBasepointsReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdReadyChannel> for HsmdMessage {
+    fn from(m: HsmdReadyChannel) -> Self {
+        HsmdMessage::HsmdReadyChannel(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdReadyChannel {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdReadyChannel(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdReadyChannelReply> for HsmdMessage {
+    fn from(m: HsmdReadyChannelReply) -> Self {
+        HsmdMessage::HsmdReadyChannelReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdReadyChannelReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdReadyChannelReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdNodeAnnouncementSigReq> for HsmdMessage {
+    fn from(m: HsmdNodeAnnouncementSigReq) -> Self {
+        HsmdMessage::HsmdNodeAnnouncementSigReq(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdNodeAnnouncementSigReq {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdNodeAnnouncementSigReq(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdNodeAnnouncementSigReply> for HsmdMessage {
+    fn from(m: HsmdNodeAnnouncementSigReply) -> Self {
+        HsmdMessage::HsmdNodeAnnouncementSigReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdNodeAnnouncementSigReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdNodeAnnouncementSigReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignWithdrawal> for HsmdMessage {
+    fn from(m: HsmdSignWithdrawal) -> Self {
+        HsmdMessage::HsmdSignWithdrawal(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignWithdrawal {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignWithdrawal(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignWithdrawalReply> for HsmdMessage {
+    fn from(m: HsmdSignWithdrawalReply) -> Self {
+        HsmdMessage::HsmdSignWithdrawalReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignWithdrawalReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignWithdrawalReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignInvoice> for HsmdMessage {
+    fn from(m: HsmdSignInvoice) -> Self {
+        HsmdMessage::HsmdSignInvoice(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignInvoice {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignInvoice(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignInvoiceReply> for HsmdMessage {
+    fn from(m: HsmdSignInvoiceReply) -> Self {
+        HsmdMessage::HsmdSignInvoiceReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignInvoiceReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignInvoiceReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdEcdhReq> for HsmdMessage {
+    fn from(m: HsmdEcdhReq) -> Self {
+        HsmdMessage::HsmdEcdhReq(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdEcdhReq {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdEcdhReq(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdEcdhResp> for HsmdMessage {
+    fn from(m: HsmdEcdhResp) -> Self {
+        HsmdMessage::HsmdEcdhResp(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdEcdhResp {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdEcdhResp(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCannouncementSigReq> for HsmdMessage {
+    fn from(m: HsmdCannouncementSigReq) -> Self {
+        HsmdMessage::HsmdCannouncementSigReq(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCannouncementSigReq {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCannouncementSigReq(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCannouncementSigReply> for HsmdMessage {
+    fn from(m: HsmdCannouncementSigReply) -> Self {
+        HsmdMessage::HsmdCannouncementSigReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCannouncementSigReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCannouncementSigReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCupdateSigReq> for HsmdMessage {
+    fn from(m: HsmdCupdateSigReq) -> Self {
+        HsmdMessage::HsmdCupdateSigReq(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCupdateSigReq {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCupdateSigReq(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCupdateSigReply> for HsmdMessage {
+    fn from(m: HsmdCupdateSigReply) -> Self {
+        HsmdMessage::HsmdCupdateSigReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCupdateSigReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCupdateSigReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignCommitmentTx> for HsmdMessage {
+    fn from(m: HsmdSignCommitmentTx) -> Self {
+        HsmdMessage::HsmdSignCommitmentTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignCommitmentTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignCommitmentTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignCommitmentTxReply> for HsmdMessage {
+    fn from(m: HsmdSignCommitmentTxReply) -> Self {
+        HsmdMessage::HsmdSignCommitmentTxReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignCommitmentTxReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignCommitmentTxReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdValidateCommitmentTx> for HsmdMessage {
+    fn from(m: HsmdValidateCommitmentTx) -> Self {
+        HsmdMessage::HsmdValidateCommitmentTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdValidateCommitmentTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdValidateCommitmentTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdValidateCommitmentTxReply> for HsmdMessage {
+    fn from(m: HsmdValidateCommitmentTxReply) -> Self {
+        HsmdMessage::HsmdValidateCommitmentTxReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdValidateCommitmentTxReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdValidateCommitmentTxReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdValidateRevocation> for HsmdMessage {
+    fn from(m: HsmdValidateRevocation) -> Self {
+        HsmdMessage::HsmdValidateRevocation(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdValidateRevocation {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdValidateRevocation(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdValidateRevocationReply> for HsmdMessage {
+    fn from(m: HsmdValidateRevocationReply) -> Self {
+        HsmdMessage::HsmdValidateRevocationReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdValidateRevocationReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdValidateRevocationReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignDelayedPaymentToUs> for HsmdMessage {
+    fn from(m: HsmdSignDelayedPaymentToUs) -> Self {
+        HsmdMessage::HsmdSignDelayedPaymentToUs(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignDelayedPaymentToUs {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignDelayedPaymentToUs(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignRemoteHtlcToUs> for HsmdMessage {
+    fn from(m: HsmdSignRemoteHtlcToUs) -> Self {
+        HsmdMessage::HsmdSignRemoteHtlcToUs(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignRemoteHtlcToUs {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignRemoteHtlcToUs(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignPenaltyToUs> for HsmdMessage {
+    fn from(m: HsmdSignPenaltyToUs) -> Self {
+        HsmdMessage::HsmdSignPenaltyToUs(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignPenaltyToUs {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignPenaltyToUs(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignLocalHtlcTx> for HsmdMessage {
+    fn from(m: HsmdSignLocalHtlcTx) -> Self {
+        HsmdMessage::HsmdSignLocalHtlcTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignLocalHtlcTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignLocalHtlcTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignRemoteCommitmentTx> for HsmdMessage {
+    fn from(m: HsmdSignRemoteCommitmentTx) -> Self {
+        HsmdMessage::HsmdSignRemoteCommitmentTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignRemoteCommitmentTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignRemoteCommitmentTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignRemoteHtlcTx> for HsmdMessage {
+    fn from(m: HsmdSignRemoteHtlcTx) -> Self {
+        HsmdMessage::HsmdSignRemoteHtlcTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignRemoteHtlcTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignRemoteHtlcTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignMutualCloseTx> for HsmdMessage {
+    fn from(m: HsmdSignMutualCloseTx) -> Self {
+        HsmdMessage::HsmdSignMutualCloseTx(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignMutualCloseTx {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignMutualCloseTx(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignTxReply> for HsmdMessage {
+    fn from(m: HsmdSignTxReply) -> Self {
+        HsmdMessage::HsmdSignTxReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignTxReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignTxReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetPerCommitmentPoint> for HsmdMessage {
+    fn from(m: HsmdGetPerCommitmentPoint) -> Self {
+        HsmdMessage::HsmdGetPerCommitmentPoint(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetPerCommitmentPoint {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetPerCommitmentPoint(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetPerCommitmentPointReply> for HsmdMessage {
+    fn from(m: HsmdGetPerCommitmentPointReply) -> Self {
+        HsmdMessage::HsmdGetPerCommitmentPointReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetPerCommitmentPointReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetPerCommitmentPointReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdDevMemleak> for HsmdMessage {
+    fn from(m: HsmdDevMemleak) -> Self {
+        HsmdMessage::HsmdDevMemleak(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdDevMemleak {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdDevMemleak(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdDevMemleakReply> for HsmdMessage {
+    fn from(m: HsmdDevMemleakReply) -> Self {
+        HsmdMessage::HsmdDevMemleakReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdDevMemleakReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdDevMemleakReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCheckFutureSecret> for HsmdMessage {
+    fn from(m: HsmdCheckFutureSecret) -> Self {
+        HsmdMessage::HsmdCheckFutureSecret(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCheckFutureSecret {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCheckFutureSecret(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdCheckFutureSecretReply> for HsmdMessage {
+    fn from(m: HsmdCheckFutureSecretReply) -> Self {
+        HsmdMessage::HsmdCheckFutureSecretReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdCheckFutureSecretReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdCheckFutureSecretReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignMessage> for HsmdMessage {
+    fn from(m: HsmdSignMessage) -> Self {
+        HsmdMessage::HsmdSignMessage(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignMessage(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignMessageReply> for HsmdMessage {
+    fn from(m: HsmdSignMessageReply) -> Self {
+        HsmdMessage::HsmdSignMessageReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignMessageReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignMessageReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetOutputScriptpubkey> for HsmdMessage {
+    fn from(m: HsmdGetOutputScriptpubkey) -> Self {
+        HsmdMessage::HsmdGetOutputScriptpubkey(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetOutputScriptpubkey {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetOutputScriptpubkey(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdGetOutputScriptpubkeyReply> for HsmdMessage {
+    fn from(m: HsmdGetOutputScriptpubkeyReply) -> Self {
+        HsmdMessage::HsmdGetOutputScriptpubkeyReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdGetOutputScriptpubkeyReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdGetOutputScriptpubkeyReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignBolt12> for HsmdMessage {
+    fn from(m: HsmdSignBolt12) -> Self {
+        HsmdMessage::HsmdSignBolt12(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignBolt12 {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignBolt12(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignBolt12Reply> for HsmdMessage {
+    fn from(m: HsmdSignBolt12Reply) -> Self {
+        HsmdMessage::HsmdSignBolt12Reply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignBolt12Reply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignBolt12Reply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignOptionWillFundOffer> for HsmdMessage {
+    fn from(m: HsmdSignOptionWillFundOffer) -> Self {
+        HsmdMessage::HsmdSignOptionWillFundOffer(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignOptionWillFundOffer {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignOptionWillFundOffer(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdSignOptionWillFundOfferReply> for HsmdMessage {
+    fn from(m: HsmdSignOptionWillFundOfferReply) -> Self {
+        HsmdMessage::HsmdSignOptionWillFundOfferReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdSignOptionWillFundOfferReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdSignOptionWillFundOfferReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdDeriveSecret> for HsmdMessage {
+    fn from(m: HsmdDeriveSecret) -> Self {
+        HsmdMessage::HsmdDeriveSecret(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdDeriveSecret {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdDeriveSecret(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<HsmdDeriveSecretReply> for HsmdMessage {
+    fn from(m: HsmdDeriveSecretReply) -> Self {
+        HsmdMessage::HsmdDeriveSecretReply(m)
+    }
+}
+
+impl TryFrom<HsmdMessage> for HsmdDeriveSecretReply {
+    type Error = anyhow::Error;
+
+    fn try_from(m: HsmdMessage) -> Result<Self> {
+        match m {
+            HsmdMessage::HsmdDeriveSecretReply(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
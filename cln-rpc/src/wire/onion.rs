@@ -0,0 +1,1429 @@
+//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! contrib/msggen/msggen/__main__.py
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `wire/onion_wire.csv`
+//! which this file was generated from
+
+use anyhow::{anyhow, Result};
+use crate::primitives::Amount;
+use crate::primitives::PublicKey;
+use crate::primitives::Sha256;
+use crate::primitives::ShortChannelId;
+use crate::primitives::TlvEntry;
+use crate::primitives::TlvStream;
+use crate::primitives::TlvValue;
+use crate::wire::{BADONION, NODE, PERM, Reader, UPDATE, WireMessage, WireType, len, read_tlv, unknown_stream, unknown_tlv, write_bigsize, write_tlv};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlindedPath {
+    pub first_node_id: PublicKey,
+    pub blinding: PublicKey,
+    pub path: Vec<OnionmsgHop>,
+}
+
+impl WireType for BlindedPath {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.first_node_id.write(buf)?;
+        self.blinding.write(buf)?;
+        len::<u8>(self.path.len())?.write(buf)?;
+        for x in &self.path {
+            x.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let first_node_id: PublicKey = r.read()?;
+        let blinding: PublicKey = r.read()?;
+        let num_hops: u8 = r.read()?;
+        let path: Vec<OnionmsgHop> = r.read_n(num_hops as usize)?;
+        Ok(BlindedPath {
+            first_node_id,
+            blinding,
+            path,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnionmsgHop {
+    pub blinded_node_id: PublicKey,
+    pub encrypted_recipient_data: Vec<u8>,
+}
+
+impl WireType for OnionmsgHop {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.blinded_node_id.write(buf)?;
+        len::<u16>(self.encrypted_recipient_data.len())?.write(buf)?;
+        buf.extend_from_slice(&self.encrypted_recipient_data);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let blinded_node_id: PublicKey = r.read()?;
+        let enclen: u16 = r.read()?;
+        let encrypted_recipient_data: Vec<u8> = r.take(enclen as usize)?.to_vec();
+        Ok(OnionmsgHop {
+            blinded_node_id,
+            encrypted_recipient_data,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlvPayloadPaymentData {
+    pub payment_secret: [u8; 32],
+    pub total_msat: Amount,
+}
+
+impl WireType for TlvPayloadPaymentData {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.payment_secret.write(buf)?;
+        buf.extend(self.total_msat.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let payment_secret: [u8; 32] = r.read()?;
+        let total_msat: Amount = r.truncated()?;
+        Ok(TlvPayloadPaymentData {
+            payment_secret,
+            total_msat,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlvPayload {
+    pub amt_to_forward: Option<Amount>,
+    pub outgoing_cltv_value: Option<u32>,
+    pub short_channel_id: Option<ShortChannelId>,
+    pub payment_data: Option<TlvPayloadPaymentData>,
+    pub payment_metadata: Option<Vec<u8>>,
+    pub encrypted_recipient_data: Option<Vec<u8>>,
+    pub blinding_point: Option<PublicKey>,
+    pub total_amount_msat: Option<Amount>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for TlvPayload {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.amt_to_forward {
+            write_tlv(&mut stream, 2, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.outgoing_cltv_value {
+            write_tlv(&mut stream, 4, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.short_channel_id {
+            write_tlv(&mut stream, 6, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.payment_data {
+            write_tlv(&mut stream, 8, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.payment_metadata {
+            write_tlv(&mut stream, 16, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.encrypted_recipient_data {
+            write_tlv(&mut stream, 10, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.blinding_point {
+            write_tlv(&mut stream, 12, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.total_amount_msat {
+            write_tlv(&mut stream, 18, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = TlvPayload::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                2 => res.amt_to_forward = Some(read_tlv(&e, |r| r.truncated())?),
+                4 => res.outgoing_cltv_value = Some(read_tlv(&e, |r| r.truncated())?),
+                6 => res.short_channel_id = Some(read_tlv(&e, |r| r.read())?),
+                8 => res.payment_data = Some(read_tlv(&e, TlvPayloadPaymentData::read)?),
+                16 => res.payment_metadata = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                10 => res.encrypted_recipient_data = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                12 => res.blinding_point = Some(read_tlv(&e, |r| r.read())?),
+                18 => res.total_amount_msat = Some(read_tlv(&e, |r| r.truncated())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedDataTlvPaymentRelay {
+    pub cltv_expiry_delta: u16,
+    pub fee_proportional_millionths: u32,
+    pub fee_base_msat: u32,
+}
+
+impl WireType for EncryptedDataTlvPaymentRelay {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.cltv_expiry_delta.write(buf)?;
+        self.fee_proportional_millionths.write(buf)?;
+        buf.extend(self.fee_base_msat.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let cltv_expiry_delta: u16 = r.read()?;
+        let fee_proportional_millionths: u32 = r.read()?;
+        let fee_base_msat: u32 = r.truncated()?;
+        Ok(EncryptedDataTlvPaymentRelay {
+            cltv_expiry_delta,
+            fee_proportional_millionths,
+            fee_base_msat,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedDataTlvPaymentConstraints {
+    pub max_cltv_expiry: u32,
+    pub htlc_minimum_msat: Amount,
+}
+
+impl WireType for EncryptedDataTlvPaymentConstraints {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.max_cltv_expiry.write(buf)?;
+        buf.extend(self.htlc_minimum_msat.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let max_cltv_expiry: u32 = r.read()?;
+        let htlc_minimum_msat: Amount = r.truncated()?;
+        Ok(EncryptedDataTlvPaymentConstraints {
+            max_cltv_expiry,
+            htlc_minimum_msat,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncryptedDataTlv {
+    pub padding: Option<Vec<u8>>,
+    pub short_channel_id: Option<ShortChannelId>,
+    pub next_node_id: Option<PublicKey>,
+    pub path_id: Option<Vec<u8>>,
+    pub next_blinding_override: Option<PublicKey>,
+    pub payment_relay: Option<EncryptedDataTlvPaymentRelay>,
+    pub payment_constraints: Option<EncryptedDataTlvPaymentConstraints>,
+    pub allowed_features: Option<Vec<u8>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for EncryptedDataTlv {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.padding {
+            write_tlv(&mut stream, 1, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.short_channel_id {
+            write_tlv(&mut stream, 2, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.next_node_id {
+            write_tlv(&mut stream, 4, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.path_id {
+            write_tlv(&mut stream, 6, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.next_blinding_override {
+            write_tlv(&mut stream, 8, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.payment_relay {
+            write_tlv(&mut stream, 10, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.payment_constraints {
+            write_tlv(&mut stream, 12, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.allowed_features {
+            write_tlv(&mut stream, 14, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = EncryptedDataTlv::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.padding = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                2 => res.short_channel_id = Some(read_tlv(&e, |r| r.read())?),
+                4 => res.next_node_id = Some(read_tlv(&e, |r| r.read())?),
+                6 => res.path_id = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                8 => res.next_blinding_override = Some(read_tlv(&e, |r| r.read())?),
+                10 => res.payment_relay = Some(read_tlv(&e, EncryptedDataTlvPaymentRelay::read)?),
+                12 => res.payment_constraints = Some(read_tlv(&e, EncryptedDataTlvPaymentConstraints::read)?),
+                14 => res.allowed_features = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OnionmsgTlv {
+    pub reply_path: Option<BlindedPath>,
+    pub encrypted_recipient_data: Option<Vec<u8>>,
+    pub invoice_request: Option<Vec<u8>>,
+    pub invoice: Option<Vec<u8>>,
+    pub invoice_error: Option<Vec<u8>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for OnionmsgTlv {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.reply_path {
+            write_tlv(&mut stream, 2, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.encrypted_recipient_data {
+            write_tlv(&mut stream, 4, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_request {
+            write_tlv(&mut stream, 64, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice {
+            write_tlv(&mut stream, 66, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.invoice_error {
+            write_tlv(&mut stream, 68, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = OnionmsgTlv::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                2 => res.reply_path = Some(read_tlv(&e, |r| r.read())?),
+                4 => res.encrypted_recipient_data = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                64 => res.invoice_request = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                66 => res.invoice = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                68 => res.invoice_error = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidRealm {
+}
+
+impl WireType for InvalidRealm {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(InvalidRealm {})
+    }
+}
+
+impl WireMessage for InvalidRealm {
+    const TYPE: u16 = PERM | 1;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemporaryNodeFailure {
+}
+
+impl WireType for TemporaryNodeFailure {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(TemporaryNodeFailure {})
+    }
+}
+
+impl WireMessage for TemporaryNodeFailure {
+    const TYPE: u16 = NODE | 2;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermanentNodeFailure {
+}
+
+impl WireType for PermanentNodeFailure {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(PermanentNodeFailure {})
+    }
+}
+
+impl WireMessage for PermanentNodeFailure {
+    const TYPE: u16 = PERM | NODE | 2;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequiredNodeFeatureMissing {
+}
+
+impl WireType for RequiredNodeFeatureMissing {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(RequiredNodeFeatureMissing {})
+    }
+}
+
+impl WireMessage for RequiredNodeFeatureMissing {
+    const TYPE: u16 = PERM | NODE | 3;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOnionVersion {
+    pub sha256_of_onion: Sha256,
+}
+
+impl WireType for InvalidOnionVersion {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sha256_of_onion.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sha256_of_onion: Sha256 = r.read()?;
+        Ok(InvalidOnionVersion {
+            sha256_of_onion,
+        })
+    }
+}
+
+impl WireMessage for InvalidOnionVersion {
+    const TYPE: u16 = BADONION | PERM | 4;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOnionHmac {
+    pub sha256_of_onion: Sha256,
+}
+
+impl WireType for InvalidOnionHmac {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sha256_of_onion.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sha256_of_onion: Sha256 = r.read()?;
+        Ok(InvalidOnionHmac {
+            sha256_of_onion,
+        })
+    }
+}
+
+impl WireMessage for InvalidOnionHmac {
+    const TYPE: u16 = BADONION | PERM | 5;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOnionKey {
+    pub sha256_of_onion: Sha256,
+}
+
+impl WireType for InvalidOnionKey {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sha256_of_onion.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sha256_of_onion: Sha256 = r.read()?;
+        Ok(InvalidOnionKey {
+            sha256_of_onion,
+        })
+    }
+}
+
+impl WireMessage for InvalidOnionKey {
+    const TYPE: u16 = BADONION | PERM | 6;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemporaryChannelFailure {
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for TemporaryChannelFailure {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(TemporaryChannelFailure {
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for TemporaryChannelFailure {
+    const TYPE: u16 = UPDATE | 7;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermanentChannelFailure {
+}
+
+impl WireType for PermanentChannelFailure {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(PermanentChannelFailure {})
+    }
+}
+
+impl WireMessage for PermanentChannelFailure {
+    const TYPE: u16 = PERM | 8;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequiredChannelFeatureMissing {
+}
+
+impl WireType for RequiredChannelFeatureMissing {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(RequiredChannelFeatureMissing {})
+    }
+}
+
+impl WireMessage for RequiredChannelFeatureMissing {
+    const TYPE: u16 = PERM | 9;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownNextPeer {
+}
+
+impl WireType for UnknownNextPeer {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(UnknownNextPeer {})
+    }
+}
+
+impl WireMessage for UnknownNextPeer {
+    const TYPE: u16 = PERM | 10;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmountBelowMinimum {
+    pub htlc_msat: Amount,
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for AmountBelowMinimum {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.htlc_msat.write(buf)?;
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let htlc_msat: Amount = r.read()?;
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(AmountBelowMinimum {
+            htlc_msat,
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for AmountBelowMinimum {
+    const TYPE: u16 = UPDATE | 11;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeInsufficient {
+    pub htlc_msat: Amount,
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for FeeInsufficient {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.htlc_msat.write(buf)?;
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let htlc_msat: Amount = r.read()?;
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(FeeInsufficient {
+            htlc_msat,
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for FeeInsufficient {
+    const TYPE: u16 = UPDATE | 12;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncorrectCltvExpiry {
+    pub cltv_expiry: u32,
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for IncorrectCltvExpiry {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.cltv_expiry.write(buf)?;
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let cltv_expiry: u32 = r.read()?;
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(IncorrectCltvExpiry {
+            cltv_expiry,
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for IncorrectCltvExpiry {
+    const TYPE: u16 = UPDATE | 13;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpiryTooSoon {
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for ExpiryTooSoon {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(ExpiryTooSoon {
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for ExpiryTooSoon {
+    const TYPE: u16 = UPDATE | 14;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncorrectOrUnknownPaymentDetails {
+    pub htlc_msat: Amount,
+    pub height: u32,
+}
+
+impl WireType for IncorrectOrUnknownPaymentDetails {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.htlc_msat.write(buf)?;
+        self.height.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let htlc_msat: Amount = r.read()?;
+        let height: u32 = r.read()?;
+        Ok(IncorrectOrUnknownPaymentDetails {
+            htlc_msat,
+            height,
+        })
+    }
+}
+
+impl WireMessage for IncorrectOrUnknownPaymentDetails {
+    const TYPE: u16 = PERM | 15;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FinalIncorrectCltvExpiry {
+    pub cltv_expiry: u32,
+}
+
+impl WireType for FinalIncorrectCltvExpiry {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.cltv_expiry.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let cltv_expiry: u32 = r.read()?;
+        Ok(FinalIncorrectCltvExpiry {
+            cltv_expiry,
+        })
+    }
+}
+
+impl WireMessage for FinalIncorrectCltvExpiry {
+    const TYPE: u16 = 18;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FinalIncorrectHtlcAmount {
+    pub incoming_htlc_amt: Amount,
+}
+
+impl WireType for FinalIncorrectHtlcAmount {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.incoming_htlc_amt.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let incoming_htlc_amt: Amount = r.read()?;
+        Ok(FinalIncorrectHtlcAmount {
+            incoming_htlc_amt,
+        })
+    }
+}
+
+impl WireMessage for FinalIncorrectHtlcAmount {
+    const TYPE: u16 = 19;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelDisabled {
+    pub disabled_flags: u16,
+    pub channel_update: Vec<u8>,
+}
+
+impl WireType for ChannelDisabled {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.disabled_flags.write(buf)?;
+        len::<u16>(self.channel_update.len())?.write(buf)?;
+        buf.extend_from_slice(&self.channel_update);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let disabled_flags: u16 = r.read()?;
+        let len: u16 = r.read()?;
+        let channel_update: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(ChannelDisabled {
+            disabled_flags,
+            channel_update,
+        })
+    }
+}
+
+impl WireMessage for ChannelDisabled {
+    const TYPE: u16 = UPDATE | 20;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpiryTooFar {
+}
+
+impl WireType for ExpiryTooFar {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(ExpiryTooFar {})
+    }
+}
+
+impl WireMessage for ExpiryTooFar {
+    const TYPE: u16 = 21;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOnionPayload {
+    pub typ: u64,
+    pub offset: u16,
+}
+
+impl WireType for InvalidOnionPayload {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        write_bigsize(buf, self.typ);
+        self.offset.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let typ: u64 = r.bigsize()?;
+        let offset: u16 = r.read()?;
+        Ok(InvalidOnionPayload {
+            typ,
+            offset,
+        })
+    }
+}
+
+impl WireMessage for InvalidOnionPayload {
+    const TYPE: u16 = PERM | 22;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MppTimeout {
+}
+
+impl WireType for MppTimeout {
+    fn write(&self, _buf: &mut Vec<u8>) -> Result<()> {
+        Ok(())
+    }
+
+    fn read(_r: &mut Reader<'_>) -> Result<Self> {
+        Ok(MppTimeout {})
+    }
+}
+
+impl WireMessage for MppTimeout {
+    const TYPE: u16 = 23;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidOnionBlinding {
+    pub sha256_of_onion: Sha256,
+}
+
+impl WireType for InvalidOnionBlinding {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.sha256_of_onion.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let sha256_of_onion: Sha256 = r.read()?;
+        Ok(InvalidOnionBlinding {
+            sha256_of_onion,
+        })
+    }
+}
+
+impl WireMessage for InvalidOnionBlinding {
+    const TYPE: u16 = BADONION | PERM | 24;
+}
+
+/// Any of the messages above, or one we don't know about.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OnionFailure {
+    InvalidRealm(InvalidRealm),
+    TemporaryNodeFailure(TemporaryNodeFailure),
+    PermanentNodeFailure(PermanentNodeFailure),
+    RequiredNodeFeatureMissing(RequiredNodeFeatureMissing),
+    InvalidOnionVersion(InvalidOnionVersion),
+    InvalidOnionHmac(InvalidOnionHmac),
+    InvalidOnionKey(InvalidOnionKey),
+    TemporaryChannelFailure(TemporaryChannelFailure),
+    PermanentChannelFailure(PermanentChannelFailure),
+    RequiredChannelFeatureMissing(RequiredChannelFeatureMissing),
+    UnknownNextPeer(UnknownNextPeer),
+    AmountBelowMinimum(AmountBelowMinimum),
+    FeeInsufficient(FeeInsufficient),
+    IncorrectCltvExpiry(IncorrectCltvExpiry),
+    ExpiryTooSoon(ExpiryTooSoon),
+    IncorrectOrUnknownPaymentDetails(IncorrectOrUnknownPaymentDetails),
+    FinalIncorrectCltvExpiry(FinalIncorrectCltvExpiry),
+    FinalIncorrectHtlcAmount(FinalIncorrectHtlcAmount),
+    ChannelDisabled(ChannelDisabled),
+    ExpiryTooFar(ExpiryTooFar),
+    InvalidOnionPayload(InvalidOnionPayload),
+    MppTimeout(MppTimeout),
+    InvalidOnionBlinding(InvalidOnionBlinding),
+    Unknown { typ: u16, payload: Vec<u8> },
+}
+
+impl OnionFailure {
+    pub fn typ(&self) -> u16 {
+        match self {
+            OnionFailure::InvalidRealm(_) => InvalidRealm::TYPE,
+            OnionFailure::TemporaryNodeFailure(_) => TemporaryNodeFailure::TYPE,
+            OnionFailure::PermanentNodeFailure(_) => PermanentNodeFailure::TYPE,
+            OnionFailure::RequiredNodeFeatureMissing(_) => RequiredNodeFeatureMissing::TYPE,
+            OnionFailure::InvalidOnionVersion(_) => InvalidOnionVersion::TYPE,
+            OnionFailure::InvalidOnionHmac(_) => InvalidOnionHmac::TYPE,
+            OnionFailure::InvalidOnionKey(_) => InvalidOnionKey::TYPE,
+            OnionFailure::TemporaryChannelFailure(_) => TemporaryChannelFailure::TYPE,
+            OnionFailure::PermanentChannelFailure(_) => PermanentChannelFailure::TYPE,
+            OnionFailure::RequiredChannelFeatureMissing(_) => RequiredChannelFeatureMissing::TYPE,
+            OnionFailure::UnknownNextPeer(_) => UnknownNextPeer::TYPE,
+            OnionFailure::AmountBelowMinimum(_) => AmountBelowMinimum::TYPE,
+            OnionFailure::FeeInsufficient(_) => FeeInsufficient::TYPE,
+            OnionFailure::IncorrectCltvExpiry(_) => IncorrectCltvExpiry::TYPE,
+            OnionFailure::ExpiryTooSoon(_) => ExpiryTooSoon::TYPE,
+            OnionFailure::IncorrectOrUnknownPaymentDetails(_) => IncorrectOrUnknownPaymentDetails::TYPE,
+            OnionFailure::FinalIncorrectCltvExpiry(_) => FinalIncorrectCltvExpiry::TYPE,
+            OnionFailure::FinalIncorrectHtlcAmount(_) => FinalIncorrectHtlcAmount::TYPE,
+            OnionFailure::ChannelDisabled(_) => ChannelDisabled::TYPE,
+            OnionFailure::ExpiryTooFar(_) => ExpiryTooFar::TYPE,
+            OnionFailure::InvalidOnionPayload(_) => InvalidOnionPayload::TYPE,
+            OnionFailure::MppTimeout(_) => MppTimeout::TYPE,
+            OnionFailure::InvalidOnionBlinding(_) => InvalidOnionBlinding::TYPE,
+            OnionFailure::Unknown { typ, .. } => *typ,
+        }
+    }
+
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        match self {
+            OnionFailure::InvalidRealm(m) => m.to_wire(),
+            OnionFailure::TemporaryNodeFailure(m) => m.to_wire(),
+            OnionFailure::PermanentNodeFailure(m) => m.to_wire(),
+            OnionFailure::RequiredNodeFeatureMissing(m) => m.to_wire(),
+            OnionFailure::InvalidOnionVersion(m) => m.to_wire(),
+            OnionFailure::InvalidOnionHmac(m) => m.to_wire(),
+            OnionFailure::InvalidOnionKey(m) => m.to_wire(),
+            OnionFailure::TemporaryChannelFailure(m) => m.to_wire(),
+            OnionFailure::PermanentChannelFailure(m) => m.to_wire(),
+            OnionFailure::RequiredChannelFeatureMissing(m) => m.to_wire(),
+            OnionFailure::UnknownNextPeer(m) => m.to_wire(),
+            OnionFailure::AmountBelowMinimum(m) => m.to_wire(),
+            OnionFailure::FeeInsufficient(m) => m.to_wire(),
+            OnionFailure::IncorrectCltvExpiry(m) => m.to_wire(),
+            OnionFailure::ExpiryTooSoon(m) => m.to_wire(),
+            OnionFailure::IncorrectOrUnknownPaymentDetails(m) => m.to_wire(),
+            OnionFailure::FinalIncorrectCltvExpiry(m) => m.to_wire(),
+            OnionFailure::FinalIncorrectHtlcAmount(m) => m.to_wire(),
+            OnionFailure::ChannelDisabled(m) => m.to_wire(),
+            OnionFailure::ExpiryTooFar(m) => m.to_wire(),
+            OnionFailure::InvalidOnionPayload(m) => m.to_wire(),
+            OnionFailure::MppTimeout(m) => m.to_wire(),
+            OnionFailure::InvalidOnionBlinding(m) => m.to_wire(),
+            OnionFailure::Unknown { typ, payload } => {
+                let mut buf = typ.to_be_bytes().to_vec();
+                buf.extend_from_slice(payload);
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Decode a message. Messages of an unknown type are returned
+    /// as [`Unknown`](Self::Unknown), it's up to the caller to
+    /// reject those with an even type.
+    pub fn from_wire(buf: &[u8]) -> Result<OnionFailure> {
+        let mut r = Reader::new(buf);
+        let typ: u16 = r.read()?;
+        Ok(match typ {
+            InvalidRealm::TYPE => OnionFailure::InvalidRealm(r.read()?),
+            TemporaryNodeFailure::TYPE => OnionFailure::TemporaryNodeFailure(r.read()?),
+            PermanentNodeFailure::TYPE => OnionFailure::PermanentNodeFailure(r.read()?),
+            RequiredNodeFeatureMissing::TYPE => OnionFailure::RequiredNodeFeatureMissing(r.read()?),
+            InvalidOnionVersion::TYPE => OnionFailure::InvalidOnionVersion(r.read()?),
+            InvalidOnionHmac::TYPE => OnionFailure::InvalidOnionHmac(r.read()?),
+            InvalidOnionKey::TYPE => OnionFailure::InvalidOnionKey(r.read()?),
+            TemporaryChannelFailure::TYPE => OnionFailure::TemporaryChannelFailure(r.read()?),
+            PermanentChannelFailure::TYPE => OnionFailure::PermanentChannelFailure(r.read()?),
+            RequiredChannelFeatureMissing::TYPE => OnionFailure::RequiredChannelFeatureMissing(r.read()?),
+            UnknownNextPeer::TYPE => OnionFailure::UnknownNextPeer(r.read()?),
+            AmountBelowMinimum::TYPE => OnionFailure::AmountBelowMinimum(r.read()?),
+            FeeInsufficient::TYPE => OnionFailure::FeeInsufficient(r.read()?),
+            IncorrectCltvExpiry::TYPE => OnionFailure::IncorrectCltvExpiry(r.read()?),
+            ExpiryTooSoon::TYPE => OnionFailure::ExpiryTooSoon(r.read()?),
+            IncorrectOrUnknownPaymentDetails::TYPE => OnionFailure::IncorrectOrUnknownPaymentDetails(r.read()?),
+            FinalIncorrectCltvExpiry::TYPE => OnionFailure::FinalIncorrectCltvExpiry(r.read()?),
+            FinalIncorrectHtlcAmount::TYPE => OnionFailure::FinalIncorrectHtlcAmount(r.read()?),
+            ChannelDisabled::TYPE => OnionFailure::ChannelDisabled(r.read()?),
+            ExpiryTooFar::TYPE => OnionFailure::ExpiryTooFar(r.read()?),
+            InvalidOnionPayload::TYPE => OnionFailure::InvalidOnionPayload(r.read()?),
+            MppTimeout::TYPE => OnionFailure::MppTimeout(r.read()?),
+            InvalidOnionBlinding::TYPE => OnionFailure::InvalidOnionBlinding(r.read()?),
+            _ => OnionFailure::Unknown {
+                typ,
+                payload: r.rest().to_vec(),
+            },
+        })
+    }
+}
+
+impl From<InvalidRealm> for OnionFailure {
+    fn from(m: InvalidRealm) -> Self {
+        OnionFailure::InvalidRealm(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidRealm {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidRealm(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TemporaryNodeFailure> for OnionFailure {
+    fn from(m: TemporaryNodeFailure) -> Self {
+        OnionFailure::TemporaryNodeFailure(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for TemporaryNodeFailure {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::TemporaryNodeFailure(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<PermanentNodeFailure> for OnionFailure {
+    fn from(m: PermanentNodeFailure) -> Self {
+        OnionFailure::PermanentNodeFailure(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for PermanentNodeFailure {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::PermanentNodeFailure(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<RequiredNodeFeatureMissing> for OnionFailure {
+    fn from(m: RequiredNodeFeatureMissing) -> Self {
+        OnionFailure::RequiredNodeFeatureMissing(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for RequiredNodeFeatureMissing {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::RequiredNodeFeatureMissing(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InvalidOnionVersion> for OnionFailure {
+    fn from(m: InvalidOnionVersion) -> Self {
+        OnionFailure::InvalidOnionVersion(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidOnionVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidOnionVersion(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InvalidOnionHmac> for OnionFailure {
+    fn from(m: InvalidOnionHmac) -> Self {
+        OnionFailure::InvalidOnionHmac(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidOnionHmac {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidOnionHmac(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InvalidOnionKey> for OnionFailure {
+    fn from(m: InvalidOnionKey) -> Self {
+        OnionFailure::InvalidOnionKey(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidOnionKey {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidOnionKey(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TemporaryChannelFailure> for OnionFailure {
+    fn from(m: TemporaryChannelFailure) -> Self {
+        OnionFailure::TemporaryChannelFailure(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for TemporaryChannelFailure {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::TemporaryChannelFailure(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<PermanentChannelFailure> for OnionFailure {
+    fn from(m: PermanentChannelFailure) -> Self {
+        OnionFailure::PermanentChannelFailure(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for PermanentChannelFailure {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::PermanentChannelFailure(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<RequiredChannelFeatureMissing> for OnionFailure {
+    fn from(m: RequiredChannelFeatureMissing) -> Self {
+        OnionFailure::RequiredChannelFeatureMissing(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for RequiredChannelFeatureMissing {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::RequiredChannelFeatureMissing(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UnknownNextPeer> for OnionFailure {
+    fn from(m: UnknownNextPeer) -> Self {
+        OnionFailure::UnknownNextPeer(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for UnknownNextPeer {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::UnknownNextPeer(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<AmountBelowMinimum> for OnionFailure {
+    fn from(m: AmountBelowMinimum) -> Self {
+        OnionFailure::AmountBelowMinimum(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for AmountBelowMinimum {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::AmountBelowMinimum(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<FeeInsufficient> for OnionFailure {
+    fn from(m: FeeInsufficient) -> Self {
+        OnionFailure::FeeInsufficient(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for FeeInsufficient {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::FeeInsufficient(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<IncorrectCltvExpiry> for OnionFailure {
+    fn from(m: IncorrectCltvExpiry) -> Self {
+        OnionFailure::IncorrectCltvExpiry(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for IncorrectCltvExpiry {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::IncorrectCltvExpiry(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ExpiryTooSoon> for OnionFailure {
+    fn from(m: ExpiryTooSoon) -> Self {
+        OnionFailure::ExpiryTooSoon(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for ExpiryTooSoon {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::ExpiryTooSoon(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<IncorrectOrUnknownPaymentDetails> for OnionFailure {
+    fn from(m: IncorrectOrUnknownPaymentDetails) -> Self {
+        OnionFailure::IncorrectOrUnknownPaymentDetails(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for IncorrectOrUnknownPaymentDetails {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::IncorrectOrUnknownPaymentDetails(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<FinalIncorrectCltvExpiry> for OnionFailure {
+    fn from(m: FinalIncorrectCltvExpiry) -> Self {
+        OnionFailure::FinalIncorrectCltvExpiry(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for FinalIncorrectCltvExpiry {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::FinalIncorrectCltvExpiry(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<FinalIncorrectHtlcAmount> for OnionFailure {
+    fn from(m: FinalIncorrectHtlcAmount) -> Self {
+        OnionFailure::FinalIncorrectHtlcAmount(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for FinalIncorrectHtlcAmount {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::FinalIncorrectHtlcAmount(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ChannelDisabled> for OnionFailure {
+    fn from(m: ChannelDisabled) -> Self {
+        OnionFailure::ChannelDisabled(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for ChannelDisabled {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::ChannelDisabled(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ExpiryTooFar> for OnionFailure {
+    fn from(m: ExpiryTooFar) -> Self {
+        OnionFailure::ExpiryTooFar(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for ExpiryTooFar {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::ExpiryTooFar(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InvalidOnionPayload> for OnionFailure {
+    fn from(m: InvalidOnionPayload) -> Self {
+        OnionFailure::InvalidOnionPayload(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidOnionPayload {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidOnionPayload(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<MppTimeout> for OnionFailure {
+    fn from(m: MppTimeout) -> Self {
+        OnionFailure::MppTimeout(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for MppTimeout {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::MppTimeout(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InvalidOnionBlinding> for OnionFailure {
+    fn from(m: InvalidOnionBlinding) -> Self {
+        OnionFailure::InvalidOnionBlinding(m)
+    }
+}
+
+impl TryFrom<OnionFailure> for InvalidOnionBlinding {
+    type Error = anyhow::Error;
+
+    fn try_from(m: OnionFailure) -> Result<Self> {
+        match m {
+            OnionFailure::InvalidOnionBlinding(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
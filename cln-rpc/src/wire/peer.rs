@@ -0,0 +1,3418 @@
+//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! contrib/msggen/msggen/__main__.py
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `wire/peer_wire.csv`
+//! which this file was generated from
+
+use anyhow::{anyhow, Result};
+use bitcoin::BlockHash;
+use crate::primitives::Amount;
+use crate::primitives::PublicKey;
+use crate::primitives::Sha256;
+use crate::primitives::ShortChannelId;
+use crate::primitives::TlvEntry;
+use crate::primitives::TlvStream;
+use crate::primitives::TlvValue;
+use crate::wire::{Reader, WireMessage, WireType, len, read_tlv, unknown_stream, unknown_tlv, write_bigsize, write_tlv};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessStack {
+    pub witness_element: Vec<WitnessElement>,
+}
+
+impl WireType for WitnessStack {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.witness_element.len())?.write(buf)?;
+        for x in &self.witness_element {
+            x.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let num_input_witness: u16 = r.read()?;
+        let witness_element: Vec<WitnessElement> = r.read_n(num_input_witness as usize)?;
+        Ok(WitnessStack {
+            witness_element,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessElement {
+    pub witness: Vec<u8>,
+}
+
+impl WireType for WitnessElement {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.witness.len())?.write(buf)?;
+        buf.extend_from_slice(&self.witness);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let len: u16 = r.read()?;
+        let witness: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(WitnessElement {
+            witness,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaseRates {
+    pub funding_weight: u16,
+    pub lease_fee_basis: u16,
+    pub channel_fee_max_proportional_thousandths: u16,
+    pub lease_fee_base_sat: u32,
+    pub channel_fee_max_base_msat: u32,
+}
+
+impl WireType for LeaseRates {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.funding_weight.write(buf)?;
+        self.lease_fee_basis.write(buf)?;
+        self.channel_fee_max_proportional_thousandths.write(buf)?;
+        self.lease_fee_base_sat.write(buf)?;
+        buf.extend(self.channel_fee_max_base_msat.to_tlv_value());
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let funding_weight: u16 = r.read()?;
+        let lease_fee_basis: u16 = r.read()?;
+        let channel_fee_max_proportional_thousandths: u16 = r.read()?;
+        let lease_fee_base_sat: u32 = r.read()?;
+        let channel_fee_max_base_msat: u32 = r.truncated()?;
+        Ok(LeaseRates {
+            funding_weight,
+            lease_fee_basis,
+            channel_fee_max_proportional_thousandths,
+            lease_fee_base_sat,
+            channel_fee_max_base_msat,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelUpdateTimestamps {
+    pub timestamp_node_id_1: u32,
+    pub timestamp_node_id_2: u32,
+}
+
+impl WireType for ChannelUpdateTimestamps {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.timestamp_node_id_1.write(buf)?;
+        self.timestamp_node_id_2.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let timestamp_node_id_1: u32 = r.read()?;
+        let timestamp_node_id_2: u32 = r.read()?;
+        Ok(ChannelUpdateTimestamps {
+            timestamp_node_id_1,
+            timestamp_node_id_2,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelUpdateChecksums {
+    pub checksum_node_id_1: u32,
+    pub checksum_node_id_2: u32,
+}
+
+impl WireType for ChannelUpdateChecksums {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.checksum_node_id_1.write(buf)?;
+        self.checksum_node_id_2.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let checksum_node_id_1: u32 = r.read()?;
+        let checksum_node_id_2: u32 = r.read()?;
+        Ok(ChannelUpdateChecksums {
+            checksum_node_id_1,
+            checksum_node_id_2,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InitTlvs {
+    pub networks: Option<Vec<BlockHash>>,
+    pub remote_addr: Option<Vec<u8>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for InitTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.networks {
+            write_tlv(&mut stream, 1, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.remote_addr {
+            write_tlv(&mut stream, 3, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = InitTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.networks = Some(read_tlv(&e, |r| r.read_all())?),
+                3 => res.remote_addr = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct N1Tlv3 {
+    pub node_id: PublicKey,
+    pub amount_msat_1: Amount,
+    pub amount_msat_2: Amount,
+}
+
+impl WireType for N1Tlv3 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.node_id.write(buf)?;
+        self.amount_msat_1.write(buf)?;
+        self.amount_msat_2.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let node_id: PublicKey = r.read()?;
+        let amount_msat_1: Amount = r.read()?;
+        let amount_msat_2: Amount = r.read()?;
+        Ok(N1Tlv3 {
+            node_id,
+            amount_msat_1,
+            amount_msat_2,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct N1 {
+    pub tlv1: Option<Amount>,
+    pub tlv2: Option<ShortChannelId>,
+    pub tlv3: Option<N1Tlv3>,
+    pub tlv4: Option<u16>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for N1 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.tlv1 {
+            write_tlv(&mut stream, 1, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.tlv2 {
+            write_tlv(&mut stream, 2, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.tlv3 {
+            write_tlv(&mut stream, 3, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.tlv4 {
+            write_tlv(&mut stream, 254, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = N1::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.tlv1 = Some(read_tlv(&e, |r| r.truncated())?),
+                2 => res.tlv2 = Some(read_tlv(&e, |r| r.read())?),
+                3 => res.tlv3 = Some(read_tlv(&e, N1Tlv3::read)?),
+                254 => res.tlv4 = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct N2 {
+    pub tlv1: Option<Amount>,
+    pub tlv2: Option<u32>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for N2 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.tlv1 {
+            write_tlv(&mut stream, 0, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.tlv2 {
+            write_tlv(&mut stream, 11, |buf| {
+                buf.extend(v.to_tlv_value());
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = N2::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                0 => res.tlv1 = Some(read_tlv(&e, |r| r.truncated())?),
+                11 => res.tlv2 = Some(read_tlv(&e, |r| r.truncated())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenChannelTlvs {
+    pub upfront_shutdown_script: Option<Vec<u8>>,
+    pub channel_type: Option<Vec<u8>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for OpenChannelTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.upfront_shutdown_script {
+            write_tlv(&mut stream, 0, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.channel_type {
+            write_tlv(&mut stream, 1, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = OpenChannelTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                0 => res.upfront_shutdown_script = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                1 => res.channel_type = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AcceptChannelTlvs {
+    pub upfront_shutdown_script: Option<Vec<u8>>,
+    pub channel_type: Option<Vec<u8>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for AcceptChannelTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.upfront_shutdown_script {
+            write_tlv(&mut stream, 0, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.channel_type {
+            write_tlv(&mut stream, 1, |buf| {
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = AcceptChannelTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                0 => res.upfront_shutdown_script = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                1 => res.channel_type = Some(read_tlv(&e, |r| Ok(r.rest().to_vec()))?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChannelReadyTlvs {
+    pub short_channel_id: Option<ShortChannelId>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for ChannelReadyTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.short_channel_id {
+            write_tlv(&mut stream, 1, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = ChannelReadyTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.short_channel_id = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningTlvsRequestFunds {
+    pub requested_sats: u64,
+    pub blockheight: u32,
+}
+
+impl WireType for OpeningTlvsRequestFunds {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.requested_sats.write(buf)?;
+        self.blockheight.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let requested_sats: u64 = r.read()?;
+        let blockheight: u32 = r.read()?;
+        Ok(OpeningTlvsRequestFunds {
+            requested_sats,
+            blockheight,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpeningTlvs {
+    pub option_upfront_shutdown_script: Option<Vec<u8>>,
+    pub request_funds: Option<OpeningTlvsRequestFunds>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for OpeningTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.option_upfront_shutdown_script {
+            write_tlv(&mut stream, 1, |buf| {
+                len::<u16>(v.len())?.write(buf)?;
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.request_funds {
+            write_tlv(&mut stream, 3, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = OpeningTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.option_upfront_shutdown_script = Some(read_tlv(&e, |r| {
+                    let shutdown_len: u16 = r.read()?;
+                    Ok(r.take(shutdown_len as usize)?.to_vec())
+                })?),
+                3 => res.request_funds = Some(read_tlv(&e, OpeningTlvsRequestFunds::read)?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptTlvsWillFund {
+    pub signature: [u8; 64],
+    pub lease_rates: LeaseRates,
+}
+
+impl WireType for AcceptTlvsWillFund {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.signature.write(buf)?;
+        self.lease_rates.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let signature: [u8; 64] = r.read()?;
+        let lease_rates: LeaseRates = r.read()?;
+        Ok(AcceptTlvsWillFund {
+            signature,
+            lease_rates,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AcceptTlvs {
+    pub option_upfront_shutdown_script: Option<Vec<u8>>,
+    pub will_fund: Option<AcceptTlvsWillFund>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for AcceptTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.option_upfront_shutdown_script {
+            write_tlv(&mut stream, 1, |buf| {
+                len::<u16>(v.len())?.write(buf)?;
+                buf.extend_from_slice(v);
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.will_fund {
+            write_tlv(&mut stream, 2, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = AcceptTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.option_upfront_shutdown_script = Some(read_tlv(&e, |r| {
+                    let shutdown_len: u16 = r.read()?;
+                    Ok(r.take(shutdown_len as usize)?.to_vec())
+                })?),
+                2 => res.will_fund = Some(read_tlv(&e, AcceptTlvsWillFund::read)?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShutdownTlvsWrongFunding {
+    pub txid: Sha256,
+    pub outnum: u32,
+}
+
+impl WireType for ShutdownTlvsWrongFunding {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.txid.write(buf)?;
+        self.outnum.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let txid: Sha256 = r.read()?;
+        let outnum: u32 = r.read()?;
+        Ok(ShutdownTlvsWrongFunding {
+            txid,
+            outnum,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownTlvs {
+    pub wrong_funding: Option<ShutdownTlvsWrongFunding>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for ShutdownTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.wrong_funding {
+            write_tlv(&mut stream, 100, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = ShutdownTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                100 => res.wrong_funding = Some(read_tlv(&e, ShutdownTlvsWrongFunding::read)?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosingSignedTlvsFeeRange {
+    pub min_fee_satoshis: u64,
+    pub max_fee_satoshis: u64,
+}
+
+impl WireType for ClosingSignedTlvsFeeRange {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.min_fee_satoshis.write(buf)?;
+        self.max_fee_satoshis.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let min_fee_satoshis: u64 = r.read()?;
+        let max_fee_satoshis: u64 = r.read()?;
+        Ok(ClosingSignedTlvsFeeRange {
+            min_fee_satoshis,
+            max_fee_satoshis,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClosingSignedTlvs {
+    pub fee_range: Option<ClosingSignedTlvsFeeRange>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for ClosingSignedTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.fee_range {
+            write_tlv(&mut stream, 1, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = ClosingSignedTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.fee_range = Some(read_tlv(&e, ClosingSignedTlvsFeeRange::read)?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UpdateAddTlvs {
+    pub blinding: Option<PublicKey>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for UpdateAddTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.blinding {
+            write_tlv(&mut stream, 2, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = UpdateAddTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                2 => res.blinding = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeAnnTlvs {
+    pub option_will_fund: Option<LeaseRates>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for NodeAnnTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.option_will_fund {
+            write_tlv(&mut stream, 1, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = NodeAnnTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.option_will_fund = Some(read_tlv(&e, |r| r.read())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryShortChannelIdsTlvsQueryFlags {
+    pub encoding_type: u8,
+    pub encoded_query_flags: Vec<u8>,
+}
+
+impl WireType for QueryShortChannelIdsTlvsQueryFlags {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.encoding_type.write(buf)?;
+        buf.extend_from_slice(&self.encoded_query_flags);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let encoding_type: u8 = r.read()?;
+        let encoded_query_flags: Vec<u8> = r.rest().to_vec();
+        Ok(QueryShortChannelIdsTlvsQueryFlags {
+            encoding_type,
+            encoded_query_flags,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryShortChannelIdsTlvs {
+    pub query_flags: Option<QueryShortChannelIdsTlvsQueryFlags>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for QueryShortChannelIdsTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.query_flags {
+            write_tlv(&mut stream, 1, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = QueryShortChannelIdsTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.query_flags = Some(read_tlv(&e, QueryShortChannelIdsTlvsQueryFlags::read)?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryChannelRangeTlvs {
+    pub query_option: Option<u64>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for QueryChannelRangeTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.query_option {
+            write_tlv(&mut stream, 1, |buf| {
+                write_bigsize(buf, *v);
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = QueryChannelRangeTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.query_option = Some(read_tlv(&e, |r| r.bigsize())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplyChannelRangeTlvsTimestampsTlv {
+    pub encoding_type: u8,
+    pub encoded_timestamps: Vec<u8>,
+}
+
+impl WireType for ReplyChannelRangeTlvsTimestampsTlv {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.encoding_type.write(buf)?;
+        buf.extend_from_slice(&self.encoded_timestamps);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let encoding_type: u8 = r.read()?;
+        let encoded_timestamps: Vec<u8> = r.rest().to_vec();
+        Ok(ReplyChannelRangeTlvsTimestampsTlv {
+            encoding_type,
+            encoded_timestamps,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplyChannelRangeTlvs {
+    pub timestamps_tlv: Option<ReplyChannelRangeTlvsTimestampsTlv>,
+    pub checksums_tlv: Option<Vec<ChannelUpdateChecksums>>,
+    /// Odd records we don't know about.
+    pub unknown: Vec<TlvEntry>,
+}
+
+impl WireType for ReplyChannelRangeTlvs {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        let mut stream = unknown_stream(&self.unknown)?;
+        if let Some(v) = &self.timestamps_tlv {
+            write_tlv(&mut stream, 1, |buf| {
+                v.write(buf)?;
+                Ok(())
+            })?;
+        }
+        if let Some(v) = &self.checksums_tlv {
+            write_tlv(&mut stream, 3, |buf| {
+                for x in v {
+                    x.write(buf)?;
+                }
+                Ok(())
+            })?;
+        }
+        buf.extend(stream.to_wire()?);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let mut res = ReplyChannelRangeTlvs::default();
+        for e in TlvStream::from_wire(r.rest())?.entries {
+            match e.typ {
+                1 => res.timestamps_tlv = Some(read_tlv(&e, ReplyChannelRangeTlvsTimestampsTlv::read)?),
+                3 => res.checksums_tlv = Some(read_tlv(&e, |r| r.read_all())?),
+                _ => unknown_tlv(&mut res.unknown, e)?,
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Init {
+    pub globalfeatures: Vec<u8>,
+    pub features: Vec<u8>,
+    pub tlvs: InitTlvs,
+}
+
+impl WireType for Init {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.globalfeatures.len())?.write(buf)?;
+        buf.extend_from_slice(&self.globalfeatures);
+        len::<u16>(self.features.len())?.write(buf)?;
+        buf.extend_from_slice(&self.features);
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let gflen: u16 = r.read()?;
+        let globalfeatures: Vec<u8> = r.take(gflen as usize)?.to_vec();
+        let flen: u16 = r.read()?;
+        let features: Vec<u8> = r.take(flen as usize)?.to_vec();
+        let tlvs: InitTlvs = r.read()?;
+        Ok(Init {
+            globalfeatures,
+            features,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for Init {
+    const TYPE: u16 = 16;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub channel_id: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+impl WireType for Error {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        len::<u16>(self.data.len())?.write(buf)?;
+        buf.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let len: u16 = r.read()?;
+        let data: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(Error {
+            channel_id,
+            data,
+        })
+    }
+}
+
+impl WireMessage for Error {
+    const TYPE: u16 = 17;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub channel_id: [u8; 32],
+    pub data: Vec<u8>,
+}
+
+impl WireType for Warning {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        len::<u16>(self.data.len())?.write(buf)?;
+        buf.extend_from_slice(&self.data);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let len: u16 = r.read()?;
+        let data: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(Warning {
+            channel_id,
+            data,
+        })
+    }
+}
+
+impl WireMessage for Warning {
+    const TYPE: u16 = 1;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ping {
+    pub num_pong_bytes: u16,
+    pub ignored: Vec<u8>,
+}
+
+impl WireType for Ping {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.num_pong_bytes.write(buf)?;
+        len::<u16>(self.ignored.len())?.write(buf)?;
+        buf.extend_from_slice(&self.ignored);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let num_pong_bytes: u16 = r.read()?;
+        let byteslen: u16 = r.read()?;
+        let ignored: Vec<u8> = r.take(byteslen as usize)?.to_vec();
+        Ok(Ping {
+            num_pong_bytes,
+            ignored,
+        })
+    }
+}
+
+impl WireMessage for Ping {
+    const TYPE: u16 = 18;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pong {
+    pub ignored: Vec<u8>,
+}
+
+impl WireType for Pong {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        len::<u16>(self.ignored.len())?.write(buf)?;
+        buf.extend_from_slice(&self.ignored);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let byteslen: u16 = r.read()?;
+        let ignored: Vec<u8> = r.take(byteslen as usize)?.to_vec();
+        Ok(Pong {
+            ignored,
+        })
+    }
+}
+
+impl WireMessage for Pong {
+    const TYPE: u16 = 19;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxAddInput {
+    pub channel_id: [u8; 32],
+    pub serial_id: u64,
+    pub prevtx: Vec<u8>,
+    pub prevtx_vout: u32,
+    pub sequence: u32,
+    pub script_sig: Vec<u8>,
+}
+
+impl WireType for TxAddInput {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.serial_id.write(buf)?;
+        len::<u16>(self.prevtx.len())?.write(buf)?;
+        buf.extend_from_slice(&self.prevtx);
+        self.prevtx_vout.write(buf)?;
+        self.sequence.write(buf)?;
+        len::<u16>(self.script_sig.len())?.write(buf)?;
+        buf.extend_from_slice(&self.script_sig);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let serial_id: u64 = r.read()?;
+        let prevtx_len: u16 = r.read()?;
+        let prevtx: Vec<u8> = r.take(prevtx_len as usize)?.to_vec();
+        let prevtx_vout: u32 = r.read()?;
+        let sequence: u32 = r.read()?;
+        let script_sig_len: u16 = r.read()?;
+        let script_sig: Vec<u8> = r.take(script_sig_len as usize)?.to_vec();
+        Ok(TxAddInput {
+            channel_id,
+            serial_id,
+            prevtx,
+            prevtx_vout,
+            sequence,
+            script_sig,
+        })
+    }
+}
+
+impl WireMessage for TxAddInput {
+    const TYPE: u16 = 66;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxAddOutput {
+    pub channel_id: [u8; 32],
+    pub serial_id: u64,
+    pub sats: u64,
+    pub script: Vec<u8>,
+}
+
+impl WireType for TxAddOutput {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.serial_id.write(buf)?;
+        self.sats.write(buf)?;
+        len::<u16>(self.script.len())?.write(buf)?;
+        buf.extend_from_slice(&self.script);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let serial_id: u64 = r.read()?;
+        let sats: u64 = r.read()?;
+        let scriptlen: u16 = r.read()?;
+        let script: Vec<u8> = r.take(scriptlen as usize)?.to_vec();
+        Ok(TxAddOutput {
+            channel_id,
+            serial_id,
+            sats,
+            script,
+        })
+    }
+}
+
+impl WireMessage for TxAddOutput {
+    const TYPE: u16 = 67;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxRemoveInput {
+    pub channel_id: [u8; 32],
+    pub serial_id: u64,
+}
+
+impl WireType for TxRemoveInput {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.serial_id.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let serial_id: u64 = r.read()?;
+        Ok(TxRemoveInput {
+            channel_id,
+            serial_id,
+        })
+    }
+}
+
+impl WireMessage for TxRemoveInput {
+    const TYPE: u16 = 68;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxRemoveOutput {
+    pub channel_id: [u8; 32],
+    pub serial_id: u64,
+}
+
+impl WireType for TxRemoveOutput {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.serial_id.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let serial_id: u64 = r.read()?;
+        Ok(TxRemoveOutput {
+            channel_id,
+            serial_id,
+        })
+    }
+}
+
+impl WireMessage for TxRemoveOutput {
+    const TYPE: u16 = 69;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxComplete {
+    pub channel_id: [u8; 32],
+}
+
+impl WireType for TxComplete {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        Ok(TxComplete {
+            channel_id,
+        })
+    }
+}
+
+impl WireMessage for TxComplete {
+    const TYPE: u16 = 70;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxSignatures {
+    pub channel_id: [u8; 32],
+    pub txid: Sha256,
+    pub witness_stack: Vec<WitnessStack>,
+}
+
+impl WireType for TxSignatures {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.txid.write(buf)?;
+        len::<u16>(self.witness_stack.len())?.write(buf)?;
+        for x in &self.witness_stack {
+            x.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let txid: Sha256 = r.read()?;
+        let num_witnesses: u16 = r.read()?;
+        let witness_stack: Vec<WitnessStack> = r.read_n(num_witnesses as usize)?;
+        Ok(TxSignatures {
+            channel_id,
+            txid,
+            witness_stack,
+        })
+    }
+}
+
+impl WireMessage for TxSignatures {
+    const TYPE: u16 = 71;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenChannel {
+    pub chain_hash: BlockHash,
+    pub temporary_channel_id: [u8; 32],
+    pub funding_satoshis: u64,
+    pub push_msat: Amount,
+    pub dust_limit_satoshis: u64,
+    pub max_htlc_value_in_flight_msat: Amount,
+    pub channel_reserve_satoshis: u64,
+    pub htlc_minimum_msat: Amount,
+    pub feerate_per_kw: u32,
+    pub to_self_delay: u16,
+    pub max_accepted_htlcs: u16,
+    pub funding_pubkey: PublicKey,
+    pub revocation_basepoint: PublicKey,
+    pub payment_basepoint: PublicKey,
+    pub delayed_payment_basepoint: PublicKey,
+    pub htlc_basepoint: PublicKey,
+    pub first_per_commitment_point: PublicKey,
+    pub channel_flags: u8,
+    pub tlvs: OpenChannelTlvs,
+}
+
+impl WireType for OpenChannel {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.temporary_channel_id.write(buf)?;
+        self.funding_satoshis.write(buf)?;
+        self.push_msat.write(buf)?;
+        self.dust_limit_satoshis.write(buf)?;
+        self.max_htlc_value_in_flight_msat.write(buf)?;
+        self.channel_reserve_satoshis.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.feerate_per_kw.write(buf)?;
+        self.to_self_delay.write(buf)?;
+        self.max_accepted_htlcs.write(buf)?;
+        self.funding_pubkey.write(buf)?;
+        self.revocation_basepoint.write(buf)?;
+        self.payment_basepoint.write(buf)?;
+        self.delayed_payment_basepoint.write(buf)?;
+        self.htlc_basepoint.write(buf)?;
+        self.first_per_commitment_point.write(buf)?;
+        self.channel_flags.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let temporary_channel_id: [u8; 32] = r.read()?;
+        let funding_satoshis: u64 = r.read()?;
+        let push_msat: Amount = r.read()?;
+        let dust_limit_satoshis: u64 = r.read()?;
+        let max_htlc_value_in_flight_msat: Amount = r.read()?;
+        let channel_reserve_satoshis: u64 = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let feerate_per_kw: u32 = r.read()?;
+        let to_self_delay: u16 = r.read()?;
+        let max_accepted_htlcs: u16 = r.read()?;
+        let funding_pubkey: PublicKey = r.read()?;
+        let revocation_basepoint: PublicKey = r.read()?;
+        let payment_basepoint: PublicKey = r.read()?;
+        let delayed_payment_basepoint: PublicKey = r.read()?;
+        let htlc_basepoint: PublicKey = r.read()?;
+        let first_per_commitment_point: PublicKey = r.read()?;
+        let channel_flags: u8 = r.read()?;
+        let tlvs: OpenChannelTlvs = r.read()?;
+        Ok(OpenChannel {
+            chain_hash,
+            temporary_channel_id,
+            funding_satoshis,
+            push_msat,
+            dust_limit_satoshis,
+            max_htlc_value_in_flight_msat,
+            channel_reserve_satoshis,
+            htlc_minimum_msat,
+            feerate_per_kw,
+            to_self_delay,
+            max_accepted_htlcs,
+            funding_pubkey,
+            revocation_basepoint,
+            payment_basepoint,
+            delayed_payment_basepoint,
+            htlc_basepoint,
+            first_per_commitment_point,
+            channel_flags,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for OpenChannel {
+    const TYPE: u16 = 32;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptChannel {
+    pub temporary_channel_id: [u8; 32],
+    pub dust_limit_satoshis: u64,
+    pub max_htlc_value_in_flight_msat: Amount,
+    pub channel_reserve_satoshis: u64,
+    pub htlc_minimum_msat: Amount,
+    pub minimum_depth: u32,
+    pub to_self_delay: u16,
+    pub max_accepted_htlcs: u16,
+    pub funding_pubkey: PublicKey,
+    pub revocation_basepoint: PublicKey,
+    pub payment_basepoint: PublicKey,
+    pub delayed_payment_basepoint: PublicKey,
+    pub htlc_basepoint: PublicKey,
+    pub first_per_commitment_point: PublicKey,
+    pub tlvs: AcceptChannelTlvs,
+}
+
+impl WireType for AcceptChannel {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.temporary_channel_id.write(buf)?;
+        self.dust_limit_satoshis.write(buf)?;
+        self.max_htlc_value_in_flight_msat.write(buf)?;
+        self.channel_reserve_satoshis.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.minimum_depth.write(buf)?;
+        self.to_self_delay.write(buf)?;
+        self.max_accepted_htlcs.write(buf)?;
+        self.funding_pubkey.write(buf)?;
+        self.revocation_basepoint.write(buf)?;
+        self.payment_basepoint.write(buf)?;
+        self.delayed_payment_basepoint.write(buf)?;
+        self.htlc_basepoint.write(buf)?;
+        self.first_per_commitment_point.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let temporary_channel_id: [u8; 32] = r.read()?;
+        let dust_limit_satoshis: u64 = r.read()?;
+        let max_htlc_value_in_flight_msat: Amount = r.read()?;
+        let channel_reserve_satoshis: u64 = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let minimum_depth: u32 = r.read()?;
+        let to_self_delay: u16 = r.read()?;
+        let max_accepted_htlcs: u16 = r.read()?;
+        let funding_pubkey: PublicKey = r.read()?;
+        let revocation_basepoint: PublicKey = r.read()?;
+        let payment_basepoint: PublicKey = r.read()?;
+        let delayed_payment_basepoint: PublicKey = r.read()?;
+        let htlc_basepoint: PublicKey = r.read()?;
+        let first_per_commitment_point: PublicKey = r.read()?;
+        let tlvs: AcceptChannelTlvs = r.read()?;
+        Ok(AcceptChannel {
+            temporary_channel_id,
+            dust_limit_satoshis,
+            max_htlc_value_in_flight_msat,
+            channel_reserve_satoshis,
+            htlc_minimum_msat,
+            minimum_depth,
+            to_self_delay,
+            max_accepted_htlcs,
+            funding_pubkey,
+            revocation_basepoint,
+            payment_basepoint,
+            delayed_payment_basepoint,
+            htlc_basepoint,
+            first_per_commitment_point,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for AcceptChannel {
+    const TYPE: u16 = 33;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FundingCreated {
+    pub temporary_channel_id: [u8; 32],
+    pub funding_txid: Sha256,
+    pub funding_output_index: u16,
+    pub signature: [u8; 64],
+}
+
+impl WireType for FundingCreated {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.temporary_channel_id.write(buf)?;
+        self.funding_txid.write(buf)?;
+        self.funding_output_index.write(buf)?;
+        self.signature.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let temporary_channel_id: [u8; 32] = r.read()?;
+        let funding_txid: Sha256 = r.read()?;
+        let funding_output_index: u16 = r.read()?;
+        let signature: [u8; 64] = r.read()?;
+        Ok(FundingCreated {
+            temporary_channel_id,
+            funding_txid,
+            funding_output_index,
+            signature,
+        })
+    }
+}
+
+impl WireMessage for FundingCreated {
+    const TYPE: u16 = 34;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FundingSigned {
+    pub channel_id: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+impl WireType for FundingSigned {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.signature.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let signature: [u8; 64] = r.read()?;
+        Ok(FundingSigned {
+            channel_id,
+            signature,
+        })
+    }
+}
+
+impl WireMessage for FundingSigned {
+    const TYPE: u16 = 35;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelReady {
+    pub channel_id: [u8; 32],
+    pub second_per_commitment_point: PublicKey,
+    pub tlvs: ChannelReadyTlvs,
+}
+
+impl WireType for ChannelReady {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.second_per_commitment_point.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let second_per_commitment_point: PublicKey = r.read()?;
+        let tlvs: ChannelReadyTlvs = r.read()?;
+        Ok(ChannelReady {
+            channel_id,
+            second_per_commitment_point,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for ChannelReady {
+    const TYPE: u16 = 36;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpenChannel2 {
+    pub chain_hash: BlockHash,
+    pub channel_id: [u8; 32],
+    pub funding_feerate_perkw: u32,
+    pub commitment_feerate_perkw: u32,
+    pub funding_satoshis: u64,
+    pub dust_limit_satoshis: u64,
+    pub max_htlc_value_in_flight_msat: Amount,
+    pub htlc_minimum_msat: Amount,
+    pub to_self_delay: u16,
+    pub max_accepted_htlcs: u16,
+    pub locktime: u32,
+    pub funding_pubkey: PublicKey,
+    pub revocation_basepoint: PublicKey,
+    pub payment_basepoint: PublicKey,
+    pub delayed_payment_basepoint: PublicKey,
+    pub htlc_basepoint: PublicKey,
+    pub first_per_commitment_point: PublicKey,
+    pub channel_flags: u8,
+    pub tlvs: OpeningTlvs,
+}
+
+impl WireType for OpenChannel2 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.channel_id.write(buf)?;
+        self.funding_feerate_perkw.write(buf)?;
+        self.commitment_feerate_perkw.write(buf)?;
+        self.funding_satoshis.write(buf)?;
+        self.dust_limit_satoshis.write(buf)?;
+        self.max_htlc_value_in_flight_msat.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.to_self_delay.write(buf)?;
+        self.max_accepted_htlcs.write(buf)?;
+        self.locktime.write(buf)?;
+        self.funding_pubkey.write(buf)?;
+        self.revocation_basepoint.write(buf)?;
+        self.payment_basepoint.write(buf)?;
+        self.delayed_payment_basepoint.write(buf)?;
+        self.htlc_basepoint.write(buf)?;
+        self.first_per_commitment_point.write(buf)?;
+        self.channel_flags.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let channel_id: [u8; 32] = r.read()?;
+        let funding_feerate_perkw: u32 = r.read()?;
+        let commitment_feerate_perkw: u32 = r.read()?;
+        let funding_satoshis: u64 = r.read()?;
+        let dust_limit_satoshis: u64 = r.read()?;
+        let max_htlc_value_in_flight_msat: Amount = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let to_self_delay: u16 = r.read()?;
+        let max_accepted_htlcs: u16 = r.read()?;
+        let locktime: u32 = r.read()?;
+        let funding_pubkey: PublicKey = r.read()?;
+        let revocation_basepoint: PublicKey = r.read()?;
+        let payment_basepoint: PublicKey = r.read()?;
+        let delayed_payment_basepoint: PublicKey = r.read()?;
+        let htlc_basepoint: PublicKey = r.read()?;
+        let first_per_commitment_point: PublicKey = r.read()?;
+        let channel_flags: u8 = r.read()?;
+        let tlvs: OpeningTlvs = r.read()?;
+        Ok(OpenChannel2 {
+            chain_hash,
+            channel_id,
+            funding_feerate_perkw,
+            commitment_feerate_perkw,
+            funding_satoshis,
+            dust_limit_satoshis,
+            max_htlc_value_in_flight_msat,
+            htlc_minimum_msat,
+            to_self_delay,
+            max_accepted_htlcs,
+            locktime,
+            funding_pubkey,
+            revocation_basepoint,
+            payment_basepoint,
+            delayed_payment_basepoint,
+            htlc_basepoint,
+            first_per_commitment_point,
+            channel_flags,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for OpenChannel2 {
+    const TYPE: u16 = 64;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptChannel2 {
+    pub channel_id: [u8; 32],
+    pub funding_satoshis: u64,
+    pub dust_limit_satoshis: u64,
+    pub max_htlc_value_in_flight_msat: Amount,
+    pub htlc_minimum_msat: Amount,
+    pub minimum_depth: u32,
+    pub to_self_delay: u16,
+    pub max_accepted_htlcs: u16,
+    pub funding_pubkey: PublicKey,
+    pub revocation_basepoint: PublicKey,
+    pub payment_basepoint: PublicKey,
+    pub delayed_payment_basepoint: PublicKey,
+    pub htlc_basepoint: PublicKey,
+    pub first_per_commitment_point: PublicKey,
+    pub tlvs: AcceptTlvs,
+}
+
+impl WireType for AcceptChannel2 {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.funding_satoshis.write(buf)?;
+        self.dust_limit_satoshis.write(buf)?;
+        self.max_htlc_value_in_flight_msat.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.minimum_depth.write(buf)?;
+        self.to_self_delay.write(buf)?;
+        self.max_accepted_htlcs.write(buf)?;
+        self.funding_pubkey.write(buf)?;
+        self.revocation_basepoint.write(buf)?;
+        self.payment_basepoint.write(buf)?;
+        self.delayed_payment_basepoint.write(buf)?;
+        self.htlc_basepoint.write(buf)?;
+        self.first_per_commitment_point.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let funding_satoshis: u64 = r.read()?;
+        let dust_limit_satoshis: u64 = r.read()?;
+        let max_htlc_value_in_flight_msat: Amount = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let minimum_depth: u32 = r.read()?;
+        let to_self_delay: u16 = r.read()?;
+        let max_accepted_htlcs: u16 = r.read()?;
+        let funding_pubkey: PublicKey = r.read()?;
+        let revocation_basepoint: PublicKey = r.read()?;
+        let payment_basepoint: PublicKey = r.read()?;
+        let delayed_payment_basepoint: PublicKey = r.read()?;
+        let htlc_basepoint: PublicKey = r.read()?;
+        let first_per_commitment_point: PublicKey = r.read()?;
+        let tlvs: AcceptTlvs = r.read()?;
+        Ok(AcceptChannel2 {
+            channel_id,
+            funding_satoshis,
+            dust_limit_satoshis,
+            max_htlc_value_in_flight_msat,
+            htlc_minimum_msat,
+            minimum_depth,
+            to_self_delay,
+            max_accepted_htlcs,
+            funding_pubkey,
+            revocation_basepoint,
+            payment_basepoint,
+            delayed_payment_basepoint,
+            htlc_basepoint,
+            first_per_commitment_point,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for AcceptChannel2 {
+    const TYPE: u16 = 65;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitRbf {
+    pub channel_id: [u8; 32],
+    pub funding_satoshis: u64,
+    pub locktime: u32,
+    pub funding_feerate_perkw: u32,
+}
+
+impl WireType for InitRbf {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.funding_satoshis.write(buf)?;
+        self.locktime.write(buf)?;
+        self.funding_feerate_perkw.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let funding_satoshis: u64 = r.read()?;
+        let locktime: u32 = r.read()?;
+        let funding_feerate_perkw: u32 = r.read()?;
+        Ok(InitRbf {
+            channel_id,
+            funding_satoshis,
+            locktime,
+            funding_feerate_perkw,
+        })
+    }
+}
+
+impl WireMessage for InitRbf {
+    const TYPE: u16 = 72;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AckRbf {
+    pub channel_id: [u8; 32],
+    pub funding_satoshis: u64,
+}
+
+impl WireType for AckRbf {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.funding_satoshis.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let funding_satoshis: u64 = r.read()?;
+        Ok(AckRbf {
+            channel_id,
+            funding_satoshis,
+        })
+    }
+}
+
+impl WireMessage for AckRbf {
+    const TYPE: u16 = 73;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shutdown {
+    pub channel_id: [u8; 32],
+    pub scriptpubkey: Vec<u8>,
+    pub tlvs: ShutdownTlvs,
+}
+
+impl WireType for Shutdown {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        len::<u16>(self.scriptpubkey.len())?.write(buf)?;
+        buf.extend_from_slice(&self.scriptpubkey);
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let len: u16 = r.read()?;
+        let scriptpubkey: Vec<u8> = r.take(len as usize)?.to_vec();
+        let tlvs: ShutdownTlvs = r.read()?;
+        Ok(Shutdown {
+            channel_id,
+            scriptpubkey,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for Shutdown {
+    const TYPE: u16 = 38;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosingSigned {
+    pub channel_id: [u8; 32],
+    pub fee_satoshis: u64,
+    pub signature: [u8; 64],
+    pub tlvs: ClosingSignedTlvs,
+}
+
+impl WireType for ClosingSigned {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.fee_satoshis.write(buf)?;
+        self.signature.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let fee_satoshis: u64 = r.read()?;
+        let signature: [u8; 64] = r.read()?;
+        let tlvs: ClosingSignedTlvs = r.read()?;
+        Ok(ClosingSigned {
+            channel_id,
+            fee_satoshis,
+            signature,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for ClosingSigned {
+    const TYPE: u16 = 39;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateAddHtlc {
+    pub channel_id: [u8; 32],
+    pub id: u64,
+    pub amount_msat: Amount,
+    pub payment_hash: Sha256,
+    pub cltv_expiry: u32,
+    pub onion_routing_packet: [u8; 1366],
+    pub tlvs: UpdateAddTlvs,
+}
+
+impl WireType for UpdateAddHtlc {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.id.write(buf)?;
+        self.amount_msat.write(buf)?;
+        self.payment_hash.write(buf)?;
+        self.cltv_expiry.write(buf)?;
+        self.onion_routing_packet.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let id: u64 = r.read()?;
+        let amount_msat: Amount = r.read()?;
+        let payment_hash: Sha256 = r.read()?;
+        let cltv_expiry: u32 = r.read()?;
+        let onion_routing_packet: [u8; 1366] = r.read()?;
+        let tlvs: UpdateAddTlvs = r.read()?;
+        Ok(UpdateAddHtlc {
+            channel_id,
+            id,
+            amount_msat,
+            payment_hash,
+            cltv_expiry,
+            onion_routing_packet,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for UpdateAddHtlc {
+    const TYPE: u16 = 128;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateFulfillHtlc {
+    pub channel_id: [u8; 32],
+    pub id: u64,
+    pub payment_preimage: [u8; 32],
+}
+
+impl WireType for UpdateFulfillHtlc {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.id.write(buf)?;
+        self.payment_preimage.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let id: u64 = r.read()?;
+        let payment_preimage: [u8; 32] = r.read()?;
+        Ok(UpdateFulfillHtlc {
+            channel_id,
+            id,
+            payment_preimage,
+        })
+    }
+}
+
+impl WireMessage for UpdateFulfillHtlc {
+    const TYPE: u16 = 130;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateFailHtlc {
+    pub channel_id: [u8; 32],
+    pub id: u64,
+    pub reason: Vec<u8>,
+}
+
+impl WireType for UpdateFailHtlc {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.id.write(buf)?;
+        len::<u16>(self.reason.len())?.write(buf)?;
+        buf.extend_from_slice(&self.reason);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let id: u64 = r.read()?;
+        let len: u16 = r.read()?;
+        let reason: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(UpdateFailHtlc {
+            channel_id,
+            id,
+            reason,
+        })
+    }
+}
+
+impl WireMessage for UpdateFailHtlc {
+    const TYPE: u16 = 131;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateFailMalformedHtlc {
+    pub channel_id: [u8; 32],
+    pub id: u64,
+    pub sha256_of_onion: Sha256,
+    pub failure_code: u16,
+}
+
+impl WireType for UpdateFailMalformedHtlc {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.id.write(buf)?;
+        self.sha256_of_onion.write(buf)?;
+        self.failure_code.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let id: u64 = r.read()?;
+        let sha256_of_onion: Sha256 = r.read()?;
+        let failure_code: u16 = r.read()?;
+        Ok(UpdateFailMalformedHtlc {
+            channel_id,
+            id,
+            sha256_of_onion,
+            failure_code,
+        })
+    }
+}
+
+impl WireMessage for UpdateFailMalformedHtlc {
+    const TYPE: u16 = 135;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitmentSigned {
+    pub channel_id: [u8; 32],
+    pub signature: [u8; 64],
+    pub htlc_signature: Vec<[u8; 64]>,
+}
+
+impl WireType for CommitmentSigned {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.signature.write(buf)?;
+        len::<u16>(self.htlc_signature.len())?.write(buf)?;
+        for x in &self.htlc_signature {
+            x.write(buf)?;
+        }
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let signature: [u8; 64] = r.read()?;
+        let num_htlcs: u16 = r.read()?;
+        let htlc_signature: Vec<[u8; 64]> = r.read_n(num_htlcs as usize)?;
+        Ok(CommitmentSigned {
+            channel_id,
+            signature,
+            htlc_signature,
+        })
+    }
+}
+
+impl WireMessage for CommitmentSigned {
+    const TYPE: u16 = 132;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevokeAndAck {
+    pub channel_id: [u8; 32],
+    pub per_commitment_secret: [u8; 32],
+    pub next_per_commitment_point: PublicKey,
+}
+
+impl WireType for RevokeAndAck {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.per_commitment_secret.write(buf)?;
+        self.next_per_commitment_point.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let per_commitment_secret: [u8; 32] = r.read()?;
+        let next_per_commitment_point: PublicKey = r.read()?;
+        Ok(RevokeAndAck {
+            channel_id,
+            per_commitment_secret,
+            next_per_commitment_point,
+        })
+    }
+}
+
+impl WireMessage for RevokeAndAck {
+    const TYPE: u16 = 133;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateFee {
+    pub channel_id: [u8; 32],
+    pub feerate_per_kw: u32,
+}
+
+impl WireType for UpdateFee {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.feerate_per_kw.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let feerate_per_kw: u32 = r.read()?;
+        Ok(UpdateFee {
+            channel_id,
+            feerate_per_kw,
+        })
+    }
+}
+
+impl WireMessage for UpdateFee {
+    const TYPE: u16 = 134;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateBlockheight {
+    pub channel_id: [u8; 32],
+    pub blockheight: u32,
+}
+
+impl WireType for UpdateBlockheight {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.blockheight.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let blockheight: u32 = r.read()?;
+        Ok(UpdateBlockheight {
+            channel_id,
+            blockheight,
+        })
+    }
+}
+
+impl WireMessage for UpdateBlockheight {
+    const TYPE: u16 = 137;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelReestablish {
+    pub channel_id: [u8; 32],
+    pub next_commitment_number: u64,
+    pub next_revocation_number: u64,
+    pub your_last_per_commitment_secret: [u8; 32],
+    pub my_current_per_commitment_point: PublicKey,
+}
+
+impl WireType for ChannelReestablish {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.next_commitment_number.write(buf)?;
+        self.next_revocation_number.write(buf)?;
+        self.your_last_per_commitment_secret.write(buf)?;
+        self.my_current_per_commitment_point.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let next_commitment_number: u64 = r.read()?;
+        let next_revocation_number: u64 = r.read()?;
+        let your_last_per_commitment_secret: [u8; 32] = r.read()?;
+        let my_current_per_commitment_point: PublicKey = r.read()?;
+        Ok(ChannelReestablish {
+            channel_id,
+            next_commitment_number,
+            next_revocation_number,
+            your_last_per_commitment_secret,
+            my_current_per_commitment_point,
+        })
+    }
+}
+
+impl WireMessage for ChannelReestablish {
+    const TYPE: u16 = 136;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnnouncementSignatures {
+    pub channel_id: [u8; 32],
+    pub short_channel_id: ShortChannelId,
+    pub node_signature: [u8; 64],
+    pub bitcoin_signature: [u8; 64],
+}
+
+impl WireType for AnnouncementSignatures {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.channel_id.write(buf)?;
+        self.short_channel_id.write(buf)?;
+        self.node_signature.write(buf)?;
+        self.bitcoin_signature.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let channel_id: [u8; 32] = r.read()?;
+        let short_channel_id: ShortChannelId = r.read()?;
+        let node_signature: [u8; 64] = r.read()?;
+        let bitcoin_signature: [u8; 64] = r.read()?;
+        Ok(AnnouncementSignatures {
+            channel_id,
+            short_channel_id,
+            node_signature,
+            bitcoin_signature,
+        })
+    }
+}
+
+impl WireMessage for AnnouncementSignatures {
+    const TYPE: u16 = 259;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelAnnouncement {
+    pub node_signature_1: [u8; 64],
+    pub node_signature_2: [u8; 64],
+    pub bitcoin_signature_1: [u8; 64],
+    pub bitcoin_signature_2: [u8; 64],
+    pub features: Vec<u8>,
+    pub chain_hash: BlockHash,
+    pub short_channel_id: ShortChannelId,
+    pub node_id_1: PublicKey,
+    pub node_id_2: PublicKey,
+    pub bitcoin_key_1: PublicKey,
+    pub bitcoin_key_2: PublicKey,
+}
+
+impl WireType for ChannelAnnouncement {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.node_signature_1.write(buf)?;
+        self.node_signature_2.write(buf)?;
+        self.bitcoin_signature_1.write(buf)?;
+        self.bitcoin_signature_2.write(buf)?;
+        len::<u16>(self.features.len())?.write(buf)?;
+        buf.extend_from_slice(&self.features);
+        self.chain_hash.write(buf)?;
+        self.short_channel_id.write(buf)?;
+        self.node_id_1.write(buf)?;
+        self.node_id_2.write(buf)?;
+        self.bitcoin_key_1.write(buf)?;
+        self.bitcoin_key_2.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let node_signature_1: [u8; 64] = r.read()?;
+        let node_signature_2: [u8; 64] = r.read()?;
+        let bitcoin_signature_1: [u8; 64] = r.read()?;
+        let bitcoin_signature_2: [u8; 64] = r.read()?;
+        let len: u16 = r.read()?;
+        let features: Vec<u8> = r.take(len as usize)?.to_vec();
+        let chain_hash: BlockHash = r.read()?;
+        let short_channel_id: ShortChannelId = r.read()?;
+        let node_id_1: PublicKey = r.read()?;
+        let node_id_2: PublicKey = r.read()?;
+        let bitcoin_key_1: PublicKey = r.read()?;
+        let bitcoin_key_2: PublicKey = r.read()?;
+        Ok(ChannelAnnouncement {
+            node_signature_1,
+            node_signature_2,
+            bitcoin_signature_1,
+            bitcoin_signature_2,
+            features,
+            chain_hash,
+            short_channel_id,
+            node_id_1,
+            node_id_2,
+            bitcoin_key_1,
+            bitcoin_key_2,
+        })
+    }
+}
+
+impl WireMessage for ChannelAnnouncement {
+    const TYPE: u16 = 256;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeAnnouncement {
+    pub signature: [u8; 64],
+    pub features: Vec<u8>,
+    pub timestamp: u32,
+    pub node_id: PublicKey,
+    pub rgb_color: [u8; 3],
+    pub alias: [u8; 32],
+    pub addresses: Vec<u8>,
+    pub tlvs: NodeAnnTlvs,
+}
+
+impl WireType for NodeAnnouncement {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.signature.write(buf)?;
+        len::<u16>(self.features.len())?.write(buf)?;
+        buf.extend_from_slice(&self.features);
+        self.timestamp.write(buf)?;
+        self.node_id.write(buf)?;
+        self.rgb_color.write(buf)?;
+        self.alias.write(buf)?;
+        len::<u16>(self.addresses.len())?.write(buf)?;
+        buf.extend_from_slice(&self.addresses);
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let signature: [u8; 64] = r.read()?;
+        let flen: u16 = r.read()?;
+        let features: Vec<u8> = r.take(flen as usize)?.to_vec();
+        let timestamp: u32 = r.read()?;
+        let node_id: PublicKey = r.read()?;
+        let rgb_color: [u8; 3] = r.read()?;
+        let alias: [u8; 32] = r.read()?;
+        let addrlen: u16 = r.read()?;
+        let addresses: Vec<u8> = r.take(addrlen as usize)?.to_vec();
+        let tlvs: NodeAnnTlvs = r.read()?;
+        Ok(NodeAnnouncement {
+            signature,
+            features,
+            timestamp,
+            node_id,
+            rgb_color,
+            alias,
+            addresses,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for NodeAnnouncement {
+    const TYPE: u16 = 257;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelUpdate {
+    pub signature: [u8; 64],
+    pub chain_hash: BlockHash,
+    pub short_channel_id: ShortChannelId,
+    pub timestamp: u32,
+    pub message_flags: u8,
+    pub channel_flags: u8,
+    pub cltv_expiry_delta: u16,
+    pub htlc_minimum_msat: Amount,
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub htlc_maximum_msat: Amount,
+}
+
+impl WireType for ChannelUpdate {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.signature.write(buf)?;
+        self.chain_hash.write(buf)?;
+        self.short_channel_id.write(buf)?;
+        self.timestamp.write(buf)?;
+        self.message_flags.write(buf)?;
+        self.channel_flags.write(buf)?;
+        self.cltv_expiry_delta.write(buf)?;
+        self.htlc_minimum_msat.write(buf)?;
+        self.fee_base_msat.write(buf)?;
+        self.fee_proportional_millionths.write(buf)?;
+        self.htlc_maximum_msat.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let signature: [u8; 64] = r.read()?;
+        let chain_hash: BlockHash = r.read()?;
+        let short_channel_id: ShortChannelId = r.read()?;
+        let timestamp: u32 = r.read()?;
+        let message_flags: u8 = r.read()?;
+        let channel_flags: u8 = r.read()?;
+        let cltv_expiry_delta: u16 = r.read()?;
+        let htlc_minimum_msat: Amount = r.read()?;
+        let fee_base_msat: u32 = r.read()?;
+        let fee_proportional_millionths: u32 = r.read()?;
+        let htlc_maximum_msat: Amount = r.read()?;
+        Ok(ChannelUpdate {
+            signature,
+            chain_hash,
+            short_channel_id,
+            timestamp,
+            message_flags,
+            channel_flags,
+            cltv_expiry_delta,
+            htlc_minimum_msat,
+            fee_base_msat,
+            fee_proportional_millionths,
+            htlc_maximum_msat,
+        })
+    }
+}
+
+impl WireMessage for ChannelUpdate {
+    const TYPE: u16 = 258;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryShortChannelIds {
+    pub chain_hash: BlockHash,
+    pub encoded_short_ids: Vec<u8>,
+    pub tlvs: QueryShortChannelIdsTlvs,
+}
+
+impl WireType for QueryShortChannelIds {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        len::<u16>(self.encoded_short_ids.len())?.write(buf)?;
+        buf.extend_from_slice(&self.encoded_short_ids);
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let len: u16 = r.read()?;
+        let encoded_short_ids: Vec<u8> = r.take(len as usize)?.to_vec();
+        let tlvs: QueryShortChannelIdsTlvs = r.read()?;
+        Ok(QueryShortChannelIds {
+            chain_hash,
+            encoded_short_ids,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for QueryShortChannelIds {
+    const TYPE: u16 = 261;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplyShortChannelIdsEnd {
+    pub chain_hash: BlockHash,
+    pub full_information: u8,
+}
+
+impl WireType for ReplyShortChannelIdsEnd {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.full_information.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let full_information: u8 = r.read()?;
+        Ok(ReplyShortChannelIdsEnd {
+            chain_hash,
+            full_information,
+        })
+    }
+}
+
+impl WireMessage for ReplyShortChannelIdsEnd {
+    const TYPE: u16 = 262;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryChannelRange {
+    pub chain_hash: BlockHash,
+    pub first_blocknum: u32,
+    pub number_of_blocks: u32,
+    pub tlvs: QueryChannelRangeTlvs,
+}
+
+impl WireType for QueryChannelRange {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.first_blocknum.write(buf)?;
+        self.number_of_blocks.write(buf)?;
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let first_blocknum: u32 = r.read()?;
+        let number_of_blocks: u32 = r.read()?;
+        let tlvs: QueryChannelRangeTlvs = r.read()?;
+        Ok(QueryChannelRange {
+            chain_hash,
+            first_blocknum,
+            number_of_blocks,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for QueryChannelRange {
+    const TYPE: u16 = 263;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplyChannelRange {
+    pub chain_hash: BlockHash,
+    pub first_blocknum: u32,
+    pub number_of_blocks: u32,
+    pub sync_complete: u8,
+    pub encoded_short_ids: Vec<u8>,
+    pub tlvs: ReplyChannelRangeTlvs,
+}
+
+impl WireType for ReplyChannelRange {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.first_blocknum.write(buf)?;
+        self.number_of_blocks.write(buf)?;
+        self.sync_complete.write(buf)?;
+        len::<u16>(self.encoded_short_ids.len())?.write(buf)?;
+        buf.extend_from_slice(&self.encoded_short_ids);
+        self.tlvs.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let first_blocknum: u32 = r.read()?;
+        let number_of_blocks: u32 = r.read()?;
+        let sync_complete: u8 = r.read()?;
+        let len: u16 = r.read()?;
+        let encoded_short_ids: Vec<u8> = r.take(len as usize)?.to_vec();
+        let tlvs: ReplyChannelRangeTlvs = r.read()?;
+        Ok(ReplyChannelRange {
+            chain_hash,
+            first_blocknum,
+            number_of_blocks,
+            sync_complete,
+            encoded_short_ids,
+            tlvs,
+        })
+    }
+}
+
+impl WireMessage for ReplyChannelRange {
+    const TYPE: u16 = 264;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GossipTimestampFilter {
+    pub chain_hash: BlockHash,
+    pub first_timestamp: u32,
+    pub timestamp_range: u32,
+}
+
+impl WireType for GossipTimestampFilter {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.chain_hash.write(buf)?;
+        self.first_timestamp.write(buf)?;
+        self.timestamp_range.write(buf)?;
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let chain_hash: BlockHash = r.read()?;
+        let first_timestamp: u32 = r.read()?;
+        let timestamp_range: u32 = r.read()?;
+        Ok(GossipTimestampFilter {
+            chain_hash,
+            first_timestamp,
+            timestamp_range,
+        })
+    }
+}
+
+impl WireMessage for GossipTimestampFilter {
+    const TYPE: u16 = 265;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnionMessage {
+    pub blinding: PublicKey,
+    pub onionmsg: Vec<u8>,
+}
+
+impl WireType for OnionMessage {
+    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {
+        self.blinding.write(buf)?;
+        len::<u16>(self.onionmsg.len())?.write(buf)?;
+        buf.extend_from_slice(&self.onionmsg);
+        Ok(())
+    }
+
+    fn read(r: &mut Reader<'_>) -> Result<Self> {
+        let blinding: PublicKey = r.read()?;
+        let len: u16 = r.read()?;
+        let onionmsg: Vec<u8> = r.take(len as usize)?.to_vec();
+        Ok(OnionMessage {
+            blinding,
+            onionmsg,
+        })
+    }
+}
+
+impl WireMessage for OnionMessage {
+    const TYPE: u16 = 513;
+}
+
+/// Any of the messages above, or one we don't know about.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerMessage {
+    Init(Init),
+    Error(Error),
+    Warning(Warning),
+    Ping(Ping),
+    Pong(Pong),
+    TxAddInput(TxAddInput),
+    TxAddOutput(TxAddOutput),
+    TxRemoveInput(TxRemoveInput),
+    TxRemoveOutput(TxRemoveOutput),
+    TxComplete(TxComplete),
+    TxSignatures(TxSignatures),
+    OpenChannel(OpenChannel),
+    AcceptChannel(AcceptChannel),
+    FundingCreated(FundingCreated),
+    FundingSigned(FundingSigned),
+    ChannelReady(ChannelReady),
+    OpenChannel2(OpenChannel2),
+    AcceptChannel2(AcceptChannel2),
+    InitRbf(InitRbf),
+    AckRbf(AckRbf),
+    Shutdown(Shutdown),
+    ClosingSigned(ClosingSigned),
+    UpdateAddHtlc(UpdateAddHtlc),
+    UpdateFulfillHtlc(UpdateFulfillHtlc),
+    UpdateFailHtlc(UpdateFailHtlc),
+    UpdateFailMalformedHtlc(UpdateFailMalformedHtlc),
+    CommitmentSigned(CommitmentSigned),
+    RevokeAndAck(RevokeAndAck),
+    UpdateFee(UpdateFee),
+    UpdateBlockheight(UpdateBlockheight),
+    ChannelReestablish(ChannelReestablish),
+    AnnouncementSignatures(AnnouncementSignatures),
+    ChannelAnnouncement(ChannelAnnouncement),
+    NodeAnnouncement(NodeAnnouncement),
+    ChannelUpdate(ChannelUpdate),
+    QueryShortChannelIds(QueryShortChannelIds),
+    ReplyShortChannelIdsEnd(ReplyShortChannelIdsEnd),
+    QueryChannelRange(QueryChannelRange),
+    ReplyChannelRange(ReplyChannelRange),
+    GossipTimestampFilter(GossipTimestampFilter),
+    OnionMessage(OnionMessage),
+    Unknown { typ: u16, payload: Vec<u8> },
+}
+
+impl PeerMessage {
+    pub fn typ(&self) -> u16 {
+        match self {
+            PeerMessage::Init(_) => Init::TYPE,
+            PeerMessage::Error(_) => Error::TYPE,
+            PeerMessage::Warning(_) => Warning::TYPE,
+            PeerMessage::Ping(_) => Ping::TYPE,
+            PeerMessage::Pong(_) => Pong::TYPE,
+            PeerMessage::TxAddInput(_) => TxAddInput::TYPE,
+            PeerMessage::TxAddOutput(_) => TxAddOutput::TYPE,
+            PeerMessage::TxRemoveInput(_) => TxRemoveInput::TYPE,
+            PeerMessage::TxRemoveOutput(_) => TxRemoveOutput::TYPE,
+            PeerMessage::TxComplete(_) => TxComplete::TYPE,
+            PeerMessage::TxSignatures(_) => TxSignatures::TYPE,
+            PeerMessage::OpenChannel(_) => OpenChannel::TYPE,
+            PeerMessage::AcceptChannel(_) => AcceptChannel::TYPE,
+            PeerMessage::FundingCreated(_) => FundingCreated::TYPE,
+            PeerMessage::FundingSigned(_) => FundingSigned::TYPE,
+            PeerMessage::ChannelReady(_) => ChannelReady::TYPE,
+            PeerMessage::OpenChannel2(_) => OpenChannel2::TYPE,
+            PeerMessage::AcceptChannel2(_) => AcceptChannel2::TYPE,
+            PeerMessage::InitRbf(_) => InitRbf::TYPE,
+            PeerMessage::AckRbf(_) => AckRbf::TYPE,
+            PeerMessage::Shutdown(_) => Shutdown::TYPE,
+            PeerMessage::ClosingSigned(_) => ClosingSigned::TYPE,
+            PeerMessage::UpdateAddHtlc(_) => UpdateAddHtlc::TYPE,
+            PeerMessage::UpdateFulfillHtlc(_) => UpdateFulfillHtlc::TYPE,
+            PeerMessage::UpdateFailHtlc(_) => UpdateFailHtlc::TYPE,
+            PeerMessage::UpdateFailMalformedHtlc(_) => UpdateFailMalformedHtlc::TYPE,
+            PeerMessage::CommitmentSigned(_) => CommitmentSigned::TYPE,
+            PeerMessage::RevokeAndAck(_) => RevokeAndAck::TYPE,
+            PeerMessage::UpdateFee(_) => UpdateFee::TYPE,
+            PeerMessage::UpdateBlockheight(_) => UpdateBlockheight::TYPE,
+            PeerMessage::ChannelReestablish(_) => ChannelReestablish::TYPE,
+            PeerMessage::AnnouncementSignatures(_) => AnnouncementSignatures::TYPE,
+            PeerMessage::ChannelAnnouncement(_) => ChannelAnnouncement::TYPE,
+            PeerMessage::NodeAnnouncement(_) => NodeAnnouncement::TYPE,
+            PeerMessage::ChannelUpdate(_) => ChannelUpdate::TYPE,
+            PeerMessage::QueryShortChannelIds(_) => QueryShortChannelIds::TYPE,
+            PeerMessage::ReplyShortChannelIdsEnd(_) => ReplyShortChannelIdsEnd::TYPE,
+            PeerMessage::QueryChannelRange(_) => QueryChannelRange::TYPE,
+            PeerMessage::ReplyChannelRange(_) => ReplyChannelRange::TYPE,
+            PeerMessage::GossipTimestampFilter(_) => GossipTimestampFilter::TYPE,
+            PeerMessage::OnionMessage(_) => OnionMessage::TYPE,
+            PeerMessage::Unknown { typ, .. } => *typ,
+        }
+    }
+
+    pub fn to_wire(&self) -> Result<Vec<u8>> {
+        match self {
+            PeerMessage::Init(m) => m.to_wire(),
+            PeerMessage::Error(m) => m.to_wire(),
+            PeerMessage::Warning(m) => m.to_wire(),
+            PeerMessage::Ping(m) => m.to_wire(),
+            PeerMessage::Pong(m) => m.to_wire(),
+            PeerMessage::TxAddInput(m) => m.to_wire(),
+            PeerMessage::TxAddOutput(m) => m.to_wire(),
+            PeerMessage::TxRemoveInput(m) => m.to_wire(),
+            PeerMessage::TxRemoveOutput(m) => m.to_wire(),
+            PeerMessage::TxComplete(m) => m.to_wire(),
+            PeerMessage::TxSignatures(m) => m.to_wire(),
+            PeerMessage::OpenChannel(m) => m.to_wire(),
+            PeerMessage::AcceptChannel(m) => m.to_wire(),
+            PeerMessage::FundingCreated(m) => m.to_wire(),
+            PeerMessage::FundingSigned(m) => m.to_wire(),
+            PeerMessage::ChannelReady(m) => m.to_wire(),
+            PeerMessage::OpenChannel2(m) => m.to_wire(),
+            PeerMessage::AcceptChannel2(m) => m.to_wire(),
+            PeerMessage::InitRbf(m) => m.to_wire(),
+            PeerMessage::AckRbf(m) => m.to_wire(),
+            PeerMessage::Shutdown(m) => m.to_wire(),
+            PeerMessage::ClosingSigned(m) => m.to_wire(),
+            PeerMessage::UpdateAddHtlc(m) => m.to_wire(),
+            PeerMessage::UpdateFulfillHtlc(m) => m.to_wire(),
+            PeerMessage::UpdateFailHtlc(m) => m.to_wire(),
+            PeerMessage::UpdateFailMalformedHtlc(m) => m.to_wire(),
+            PeerMessage::CommitmentSigned(m) => m.to_wire(),
+            PeerMessage::RevokeAndAck(m) => m.to_wire(),
+            PeerMessage::UpdateFee(m) => m.to_wire(),
+            PeerMessage::UpdateBlockheight(m) => m.to_wire(),
+            PeerMessage::ChannelReestablish(m) => m.to_wire(),
+            PeerMessage::AnnouncementSignatures(m) => m.to_wire(),
+            PeerMessage::ChannelAnnouncement(m) => m.to_wire(),
+            PeerMessage::NodeAnnouncement(m) => m.to_wire(),
+            PeerMessage::ChannelUpdate(m) => m.to_wire(),
+            PeerMessage::QueryShortChannelIds(m) => m.to_wire(),
+            PeerMessage::ReplyShortChannelIdsEnd(m) => m.to_wire(),
+            PeerMessage::QueryChannelRange(m) => m.to_wire(),
+            PeerMessage::ReplyChannelRange(m) => m.to_wire(),
+            PeerMessage::GossipTimestampFilter(m) => m.to_wire(),
+            PeerMessage::OnionMessage(m) => m.to_wire(),
+            PeerMessage::Unknown { typ, payload } => {
+                let mut buf = typ.to_be_bytes().to_vec();
+                buf.extend_from_slice(payload);
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Decode a message. Messages of an unknown type are returned
+    /// as [`Unknown`](Self::Unknown), it's up to the caller to
+    /// reject those with an even type.
+    pub fn from_wire(buf: &[u8]) -> Result<PeerMessage> {
+        let mut r = Reader::new(buf);
+        let typ: u16 = r.read()?;
+        Ok(match typ {
+            Init::TYPE => PeerMessage::Init(r.read()?),
+            Error::TYPE => PeerMessage::Error(r.read()?),
+            Warning::TYPE => PeerMessage::Warning(r.read()?),
+            Ping::TYPE => PeerMessage::Ping(r.read()?),
+            Pong::TYPE => PeerMessage::Pong(r.read()?),
+            TxAddInput::TYPE => PeerMessage::TxAddInput(r.read()?),
+            TxAddOutput::TYPE => PeerMessage::TxAddOutput(r.read()?),
+            TxRemoveInput::TYPE => PeerMessage::TxRemoveInput(r.read()?),
+            TxRemoveOutput::TYPE => PeerMessage::TxRemoveOutput(r.read()?),
+            TxComplete::TYPE => PeerMessage::TxComplete(r.read()?),
+            TxSignatures::TYPE => PeerMessage::TxSignatures(r.read()?),
+            OpenChannel::TYPE => PeerMessage::OpenChannel(r.read()?),
+            AcceptChannel::TYPE => PeerMessage::AcceptChannel(r.read()?),
+            FundingCreated::TYPE => PeerMessage::FundingCreated(r.read()?),
+            FundingSigned::TYPE => PeerMessage::FundingSigned(r.read()?),
+            ChannelReady::TYPE => PeerMessage::ChannelReady(r.read()?),
+            OpenChannel2::TYPE => PeerMessage::OpenChannel2(r.read()?),
+            AcceptChannel2::TYPE => PeerMessage::AcceptChannel2(r.read()?),
+            InitRbf::TYPE => PeerMessage::InitRbf(r.read()?),
+            AckRbf::TYPE => PeerMessage::AckRbf(r.read()?),
+            Shutdown::TYPE => PeerMessage::Shutdown(r.read()?),
+            ClosingSigned::TYPE => PeerMessage::ClosingSigned(r.read()?),
+            UpdateAddHtlc::TYPE => PeerMessage::UpdateAddHtlc(r.read()?),
+            UpdateFulfillHtlc::TYPE => PeerMessage::UpdateFulfillHtlc(r.read()?),
+            UpdateFailHtlc::TYPE => PeerMessage::UpdateFailHtlc(r.read()?),
+            UpdateFailMalformedHtlc::TYPE => PeerMessage::UpdateFailMalformedHtlc(r.read()?),
+            CommitmentSigned::TYPE => PeerMessage::CommitmentSigned(r.read()?),
+            RevokeAndAck::TYPE => PeerMessage::RevokeAndAck(r.read()?),
+            UpdateFee::TYPE => PeerMessage::UpdateFee(r.read()?),
+            UpdateBlockheight::TYPE => PeerMessage::UpdateBlockheight(r.read()?),
+            ChannelReestablish::TYPE => PeerMessage::ChannelReestablish(r.read()?),
+            AnnouncementSignatures::TYPE => PeerMessage::AnnouncementSignatures(r.read()?),
+            ChannelAnnouncement::TYPE => PeerMessage::ChannelAnnouncement(r.read()?),
+            NodeAnnouncement::TYPE => PeerMessage::NodeAnnouncement(r.read()?),
+            ChannelUpdate::TYPE => PeerMessage::ChannelUpdate(r.read()?),
+            QueryShortChannelIds::TYPE => PeerMessage::QueryShortChannelIds(r.read()?),
+            ReplyShortChannelIdsEnd::TYPE => PeerMessage::ReplyShortChannelIdsEnd(r.read()?),
+            QueryChannelRange::TYPE => PeerMessage::QueryChannelRange(r.read()?),
+            ReplyChannelRange::TYPE => PeerMessage::ReplyChannelRange(r.read()?),
+            GossipTimestampFilter::TYPE => PeerMessage::GossipTimestampFilter(r.read()?),
+            OnionMessage::TYPE => PeerMessage::OnionMessage(r.read()?),
+            _ => PeerMessage::Unknown {
+                typ,
+                payload: r.rest().to_vec(),
+            },
+        })
+    }
+}
+
+impl From<Init> for PeerMessage {
+    fn from(m: Init) -> Self {
+        PeerMessage::Init(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Init {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Init(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<Error> for PeerMessage {
+    fn from(m: Error) -> Self {
+        PeerMessage::Error(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Error {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Error(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<Warning> for PeerMessage {
+    fn from(m: Warning) -> Self {
+        PeerMessage::Warning(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Warning {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Warning(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<Ping> for PeerMessage {
+    fn from(m: Ping) -> Self {
+        PeerMessage::Ping(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Ping {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Ping(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<Pong> for PeerMessage {
+    fn from(m: Pong) -> Self {
+        PeerMessage::Pong(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Pong {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Pong(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxAddInput> for PeerMessage {
+    fn from(m: TxAddInput) -> Self {
+        PeerMessage::TxAddInput(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxAddInput {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxAddInput(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxAddOutput> for PeerMessage {
+    fn from(m: TxAddOutput) -> Self {
+        PeerMessage::TxAddOutput(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxAddOutput {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxAddOutput(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxRemoveInput> for PeerMessage {
+    fn from(m: TxRemoveInput) -> Self {
+        PeerMessage::TxRemoveInput(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxRemoveInput {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxRemoveInput(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxRemoveOutput> for PeerMessage {
+    fn from(m: TxRemoveOutput) -> Self {
+        PeerMessage::TxRemoveOutput(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxRemoveOutput {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxRemoveOutput(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxComplete> for PeerMessage {
+    fn from(m: TxComplete) -> Self {
+        PeerMessage::TxComplete(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxComplete {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxComplete(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<TxSignatures> for PeerMessage {
+    fn from(m: TxSignatures) -> Self {
+        PeerMessage::TxSignatures(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for TxSignatures {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::TxSignatures(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<OpenChannel> for PeerMessage {
+    fn from(m: OpenChannel) -> Self {
+        PeerMessage::OpenChannel(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for OpenChannel {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::OpenChannel(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<AcceptChannel> for PeerMessage {
+    fn from(m: AcceptChannel) -> Self {
+        PeerMessage::AcceptChannel(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for AcceptChannel {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::AcceptChannel(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<FundingCreated> for PeerMessage {
+    fn from(m: FundingCreated) -> Self {
+        PeerMessage::FundingCreated(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for FundingCreated {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::FundingCreated(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<FundingSigned> for PeerMessage {
+    fn from(m: FundingSigned) -> Self {
+        PeerMessage::FundingSigned(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for FundingSigned {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::FundingSigned(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ChannelReady> for PeerMessage {
+    fn from(m: ChannelReady) -> Self {
+        PeerMessage::ChannelReady(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ChannelReady {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ChannelReady(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<OpenChannel2> for PeerMessage {
+    fn from(m: OpenChannel2) -> Self {
+        PeerMessage::OpenChannel2(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for OpenChannel2 {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::OpenChannel2(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<AcceptChannel2> for PeerMessage {
+    fn from(m: AcceptChannel2) -> Self {
+        PeerMessage::AcceptChannel2(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for AcceptChannel2 {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::AcceptChannel2(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<InitRbf> for PeerMessage {
+    fn from(m: InitRbf) -> Self {
+        PeerMessage::InitRbf(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for InitRbf {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::InitRbf(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<AckRbf> for PeerMessage {
+    fn from(m: AckRbf) -> Self {
+        PeerMessage::AckRbf(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for AckRbf {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::AckRbf(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<Shutdown> for PeerMessage {
+    fn from(m: Shutdown) -> Self {
+        PeerMessage::Shutdown(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for Shutdown {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::Shutdown(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ClosingSigned> for PeerMessage {
+    fn from(m: ClosingSigned) -> Self {
+        PeerMessage::ClosingSigned(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ClosingSigned {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ClosingSigned(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateAddHtlc> for PeerMessage {
+    fn from(m: UpdateAddHtlc) -> Self {
+        PeerMessage::UpdateAddHtlc(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateAddHtlc {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateAddHtlc(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateFulfillHtlc> for PeerMessage {
+    fn from(m: UpdateFulfillHtlc) -> Self {
+        PeerMessage::UpdateFulfillHtlc(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateFulfillHtlc {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateFulfillHtlc(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateFailHtlc> for PeerMessage {
+    fn from(m: UpdateFailHtlc) -> Self {
+        PeerMessage::UpdateFailHtlc(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateFailHtlc {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateFailHtlc(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateFailMalformedHtlc> for PeerMessage {
+    fn from(m: UpdateFailMalformedHtlc) -> Self {
+        PeerMessage::UpdateFailMalformedHtlc(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateFailMalformedHtlc {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateFailMalformedHtlc(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<CommitmentSigned> for PeerMessage {
+    fn from(m: CommitmentSigned) -> Self {
+        PeerMessage::CommitmentSigned(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for CommitmentSigned {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::CommitmentSigned(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<RevokeAndAck> for PeerMessage {
+    fn from(m: RevokeAndAck) -> Self {
+        PeerMessage::RevokeAndAck(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for RevokeAndAck {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::RevokeAndAck(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateFee> for PeerMessage {
+    fn from(m: UpdateFee) -> Self {
+        PeerMessage::UpdateFee(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateFee {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateFee(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<UpdateBlockheight> for PeerMessage {
+    fn from(m: UpdateBlockheight) -> Self {
+        PeerMessage::UpdateBlockheight(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for UpdateBlockheight {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::UpdateBlockheight(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ChannelReestablish> for PeerMessage {
+    fn from(m: ChannelReestablish) -> Self {
+        PeerMessage::ChannelReestablish(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ChannelReestablish {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ChannelReestablish(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<AnnouncementSignatures> for PeerMessage {
+    fn from(m: AnnouncementSignatures) -> Self {
+        PeerMessage::AnnouncementSignatures(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for AnnouncementSignatures {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::AnnouncementSignatures(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ChannelAnnouncement> for PeerMessage {
+    fn from(m: ChannelAnnouncement) -> Self {
+        PeerMessage::ChannelAnnouncement(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ChannelAnnouncement {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ChannelAnnouncement(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<NodeAnnouncement> for PeerMessage {
+    fn from(m: NodeAnnouncement) -> Self {
+        PeerMessage::NodeAnnouncement(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for NodeAnnouncement {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::NodeAnnouncement(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ChannelUpdate> for PeerMessage {
+    fn from(m: ChannelUpdate) -> Self {
+        PeerMessage::ChannelUpdate(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ChannelUpdate {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ChannelUpdate(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<QueryShortChannelIds> for PeerMessage {
+    fn from(m: QueryShortChannelIds) -> Self {
+        PeerMessage::QueryShortChannelIds(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for QueryShortChannelIds {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::QueryShortChannelIds(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ReplyShortChannelIdsEnd> for PeerMessage {
+    fn from(m: ReplyShortChannelIdsEnd) -> Self {
+        PeerMessage::ReplyShortChannelIdsEnd(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ReplyShortChannelIdsEnd {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ReplyShortChannelIdsEnd(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<QueryChannelRange> for PeerMessage {
+    fn from(m: QueryChannelRange) -> Self {
+        PeerMessage::QueryChannelRange(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for QueryChannelRange {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::QueryChannelRange(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<ReplyChannelRange> for PeerMessage {
+    fn from(m: ReplyChannelRange) -> Self {
+        PeerMessage::ReplyChannelRange(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for ReplyChannelRange {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::ReplyChannelRange(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<GossipTimestampFilter> for PeerMessage {
+    fn from(m: GossipTimestampFilter) -> Self {
+        PeerMessage::GossipTimestampFilter(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for GossipTimestampFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::GossipTimestampFilter(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
+
+impl From<OnionMessage> for PeerMessage {
+    fn from(m: OnionMessage) -> Self {
+        PeerMessage::OnionMessage(m)
+    }
+}
+
+impl TryFrom<PeerMessage> for OnionMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(m: PeerMessage) -> Result<Self> {
+        match m {
+            PeerMessage::OnionMessage(m) => Ok(m),
+            m => Err(anyhow!("Expected message type {}, got {}", Self::TYPE, m.typ())),
+        }
+    }
+}
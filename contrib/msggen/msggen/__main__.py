@@ -2,9 +2,10 @@ import json
 import os
 import argparse
 from pathlib import Path
-from msggen.gen.grpc import GrpcGenerator, GrpcConverterGenerator, GrpcUnconverterGenerator, GrpcServerGenerator
+from msggen.gen.grpc import GrpcGenerator, GrpcConverterGenerator, GrpcUnconverterGenerator, GrpcServerGenerator, GrpcClientGenerator
 from msggen.gen.grpc2py import Grpc2PyGenerator
 from msggen.gen.rust import RustGenerator
+from msggen.gen.wire import WireGenerator
 from msggen.gen.generator import GeneratorChain
 from msggen.utils import load_jsonrpc_service
 
@@ -25,6 +26,10 @@ def add_handler_gen_grpc(generator_chain: GeneratorChain, meta):
     dest = open(fname, "w")
     generator_chain.add_generator(GrpcServerGenerator(dest))
 
+    fname = Path("cln-grpc") / "src" / "client.rs"
+    dest = open(fname, "w")
+    generator_chain.add_generator(GrpcClientGenerator(dest))
+
 
 def add_handler_get_grpc2py(generator_chain: GeneratorChain):
     fname = Path("contrib") / "pyln-testing" / "pyln" / "testing" / "grpc2py.py"
@@ -38,6 +43,10 @@ def add_handler_gen_rust_jsonrpc(generator_chain: GeneratorChain):
     generator_chain.add_generator(RustGenerator(dest))
 
 
+def add_handler_gen_rust_wire(generator_chain: GeneratorChain, rootdir: Path):
+    generator_chain.add_generator(WireGenerator(rootdir))
+
+
 def load_msggen_meta():
     meta = json.load(open('.msggen.json', 'r'))
     return meta
@@ -59,6 +68,7 @@ def run(rootdir: Path):
     add_handler_gen_grpc(generator_chain, meta)
     add_handler_gen_rust_jsonrpc(generator_chain)
     add_handler_get_grpc2py(generator_chain)
+    add_handler_gen_rust_wire(generator_chain, rootdir)
 
     generator_chain.generate(service)
 
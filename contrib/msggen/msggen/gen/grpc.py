@@ -1,6 +1,7 @@
 # A grpc model
 from msggen.model import ArrayField, Field, CompositeField, EnumField, PrimitiveField, Service
 from msggen.gen import IGenerator
+from msggen.gen.rust import overrides as rust_overrides
 from typing import TextIO, List, Dict, Any
 from textwrap import indent, dedent
 import re
@@ -17,6 +18,7 @@ typemap = {
     'pubkey': 'bytes',
     'short_channel_id': 'string',
     'signature': 'bytes',
+    'bip340sig': 'string',
     'string': 'string',
     'txid': 'bytes',
     'u8': 'uint32',  # Yep, this is the smallest integer type in grpc...
@@ -30,6 +32,7 @@ typemap = {
     "outputdesc": "OutputDesc",
     "secret": "bytes",
     "hash": "bytes",
+    "Rune": "string",
 }
 
 
@@ -252,6 +255,10 @@ class GrpcConverterGenerator(IGenerator):
 
     def generate_composite(self, prefix, field: CompositeField):
         """Generates the conversions from JSON-RPC to GRPC.
+
+        Requests are converted on the client, from caller input whose
+        hex fields are plain strings, so their conversions are
+        `TryFrom` and return an `RpcError` for malformed fields.
         """
         if overrides.get(field.path, "") is None:
             return
@@ -265,13 +272,24 @@ class GrpcConverterGenerator(IGenerator):
                 self.generate_composite(prefix, f)
 
         pbname = self.to_camel_case(field.typename)
+        fallible = prefix == "requests"
         # And now we can convert the current field:
-        self.write(f"""\
-        #[allow(unused_variables)]
-        impl From<{prefix}::{field.typename}> for pb::{pbname} {{
-            fn from(c: {prefix}::{field.typename}) -> Self {{
-                Self {{
-        """)
+        if fallible:
+            self.write(f"""\
+            #[allow(unused_variables)]
+            impl TryFrom<{prefix}::{field.typename}> for pb::{pbname} {{
+                type Error = RpcError;
+
+                fn try_from(c: {prefix}::{field.typename}) -> Result<Self, RpcError> {{
+                    Ok(Self {{
+            """)
+        else:
+            self.write(f"""\
+            #[allow(unused_variables)]
+            impl From<{prefix}::{field.typename}> for pb::{pbname} {{
+                fn from(c: {prefix}::{field.typename}) -> Self {{
+                    Self {{
+            """)
 
         for f in field.fields:
             if overrides.get(f.path, "") is None:
@@ -279,16 +297,54 @@ class GrpcConverterGenerator(IGenerator):
 
             name = f.normalized()
             name = re.sub(r'(?<!^)(?=[A-Z])', '_', name).lower()
-            if isinstance(f, ArrayField):
-                typ = f.itemtype.typename
+            err = f'|e| invalid("{f.path}", e)'
+            if isinstance(f, ArrayField) and fallible:
+                typ = overrides.get(f.itemtype.path) or f.itemtype.typename
+                # The conversion of the whole array, as an iterator
+                # `it`, `None` if the elements have the same type on
+                # both sides.
+                collect = '.collect::<Result<_, _>>()'
+                mapping = {
+                    'hex': f'it.map(hex::decode){collect}.map_err({err})?',
+                    'u32': None,
+                    'string': None,
+                    'secret': 'it.map(|i| i.to_vec()).collect()',
+                    'pubkey': 'it.map(|i| i.serialize().to_vec()).collect()',
+                }.get(typ, 'it.map(|i| i.into()).collect()')
+                if isinstance(f.itemtype, CompositeField):
+                    mapping = f'it.map(TryInto::try_into){collect}?'
+
+                if mapping is None and f.required:
+                    rhs = f'c.{name}'
+                elif mapping is None:
+                    rhs = f'c.{name}.unwrap_or_default()'
+                elif f.required:
+                    rhs = mapping.replace('it.', f'c.{name}.into_iter().', 1)
+                else:
+                    rhs = mapping.replace('it.', f'c.{name}.map(|arr| arr.into_iter().', 1)
+                    if '?' in mapping:
+                        rhs = rhs[:-1] + ').transpose()?.unwrap_or_default()'
+                    else:
+                        rhs += ').unwrap_or_default()'
+                self.write(f"{name}: {rhs}, // Rule #3 for type {typ}\n", numindent=3)
+            elif isinstance(f, ArrayField):
+                typ = overrides.get(f.itemtype.path) or f.itemtype.typename
                 # The inner conversion applied to each element in the
-                # array. The current item is called `i`
+                # array. The current item is called `i`, `None` if
+                # the elements have the same type on both sides.
                 mapping = {
                     'hex': f'hex::decode(i).unwrap()',
+                    'u32': None,
+                    'string': None,
                     'secret': f'i.to_vec()',
+                    'pubkey': f'i.serialize().to_vec()',
                 }.get(typ, f'i.into()')
 
-                if f.required:
+                if mapping is None and f.required:
+                    self.write(f"{name}: c.{name}, // Rule #3 for type {typ} \n", numindent=3)
+                elif mapping is None:
+                    self.write(f"{name}: c.{name}.unwrap_or_default(), // Rule #3 \n", numindent=3)
+                elif f.required:
                     self.write(f"{name}: c.{name}.into_iter().map(|i| {mapping}).collect(), // Rule #3 for type {typ} \n", numindent=3)
                 else:
                     self.write(f"{name}: c.{name}.map(|arr| arr.into_iter().map(|i| {mapping}).collect()).unwrap_or(vec![]), // Rule #3 \n", numindent=3)
@@ -316,38 +372,71 @@ class GrpcConverterGenerator(IGenerator):
                     'hex?': f'c.{name}.map(|v| hex::decode(v).unwrap())',
                     'txid': f'hex::decode(&c.{name}).unwrap()',
                     'txid?': f'c.{name}.map(|v| hex::decode(v).unwrap())',
+                    'signature': f'hex::decode(&c.{name}).unwrap()',
+                    'signature?': f'c.{name}.map(|v| hex::decode(v).unwrap())',
                     'short_channel_id': f'c.{name}.to_string()',
                     'short_channel_id?': f'c.{name}.map(|v| v.to_string())',
                     'hash': f'c.{name}.to_vec()',
                     'hash?': f'c.{name}.map(|v| v.to_vec())',
                     'secret': f'c.{name}.to_vec()',
                     'secret?': f'c.{name}.map(|v| v.to_vec())',
+                    'DecodeRoutehintList?': f'c.{name}.map(|drl| drl.into())',
+                    'PluginOptions?': f'c.{name}.map(|o| o.into())',
+                    'Rune': f'c.{name}.to_string()',
+                    'Rune?': f'c.{name}.map(|v| v.to_string())',
+                    'outpoint': f'Some(c.{name}.into())',
+                    'outpoint?': f'c.{name}.map(|o| o.into())',
+                    'msat_or_all': f'Some(c.{name}.into())',
+                    'msat_or_all?': f'c.{name}.map(|o| o.into())',
+                    'msat_or_any': f'Some(c.{name}.into())',
+                    'msat_or_any?': f'c.{name}.map(|o| o.into())',
+                    'feerate': f'Some(c.{name}.into())',
+                    'feerate?': f'c.{name}.map(|o| o.into())',
+                    'RoutehintList?': f'c.{name}.map(|rl| rl.into())',
+                    'TlvStream?': f'c.{name}.map(|s| s.into())',
+                    'RuneRestrictions?': f'c.{name}.map(|r| r.into())',
                 }.get(
                     typ,
                     f'c.{name}'  # default to just assignment
                 )
+                if fallible and typ.rstrip('?') in ('hex', 'txid', 'signature'):
+                    if f.required:
+                        rhs = f'hex::decode(&c.{name}).map_err({err})?'
+                    else:
+                        rhs = f'c.{name}.map(hex::decode).transpose().map_err({err})?'
 
                 self.write(f"{name}: {rhs}, // Rule #2 for type {typ}\n", numindent=3)
 
             elif isinstance(f, CompositeField):
-                rhs = ""
-                if f.required:
+                if fallible and f.required:
+                    rhs = f'Some(c.{name}.try_into()?)'
+                elif fallible:
+                    rhs = f'c.{name}.map(TryInto::try_into).transpose()?'
+                elif f.required:
                     rhs = f'Some(c.{name}.into())'
                 else:
                     rhs = f'c.{name}.map(|v| v.into())'
                 self.write(f"{name}: {rhs},\n", numindent=3)
-        self.write(f"""\
+        if fallible:
+            self.write(f"""\
+                    }})
                 }}
             }}
-        }}
 
-        """)
+            """)
+        else:
+            self.write(f"""\
+                    }}
+                }}
+            }}
+
+            """)
 
     def to_camel_case(self, snake_str):
         components = snake_str.split('_')
         # We capitalize the first letter of each component except the first one
         # with the 'title' method and join them together.
-        return components[0] + ''.join(x.title() for x in components[1:])
+        return components[0] + ''.join(x[:1].upper() + x[1:] for x in components[1:])
 
     def generate_requests(self, service):
         for meth in service.methods:
@@ -373,10 +462,13 @@ class GrpcConverterGenerator(IGenerator):
         use bitcoin::hashes::sha256::Hash as Sha256;
         use bitcoin::hashes::Hash;
         use cln_rpc::primitives::PublicKey;
+        use cln_rpc::RpcError;
+        use crate::status::{invalid, missing};
 
         """)
 
         self.generate_responses(service)
+        self.generate_requests(service)
 
     def write(self, text: str, numindent: int = 0) -> None:
         raw = dedent(text)
@@ -386,11 +478,21 @@ class GrpcConverterGenerator(IGenerator):
         self.dest.write(raw)
 
 
+def snake(f) -> str:
+    """The field name as used in the generated Rust structs."""
+    return re.sub(r'(?<!^)(?=[A-Z])', '_', f.normalized()).lower()
+
+
 class GrpcUnconverterGenerator(GrpcConverterGenerator):
     """Generator to generate the conversions from GRPC to JSON-RPC (for requests).
+
+    The grpc messages come from remote clients, so the conversions
+    are `TryFrom` and return an `RpcError` for malformed fields
+    rather than panicking.
     """
     def generate(self, service: Service):
         self.generate_requests(service)
+        self.generate_responses(service)
 
     def generate_composite(self, prefix, field: CompositeField) -> None:
         # First pass: generate any sub-fields before we generate the
@@ -406,35 +508,62 @@ class GrpcUnconverterGenerator(GrpcConverterGenerator):
 
         pbname = self.to_camel_case(field.typename)
         # And now we can convert the current field:
+        self.write("#[allow(unused_variables)]\n")
+        if any(f.deprecated for f in field.fields):
+            # Deprecated fields are still filled in.
+            self.write("#[allow(deprecated)]\n")
         self.write(f"""\
-        #[allow(unused_variables)]
-        impl From<pb::{pbname}> for {prefix}::{field.typename} {{
-            fn from(c: pb::{pbname}) -> Self {{
-                Self {{
+        impl TryFrom<pb::{pbname}> for {prefix}::{field.typename} {{
+            type Error = RpcError;
+
+            fn try_from(c: pb::{pbname}) -> Result<Self, RpcError> {{
         """)
 
+        # The request structs are `#[non_exhaustive]`, so we pass the
+        # required fields to the builder and assign the optional ones
+        # afterwards.
+        fields = []
         for f in field.fields:
-            name = f.normalized()
-            if isinstance(f, ArrayField):
-                typ = f.itemtype.typename
+            name = snake(f)
+            if overrides.get(f.path, "") is None:
+                # Not part of the grpc interface, but it may still be
+                # in the JSON-RPC one.
+                if rust_overrides.get(f.path, "") is not None:
+                    fields.append((f, "None", ""))
+            elif isinstance(f, ArrayField):
+                typ = overrides.get(f.itemtype.path) or f.itemtype.typename
+                err = f'|e| invalid("{f.path}", e)'
+                # The conversion applied to the elements, `None` if
+                # the elements have the same type on both sides.
                 mapping = {
-                    'hex': f'hex::encode(s)',
-                    'u32': f's',
-                    'secret': f's.try_into().unwrap()'
-                }.get(typ, f's.into()')
-                if f.required:
-                    self.write(f"{name}: c.{name}.into_iter().map(|s| {mapping}).collect(), // Rule #4\n", numindent=3)
-                else:
-                    self.write(f"{name}: Some(c.{name}.into_iter().map(|s| {mapping}).collect()), // Rule #4\n", numindent=3)
+                    'hex': '.map(hex::encode).collect()',
+                    'u32': None,
+                    'string': None,
+                    'secret': f'.map(TryInto::try_into).collect::<Result<_, _>>().map_err({err})?',
+                    'pubkey': f'.map(|s| PublicKey::from_slice(&s)).collect::<Result<_, _>>().map_err({err})?',
+                    'outputdesc': '.map(TryInto::try_into).collect::<Result<_, _>>()?',
+                    'feerate': '.map(TryInto::try_into).collect::<Result<_, _>>()?',
+                    'outpoint': '.map(TryInto::try_into).collect::<Result<_, _>>()?',
+                }.get(typ, '.map(Into::into).collect()')
+                if isinstance(f.itemtype, CompositeField):
+                    mapping = '.map(TryInto::try_into).collect::<Result<_, _>>()?'
+                rhs = f"c.{name}"
+                if mapping is not None:
+                    rhs += f".into_iter(){mapping}"
+                if not f.required:
+                    rhs = f"Some({rhs})"
+                fields.append((f, rhs, " // Rule #4"))
 
             elif isinstance(f, EnumField):
+                err = f'|e| invalid("{f.path}", e)'
                 if f.required:
-                    self.write(f"{name}: c.{name}.try_into().unwrap(),\n", numindent=3)
+                    fields.append((f, f"c.{name}.try_into().map_err({err})?", ""))
                 else:
-                    self.write(f"{name}: c.{name}.map(|v| v.try_into().unwrap()),\n", numindent=3)
-                pass
+                    fields.append((f, f"c.{name}.map(TryInto::try_into).transpose().map_err({err})?", ""))
             elif isinstance(f, PrimitiveField):
                 typ = f.typename + ("?" if not f.required else "")
+                err = f'|e| invalid("{f.path}", e)'
+                missing = f'c.{name}.ok_or_else(|| missing("{f.path}"))?'
                 # We may need to reduce or increase the size of some
                 # types, or have some conversion such as
                 # hex-decoding. Also includes the `Some()` that grpc
@@ -443,43 +572,79 @@ class GrpcUnconverterGenerator(GrpcConverterGenerator):
                     'u16': f'c.{name} as u16',
                     'u16?': f'c.{name}.map(|v| v as u16)',
                     'hex': f'hex::encode(&c.{name})',
-                    'hex?': f'c.{name}.map(|v| hex::encode(v))',
-                    'txid?': f'c.{name}.map(|v| hex::encode(v))',
-                    'pubkey': f'PublicKey::from_slice(&c.{name}).unwrap()',
-                    'pubkey?': f'c.{name}.map(|v| PublicKey::from_slice(&v).unwrap())',
-                    'msat': f'c.{name}.unwrap().into()',
+                    'hex?': f'c.{name}.map(hex::encode)',
+                    'txid?': f'c.{name}.map(hex::encode)',
+                    'pubkey': f'PublicKey::from_slice(&c.{name}).map_err({err})?',
+                    'pubkey?': f'c.{name}.map(|v| PublicKey::from_slice(&v)).transpose().map_err({err})?',
+                    'msat': f'{missing}.into()',
                     'msat?': f'c.{name}.map(|a| a.into())',
-                    'msat_or_all': f'c.{name}.unwrap().into()',
-                    'msat_or_all?': f'c.{name}.map(|a| a.into())',
-                    'msat_or_any': f'c.{name}.unwrap().into()',
-                    'msat_or_any?': f'c.{name}.map(|a| a.into())',
-                    'feerate': f'c.{name}.unwrap().into()',
-                    'feerate?': f'c.{name}.map(|a| a.into())',
-                    'outpoint?': f'c.{name}.map(|a| a.into())',
-                    'RoutehintList?': f'c.{name}.map(|rl| rl.into())',
-                    'short_channel_id': f'cln_rpc::primitives::ShortChannelId::from_str(&c.{name}).unwrap()',
-                    'short_channel_id?': f'c.{name}.map(|v| cln_rpc::primitives::ShortChannelId::from_str(&v).unwrap())',
-                    'secret': f'c.{name}.try_into().unwrap()',
-                    'secret?': f'c.{name}.map(|v| v.try_into().unwrap())',
-                    'hash': f'Sha256::from_slice(&c.{name}).unwrap()',
-                    'hash?': f'c.{name}.map(|v| Sha256::from_slice(&v).unwrap())',
+                    'msat_or_all': f'{missing}.try_into()?',
+                    'msat_or_all?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'msat_or_any': f'{missing}.try_into()?',
+                    'msat_or_any?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'feerate': f'{missing}.try_into()?',
+                    'feerate?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'outpoint?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'RoutehintList?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'short_channel_id': f'cln_rpc::primitives::ShortChannelId::from_str(&c.{name}).map_err({err})?',
+                    'short_channel_id?': f'c.{name}.map(|v| cln_rpc::primitives::ShortChannelId::from_str(&v)).transpose().map_err({err})?',
+                    'secret': f'c.{name}.try_into().map_err({err})?',
+                    'secret?': f'c.{name}.map(TryInto::try_into).transpose().map_err({err})?',
+                    'hash': f'Sha256::from_slice(&c.{name}).map_err({err})?',
+                    'hash?': f'c.{name}.map(|v| Sha256::from_slice(&v)).transpose().map_err({err})?',
                     'txid': f'hex::encode(&c.{name})',
                     'TlvStream?': f'c.{name}.map(|s| s.into())',
+                    'Rune': f'c.{name}.parse().map_err({err})?',
+                    'Rune?': f'c.{name}.map(|v| v.parse()).transpose().map_err({err})?',
+                    'RuneRestrictions?': f'c.{name}.map(TryInto::try_into).transpose().map_err({err})?',
+                    'u8': f'c.{name} as u8',
+                    'u8?': f'c.{name}.map(|v| v as u8)',
+                    'signature': f'hex::encode(&c.{name})',
+                    'signature?': f'c.{name}.map(hex::encode)',
+                    'DecodeRoutehintList?': f'c.{name}.map(TryInto::try_into).transpose()?',
+                    'PluginOptions?': f'c.{name}.map(|o| o.into())',
                 }.get(
                     typ,
                     f'c.{name}'  # default to just assignment
                 )
-                self.write(f"{name}: {rhs}, // Rule #1 for type {typ}\n", numindent=3)
+                fields.append((f, rhs, f" // Rule #1 for type {typ}"))
             elif isinstance(f, CompositeField):
-                rhs = ""
                 if f.required:
-                    rhs = f'c.{name}.unwrap().into()'
+                    rhs = f'c.{name}.ok_or_else(|| missing("{f.path}"))?.try_into()?'
                 else:
-                    rhs = f'c.{name}.map(|v| v.into())'
-                self.write(f"{name}: {rhs},\n", numindent=3)
+                    rhs = f'c.{name}.map(TryInto::try_into).transpose()?'
+                fields.append((f, rhs, ""))
+
+        if prefix != "requests":
+            # Only the requests are `#[non_exhaustive]`.
+            self.write("Ok(Self {\n", numindent=2)
+            for f, rhs, comment in fields:
+                self.write(f"{snake(f)}: {rhs},{comment}\n", numindent=3)
+            self.write("})\n", numindent=2)
+            self.write(f"""\
+                }}
+            }}
+
+            """)
+            return
+
+        required = [(f, rhs, comment) for f, rhs, comment in fields if f.required]
+        optional = [(f, rhs, comment) for f, rhs, comment in fields if not f.required]
+        let, end = ("let mut r = ", ";") if optional else ("Ok(", ")")
+        if required:
+            self.write(f"{let}Self::builder(\n", numindent=2)
+            for f, rhs, comment in required:
+                self.write(f"{rhs},{comment}\n", numindent=3)
+            self.write(f").build(){end}\n", numindent=2)
+        else:
+            self.write(f"{let}Self::builder().build(){end}\n", numindent=2)
+
+        for f, rhs, comment in optional:
+            self.write(f"r.{snake(f)} = {rhs};{comment}\n", numindent=2)
+        if optional:
+            self.write("Ok(r)\n", numindent=2)
 
         self.write(f"""\
-                }}
             }}
         }}
 
@@ -491,7 +656,8 @@ class GrpcServerGenerator(GrpcConverterGenerator):
         self.write(f"""\
         use crate::pb::node_server::Node;
         use crate::pb;
-        use cln_rpc::{{Request, Response, ClnRpc}};
+        use crate::status::rpc_status;
+        use cln_rpc::{{Request, Response, ClnRpc, RpcError}};
         use anyhow::Result;
         use std::path::{{Path, PathBuf}};
         use cln_rpc::model::requests;
@@ -529,7 +695,9 @@ class GrpcServerGenerator(GrpcConverterGenerator):
                 request: tonic::Request<pb::{method.request.typename}>,
             ) -> Result<tonic::Response<pb::{method.response.typename}>, tonic::Status> {{
                 let req = request.into_inner();
-                let req: requests::{method.request.typename} = req.into();
+                let req: requests::{method.request.typename} = req
+                    .try_into()
+                    .map_err(|e: RpcError| Status::invalid_argument(e.message))?;
                 debug!("Client asked for {name}");
                 trace!("{name} request: {{:?}}", req);
                 let mut rpc = ClnRpc::new(&self.rpc_path)
@@ -537,9 +705,7 @@ class GrpcServerGenerator(GrpcConverterGenerator):
                     .map_err(|e| Status::new(Code::Internal, e.to_string()))?;
                 let result = rpc.call(Request::{method.name}(req))
                     .await
-                    .map_err(|e| Status::new(
-                       Code::Unknown,
-                       format!("Error calling method {method.name}: {{:?}}", e)))?;
+                    .map_err(|e| rpc_status("{method.name}", e))?;
                 match result {{
                     Response::{method.name}(r) => {{
                        trace!("{name} response: {{:?}}", r);
@@ -559,3 +725,72 @@ class GrpcServerGenerator(GrpcConverterGenerator):
         self.write(f"""\
         }}
         """, numindent=0)
+
+
+class GrpcClientGenerator(GrpcConverterGenerator):
+    """Generator for an `Rpc` implementation forwarding the calls to a
+    remote node's `Node` service.
+    """
+    def generate(self, service: Service) -> None:
+        self.write(f"""\
+        // This file was automatically derived from the JSON-RPC schemas in
+        // `doc/schemas`. Do not edit this file manually as it would get
+        // overwritten.
+
+        use crate::pb;
+        use crate::pb::node_client::NodeClient;
+        use crate::status::status_error;
+        use cln_rpc::rpc::{{self, Value}};
+        use cln_rpc::{{Request, Response, Rpc, RpcError}};
+        use tonic::transport::Channel;
+
+        /// An [`Rpc`] forwarding the calls to a remote node's
+        /// [`Node`](crate::pb::node_server::Node) service.
+        #[derive(Clone, Debug)]
+        pub struct NodeRpc {{
+            client: NodeClient<Channel>,
+        }}
+
+        impl NodeRpc {{
+            pub fn new(client: NodeClient<Channel>) -> Self {{
+                Self {{ client }}
+            }}
+
+            /// Connect to the `Node` service at `uri`. Use
+            /// [`NodeRpc::new`] to configure the channel, e.g., with TLS.
+            pub async fn connect(uri: String) -> anyhow::Result<Self> {{
+                Ok(Self::new(NodeClient::connect(uri).await?))
+            }}
+        }}
+
+        #[tonic::async_trait]
+        impl Rpc for NodeRpc {{
+            async fn call_raw(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {{
+                let req = rpc::request(method, params)?;
+                rpc::result(self.call(req).await?)
+            }}
+
+            async fn call(&mut self, req: Request) -> Result<Response, RpcError> {{
+                match req {{
+        """)
+
+        for method in service.methods:
+            mname = method_name_overrides.get(method.name, method.name)
+            # Tonic will convert to snake-case, so we have to do it here too
+            name = re.sub(r'(?<!^)(?=[A-Z])', '_', mname).lower()
+            self.write(dedent(f"""\
+                Request::{method.name}(req) => {{
+                    let res = self
+                        .client
+                        .{name}(pb::{method.request.typename}::try_from(req)?)
+                        .await
+                        .map_err(|s| status_error("{method.name}", s))?;
+                    Ok(Response::{method.name}(res.into_inner().try_into()?))
+                }}
+            """), numindent=3)
+
+        self.write(f"""\
+                }}
+            }}
+        }}
+        """)
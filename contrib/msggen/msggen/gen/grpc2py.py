@@ -44,6 +44,11 @@ class Grpc2PyGenerator(IGenerator):
             'short_channel_id': "m.{name}",
             'msat': "amount2msat(m.{name})",
             'number': "m.{name}",
+            'bip340sig': "m.{name}",
+            'DecodeRoutehintList': "decoderoutehintlist2py(m.{name})",
+            'PluginOptions': "{{k: json.loads(v) for k, v in m.{name}.options.items()}}",
+            'Rune': "m.{name}",
+            'integer': "m.{name}",
         }
 
     def generate_responses(self, service):
@@ -75,6 +80,15 @@ class Grpc2PyGenerator(IGenerator):
         def amount_or_all2msat(a):
             breakpoint()
 
+        def decoderoutehintlist2py(m):
+            return [[{
+                "pubkey": hexlify(h.id),
+                "short_channel_id": h.short_channel_id,
+                "fee_base_msat": amount2msat(h.feebase),
+                "fee_proportional_millionths": h.feeprop,
+                "cltv_expiry_delta": h.expirydelta,
+            } for h in r.hops] for r in m.hints]
+
 
         def remove_default(d):
             # grpc is really not good at empty values, they get replaced with the type's default value...
@@ -122,7 +136,7 @@ class Grpc2PyGenerator(IGenerator):
             if isinstance(f, PrimitiveField):
                 typ = f.typename
 
-                rhs = self.converters[typ].format(name=f.name)
+                rhs = self.converters[typ].format(name=name)
 
                 self.write(f'        "{name}": {rhs},  # PrimitiveField in generate_composite\n', cleanup=False)
 
@@ -1,3 +1,4 @@
+from collections import namedtuple
 from typing import TextIO
 from typing import Tuple
 from textwrap import dedent, indent
@@ -41,9 +42,11 @@ typemap = {
     'pubkey': 'PublicKey',
     'short_channel_id': 'ShortChannelId',
     'signature': 'String',
+    'bip340sig': 'String',
     'string': 'String',
     'txid': 'String',
     'float': 'f32',
+    'integer': 'i64',
     'utxo': 'Utxo',
     'feerate': 'Feerate',
     'outpoint': 'Outpoint',
@@ -65,6 +68,10 @@ header = f"""#![allow(non_camel_case_types)]
 """
 
 
+# The name and type of a struct member, used to generate the builders.
+Member = namedtuple('Member', ['name', 'typename', 'required', 'deprecated'])
+
+
 def normalize_varname(field):
     """Make sure that the variable name of this field is valid.
     """
@@ -74,13 +81,17 @@ def normalize_varname(field):
     return field
 
 
-def gen_field(field):
+def gen_field(field, builder=False):
+    """Generate the member definition, any type declarations it needs,
+    and the `Member` describing it. `builder` is passed on to
+    `gen_composite` for the types declared.
+    """
     if isinstance(field, CompositeField):
-        return gen_composite(field)
+        return gen_composite(field, builder)
     elif isinstance(field, EnumField):
         return gen_enum(field)
     elif isinstance(field, ArrayField):
-        return gen_array(field)
+        return gen_array(field, builder)
     elif isinstance(field, PrimitiveField):
         return gen_primitive(field)
     else:
@@ -91,7 +102,7 @@ def gen_enum(e):
     defi, decl = "", ""
 
     if e.path in overrides and overrides[e.path] is None:
-        return "", ""
+        return "", "", None
 
     if e.description != "":
         decl += f"/// {e.description}\n"
@@ -135,10 +146,10 @@ def gen_enum(e):
     if e.required:
         defi = f"    // Path `{e.path}`\n    #[serde(rename = \"{e.name}\")]\n    pub {e.name.normalized()}: {typename},\n"
     else:
-        defi = f'    #[serde(skip_serializing_if = "Option::is_none")]\n'
+        defi = f'    #[serde(rename = "{e.name}", skip_serializing_if = "Option::is_none")]\n'
         defi += f"    pub {e.name.normalized()}: Option<{typename}>,\n"
 
-    return defi, decl
+    return defi, decl, Member(e.name.normalized(), typename, e.required, e.deprecated)
 
 
 def gen_primitive(p):
@@ -147,20 +158,26 @@ def gen_primitive(p):
     typename = typemap.get(p.typename, p.typename)
     normalize_varname(p)
 
+    # Keywords can't be used as field names, so we rename the field
+    # but keep the original name on the wire.
+    name, attr = p.name, f"alias = \"{org}\""
+    if org in keywords or not str(name).isidentifier():
+        name, attr = p.name.normalized(), f"rename = \"{org}\""
+
     if p.deprecated:
         defi += "    #[deprecated]\n"
     if p.required:
-        defi += f"    #[serde(alias = \"{org}\")]\n    pub {p.name}: {typename},\n"
+        defi += f"    #[serde({attr})]\n    pub {name}: {typename},\n"
     else:
-        defi += f"    #[serde(alias = \"{org}\", skip_serializing_if = \"Option::is_none\")]\n    pub {p.name}: Option<{typename}>,\n"
+        defi += f"    #[serde({attr}, skip_serializing_if = \"Option::is_none\")]\n    pub {name}: Option<{typename}>,\n"
 
-    return defi, decl
+    return defi, decl, Member(name, typename, p.required, p.deprecated)
 
 
-def gen_array(a):
+def gen_array(a, builder=False):
     name = a.name.normalized().replace("[]", "")
     logger.debug(f"Generating array field {a.name} -> {name} ({a.path})")
-    _, decl = gen_field(a.itemtype)
+    _, decl, _ = gen_field(a.itemtype, builder)
 
     if a.path in overrides:
         decl = ""  # No declaration if we have an override
@@ -173,35 +190,46 @@ def gen_array(a):
         itemtype = a.itemtype.typename
 
     if itemtype is None:
-        return ("", "")  # Override said not to include
+        return ("", "", None)  # Override said not to include
 
     itemtype = typemap.get(itemtype, itemtype)
-    alias = a.name.normalized()
+    typename = f"{'Vec<'*a.dims}{itemtype}{'>'*a.dims}"
+    alias = str(a.name).replace("[]", "")
     defi = ""
     if a.deprecated:
         defi += "    #[deprecated]\n"
     if a.required:
-        defi += f"    #[serde(alias = \"{alias}\")]\n    pub {name}: {'Vec<'*a.dims}{itemtype}{'>'*a.dims},\n"
+        defi += f"    #[serde(alias = \"{alias}\")]\n    pub {name}: {typename},\n"
     else:
-        defi += f"    #[serde(alias = \"{alias}\", skip_serializing_if = \"crate::is_none_or_empty\")]\n    pub {name}: Option<{'Vec<'*a.dims}{itemtype}{'>'*a.dims}>,\n"
+        defi += f"    #[serde(alias = \"{alias}\", skip_serializing_if = \"crate::is_none_or_empty\")]\n    pub {name}: Option<{typename}>,\n"
 
-    return (defi, decl)
+    return (defi, decl, Member(name, typename, a.required, a.deprecated))
 
 
-def gen_composite(c) -> Tuple[str, str]:
+def gen_composite(c, builder=False) -> Tuple[str, str, Member]:
+    """Generate the struct for `c`. With `builder` the struct is
+    `#[non_exhaustive]`, and gets a builder since it can't be
+    constructed outside of this crate otherwise.
+    """
     logger.debug(f"Generating composite field {c.name} ({c.path})")
     fields = []
     for f in c.fields:
-        fields.append(gen_field(f))
+        fields.append(gen_field(f, builder))
 
     r = "".join([f[1] for f in fields])
 
-    r += f"""#[derive(Clone, Debug, Deserialize, Serialize)]\npub struct {c.typename} {{\n"""
+    r += "#[derive(Clone, Debug, Deserialize, Serialize)]\n"
+    if builder:
+        r += "#[non_exhaustive]\n"
+    r += f"pub struct {c.typename} {{\n"
 
     r += "".join([f[0] for f in fields])
 
     r += "}\n\n"
 
+    if builder:
+        r += gen_builder(c.typename, [f[2] for f in fields if f[2] is not None])
+
     defi = ""
     if c.deprecated:
         defi += "    #[deprecated]\n"
@@ -210,7 +238,50 @@ def gen_composite(c) -> Tuple[str, str]:
     else:
         defi += f"    #[serde(alias = \"{c.name.name}\", skip_serializing_if = \"Option::is_none\")]\n    pub {c.name}: Option<{c.typename}>,\n"
 
-    return defi, r
+    return defi, r, Member(str(c.name), c.typename, c.required, c.deprecated)
+
+
+def gen_builder(typename, members) -> str:
+    """Generate `{typename}::builder`, taking the required members, and
+    the builder setting the optional ones.
+    """
+    required = [m for m in members if m.required]
+    optional = [m for m in members if not m.required]
+    # Setting deprecated members is still allowed.
+    allow = "#[allow(deprecated)]\n" if any(m.deprecated for m in members) else ""
+
+    args = ", ".join(f"{m.name}: {m.typename}" for m in required)
+    r = f"{allow}impl {typename} {{\n"
+    r += f"    /// Start building a `{typename}` from its required fields.\n"
+    r += f"    pub fn builder({args}) -> {typename}Builder {{\n"
+    r += f"        {typename}Builder {{\n"
+    r += f"            inner: {typename} {{\n"
+    for m in members:
+        value = m.name if m.required else f"{m.name}: None"
+        r += f"                {value},\n"
+    r += "            },\n"
+    r += "        }\n"
+    r += "    }\n"
+    r += "}\n\n"
+
+    r += f"/// Builder for [`{typename}`], see [`{typename}::builder`].\n"
+    r += f"#[derive(Clone, Debug)]\npub struct {typename}Builder {{\n"
+    r += f"    inner: {typename},\n"
+    r += "}\n\n"
+
+    r += f"{allow}impl {typename}Builder {{\n"
+    for m in optional:
+        if m.deprecated:
+            r += "    #[deprecated]\n"
+        r += f"    pub fn {m.name}(mut self, {m.name}: {m.typename}) -> Self {{\n"
+        r += f"        self.inner.{m.name} = Some({m.name});\n"
+        r += "        self\n"
+        r += "    }\n\n"
+    r += f"    pub fn build(self) -> {typename} {{\n"
+    r += "        self.inner\n"
+    r += "    }\n"
+    r += "}\n\n"
+    return r
 
 
 class RustGenerator(IGenerator):
@@ -236,7 +307,7 @@ class RustGenerator(IGenerator):
 
         for meth in service.methods:
             req = meth.request
-            _, decl = gen_composite(req)
+            _, decl, _ = gen_composite(req, builder=True)
             self.write(decl, numindent=1)
             self.generate_request_trait_impl(meth)
 
@@ -269,7 +340,7 @@ class RustGenerator(IGenerator):
 
         for meth in service.methods:
             res = meth.response
-            _, decl = gen_composite(res)
+            _, decl, _ = gen_composite(res)
             self.write(decl, numindent=1)
             self.generate_response_trait_impl(meth)
 
@@ -305,6 +376,8 @@ class RustGenerator(IGenerator):
         """)
 
         for method in service.methods:
+            if method.rpc_name != method.name.lower():
+                self.write(f"#[serde(rename = \"{method.rpc_name}\")]\n", numindent=1)
             self.write(f"{method.name}(requests::{method.request.typename}),\n", numindent=1)
 
         self.write(f"""\
@@ -317,6 +390,8 @@ class RustGenerator(IGenerator):
         """)
 
         for method in service.methods:
+            if method.rpc_name != method.name.lower():
+                self.write(f"#[serde(rename = \"{method.rpc_name}\")]\n", numindent=1)
             self.write(f"{method.name}(responses::{method.response.typename}),\n", numindent=1)
 
         self.write(f"""\
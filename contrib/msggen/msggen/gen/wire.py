@@ -0,0 +1,518 @@
+"""Generate the Rust wire message codec from the spec CSV files.
+
+The CSV files in `wire/` and `hsmd/` are the same ones
+`tools/generate-wire.py` generates the C code from:
+
+    msgtype,<msgname>,<value>[,<option>]
+    msgdata,<msgname>,<fieldname>,<typename>,[<count>][,<option>]
+    tlvtype,<tlvstreamname>,<tlvname>,<value>[,<option>]
+    tlvdata,<tlvstreamname>,<tlvname>,<fieldname>,<typename>,[<count>][,<option>]
+    subtype,<subtypename>
+    subtypedata,<subtypename>,<fieldname>,<typename>,[<count>]
+
+where <count> is a fixed number, the name of a previous field holding
+the length, or '...' for the rest of the TLV record. A <typename>
+starting with '?' is an optional field.
+"""
+from collections import OrderedDict
+from pathlib import Path
+from typing import TextIO
+from textwrap import indent
+import re
+import sys
+
+from msggen.gen.generator import IGenerator
+
+
+header = """//! This file was automatically generated using the following command:
+//!
+//! ```bash
+//! {command}
+//! ```
+//!
+//! Do not edit this file, it'll be overwritten. Rather edit `{csv}`
+//! which this file was generated from
+"""
+
+# The modules we generate, the CSV they're generated from and the name
+# of the enum over their messages.
+modules = [
+    ('peer', 'wire/peer_wire.csv', 'PeerMessage'),
+    ('onion', 'wire/onion_wire.csv', 'OnionFailure'),
+    ('bolt12', 'wire/bolt12_wire.csv', None),
+    ('channel_type', 'wire/channel_type_wire.csv', None),
+    ('hsmd', 'hsmd/hsmd_wire.csv', 'HsmdMessage'),
+]
+
+# Rust type of each CSV type.
+typemap = {
+    'byte': 'u8',
+    'u8': 'u8',
+    'u16': 'u16',
+    'u32': 'u32',
+    'u64': 'u64',
+    'tu16': 'u16',
+    'tu32': 'u32',
+    'tu64': 'u64',
+    'bigsize': 'u64',
+    'point': 'PublicKey',
+    'signature': '[u8; 64]',
+    'bip340sig': '[u8; 64]',
+    'chain_hash': 'BlockHash',
+    'channel_id': '[u8; 32]',
+    'sha256': 'Sha256',
+    'short_channel_id': 'ShortChannelId',
+    'utf8': 'String',
+    # The types below are only used by `hsmd_wire.csv`, they use the
+    # encoding of the C types they're named after.
+    'bool': 'bool',
+    'node_id': 'PublicKey',
+    'pubkey': 'PublicKey',
+    'secret': 'Secret',
+    'privkey': 'Secret',
+    'wirestring': 'String',
+    'amount_sat': 'Amount',
+    'amount_msat': 'Amount',
+    'bitcoin_txid': 'Txid',
+    'chainparams': 'BlockHash',
+    'secp256k1_ecdsa_signature': 'Signature',
+    'secp256k1_ecdsa_recoverable_signature': 'RecoverableSignature',
+    'bitcoin_signature': 'EcdsaSig',
+    'ext_key': 'ExtendedPubKey',
+    'wally_psbt': 'Psbt',
+    'bitcoin_tx': 'BitcoinTx',
+    'bip32_key_version': 'Bip32KeyVersion',
+    'secrets': 'Secrets',
+    'basepoints': 'Basepoints',
+    'simple_htlc': 'SimpleHtlc',
+    'utxo': 'Utxo',
+}
+
+# Where the Rust types that aren't generated are imported from.
+imports = {
+    'Amount': 'crate::primitives::Amount',
+    'PublicKey': 'crate::primitives::PublicKey',
+    'Secret': 'crate::primitives::Secret',
+    'Sha256': 'crate::primitives::Sha256',
+    'ShortChannelId': 'crate::primitives::ShortChannelId',
+    'BlockHash': 'bitcoin::BlockHash',
+    'Txid': 'bitcoin::Txid',
+    'EcdsaSig': 'bitcoin::EcdsaSig',
+    'Psbt': 'bitcoin::psbt::Psbt',
+    'Signature': 'bitcoin::secp256k1::ecdsa::Signature',
+    'RecoverableSignature': 'bitcoin::secp256k1::ecdsa::RecoverableSignature',
+    'ExtendedPubKey': 'bitcoin::util::bip32::ExtendedPubKey',
+    'BitcoinTx': 'crate::hsmd::BitcoinTx',
+    'Bip32KeyVersion': 'crate::hsmd::Bip32KeyVersion',
+    'Secrets': 'crate::hsmd::Secrets',
+    'Basepoints': 'crate::hsmd::Basepoints',
+    'SimpleHtlc': 'crate::hsmd::SimpleHtlc',
+    'Utxo': 'crate::hsmd::Utxo',
+}
+
+truncated = ['tu16', 'tu32', 'tu64']
+
+# Types not written with their `WireType` implementation.
+special = truncated + ['bigsize', 'utf8', 'wirestring', 'amount_sat']
+
+# Like the C generator, amounts are recognized by their name.
+amount_names = ['msat', 'amt']
+
+keywords = ['type']
+
+
+def camel(name: str) -> str:
+    return ''.join(p[0].upper() + p[1:] for p in name.split('_') if p)
+
+
+def ident(name: str) -> str:
+    return 'typ' if name in keywords else name
+
+
+class Field:
+    def __init__(self, name, typename, count):
+        self.name = name
+        self.optional = typename.startswith('?')
+        self.typename = typename.lstrip('?')
+        self.count = count
+        # The field whose length we hold, if we're a length field.
+        self.len_of = None
+
+    def is_amount(self):
+        return self.typename in ['u64', 'tu64'] and any(n in self.name for n in amount_names)
+
+    def is_bytes(self):
+        return self.typename in ['byte', 'u8', 'utf8']
+
+    def is_array(self):
+        return self.count != ''
+
+    def is_fixed(self):
+        return self.count.isdigit()
+
+    def is_rest(self):
+        return self.count == '...'
+
+
+class FieldSet:
+    def __init__(self, name):
+        self.name = name
+        self.fields = OrderedDict()
+
+    def add(self, name, typename, count):
+        f = Field(name, typename, count)
+        if f.is_array() and not f.is_fixed() and not f.is_rest():
+            lenfield = self.fields[count]
+            assert lenfield.len_of is None, f"{self.name}.{count} is a length twice"
+            lenfield.len_of = name
+        self.fields[name] = f
+
+    def data_fields(self):
+        return [f for f in self.fields.values() if f.len_of is None]
+
+
+class Message(FieldSet):
+    def __init__(self, name, value):
+        FieldSet.__init__(self, name)
+        self.value = value
+
+
+class Tlv:
+    def __init__(self, name):
+        self.name = name
+        self.records = OrderedDict()
+
+
+class Module:
+    def __init__(self, name, csv, enum):
+        self.name = name
+        self.csv = csv
+        self.enum = enum
+        self.messages = OrderedDict()
+        self.subtypes = OrderedDict()
+        self.tlvs = OrderedDict()
+
+
+def load(path: Path, module: Module):
+    for line in open(path):
+        line = line.strip()
+        if not line or line.startswith('#'):
+            continue
+        t = line.split(',')
+        if t[0] == 'msgtype':
+            module.messages[t[1]] = Message(t[1], t[2])
+        elif t[0] == 'msgdata':
+            module.messages[t[1]].add(t[2], t[3], t[4])
+        elif t[0] == 'subtype':
+            module.subtypes[t[1]] = FieldSet(t[1])
+        elif t[0] == 'subtypedata':
+            module.subtypes[t[1]].add(t[2], t[3], t[4])
+        elif t[0] == 'tlvtype':
+            tlv = module.tlvs.setdefault(t[1], Tlv(t[1]))
+            tlv.records[t[2]] = Message(f"{t[1]}_{t[2]}", t[3])
+        elif t[0] == 'tlvdata':
+            module.tlvs[t[1]].records[t[2]].add(t[3], t[4], t[5])
+        else:
+            raise ValueError(f"Unknown line {line} in {path}")
+
+
+class WireGenerator(IGenerator):
+    """Generate `cln-rpc/src/wire/*.rs` from the CSV files in `wire/`.
+
+    Unlike the other generators this doesn't use the JSON-RPC schemas,
+    it's run alongside them so all generated code is updated at once.
+    """
+
+    def __init__(self, rootdir: Path):
+        self.rootdir = rootdir
+        self.modules = []
+        for name, csv, enum in modules:
+            m = Module(name, csv, enum)
+            load(rootdir / csv, m)
+            self.modules.append(m)
+
+    def generate(self, service):
+        for m in self.modules:
+            fname = self.rootdir / 'cln-rpc' / 'src' / 'wire' / f'{m.name}.rs'
+            with open(fname, 'w') as dest:
+                ModuleGenerator(m, self.modules, dest).generate()
+
+
+class ModuleGenerator:
+    def __init__(self, module: Module, modules, dest: TextIO):
+        self.module = module
+        self.modules = modules
+        self.dest = dest
+        self.imports = set()
+
+    def write(self, text: str) -> None:
+        self.dest.write(text)
+
+    def subtype_module(self, name):
+        for m in self.modules:
+            if name in m.subtypes or name in m.tlvs:
+                return m
+        raise ValueError(f"Unknown type {name}")
+
+    def rust_type(self, f: Field) -> str:
+        if f.is_amount():
+            t = 'Amount'
+        elif f.typename in typemap:
+            t = typemap[f.typename]
+        else:
+            t = camel(f.typename)
+            m = self.subtype_module(f.typename)
+            if m is not self.module:
+                self.imports.add(f"super::{m.name}::{t}")
+        if t in imports:
+            self.imports.add(imports[t])
+
+        if f.typename == 'utf8':
+            assert f.is_array() and not f.is_fixed()
+            return 'String'
+        if f.is_fixed():
+            assert f.is_bytes(), f"Only byte arrays can have a fixed size: {f.name}"
+            return f'[u8; {f.count}]'
+        if f.is_array():
+            return f'Vec<{t}>'
+        if f.optional:
+            assert f.typename not in special, f"{f.typename} can't be optional: {f.name}"
+            return f'Option<{t}>'
+        return t
+
+    def gen_write(self, f: Field, expr) -> str:
+        """Write field `f`, whose value is `expr(name)`. Fields of a
+        single field TLV record are all called `v`, and are references.
+        """
+        v = expr(f.name)
+        ref = v if v == 'v' else f"&{v}"
+        if f.len_of is not None:
+            return f"len::<{self.rust_type(f)}>({expr(f.len_of)}.len())?.write(buf)?;\n"
+        if f.typename in truncated:
+            return f"buf.extend({v}.to_tlv_value());\n"
+        if f.typename == 'bigsize':
+            val = '*v' if v == 'v' else v
+            return f"write_bigsize(buf, {val});\n"
+        if f.typename == 'utf8':
+            return f"buf.extend_from_slice({v}.as_bytes());\n"
+        if f.typename == 'wirestring':
+            return f"write_wirestring(buf, {ref})?;\n"
+        if f.typename == 'amount_sat':
+            return f"write_sat(buf, {ref})?;\n"
+        if f.is_array() and f.is_bytes() and not f.is_fixed():
+            return f"buf.extend_from_slice({ref});\n"
+        if f.is_array() and not f.is_fixed():
+            return f"for x in {ref} {{\n    x.write(buf)?;\n}}\n"
+        return f"{v}.write(buf)?;\n"
+
+    def gen_read(self, f: Field) -> str:
+        """An expression reading field `f`."""
+        if f.len_of is not None or not f.is_array() or f.is_fixed():
+            if f.typename in truncated:
+                return "r.truncated()?"
+            if f.typename == 'bigsize':
+                return "r.bigsize()?"
+            if f.typename == 'wirestring':
+                return "r.wirestring()?"
+            if f.typename == 'amount_sat':
+                return "r.sat()?"
+            return "r.read()?"
+        n = f"{f.count} as usize"
+        if f.typename == 'utf8':
+            return "r.utf8(r.len())?" if f.is_rest() else f"r.utf8({n})?"
+        if f.is_bytes():
+            return "r.rest().to_vec()" if f.is_rest() else f"r.take({n})?.to_vec()"
+        return "r.read_all()?" if f.is_rest() else f"r.read_n({n})?"
+
+    def gen_lets(self, fs: FieldSet) -> str:
+        """Read the fields of `fs` into variables."""
+        res = ""
+        for f in fs.fields.values():
+            res += f"let {ident(f.name)}: {self.rust_type(f)} = {self.gen_read(f)};\n"
+        return res
+
+    def gen_struct(self, name: str, fs: FieldSet, derive="Clone, Debug, PartialEq, Eq") -> str:
+        fields = fs.data_fields()
+        res = f"#[derive({derive})]\npub struct {name} {{\n"
+        for f in fields:
+            res += f"    pub {ident(f.name)}: {self.rust_type(f)},\n"
+        res += "}\n\n"
+
+        # Some failure messages have no fields at all.
+        u = "" if fs.fields else "_"
+        res += f"impl WireType for {name} {{\n"
+        res += f"    fn write(&self, {u}buf: &mut Vec<u8>) -> Result<()> {{\n"
+        for f in fs.fields.values():
+            res += indent(self.gen_write(f, lambda n: f"self.{ident(n)}"), ' ' * 8)
+        res += "        Ok(())\n    }\n\n"
+        res += f"    fn read({u}r: &mut Reader<'_>) -> Result<Self> {{\n"
+        res += indent(self.gen_lets(fs), ' ' * 8)
+        if fields:
+            res += f"        Ok({name} {{\n"
+            for f in fields:
+                res += f"            {ident(f.name)},\n"
+            res += "        })\n"
+        else:
+            res += f"        Ok({name} {{}})\n"
+        res += "    }\n}\n\n"
+        return res
+
+    def record_type(self, tlv: Tlv, rec: Message) -> str:
+        fields = rec.data_fields()
+        if len(fields) == 1:
+            return self.rust_type(fields[0])
+        return camel(rec.name)
+
+    def gen_tlv(self, tlv: Tlv) -> str:
+        name = camel(tlv.name)
+        res = ""
+        # Records with more than one field get their own struct.
+        for rec in tlv.records.values():
+            if len(rec.data_fields()) > 1:
+                res += self.gen_struct(camel(rec.name), rec)
+
+        res += f"#[derive(Clone, Debug, Default, PartialEq, Eq)]\npub struct {name} {{\n"
+        for rec in tlv.records.values():
+            res += f"    pub {ident(rec.name[len(tlv.name) + 1:])}: Option<{self.record_type(tlv, rec)}>,\n"
+        res += "    /// Odd records we don't know about.\n"
+        res += "    pub unknown: Vec<TlvEntry>,\n}\n\n"
+
+        res += f"impl WireType for {name} {{\n"
+        res += "    fn write(&self, buf: &mut Vec<u8>) -> Result<()> {\n"
+        res += "        let mut stream = unknown_stream(&self.unknown)?;\n"
+        for rec in tlv.records.values():
+            field = ident(rec.name[len(tlv.name) + 1:])
+            fields = rec.data_fields()
+            res += f"        if let Some(v) = &self.{field} {{\n"
+            res += f"            write_tlv(&mut stream, {rec.value}, |buf| {{\n"
+            if len(fields) == 1:
+                body = "".join(self.gen_write(f, lambda n: "v") for f in rec.fields.values())
+            else:
+                body = "v.write(buf)?;\n"
+            res += indent(body, ' ' * 16)
+            res += "                Ok(())\n            })?;\n        }\n"
+        res += "        buf.extend(stream.to_wire()?);\n"
+        res += "        Ok(())\n    }\n\n"
+
+        res += "    fn read(r: &mut Reader<'_>) -> Result<Self> {\n"
+        res += f"        let mut res = {name}::default();\n"
+        res += "        for e in TlvStream::from_wire(r.rest())?.entries {\n"
+        res += "            match e.typ {\n"
+        for rec in tlv.records.values():
+            field = ident(rec.name[len(tlv.name) + 1:])
+            fields = rec.data_fields()
+            if len(fields) > 1:
+                reader = f"{camel(rec.name)}::read"
+            elif len(rec.fields) == 1:
+                expr = self.gen_read(fields[0])
+                reader = f"|r| {expr[:-1]}" if expr.endswith('?') else f"|r| Ok({expr})"
+            else:
+                lets = "".join(
+                    f"let {ident(f.name)}: {self.rust_type(f)} = {self.gen_read(f)};\n"
+                    for f in rec.fields.values() if f.len_of is not None
+                )
+                expr = self.gen_read(fields[0])
+                tail = expr[:-1] if expr.endswith('?') else f"Ok({expr})"
+                reader = "|r| {\n" + indent(lets + tail + "\n", ' ' * 4) + "}"
+            reader = indent(reader, ' ' * 16).strip()
+            res += f"                {rec.value} => res.{field} = Some(read_tlv(&e, {reader})?),\n"
+        res += "                _ => unknown_tlv(&mut res.unknown, e)?,\n"
+        res += "            }\n        }\n        Ok(res)\n    }\n}\n\n"
+        return res
+
+    def message_type(self, msg: Message) -> str:
+        """The type of `msg`, using the failure code flag constants."""
+        return ' | '.join(msg.value.split('|'))
+
+    def gen_enum(self) -> str:
+        enum = self.module.enum
+        msgs = self.module.messages.values()
+        res = "/// Any of the messages above, or one we don't know about.\n"
+        res += "#[allow(clippy::large_enum_variant)]\n"
+        res += f"#[derive(Clone, Debug, PartialEq, Eq)]\npub enum {enum} {{\n"
+        for m in msgs:
+            res += f"    {camel(m.name)}({camel(m.name)}),\n"
+        res += "    Unknown { typ: u16, payload: Vec<u8> },\n}\n\n"
+
+        res += f"impl {enum} {{\n"
+        res += "    pub fn typ(&self) -> u16 {\n        match self {\n"
+        for m in msgs:
+            res += f"            {enum}::{camel(m.name)}(_) => {camel(m.name)}::TYPE,\n"
+        res += f"            {enum}::Unknown {{ typ, .. }} => *typ,\n"
+        res += "        }\n    }\n\n"
+
+        res += "    pub fn to_wire(&self) -> Result<Vec<u8>> {\n        match self {\n"
+        for m in msgs:
+            res += f"            {enum}::{camel(m.name)}(m) => m.to_wire(),\n"
+        res += f"            {enum}::Unknown {{ typ, payload }} => {{\n"
+        res += "                let mut buf = typ.to_be_bytes().to_vec();\n"
+        res += "                buf.extend_from_slice(payload);\n"
+        res += "                Ok(buf)\n            }\n"
+        res += "        }\n    }\n\n"
+
+        res += "    /// Decode a message. Messages of an unknown type are returned\n"
+        res += "    /// as [`Unknown`](Self::Unknown), it's up to the caller to\n"
+        res += "    /// reject those with an even type.\n"
+        res += f"    pub fn from_wire(buf: &[u8]) -> Result<{enum}> {{\n"
+        res += "        let mut r = Reader::new(buf);\n"
+        res += "        let typ: u16 = r.read()?;\n"
+        res += "        Ok(match typ {\n"
+        for m in msgs:
+            res += f"            {camel(m.name)}::TYPE => {enum}::{camel(m.name)}(r.read()?),\n"
+        res += f"            _ => {enum}::Unknown {{\n"
+        res += "                typ,\n                payload: r.rest().to_vec(),\n            },\n"
+        res += "        })\n    }\n}\n\n"
+
+        for m in msgs:
+            res += f"impl From<{camel(m.name)}> for {enum} {{\n"
+            res += f"    fn from(m: {camel(m.name)}) -> Self {{\n"
+            res += f"        {enum}::{camel(m.name)}(m)\n    }}\n}}\n\n"
+
+            res += f"impl TryFrom<{enum}> for {camel(m.name)} {{\n"
+            res += "    type Error = anyhow::Error;\n\n"
+            res += f"    fn try_from(m: {enum}) -> Result<Self> {{\n"
+            res += "        match m {\n"
+            res += f"            {enum}::{camel(m.name)}(m) => Ok(m),\n"
+            res += f"            m => Err(anyhow!(\"Expected message type {{}}, got {{}}\", Self::TYPE, m.typ())),\n"
+            res += "        }\n    }\n}\n\n"
+        return res
+
+    def generate(self):
+        body = ""
+        for name, st in self.module.subtypes.items():
+            body += self.gen_struct(camel(name), st)
+        for tlv in self.module.tlvs.values():
+            body += self.gen_tlv(tlv)
+        for m in self.module.messages.values():
+            body += self.gen_struct(camel(m.name), m)
+            body += f"impl WireMessage for {camel(m.name)} {{\n"
+            body += f"    const TYPE: u16 = {self.message_type(m)};\n}}\n\n"
+        if self.module.enum:
+            body += self.gen_enum()
+
+        uses = ["anyhow::Result"]
+        if self.module.enum:
+            uses = ["anyhow::{anyhow, Result}"]
+        if self.module.tlvs:
+            self.imports.add("crate::primitives::TlvEntry")
+            self.imports.add("crate::primitives::TlvStream")
+        for imp in sorted(self.imports):
+            uses.append(imp)
+        code = ["Reader", "WireType"]
+        if "len::<" in body:
+            code.append("len")
+        for word in ["WireMessage", "read_tlv", "write_tlv", "unknown_tlv", "unknown_stream",
+                     "write_bigsize", "write_wirestring", "write_sat",
+                     "BADONION", "PERM", "NODE", "UPDATE"]:
+            if re.search(rf"\b{word}\b", body):
+                code.append(word)
+        if "to_tlv_value" in body:
+            uses.append("crate::primitives::TlvValue")
+        uses.append(f"crate::wire::{{{', '.join(sorted(code))}}}")
+
+        self.write(header.format(command=" ".join(sys.argv), csv=self.module.csv))
+        self.write("\n")
+        for u in uses:
+            self.write(f"use {u};\n")
+        self.write("\n")
+        self.write(body.rstrip() + "\n")
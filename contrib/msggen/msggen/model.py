@@ -6,7 +6,7 @@ logger = logging.getLogger(__name__)
 
 
 def path2type(path):
-    typename = "".join([s.capitalize() for s in path.replace("[]", "").split(".")])
+    typename = "".join([s.capitalize() for s in path.replace("[]", "").replace("-", "_").split(".")])
     return typename
 
 
@@ -19,6 +19,7 @@ class FieldName:
             "type": "item_type"
         }.get(self.name, self.name)
 
+        name = name.replace('#', '').strip()
         name = name.replace(' ', '_').replace('-', '_').replace('[]', '')
         return name
 
@@ -85,10 +86,11 @@ class Service:
 
 
 class Method:
-    def __init__(self, name: str, request: Field, response: Field):
+    def __init__(self, name: str, request: Field, response: Field, rpc_name: Optional[str] = None):
         self.name = name
         self.request = request
         self.response = response
+        self.rpc_name = rpc_name if rpc_name is not None else name.lower()
 
 
 class CompositeField(Field):
@@ -110,31 +112,26 @@ class CompositeField(Field):
         }
         # Yes, this is ugly, but walking nested dicts always is.
         for a in [top] + js.get('allOf', []):
-            var = a.get('then', {})
-            props = var.get('properties', None)
-            if isinstance(props, dict):
-                for k, v in props.items():
-                    if k not in properties:
-                        properties[k] = v
-            var = a.get('else', {})
-            props = var.get('properties', None)
-            if isinstance(props, dict):
-                for k, v in props.items():
-                    if k not in properties:
-                        properties[k] = v
+            for branch in ('then', 'else'):
+                props = a.get(branch, {}).get('properties', None)
+                if isinstance(props, dict):
+                    for k, v in props.items():
+                        if k not in properties:
+                            properties[k] = v
 
         # Identify required fields
         required = js.get("required", [])
         fields = []
         for fname, ftype in properties.items():
             field = None
-            desc = ftype["description"] if "description" in ftype else ""
             fpath = f"{path}.{fname}"
+            ftype = schema_overrides.get(fpath, ftype)
+            desc = ftype["description"] if "description" in ftype else ""
 
             if fpath in overrides:
                 field = copy(overrides[fpath])
                 field.path = fpath
-                field.description = desc
+                field.description = desc or field.description
                 if isinstance(field, ArrayField):
                     field.itemtype.path = fpath
 
@@ -263,6 +260,7 @@ class PrimitiveField(Field):
         "string",
         "pubkey",
         "signature",
+        "bip340sig",
         "msat",
         "msat_or_any",
         "msat_or_all",
@@ -349,6 +347,21 @@ RoutehintListField = PrimitiveField(
     None
 )
 
+# The route hints returned by `decodepay` and `decode` are an array
+# of arrays of hops, which we map onto `Routehint`s directly.
+DecodeRoutehintListField = PrimitiveField(
+    "DecodeRoutehintList",
+    None,
+    None
+)
+
+# Plugin options are a free-form dict of option names to values.
+PluginOptionsField = PrimitiveField(
+    "PluginOptions",
+    None,
+    None
+)
+
 # TlvStreams are special, they don't have preset dict-keys, rather
 # they can specify `u64` keys pointing to hex payloads. So the schema
 # has to rely on additionalProperties to make it work.
@@ -358,6 +371,70 @@ TlvStreamField = PrimitiveField(
     None
 )
 
+# Runes are parsed so their restrictions can be inspected, and the
+# restrictions to add are either a list or the `readonly` shorthand.
+RuneField = PrimitiveField(
+    "Rune",
+    None,
+    None
+)
+
+RuneRestrictionsField = PrimitiveField(
+    "RuneRestrictions",
+    None,
+    None
+)
+
+# `decode` lists the bolt11 fields as `{}` placeholders in its first
+# conditional branch, so spell out their types here.
+DecodeSignatureField = PrimitiveField(
+    "string", None, "signature of the *payee* on this invoice"
+)
+DecodeCurrencyField = PrimitiveField(
+    "string", None, "the BIP173 name for the currency"
+)
+DecodeAmountField = PrimitiveField("msat", None, "Amount the invoice asked for")
+DecodeDescriptionField = PrimitiveField(
+    "string", None, "the description of the purpose of the purchase"
+)
+DecodeFeaturesField = PrimitiveField(
+    "hex", None, "the features bitmap for this invoice"
+)
+
+# `listfunds` lists `reserved_to_block` as a `{}` placeholder in the
+# branch for confirmed outputs, before the one that defines it.
+ListfundsReservedToBlockField = PrimitiveField(
+    "u32", None, "Block height where reservation will expire"
+)
+
+# Likewise its fallbacks are first listed with placeholder item fields
+# for invalid bolt12 invoices, so use the bolt11 definition instead.
+# Only `hex` is present in both.
+DecodeFallbacksSchema = {
+    "type": "array",
+    "description": "onchain addresses",
+    "items": {
+        "type": "object",
+        "required": ["hex"],
+        "properties": {
+            "type": {
+                "type": "string",
+                "description": "the address type (if known)",
+                "enum": ["P2PKH", "P2SH", "P2WPKH", "P2WSH"],
+            },
+            "addr": {
+                "type": "string",
+                "description": "the address in appropriate format for *type*",
+            },
+            "hex": {"type": "hex", "description": "Raw encoded address"},
+            "warning_invoice_fallbacks_version_invalid": {
+                "type": "string",
+                "description": "`version` is > 16",
+            },
+        },
+    },
+}
+
 # Override fields with manually managed types, fieldpath -> field mapping
 overrides = {
     'Invoice.label': InvoiceLabelField,
@@ -370,6 +447,23 @@ overrides = {
     'Pay.exclude': PayExclude,
     'KeySend.routehints': RoutehintListField,
     'KeySend.extratlvs': TlvStreamField,
+    'DecodePay.routes': DecodeRoutehintListField,
+    'Decode.routes': DecodeRoutehintListField,
+    'Decode.signature': DecodeSignatureField,
+    'Decode.currency': DecodeCurrencyField,
+    'Decode.amount_msat': DecodeAmountField,
+    'Decode.description': DecodeDescriptionField,
+    'Decode.features': DecodeFeaturesField,
+    'ListFunds.outputs[].reserved_to_block': ListfundsReservedToBlockField,
+    'ListConfigs.plugins[].options': PluginOptionsField,
+    'ListConfigs.important-plugins[].options': PluginOptionsField,
+    'CommandoRune.rune': RuneField,
+    'CommandoRune.restrictions': RuneRestrictionsField,
+}
+
+# Replace the schema of a field before it is parsed, fieldpath -> schema
+schema_overrides = {
+    'Decode.fallbacks': DecodeFallbacksSchema,
 }
 
 
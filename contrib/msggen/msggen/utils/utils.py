@@ -4,12 +4,19 @@ from pathlib import Path
 from msggen.model import Method, CompositeField, Service
 
 
+# Methods whose JSON-RPC name isn't just the lowercased method name.
+method_rpc_names = {
+    "CommandoRune": "commando-rune",
+}
+
+
 def load_jsonrpc_method(name, schema_dir: Path):
     """Load a method based on the file naming conventions for the JSON-RPC.
     """
+    rpc_name = method_rpc_names.get(name, name.lower())
     base_path = schema_dir
-    req_file = base_path / f"{name.lower()}.request.json"
-    resp_file = base_path / f"{name.lower()}.schema.json"
+    req_file = base_path / f"{rpc_name}.request.json"
+    resp_file = base_path / f"{rpc_name}.schema.json"
     request = CompositeField.from_js(json.load(open(req_file)), path=name)
     response = CompositeField.from_js(json.load(open(resp_file)), path=name)
 
@@ -22,6 +29,7 @@ def load_jsonrpc_method(name, schema_dir: Path):
         name,
         request=request,
         response=response,
+        rpc_name=rpc_name,
     )
 
 
@@ -32,6 +40,7 @@ def load_jsonrpc_service(schema_dir: str):
         "ListFunds",
         "SendPay",
         "ListChannels",
+        "ListConfigs",
         "AddGossip",
         "AutoCleanInvoice",
         "CheckMessage",
@@ -64,8 +73,8 @@ def load_jsonrpc_service(schema_dir: str):
         "TxDiscard",
         "TxPrepare",
         "TxSend",
-        # "decodepay",
-        # "decode",
+        "DecodePay",
+        "Decode",
         # "delpay",
         # "disableoffer",
         "Disconnect",
@@ -93,16 +102,20 @@ def load_jsonrpc_service(schema_dir: str):
         # "parsefeerate",
         "Ping",
         # "plugin",
-        # "reserveinputs",
-        # "sendcustommsg",
+        "ReserveInputs",
+        "SendCustomMsg",
         # "sendinvoice",
-        # "sendonionmessage",
+        "SendOnionMessage",
+        "BlindedPath",
         "SetChannel",
         "SignMessage",
-        # "unreserveinputs",
-        # "waitblockheight",
-        # "ListConfigs",
+        "UnreserveInputs",
+        "WaitBlockHeight",
+        "CommandoRune",
         # "check",  # No point in mapping this one
+        "StaticBackup",
+        "EmergencyRecover",
+        "RecoverChannel",
         "Stop",
         # "notifications",  # No point in mapping this
         # "help",
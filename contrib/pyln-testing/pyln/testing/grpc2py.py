@@ -14,6 +14,15 @@ def amount2msat(a):
 def amount_or_all2msat(a):
     breakpoint()
 
+def decoderoutehintlist2py(m):
+    return [[{
+        "pubkey": hexlify(h.id),
+        "short_channel_id": h.short_channel_id,
+        "fee_base_msat": amount2msat(h.feebase),
+        "fee_proportional_millionths": h.feeprop,
+        "cltv_expiry_delta": h.expirydelta,
+    } for h in r.hops] for r in m.hints]
+
 
 def remove_default(d):
     # grpc is really not good at empty values, they get replaced with the type's default value...
@@ -209,6 +218,7 @@ def listfunds_outputs2py(m):
         "redeemscript": hexlify(m.redeemscript),  # PrimitiveField in generate_composite
         "status": str(m.status),  # EnumField in generate_composite
         "reserved": m.reserved,  # PrimitiveField in generate_composite
+        "reserved_to_block": m.reserved_to_block,  # PrimitiveField in generate_composite
         "blockheight": m.blockheight,  # PrimitiveField in generate_composite
     })
 
@@ -279,6 +289,87 @@ def listchannels2py(m):
     })
 
 
+def listconfigs_plugins2py(m):
+    return remove_default({
+        "path": m.path,  # PrimitiveField in generate_composite
+        "name": m.name,  # PrimitiveField in generate_composite
+        "options": {k: json.loads(v) for k, v in m.options.options.items()},  # PrimitiveField in generate_composite
+    })
+
+
+def listconfigs_important_plugins2py(m):
+    return remove_default({
+        "path": m.path,  # PrimitiveField in generate_composite
+        "name": m.name,  # PrimitiveField in generate_composite
+        "options": {k: json.loads(v) for k, v in m.options.options.items()},  # PrimitiveField in generate_composite
+    })
+
+
+def listconfigs2py(m):
+    return remove_default({
+        "version": m.version,  # PrimitiveField in generate_composite
+        "plugins": [listconfigs_plugins2py(i) for i in m.plugins],  # ArrayField[composite] in generate_composite
+        "important_plugins": [listconfigs_important_plugins2py(i) for i in m.important_plugins],  # ArrayField[composite] in generate_composite
+        "conf": m.conf,  # PrimitiveField in generate_composite
+        "lightning_dir": m.lightning_dir,  # PrimitiveField in generate_composite
+        "network": m.network,  # PrimitiveField in generate_composite
+        "allow_deprecated_apis": m.allow_deprecated_apis,  # PrimitiveField in generate_composite
+        "rpc_file": m.rpc_file,  # PrimitiveField in generate_composite
+        "disable_plugin": [m.disable_plugin for i in m.disable_plugin], # ArrayField[primitive] in generate_composite
+        "bookkeeper_dir": m.bookkeeper_dir,  # PrimitiveField in generate_composite
+        "bookkeeper_db": m.bookkeeper_db,  # PrimitiveField in generate_composite
+        "always_use_proxy": m.always_use_proxy,  # PrimitiveField in generate_composite
+        "daemon": m.daemon,  # PrimitiveField in generate_composite
+        "wallet": m.wallet,  # PrimitiveField in generate_composite
+        "large_channels": m.large_channels,  # PrimitiveField in generate_composite
+        "experimental_dual_fund": m.experimental_dual_fund,  # PrimitiveField in generate_composite
+        "experimental_onion_messages": m.experimental_onion_messages,  # PrimitiveField in generate_composite
+        "experimental_offers": m.experimental_offers,  # PrimitiveField in generate_composite
+        "experimental_shutdown_wrong_funding": m.experimental_shutdown_wrong_funding,  # PrimitiveField in generate_composite
+        "experimental_websocket_port": m.experimental_websocket_port,  # PrimitiveField in generate_composite
+        "database_upgrade": m.database_upgrade,  # PrimitiveField in generate_composite
+        "rgb": hexlify(m.rgb),  # PrimitiveField in generate_composite
+        "alias": m.alias,  # PrimitiveField in generate_composite
+        "pid_file": m.pid_file,  # PrimitiveField in generate_composite
+        "ignore_fee_limits": m.ignore_fee_limits,  # PrimitiveField in generate_composite
+        "watchtime_blocks": m.watchtime_blocks,  # PrimitiveField in generate_composite
+        "max_locktime_blocks": m.max_locktime_blocks,  # PrimitiveField in generate_composite
+        "funding_confirms": m.funding_confirms,  # PrimitiveField in generate_composite
+        "cltv_delta": m.cltv_delta,  # PrimitiveField in generate_composite
+        "cltv_final": m.cltv_final,  # PrimitiveField in generate_composite
+        "commit_time": m.commit_time,  # PrimitiveField in generate_composite
+        "fee_base": m.fee_base,  # PrimitiveField in generate_composite
+        "rescan": m.rescan,  # PrimitiveField in generate_composite
+        "fee_per_satoshi": m.fee_per_satoshi,  # PrimitiveField in generate_composite
+        "max_concurrent_htlcs": m.max_concurrent_htlcs,  # PrimitiveField in generate_composite
+        "htlc_minimum_msat": amount2msat(m.htlc_minimum_msat),  # PrimitiveField in generate_composite
+        "htlc_maximum_msat": amount2msat(m.htlc_maximum_msat),  # PrimitiveField in generate_composite
+        "max_dust_htlc_exposure_msat": amount2msat(m.max_dust_htlc_exposure_msat),  # PrimitiveField in generate_composite
+        "min_capacity_sat": m.min_capacity_sat,  # PrimitiveField in generate_composite
+        "addr": m.addr,  # PrimitiveField in generate_composite
+        "announce_addr": m.announce_addr,  # PrimitiveField in generate_composite
+        "bind_addr": m.bind_addr,  # PrimitiveField in generate_composite
+        "offline": m.offline,  # PrimitiveField in generate_composite
+        "autolisten": m.autolisten,  # PrimitiveField in generate_composite
+        "proxy": m.proxy,  # PrimitiveField in generate_composite
+        "disable_dns": m.disable_dns,  # PrimitiveField in generate_composite
+        "disable_ip_discovery": m.disable_ip_discovery,  # PrimitiveField in generate_composite
+        "encrypted_hsm": m.encrypted_hsm,  # PrimitiveField in generate_composite
+        "rpc_file_mode": m.rpc_file_mode,  # PrimitiveField in generate_composite
+        "log_level": m.log_level,  # PrimitiveField in generate_composite
+        "log_prefix": m.log_prefix,  # PrimitiveField in generate_composite
+        "log_file": m.log_file,  # PrimitiveField in generate_composite
+        "log_timestamps": m.log_timestamps,  # PrimitiveField in generate_composite
+        "force_feerates": m.force_feerates,  # PrimitiveField in generate_composite
+        "subdaemon": m.subdaemon,  # PrimitiveField in generate_composite
+        "fetchinvoice_noconnect": m.fetchinvoice_noconnect,  # PrimitiveField in generate_composite
+        "accept_htlc_tlv_types": m.accept_htlc_tlv_types,  # PrimitiveField in generate_composite
+        "tor_service_password": m.tor_service_password,  # PrimitiveField in generate_composite
+        "dev_allowdustreserve": m.dev_allowdustreserve,  # PrimitiveField in generate_composite
+        "announce_addr_dns": m.announce_addr_dns,  # PrimitiveField in generate_composite
+    })
+
+
 def addgossip2py(m):
     return remove_default({
     })
@@ -725,6 +816,236 @@ def txsend2py(m):
     })
 
 
+def decodepay_fallbacks2py(m):
+    return remove_default({
+        "type": str(m.item_type),  # EnumField in generate_composite
+        "addr": m.addr,  # PrimitiveField in generate_composite
+        "hex": hexlify(m.hex),  # PrimitiveField in generate_composite
+    })
+
+
+def decodepay_extra2py(m):
+    return remove_default({
+        "tag": m.tag,  # PrimitiveField in generate_composite
+        "data": m.data,  # PrimitiveField in generate_composite
+    })
+
+
+def decodepay2py(m):
+    return remove_default({
+        "currency": m.currency,  # PrimitiveField in generate_composite
+        "created_at": m.created_at,  # PrimitiveField in generate_composite
+        "expiry": m.expiry,  # PrimitiveField in generate_composite
+        "payee": hexlify(m.payee),  # PrimitiveField in generate_composite
+        "msatoshi": m.msatoshi,  # PrimitiveField in generate_composite
+        "amount_msat": amount2msat(m.amount_msat),  # PrimitiveField in generate_composite
+        "payment_hash": hexlify(m.payment_hash),  # PrimitiveField in generate_composite
+        "signature": hexlify(m.signature),  # PrimitiveField in generate_composite
+        "description": m.description,  # PrimitiveField in generate_composite
+        "description_hash": hexlify(m.description_hash),  # PrimitiveField in generate_composite
+        "min_final_cltv_expiry": m.min_final_cltv_expiry,  # PrimitiveField in generate_composite
+        "payment_secret": hexlify(m.payment_secret),  # PrimitiveField in generate_composite
+        "features": hexlify(m.features),  # PrimitiveField in generate_composite
+        "payment_metadata": hexlify(m.payment_metadata),  # PrimitiveField in generate_composite
+        "fallbacks": [decodepay_fallbacks2py(i) for i in m.fallbacks],  # ArrayField[composite] in generate_composite
+        "routes": decoderoutehintlist2py(m.routes),  # PrimitiveField in generate_composite
+        "extra": [decodepay_extra2py(i) for i in m.extra],  # ArrayField[composite] in generate_composite
+    })
+
+
+def decode_offer_paths_path2py(m):
+    return remove_default({
+        "blinded_node_id": hexlify(m.blinded_node_id),  # PrimitiveField in generate_composite
+        "encrypted_recipient_data": hexlify(m.encrypted_recipient_data),  # PrimitiveField in generate_composite
+    })
+
+
+def decode_offer_paths2py(m):
+    return remove_default({
+        "first_node_id": hexlify(m.first_node_id),  # PrimitiveField in generate_composite
+        "blinding": hexlify(m.blinding),  # PrimitiveField in generate_composite
+        "path": [decode_offer_paths_path2py(i) for i in m.path],  # ArrayField[composite] in generate_composite
+    })
+
+
+def decode_offer_recurrence_paywindow2py(m):
+    return remove_default({
+        "seconds_before": m.seconds_before,  # PrimitiveField in generate_composite
+        "seconds_after": m.seconds_after,  # PrimitiveField in generate_composite
+        "proportional_amount": m.proportional_amount,  # PrimitiveField in generate_composite
+    })
+
+
+def decode_offer_recurrence2py(m):
+    return remove_default({
+        "time_unit": m.time_unit,  # PrimitiveField in generate_composite
+        "time_unit_name": m.time_unit_name,  # PrimitiveField in generate_composite
+        "period": m.period,  # PrimitiveField in generate_composite
+        "basetime": m.basetime,  # PrimitiveField in generate_composite
+        "start_any_period": m.start_any_period,  # PrimitiveField in generate_composite
+        "limit": m.limit,  # PrimitiveField in generate_composite
+    })
+
+
+def decode_unknown_offer_tlvs2py(m):
+    return remove_default({
+        "item_type": m.item_type,  # PrimitiveField in generate_composite
+        "length": m.length,  # PrimitiveField in generate_composite
+        "value": hexlify(m.value),  # PrimitiveField in generate_composite
+    })
+
+
+def decode_unknown_invoice_request_tlvs2py(m):
+    return remove_default({
+        "item_type": m.item_type,  # PrimitiveField in generate_composite
+        "length": m.length,  # PrimitiveField in generate_composite
+        "value": hexlify(m.value),  # PrimitiveField in generate_composite
+    })
+
+
+def decode_invoice_paths_path2py(m):
+    return remove_default({
+        "blinded_node_id": hexlify(m.blinded_node_id),  # PrimitiveField in generate_composite
+        "encrypted_recipient_data": hexlify(m.encrypted_recipient_data),  # PrimitiveField in generate_composite
+        "fee_base_msat": amount2msat(m.fee_base_msat),  # PrimitiveField in generate_composite
+        "fee_proportional_millionths": m.fee_proportional_millionths,  # PrimitiveField in generate_composite
+        "cltv_expiry_delta": m.cltv_expiry_delta,  # PrimitiveField in generate_composite
+        "features": hexlify(m.features),  # PrimitiveField in generate_composite
+    })
+
+
+def decode_invoice_paths2py(m):
+    return remove_default({
+        "first_node_id": hexlify(m.first_node_id),  # PrimitiveField in generate_composite
+        "blinding": hexlify(m.blinding),  # PrimitiveField in generate_composite
+        "path": [decode_invoice_paths_path2py(i) for i in m.path],  # ArrayField[composite] in generate_composite
+    })
+
+
+def decode_invoice_fallbacks2py(m):
+    return remove_default({
+        "version": m.version,  # PrimitiveField in generate_composite
+        "hex": hexlify(m.hex),  # PrimitiveField in generate_composite
+        "address": m.address,  # PrimitiveField in generate_composite
+    })
+
+
+def decode_unknown_invoice_tlvs2py(m):
+    return remove_default({
+        "item_type": m.item_type,  # PrimitiveField in generate_composite
+        "length": m.length,  # PrimitiveField in generate_composite
+        "value": hexlify(m.value),  # PrimitiveField in generate_composite
+    })
+
+
+def decode_fallbacks2py(m):
+    return remove_default({
+        "type": str(m.item_type),  # EnumField in generate_composite
+        "addr": m.addr,  # PrimitiveField in generate_composite
+        "hex": hexlify(m.hex),  # PrimitiveField in generate_composite
+        "warning_invoice_fallbacks_version_invalid": m.warning_invoice_fallbacks_version_invalid,  # PrimitiveField in generate_composite
+    })
+
+
+def decode_extra2py(m):
+    return remove_default({
+        "tag": m.tag,  # PrimitiveField in generate_composite
+        "data": m.data,  # PrimitiveField in generate_composite
+    })
+
+
+def decode_restrictions2py(m):
+    return remove_default({
+        "alternatives": [m.alternatives for i in m.alternatives], # ArrayField[primitive] in generate_composite
+        "summary": m.summary,  # PrimitiveField in generate_composite
+    })
+
+
+def decode2py(m):
+    return remove_default({
+        "type": str(m.item_type),  # EnumField in generate_composite
+        "valid": m.valid,  # PrimitiveField in generate_composite
+        "offer_id": hexlify(m.offer_id),  # PrimitiveField in generate_composite
+        "offer_chains": [hexlify(m.offer_chains) for i in hexlify(m.offer_chains)], # ArrayField[primitive] in generate_composite
+        "offer_metadata": hexlify(m.offer_metadata),  # PrimitiveField in generate_composite
+        "offer_currency": m.offer_currency,  # PrimitiveField in generate_composite
+        "warning_unknown_offer_currency": m.warning_unknown_offer_currency,  # PrimitiveField in generate_composite
+        "currency_minor_unit": m.currency_minor_unit,  # PrimitiveField in generate_composite
+        "offer_amount": m.offer_amount,  # PrimitiveField in generate_composite
+        "offer_amount_msat": amount2msat(m.offer_amount_msat),  # PrimitiveField in generate_composite
+        "offer_description": m.offer_description,  # PrimitiveField in generate_composite
+        "offer_issuer": m.offer_issuer,  # PrimitiveField in generate_composite
+        "offer_features": hexlify(m.offer_features),  # PrimitiveField in generate_composite
+        "offer_absolute_expiry": m.offer_absolute_expiry,  # PrimitiveField in generate_composite
+        "offer_quantity_max": m.offer_quantity_max,  # PrimitiveField in generate_composite
+        "offer_paths": [decode_offer_paths2py(i) for i in m.offer_paths],  # ArrayField[composite] in generate_composite
+        "offer_node_id": hexlify(m.offer_node_id),  # PrimitiveField in generate_composite
+        "unknown_offer_tlvs": [decode_unknown_offer_tlvs2py(i) for i in m.unknown_offer_tlvs],  # ArrayField[composite] in generate_composite
+        "signature": m.signature,  # PrimitiveField in generate_composite
+        "currency": m.currency,  # PrimitiveField in generate_composite
+        "amount_msat": amount2msat(m.amount_msat),  # PrimitiveField in generate_composite
+        "description": m.description,  # PrimitiveField in generate_composite
+        "features": hexlify(m.features),  # PrimitiveField in generate_composite
+        "warning_missing_offer_node_id": m.warning_missing_offer_node_id,  # PrimitiveField in generate_composite
+        "warning_invalid_offer_description": m.warning_invalid_offer_description,  # PrimitiveField in generate_composite
+        "warning_missing_offer_description": m.warning_missing_offer_description,  # PrimitiveField in generate_composite
+        "warning_invalid_offer_currency": m.warning_invalid_offer_currency,  # PrimitiveField in generate_composite
+        "warning_invalid_offer_issuer": m.warning_invalid_offer_issuer,  # PrimitiveField in generate_composite
+        "invreq_metadata": hexlify(m.invreq_metadata),  # PrimitiveField in generate_composite
+        "invreq_payer_id": hexlify(m.invreq_payer_id),  # PrimitiveField in generate_composite
+        "invreq_chain": hexlify(m.invreq_chain),  # PrimitiveField in generate_composite
+        "invreq_amount_msat": amount2msat(m.invreq_amount_msat),  # PrimitiveField in generate_composite
+        "invreq_features": hexlify(m.invreq_features),  # PrimitiveField in generate_composite
+        "invreq_quantity": m.invreq_quantity,  # PrimitiveField in generate_composite
+        "invreq_payer_note": m.invreq_payer_note,  # PrimitiveField in generate_composite
+        "invreq_recurrence_counter": m.invreq_recurrence_counter,  # PrimitiveField in generate_composite
+        "invreq_recurrence_start": m.invreq_recurrence_start,  # PrimitiveField in generate_composite
+        "unknown_invoice_request_tlvs": [decode_unknown_invoice_request_tlvs2py(i) for i in m.unknown_invoice_request_tlvs],  # ArrayField[composite] in generate_composite
+        "warning_missing_invreq_metadata": m.warning_missing_invreq_metadata,  # PrimitiveField in generate_composite
+        "warning_missing_invreq_payer_id": m.warning_missing_invreq_payer_id,  # PrimitiveField in generate_composite
+        "warning_invalid_invreq_payer_note": m.warning_invalid_invreq_payer_note,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_request_signature": m.warning_missing_invoice_request_signature,  # PrimitiveField in generate_composite
+        "warning_invalid_invoice_request_signature": m.warning_invalid_invoice_request_signature,  # PrimitiveField in generate_composite
+        "invoice_paths": [decode_invoice_paths2py(i) for i in m.invoice_paths],  # ArrayField[composite] in generate_composite
+        "invoice_created_at": m.invoice_created_at,  # PrimitiveField in generate_composite
+        "invoice_relative_expiry": m.invoice_relative_expiry,  # PrimitiveField in generate_composite
+        "invoice_payment_hash": hexlify(m.invoice_payment_hash),  # PrimitiveField in generate_composite
+        "invoice_amount_msat": amount2msat(m.invoice_amount_msat),  # PrimitiveField in generate_composite
+        "invoice_fallbacks": [decode_invoice_fallbacks2py(i) for i in m.invoice_fallbacks],  # ArrayField[composite] in generate_composite
+        "invoice_features": hexlify(m.invoice_features),  # PrimitiveField in generate_composite
+        "invoice_node_id": hexlify(m.invoice_node_id),  # PrimitiveField in generate_composite
+        "invoice_recurrence_basetime": m.invoice_recurrence_basetime,  # PrimitiveField in generate_composite
+        "unknown_invoice_tlvs": [decode_unknown_invoice_tlvs2py(i) for i in m.unknown_invoice_tlvs],  # ArrayField[composite] in generate_composite
+        "warning_missing_invoice_paths": m.warning_missing_invoice_paths,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_blindedpay": m.warning_missing_invoice_blindedpay,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_created_at": m.warning_missing_invoice_created_at,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_payment_hash": m.warning_missing_invoice_payment_hash,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_amount": m.warning_missing_invoice_amount,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_recurrence_basetime": m.warning_missing_invoice_recurrence_basetime,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_node_id": m.warning_missing_invoice_node_id,  # PrimitiveField in generate_composite
+        "warning_missing_invoice_signature": m.warning_missing_invoice_signature,  # PrimitiveField in generate_composite
+        "warning_invalid_invoice_signature": m.warning_invalid_invoice_signature,  # PrimitiveField in generate_composite
+        "fallbacks": [decode_fallbacks2py(i) for i in m.fallbacks],  # ArrayField[composite] in generate_composite
+        "created_at": m.created_at,  # PrimitiveField in generate_composite
+        "expiry": m.expiry,  # PrimitiveField in generate_composite
+        "payee": hexlify(m.payee),  # PrimitiveField in generate_composite
+        "msatoshi": m.msatoshi,  # PrimitiveField in generate_composite
+        "payment_hash": hexlify(m.payment_hash),  # PrimitiveField in generate_composite
+        "description_hash": hexlify(m.description_hash),  # PrimitiveField in generate_composite
+        "min_final_cltv_expiry": m.min_final_cltv_expiry,  # PrimitiveField in generate_composite
+        "payment_secret": hexlify(m.payment_secret),  # PrimitiveField in generate_composite
+        "payment_metadata": hexlify(m.payment_metadata),  # PrimitiveField in generate_composite
+        "routes": decoderoutehintlist2py(m.routes),  # PrimitiveField in generate_composite
+        "extra": [decode_extra2py(i) for i in m.extra],  # ArrayField[composite] in generate_composite
+        "unique_id": m.unique_id,  # PrimitiveField in generate_composite
+        "version": m.version,  # PrimitiveField in generate_composite
+        "string": m.string,  # PrimitiveField in generate_composite
+        "restrictions": [decode_restrictions2py(i) for i in m.restrictions],  # ArrayField[composite] in generate_composite
+        "warning_rune_invalid_utf8": m.warning_rune_invalid_utf8,  # PrimitiveField in generate_composite
+        "hex": hexlify(m.hex),  # PrimitiveField in generate_composite
+    })
+
+
 def disconnect2py(m):
     return remove_default({
     })
@@ -851,6 +1172,53 @@ def ping2py(m):
     })
 
 
+def reserveinputs_reservations2py(m):
+    return remove_default({
+        "txid": hexlify(m.txid),  # PrimitiveField in generate_composite
+        "vout": m.vout,  # PrimitiveField in generate_composite
+        "was_reserved": m.was_reserved,  # PrimitiveField in generate_composite
+        "reserved": m.reserved,  # PrimitiveField in generate_composite
+        "reserved_to_block": m.reserved_to_block,  # PrimitiveField in generate_composite
+    })
+
+
+def reserveinputs2py(m):
+    return remove_default({
+        "reservations": [reserveinputs_reservations2py(i) for i in m.reservations],  # ArrayField[composite] in generate_composite
+    })
+
+
+def sendcustommsg2py(m):
+    return remove_default({
+        "status": m.status,  # PrimitiveField in generate_composite
+    })
+
+
+def sendonionmessage2py(m):
+    return remove_default({
+    })
+
+
+def blindedpath_blindedpath_hops2py(m):
+    return remove_default({
+        "blinded_node_id": hexlify(m.blinded_node_id),  # PrimitiveField in generate_composite
+        "encrypted_recipient_data": hexlify(m.encrypted_recipient_data),  # PrimitiveField in generate_composite
+    })
+
+
+def blindedpath_blindedpath2py(m):
+    return remove_default({
+        "first_node_id": hexlify(m.first_node_id),  # PrimitiveField in generate_composite
+        "blinding": hexlify(m.blinding),  # PrimitiveField in generate_composite
+        "hops": [blindedpath_blindedpath_hops2py(i) for i in m.hops],  # ArrayField[composite] in generate_composite
+    })
+
+
+def blindedpath2py(m):
+    return remove_default({
+    })
+
+
 def setchannel_channels2py(m):
     return remove_default({
         "peer_id": hexlify(m.peer_id),  # PrimitiveField in generate_composite
@@ -879,6 +1247,54 @@ def signmessage2py(m):
     })
 
 
+def unreserveinputs_reservations2py(m):
+    return remove_default({
+        "txid": hexlify(m.txid),  # PrimitiveField in generate_composite
+        "vout": m.vout,  # PrimitiveField in generate_composite
+        "was_reserved": m.was_reserved,  # PrimitiveField in generate_composite
+        "reserved": m.reserved,  # PrimitiveField in generate_composite
+        "reserved_to_block": m.reserved_to_block,  # PrimitiveField in generate_composite
+    })
+
+
+def unreserveinputs2py(m):
+    return remove_default({
+        "reservations": [unreserveinputs_reservations2py(i) for i in m.reservations],  # ArrayField[composite] in generate_composite
+    })
+
+
+def waitblockheight2py(m):
+    return remove_default({
+        "blockheight": m.blockheight,  # PrimitiveField in generate_composite
+    })
+
+
+def commandorune2py(m):
+    return remove_default({
+        "rune": m.rune,  # PrimitiveField in generate_composite
+        "unique_id": m.unique_id,  # PrimitiveField in generate_composite
+        "warning_unrestricted_rune": m.warning_unrestricted_rune,  # PrimitiveField in generate_composite
+    })
+
+
+def staticbackup2py(m):
+    return remove_default({
+        "scb": [m.scb for i in m.scb], # ArrayField[primitive] in generate_composite
+    })
+
+
+def emergencyrecover2py(m):
+    return remove_default({
+        "stubs": [m.stubs for i in m.stubs], # ArrayField[primitive] in generate_composite
+    })
+
+
+def recoverchannel2py(m):
+    return remove_default({
+        "stubs": [m.stubs for i in m.stubs], # ArrayField[primitive] in generate_composite
+    })
+
+
 def stop2py(m):
     return remove_default({
     })
@@ -0,0 +1,271 @@
+//! Peer-to-peer custom messages.
+//!
+//! Core Lightning lets plugins exchange messages with direct peers
+//! using the `sendcustommsg` RPC method and the `custommsg` hook. The
+//! wire format is a 2-byte big-endian message type followed by the
+//! payload. Only odd types in the custom range are allowed, so that
+//! peers that don't understand the message can ignore it ("it's ok to
+//! be odd").
+//!
+//! ```no_run
+//! use cln_plugin::{custommsg::CustomMsgSender, Builder};
+//!
+//! # async fn run() -> Result<(), anyhow::Error> {
+//! let (builder, mut incoming) = Builder::new(tokio::io::stdin(), tokio::io::stdout())
+//!     .custommsg_channel(&[0xFFFF])?;
+//!
+//! if let Some(plugin) = builder.start(()).await? {
+//!     let sender = CustomMsgSender::from_plugin(&plugin).await?;
+//!     while let Some(msg) = incoming.recv().await {
+//!         // Echo the message back to the peer that sent it.
+//!         sender.send(&msg.peer_id, msg.msg_type, &msg.payload).await?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use crate::{Error, Plugin};
+use anyhow::{anyhow, Context};
+use cln_rpc::model::requests::SendcustommsgRequest;
+use cln_rpc::primitives::PublicKey;
+use cln_rpc::ClnRpc;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+/// The first message type that may be used for custom messages.
+pub const MIN_CUSTOM_TYPE: u16 = 32768;
+
+/// How many incoming messages we buffer before dropping new ones.
+const CHANNEL_SIZE: usize = 64;
+
+/// A custom message exchanged with a direct peer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomMessage {
+    /// The peer that sent the message, or that we're sending it to.
+    pub peer_id: PublicKey,
+    pub msg_type: u16,
+    /// The message payload, without the 2-byte type prefix.
+    pub payload: Vec<u8>,
+}
+
+impl CustomMessage {
+    /// Parse a message from its wire encoding, i.e., the type prefix
+    /// followed by the payload.
+    pub fn from_wire(peer_id: PublicKey, msg: &[u8]) -> Result<CustomMessage, Error> {
+        if msg.len() < 2 {
+            return Err(anyhow!("custom message is too short to contain a type"));
+        }
+        Ok(CustomMessage {
+            peer_id,
+            msg_type: u16::from_be_bytes([msg[0], msg[1]]),
+            payload: msg[2..].to_vec(),
+        })
+    }
+
+    /// Encode the message for the wire, prepending the type.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(self.payload.len() + 2);
+        msg.extend_from_slice(&self.msg_type.to_be_bytes());
+        msg.extend_from_slice(&self.payload);
+        msg
+    }
+}
+
+/// Check that `msg_type` may be used for a custom message.
+pub fn check_type(msg_type: u16) -> Result<(), Error> {
+    if msg_type < MIN_CUSTOM_TYPE {
+        Err(anyhow!(
+            "custom message type {} is below {}",
+            msg_type,
+            MIN_CUSTOM_TYPE
+        ))
+    } else if msg_type & 1 == 0 {
+        Err(anyhow!("custom message type {} is not odd", msg_type))
+    } else {
+        Ok(())
+    }
+}
+
+/// The payload of the `custommsg` hook.
+#[derive(Deserialize, Debug)]
+struct CustommsgHook {
+    peer_id: String,
+    payload: String,
+}
+
+/// The receiving end of a custom message channel, returned by
+/// [`crate::Builder::custommsg_channel`]. Only messages matching the
+/// types the channel was created with are delivered.
+pub struct CustomMsgReceiver {
+    receiver: mpsc::Receiver<CustomMessage>,
+}
+
+impl CustomMsgReceiver {
+    /// Wait for the next message from any peer. Returns `None` once
+    /// the plugin shuts down.
+    pub async fn recv(&mut self) -> Option<CustomMessage> {
+        self.receiver.recv().await
+    }
+
+    /// Wait for the next message from `peer_id`, discarding messages
+    /// from other peers in the meantime.
+    pub async fn recv_from(&mut self, peer_id: &PublicKey) -> Option<CustomMessage> {
+        loop {
+            let msg = self.receiver.recv().await?;
+            if &msg.peer_id == peer_id {
+                return Some(msg);
+            }
+        }
+    }
+}
+
+/// Filters `custommsg` hook calls and forwards the matching messages
+/// to a [`CustomMsgReceiver`].
+#[derive(Clone)]
+pub(crate) struct CustomMsgFilter {
+    types: Arc<HashSet<u16>>,
+    sender: mpsc::Sender<CustomMessage>,
+}
+
+impl CustomMsgFilter {
+    pub(crate) fn new(types: &[u16]) -> Result<(CustomMsgFilter, CustomMsgReceiver), Error> {
+        for t in types {
+            check_type(*t)?;
+        }
+        let (sender, receiver) = mpsc::channel(CHANNEL_SIZE);
+        Ok((
+            CustomMsgFilter {
+                types: Arc::new(types.iter().cloned().collect()),
+                sender,
+            },
+            CustomMsgReceiver { receiver },
+        ))
+    }
+
+    /// Handle a single `custommsg` hook call. The hook is chained, so
+    /// we always let `lightningd` continue, even for messages we
+    /// don't care about or can't parse: it treats any other reply as
+    /// fatal.
+    pub(crate) fn handle(&self, v: serde_json::Value) -> serde_json::Value {
+        match Self::parse(v) {
+            Ok(msg) if self.types.contains(&msg.msg_type) => {
+                // Don't hold up the hook chain if the plugin isn't
+                // keeping up with incoming messages.
+                if let Err(e) = self.sender.try_send(msg) {
+                    log::warn!("Dropping custom message: {}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Could not parse custommsg payload: {:#}", e),
+        }
+        json!({"result": "continue"})
+    }
+
+    fn parse(v: serde_json::Value) -> Result<CustomMessage, Error> {
+        let call: CustommsgHook =
+            serde_json::from_value(v).context("parsing custommsg hook payload")?;
+        let peer_id = PublicKey::from_str(&call.peer_id).context("parsing peer_id")?;
+        let raw = hex::decode(&call.payload).context("decoding custommsg payload")?;
+        CustomMessage::from_wire(peer_id, &raw)
+    }
+}
+
+/// Sends custom messages to peers using `sendcustommsg`.
+#[derive(Clone)]
+pub struct CustomMsgSender {
+    rpc: Arc<Mutex<ClnRpc>>,
+}
+
+impl CustomMsgSender {
+    /// Connect to the RPC socket at `rpc_path`.
+    pub async fn new<P: AsRef<Path>>(rpc_path: P) -> Result<CustomMsgSender, Error> {
+        Ok(CustomMsgSender {
+            rpc: Arc::new(Mutex::new(ClnRpc::new(rpc_path).await?)),
+        })
+    }
+
+    /// Connect to the RPC socket of the node `plugin` is running on.
+    pub async fn from_plugin<S>(plugin: &Plugin<S>) -> Result<CustomMsgSender, Error>
+    where
+        S: Clone + Send,
+    {
+        let conf = plugin.configuration();
+        let path: PathBuf = Path::new(&conf.lightning_dir).join(&conf.rpc_file);
+        CustomMsgSender::new(path).await
+    }
+
+    /// Send a message of type `msg_type` to `peer_id`. The peer must
+    /// be connected.
+    pub async fn send(
+        &self,
+        peer_id: &PublicKey,
+        msg_type: u16,
+        payload: &[u8],
+    ) -> Result<(), Error> {
+        check_type(msg_type)?;
+        let msg = CustomMessage {
+            peer_id: *peer_id,
+            msg_type,
+            payload: payload.to_vec(),
+        };
+        self.rpc
+            .lock()
+            .await
+            .call_typed(
+                SendcustommsgRequest::builder(msg.peer_id, hex::encode(msg.to_wire())).build(),
+            )
+            .await
+            .map_err(|e| anyhow!("sendcustommsg to {} failed: {}", peer_id, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PEER: &str = "02df5ffe895c778e10f7742a6c5b8a0cefbe9465df58b92fadeb883752c8107c8f";
+
+    #[test]
+    fn test_check_type() {
+        assert!(check_type(0x8001).is_ok());
+        assert!(check_type(0xFFFF).is_ok());
+        assert!(check_type(0x8000).is_err());
+        assert!(check_type(0x1337).is_err());
+        assert!(CustomMsgFilter::new(&[0x8001, 0x8002]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter() {
+        let (filter, mut receiver) = CustomMsgFilter::new(&[0x8001]).unwrap();
+
+        let r = filter.handle(json!({"peer_id": PEER, "payload": "8003ffff"}));
+        assert_eq!(r, json!({"result": "continue"}));
+        let r = filter.handle(json!({"peer_id": PEER, "payload": "8001deadbeef"}));
+        assert_eq!(r, json!({"result": "continue"}));
+        // Malformed payloads are skipped, but must not stop the hook
+        // chain.
+        let r = filter.handle(json!({"peer_id": PEER, "payload": "80"}));
+        assert_eq!(r, json!({"result": "continue"}));
+        let r = filter.handle(json!({"peer_id": "02", "payload": "8001"}));
+        assert_eq!(r, json!({"result": "continue"}));
+        drop(filter);
+
+        let peer = PublicKey::from_str(PEER).unwrap();
+        let msg = receiver.recv_from(&peer).await.unwrap();
+        assert_eq!(
+            msg,
+            CustomMessage {
+                peer_id: peer,
+                msg_type: 0x8001,
+                payload: vec![0xde, 0xad, 0xbe, 0xef],
+            }
+        );
+        assert_eq!(hex::encode(msg.to_wire()), "8001deadbeef");
+        assert_eq!(receiver.recv().await, None);
+    }
+}
@@ -18,6 +18,7 @@ use tokio_util::codec::FramedRead;
 use tokio_util::codec::FramedWrite;
 
 mod codec;
+pub mod custommsg;
 mod logging;
 pub mod messages;
 
@@ -175,6 +176,73 @@ where
         self
     }
 
+    /// Register the `custommsg` hook and forward custom messages from
+    /// peers whose type is in `types` to the returned
+    /// [`custommsg::CustomMsgReceiver`]. All types must be odd and
+    /// at least [`custommsg::MIN_CUSTOM_TYPE`]. Messages of other
+    /// types are passed on to the next plugin in the hook chain.
+    pub fn custommsg_channel(
+        self,
+        types: &[u16],
+    ) -> Result<(Builder<S, I, O>, custommsg::CustomMsgReceiver), Error> {
+        let (filter, receiver) = custommsg::CustomMsgFilter::new(types)?;
+        let builder = self.hook("custommsg", move |_p, v| {
+            let res = filter.handle(v);
+            async move { Ok(res) }
+        });
+        Ok((builder, receiver))
+    }
+
+    /// Handle unsolicited onion messages, i.e., those that did not
+    /// arrive over a blinded path we created. Messages are always
+    /// passed on to the next plugin, errors in `callback` are only
+    /// logged.
+    pub fn onion_message_recv<C, F>(self, callback: C) -> Self
+    where
+        C: Send + Sync + 'static,
+        C: Fn(Plugin<S>, messages::OnionMessage) -> F + 'static,
+        F: Future<Output = Result<(), Error>> + Send + Sync + 'static,
+    {
+        self.onion_message_hook("onion_message_recv", callback)
+    }
+
+    /// Handle onion messages that arrived over a blinded path we
+    /// created using `blindedpath`. The callback must check that the
+    /// message's `pathsecret` matches the one used to create the path.
+    pub fn onion_message_recv_secret<C, F>(self, callback: C) -> Self
+    where
+        C: Send + Sync + 'static,
+        C: Fn(Plugin<S>, messages::OnionMessage) -> F + 'static,
+        F: Future<Output = Result<(), Error>> + Send + Sync + 'static,
+    {
+        self.onion_message_hook("onion_message_recv_secret", callback)
+    }
+
+    fn onion_message_hook<C, F>(self, hookname: &str, callback: C) -> Self
+    where
+        C: Send + Sync + 'static,
+        C: Fn(Plugin<S>, messages::OnionMessage) -> F + 'static,
+        F: Future<Output = Result<(), Error>> + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        let name = hookname.to_string();
+        self.hook(hookname, move |p, v| {
+            let callback = callback.clone();
+            let name = name.clone();
+            async move {
+                match serde_json::from_value::<messages::OnionMessageHook>(v) {
+                    Ok(h) => {
+                        if let Err(e) = callback(p, h.onion_message).await {
+                            log::warn!("Error in {} hook: {}", name, e);
+                        }
+                    }
+                    Err(e) => log::warn!("Could not parse {} payload: {}", name, e),
+                }
+                Ok(json!({"result": "continue"}))
+            }
+        })
+    }
+
     /// Register a custom RPC method for the RPC passthrough from the
     /// main daemon
     pub fn rpcmethod<C, F>(mut self, name: &str, description: &str, callback: C) -> Builder<S, I, O>
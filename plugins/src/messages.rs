@@ -1,4 +1,6 @@
 use crate::options::ConfigOption;
+use cln_rpc::onionmessage::BlindedPath;
+use cln_rpc::primitives::Secret;
 use serde::de::{self, Deserializer};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -92,6 +94,37 @@ pub struct ProxyInfo {
     pub port: i64,
 }
 
+/// The payload of the `onion_message_recv` and
+/// `onion_message_recv_secret` hooks.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OnionMessageHook {
+    pub onion_message: OnionMessage,
+}
+
+/// An onion message addressed to us.
+#[derive(Clone, Debug, Deserialize)]
+pub struct OnionMessage {
+    /// Only present for `onion_message_recv_secret`, i.e., messages
+    /// that arrived over a blinded path we created. The plugin must
+    /// check it matches the `pathsecret` it passed to `blindedpath`.
+    pub pathsecret: Option<Secret>,
+    /// A path the sender wants us to use for replies.
+    pub reply_blindedpath: Option<BlindedPath>,
+    pub invoice_request: Option<String>,
+    pub invoice: Option<String>,
+    pub invoice_error: Option<String>,
+    /// Fields `lightningd` doesn't know about, e.g., those used by
+    /// custom protocols.
+    #[serde(default)]
+    pub unknown_fields: Vec<OnionMessageField>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OnionMessageField {
+    pub number: u64,
+    pub value: String,
+}
+
 #[derive(Debug)]
 pub(crate) enum JsonRpc<N, R> {
     Request(serde_json::Value, R),
@@ -218,4 +251,34 @@ mod test {
             _ => panic!("Couldn't parse init message"),
         }
     }
+
+    #[test]
+    fn test_onion_message_hook() {
+        let value = json!({
+            "onion_message": {
+                "pathsecret": "1a8b1c4a6b7d3f8e0c2d5e9f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d",
+                "reply_blindedpath": {
+                    "first_node_id": "022d223620a359a47ff7f7ac447c85c46c923da53389221a0054c11c1e3ca31d59",
+                    "blinding": "035d2b1192dfba134e10e540875d366ebc8bc353d5aa766b80c090b39c3a5d885d",
+                    "hops": [{
+                        "blinded_node_id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+                        "encrypted_recipient_data": "00112233"
+                    }]
+                },
+                "invoice_request": "0a0b0c",
+                "unknown_fields": [{"number": 65535, "value": "42"}]
+            }
+        });
+        let hook: OnionMessageHook = serde_json::from_value(value).unwrap();
+        let om = hook.onion_message;
+        assert!(om.pathsecret.is_some());
+        let path = om.reply_blindedpath.unwrap();
+        assert_eq!(
+            path.hops[0].encrypted_recipient_data,
+            vec![0x00, 0x11, 0x22, 0x33]
+        );
+        assert_eq!(om.invoice_request.as_deref(), Some("0a0b0c"));
+        assert_eq!(om.invoice, None);
+        assert_eq!(om.unknown_fields[0].number, 65535);
+    }
 }